@@ -231,6 +231,7 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
           final proofResult = await provePrepare(
             documentsPath: documentsPath,
             inputPath: inputPath,
+            maxDurationMs: null,
           );
           result = TaskResult(
             taskType: taskType,
@@ -243,6 +244,7 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
           final proofResult = await proveShow(
             documentsPath: documentsPath,
             inputPath: inputPath,
+            maxDurationMs: null,
           );
           result = TaskResult(
             taskType: taskType,
@@ -281,8 +283,9 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
           final elapsed = DateTime.now().difference(startTime).inMilliseconds;
           result = TaskResult(
             taskType: taskType,
-            success: verifyResult,
-            verifyResult: verifyResult,
+            success: verifyResult.valid,
+            error: verifyResult.failureReason,
+            verifyResult: verifyResult.valid,
             clientTimingMs: elapsed,
           );
           break;
@@ -295,8 +298,9 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
           final elapsed = DateTime.now().difference(startTime).inMilliseconds;
           result = TaskResult(
             taskType: taskType,
-            success: verifyResult,
-            verifyResult: verifyResult,
+            success: verifyResult.valid,
+            error: verifyResult.failureReason,
+            verifyResult: verifyResult.valid,
             clientTimingMs: elapsed,
           );
           break;
@@ -336,6 +340,7 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
       final results = await runCompleteBenchmark(
         documentsPath: documentsPath,
         inputPath: null,
+        resume: false,
       );
 
       final clientTimingMs =
@@ -848,6 +853,15 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
             ),
             const SizedBox(height: 16),
 
+            // Steps that failed leave their metrics at 0
+            if (results.errorSummary != null) ...[
+              Text(
+                'Some steps failed:\n${results.errorSummary}',
+                style: const TextStyle(color: Colors.red),
+              ),
+              const SizedBox(height: 16),
+            ],
+
             // Timing Metrics Section
             const Text(
               'Timing Metrics',
@@ -867,12 +881,27 @@ class _E2EProofWorkflowScreenState extends State<E2EProofWorkflowScreen> {
               children: [
                 _buildTableHeader(['Operation', 'Time (ms)']),
                 _buildTimingRow('Prepare Setup', results.prepareSetupMs),
+                _buildTimingRow(
+                    '  Synthesis', results.prepareSetupSynthesisMs),
+                _buildTimingRow('  Key Gen', results.prepareSetupKeygenMs),
+                _buildTimingRow('  Key Save', results.prepareKeySaveMs),
+                _buildTimingRow('  Key Load', results.prepareKeyLoadMs),
                 _buildTimingRow('Show Setup', results.showSetupMs),
+                _buildTimingRow('  Synthesis', results.showSetupSynthesisMs),
+                _buildTimingRow('  Key Gen', results.showSetupKeygenMs),
+                _buildTimingRow('  Key Save', results.showKeySaveMs),
+                _buildTimingRow('  Key Load', results.showKeyLoadMs),
                 _buildTimingRow('Generate Blinds', results.generateBlindsMs),
                 _buildTimingRow('Prove Prepare', results.provePrepareMs),
                 _buildTimingRow('Reblind Prepare', results.reblindPrepareMs),
+                _buildTimingRow(
+                    '  Recommit', results.reblindPrepareRecommitMs),
+                _buildTimingRow('  Re-prove', results.reblindPrepareProveMs),
                 _buildTimingRow('Prove Show', results.proveShowMs),
                 _buildTimingRow('Reblind Show', results.reblindShowMs),
+                _buildTimingRow(
+                    '  Recommit', results.reblindShowRecommitMs),
+                _buildTimingRow('  Re-prove', results.reblindShowProveMs),
                 _buildTimingRow('Verify Prepare', results.verifyPrepareMs),
                 _buildTimingRow('Verify Show', results.verifyShowMs),
               ],
@@ -0,0 +1,41 @@
+//! React Native bindings for the presentation flow.
+//!
+//! `uniffi-bindgen-react-native` generates a JSI turbo module from this crate
+//! and the UniFFI exports of `spartan2-hyrax-mopro` it links, so every export
+//! of the prover is callable from JavaScript. `Vec<u8>` crosses JSI as an
+//! `ArrayBuffer` without base64 or JSON copies; the functions here move whole
+//! presentations that way, as one buffer made by `encode_presentation`, rather
+//! than as a record of nested byte fields. Proving and verifying are async and
+//! run on the prover's worker thread, so they return promises and leave the JS
+//! thread free.
+
+use spartan2_hyrax_mopro::{
+    complete_presentation_async, decode_presentation, encode_presentation,
+    verify_presentation_async, IssuerPublicKey, PresentationVerification, ZkProofError,
+};
+
+uniffi::setup_scaffolding!();
+
+/// Complete the pending presentation with the device's signature over its challenge
+/// Resolves to the bundle encoded as one buffer, ready to send to the verifier.
+#[uniffi::export]
+pub async fn complete_presentation_buffer(
+    documents_path: String,
+    device_signature: Vec<u8>,
+) -> Result<Vec<u8>, ZkProofError> {
+    encode_presentation(complete_presentation_async(documents_path, device_signature).await?)
+}
+
+/// Verify a presentation received as one buffer, answering `verifier_nonce`
+/// and made against one of `issuer_keys`. Oversized buffers are rejected before
+/// decoding (see `set_verifier_limits`).
+#[uniffi::export]
+pub async fn verify_presentation_buffer(
+    documents_path: String,
+    presentation: Vec<u8>,
+    verifier_nonce: Vec<u8>,
+    issuer_keys: Vec<IssuerPublicKey>,
+) -> Result<PresentationVerification, ZkProofError> {
+    let bundle = decode_presentation(presentation)?;
+    verify_presentation_async(documents_path, bundle, verifier_nonce, issuer_keys).await
+}
@@ -0,0 +1,49 @@
+// Entry point of the React Native package.
+//
+// Re-exports the generated bindings and adds the flow a wallet runs for one
+// presentation. Byte fields (nonces, signatures, presentations) are
+// ArrayBuffers passed through JSI without copies into base64 strings.
+
+import {
+  completePresentationBuffer,
+  startPresentation,
+  verifyPresentationBuffer,
+  type IssuerPublicKey,
+  type PresentationVerification,
+} from './generated/spartan2_hyrax_mopro_rn';
+
+export * from './generated/spartan2_hyrax_mopro_rn';
+export * from './generated/spartan2_hyrax_mopro';
+
+/** Signs a challenge with the device key held in the Secure Enclave / StrongBox */
+export type SignChallenge = (challenge: ArrayBuffer) => Promise<ArrayBuffer>;
+
+/**
+ * Prove a presentation for `verifierNonce` and resolve to the encoded bundle
+ * to send to the verifier. `sign` is awaited between starting and completing
+ * the presentation, so it may prompt for biometrics.
+ */
+export async function present(
+  documentsPath: string,
+  verifierNonce: ArrayBuffer,
+  sign: SignChallenge
+): Promise<ArrayBuffer> {
+  const { challenge } = startPresentation(documentsPath, verifierNonce);
+  const signature = await sign(challenge);
+  return completePresentationBuffer(documentsPath, signature);
+}
+
+/**
+ * Verify an encoded presentation against the verifying keys in `documentsPath`.
+ * `verifierNonce` is the nonce the verifier issued; a presentation signed over
+ * any other nonce, or whose credential was issued under a key not in
+ * `issuerKeys`, is rejected.
+ */
+export function verify(
+  documentsPath: string,
+  presentation: ArrayBuffer,
+  verifierNonce: ArrayBuffer,
+  issuerKeys: IssuerPublicKey[]
+): Promise<PresentationVerification> {
+  return verifyPresentationBuffer(documentsPath, presentation, verifierNonce, issuerKeys);
+}
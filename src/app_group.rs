@@ -0,0 +1,363 @@
+//! Artifact stores shared through an iOS App Group container.
+//!
+//! A wallet app and its extensions (e.g. a credential autofill extension) run
+//! as separate processes and only share files placed in their App Group
+//! container. `open_app_group_store` creates the artifact store there and
+//! returns the documents path every process then passes to operations. The
+//! operation lock only serializes operations within one process, so on a
+//! store opened this way each operation also holds an exclusive `flock` on
+//! `.lock` in the store: an extension waits for a proof the app is writing
+//! instead of reading half-replaced keys or proofs. Waiting gives up after
+//! `LOCK_TIMEOUT` with a timed-out error rather than blocking forever. Nested
+//! operations on the same store take the file lock once.
+//!
+//! iOS terminates a suspended process that holds a file lock in a shared
+//! container (`0xdead10cc`), so the lock is not held while a proof is being
+//! computed: the prover's compute scope releases it after the proving key is
+//! loaded and takes it again before the outputs are written. The circuit
+//! inputs and shared blinds in the store are digested before the lock is
+//! released and again once it is retaken; if another process changed them in
+//! between, the prove fails without writing its outputs. Nothing else is
+//! checked, so a proof another process wrote meanwhile is overwritten by this
+//! one. Hosts should still run operations on a shared store under a background
+//! task assertion (`beginBackgroundTask`).
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    sync::{Mutex, Once},
+    time::Duration,
+};
+
+use ecdsa_spartan2::{prover::set_compute_scope, setup::SHARED_BLINDS};
+use sha2::{Digest, Sha256};
+
+use crate::ZkProofError;
+
+/// Directory of the store inside the App Group container
+const STORE_DIR: &str = "zkproof";
+/// Marks a documents directory as shared between processes
+const SHARED_MARKER: &str = ".shared";
+/// File whose `flock` serializes operations across processes
+const LOCK_FILE: &str = ".lock";
+
+/// How long an operation waits for another process to release a store
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Interval between attempts to take a store lock
+#[cfg(unix)]
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// Stores whose file lock an operation of this process holds, by canonical path
+/// Each entry shares the open file description of its `StoreLock`, so
+/// unlocking or locking it acts on that lock.
+static HELD: Mutex<Option<HashMap<PathBuf, File>>> = Mutex::new(None);
+
+static INSTALL_COMPUTE_SCOPE: Once = Once::new();
+
+/// Open (creating if needed) the artifact store in the App Group container at
+/// `container_path` and return its documents path
+/// Pass the returned path as `documents_path` from the app and every
+/// extension. `container_path` is the URL `FileManager.containerURL(
+/// forSecurityApplicationGroupIdentifier:)` returns.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn open_app_group_store(container_path: String) -> Result<String, ZkProofError> {
+    if !Path::new(&container_path).is_dir() {
+        return Err(ZkProofError::file_not_found(format!(
+            "App Group container '{}' does not exist",
+            container_path
+        )));
+    }
+    let store = Path::new(&container_path).join(STORE_DIR);
+    let create = || -> io::Result<()> {
+        fs::create_dir_all(store.join("keys"))?;
+        if !store.join(SHARED_MARKER).exists() {
+            fs::write(store.join(SHARED_MARKER), b"")?;
+        }
+        Ok(())
+    };
+    create().map_err(|e| {
+        ZkProofError::io_error(format!(
+            "Failed to create artifact store in '{}': {}",
+            container_path, e
+        ))
+        .with_source(&e)
+    })?;
+    Ok(store.to_string_lossy().into_owned())
+}
+
+/// Holds the cross-process lock of a shared store until dropped
+pub(crate) struct StoreLock {
+    /// `None` when the store is not shared or an enclosing operation holds the lock
+    held: Option<(PathBuf, File)>,
+}
+
+impl StoreLock {
+    /// Take the file lock of the store at `documents_path` if it is shared
+    /// Must be called with the operation lock held.
+    pub(crate) fn acquire(documents_path: &str) -> Result<Self, ZkProofError> {
+        let store = Path::new(documents_path);
+        if !store.join(SHARED_MARKER).exists() {
+            return Ok(Self { held: None });
+        }
+        let key = store.canonicalize().unwrap_or_else(|_| store.to_path_buf());
+        if is_held(&key) {
+            return Ok(Self { held: None });
+        }
+
+        let lock_failed = |e: io::Error| {
+            let message = format!("Failed to lock artifact store '{}': {}", documents_path, e);
+            match e.kind() {
+                io::ErrorKind::TimedOut => ZkProofError::timed_out(message),
+                _ => ZkProofError::io_error(message),
+            }
+            .with_source(&e)
+        };
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(store.join(LOCK_FILE))
+            .and_then(|file| lock_exclusive(&file, LOCK_TIMEOUT).map(|()| file))
+            .map_err(lock_failed)?;
+        let shared = file.try_clone().map_err(lock_failed)?;
+        HELD.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(HashMap::new)
+            .insert(key.clone(), shared);
+        INSTALL_COMPUTE_SCOPE.call_once(|| set_compute_scope(Some(release_during)));
+        Ok(Self {
+            held: Some((key, file)),
+        })
+    }
+}
+
+fn is_held(key: &Path) -> bool {
+    HELD.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .is_some_and(|held| held.contains_key(key))
+}
+
+impl Drop for StoreLock {
+    fn drop(&mut self) {
+        // Closing the last handle to the file releases the lock
+        if let Some((key, _file)) = self.held.take() {
+            let mut held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(held) = held.as_mut() {
+                held.remove(&key);
+            }
+        }
+    }
+}
+
+/// Compute scope of the prover: run `compute` with the store locks of this
+/// process released, then take them again
+/// Fails if another process changed a store's inputs meanwhile (see `inputs_digest`).
+fn release_during(compute: &mut dyn FnMut()) -> io::Result<()> {
+    let stores: Vec<(PathBuf, File)> = {
+        let held = HELD.lock().unwrap_or_else(|e| e.into_inner());
+        held.iter()
+            .flat_map(|held| held.iter())
+            .filter_map(|(store, file)| Some((store.clone(), file.try_clone().ok()?)))
+            .collect()
+    };
+    let mut released = Vec::new();
+    for (store, file) in &stores {
+        let released_store = inputs_digest(store).and_then(|digest| {
+            unlock(file)?;
+            Ok((store, file, digest))
+        });
+        match released_store {
+            Ok(store) => released.push(store),
+            Err(e) => tracing::warn!("Keeping an artifact store locked while proving: {}", e),
+        }
+    }
+    compute();
+    for (_, file, _) in &released {
+        lock_exclusive(file, LOCK_TIMEOUT)?;
+    }
+    for (store, _, digest) in released {
+        if inputs_digest(store)? != digest {
+            return Err(io::Error::other(format!(
+                "another process changed the inputs in '{}' while proving",
+                store.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// SHA-256 over the circuit inputs (`.json` files) and shared blinds in `store`,
+/// in every namespace
+fn inputs_digest(store: &Path) -> io::Result<[u8; 32]> {
+    let mut inputs = Vec::new();
+    collect_inputs(store, &mut inputs)?;
+    inputs.sort();
+    let mut hasher = Sha256::new();
+    for path in inputs {
+        let contents = fs::read(&path)?;
+        for field in [path.to_string_lossy().as_bytes(), &contents] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+    }
+    Ok(hasher.finalize().into())
+}
+
+fn collect_inputs(dir: &Path, inputs: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_inputs(&path, inputs)?;
+        } else if path.extension().is_some_and(|ext| ext == "json")
+            || path.file_name() == Path::new(SHARED_BLINDS).file_name()
+        {
+            inputs.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn lock_exclusive(file: &File, timeout: Duration) -> io::Result<()> {
+    use std::{os::unix::io::AsRawFd, thread, time::Instant};
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+            return Ok(());
+        }
+        let e = io::Error::last_os_error();
+        match e.kind() {
+            io::ErrorKind::Interrupted => {}
+            io::ErrorKind::WouldBlock if Instant::now() < deadline => thread::sleep(LOCK_POLL),
+            io::ErrorKind::WouldBlock => {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("another process held the lock for {:?}", timeout),
+                ))
+            }
+            _ => return Err(e),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn unlock(file: &File) -> io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_UN) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// App Groups only exist on Apple platforms; elsewhere the operation lock suffices
+#[cfg(not(unix))]
+fn lock_exclusive(_file: &File, _timeout: Duration) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn unlock(_file: &File) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lock_file(store: &str) -> File {
+        File::options()
+            .write(true)
+            .open(Path::new(store).join(LOCK_FILE))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_app_group_store_is_shared() {
+        let container = tempfile::tempdir().unwrap();
+        let other_container = tempfile::tempdir().unwrap();
+        let documents = tempfile::tempdir().unwrap();
+        let store = open_app_group_store(container.path().to_string_lossy().into_owned()).unwrap();
+        assert!(Path::new(&store).join("keys").is_dir());
+        // Opening again keeps the store as it is
+        assert_eq!(
+            open_app_group_store(container.path().to_string_lossy().into_owned()).unwrap(),
+            store
+        );
+        let other_store =
+            open_app_group_store(other_container.path().to_string_lossy().into_owned()).unwrap();
+
+        crate::session::run_exclusive(|| {
+            let outer = StoreLock::acquire(&store).unwrap();
+            assert!(outer.held.is_some());
+            assert!(StoreLock::acquire(&store).unwrap().held.is_none());
+            // Held per store: another shared store is locked on its own
+            assert!(StoreLock::acquire(&other_store).unwrap().held.is_some());
+            assert!(StoreLock::acquire(&documents.path().to_string_lossy())
+                .unwrap()
+                .held
+                .is_none());
+        });
+        assert!(Path::new(&store).join(LOCK_FILE).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock_times_out_and_is_released_while_computing() {
+        let container = tempfile::tempdir().unwrap();
+        let store = open_app_group_store(container.path().to_string_lossy().into_owned()).unwrap();
+
+        crate::session::run_exclusive(|| {
+            let _lock = StoreLock::acquire(&store).unwrap();
+            // A separate open of the file stands in for another process
+            let other = lock_file(&store);
+            let err = lock_exclusive(&other, Duration::from_millis(100)).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+            let mut computed = false;
+            release_during(&mut || {
+                lock_exclusive(&other, Duration::ZERO).unwrap();
+                unlock(&other).unwrap();
+                computed = true;
+            })
+            .unwrap();
+            assert!(computed);
+            assert!(lock_exclusive(&other, Duration::ZERO).is_err());
+        });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_inputs_changed_while_computing_are_rejected() {
+        let container = tempfile::tempdir().unwrap();
+        let store = open_app_group_store(container.path().to_string_lossy().into_owned()).unwrap();
+        let blinds = Path::new(&store).join(SHARED_BLINDS);
+        fs::write(&blinds, b"blinds").unwrap();
+        fs::write(Path::new(&store).join("show_input.json"), b"{}").unwrap();
+
+        crate::session::run_exclusive(|| {
+            let _lock = StoreLock::acquire(&store).unwrap();
+            // Outputs another process writes are not inputs
+            release_during(&mut || {
+                fs::write(Path::new(&store).join("keys/show.proof"), b"proof").unwrap();
+            })
+            .unwrap();
+
+            let err =
+                release_during(&mut || fs::write(&blinds, b"reblinded").unwrap()).unwrap_err();
+            assert!(err.to_string().contains("changed the inputs"));
+            let err = release_during(&mut || {
+                fs::write(Path::new(&store).join("show_input.json"), b"[]").unwrap();
+            })
+            .unwrap_err();
+            assert!(err.to_string().contains("changed the inputs"));
+            // The lock is held again either way
+            assert!(lock_exclusive(&lock_file(&store), Duration::ZERO).is_err());
+        });
+    }
+}
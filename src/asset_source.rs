@@ -0,0 +1,52 @@
+//! Bundled circuits and keys read through the host, e.g. Android's `AssetManager`.
+//!
+//! Assets packed into an APK have no path on disk, so without this a host
+//! copies the compiled circuits and keys to the documents directory at first
+//! launch, keeping ~100 MB twice. With `set_asset_source`, keys and circuits
+//! found neither in the documents directory nor under `set_assets_path` are
+//! read through the host's `AssetReader` instead, in chunks, by their path
+//! relative to the assets root (`keys/prepare_proving.key`,
+//! `build/jwt/jwt_js/jwt.r1cs`). Keys set up or imported on the device still
+//! take precedence.
+
+use std::sync::Arc;
+
+use ecdsa_spartan2::asset_source;
+
+use crate::ZkProofError;
+
+/// Reads assets bundled with the app on the host's behalf
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait AssetReader: Send + Sync {
+    /// Up to `max_len` bytes of the asset at `path` starting at `offset`, empty
+    /// past its end, or `None` if the app has no such asset
+    fn read_asset(
+        &self,
+        path: String,
+        offset: u64,
+        max_len: u32,
+    ) -> Result<Option<Vec<u8>>, ZkProofError>;
+}
+
+/// Adapts a host `AssetReader` to the prover's `AssetSource`
+struct ForeignReader(Arc<dyn AssetReader>);
+
+impl asset_source::AssetSource for ForeignReader {
+    fn read(&self, path: &str, offset: u64, max_len: usize) -> Result<Option<Vec<u8>>, String> {
+        self.0
+            .read_asset(
+                path.to_string(),
+                offset,
+                u32::try_from(max_len).unwrap_or(u32::MAX),
+            )
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Read bundled circuits and keys through `reader`; `None` reads them from disk only
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_asset_source(reader: Option<Arc<dyn AssetReader>>) {
+    asset_source::set_asset_source(
+        reader.map(|reader| Arc::new(ForeignReader(reader)) as Arc<dyn asset_source::AssetSource>),
+    );
+}
@@ -0,0 +1,283 @@
+//! Awaitable variants of the long-running exports.
+//!
+//! Each `*_async` function runs its blocking counterpart on a dedicated worker
+//! thread and resolves when it finishes, so the generated bindings expose a
+//! Swift `async` function or a Kotlin `suspend fun` that never blocks the
+//! caller's thread. The future does not depend on any async runtime; UniFFI
+//! polls it from the foreign executor.
+//!
+//! Operations change the process working directory, so the worker runs them one
+//! at a time in submission order, the same guarantee `ZkWallet` gives its calls.
+
+use std::{
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crate::{
+    complete_presentation, import_credential, prove_both_parallel, prove_prepare, prove_show,
+    reblind_all, run_complete_benchmark, run_log::panic_message, setup_prepare_keys,
+    setup_show_keys, verify_prepare, verify_presentation, verify_show, BenchmarkResults,
+    CredentialReceipt, IssuerPublicKey, ParallelProveResult, PresentationBundle,
+    PresentationVerification, ProofResult, ReblindAllResult, VerifyResult, ZkProofError,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+static WORKER: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+
+/// Result of a job on the worker thread, ready once the job has run
+struct Pending<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+struct Slot<T> {
+    result: Option<Result<T, ZkProofError>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for Pending<T> {
+    type Output = Result<T, ZkProofError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Ok(mut slot) = self.slot.lock() else {
+            return Poll::Ready(Err(ZkProofError::io_error(
+                "Async result lock poisoned by an earlier panic".to_string(),
+            )));
+        };
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Queue `op` on the worker thread, resolving to its result
+/// A panic in `op` resolves to an error rather than leaving the future pending.
+fn run_on_worker<T, F>(op: F) -> Pending<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, ZkProofError> + Send + 'static,
+{
+    let slot = Arc::new(Mutex::new(Slot {
+        result: None,
+        waker: None,
+    }));
+    let pending = Pending { slot: slot.clone() };
+    let complete = move |result: Result<T, ZkProofError>| {
+        if let Ok(mut slot) = slot.lock() {
+            slot.result = Some(result);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        }
+    };
+
+    let job: Job = Box::new(move || {
+        let result = catch_unwind(AssertUnwindSafe(op)).unwrap_or_else(|panic| {
+            Err(ZkProofError::io_error(format!(
+                "Async operation panicked: {}",
+                panic_message(panic.as_ref())
+            )))
+        });
+        complete(result);
+    });
+    let sent = worker()
+        .lock()
+        .map_err(|_| ())
+        .and_then(|sender| sender.send(job).map_err(|_| ()));
+    if sent.is_err() {
+        if let Ok(mut slot) = pending.slot.lock() {
+            slot.result = Some(Err(ZkProofError::io_error(
+                "Async worker thread is not running".to_string(),
+            )));
+        }
+    }
+    pending
+}
+
+fn worker() -> &'static Mutex<Sender<Job>> {
+    WORKER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let spawned = thread::Builder::new()
+            .name("zkproof-async".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    job();
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::error!("Failed to start async worker thread: {}", e);
+        }
+        Mutex::new(sender)
+    })
+}
+
+/// `setup_prepare_keys` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn setup_prepare_keys_async(
+    documents_path: String,
+    input_path: Option<String>,
+) -> Result<String, ZkProofError> {
+    run_on_worker(move || setup_prepare_keys(documents_path, input_path)).await
+}
+
+/// `setup_show_keys` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn setup_show_keys_async(
+    documents_path: String,
+    input_path: Option<String>,
+) -> Result<String, ZkProofError> {
+    run_on_worker(move || setup_show_keys(documents_path, input_path)).await
+}
+
+/// `import_credential` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn import_credential_async(
+    documents_path: String,
+    jwt: String,
+    issuer_public_key: Vec<u8>,
+) -> Result<CredentialReceipt, ZkProofError> {
+    run_on_worker(move || import_credential(documents_path, jwt, issuer_public_key)).await
+}
+
+/// `prove_prepare` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn prove_prepare_async(
+    documents_path: String,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    run_on_worker(move || prove_prepare(documents_path, input_path, max_duration_ms)).await
+}
+
+/// `prove_show` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn prove_show_async(
+    documents_path: String,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    run_on_worker(move || prove_show(documents_path, input_path, max_duration_ms)).await
+}
+
+/// `prove_both_parallel` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn prove_both_parallel_async(
+    documents_path: String,
+    prepare_input_path: Option<String>,
+    show_input_path: Option<String>,
+) -> Result<ParallelProveResult, ZkProofError> {
+    run_on_worker(move || prove_both_parallel(documents_path, prepare_input_path, show_input_path))
+        .await
+}
+
+/// `reblind_all` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn reblind_all_async(documents_path: String) -> Result<ReblindAllResult, ZkProofError> {
+    run_on_worker(move || reblind_all(documents_path)).await
+}
+
+/// `verify_prepare` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn verify_prepare_async(documents_path: String) -> Result<VerifyResult, ZkProofError> {
+    run_on_worker(move || verify_prepare(documents_path)).await
+}
+
+/// `verify_show` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn verify_show_async(documents_path: String) -> Result<VerifyResult, ZkProofError> {
+    run_on_worker(move || verify_show(documents_path)).await
+}
+
+/// `complete_presentation` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn complete_presentation_async(
+    documents_path: String,
+    device_signature: Vec<u8>,
+) -> Result<PresentationBundle, ZkProofError> {
+    run_on_worker(move || complete_presentation(documents_path, device_signature)).await
+}
+
+/// `verify_presentation` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn verify_presentation_async(
+    documents_path: String,
+    bundle: PresentationBundle,
+    verifier_nonce: Vec<u8>,
+    issuer_keys: Vec<IssuerPublicKey>,
+) -> Result<PresentationVerification, ZkProofError> {
+    run_on_worker(move || verify_presentation(documents_path, bundle, verifier_nonce, issuer_keys))
+        .await
+}
+
+/// `run_complete_benchmark` on the worker thread
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub async fn run_complete_benchmark_async(
+    documents_path: String,
+    input_path: Option<String>,
+    resume: bool,
+) -> Result<BenchmarkResults, ZkProofError> {
+    run_on_worker(move || run_complete_benchmark(documents_path, input_path, resume)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Wake;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn test_jobs_run_off_the_calling_thread_in_order() {
+        let caller = thread::current().id();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let jobs: Vec<_> = (0..3)
+            .map(|i| {
+                let order = order.clone();
+                run_on_worker(move || {
+                    order.lock().unwrap().push(i);
+                    Ok(thread::current().id())
+                })
+            })
+            .collect();
+        for job in jobs {
+            assert_ne!(block_on(job).unwrap(), caller);
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+
+        let panicked = block_on(run_on_worker(|| -> Result<(), ZkProofError> {
+            panic!("boom")
+        }));
+        assert!(panicked.unwrap_err().to_string().contains("boom"));
+        assert!(block_on(run_on_worker(|| Ok(1))).is_ok());
+    }
+}
@@ -0,0 +1,292 @@
+//! Prepare proving split across short background execution windows.
+//!
+//! Prepare is too slow to finish inside one OS background slot on some phones
+//! (e.g. an iOS `BGTask` window of ~25 s), so the job runs in two stages that
+//! can land in different slots: witness generation, stored as the prefilled
+//! witness, then the Spartan prove over it. Progress is persisted after every
+//! stage, so a job survives the app being suspended or killed between slices.
+//! Stage durations from earlier runs decide whether the next stage still fits
+//! in the current slice's budget; leave some headroom below the OS limit.
+//!
+//! Only the boundary between the stages is resumable. The prove is a single
+//! spartan2 call whose intermediate state cannot be persisted, so it is never
+//! split: a device whose prove alone exceeds the budget gets an error rather
+//! than a job that makes progress across slots.
+
+use std::{
+    fs::{create_dir_all, File},
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use ecdsa_spartan2::{
+    prefill_prepare_witness,
+    prover::prove_circuit,
+    remove_prefilled_witness,
+    setup::{PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_PROVING_KEY, PREPARE_WITNESS},
+    CircuitKind, PrepareCircuit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    extract_comm_w_shared, get_proof_size, prove_failed, run_log::RunLog, with_working_dir,
+    ProofResult, ZkProofError,
+};
+
+/// Location of the persisted background job
+pub const BACKGROUND_PROVE_STATE: &str = "keys/background_prove.json";
+
+/// Next step of a background Prepare prove
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum BackgroundProveStage {
+    /// Generate the Prepare witness
+    Witness,
+    /// Prove over the stored witness
+    Prove,
+    /// The proof is stored at the usual Prepare paths
+    Done,
+}
+
+/// Progress of the background Prepare job after a slice
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct BackgroundProveJob {
+    pub stage: BackgroundProveStage,
+    /// Time spent in this slice
+    pub slice_ms: u64,
+    /// Expected time for the remaining stages, if every stage has run before
+    pub estimated_remaining_ms: Option<u64>,
+    /// Set once the job reaches `Done`
+    pub result: Option<ProofResult>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BackgroundProveState {
+    /// Stage and input of the job in progress, if any
+    active: Option<ActiveJob>,
+    /// Duration of each stage the last time it ran
+    last_witness_ms: Option<u64>,
+    last_prove_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ActiveJob {
+    stage: BackgroundProveStage,
+    input_path: Option<String>,
+}
+
+impl BackgroundProveState {
+    fn load() -> Result<Self, ZkProofError> {
+        if !Path::new(BACKGROUND_PROVE_STATE).exists() {
+            return Ok(Self::default());
+        }
+        serde_json::from_reader(File::open(BACKGROUND_PROVE_STATE)?).map_err(|e| {
+            ZkProofError::invalid_input(format!(
+                "Corrupt background prove state '{}': {}",
+                BACKGROUND_PROVE_STATE, e
+            ))
+            .with_source(&e)
+        })
+    }
+
+    fn save(&self) -> Result<(), ZkProofError> {
+        if let Some(parent) = Path::new(BACKGROUND_PROVE_STATE).parent() {
+            create_dir_all(parent)?;
+        }
+        serde_json::to_writer(File::create(BACKGROUND_PROVE_STATE)?, self).map_err(|e| {
+            ZkProofError::io_error(format!("Failed to save background prove state: {}", e))
+                .with_source(&e)
+        })
+    }
+
+    fn estimate_ms(&self, stage: BackgroundProveStage) -> Option<u64> {
+        match stage {
+            BackgroundProveStage::Witness => self.last_witness_ms,
+            BackgroundProveStage::Prove => self.last_prove_ms,
+            BackgroundProveStage::Done => Some(0),
+        }
+    }
+
+    fn estimated_remaining_ms(&self, stage: BackgroundProveStage) -> Option<u64> {
+        match stage {
+            BackgroundProveStage::Witness => Some(
+                self.estimate_ms(BackgroundProveStage::Witness)?
+                    + self.estimate_ms(BackgroundProveStage::Prove)?,
+            ),
+            stage => self.estimate_ms(stage),
+        }
+    }
+}
+
+/// Start a background Prepare prove, replacing any job in progress
+/// Nothing is proved yet; call `resume_background_prove` from each background slot.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn start_background_prove(
+    documents_path: String,
+    input_path: Option<String>,
+) -> Result<BackgroundProveJob, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        if !Path::new(PREPARE_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "Prepare keys not found; call setup_prepare_keys first".to_string(),
+            ));
+        }
+        let mut state = BackgroundProveState::load()?;
+        state.active = Some(ActiveJob {
+            stage: BackgroundProveStage::Witness,
+            input_path,
+        });
+        state.save()?;
+        Ok(BackgroundProveJob {
+            stage: BackgroundProveStage::Witness,
+            slice_ms: 0,
+            estimated_remaining_ms: state.estimated_remaining_ms(BackgroundProveStage::Witness),
+            result: None,
+        })
+    })
+}
+
+/// Run as many stages of the background job as fit in `budget_ms`
+/// A stage whose previous duration exceeds what is left of the budget waits for
+/// the next slice; one that has never run is only started at the beginning of a
+/// slice. A stage always runs to completion, so fails if one is known to take
+/// longer than the whole budget.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn resume_background_prove(
+    documents_path: String,
+    budget_ms: u64,
+) -> Result<BackgroundProveJob, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let mut state = BackgroundProveState::load()?;
+        let Some(mut job) = state.active.clone() else {
+            return Err(ZkProofError::invalid_input(
+                "No background prove in progress; call start_background_prove first".to_string(),
+            ));
+        };
+        let run = RunLog::start("resume_background_prove");
+        let slice_start = Instant::now();
+        let mut ran_stage = false;
+        let mut result = None;
+
+        while job.stage != BackgroundProveStage::Done {
+            let spent_ms = slice_start.elapsed().as_millis() as u64;
+            match state.estimate_ms(job.stage) {
+                Some(estimate) if estimate > budget_ms => {
+                    return Err(ZkProofError::invalid_input(format!(
+                        "{:?} stage takes about {}ms, more than the {}ms budget",
+                        job.stage, estimate, budget_ms
+                    )));
+                }
+                Some(estimate) if estimate > budget_ms.saturating_sub(spent_ms) => break,
+                None if ran_stage => break,
+                _ => {}
+            }
+
+            let stage_start = Instant::now();
+            match job.stage {
+                BackgroundProveStage::Witness => {
+                    let input = job.input_path.as_ref().map(PathBuf::from);
+                    run.phase("witness", None, || {
+                        prefill_prepare_witness(input.as_deref()).map_err(|e| {
+                            ZkProofError::proof_generation_failed(format!(
+                                "Failed to generate prepare witness: {}",
+                                e
+                            ))
+                            .with_source(&e)
+                        })
+                    })?;
+                    state.last_witness_ms = Some(stage_start.elapsed().as_millis() as u64);
+                    job.stage = BackgroundProveStage::Prove;
+                }
+                BackgroundProveStage::Prove => {
+                    let circuit = PrepareCircuit::new(job.input_path.as_ref().map(PathBuf::from));
+                    run.phase("prove", Some(PREPARE_PROOF), || {
+                        prove_circuit(
+                            circuit,
+                            PREPARE_PROVING_KEY,
+                            PREPARE_INSTANCE,
+                            PREPARE_WITNESS,
+                            PREPARE_PROOF,
+                        )
+                        .map_err(prove_failed)
+                    })?;
+                    let prove_ms = stage_start.elapsed().as_millis() as u64;
+                    state.last_prove_ms = Some(prove_ms);
+                    job.stage = BackgroundProveStage::Done;
+                    result = Some(ProofResult {
+                        prep_ms: 0, // prover doesn't separate timing
+                        prove_ms,
+                        total_ms: prove_ms,
+                        proof_size_bytes: get_proof_size(PREPARE_PROOF)?,
+                        comm_w_shared: extract_comm_w_shared(PREPARE_INSTANCE)?,
+                    });
+                }
+                BackgroundProveStage::Done => unreachable!(),
+            }
+            ran_stage = true;
+            state.active = Some(job.clone());
+            state.save()?;
+        }
+
+        if job.stage == BackgroundProveStage::Done {
+            finish(&mut state)?;
+        }
+        Ok(BackgroundProveJob {
+            stage: job.stage,
+            slice_ms: slice_start.elapsed().as_millis() as u64,
+            estimated_remaining_ms: state.estimated_remaining_ms(job.stage),
+            result,
+        })
+    })
+}
+
+/// Abandon the background job in progress, if any
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn cancel_background_prove(documents_path: String) -> Result<(), ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let mut state = BackgroundProveState::load()?;
+        finish(&mut state)
+    })
+}
+
+/// Clear the active job, keeping stage timings for later estimates
+/// The prefilled witness is deleted, so the credential's witness does not stay
+/// on disk after the job.
+fn finish(state: &mut BackgroundProveState) -> Result<(), ZkProofError> {
+    state.active = None;
+    state.save()?;
+    remove_prefilled_witness(CircuitKind::Prepare)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_remaining() {
+        let mut state = BackgroundProveState::default();
+        assert_eq!(
+            state.estimated_remaining_ms(BackgroundProveStage::Witness),
+            None
+        );
+        state.last_witness_ms = Some(4_000);
+        assert_eq!(
+            state.estimated_remaining_ms(BackgroundProveStage::Witness),
+            None
+        );
+        state.last_prove_ms = Some(15_000);
+        assert_eq!(
+            state.estimated_remaining_ms(BackgroundProveStage::Witness),
+            Some(19_000)
+        );
+        assert_eq!(
+            state.estimated_remaining_ms(BackgroundProveStage::Prove),
+            Some(15_000)
+        );
+        assert_eq!(
+            state.estimated_remaining_ms(BackgroundProveStage::Done),
+            Some(0)
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Checkpoint letting `run_complete_benchmark` resume an earlier run.
+//!
+//! Each time a benchmark step succeeds, the checkpoint records its duration
+//! and the SHA-256 of every artifact it wrote, so the record survives the app
+//! being killed mid-run. A resumed run over the same input reuses a setup,
+//! blind generation or prove step whose artifacts still have the recorded
+//! digests (and whose keys were reused too) and reports its earlier duration.
+//! Reblind and verify steps always run again. Paths are relative to the
+//! documents directory of the artifact layout, or the working directory.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io,
+    path::Path,
+};
+
+use ecdsa_spartan2::{
+    artifact_path,
+    layout::in_documents,
+    setup::{
+        private_instance_path, public_instance_path, write_atomic, PREPARE_INSTANCE, PREPARE_PROOF,
+        PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY, PREPARE_WITNESS, SHARED_BLINDS, SHOW_INSTANCE,
+        SHOW_PROOF, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, SHOW_WITNESS,
+    },
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Location of the checkpoint, relative to the documents directory
+pub const BENCHMARK_CHECKPOINT: &str = "keys/benchmark_checkpoint.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct Checkpoint {
+    /// SHA-256 of the benchmark input file, of nothing for the built-in input
+    input_digest: [u8; 32],
+    /// Steps that can be reused, with the duration they took
+    step_durations_ms: BTreeMap<String, u64>,
+    /// SHA-256 of each artifact as last written by a successful step
+    artifact_digests: BTreeMap<String, [u8; 32]>,
+}
+
+impl Checkpoint {
+    /// An empty checkpoint for the input at `input_path`
+    pub(crate) fn new(input_path: Option<&str>) -> Self {
+        let input = input_path.and_then(|path| fs::read(in_documents(path)).ok());
+        Self {
+            input_digest: Sha256::digest(input.unwrap_or_default()).into(),
+            ..Self::default()
+        }
+    }
+
+    /// The stored checkpoint if it was made over the same input, else an empty one
+    pub(crate) fn load(input_path: Option<&str>) -> Self {
+        let fresh = Self::new(input_path);
+        fs::read(in_documents(BENCHMARK_CHECKPOINT))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Self>(&bytes).ok())
+            .filter(|stored| stored.input_digest == fresh.input_digest)
+            .unwrap_or(fresh)
+    }
+
+    /// The recorded duration of `step`, if its artifacts are as it left them
+    pub(crate) fn reusable(&self, step: &str) -> Option<u64> {
+        let duration_ms = *self.step_durations_ms.get(step)?;
+        step_artifacts(step)
+            .iter()
+            .all(|path| {
+                file_digest(path)
+                    .is_some_and(|digest| self.artifact_digests.get(path) == Some(&digest))
+            })
+            .then_some(duration_ms)
+    }
+
+    /// Record that `step` succeeded in `duration_ms`, with its artifacts as now
+    /// on disk, and save the checkpoint
+    pub(crate) fn record(&mut self, step: &str, duration_ms: u64) {
+        if REUSABLE_STEPS.contains(&step) {
+            self.step_durations_ms.insert(step.to_string(), duration_ms);
+        }
+        // Proofs made with the keys this step replaced cannot be reused
+        for dependent in dependents(step) {
+            self.step_durations_ms.remove(*dependent);
+        }
+        for path in step_artifacts(step) {
+            match file_digest(&path) {
+                Some(digest) => self.artifact_digests.insert(path, digest),
+                None => self.artifact_digests.remove(&path),
+            };
+        }
+
+        // The checkpoint only saves time, so failing to write it must not fail the run
+        let save = || -> Result<(), Box<dyn std::error::Error>> {
+            let path = in_documents(BENCHMARK_CHECKPOINT);
+            if let Some(parent) = Path::new(&path).parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(&path, &serde_json::to_vec(self)?)?;
+            Ok(())
+        };
+        if let Err(e) = save() {
+            tracing::warn!("Failed to save benchmark checkpoint: {}", e);
+        }
+    }
+}
+
+/// Steps a resumed run may skip
+const REUSABLE_STEPS: [&str; 5] = [
+    "setup_prepare",
+    "setup_show",
+    "generate_blinds",
+    "prove_prepare",
+    "prove_show",
+];
+
+/// Reusable steps that must run again whenever `step` does
+fn dependents(step: &str) -> &'static [&'static str] {
+    match step {
+        "setup_prepare" => &["prove_prepare"],
+        "setup_show" => &["prove_show"],
+        _ => &[],
+    }
+}
+
+/// Artifacts `step` writes; a reblind rewrites those of the prove before it
+fn step_artifacts(step: &str) -> Vec<String> {
+    let proved = |instance: &str, witness: &str, proof: &str| {
+        vec![
+            public_instance_path(instance),
+            private_instance_path(instance),
+            artifact_path(witness),
+            artifact_path(proof),
+        ]
+    };
+    match step {
+        "setup_prepare" => vec![
+            artifact_path(PREPARE_PROVING_KEY),
+            artifact_path(PREPARE_VERIFYING_KEY),
+        ],
+        "setup_show" => vec![
+            artifact_path(SHOW_PROVING_KEY),
+            artifact_path(SHOW_VERIFYING_KEY),
+        ],
+        "generate_blinds" => vec![artifact_path(SHARED_BLINDS)],
+        "prove_prepare" | "reblind_prepare" => {
+            proved(PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF)
+        }
+        "prove_show" | "reblind_show" => proved(SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF),
+        _ => Vec::new(),
+    }
+}
+
+/// Streamed, since proving keys can be hundreds of megabytes
+fn file_digest(path: &str) -> Option<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path).ok()?, &mut hasher).ok()?;
+    Some(hasher.finalize().into())
+}
@@ -0,0 +1,223 @@
+//! Uploading benchmark reports from volunteer devices.
+//!
+//! Each report is the `BenchmarkResults` plus device metadata and
+//! `get_version_info`, POSTed as JSON to a collection endpoint. Reports are
+//! queued in the documents directory first and removed only once the endpoint
+//! accepts them, so runs done offline are sent by a later upload or
+//! `flush_benchmark_uploads`.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    get_version_info, self_test::available_memory_bytes, unix_now, with_working_dir,
+    BenchmarkResults, VersionInfo, ZkProofError,
+};
+
+/// Reports not yet accepted by the endpoint, one JSON object per line
+pub const BENCHMARK_UPLOAD_QUEUE: &str = "logs/benchmark_upload_queue.jsonl";
+
+/// Layout version of the uploaded JSON
+const REPORT_VERSION: u32 = 1;
+
+/// Attempts per report before it is left queued
+const MAX_ATTEMPTS: u32 = 3;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delay before the second attempt, doubled for each later one
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// What an upload left behind
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct UploadOutcome {
+    pub uploaded: u32,
+    /// Reports the endpoint refused with a client error; dropped from the queue
+    pub rejected: u32,
+    /// Reports still queued, e.g. because the device is offline
+    pub pending: u32,
+}
+
+#[derive(Serialize)]
+struct DeviceMetadata {
+    os: String,
+    arch: String,
+    rayon_threads: u32,
+    available_memory_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct BenchmarkReport {
+    report_version: u32,
+    created_unix: u64,
+    device: DeviceMetadata,
+    versions: VersionInfo,
+    results: BenchmarkResults,
+}
+
+enum Delivery {
+    Accepted,
+    Rejected(u16),
+    Failed(String),
+}
+
+/// Queue `results` with device metadata and upload every queued report
+/// `token`, if given, is sent as a bearer token. Network failures leave
+/// reports queued rather than returning an error.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn upload_benchmark(
+    documents_path: String,
+    results: BenchmarkResults,
+    endpoint: String,
+    token: Option<String>,
+) -> Result<UploadOutcome, ZkProofError> {
+    let queued = with_working_dir(&documents_path, || {
+        let report = BenchmarkReport {
+            report_version: REPORT_VERSION,
+            created_unix: unix_now(),
+            device: DeviceMetadata {
+                os: std::env::consts::OS.to_string(),
+                arch: std::env::consts::ARCH.to_string(),
+                rayon_threads: rayon::current_num_threads() as u32,
+                available_memory_bytes: available_memory_bytes(),
+            },
+            versions: get_version_info(),
+            results,
+        };
+        let line = serde_json::to_string(&report).map_err(|e| {
+            ZkProofError::invalid_input(format!("Failed to serialize report: {}", e))
+                .with_source(&e)
+        })?;
+
+        if let Some(parent) = Path::new(BENCHMARK_UPLOAD_QUEUE).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut queue = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(BENCHMARK_UPLOAD_QUEUE)?;
+        writeln!(queue, "{}", line)?;
+        read_queue()
+    })?;
+    flush_queue(&documents_path, queued, &endpoint, token.as_deref())
+}
+
+/// Upload reports queued by earlier `upload_benchmark` calls
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn flush_benchmark_uploads(
+    documents_path: String,
+    endpoint: String,
+    token: Option<String>,
+) -> Result<UploadOutcome, ZkProofError> {
+    let queued = with_working_dir(&documents_path, read_queue)?;
+    flush_queue(&documents_path, queued, &endpoint, token.as_deref())
+}
+
+fn read_queue() -> Result<Vec<String>, ZkProofError> {
+    match File::open(BENCHMARK_UPLOAD_QUEUE) {
+        Ok(file) => Ok(BufReader::new(file)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|line| serde_json::from_str::<Value>(line).is_ok())
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Upload `queued` and drop the reports the endpoint settled from the queue
+/// The uploads, retries and backoff run without the operation lock, so proofs
+/// are not held up by a slow network; the lock is taken again only to rewrite
+/// the queue, keeping any report queued in the meantime.
+fn flush_queue(
+    documents_path: &str,
+    queued: Vec<String>,
+    endpoint: &str,
+    token: Option<&str>,
+) -> Result<UploadOutcome, ZkProofError> {
+    let mut outcome = UploadOutcome {
+        uploaded: 0,
+        rejected: 0,
+        pending: 0,
+    };
+    let mut settled = Vec::new();
+    for report in queued {
+        match deliver(endpoint, token, &report) {
+            Delivery::Accepted => outcome.uploaded += 1,
+            Delivery::Rejected(status) => {
+                tracing::warn!("Benchmark endpoint rejected a report with HTTP {}", status);
+                outcome.rejected += 1;
+            }
+            Delivery::Failed(reason) => {
+                // Later reports would fail the same way; keep them for the next flush
+                tracing::warn!(
+                    "Benchmark upload failed, keeping reports queued: {}",
+                    reason
+                );
+                break;
+            }
+        }
+        settled.push(report);
+    }
+
+    with_working_dir(documents_path, || {
+        let mut remaining = read_queue()?;
+        for report in &settled {
+            if let Some(index) = remaining.iter().position(|line| line == report) {
+                remaining.remove(index);
+            }
+        }
+        outcome.pending = remaining.len() as u32;
+        if settled.is_empty() {
+            return Ok(outcome);
+        }
+
+        let tmp = format!("{}.tmp", BENCHMARK_UPLOAD_QUEUE);
+        fs::write(
+            &tmp,
+            remaining
+                .iter()
+                .map(|report| format!("{}\n", report))
+                .collect::<String>(),
+        )?;
+        fs::rename(&tmp, BENCHMARK_UPLOAD_QUEUE)?;
+        Ok(outcome)
+    })
+}
+
+/// POST one report, retrying network errors, timeouts and server errors
+fn deliver(endpoint: &str, token: Option<&str>, report: &str) -> Delivery {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut last_error = String::new();
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = ureq::post(endpoint)
+            .timeout(REQUEST_TIMEOUT)
+            .set("Content-Type", "application/json");
+        if let Some(token) = token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+        match request.send_string(report) {
+            Ok(_) => return Delivery::Accepted,
+            Err(ureq::Error::Status(status, _))
+                if (400..500).contains(&status) && status != 408 && status != 429 =>
+            {
+                return Delivery::Rejected(status)
+            }
+            Err(e) => last_error = e.to_string(),
+        }
+        if attempt < MAX_ATTEMPTS {
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+    Delivery::Failed(last_error)
+}
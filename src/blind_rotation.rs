@@ -0,0 +1,266 @@
+//! Replacing a credential's shared blinds with a freshly proved Prepare.
+//!
+//! `reblind_all` resamples the blinds but keeps reblinding the Prepare proof
+//! made at import, so its prep randomness lives as long as the credential.
+//! `rotate_shared_blinds` samples new blinds, proves Prepare again from the
+//! stored input, and reblinds it (and the stored Show proof, if any) under the
+//! new blinds. Everything is written to a staging directory next to the
+//! credential's artifacts. Once all of it exists a `COMMITTED` marker is
+//! written to the staging directory, and only then are the files renamed into
+//! place. The renames are not atomic together, so the staging directory is the
+//! journal: `recover_interrupted_rotations` runs before every store operation,
+//! finishing the renames of a committed rotation and discarding an
+//! uncommitted one. An interrupted rotation therefore leaves either the
+//! previous state or the rotated one, never a mix.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use ecdsa_spartan2::{
+    load_proving_key, load_shared_blinds,
+    namespace::CREDENTIALS_DIR,
+    prover::{generate_shared_blinds as gen_shared_blinds, prove_circuit_with_pk},
+    setup::{
+        instance_part_path, write_atomic, PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_PROVING_KEY,
+        PREPARE_WITNESS, PRIVATE_INSTANCE_EXT, PUBLIC_INSTANCE_EXT, SHARED_BLINDS, SHOW_INSTANCE,
+        SHOW_PROOF, SHOW_PROVING_KEY, SHOW_WITNESS,
+    },
+    ArtifactNamespace, PrepareCircuit, ProvingKey, ShowCircuit, E,
+};
+
+use crate::{
+    extract_comm_w_shared, prove_failed, reblind_loaded, reset_presentation_counter,
+    run_log::RunLog, with_working_dir, ZkProofError, NUM_SHARED,
+};
+
+/// Staging directory inside the credential directory
+const STAGING_DIR: &str = "rotating";
+
+/// File in the staging directory marking a rotation as complete
+const COMMITTED_MARKER: &str = "COMMITTED";
+
+/// Outcome of `rotate_shared_blinds`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct BlindRotation {
+    pub credential_id: String,
+    /// comm_W_shared before the rotation, if a Prepare instance was stored
+    pub previous_comm_w_shared: Option<String>,
+    pub comm_w_shared: String,
+    /// Whether the stored Show proof was reblinded along with Prepare
+    pub show_rotated: bool,
+    pub total_ms: u64,
+}
+
+/// Sample new shared blinds for `credential_id` and prove Prepare under them
+/// Proves from the input kept by `import_credential`, so import the credential
+/// first. The credential's artifacts are replaced only after the new Prepare
+/// (and Show, if one was stored) proof is complete; the presentation counter
+/// restarts as after any fresh blinds.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn rotate_shared_blinds(
+    documents_path: String,
+    credential_id: String,
+) -> Result<BlindRotation, ZkProofError> {
+    let namespace =
+        ArtifactNamespace::credential(&credential_id).map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        let staging = staging_dir(&namespace);
+        let staged = |artifact: &str| -> String {
+            let resolved = namespace.resolve(artifact);
+            let file = Path::new(&resolved).file_name().unwrap_or_default();
+            staging.join(file).to_string_lossy().into_owned()
+        };
+
+        let pk = load_proving_key::<E>(PREPARE_PROVING_KEY).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load proving key from '{}': {}",
+                PREPARE_PROVING_KEY, e
+            ))
+            .with_source(&*e)
+        })?;
+        let previous_comm_w_shared =
+            extract_comm_w_shared(&namespace.resolve(PREPARE_INSTANCE)).ok();
+        let show_rotated = Path::new(&stored_instance(&namespace, SHOW_INSTANCE)).exists();
+
+        // `with_working_dir` has already recovered any earlier rotation
+        fs::create_dir_all(&staging)?;
+
+        let run = RunLog::start("rotate_shared_blinds");
+        let start = Instant::now();
+        let result = stage_rotation(&run, &pk, &namespace, show_rotated, &staged);
+        let comm_w_shared = match result {
+            Ok(comm_w_shared) => comm_w_shared,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&staging);
+                return Err(e);
+            }
+        };
+
+        write_atomic(
+            &staging.join(COMMITTED_MARKER).to_string_lossy(),
+            credential_id.as_bytes(),
+        )?;
+        commit_staged(&namespace)?;
+
+        Ok(BlindRotation {
+            credential_id: credential_id.clone(),
+            previous_comm_w_shared,
+            comm_w_shared,
+            show_rotated,
+            total_ms: start.elapsed().as_millis() as u64,
+        })
+    })
+}
+
+/// Finish or discard every rotation a previous process left in a staging directory
+pub(crate) fn recover_interrupted_rotations() -> Result<(), ZkProofError> {
+    let Ok(credentials) = fs::read_dir(CREDENTIALS_DIR) else {
+        return Ok(());
+    };
+    for entry in credentials {
+        let id = entry?.file_name();
+        let Ok(namespace) = ArtifactNamespace::credential(&id.to_string_lossy()) else {
+            continue;
+        };
+        let staging = staging_dir(&namespace);
+        if !staging.is_dir() {
+            continue;
+        }
+        if staging.join(COMMITTED_MARKER).exists() {
+            commit_staged(&namespace)?;
+        } else {
+            fs::remove_dir_all(&staging)?;
+        }
+    }
+    Ok(())
+}
+
+/// Staging directory of a rotation of the credential in `namespace`
+fn staging_dir(namespace: &ArtifactNamespace) -> PathBuf {
+    let prepare_proof = namespace.resolve(PREPARE_PROOF);
+    Path::new(&prepare_proof)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(STAGING_DIR)
+}
+
+/// Move the files of the committed rotation staged for `namespace` into place
+/// Safe to repeat after an interruption at any point: files already moved are
+/// no longer staged, and the staging directory with its marker goes last. The
+/// blinds move after the proofs so they never run ahead of the proofs
+/// reblinded with them.
+fn commit_staged(namespace: &ArtifactNamespace) -> Result<(), ZkProofError> {
+    let staging = staging_dir(namespace);
+    let credential_dir = staging.parent().unwrap_or(Path::new(""));
+    let blinds = namespace.resolve(SHARED_BLINDS);
+    let blinds = Path::new(&blinds).file_name();
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&staging)? {
+        let name = entry?.file_name();
+        if name != COMMITTED_MARKER {
+            files.push(name);
+        }
+    }
+    files.sort_by_key(|name| Some(name.as_os_str()) == blinds);
+    for name in files {
+        fs::rename(staging.join(&name), credential_dir.join(&name))?;
+    }
+
+    // Instances stored before the public/private split are superseded
+    for artifact in [PREPARE_INSTANCE, SHOW_INSTANCE] {
+        let _ = fs::remove_file(namespace.resolve(artifact));
+    }
+    reset_presentation_counter(namespace)?;
+    fs::remove_dir_all(&staging)?;
+    Ok(())
+}
+
+/// Write the rotated artifacts under `staged` paths, returning the new comm_W_shared
+fn stage_rotation(
+    run: &RunLog,
+    pk: &ProvingKey,
+    namespace: &ArtifactNamespace,
+    with_show: bool,
+    staged: &dyn Fn(&str) -> String,
+) -> Result<String, ZkProofError> {
+    let blinds_path = staged(SHARED_BLINDS);
+    run.phase("generate_blinds", Some(&blinds_path), || {
+        gen_shared_blinds::<E>(&blinds_path, NUM_SHARED).map_err(prove_failed)
+    })?;
+    let shared_blinds = load_shared_blinds::<E>(&blinds_path).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+            .with_source(&*e)
+    })?;
+
+    let [instance, witness, proof] = [PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF].map(staged);
+    run.phase("prove_prepare", Some(&proof), || {
+        prove_circuit_with_pk(PrepareCircuit::new(None), pk, &instance, &witness, &proof)
+            .map_err(prove_failed)
+    })?;
+    run.phase("reblind_prepare", Some(&proof), || {
+        reblind_loaded(
+            PrepareCircuit::new(None),
+            pk,
+            &shared_blinds,
+            &instance,
+            &witness,
+            &proof,
+        )
+    })?;
+    let comm_w_shared = extract_comm_w_shared(&instance)?;
+
+    if with_show {
+        let show_pk = load_proving_key::<E>(SHOW_PROVING_KEY).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load proving key from '{}': {}",
+                SHOW_PROVING_KEY, e
+            ))
+            .with_source(&*e)
+        })?;
+        let [instance, witness, proof] = [SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF].map(staged);
+        fs::copy(
+            stored_instance(namespace, SHOW_INSTANCE),
+            instance_part_path(&staged(SHOW_INSTANCE), PRIVATE_INSTANCE_EXT),
+        )?;
+        // The reblind keeps the public values, nonce hash included, stored beside it
+        fs::copy(
+            instance_part_path(&namespace.resolve(SHOW_INSTANCE), PUBLIC_INSTANCE_EXT),
+            instance_part_path(&staged(SHOW_INSTANCE), PUBLIC_INSTANCE_EXT),
+        )?;
+        fs::copy(namespace.resolve(SHOW_WITNESS), staged(SHOW_WITNESS))?;
+        run.phase("reblind_show", Some(&proof), || {
+            reblind_loaded(
+                ShowCircuit::default(),
+                &show_pk,
+                &shared_blinds,
+                &instance,
+                &witness,
+                &proof,
+            )
+        })?;
+        if extract_comm_w_shared(&instance)? != comm_w_shared {
+            return Err(ZkProofError::verification_failed(
+                "Prepare and Show comm_W_shared differ after rotation".to_string(),
+            ));
+        }
+    }
+    Ok(comm_w_shared)
+}
+
+/// File holding the full `instance` in `namespace`, its private part unless it
+/// was stored before the split
+fn stored_instance(namespace: &ArtifactNamespace, instance: &str) -> String {
+    let resolved = namespace.resolve(instance);
+    let private = instance_part_path(&resolved, PRIVATE_INSTANCE_EXT);
+    if Path::new(&private).exists() {
+        private
+    } else {
+        resolved
+    }
+}
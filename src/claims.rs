@@ -0,0 +1,34 @@
+//! Claim hashes and shared-witness values without running a circuit.
+//!
+//! Lets issuers and verifiers compute what Prepare and Show enforce about a
+//! credential's disclosures, e.g. to check a presented `_sd` digest or to know
+//! which values a `comm_W_shared` commits to. See `ecdsa_spartan2::claims`.
+
+use ecdsa_spartan2::{claims, PrepareParams};
+
+use crate::ZkProofError;
+
+/// SD-JWT `_sd` digest of each base64url disclosure, as the Prepare circuit hashes it
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn hash_claims(disclosures: Vec<String>) -> Vec<String> {
+    claims::hash_claims(&disclosures)
+}
+
+/// Hex scalars that `comm_W_shared` commits to for `disclosure`
+/// `keybinding_x` and `keybinding_y` are the base64url coordinates of the
+/// credential's `cnf.jwk`. Sized for the compiled Prepare and Show circuits.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn commit_claims(
+    disclosure: String,
+    keybinding_x: String,
+    keybinding_y: String,
+) -> Result<Vec<String>, ZkProofError> {
+    let shared = claims::commit_claims(
+        &disclosure,
+        &keybinding_x,
+        &keybinding_y,
+        PrepareParams::default().max_claim_length,
+    )
+    .map_err(ZkProofError::invalid_input)?;
+    Ok(shared.iter().map(|s| format!("{:?}", s)).collect())
+}
@@ -0,0 +1,162 @@
+//! Credential issuance: from the SD-JWT a wallet receives to a stored Prepare proof.
+//!
+//! `import_credential` builds the Prepare input from the credential, samples
+//! fresh shared blinds, and proves Prepare, so the app never handles circuit
+//! inputs or blinds itself. The input is kept as `jwt_input.json`, where
+//! `PrepareCircuit` looks by default, so later reblinds reuse it.
+//! `import_credential_with_issuer_keys` does the same with the signing key
+//! picked from the issuer's key set by the JWT's `kid` and the current time.
+//! Both prove in the smallest JWT circuit variant the credential fits, which
+//! must have had its keys set up.
+
+use std::{fs::File, path::Path};
+
+use ecdsa_spartan2::{
+    artifact_path, build_prepare_input, credential_id,
+    namespace::artifact_namespace,
+    prover::{generate_shared_blinds as gen_shared_blinds, prove_circuit},
+    select_issuer_key,
+    setup::{PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_PROVING_KEY, PREPARE_WITNESS, SHARED_BLINDS},
+    variant::has_stored_key,
+    IssuerKey, IssuerKeySet, JwtVariant, PrepareCircuit, ProofValidity, E,
+};
+
+use crate::{
+    extract_comm_w_shared, prove_failed, reset_presentation_counter, run_log::RunLog, unix_now,
+    with_working_dir, ProofFreshness, ZkProofError, NUM_SHARED,
+};
+
+/// Prepare input built from the imported credential
+pub(crate) const PREPARE_INPUT: &str = "jwt_input.json";
+
+/// How long an imported credential's Prepare proof stays acceptable to verifiers
+const PREPARE_PROOF_TTL_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Outcome of importing a credential
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct CredentialReceipt {
+    /// Hex SHA-256 of the issuer-signed JWT
+    pub credential_id: String,
+    /// Shared witness commitment every later Show proof must match
+    pub comm_w_shared: String,
+    pub prepare_proof_path: String,
+    /// Validity window bound into the Prepare proof
+    pub freshness: ProofFreshness,
+    /// `kid` of the issuer key the credential was checked against, if known
+    pub issuer_kid: Option<String>,
+}
+
+/// One of an issuer's signing keys
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct IssuerPublicKey {
+    /// `kid` the issuer sets in the JWT header for this key
+    pub kid: String,
+    /// P-256 key as 65-byte uncompressed SEC1 or 64-byte `X || Y`
+    pub public_key: Vec<u8>,
+    /// Unix seconds from which the key signs, 0 for no lower bound
+    pub not_before_unix: u64,
+    /// Unix seconds after which the key no longer signs, 0 for no upper bound
+    pub not_after_unix: u64,
+}
+
+/// Build the key set, rejecting malformed keys and repeated `kid`s
+pub(crate) fn issuer_key_set(keys: Vec<IssuerPublicKey>) -> Result<IssuerKeySet, ZkProofError> {
+    IssuerKeySet::new(
+        keys.into_iter()
+            .map(|key| IssuerKey {
+                kid: key.kid,
+                public_key: key.public_key,
+                not_before: key.not_before_unix,
+                not_after: key.not_after_unix,
+            })
+            .collect(),
+    )
+    .map_err(ZkProofError::invalid_input)
+}
+
+/// Import an SD-JWT credential and prove Prepare over it
+/// `issuer_public_key` is the issuer's P-256 key as 65-byte uncompressed SEC1 or
+/// 64-byte `X || Y`. Replaces any previously imported credential and its blinds.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn import_credential(
+    documents_path: String,
+    jwt: String,
+    issuer_public_key: Vec<u8>,
+) -> Result<CredentialReceipt, ZkProofError> {
+    import_with_key(documents_path, jwt, &issuer_public_key, None)
+}
+
+/// `import_credential` against whichever of `issuer_keys` signed the credential
+/// Only keys matching the JWT header's `kid` (any key, without one) and valid
+/// now are tried. The chosen key's coordinates are bound into the Prepare proof,
+/// so verifiers can check it with `verify_prepare_issuer`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn import_credential_with_issuer_keys(
+    documents_path: String,
+    jwt: String,
+    issuer_keys: Vec<IssuerPublicKey>,
+) -> Result<CredentialReceipt, ZkProofError> {
+    let keys = issuer_key_set(issuer_keys)?;
+    let key =
+        select_issuer_key(jwt.trim(), &keys, unix_now()).map_err(ZkProofError::invalid_input)?;
+    import_with_key(documents_path, jwt, &key.public_key, Some(key.kid.clone()))
+}
+
+fn import_with_key(
+    documents_path: String,
+    jwt: String,
+    issuer_public_key: &[u8],
+    issuer_kid: Option<String>,
+) -> Result<CredentialReceipt, ZkProofError> {
+    let variant = JwtVariant::for_jwt(jwt.trim()).map_err(ZkProofError::invalid_input)?;
+    let mut input = build_prepare_input(jwt.trim(), issuer_public_key, &variant.params())
+        .map_err(ZkProofError::invalid_input)?;
+    let validity = ProofValidity::starting_at(unix_now(), PREPARE_PROOF_TTL_SECS);
+    validity
+        .apply_to_input(&mut input)
+        .map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        ecdsa_spartan2::set_jwt_variant(variant);
+        if !has_stored_key(PREPARE_PROVING_KEY) {
+            return Err(ZkProofError::setup_required(format!(
+                "Prepare keys for the {} circuit not found; call setup_prepare_keys first",
+                variant.name()
+            )));
+        }
+        let run = RunLog::start("import_credential");
+
+        serde_json::to_writer(File::create(PREPARE_INPUT)?, &input).map_err(|e| {
+            ZkProofError::io_error(format!("Failed to save prepare input: {}", e)).with_source(&e)
+        })?;
+
+        run.phase("generate_blinds", Some(SHARED_BLINDS), || {
+            gen_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED).map_err(prove_failed)
+        })?;
+        reset_presentation_counter(&artifact_namespace())?;
+
+        run.phase("prove", Some(PREPARE_PROOF), || {
+            prove_circuit(
+                PrepareCircuit::new(None),
+                PREPARE_PROVING_KEY,
+                PREPARE_INSTANCE,
+                PREPARE_WITNESS,
+                PREPARE_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+
+        Ok(CredentialReceipt {
+            credential_id: credential_id(jwt.trim()),
+            comm_w_shared: extract_comm_w_shared(PREPARE_INSTANCE)?,
+            prepare_proof_path: Path::new(&documents_path)
+                .join(artifact_path(PREPARE_PROOF))
+                .to_string_lossy()
+                .into_owned(),
+            freshness: validity.into(),
+            issuer_kid,
+        })
+    })
+}
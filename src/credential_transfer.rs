@@ -0,0 +1,237 @@
+//! Moving a credential's proving state between a holder's devices.
+//!
+//! A credential is expensive to set up again (Prepare is proved over the
+//! issuer's signature) and its shared blinds must survive the move, or later
+//! Show proofs would no longer link to the stored Prepare proof. A transfer
+//! package carries the credential namespace's Prepare proof, both parts of its
+//! instance and its witness, the shared blinds, and metadata, encrypted under a key the two
+//! devices agree on out of band:
+//!
+//! ```text
+//! ZKCT || version (u8) || nonce (12) || ChaCha20-Poly1305(bincode(package))
+//! ```
+//!
+//! The header is authenticated as associated data. Witnesses and blinds are
+//! carried in the clear inside the encryption and resealed on import if
+//! artifact protection is enabled there.
+
+use std::{fs, path::Path};
+
+use ecdsa_spartan2::{
+    sealed::{self, decrypt_with_key, encrypt_with_key, KEY_LEN},
+    setup::{
+        instance_part_path, write_atomic, PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_WITNESS,
+        PRIVATE_INSTANCE_EXT, PUBLIC_INSTANCE_EXT, SHARED_BLINDS,
+    },
+    ArtifactNamespace,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{unix_now, with_working_dir, ZkProofError};
+
+const MAGIC: &[u8; 4] = b"ZKCT";
+
+/// Layout version of the package
+/// Version 2 carries the Prepare instance split into public and private parts.
+const PACKAGE_VERSION: u8 = 2;
+
+const HEADER_LEN: usize = MAGIC.len() + 1;
+
+#[derive(Serialize, Deserialize)]
+struct CredentialPackage {
+    credential_id: String,
+    exported_at_unix: u64,
+    /// Library version of the exporting device
+    crate_version: String,
+    prepare_proof: Vec<u8>,
+    prepare_public_instance: Vec<u8>,
+    prepare_private_instance: Vec<u8>,
+    prepare_witness: Vec<u8>,
+    shared_blinds: Vec<u8>,
+}
+
+/// Encrypt the stored state of `credential_id` for moving to another device
+/// The credential must live in its own namespace (see `set_artifact_namespace`).
+/// `encryption_key` is 32 bytes; the importing device needs the same key.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn export_credential(
+    documents_path: String,
+    credential_id: String,
+    encryption_key: Vec<u8>,
+) -> Result<Vec<u8>, ZkProofError> {
+    let key = transfer_key(&encryption_key)?;
+    let namespace =
+        ArtifactNamespace::credential(&credential_id).map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        let read_file = |path: String| -> Result<Vec<u8>, ZkProofError> {
+            fs::read(&path).map_err(|e| {
+                ZkProofError::file_not_found(format!(
+                    "Failed to read '{}' for credential export: {}",
+                    path, e
+                ))
+                .with_source(&e)
+            })
+        };
+        let read = |artifact: &str| read_file(namespace.resolve(artifact));
+        let read_instance = |ext: &str| {
+            read_file(instance_part_path(
+                &namespace.resolve(PREPARE_INSTANCE),
+                ext,
+            ))
+        };
+        let open = |bytes: Vec<u8>| {
+            sealed::open(bytes).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to unseal credential state: {}", e))
+                    .with_source(&*e)
+            })
+        };
+
+        let package = CredentialPackage {
+            credential_id: credential_id.clone(),
+            exported_at_unix: unix_now(),
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            prepare_proof: read(PREPARE_PROOF)?,
+            prepare_public_instance: read_instance(PUBLIC_INSTANCE_EXT)?,
+            prepare_private_instance: read_instance(PRIVATE_INSTANCE_EXT)?,
+            prepare_witness: open(read(PREPARE_WITNESS)?)?,
+            shared_blinds: open(read(SHARED_BLINDS)?)?,
+        };
+        encode_package(&package, &key)
+    })
+}
+
+/// Install a package from `export_credential`, returning its credential id
+/// The state is written to that credential's namespace, replacing any state
+/// already there; select it with `set_artifact_namespace` before presenting.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn import_credential_package(
+    documents_path: String,
+    package: Vec<u8>,
+    encryption_key: Vec<u8>,
+) -> Result<String, ZkProofError> {
+    let key = transfer_key(&encryption_key)?;
+    let package = decode_package(&package, &key)?;
+    let namespace = ArtifactNamespace::credential(&package.credential_id)
+        .map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        let seal = |bytes: Vec<u8>| {
+            sealed::seal_if_enabled(bytes).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to seal credential state: {}", e))
+                    .with_source(&*e)
+            })
+        };
+        let instance_path = namespace.resolve(PREPARE_INSTANCE);
+        let files = [
+            (namespace.resolve(PREPARE_PROOF), package.prepare_proof),
+            (
+                instance_part_path(&instance_path, PUBLIC_INSTANCE_EXT),
+                package.prepare_public_instance,
+            ),
+            (
+                instance_part_path(&instance_path, PRIVATE_INSTANCE_EXT),
+                package.prepare_private_instance,
+            ),
+            (
+                namespace.resolve(PREPARE_WITNESS),
+                seal(package.prepare_witness)?,
+            ),
+            (
+                namespace.resolve(SHARED_BLINDS),
+                seal(package.shared_blinds)?,
+            ),
+        ];
+        for (path, bytes) in files {
+            if let Some(parent) = Path::new(&path).parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(&path, &bytes)?;
+        }
+        Ok(package.credential_id)
+    })
+}
+
+fn transfer_key(key: &[u8]) -> Result<[u8; KEY_LEN], ZkProofError> {
+    <[u8; KEY_LEN]>::try_from(key).map_err(|_| {
+        ZkProofError::invalid_input(format!(
+            "Encryption key must be {} bytes, got {}",
+            KEY_LEN,
+            key.len()
+        ))
+    })
+}
+
+fn header() -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[..MAGIC.len()].copy_from_slice(MAGIC);
+    header[MAGIC.len()] = PACKAGE_VERSION;
+    header
+}
+
+fn encode_package(
+    package: &CredentialPackage,
+    key: &[u8; KEY_LEN],
+) -> Result<Vec<u8>, ZkProofError> {
+    let plaintext = bincode::serialize(package).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to serialize credential: {}", e))
+            .with_source(&*e)
+    })?;
+    let header = header();
+    let encrypted = encrypt_with_key(key, &plaintext, &header).map_err(|e| {
+        ZkProofError::io_error(format!("Failed to encrypt credential: {}", e)).with_source(&*e)
+    })?;
+    Ok([header.as_slice(), &encrypted].concat())
+}
+
+fn decode_package(bytes: &[u8], key: &[u8; KEY_LEN]) -> Result<CredentialPackage, ZkProofError> {
+    if bytes.len() < HEADER_LEN || !bytes.starts_with(MAGIC) {
+        return Err(ZkProofError::invalid_input(
+            "Not a credential transfer package",
+        ));
+    }
+    let (header, encrypted) = bytes.split_at(HEADER_LEN);
+    if header[MAGIC.len()] != PACKAGE_VERSION {
+        return Err(ZkProofError::invalid_input(format!(
+            "Unsupported credential package version {}",
+            header[MAGIC.len()]
+        )));
+    }
+    let plaintext = decrypt_with_key(key, encrypted, header).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to decrypt credential package: {}", e))
+            .with_source(&*e)
+    })?;
+    ecdsa_spartan2::setup::deserialize_bounded(&plaintext).map_err(|e| {
+        ZkProofError::invalid_input(format!("Malformed credential package: {}", e)).with_source(&*e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_round_trip_and_wrong_key() {
+        let package = CredentialPackage {
+            credential_id: "abc".to_string(),
+            exported_at_unix: 1,
+            crate_version: "0.1.0".to_string(),
+            prepare_proof: vec![1; 10],
+            prepare_public_instance: vec![2; 10],
+            prepare_private_instance: vec![5; 10],
+            prepare_witness: vec![3; 10],
+            shared_blinds: vec![4; 10],
+        };
+        let key = [7u8; KEY_LEN];
+        let bytes = encode_package(&package, &key).unwrap();
+
+        let decoded = decode_package(&bytes, &key).unwrap();
+        assert_eq!(decoded.credential_id, "abc");
+        assert_eq!(decoded.shared_blinds, vec![4; 10]);
+        assert!(decode_package(&bytes, &[8u8; KEY_LEN]).is_err());
+
+        let mut downgraded = bytes;
+        downgraded[MAGIC.len()] = 0;
+        assert!(decode_package(&downgraded, &key).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Built-in demo circuit for integration and on-device testing.
+//!
+//! `DemoCircuit` needs no circom artifacts and proves in milliseconds, yet goes
+//! through the same setup, prove, reblind and verify code as Prepare and Show,
+//! so app integrations can be exercised without shipping the JWT circuits.
+//! Artifacts live under `keys/demo_*`.
+//!
+//! `PermutationCircuit` does the same for circuits that draw verifier
+//! challenges (`keys/permutation_*`). Its proofs cannot be reblinded, since new
+//! blinds would change the challenges, so it has no reblind entry point.
+
+use std::path::Path;
+
+use ecdsa_spartan2::{
+    circuits::permutation_circuit::{DEFAULT_PERMUTATION_VALUES, PERMUTATION_LEN},
+    prover::{generate_shared_blinds, prove_circuit, reblind},
+    setup::{
+        DEMO_INSTANCE, DEMO_PROOF, DEMO_PROVING_KEY, DEMO_SHARED_BLINDS, DEMO_VERIFYING_KEY,
+        DEMO_WITNESS, PERMUTATION_INSTANCE, PERMUTATION_PROOF, PERMUTATION_PROVING_KEY,
+        PERMUTATION_VERIFYING_KEY, PERMUTATION_WITNESS,
+    },
+    DemoCircuit, PermutationCircuit, Scalar, E,
+};
+
+use crate::{
+    extract_comm_w_shared, get_proof_size, prove_failed, run_log::RunLog, setup_and_save_keys,
+    verify_result_with, with_working_dir, ProofResult, VerifyResult, ZkProofError, NUM_SHARED,
+};
+
+/// Setup demo circuit keys
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_demo_keys(documents_path: String) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("setup_demo_keys");
+
+        let start = std::time::Instant::now();
+        run.phase("setup", Some(DEMO_PROVING_KEY), || {
+            setup_and_save_keys(DemoCircuit::default(), DEMO_PROVING_KEY, DEMO_VERIFYING_KEY)
+        })?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        Ok(format!(
+            "Demo circuit keys setup completed in {}ms",
+            elapsed_ms
+        ))
+    })
+}
+
+/// Prove knowledge of `secret` with the demo circuit
+/// `None` uses `DEFAULT_DEMO_SECRET`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_demo(
+    documents_path: String,
+    secret: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    let circuit = secret.map_or_else(DemoCircuit::default, DemoCircuit::new);
+
+    with_working_dir(&documents_path, || {
+        if !Path::new(DEMO_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "Demo proving key not found; call setup_demo_keys first",
+            ));
+        }
+        let run = RunLog::start("prove_demo");
+
+        let start = std::time::Instant::now();
+        run.phase("prove", Some(DEMO_PROOF), || {
+            prove_circuit::<E, _>(
+                circuit,
+                DEMO_PROVING_KEY,
+                DEMO_INSTANCE,
+                DEMO_WITNESS,
+                DEMO_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        Ok(ProofResult {
+            prep_ms: 0,
+            prove_ms: total_ms,
+            total_ms,
+            proof_size_bytes: get_proof_size(DEMO_PROOF)?,
+            comm_w_shared: extract_comm_w_shared(DEMO_INSTANCE)?,
+        })
+    })
+}
+
+/// Reblind the demo proof
+/// Samples the demo's shared blinds on first use, so repeated reblinds keep the
+/// same comm_W_shared.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_demo(documents_path: String) -> Result<ProofResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("reblind_demo");
+        if !Path::new(DEMO_SHARED_BLINDS).exists() {
+            run.phase("generate_blinds", Some(DEMO_SHARED_BLINDS), || {
+                generate_shared_blinds::<E>(DEMO_SHARED_BLINDS, NUM_SHARED).map_err(prove_failed)
+            })?;
+        }
+
+        let start = std::time::Instant::now();
+        run.phase("reblind", Some(DEMO_PROOF), || {
+            reblind::<E, _>(
+                DemoCircuit::default(),
+                DEMO_PROVING_KEY,
+                DEMO_INSTANCE,
+                DEMO_WITNESS,
+                DEMO_PROOF,
+                DEMO_SHARED_BLINDS,
+            )
+            .map_err(prove_failed)
+        })?;
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+
+        Ok(ProofResult {
+            prep_ms: 0,
+            prove_ms: elapsed_ms,
+            total_ms: elapsed_ms,
+            proof_size_bytes: get_proof_size(DEMO_PROOF)?,
+            comm_w_shared: extract_comm_w_shared(DEMO_INSTANCE)?,
+        })
+    })
+}
+
+/// Verify the demo proof
+/// With `secret`, the proof is also rejected unless its public output is the one
+/// `secret` produces.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_demo(
+    documents_path: String,
+    secret: Option<u64>,
+) -> Result<VerifyResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_demo");
+        run.phase("verify", None, || {
+            verify_result_with(
+                DEMO_PROOF,
+                DEMO_VERIFYING_KEY,
+                |public_values| match secret {
+                    Some(secret) if public_values != [DemoCircuit::output::<Scalar>(secret)] => {
+                        Err("Public output does not match the expected secret".to_string())
+                    }
+                    _ => Ok(()),
+                },
+            )
+        })
+    })
+}
+
+/// Setup keys for the challenge-drawing permutation circuit
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_permutation_keys(documents_path: String) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("setup_permutation_keys");
+
+        let start = std::time::Instant::now();
+        run.phase("setup", Some(PERMUTATION_PROVING_KEY), || {
+            setup_and_save_keys(
+                PermutationCircuit::default(),
+                PERMUTATION_PROVING_KEY,
+                PERMUTATION_VERIFYING_KEY,
+            )
+        })?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        Ok(format!(
+            "Permutation circuit keys setup completed in {}ms",
+            elapsed_ms
+        ))
+    })
+}
+
+/// Prove that `values` contains `values[reveal]`
+/// `values` must hold `PERMUTATION_LEN` entries; `None` uses `DEFAULT_PERMUTATION_VALUES`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_permutation(
+    documents_path: String,
+    values: Option<Vec<u64>>,
+    reveal: u32,
+) -> Result<ProofResult, ZkProofError> {
+    let values: [u64; PERMUTATION_LEN] = match values {
+        Some(values) => values.try_into().map_err(|values: Vec<u64>| {
+            ZkProofError::invalid_input(format!(
+                "Permutation circuit takes {} values, got {}",
+                PERMUTATION_LEN,
+                values.len()
+            ))
+        })?,
+        None => DEFAULT_PERMUTATION_VALUES,
+    };
+    let reveal = reveal as usize;
+    if reveal >= PERMUTATION_LEN {
+        return Err(ZkProofError::invalid_input(format!(
+            "Reveal index {} is out of range for {} values",
+            reveal, PERMUTATION_LEN
+        )));
+    }
+    let circuit = PermutationCircuit::new(values, reveal);
+
+    with_working_dir(&documents_path, || {
+        if !Path::new(PERMUTATION_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "Permutation proving key not found; call setup_permutation_keys first",
+            ));
+        }
+        let run = RunLog::start("prove_permutation");
+
+        let start = std::time::Instant::now();
+        run.phase("prove", Some(PERMUTATION_PROOF), || {
+            prove_circuit::<E, _>(
+                circuit,
+                PERMUTATION_PROVING_KEY,
+                PERMUTATION_INSTANCE,
+                PERMUTATION_WITNESS,
+                PERMUTATION_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        Ok(ProofResult {
+            prep_ms: 0,
+            prove_ms: total_ms,
+            total_ms,
+            proof_size_bytes: get_proof_size(PERMUTATION_PROOF)?,
+            comm_w_shared: extract_comm_w_shared(PERMUTATION_INSTANCE)?,
+        })
+    })
+}
+
+/// Verify the permutation proof
+/// With `revealed`, the proof is also rejected unless it reveals that value.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_permutation(
+    documents_path: String,
+    revealed: Option<u64>,
+) -> Result<VerifyResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_permutation");
+        run.phase("verify", None, || {
+            verify_result_with(
+                PERMUTATION_PROOF,
+                PERMUTATION_VERIFYING_KEY,
+                |public_values| match revealed {
+                    Some(revealed) if public_values.first() != Some(&Scalar::from(revealed)) => {
+                        Err("Revealed value does not match the expected one".to_string())
+                    }
+                    _ => Ok(()),
+                },
+            )
+        })
+    })
+}
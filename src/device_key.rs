@@ -0,0 +1,88 @@
+//! Hardware-bound device keys for the Show keybinding.
+//!
+//! The Show circuit proves a device signature over the verifier's challenge. With
+//! a `DeviceSigner`, the platform signs inside Secure Enclave (iOS) or StrongBox
+//! (Android) and only the public key and signature cross into Rust, so the device
+//! private key never leaves the hardware.
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ecdsa_spartan2::signature::apply_device_signature;
+use serde_json::Value;
+
+use crate::{prove_show_in_cwd, with_working_dir, ProofResult, ZkProofError};
+
+/// Show input with the device signature filled in; removed after proving
+const SIGNED_SHOW_INPUT: &str = "keys/show_input_signed.json";
+
+/// Signs challenges with a P-256 key held in platform secure hardware
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait DeviceSigner: Send + Sync {
+    /// Device public key as a 65-byte uncompressed SEC1 point or 64-byte `X || Y`
+    fn public_key(&self) -> Result<Vec<u8>, ZkProofError>;
+    /// ECDSA-SHA256 signature over `challenge`, as DER or 64-byte `r || s`
+    fn sign(&self, challenge: Vec<u8>) -> Result<Vec<u8>, ZkProofError>;
+}
+
+/// Generate a Show proof whose device signature comes from `signer`
+/// The Show input supplies the claim and date; the keybinding fields are
+/// replaced with the signer's key and its signature over `challenge`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_show_with_device_key(
+    documents_path: String,
+    input_path: Option<String>,
+    challenge: Vec<u8>,
+    signer: Arc<dyn DeviceSigner>,
+) -> Result<ProofResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let mut show_input = load_show_input(input_path.map(PathBuf::from))?;
+
+        let pubkey = signer.public_key()?;
+        let signature = signer.sign(challenge.clone())?;
+        apply_device_signature(&mut show_input, &challenge, &signature, &pubkey)
+            .map_err(ZkProofError::invalid_input)?;
+
+        with_signed_show_input(&show_input, |path| prove_show_in_cwd(path, None))
+    })
+}
+
+/// Load the Show input JSON, resolving the default location like `ShowCircuit`
+pub(crate) fn load_show_input(input_path: Option<PathBuf>) -> Result<Value, ZkProofError> {
+    let input_path = input_path.unwrap_or_else(|| {
+        // Try mobile flat path first, fall back to development nested path
+        let mobile_path = PathBuf::from("show_input.json");
+        if mobile_path.exists() {
+            mobile_path
+        } else {
+            PathBuf::from("../circom/inputs/show/default.json")
+        }
+    });
+    let file = File::open(&input_path).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to open '{}': {}", input_path.display(), e))
+            .with_source(&e)
+    })?;
+    serde_json::from_reader(file).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to parse show input: {}", e)).with_source(&e)
+    })
+}
+
+/// Run `f` with `show_input` written to a temporary input file
+pub(crate) fn with_signed_show_input<T>(
+    show_input: &Value,
+    f: impl FnOnce(Option<PathBuf>) -> Result<T, ZkProofError>,
+) -> Result<T, ZkProofError> {
+    if let Some(parent) = Path::new(SIGNED_SHOW_INPUT).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    serde_json::to_writer(File::create(SIGNED_SHOW_INPUT)?, show_input).map_err(|e| {
+        ZkProofError::io_error(format!("Failed to write signed show input: {}", e)).with_source(&e)
+    })?;
+
+    let result = f(Some(PathBuf::from(SIGNED_SHOW_INPUT)));
+    let _ = fs::remove_file(SIGNED_SHOW_INPUT);
+    result
+}
@@ -0,0 +1,102 @@
+//! Standalone ECDSA (P-256) circuit over caller-supplied signatures.
+//!
+//! Unlike Prepare and Show, the circuit input is built in memory from the raw
+//! signature, message hash, and public key, so every call proves a fresh
+//! signature. Keys and proofs live under `keys/` like the other circuits.
+
+use ecdsa_spartan2::{
+    prover::{prove_circuit, verify_circuit},
+    setup::{ECDSA_INSTANCE, ECDSA_PROOF, ECDSA_PROVING_KEY, ECDSA_VERIFYING_KEY, ECDSA_WITNESS},
+    ECDSACircuit, E,
+};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    extract_comm_w_shared, get_proof_size, prove_failed, run_log::RunLog, setup_and_save_keys,
+    with_working_dir, ProofResult, ZkProofError,
+};
+
+/// Setup ECDSA circuit keys
+/// Generates proving and verifying keys for the ECDSA circuit
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_ecdsa_keys(
+    documents_path: String,
+    input_path: Option<String>,
+) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let circuit = ECDSACircuit::new(input_path.map(PathBuf::from));
+        let run = RunLog::start("setup_ecdsa_keys");
+
+        let start = std::time::Instant::now();
+        run.phase("setup", Some(ECDSA_PROVING_KEY), || {
+            setup_and_save_keys(circuit, ECDSA_PROVING_KEY, ECDSA_VERIFYING_KEY)
+        })?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        Ok(format!(
+            "ECDSA circuit keys setup completed in {}ms",
+            elapsed_ms
+        ))
+    })
+}
+
+/// Prove a P-256 signature with the ECDSA circuit
+/// `signature` is 64-byte `r || s` or DER, `msg_hash` the big-endian digest, and
+/// `pubkey` a 65-byte uncompressed SEC1 point or 64-byte `X || Y`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_ecdsa(
+    documents_path: String,
+    signature: Vec<u8>,
+    msg_hash: Vec<u8>,
+    pubkey: Vec<u8>,
+) -> Result<ProofResult, ZkProofError> {
+    let circuit = ECDSACircuit::from_signature(&signature, &msg_hash, &pubkey)
+        .map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        if !Path::new(ECDSA_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "ECDSA proving key not found; call setup_ecdsa_keys first".to_string(),
+            ));
+        }
+        let run = RunLog::start("prove_ecdsa");
+
+        let start = std::time::Instant::now();
+        run.phase("prove", Some(ECDSA_PROOF), || {
+            prove_circuit(
+                circuit,
+                ECDSA_PROVING_KEY,
+                ECDSA_INSTANCE,
+                ECDSA_WITNESS,
+                ECDSA_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        let proof_size_bytes = get_proof_size(ECDSA_PROOF)?;
+        let comm_w_shared = extract_comm_w_shared(ECDSA_INSTANCE)?;
+
+        Ok(ProofResult {
+            prep_ms: 0,
+            prove_ms: total_ms,
+            total_ms,
+            proof_size_bytes,
+            comm_w_shared,
+        })
+    })
+}
+
+/// Verify ECDSA circuit proof
+/// Verifies the proof using the verifying key
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_ecdsa(documents_path: String) -> Result<bool, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_ecdsa");
+        run.phase("verify", None, || {
+            verify_circuit::<E>(ECDSA_PROOF, ECDSA_VERIFYING_KEY);
+            Ok(())
+        })?;
+        Ok(true)
+    })
+}
@@ -0,0 +1,181 @@
+//! Proving-engine comparison benchmarks.
+//!
+//! The Prepare and Show circuits are compiled for T256's scalar field and only
+//! run on the crate's engine, so engines are compared on `SyntheticCircuit`
+//! instead: the same constraint count and shared-witness layout over each
+//! curve, isolating the cost of the field and group arithmetic.
+
+use std::time::Instant;
+
+#[cfg(feature = "pallas-hyrax")]
+use ecdsa_spartan2::PallasE;
+use ecdsa_spartan2::{
+    load_instance, load_proof, load_shared_blinds, load_witness,
+    prover::{
+        generate_shared_blinds, prove_circuit_with_pk, reblind_with_loaded_data,
+        verify_circuit_with_loaded_data,
+    },
+    save_keys,
+    setup::{
+        setup_circuit_keys_no_save, SYNTHETIC_INSTANCE, SYNTHETIC_PROOF, SYNTHETIC_PROVING_KEY,
+        SYNTHETIC_SHARED_BLINDS, SYNTHETIC_VERIFYING_KEY, SYNTHETIC_WITNESS,
+    },
+    spartan2::traits::Engine,
+    SyntheticCircuit, E,
+};
+
+use crate::{
+    get_proof_size, prove_failed, run_log::RunLog, with_working_dir, ZkProofError, NUM_SHARED,
+};
+
+/// Engine to benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum BenchmarkEngine {
+    /// The engine the credential circuits are proved with
+    Default,
+    /// PallasHyraxEngine; requires the `pallas-hyrax` feature
+    PallasHyrax,
+}
+
+/// Timings and sizes for one engine on the synthetic circuit
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct EngineBenchmarkResults {
+    pub engine: String,
+    pub num_constraints: u64,
+    // Timing metrics (milliseconds)
+    pub setup_ms: u64,
+    pub generate_blinds_ms: u64,
+    pub prove_ms: u64,
+    pub reblind_ms: u64,
+    pub verify_ms: u64,
+    // Size metrics (bytes)
+    pub proving_key_bytes: u64,
+    pub verifying_key_bytes: u64,
+    pub proof_bytes: u64,
+    pub witness_bytes: u64,
+}
+
+/// Run setup, prove, reblind and verify for `engine` over a synthetic circuit
+/// `num_constraints` defaults to `DEFAULT_SYNTHETIC_CONSTRAINTS`; use the same
+/// value across engines for a like-for-like comparison.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn run_complete_benchmark_engine(
+    documents_path: String,
+    engine: BenchmarkEngine,
+    num_constraints: Option<u64>,
+) -> Result<EngineBenchmarkResults, ZkProofError> {
+    let circuit = num_constraints.map_or_else(SyntheticCircuit::default, |n| {
+        SyntheticCircuit::new(n as usize)
+    });
+
+    with_working_dir(&documents_path, || match engine {
+        BenchmarkEngine::Default => benchmark_engine::<E>(circuit, std::any::type_name::<E>()),
+        #[cfg(feature = "pallas-hyrax")]
+        BenchmarkEngine::PallasHyrax => {
+            benchmark_engine::<PallasE>(circuit, std::any::type_name::<PallasE>())
+        }
+        #[cfg(not(feature = "pallas-hyrax"))]
+        BenchmarkEngine::PallasHyrax => Err(ZkProofError::invalid_input(
+            "PallasHyrax benchmark requires the `pallas-hyrax` feature".to_string(),
+        )),
+    })
+}
+
+fn benchmark_engine<EB: Engine>(
+    circuit: SyntheticCircuit,
+    engine_name: &str,
+) -> Result<EngineBenchmarkResults, ZkProofError> {
+    let run = RunLog::start("run_complete_benchmark_engine");
+
+    // Step 1: Setup
+    let start = Instant::now();
+    let (pk, vk) = run.phase("setup", None, || {
+        Ok(setup_circuit_keys_no_save::<EB, _>(circuit.clone()))
+    })?;
+    let setup_ms = start.elapsed().as_millis() as u64;
+
+    save_keys::<EB>(SYNTHETIC_PROVING_KEY, SYNTHETIC_VERIFYING_KEY, &pk, &vk).map_err(|e| {
+        ZkProofError::io_error(format!("Failed to save synthetic keys: {}", e)).with_source(&*e)
+    })?;
+
+    // Step 2: Generate Shared Blinds
+    let start = Instant::now();
+    run.phase("generate_blinds", Some(SYNTHETIC_SHARED_BLINDS), || {
+        generate_shared_blinds::<EB>(SYNTHETIC_SHARED_BLINDS, NUM_SHARED).map_err(prove_failed)
+    })?;
+    let generate_blinds_ms = start.elapsed().as_millis() as u64;
+
+    // Step 3: Prove
+    let start = Instant::now();
+    run.phase("prove", Some(SYNTHETIC_PROOF), || {
+        prove_circuit_with_pk::<EB, _>(
+            circuit.clone(),
+            &pk,
+            SYNTHETIC_INSTANCE,
+            SYNTHETIC_WITNESS,
+            SYNTHETIC_PROOF,
+        )
+        .map_err(prove_failed)
+    })?;
+    let prove_ms = start.elapsed().as_millis() as u64;
+
+    // Step 4: Reblind
+    // Load data before timing (file I/O should not be part of reblind benchmark)
+    let instance = load_instance::<EB>(SYNTHETIC_INSTANCE).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load synthetic instance: {}", e))
+            .with_source(&*e)
+    })?;
+    let witness = load_witness::<EB>(SYNTHETIC_WITNESS).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load synthetic witness: {}", e))
+            .with_source(&*e)
+    })?;
+    let shared_blinds = load_shared_blinds::<EB>(SYNTHETIC_SHARED_BLINDS).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load synthetic shared blinds: {}", e))
+            .with_source(&*e)
+    })?;
+
+    let start = Instant::now();
+    run.phase("reblind", Some(SYNTHETIC_PROOF), || {
+        reblind_with_loaded_data::<EB, _>(
+            circuit.clone(),
+            &pk,
+            instance,
+            witness,
+            &shared_blinds,
+            SYNTHETIC_INSTANCE,
+            SYNTHETIC_WITNESS,
+            SYNTHETIC_PROOF,
+        )
+        .map_err(prove_failed)
+    })?;
+    let reblind_ms = start.elapsed().as_millis() as u64;
+
+    // Step 5: Verify
+    // Load proof before timing (file I/O should not be part of verify benchmark)
+    let proof = load_proof::<EB>(SYNTHETIC_PROOF).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load synthetic proof: {}", e))
+            .with_source(&*e)
+    })?;
+
+    let start = Instant::now();
+    run.phase("verify", None, || {
+        verify_circuit_with_loaded_data::<EB>(&proof, &vk);
+        Ok(())
+    })?;
+    let verify_ms = start.elapsed().as_millis() as u64;
+
+    Ok(EngineBenchmarkResults {
+        engine: engine_name.to_string(),
+        num_constraints: circuit.num_constraints() as u64,
+        setup_ms,
+        generate_blinds_ms,
+        prove_ms,
+        reblind_ms,
+        verify_ms,
+        proving_key_bytes: get_proof_size(SYNTHETIC_PROVING_KEY)?,
+        verifying_key_bytes: get_proof_size(SYNTHETIC_VERIFYING_KEY)?,
+        proof_bytes: get_proof_size(SYNTHETIC_PROOF)?,
+        witness_bytes: get_proof_size(SYNTHETIC_WITNESS)?,
+    })
+}
@@ -0,0 +1,292 @@
+//! FFI error type with machine-readable codes and cause chains.
+//!
+//! Every `ZkProofError` variant carries a stable numeric `code` (see
+//! `error_code`) and the chain of underlying errors as `ErrorCause`s, outermost
+//! first, so host code can branch on the failure category (a missing file, a
+//! Spartan rejection) without parsing `message`.
+
+use std::error::Error;
+
+use bellpepper_core::SynthesisError;
+use ecdsa_spartan2::spartan2::errors::SpartanError;
+
+/// Stable values of `ZkProofError`'s `code`, one per variant
+pub mod error_code {
+    pub const FILE_NOT_FOUND: u32 = 1;
+    pub const PROOF_GENERATION_FAILED: u32 = 2;
+    pub const VERIFICATION_FAILED: u32 = 3;
+    pub const INVALID_INPUT: u32 = 4;
+    pub const SETUP_REQUIRED: u32 = 5;
+    pub const IO_ERROR: u32 = 6;
+    pub const TIMED_OUT: u32 = 7;
+}
+
+/// Library an underlying error came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ErrorOrigin {
+    Io,
+    Spartan,
+    Synthesis,
+    Json,
+    Other,
+}
+
+/// One link of an error's source chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ErrorCause {
+    pub origin: ErrorOrigin,
+    /// Kind within the origin, e.g. "NotFound" for IO or "InvalidSumcheckProof" for Spartan
+    pub kind: String,
+    pub message: String,
+}
+
+impl ErrorCause {
+    fn from_error(e: &(dyn Error + 'static)) -> Self {
+        let (origin, kind) = if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            (ErrorOrigin::Io, format!("{:?}", io.kind()))
+        } else if let Some(spartan) = e.downcast_ref::<SpartanError>() {
+            (ErrorOrigin::Spartan, variant_name(spartan))
+        } else if let Some(synthesis) = e.downcast_ref::<SynthesisError>() {
+            (ErrorOrigin::Synthesis, variant_name(synthesis))
+        } else if let Some(json) = e.downcast_ref::<serde_json::Error>() {
+            (ErrorOrigin::Json, format!("{:?}", json.classify()))
+        } else {
+            (ErrorOrigin::Other, String::new())
+        };
+        Self {
+            origin,
+            kind,
+            message: e.to_string(),
+        }
+    }
+}
+
+/// Name of an enum variant, from its `Debug` output
+fn variant_name(value: &dyn std::fmt::Debug) -> String {
+    format!("{:?}", value)
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Errors that can occur during ZK proof operations
+#[derive(Debug)]
+#[cfg_attr(feature = "uniffi", uniffi::error)]
+pub enum ZkProofError {
+    FileNotFound {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+    ProofGenerationFailed {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+    VerificationFailed {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+    InvalidInput {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+    SetupRequired {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+    IoError {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+    TimedOut {
+        code: u32,
+        message: String,
+        causes: Vec<ErrorCause>,
+    },
+}
+
+impl ZkProofError {
+    pub fn file_not_found(message: impl Into<String>) -> Self {
+        Self::FileNotFound {
+            code: error_code::FILE_NOT_FOUND,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn proof_generation_failed(message: impl Into<String>) -> Self {
+        Self::ProofGenerationFailed {
+            code: error_code::PROOF_GENERATION_FAILED,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn verification_failed(message: impl Into<String>) -> Self {
+        Self::VerificationFailed {
+            code: error_code::VERIFICATION_FAILED,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::InvalidInput {
+            code: error_code::INVALID_INPUT,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn setup_required(message: impl Into<String>) -> Self {
+        Self::SetupRequired {
+            code: error_code::SETUP_REQUIRED,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn io_error(message: impl Into<String>) -> Self {
+        Self::IoError {
+            code: error_code::IO_ERROR,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    pub fn timed_out(message: impl Into<String>) -> Self {
+        Self::TimedOut {
+            code: error_code::TIMED_OUT,
+            message: message.into(),
+            causes: Vec::new(),
+        }
+    }
+
+    /// Record `source` and everything it wraps as this error's causes
+    pub fn with_source(mut self, source: &(dyn Error + 'static)) -> Self {
+        let causes = self.causes_mut();
+        let mut next = Some(source);
+        while let Some(e) = next {
+            causes.push(ErrorCause::from_error(e));
+            next = e.source();
+        }
+        self
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::FileNotFound { code, .. }
+            | Self::ProofGenerationFailed { code, .. }
+            | Self::VerificationFailed { code, .. }
+            | Self::InvalidInput { code, .. }
+            | Self::SetupRequired { code, .. }
+            | Self::IoError { code, .. }
+            | Self::TimedOut { code, .. } => *code,
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::FileNotFound { message, .. }
+            | Self::ProofGenerationFailed { message, .. }
+            | Self::VerificationFailed { message, .. }
+            | Self::InvalidInput { message, .. }
+            | Self::SetupRequired { message, .. }
+            | Self::IoError { message, .. }
+            | Self::TimedOut { message, .. } => message,
+        }
+    }
+
+    pub fn causes(&self) -> &[ErrorCause] {
+        match self {
+            Self::FileNotFound { causes, .. }
+            | Self::ProofGenerationFailed { causes, .. }
+            | Self::VerificationFailed { causes, .. }
+            | Self::InvalidInput { causes, .. }
+            | Self::SetupRequired { causes, .. }
+            | Self::IoError { causes, .. }
+            | Self::TimedOut { causes, .. } => causes,
+        }
+    }
+
+    fn causes_mut(&mut self) -> &mut Vec<ErrorCause> {
+        match self {
+            Self::FileNotFound { causes, .. }
+            | Self::ProofGenerationFailed { causes, .. }
+            | Self::VerificationFailed { causes, .. }
+            | Self::InvalidInput { causes, .. }
+            | Self::SetupRequired { causes, .. }
+            | Self::IoError { causes, .. }
+            | Self::TimedOut { causes, .. } => causes,
+        }
+    }
+}
+
+impl std::fmt::Display for ZkProofError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = self.message();
+        match self {
+            ZkProofError::FileNotFound { .. } => write!(f, "File not found: {}", message),
+            ZkProofError::ProofGenerationFailed { .. } => {
+                write!(f, "Proof generation failed: {}", message)
+            }
+            ZkProofError::VerificationFailed { .. } => {
+                write!(f, "Verification failed: {}", message)
+            }
+            ZkProofError::InvalidInput { .. } => write!(f, "Invalid input: {}", message),
+            ZkProofError::SetupRequired { .. } => write!(f, "Setup required: {}", message),
+            ZkProofError::IoError { .. } => write!(f, "IO error: {}", message),
+            ZkProofError::TimedOut { .. } => write!(f, "Timed out: {}", message),
+        }
+    }
+}
+
+impl Error for ZkProofError {}
+
+impl From<std::io::Error> for ZkProofError {
+    fn from(e: std::io::Error) -> Self {
+        ZkProofError::io_error(e.to_string()).with_source(&e)
+    }
+}
+
+/// Raised when a foreign callback (device signer, key wrapper) fails unexpectedly
+#[cfg(feature = "uniffi")]
+impl From<uniffi::UnexpectedUniFFICallbackError> for ZkProofError {
+    fn from(e: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        ZkProofError::invalid_input(format!("Host callback failed: {}", e.reason))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_chain_is_preserved() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "keys/prepare.pk");
+        let err = ZkProofError::setup_required("Prepare keys missing").with_source(&io);
+
+        assert_eq!(err.code(), error_code::SETUP_REQUIRED);
+        assert_eq!(err.causes().len(), 1);
+        assert_eq!(err.causes()[0].origin, ErrorOrigin::Io);
+        assert_eq!(err.causes()[0].kind, "NotFound");
+        assert_eq!(err.to_string(), "Setup required: Prepare keys missing");
+    }
+
+    #[test]
+    fn test_variant_name() {
+        assert_eq!(
+            variant_name(&SynthesisError::AssignmentMissing),
+            "AssignmentMissing"
+        );
+        assert_eq!(variant_name(&Some(1)), "Some");
+    }
+}
@@ -0,0 +1,327 @@
+//! Loaded keys held across calls as opaque handles.
+//!
+//! `prove_prepare`, `verify_show` and the other path-based functions
+//! deserialize their key from `keys/` on every call, which for the Prepare
+//! proving key takes seconds on a phone. `load_proving_key` and
+//! `load_verifying_key` load the stored key of a circuit once and return a
+//! handle; the `*_with_key` functions prove, reblind and verify with it instead.
+//! Proofs, instances and witnesses are still read and written in the documents
+//! directory as by the path-based functions. A handle keeps the key it was
+//! loaded with: after new setup, load it again.
+//!
+//! `preload_keys_async` loads proving keys on a background thread and hands
+//! them to a callback, so an app can overlap loading with onboarding UI. It
+//! holds the operation lock while loading, like every other operation, so calls
+//! made in the meantime wait for it.
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::Instant,
+};
+
+use ecdsa_spartan2::{
+    jwt_variant, load_proving_key as load_stored_proving_key,
+    load_verifying_key as load_stored_verifying_key,
+    setup::{
+        PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY,
+        PREPARE_WITNESS, SHOW_INSTANCE, SHOW_PROOF, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
+        SHOW_WITNESS,
+    },
+    CircuitKind, JwtVariant, PrepareCircuit, ShowCircuit, E,
+};
+
+use crate::{
+    memory_keys::{
+        check_circuit, check_proving_key, prove_with_pk, reblind_with_pk, verify_with_vk,
+    },
+    run_log::panic_message,
+    with_working_dir, ProofResult, VerifyResult, ZkProofError,
+};
+
+/// Proving key of one circuit, loaded once
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct ProvingKey {
+    circuit: CircuitKind,
+    /// JWT circuit selected when loaded; only meaningful for Prepare
+    variant: JwtVariant,
+    key: ecdsa_spartan2::ProvingKey,
+}
+
+/// Verifying key of one circuit, loaded once
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct VerifyingKey {
+    circuit: CircuitKind,
+    key: ecdsa_spartan2::VerifyingKey,
+}
+
+/// Receives the outcome of `preload_keys_async`, on the loading thread
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait KeysReadyCallback: Send + Sync {
+    /// Every requested key loaded, in the order requested
+    fn on_ready(&self, keys: Vec<Arc<ProvingKey>>, load_ms: u64);
+    /// Loading failed; `code` is one of `error_code`
+    fn on_error(&self, code: u32, message: String);
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+impl ProvingKey {
+    /// "prepare" or "show"
+    pub fn circuit(&self) -> String {
+        self.circuit.name().to_string()
+    }
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+impl VerifyingKey {
+    /// "prepare" or "show"
+    pub fn circuit(&self) -> String {
+        self.circuit.name().to_string()
+    }
+}
+
+/// Load the stored proving key of `circuit` ("prepare" or "show")
+/// Reads it from `documents_path` as `prove_prepare` and `prove_show` do, for
+/// the current artifact namespace and, for Prepare, the selected JWT variant.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn load_proving_key(
+    documents_path: String,
+    circuit: String,
+) -> Result<Arc<ProvingKey>, ZkProofError> {
+    let circuit: CircuitKind = circuit.parse().map_err(ZkProofError::invalid_input)?;
+    let pk_path = match circuit {
+        CircuitKind::Prepare => PREPARE_PROVING_KEY,
+        CircuitKind::Show => SHOW_PROVING_KEY,
+    };
+
+    with_working_dir(&documents_path, || {
+        let variant = jwt_variant();
+        let key = load_stored_proving_key::<E>(pk_path).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load proving key from '{}': {}",
+                pk_path, e
+            ))
+            .with_source(&*e)
+        })?;
+        Ok(Arc::new(ProvingKey {
+            circuit,
+            variant,
+            key,
+        }))
+    })
+}
+
+/// Load the proving keys of `circuits` in the background, then call `callback`
+/// Returns once loading has started; errors only for an unknown circuit name or
+/// if the loading thread cannot be started. Loading failures, panics included,
+/// go to `callback.on_error`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn preload_keys_async(
+    documents_path: String,
+    circuits: Vec<String>,
+    callback: Arc<dyn KeysReadyCallback>,
+) -> Result<(), ZkProofError> {
+    for circuit in &circuits {
+        circuit
+            .parse::<CircuitKind>()
+            .map_err(ZkProofError::invalid_input)?;
+    }
+
+    thread::Builder::new()
+        .name("zkproof-preload".to_string())
+        .spawn(move || {
+            let start = Instant::now();
+            let loaded = catch_unwind(AssertUnwindSafe(|| {
+                circuits
+                    .into_iter()
+                    .map(|circuit| load_proving_key(documents_path.clone(), circuit))
+                    .collect::<Result<Vec<_>, _>>()
+            }))
+            .unwrap_or_else(|panic| {
+                Err(ZkProofError::io_error(format!(
+                    "Key preloading panicked: {}",
+                    panic_message(panic.as_ref())
+                )))
+            });
+            match loaded {
+                Ok(keys) => callback.on_ready(keys, start.elapsed().as_millis() as u64),
+                Err(e) => callback.on_error(e.code(), e.to_string()),
+            }
+        })
+        .map_err(|e| {
+            ZkProofError::io_error(format!("Failed to start key preloading: {}", e)).with_source(&e)
+        })?;
+    Ok(())
+}
+
+/// Load the stored verifying key of `circuit` ("prepare" or "show")
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn load_verifying_key(
+    documents_path: String,
+    circuit: String,
+) -> Result<Arc<VerifyingKey>, ZkProofError> {
+    let circuit: CircuitKind = circuit.parse().map_err(ZkProofError::invalid_input)?;
+    let vk_path = match circuit {
+        CircuitKind::Prepare => PREPARE_VERIFYING_KEY,
+        CircuitKind::Show => SHOW_VERIFYING_KEY,
+    };
+
+    with_working_dir(&documents_path, || {
+        let key = load_stored_verifying_key::<E>(vk_path).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load verifying key from '{}': {}",
+                vk_path, e
+            ))
+            .with_source(&*e)
+        })?;
+        Ok(Arc::new(VerifyingKey { circuit, key }))
+    })
+}
+
+/// `prove_prepare` with a loaded Prepare proving key
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_prepare_with_key(
+    documents_path: String,
+    key: Arc<ProvingKey>,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    check_proving_key(CircuitKind::Prepare, key.circuit, key.variant)?;
+    with_working_dir(&documents_path, || {
+        prove_with_pk(
+            "prove_prepare_with_key",
+            PrepareCircuit::new(input_path.map(PathBuf::from)),
+            &key.key,
+            [PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF],
+            max_duration_ms,
+        )
+    })
+}
+
+/// `prove_show` with a loaded Show proving key
+/// As with `prove_show_with_keys`, the presentation policy is not applied.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_show_with_key(
+    documents_path: String,
+    key: Arc<ProvingKey>,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    check_proving_key(CircuitKind::Show, key.circuit, key.variant)?;
+    with_working_dir(&documents_path, || {
+        prove_with_pk(
+            "prove_show_with_key",
+            ShowCircuit::new(input_path.map(PathBuf::from)),
+            &key.key,
+            [SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF],
+            max_duration_ms,
+        )
+    })
+}
+
+/// `reblind_prepare` with a loaded Prepare proving key
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_prepare_with_key(
+    documents_path: String,
+    key: Arc<ProvingKey>,
+) -> Result<ProofResult, ZkProofError> {
+    check_proving_key(CircuitKind::Prepare, key.circuit, key.variant)?;
+    with_working_dir(&documents_path, || {
+        reblind_with_pk(
+            "reblind_prepare_with_key",
+            PrepareCircuit::default(),
+            &key.key,
+            [PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF],
+        )
+    })
+}
+
+/// `reblind_show` with a loaded Show proving key
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_show_with_key(
+    documents_path: String,
+    key: Arc<ProvingKey>,
+) -> Result<ProofResult, ZkProofError> {
+    check_proving_key(CircuitKind::Show, key.circuit, key.variant)?;
+    with_working_dir(&documents_path, || {
+        reblind_with_pk(
+            "reblind_show_with_key",
+            ShowCircuit::default(),
+            &key.key,
+            [SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF],
+        )
+    })
+}
+
+/// `verify_prepare` with a loaded Prepare verifying key
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_prepare_with_key(
+    documents_path: String,
+    key: Arc<VerifyingKey>,
+) -> Result<VerifyResult, ZkProofError> {
+    check_circuit(CircuitKind::Prepare, key.circuit)?;
+    verify_with_vk(
+        "verify_prepare_with_key",
+        documents_path,
+        &key.key,
+        CircuitKind::Prepare,
+        PREPARE_PROOF,
+    )
+}
+
+/// `verify_show` with a loaded Show verifying key
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_show_with_key(
+    documents_path: String,
+    key: Arc<VerifyingKey>,
+) -> Result<VerifyResult, ZkProofError> {
+    check_circuit(CircuitKind::Show, key.circuit)?;
+    verify_with_vk(
+        "verify_show_with_key",
+        documents_path,
+        &key.key,
+        CircuitKind::Show,
+        SHOW_PROOF,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_code;
+    use std::sync::{
+        mpsc::{self, Sender},
+        Mutex,
+    };
+
+    struct Outcome(Mutex<Sender<Result<usize, u32>>>);
+
+    impl KeysReadyCallback for Outcome {
+        fn on_ready(&self, keys: Vec<Arc<ProvingKey>>, _load_ms: u64) {
+            self.0.lock().unwrap().send(Ok(keys.len())).unwrap();
+        }
+
+        fn on_error(&self, code: u32, _message: String) {
+            self.0.lock().unwrap().send(Err(code)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_preload_reports_missing_keys_to_callback() {
+        let (sender, receiver) = mpsc::channel();
+        let callback = Arc::new(Outcome(Mutex::new(sender)));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().into_owned();
+
+        let unknown = preload_keys_async(path.clone(), vec!["nope".to_string()], callback.clone());
+        assert!(unknown.is_err());
+
+        preload_keys_async(path.clone(), vec!["show".to_string()], callback.clone()).unwrap();
+        assert_eq!(receiver.recv().unwrap(), Err(error_code::SETUP_REQUIRED));
+
+        preload_keys_async(path, Vec::new(), callback).unwrap();
+        assert_eq!(receiver.recv().unwrap(), Ok(0));
+    }
+}
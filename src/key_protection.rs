@@ -0,0 +1,84 @@
+//! Platform-key protection for linkage secrets at rest.
+//!
+//! Shared blinds and witnesses link a holder's presentations, so anyone who
+//! extracts the app sandbox could otherwise de-anonymize them. With protection
+//! enabled they are sealed under a random data key; only a wrapped copy of that
+//! key is stored, and wrapping is done by the host with an Android Keystore or
+//! iOS Keychain key that never leaves the platform.
+
+use std::{fs, path::Path, sync::Arc};
+
+use ecdsa_spartan2::{
+    artifact_path,
+    sealed::{self, generate_key, is_sealed, set_sealing_key, KEY_LEN},
+    setup::{PREPARE_WITNESS, SHARED_BLINDS, SHOW_WITNESS},
+};
+
+use crate::{with_working_dir, ZkProofError};
+
+/// The data key, wrapped by the host's platform key
+const WRAPPED_KEY: &str = "keys/artifact_key.wrapped";
+
+/// Files holding linkage secrets, sealed when protection is enabled
+const PROTECTED_FILES: [&str; 3] = [SHARED_BLINDS, PREPARE_WITNESS, SHOW_WITNESS];
+
+/// Wraps and unwraps the data key with a hardware-backed platform key
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait KeyWrapper: Send + Sync {
+    fn wrap_key(&self, key: Vec<u8>) -> Result<Vec<u8>, ZkProofError>;
+    fn unwrap_key(&self, wrapped: Vec<u8>) -> Result<Vec<u8>, ZkProofError>;
+}
+
+/// Seal shared blinds and witnesses under a data key protected by `wrapper`
+/// Creates and wraps the data key on first use, then seals any existing plaintext
+/// secrets. Must be called again after each app launch before proving or reblinding.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn enable_artifact_protection(
+    documents_path: String,
+    wrapper: Arc<dyn KeyWrapper>,
+) -> Result<(), ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let key = if Path::new(WRAPPED_KEY).exists() {
+            let unwrapped = wrapper.unwrap_key(fs::read(WRAPPED_KEY)?)?;
+            <[u8; KEY_LEN]>::try_from(unwrapped.as_slice()).map_err(|_| {
+                ZkProofError::invalid_input(format!(
+                    "Unwrapped data key must be {} bytes, got {}",
+                    KEY_LEN,
+                    unwrapped.len()
+                ))
+            })?
+        } else {
+            let key = generate_key();
+            let wrapped = wrapper.wrap_key(key.to_vec())?;
+            if let Some(parent) = Path::new(WRAPPED_KEY).parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(WRAPPED_KEY, wrapped)?;
+            key
+        };
+        set_sealing_key(Some(key));
+
+        for path in PROTECTED_FILES {
+            let path = &artifact_path(path);
+            if !Path::new(path).exists() {
+                continue;
+            }
+            let bytes = fs::read(path)?;
+            if !is_sealed(&bytes) {
+                let sealed = sealed::seal_if_enabled(bytes).map_err(|e| {
+                    ZkProofError::io_error(format!("Failed to seal '{}': {}", path, e))
+                        .with_source(&*e)
+                })?;
+                fs::write(path, sealed)?;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+/// Forget the data key; sealed files stay sealed until protection is re-enabled
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn disable_artifact_protection() {
+    set_sealing_key(None);
+}
@@ -1,22 +1,187 @@
 use ecdsa_spartan2::{
-    load_instance, load_proof, load_shared_blinds, load_witness,
+    artifact_path, build_info,
+    domain::{check_domain, set_domain_separator as set_domain},
+    export_verifier_spec as describe_verification, export_witness_debug as write_witness_dump,
+    format, inspect_proof_bytes,
+    key_strip::{compact_size, strip_proving_key},
+    layout::{artifact_layout, assets_dir, set_artifact_layout},
+    load_instance, load_proof, load_proving_key as load_spartan_proving_key, load_shared_blinds,
+    load_verifying_key as load_spartan_verifying_key, load_witness,
+    namespace::artifact_namespace,
     prover::{
-        generate_shared_blinds as gen_shared_blinds, prove_circuit, prove_circuit_with_pk,
-        reblind, reblind_with_loaded_data, verify_circuit, verify_circuit_with_loaded_data,
+        generate_shared_blinds_with_rng, prove_circuit_until, prove_circuit_with_pk, reblind,
+        reblind_with_loaded_data, verify_circuit_with_loaded_data,
     },
-    save_keys,
+    save_keys, save_keys_timed,
     setup::{
-        setup_circuit_keys, setup_circuit_keys_no_save, PREPARE_INSTANCE, PREPARE_PROOF,
-        PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY, PREPARE_WITNESS, SHARED_BLINDS,
-        SHOW_INSTANCE, SHOW_PROOF, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, SHOW_WITNESS,
+        load_keys, PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY,
+        PREPARE_WITNESS, SHARED_BLINDS, SHOW_INSTANCE, SHOW_PROOF, SHOW_PROVING_KEY,
+        SHOW_VERIFYING_KEY, SHOW_WITNESS,
     },
-    PrepareCircuit, ShowCircuit, E,
+    setup_circuit_keys_timed,
+    spartan2::{
+        r1cs::{R1CSWitness, SplitR1CSInstance},
+        traits::{circuit::SpartanCircuit, snark::R1CSSNARKTrait},
+    },
+    take_constraint_failure, ArtifactLayout as SpartanArtifactLayout, ArtifactNamespace,
+    CircuitKind, DumpFormat, JwtVariant, PrepareCircuit, PreparePublicValues, ProofValidity,
+    ProveError, ProvingKey as SpartanProvingKey, Scalar, SetupTimings, ShowCircuit,
+    VerifyingKey as SpartanVerifyingKey, E,
+};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use std::{
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+mod app_group;
+mod asset_source;
+mod async_ops;
+mod background_prove;
+#[cfg(feature = "benchmark-upload")]
+mod benchmark_checkpoint;
+mod benchmark_upload;
+mod blind_rotation;
+mod claims;
+mod credential;
+mod credential_transfer;
+mod demo;
+mod device_key;
+#[cfg(feature = "ecdsa")]
+mod ecdsa;
+mod engine_benchmark;
+mod error;
+mod key_handles;
+mod key_protection;
+mod logging;
+mod mdoc;
+#[cfg(feature = "membership")]
+mod membership;
+mod memory_keys;
+mod presentation;
+mod presentation_flow;
+mod prove_options;
+#[cfg(feature = "pseudonym")]
+mod pseudonym;
+mod public_inputs;
+#[cfg(feature = "range")]
+mod range;
+mod rng;
+mod run_log;
+mod self_test;
+mod session;
+#[cfg(feature = "stress-test")]
+mod stress;
+mod transport;
+mod unlinkability;
+mod verifier_cache;
+mod verifier_limits;
+mod wallet;
+mod witness_source;
+
+pub use app_group::open_app_group_store;
+use app_group::StoreLock;
+pub use asset_source::{set_asset_source, AssetReader};
+pub use async_ops::{
+    complete_presentation_async, import_credential_async, prove_both_parallel_async,
+    prove_prepare_async, prove_show_async, reblind_all_async, run_complete_benchmark_async,
+    setup_prepare_keys_async, setup_show_keys_async, verify_prepare_async,
+    verify_presentation_async, verify_show_async,
+};
+pub use background_prove::{
+    cancel_background_prove, resume_background_prove, start_background_prove, BackgroundProveJob,
+    BackgroundProveStage,
+};
+use benchmark_checkpoint::Checkpoint;
+#[cfg(feature = "benchmark-upload")]
+pub use benchmark_upload::{
+    flush_benchmark_uploads, upload_benchmark, UploadOutcome, BENCHMARK_UPLOAD_QUEUE,
+};
+pub use blind_rotation::{rotate_shared_blinds, BlindRotation};
+pub use claims::{commit_claims, hash_claims};
+pub use credential::{
+    import_credential, import_credential_with_issuer_keys, CredentialReceipt, IssuerPublicKey,
+};
+pub use credential_transfer::{export_credential, import_credential_package};
+pub use demo::{
+    prove_demo, prove_permutation, reblind_demo, setup_demo_keys, setup_permutation_keys,
+    verify_demo, verify_permutation,
+};
+pub use device_key::{prove_show_with_device_key, DeviceSigner};
+#[cfg(feature = "ecdsa")]
+pub use ecdsa::{prove_ecdsa, setup_ecdsa_keys, verify_ecdsa};
+pub use engine_benchmark::{
+    run_complete_benchmark_engine, BenchmarkEngine, EngineBenchmarkResults,
+};
+pub use error::{error_code, ErrorCause, ErrorOrigin, ZkProofError};
+pub use key_handles::{
+    load_proving_key, load_verifying_key, preload_keys_async, prove_prepare_with_key,
+    prove_show_with_key, reblind_prepare_with_key, reblind_show_with_key, verify_prepare_with_key,
+    verify_show_with_key, KeysReadyCallback, ProvingKey, VerifyingKey,
+};
+pub use key_protection::{disable_artifact_protection, enable_artifact_protection, KeyWrapper};
+pub use logging::{init_logging, set_log_level, LogCallback};
+pub use mdoc::{
+    encode_device_response, MdocDataElement, MdocElementValue, ZK_PROOF_EXTENSION_VERSION,
+};
+#[cfg(feature = "membership")]
+pub use membership::{
+    membership_root, prove_claim_membership, setup_membership_keys, verify_claim_membership,
+};
+pub use memory_keys::{
+    prove_prepare_with_keys, prove_show_with_keys, reblind_prepare_with_keys,
+    reblind_show_with_keys, setup_keys_in_memory, verify_prepare_with_keys,
+    verify_presentation_with_keys, verify_show_with_keys, CircuitKeys,
+};
+pub use presentation::{PresentationPolicy, ReblindPolicy};
+pub use presentation_flow::{
+    complete_presentation, complete_presentation_with_shows, set_show_predicate,
+    start_presentation, verify_presentation, Challenge, PresentationBundle,
+    PresentationVerification, ShowPresentation, ShowRequest,
+};
+pub use prove_options::{get_prove_options, set_prove_options, ProgressGranularity, ProveOptions};
+use prove_options::with_prove_options;
+#[cfg(feature = "pseudonym")]
+pub use pseudonym::{
+    prove_pseudonym, setup_pseudonym_keys, verify_pseudonym, PseudonymProof, PseudonymVerification,
+};
+pub use public_inputs::verify_with_public_inputs;
+#[cfg(feature = "range")]
+pub use range::{prove_claim_range, setup_range_keys, verify_claim_range};
+pub use rng::RngSource;
+use presentation::PresentationState;
+pub use run_log::{get_last_run_report, set_run_log_enabled, PhaseRecord, RunReport};
+use run_log::RunLog;
+pub use self_test::{self_test, SelfTestReport, SELF_TEST_CONSTRAINTS};
+pub use session::{create_session, SessionConfig, ZkSession};
+#[cfg(feature = "stress-test")]
+pub use stress::{stress_test_concurrent, StressTestReport};
+pub use transport::{
+    chunk_presentation, decode_presentation, encode_presentation, reassemble_presentation,
+    CHUNK_HEADER_BYTES,
+};
+pub use unlinkability::{check_unlinkability, UnlinkabilityReport};
+pub use verifier_cache::{VerifierCache, VerifierCacheStats};
+pub use verifier_limits::{get_verifier_limits, set_verifier_limits, VerifierLimits};
+pub use wallet::{WalletConfig, ZkWallet};
+pub use witness_source::{
+    check_witness_file, set_witness_source, use_witness_helper_process, WitnessSource,
 };
-use std::path::PathBuf;
 
 // Initializes the shared UniFFI scaffolding and defines the `MoproError` enum.
 mopro_ffi::app!();
 
+#[cfg(feature = "mem-profile")]
+#[global_allocator]
+static ALLOC: ecdsa_spartan2::mem_profile::Alloc = ecdsa_spartan2::mem_profile::Alloc;
+
+// Note: While circuits have 98 shared values (2 keybindings + 96 claim scalars),
+// Hyrax batches all these into a single commitment point.
+// num_shared_rows() returns the number of Hyrax commitment points, not individual scalars.
+const NUM_SHARED: usize = 1;
+
 // ============================================================================
 // Core Types
 // ============================================================================
@@ -32,6 +197,7 @@ pub struct ProofResult {
 }
 
 /// Result of a complete benchmark run with timing and size metrics
+#[derive(Serialize)]
 #[cfg_attr(feature = "uniffi", uniffi::record)]
 pub struct BenchmarkResults {
     // Timing metrics (milliseconds)
@@ -44,6 +210,24 @@ pub struct BenchmarkResults {
     pub reblind_show_ms: u64,
     pub verify_prepare_ms: u64,
     pub verify_show_ms: u64,
+    // Reblind breakdown (milliseconds): applying the shared blinds and
+    // recomputing the commitments, then regenerating the evaluation proof
+    pub reblind_prepare_recommit_ms: u64,
+    pub reblind_prepare_prove_ms: u64,
+    pub reblind_show_recommit_ms: u64,
+    pub reblind_show_prove_ms: u64,
+    // Setup breakdown (milliseconds): synthesizing the circuit's shape and
+    // generating the keys make up the setup time; saving the keys and loading
+    // them back are timed on their own. Synthesis and key generation are 0
+    // when a resumed run reused the keys
+    pub prepare_setup_synthesis_ms: u64,
+    pub prepare_setup_keygen_ms: u64,
+    pub prepare_key_save_ms: u64,
+    pub prepare_key_load_ms: u64,
+    pub show_setup_synthesis_ms: u64,
+    pub show_setup_keygen_ms: u64,
+    pub show_key_save_ms: u64,
+    pub show_key_load_ms: u64,
     // Size metrics (bytes)
     pub prepare_proving_key_bytes: u64,
     pub prepare_verifying_key_bytes: u64,
@@ -53,6 +237,163 @@ pub struct BenchmarkResults {
     pub show_proof_bytes: u64,
     pub prepare_witness_bytes: u64,
     pub show_witness_bytes: u64,
+    /// Proving key sizes in the compact encoding of `strip_proving_key_for_mobile`
+    pub prepare_proving_key_stripped_bytes: u64,
+    pub show_proving_key_stripped_bytes: u64,
+    /// Outcome of every step in pipeline order; the metrics of steps that
+    /// neither succeeded nor were reused, and sizes of artifacts they did not
+    /// write, are 0
+    pub steps: Vec<BenchmarkStep>,
+    /// One line per failed step; `None` when every step succeeded
+    pub error_summary: Option<String>,
+}
+
+/// How a benchmark step ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum BenchmarkStepStatus {
+    Succeeded,
+    Failed,
+    /// Not run because a step it depends on did not succeed
+    Skipped,
+    /// Not run because a resumed run found its artifacts from an earlier run
+    /// intact; the duration is the one measured then
+    Reused,
+}
+
+/// Outcome of one step of `run_complete_benchmark`
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct BenchmarkStep {
+    pub name: String,
+    pub status: BenchmarkStepStatus,
+    /// Time spent in the measured part of the step, also when it failed
+    pub duration_ms: u64,
+    /// Error or panic message of a failed step
+    pub error: Option<String>,
+}
+
+/// Result of reblinding both circuits under fresh shared blinds
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ReblindAllResult {
+    pub prepare: ProofResult,
+    pub show: ProofResult,
+    /// Shared witness commitment common to both reblinded proofs
+    pub comm_w_shared: String,
+}
+
+/// A Show proof ready to be presented, after applying the presentation policy
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PresentationResult {
+    pub show: ProofResult,
+    /// Whether fresh blinds were sampled for this presentation
+    pub reblinded: bool,
+    /// Presentations made under the current shared blinds, including this one
+    pub presentations_with_current_blinds: u64,
+}
+
+/// Result of proving Prepare and Show concurrently
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ParallelProveResult {
+    pub prepare: ProofResult,
+    pub show: ProofResult,
+    /// Wall-clock time for both circuits proved one after the other
+    pub serial_wall_clock_ms: u64,
+    /// Wall-clock time for both circuits proved concurrently
+    pub wall_clock_ms: u64,
+    /// Serial wall-clock time divided by concurrent wall-clock time
+    pub speedup: f64,
+}
+
+/// Issued-at and expiry of a Prepare proof, as claimed by the prover
+/// They are public values of the proof but not constrained by the circuit, so
+/// they cannot be changed after proving but say nothing the prover could not
+/// have made up.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ProofFreshness {
+    pub issued_at_unix: u64,
+    pub expires_at_unix: u64,
+}
+
+impl From<ProofValidity> for ProofFreshness {
+    fn from(v: ProofValidity) -> Self {
+        ProofFreshness {
+            issued_at_unix: v.issued_at,
+            expires_at_unix: v.expires_at,
+        }
+    }
+}
+
+/// An artifact a migration could not carry over
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct RegenerationNeeded {
+    pub path: String,
+    pub reason: String,
+}
+
+/// Outcome of `migrate_artifacts_between`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct MigrationResult {
+    pub rewritten: Vec<String>,
+    /// Keys to set up again, or proofs and their state to prove again
+    pub regenerate: Vec<RegenerationNeeded>,
+}
+
+/// Outcome of `strip_proving_key_for_mobile`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct KeyStripResult {
+    /// Size in the fixed-width encoding `setup_*_keys` writes
+    pub full_bytes: u64,
+    /// Size of the key as now stored
+    pub stripped_bytes: u64,
+}
+
+/// Directories a benchmark reads shipped artifacts from and writes to
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ArtifactLayout {
+    /// The `circom` directory with the compiled circuits and any bundled
+    /// `keys/`; may be read-only, such as inside an app bundle
+    pub assets_path: String,
+    /// Writable directory for keys, proofs, witnesses and logs
+    pub documents_path: String,
+}
+
+/// Digest of the constraint system a circuit was compiled from
+#[derive(Serialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct CircuitDigest {
+    pub circuit: String,
+    /// Hex SHA-256 of the circuit's `.r1cs`, or "unknown"
+    pub r1cs_sha256: String,
+}
+
+/// What this build of the library was compiled from
+#[derive(Serialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct VersionInfo {
+    pub crate_version: String,
+    /// Commit of the spartan2 prover, or "unknown"
+    pub spartan2_rev: String,
+    pub circuit_digests: Vec<CircuitDigest>,
+    pub key_format_version: u32,
+    /// Format of stored proofs, instances, witnesses and shared blinds
+    pub proof_format_version: u32,
+}
+
+/// Outcome of verifying a single proof
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct VerifyResult {
+    pub valid: bool,
+    pub verify_ms: u64,
+    /// Why the proof was rejected; `None` when `valid`
+    pub failure_reason: Option<String>,
+    /// Shared witness commitment carried by the proof
+    pub comm_w_shared: String,
 }
 
 impl BenchmarkResults {
@@ -68,58 +409,151 @@ impl BenchmarkResults {
     }
 }
 
-/// Errors that can occur during ZK proof operations
-#[derive(Debug)]
-#[cfg_attr(feature = "uniffi", uniffi::error)]
-pub enum ZkProofError {
-    FileNotFound { message: String },
-    ProofGenerationFailed { message: String },
-    VerificationFailed { message: String },
-    InvalidInput { message: String },
-    SetupRequired { message: String },
-    IoError { message: String },
-}
-
-impl std::fmt::Display for ZkProofError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ZkProofError::FileNotFound { message } => write!(f, "File not found: {}", message),
-            ZkProofError::ProofGenerationFailed { message } => {
-                write!(f, "Proof generation failed: {}", message)
-            }
-            ZkProofError::VerificationFailed { message } => {
-                write!(f, "Verification failed: {}", message)
-            }
-            ZkProofError::InvalidInput { message } => write!(f, "Invalid input: {}", message),
-            ZkProofError::SetupRequired { message } => write!(f, "Setup required: {}", message),
-            ZkProofError::IoError { message } => write!(f, "IO error: {}", message),
-        }
-    }
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Current time in Unix seconds
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
 }
 
-impl std::error::Error for ZkProofError {}
+/// Validity window bound into the stored Prepare proof
+/// Read from the public values the Prepare instance was proved with, not from
+/// the credential input, which may have changed since.
+fn prepare_validity_in_cwd() -> Result<ProofValidity, ZkProofError> {
+    use ecdsa_spartan2::setup::load_public_instance;
 
-impl From<std::io::Error> for ZkProofError {
-    fn from(e: std::io::Error) -> Self {
-        ZkProofError::IoError {
-            message: e.to_string(),
-        }
-    }
+    let public = load_public_instance::<E>(PREPARE_INSTANCE).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load Prepare instance: {}", e))
+            .with_source(&*e)
+    })?;
+    check_domain(CircuitKind::Prepare, &public.public_values)
+        .and_then(PreparePublicValues::from_public_values)
+        .map(|public| public.validity)
+        .map_err(ZkProofError::invalid_input)
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
+/// Verify a proof from the current working directory under the configured domain
+/// Returns the public values after the domain tag
+fn verify_in_cwd(
+    circuit: CircuitKind,
+    proof_path: &str,
+    vk_path: &str,
+) -> Result<Vec<Scalar>, ZkProofError> {
+    let proof = load_proof::<E>(proof_path).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load proof from '{}': {}", proof_path, e))
+            .with_source(&*e)
+    })?;
+    let vk = load_spartan_verifying_key::<E>(vk_path).map_err(|e| {
+        ZkProofError::setup_required(format!(
+            "Failed to load verifying key from '{}': {}",
+            vk_path, e
+        ))
+        .with_source(&*e)
+    })?;
+
+    let public_values = proof
+        .verify(&vk)
+        .map_err(|e| ZkProofError::verification_failed(format!("{:?}", e)).with_source(&e))?;
+    check_domain(circuit, &public_values)
+        .map(<[Scalar]>::to_vec)
+        .map_err(ZkProofError::verification_failed)
+}
+
+/// Verify a proof in the current directory, reporting rejection as data
+/// Failing verification or the domain check, or a panic inside the verifier,
+/// yields `valid: false` with the reason instead of an error.
+fn verify_result_in_cwd(
+    circuit: CircuitKind,
+    proof_path: &str,
+    vk_path: &str,
+) -> Result<VerifyResult, ZkProofError> {
+    verify_result_with(proof_path, vk_path, |public_values| {
+        check_domain(circuit, public_values).map(|_| ())
+    })
+}
+
+/// `verify_result_in_cwd` with a caller-supplied check of the public values
+fn verify_result_with(
+    proof_path: &str,
+    vk_path: &str,
+    check_public: impl FnOnce(&[Scalar]) -> Result<(), String>,
+) -> Result<VerifyResult, ZkProofError> {
+    let vk = load_spartan_verifying_key::<E>(vk_path).map_err(|e| {
+        ZkProofError::setup_required(format!(
+            "Failed to load verifying key from '{}': {}",
+            vk_path, e
+        ))
+        .with_source(&*e)
+    })?;
+    verify_result_with_vk(proof_path, &vk, check_public)
+}
+
+/// `verify_result_with` against an already loaded verifying key
+fn verify_result_with_vk(
+    proof_path: &str,
+    vk: &SpartanVerifyingKey,
+    check_public: impl FnOnce(&[Scalar]) -> Result<(), String>,
+) -> Result<VerifyResult, ZkProofError> {
+    let proof = load_proof::<E>(proof_path).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load proof from '{}': {}", proof_path, e))
+            .with_source(&*e)
+    })?;
+    let comm_w_shared = format!("{:?}", proof.comm_W_shared());
+
+    let start = Instant::now();
+    let outcome = catch_unwind(AssertUnwindSafe(|| proof.verify(vk)));
+    let verify_ms = start.elapsed().as_millis() as u64;
+
+    let failure_reason = match outcome {
+        Ok(Ok(public_values)) => check_public(&public_values).err(),
+        Ok(Err(e)) => Some(format!("{:?}", e)),
+        Err(panic) => Some(format!(
+            "Verifier panicked: {}",
+            run_log::panic_message(panic.as_ref())
+        )),
+    };
+    Ok(VerifyResult {
+        valid: failure_reason.is_none(),
+        verify_ms,
+        failure_reason,
+        comm_w_shared,
+    })
+}
 
 /// Safely execute a function with a changed working directory
+/// Holds the process-wide operation lock (see `session`), so calls from other
+/// threads wait instead of changing the directory underneath `f`.
 fn with_working_dir<F, T>(path: &str, f: F) -> Result<T, ZkProofError>
+where
+    F: FnOnce() -> Result<T, ZkProofError>,
+{
+    session::run_exclusive(|| {
+        let _store = StoreLock::acquire(path)?;
+        in_working_dir(path, || {
+            blind_rotation::recover_interrupted_rotations()?;
+            f()
+        })
+    })
+}
+
+/// `with_working_dir` without taking the operation lock
+fn in_working_dir<F, T>(path: &str, f: F) -> Result<T, ZkProofError>
 where
     F: FnOnce() -> Result<T, ZkProofError>,
 {
     let original_dir = std::env::current_dir()?;
 
-    std::env::set_current_dir(path).map_err(|e| ZkProofError::IoError {
-        message: format!("Failed to set working directory to '{}': {}", path, e),
+    std::env::set_current_dir(path).map_err(|e| {
+        ZkProofError::io_error(format!(
+            "Failed to set working directory to '{}': {}",
+            path, e
+        ))
+        .with_source(&e)
     })?;
 
     let result = f();
@@ -130,6 +564,143 @@ where
     result
 }
 
+/// Run `f` with the operation lock held and `layout` installed, so that
+/// circuits and artifacts resolve against it instead of the working directory
+fn with_layout<F, T>(layout: &ArtifactLayout, f: F) -> Result<T, ZkProofError>
+where
+    F: FnOnce() -> Result<T, ZkProofError>,
+{
+    if !Path::new(&layout.documents_path).is_dir() {
+        return Err(ZkProofError::io_error(format!(
+            "Documents directory '{}' does not exist",
+            layout.documents_path
+        )));
+    }
+
+    session::run_exclusive(|| {
+        let _store = StoreLock::acquire(&layout.documents_path)?;
+        let _saved = SavedLayout(artifact_layout());
+        set_artifact_layout(Some(SpartanArtifactLayout {
+            assets_dir: PathBuf::from(&layout.assets_path),
+            documents_dir: PathBuf::from(&layout.documents_path),
+        }));
+        f()
+    })
+}
+
+/// Layout in force before `with_layout`, restored when dropped so that it
+/// comes back even if the operation panics
+struct SavedLayout(Option<SpartanArtifactLayout>);
+
+impl Drop for SavedLayout {
+    fn drop(&mut self) {
+        set_artifact_layout(self.0.take());
+    }
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Set the deployment domain separator bound into every Prepare and Show proof
+/// Proofs verify only under the domain they were made with, so give test and
+/// production builds different domains (e.g. "zkid-v1-staging"); `None` restores
+/// the default "zkid-v1". Set it before setup, proving, and verifying.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_domain_separator(domain: Option<String>) -> Result<(), ZkProofError> {
+    if domain.as_deref().is_some_and(str::is_empty) {
+        return Err(ZkProofError::invalid_input(
+            "Domain separator must not be empty".to_string(),
+        ));
+    }
+    set_domain(domain);
+    Ok(())
+}
+
+/// Store proofs, instances, witnesses and shared blinds under `credentials/<id>/`
+/// Select a credential before importing, proving, presenting or verifying it,
+/// so that several credentials on one device keep separate artifacts. `None`
+/// restores the flat `keys/` layout used by earlier versions. Keys are shared
+/// by all credentials.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_artifact_namespace(credential_id: Option<String>) -> Result<(), ZkProofError> {
+    let namespace = match credential_id {
+        Some(id) => ArtifactNamespace::credential(&id).map_err(ZkProofError::invalid_input)?,
+        None => ArtifactNamespace::Legacy,
+    };
+    ecdsa_spartan2::set_artifact_namespace(namespace);
+    Ok(())
+}
+
+/// Select the JWT circuit size used for Prepare setup, proving and verifying
+/// "jwt_1k", "jwt" (the default, up to 1920 bytes) or "jwt_4k"; each has its own
+/// Prepare keys. `import_credential` selects the smallest one the credential fits.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_jwt_variant(variant: Option<String>) -> Result<(), ZkProofError> {
+    let variant = match variant {
+        Some(name) => name.parse().map_err(ZkProofError::invalid_input)?,
+        None => JwtVariant::default(),
+    };
+    if !variant.is_available() {
+        return Err(ZkProofError::invalid_input(format!(
+            "The {} circuit is not compiled into this build",
+            variant.name()
+        )));
+    }
+    ecdsa_spartan2::set_jwt_variant(variant);
+    Ok(())
+}
+
+/// The JWT circuit `import_credential` would prove `jwt` in
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn jwt_variant_for(jwt: String) -> Result<String, ZkProofError> {
+    JwtVariant::for_jwt(jwt.trim())
+        .map(|variant| variant.name().to_string())
+        .map_err(ZkProofError::invalid_input)
+}
+
+/// Read compiled circuits and keys shipped with the app from `assets_path`
+/// The directory may be read-only, such as the app bundle or extracted Android
+/// assets: it holds `build/` with the compiled circuits and optionally `keys/`
+/// with keys set up ahead of time. Everything written still goes to the
+/// `documents_path` of each call, and keys set up or imported there take
+/// precedence over bundled ones. `None` reads circuits from `../circom` next to
+/// the documents directory and keys from the documents directory, as before.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_assets_path(assets_path: Option<String>) -> Result<(), ZkProofError> {
+    // Absolute, since operations change the working directory
+    let assets_dir = assets_path
+        .map(|path| {
+            std::fs::canonicalize(&path).map_err(|e| {
+                ZkProofError::file_not_found(format!(
+                    "Assets directory '{}' not found: {}",
+                    path, e
+                ))
+                .with_source(&e)
+            })
+        })
+        .transpose()?;
+    ecdsa_spartan2::set_assets_dir(assets_dir);
+    Ok(())
+}
+
+/// Reuse generated witnesses while a circuit's input file is unchanged
+/// Speeds up repeated proves over the same credential. Cached witnesses are
+/// sealed like stored ones; call `clear_witness_cache` after updating circuits.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_witness_cache_enabled(enabled: bool) {
+    ecdsa_spartan2::set_witness_cache_enabled(enabled);
+}
+
+/// Evaluate every constraint against the witness before proving
+/// A witness that fails one is then reported as a `ProofGenerationFailed`
+/// naming the constraint, its signals and their values, instead of a prover
+/// panic. Roughly doubles synthesis time; meant for debugging circuits.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_constraint_check_enabled(enabled: bool) {
+    ecdsa_spartan2::set_constraint_check_enabled(enabled);
+}
+
 // ============================================================================
 // Setup Operations
 // ============================================================================
@@ -143,9 +714,12 @@ pub fn setup_prepare_keys(
 ) -> Result<String, ZkProofError> {
     with_working_dir(&documents_path, || {
         let circuit = PrepareCircuit::new(input_path.map(PathBuf::from));
+        let run = RunLog::start("setup_prepare_keys");
 
         let start = std::time::Instant::now();
-        setup_circuit_keys(circuit, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY);
+        run.phase("setup", Some(PREPARE_PROVING_KEY), || {
+            setup_and_save_keys(circuit, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY)
+        })?;
         let elapsed_ms = start.elapsed().as_millis();
 
         Ok(format!(
@@ -164,9 +738,12 @@ pub fn setup_show_keys(
 ) -> Result<String, ZkProofError> {
     with_working_dir(&documents_path, || {
         let circuit = ShowCircuit::new(input_path.map(PathBuf::from));
+        let run = RunLog::start("setup_show_keys");
 
         let start = std::time::Instant::now();
-        setup_circuit_keys(circuit, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY);
+        run.phase("setup", Some(SHOW_PROVING_KEY), || {
+            setup_and_save_keys(circuit, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY)
+        })?;
         let elapsed_ms = start.elapsed().as_millis();
 
         Ok(format!(
@@ -176,6 +753,115 @@ pub fn setup_show_keys(
     })
 }
 
+/// Install Prepare and Show keys from a package made by `ecdsa-spartan2 export-keys`
+/// Use instead of the setup functions so phones never run setup. The package is
+/// rejected, and nothing written, if its format version, key digests or circuit
+/// digests do not match this build. Returns the key paths written.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn import_key_package(
+    documents_path: String,
+    package: Vec<u8>,
+) -> Result<Vec<String>, ZkProofError> {
+    with_working_dir(&documents_path, || install_key_package(&package))
+}
+
+/// `import_key_package` reading the package from `package_path`
+/// A relative `package_path` is resolved against `documents_path`, e.g. for a
+/// package bundled with the app's assets pass an absolute path.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn import_key_package_file(
+    documents_path: String,
+    package_path: String,
+) -> Result<Vec<String>, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let package = std::fs::read(&package_path).map_err(|e| {
+            ZkProofError::file_not_found(format!(
+                "Failed to read key package from '{}': {}",
+                package_path, e
+            ))
+            .with_source(&e)
+        })?;
+        install_key_package(&package)
+    })
+}
+
+/// Update a stored artifact in place with a patch made by `ecdsa-spartan2 diff-artifact`
+/// Download the patch instead of the whole file after a circuit update, e.g.
+/// for the key package before `import_key_package_file`. A relative
+/// `artifact_path` is resolved against `documents_path`. The artifact is left
+/// as it is if the patch was made against another version of it or the result
+/// fails its digest check. Returns the SHA-256 of the patched artifact.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn apply_artifact_patch(
+    documents_path: String,
+    artifact_path: String,
+    patch: Vec<u8>,
+) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("apply_artifact_patch");
+        let old = std::fs::read(&artifact_path).map_err(|e| {
+            ZkProofError::file_not_found(format!(
+                "Failed to read artifact from '{}': {}",
+                artifact_path, e
+            ))
+            .with_source(&e)
+        })?;
+        run.phase("patch", Some(&artifact_path), || {
+            let new = ecdsa_spartan2::apply_artifact_patch(&old, &patch).map_err(|e| {
+                ZkProofError::invalid_input(format!("Failed to patch '{}': {}", artifact_path, e))
+            })?;
+            ecdsa_spartan2::setup::write_atomic(&artifact_path, &new).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to write '{}': {}", artifact_path, e))
+                    .with_source(&e)
+            })?;
+            Ok(ecdsa_spartan2::vectors::sha256_hex(&new))
+        })
+    })
+}
+
+fn install_key_package(package: &[u8]) -> Result<Vec<String>, ZkProofError> {
+    let run = RunLog::start("import_key_package");
+    run.phase("install", Some(PREPARE_PROVING_KEY), || {
+        ecdsa_spartan2::import_key_package(package).map_err(|e| {
+            ZkProofError::invalid_input(format!("Failed to import key package: {}", e))
+                .with_source(&*e)
+        })
+    })
+}
+
+/// Re-encode the stored proving key of `circuit` compactly, for bundling with an app
+/// Run on a desktop before packaging keys; proving reads either encoding, and
+/// a key the compact encoding would not shrink is left as it is. Prepare keys
+/// are those of the selected JWT variant.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn strip_proving_key_for_mobile(
+    documents_path: String,
+    circuit: String,
+) -> Result<KeyStripResult, ZkProofError> {
+    let circuit: CircuitKind = circuit.parse().map_err(ZkProofError::invalid_input)?;
+    let pk_path = match circuit {
+        CircuitKind::Prepare => PREPARE_PROVING_KEY,
+        CircuitKind::Show => SHOW_PROVING_KEY,
+    };
+
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("strip_proving_key_for_mobile");
+        let report = run.phase("strip", Some(pk_path), || {
+            strip_proving_key::<E>(pk_path).map_err(|e| {
+                ZkProofError::setup_required(format!(
+                    "Failed to strip proving key '{}': {}",
+                    pk_path, e
+                ))
+                .with_source(&*e)
+            })
+        })?;
+        Ok(KeyStripResult {
+            full_bytes: report.full_bytes,
+            stripped_bytes: report.stripped_bytes,
+        })
+    })
+}
+
 // ============================================================================
 // Shared Blinds Generation
 // ============================================================================
@@ -187,11 +873,31 @@ pub fn generate_shared_blinds(documents_path: String) -> Result<String, ZkProofE
     with_working_dir(&documents_path, || {
         use ecdsa_spartan2::prover::generate_shared_blinds as gen_blinds;
 
-        // Note: While circuits have 98 shared values (2 keybindings + 96 claim scalars),
-        // Hyrax batches all these into a single commitment point.
-        // num_shared_rows() returns the number of Hyrax commitment points, not individual scalars.
-        const NUM_SHARED: usize = 1;
-        gen_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
+        let run = RunLog::start("generate_shared_blinds");
+        run.phase("generate_blinds", Some(SHARED_BLINDS), || {
+            gen_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
+            Ok(())
+        })?;
+        reset_presentation_counter(&artifact_namespace())?;
+
+        Ok("Shared blinds generated successfully".to_string())
+    })
+}
+
+/// Generate shared blinding factors from a caller-chosen randomness source
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn generate_shared_blinds_from_source(
+    documents_path: String,
+    rng: RngSource,
+) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let mut rng = rng.into_rng();
+        let run = RunLog::start("generate_shared_blinds");
+        run.phase("generate_blinds", Some(SHARED_BLINDS), || {
+            generate_shared_blinds_with_rng::<E>(SHARED_BLINDS, NUM_SHARED, &mut *rng)
+                .map_err(prove_failed)
+        })?;
+        reset_presentation_counter(&artifact_namespace())?;
 
         Ok("Shared blinds generated successfully".to_string())
     })
@@ -202,23 +908,34 @@ pub fn generate_shared_blinds(documents_path: String) -> Result<String, ZkProofE
 // ============================================================================
 
 /// Generate Prepare (JWT) circuit proof
-/// Runs prep_prove + prove phases using existing keys
+/// Runs prep_prove + prove phases using existing keys. With `max_duration_ms`
+/// set, fails with `TimedOut` once that much time has passed, leaving the stored
+/// proof untouched; enabled witness caching keeps the witness for a retry.
 #[cfg_attr(feature = "uniffi", uniffi::export)]
 pub fn prove_prepare(
     documents_path: String,
     input_path: Option<String>,
+    max_duration_ms: Option<u64>,
 ) -> Result<ProofResult, ZkProofError> {
     with_working_dir(&documents_path, || {
         let circuit = PrepareCircuit::new(input_path.map(PathBuf::from));
+        let run = RunLog::start("prove_prepare");
 
         let start = std::time::Instant::now();
-        prove_circuit(
-            circuit,
-            PREPARE_PROVING_KEY,
-            PREPARE_INSTANCE,
-            PREPARE_WITNESS,
-            PREPARE_PROOF,
-        );
+        let deadline = deadline_after(start, max_duration_ms);
+        run.phase("prove", Some(PREPARE_PROOF), || {
+            locate_constraint_failure(|| {
+                prove_circuit_until(
+                    circuit,
+                    PREPARE_PROVING_KEY,
+                    PREPARE_INSTANCE,
+                    PREPARE_WITNESS,
+                    PREPARE_PROOF,
+                    deadline,
+                )
+                .map_err(prove_failed)
+            })
+        })?;
         let total_ms = start.elapsed().as_millis() as u64;
 
         // Get proof size and comm_W_shared
@@ -236,36 +953,83 @@ pub fn prove_prepare(
 }
 
 /// Generate Show circuit proof
-/// Runs prep_prove + prove phases using existing keys
+/// Runs prep_prove + prove phases using existing keys; `max_duration_ms` as for
+/// `prove_prepare`
 #[cfg_attr(feature = "uniffi", uniffi::export)]
 pub fn prove_show(
     documents_path: String,
     input_path: Option<String>,
+    max_duration_ms: Option<u64>,
 ) -> Result<ProofResult, ZkProofError> {
     with_working_dir(&documents_path, || {
-        let circuit = ShowCircuit::new(input_path.map(PathBuf::from));
+        prove_show_in_cwd(input_path.map(PathBuf::from), max_duration_ms)
+    })
+}
 
-        let start = std::time::Instant::now();
-        prove_circuit(
-            circuit,
-            SHOW_PROVING_KEY,
-            SHOW_INSTANCE,
-            SHOW_WITNESS,
-            SHOW_PROOF,
-        );
-        let total_ms = start.elapsed().as_millis() as u64;
+/// Prove Show from the current working directory and apply the presentation policy
+fn prove_show_in_cwd(
+    input_path: Option<PathBuf>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    let circuit = ShowCircuit::new(input_path);
+    let run = RunLog::start("prove_show");
+
+    let start = std::time::Instant::now();
+    let deadline = deadline_after(start, max_duration_ms);
+    run.phase("prove", Some(SHOW_PROOF), || {
+        locate_constraint_failure(|| {
+            prove_circuit_until(
+                circuit,
+                SHOW_PROVING_KEY,
+                SHOW_INSTANCE,
+                SHOW_WITNESS,
+                SHOW_PROOF,
+                deadline,
+            )
+            .map_err(prove_failed)
+        })
+    })?;
+    let total_ms = start.elapsed().as_millis() as u64;
+    // Reblind per the presentation policy before handing out the proof
+    present_in_cwd()?;
+
+    // Get proof size and comm_W_shared
+    let proof_size_bytes = get_proof_size(SHOW_PROOF)?;
+    let comm_w_shared = extract_comm_w_shared(SHOW_INSTANCE)?;
+
+    Ok(ProofResult {
+        prep_ms: 0,
+        prove_ms: total_ms,
+        total_ms,
+        proof_size_bytes,
+        comm_w_shared,
+    })
+}
 
-        // Get proof size and comm_W_shared
-        let proof_size_bytes = get_proof_size(SHOW_PROOF)?;
-        let comm_w_shared = extract_comm_w_shared(SHOW_INSTANCE)?;
+/// `prove_prepare` with `options` in force for this call only
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_prepare_with_options(
+    documents_path: String,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+    options: ProveOptions,
+) -> Result<ProofResult, ZkProofError> {
+    with_prove_options(options, || {
+        prove_prepare(documents_path, input_path, max_duration_ms)
+    })
+}
 
-        Ok(ProofResult {
-            prep_ms: 0,
-            prove_ms: total_ms,
-            total_ms,
-            proof_size_bytes,
-            comm_w_shared,
-        })
+/// `prove_show` with `options` in force for this call only
+/// The options' reblind policy and seed govern the reblind before presenting.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_show_with_options(
+    documents_path: String,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+    options: ProveOptions,
+) -> Result<ProofResult, ZkProofError> {
+    with_prove_options(options, || {
+        prove_show(documents_path, input_path, max_duration_ms)
     })
 }
 
@@ -279,16 +1043,20 @@ pub fn prove_show(
 pub fn reblind_prepare(documents_path: String) -> Result<ProofResult, ZkProofError> {
     with_working_dir(&documents_path, || {
         let circuit = PrepareCircuit::new(None);
+        let run = RunLog::start("reblind_prepare");
 
         let start = std::time::Instant::now();
-        reblind(
-            circuit,
-            PREPARE_PROVING_KEY,
-            PREPARE_INSTANCE,
-            PREPARE_WITNESS,
-            PREPARE_PROOF,
-            SHARED_BLINDS,
-        );
+        run.phase("reblind", Some(PREPARE_PROOF), || {
+            reblind(
+                circuit,
+                PREPARE_PROVING_KEY,
+                PREPARE_INSTANCE,
+                PREPARE_WITNESS,
+                PREPARE_PROOF,
+                SHARED_BLINDS,
+            )
+            .map_err(prove_failed)
+        })?;
         let elapsed_ms = start.elapsed().as_millis() as u64;
 
         // Get proof size and comm_W_shared
@@ -311,16 +1079,20 @@ pub fn reblind_prepare(documents_path: String) -> Result<ProofResult, ZkProofErr
 pub fn reblind_show(documents_path: String) -> Result<ProofResult, ZkProofError> {
     with_working_dir(&documents_path, || {
         let circuit = ShowCircuit::new(None);
+        let run = RunLog::start("reblind_show");
 
         let start = std::time::Instant::now();
-        reblind(
-            circuit,
-            SHOW_PROVING_KEY,
-            SHOW_INSTANCE,
-            SHOW_WITNESS,
-            SHOW_PROOF,
-            SHARED_BLINDS,
-        );
+        run.phase("reblind", Some(SHOW_PROOF), || {
+            reblind(
+                circuit,
+                SHOW_PROVING_KEY,
+                SHOW_INSTANCE,
+                SHOW_WITNESS,
+                SHOW_PROOF,
+                SHARED_BLINDS,
+            )
+            .map_err(prove_failed)
+        })?;
         let elapsed_ms = start.elapsed().as_millis() as u64;
 
         // Get proof size and comm_W_shared
@@ -337,212 +1109,628 @@ pub fn reblind_show(documents_path: String) -> Result<ProofResult, ZkProofError>
     })
 }
 
-// ============================================================================
-// Verify Operations
-// ============================================================================
-
-/// Verify Prepare circuit proof
-/// Verifies the proof using the verifying key
+/// Reblind both Prepare and Show proofs under freshly sampled shared blinds
+/// Wallets reblind the pair together before a presentation so that both proofs
+/// carry the same new comm_W_shared. The blinds are drawn from the prove
+/// options' seed if one is set.
 #[cfg_attr(feature = "uniffi", uniffi::export)]
-pub fn verify_prepare(documents_path: String) -> Result<bool, ZkProofError> {
-    with_working_dir(&documents_path, || {
-        verify_circuit(PREPARE_PROOF, PREPARE_VERIFYING_KEY);
-        Ok(true)
-    })
+pub fn reblind_all(documents_path: String) -> Result<ReblindAllResult, ZkProofError> {
+    reblind_all_from_source(documents_path, get_prove_options().rng_source())
 }
 
-/// Verify Show circuit proof
-/// Verifies the proof using the verifying key
+/// Reblind both proofs under fresh shared blinds drawn from `rng`
 #[cfg_attr(feature = "uniffi", uniffi::export)]
-pub fn verify_show(documents_path: String) -> Result<bool, ZkProofError> {
+pub fn reblind_all_from_source(
+    documents_path: String,
+    rng: RngSource,
+) -> Result<ReblindAllResult, ZkProofError> {
     with_working_dir(&documents_path, || {
-        verify_circuit(SHOW_PROOF, SHOW_VERIFYING_KEY);
-        Ok(true)
+        let mut rng = rng.into_rng();
+        reblind_all_in_cwd(&mut *rng)
     })
 }
 
-// ============================================================================
-// Benchmark Operations
+/// `reblind_prepare` with `options` in force for this call only
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_prepare_with_options(
+    documents_path: String,
+    options: ProveOptions,
+) -> Result<ProofResult, ZkProofError> {
+    with_prove_options(options, || reblind_prepare(documents_path))
+}
+
+/// `reblind_show` with `options` in force for this call only
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_show_with_options(
+    documents_path: String,
+    options: ProveOptions,
+) -> Result<ProofResult, ZkProofError> {
+    with_prove_options(options, || reblind_show(documents_path))
+}
+
+/// `reblind_all` with `options` in force for this call only
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_all_with_options(
+    documents_path: String,
+    options: ProveOptions,
+) -> Result<ReblindAllResult, ZkProofError> {
+    with_prove_options(options, || reblind_all(documents_path))
+}
+
+// ============================================================================
+// Presentation Operations
+// ============================================================================
+
+/// Configure when presentations sample fresh shared blinds
+/// The policy belongs to the credential namespace selected with
+/// `set_artifact_namespace`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_presentation_policy(
+    documents_path: String,
+    policy: PresentationPolicy,
+) -> Result<(), ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let mut state = PresentationState::load()?;
+        state.policy = policy;
+        state.save()
+    })
+}
+
+/// Return the stored Show proof for presentation
+/// Transparently reblinds both proofs first when the presentation policy requires it
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn get_presentation(documents_path: String) -> Result<PresentationResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let start = std::time::Instant::now();
+        let (reblinded, presentations_with_current_blinds) = present_in_cwd()?;
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+
+        Ok(PresentationResult {
+            show: ProofResult {
+                prep_ms: 0,
+                prove_ms: elapsed_ms,
+                total_ms: elapsed_ms,
+                proof_size_bytes: get_proof_size(SHOW_PROOF)?,
+                comm_w_shared: extract_comm_w_shared(SHOW_INSTANCE)?,
+            },
+            reblinded,
+            presentations_with_current_blinds,
+        })
+    })
+}
+
+// ============================================================================
+// Verify Operations
+// ============================================================================
+
+/// Verify Prepare circuit proof
+/// A rejected proof is reported in the result; errors mean the proof or
+/// verifying key could not be loaded
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_prepare(documents_path: String) -> Result<VerifyResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_prepare");
+        run.phase("verify", None, || {
+            verify_result_in_cwd(CircuitKind::Prepare, PREPARE_PROOF, PREPARE_VERIFYING_KEY)
+        })
+    })
+}
+
+/// Verify Prepare circuit proof and reject it if stale
+/// Fails unless the proof claims to have been issued at most `max_age_secs` ago
+/// and has not expired. The window is chosen by the prover, so this filters out
+/// stale proofs from honest wallets; it is not a freshness guarantee, which
+/// needs a verifier nonce (see `verify_presentation`).
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_prepare_fresh(
+    documents_path: String,
+    max_age_secs: u64,
+) -> Result<ProofFreshness, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_prepare_fresh");
+        let public_values = run.phase("verify", None, || {
+            verify_in_cwd(CircuitKind::Prepare, PREPARE_PROOF, PREPARE_VERIFYING_KEY)
+        })?;
+        let validity = PreparePublicValues::from_public_values(&public_values)
+            .map_err(ZkProofError::verification_failed)?
+            .validity;
+        validity
+            .check_fresh(unix_now(), max_age_secs)
+            .map_err(ZkProofError::verification_failed)?;
+
+        Ok(validity.into())
+    })
+}
+
+/// Verify Prepare circuit proof and report which of `issuer_keys` it was made against
+/// Returns the key's `kid`. Fails if the proof is rejected, names a key not in
+/// the set, or claims an issued-at time outside that key's validity window.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_prepare_issuer(
+    documents_path: String,
+    issuer_keys: Vec<IssuerPublicKey>,
+) -> Result<String, ZkProofError> {
+    let keys = credential::issuer_key_set(issuer_keys)?;
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_prepare_issuer");
+        let public_values = run.phase("verify", None, || {
+            verify_in_cwd(CircuitKind::Prepare, PREPARE_PROOF, PREPARE_VERIFYING_KEY)
+        })?;
+        let public = PreparePublicValues::from_public_values(&public_values)
+            .map_err(ZkProofError::verification_failed)?;
+        let key = keys.identify(&public.issuer_key).ok_or_else(|| {
+            ZkProofError::verification_failed(
+                "Prepare proof was made against an unknown issuer key".to_string(),
+            )
+        })?;
+        if !key.is_valid_at(public.validity.issued_at) {
+            return Err(ZkProofError::verification_failed(format!(
+                "Issuer key '{}' was not valid when the proof was issued",
+                key.kid
+            )));
+        }
+        Ok(key.kid.clone())
+    })
+}
+
+/// Verify Show circuit proof
+/// A rejected proof is reported in the result; errors mean the proof or
+/// verifying key could not be loaded
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_show(documents_path: String) -> Result<VerifyResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_show");
+        run.phase("verify", None, || {
+            verify_result_in_cwd(CircuitKind::Show, SHOW_PROOF, SHOW_VERIFYING_KEY)
+        })
+    })
+}
+
+// ============================================================================
+// Parallel Operations
+// ============================================================================
+
+/// Prove Prepare and Show one after the other and then concurrently, and
+/// compare the wall-clock times of the two runs
+/// The concurrent run splits the available cores between two thread pools.
+/// Keys and proofs resolve against `documents_path` and circuits against
+/// `../circom` next to it, or the directory set with `set_assets_path`, so the
+/// working directory is left alone. The proofs left behind are those of the
+/// concurrent run.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_both_parallel(
+    documents_path: String,
+    prepare_input_path: Option<String>,
+    show_input_path: Option<String>,
+) -> Result<ParallelProveResult, ZkProofError> {
+    let assets_path = assets_dir().unwrap_or_else(|| Path::new(&documents_path).join("../circom"));
+    let layout = ArtifactLayout {
+        assets_path: assets_path.to_string_lossy().into_owned(),
+        documents_path,
+    };
+    with_layout(&layout, || {
+        let (prepare_pk, show_pk) = rayon::join(
+            || {
+                load_spartan_proving_key::<E>(PREPARE_PROVING_KEY).map_err(|e| {
+                    ZkProofError::setup_required(format!(
+                        "Failed to load prepare proving key: {}",
+                        e
+                    ))
+                    .with_source(&*e)
+                })
+            },
+            || {
+                load_spartan_proving_key::<E>(SHOW_PROVING_KEY).map_err(|e| {
+                    ZkProofError::setup_required(format!("Failed to load show proving key: {}", e))
+                        .with_source(&*e)
+                })
+            },
+        );
+        let (prepare_pk, show_pk) = (prepare_pk?, show_pk?);
+
+        let prove_prepare = || {
+            let t0 = Instant::now();
+            let circuit = PrepareCircuit::new(prepare_input_path.as_ref().map(PathBuf::from));
+            prove_circuit_with_pk(
+                circuit,
+                &prepare_pk,
+                PREPARE_INSTANCE,
+                PREPARE_WITNESS,
+                PREPARE_PROOF,
+            )
+            .map_err(prove_failed)?;
+            Ok::<_, ZkProofError>(t0.elapsed().as_millis() as u64)
+        };
+        let prove_show = || {
+            let t0 = Instant::now();
+            let circuit = ShowCircuit::new(show_input_path.as_ref().map(PathBuf::from));
+            prove_circuit_with_pk(circuit, &show_pk, SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF)
+                .map_err(prove_failed)?;
+            Ok::<_, ZkProofError>(t0.elapsed().as_millis() as u64)
+        };
+
+        // Serial baseline, each circuit with every core
+        let start = Instant::now();
+        prove_prepare()?;
+        prove_show()?;
+        let serial_wall_clock_ms = start.elapsed().as_millis() as u64;
+
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2);
+        let build_pool = |num_threads: usize| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads.max(1))
+                .build()
+                .map_err(|e| {
+                    ZkProofError::proof_generation_failed(format!(
+                        "Failed to build thread pool: {}",
+                        e
+                    ))
+                    .with_source(&e)
+                })
+        };
+        // Prepare dominates proving time, so it gets the larger half
+        let prepare_pool = build_pool(threads - threads / 2)?;
+        let show_pool = build_pool(threads / 2)?;
+
+        let start = Instant::now();
+        let (prepare_ms, show_ms) = std::thread::scope(|scope| {
+            let prepare = scope.spawn(|| prepare_pool.install(&prove_prepare));
+            let show = scope.spawn(|| show_pool.install(&prove_show));
+
+            Ok::<_, ZkProofError>((join_prover(prepare, "Prepare")?, join_prover(show, "Show")?))
+        })?;
+        let wall_clock_ms = start.elapsed().as_millis() as u64;
+
+        let prepare = ProofResult {
+            prep_ms: 0,
+            prove_ms: prepare_ms,
+            total_ms: prepare_ms,
+            proof_size_bytes: get_proof_size(PREPARE_PROOF)?,
+            comm_w_shared: extract_comm_w_shared(PREPARE_INSTANCE)?,
+        };
+        let show = ProofResult {
+            prep_ms: 0,
+            prove_ms: show_ms,
+            total_ms: show_ms,
+            proof_size_bytes: get_proof_size(SHOW_PROOF)?,
+            comm_w_shared: extract_comm_w_shared(SHOW_INSTANCE)?,
+        };
+
+        Ok(ParallelProveResult {
+            prepare,
+            show,
+            serial_wall_clock_ms,
+            wall_clock_ms,
+            speedup: serial_wall_clock_ms as f64 / wall_clock_ms.max(1) as f64,
+        })
+    })
+}
+
+// ============================================================================
+// Benchmark Operations
 // ============================================================================
 
 /// Run complete benchmark pipeline for both Prepare and Show circuits
 /// Executes all 9 steps: setup, prove, reblind, and verify for both circuits
-/// Returns comprehensive timing and size metrics
+/// Returns comprehensive timing and size metrics. A step that fails or panics
+/// does not abort the run: it is recorded in `steps`, the steps depending on
+/// it are skipped, and the results of the others are still returned. With
+/// `resume`, setup and prove steps whose artifacts an earlier run over the same
+/// input left intact are reused rather than run and timed again.
+/// Circuits are read from `../circom` next to `documents_path`, or from the
+/// directory set with `set_assets_path`.
 #[cfg_attr(feature = "uniffi", uniffi::export)]
 pub fn run_complete_benchmark(
     documents_path: String,
     input_path: Option<String>,
+    resume: bool,
 ) -> Result<BenchmarkResults, ZkProofError> {
-    with_working_dir(&documents_path, || {
-        // Note: While circuits have 98 shared values (2 keybindings + 96 claim scalars),
-        // Hyrax batches all these into a single commitment point.
-        // num_shared_rows() returns the number of Hyrax commitment points, not individual scalars.
-        const NUM_SHARED: usize = 1;
+    let assets_path = assets_dir().unwrap_or_else(|| Path::new(&documents_path).join("../circom"));
+    let layout = ArtifactLayout {
+        assets_path: assets_path.to_string_lossy().into_owned(),
+        documents_path,
+    };
+    run_complete_benchmark_with_layout(layout, input_path, resume)
+}
+
+/// `run_complete_benchmark` with circuits and artifacts in the directories of
+/// `layout`, leaving the working directory alone
+/// Lets the benchmark run with the compiled circuits in a read-only app bundle
+/// and keys and proofs in a separate writable directory. A relative
+/// `input_path` is resolved against `layout.documents_path`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn run_complete_benchmark_with_layout(
+    layout: ArtifactLayout,
+    input_path: Option<String>,
+    resume: bool,
+) -> Result<BenchmarkResults, ZkProofError> {
+    with_layout(&layout, || {
+        let run = RunLog::start("run_complete_benchmark");
+        let checkpoint = if resume {
+            Checkpoint::load(input_path.as_deref())
+        } else {
+            Checkpoint::new(input_path.as_deref())
+        };
+        let mut steps = BenchmarkSteps::new(&run, checkpoint, resume);
 
         // Step 1: Setup Prepare Circuit
         let prepare_circuit = PrepareCircuit::new(input_path.as_ref().map(PathBuf::from));
-        let start = std::time::Instant::now();
-        let (prepare_pk, prepare_vk) = setup_circuit_keys_no_save(prepare_circuit);
-        let prepare_setup_ms = start.elapsed().as_millis() as u64;
-
+        let mut prepare_setup = SetupTimings::default();
+        let prepare_keys = steps.resumable(
+            "setup_prepare",
+            None,
+            Some(()),
+            || load_benchmark_keys(PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY),
+            |()| {
+                let (keys, timings) = setup_benchmark_keys(prepare_circuit)?;
+                prepare_setup = timings;
+                Ok(keys)
+            },
+        );
         // Save Prepare keys after timing
-        save_keys(
-            PREPARE_PROVING_KEY,
-            PREPARE_VERIFYING_KEY,
-            &prepare_pk,
-            &prepare_vk,
-        )
-        .map_err(|e| ZkProofError::IoError {
-            message: format!("Failed to save Prepare keys: {}", e),
-        })?;
+        let prepare_keys = steps.save(prepare_keys, |(pk, vk)| {
+            let t0 = Instant::now();
+            save_keys(PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY, pk, vk).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to save Prepare keys: {}", e))
+                    .with_source(&*e)
+            })?;
+            prepare_setup.save_ms = t0.elapsed().as_millis();
+            Ok(())
+        });
+        // Read the keys back as a phone installing them would; the loaded copy is dropped
+        steps.step("load_prepare_keys", None, prepare_keys.as_ref(), Ok, |_| {
+            load_benchmark_keys(PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY).map(drop)
+        });
 
         // Step 2: Setup Show Circuit
         let show_circuit = ShowCircuit::new(input_path.as_ref().map(PathBuf::from));
-        let start = std::time::Instant::now();
-        let (show_pk, show_vk) = setup_circuit_keys_no_save(show_circuit);
-        let show_setup_ms = start.elapsed().as_millis() as u64;
-
+        let mut show_setup = SetupTimings::default();
+        let show_keys = steps.resumable(
+            "setup_show",
+            None,
+            Some(()),
+            || load_benchmark_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY),
+            |()| {
+                let (keys, timings) = setup_benchmark_keys(show_circuit)?;
+                show_setup = timings;
+                Ok(keys)
+            },
+        );
         // Save Show keys after timing
-        save_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, &show_pk, &show_vk).map_err(|e| {
-            ZkProofError::IoError {
-                message: format!("Failed to save Show keys: {}", e),
-            }
-        })?;
+        let show_keys = steps.save(show_keys, |(pk, vk)| {
+            let t0 = Instant::now();
+            save_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, pk, vk).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to save Show keys: {}", e)).with_source(&*e)
+            })?;
+            show_setup.save_ms = t0.elapsed().as_millis();
+            Ok(())
+        });
+        steps.step("load_show_keys", None, show_keys.as_ref(), Ok, |_| {
+            load_benchmark_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY).map(drop)
+        });
 
         // Step 3: Generate Shared Blinds
-        let start = std::time::Instant::now();
-        gen_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
-        let generate_blinds_ms = start.elapsed().as_millis() as u64;
+        let mut rng = get_prove_options().rng_source().into_rng();
+        let blinds = steps.resumable(
+            "generate_blinds",
+            Some(SHARED_BLINDS),
+            Some(()),
+            || Ok(()),
+            |()| {
+                generate_shared_blinds_with_rng::<E>(SHARED_BLINDS, NUM_SHARED, &mut *rng)
+                    .map_err(prove_failed)
+            },
+        );
+        // Loaded once for both reblinds (file I/O should not be part of reblind benchmark)
+        let shared_blinds = steps.then(blinds, |()| {
+            load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+                ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+                    .with_source(&*e)
+            })
+        });
 
         // Step 4: Prove Prepare Circuit
-        let start = std::time::Instant::now();
         let prepare_circuit = PrepareCircuit::new(input_path.as_ref().map(PathBuf::from));
-        prove_circuit_with_pk(
-            prepare_circuit,
-            &prepare_pk,
-            PREPARE_INSTANCE,
-            PREPARE_WITNESS,
-            PREPARE_PROOF,
+        let prepare_proved = steps.resumable(
+            "prove_prepare",
+            Some(PREPARE_PROOF),
+            prepare_keys.as_ref(),
+            || Ok(()),
+            |(pk, _)| {
+                prove_circuit_with_pk(
+                    prepare_circuit,
+                    pk,
+                    PREPARE_INSTANCE,
+                    PREPARE_WITNESS,
+                    PREPARE_PROOF,
+                )
+                .map_err(prove_failed)
+            },
         );
-        let prove_prepare_ms = start.elapsed().as_millis() as u64;
 
         // Step 5: Reblind Prepare
         // Load data before timing (file I/O should not be part of reblind benchmark)
-        let prepare_instance =
-            load_instance(PREPARE_INSTANCE).map_err(|e| ZkProofError::FileNotFound {
-                message: format!("Failed to load prepare instance: {}", e),
-            })?;
-        let prepare_witness =
-            load_witness(PREPARE_WITNESS).map_err(|e| ZkProofError::FileNotFound {
-                message: format!("Failed to load prepare witness: {}", e),
-            })?;
-        let shared_blinds =
-            load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| ZkProofError::FileNotFound {
-                message: format!("Failed to load shared blinds: {}", e),
-            })?;
-
-        let start = std::time::Instant::now();
-        reblind_with_loaded_data(
-            PrepareCircuit::default(),
-            &prepare_pk,
-            prepare_instance,
-            prepare_witness,
-            &shared_blinds,
-            PREPARE_INSTANCE,
-            PREPARE_WITNESS,
-            PREPARE_PROOF,
+        let reblind_prepare = steps.step(
+            "reblind_prepare",
+            Some(PREPARE_PROOF),
+            prepare_keys
+                .as_ref()
+                .zip(shared_blinds.as_ref())
+                .zip(prepare_proved),
+            |((keys, blinds), ())| {
+                let (instance, witness) =
+                    load_instance_and_witness(PREPARE_INSTANCE, PREPARE_WITNESS)?;
+                Ok((keys, blinds, instance, witness))
+            },
+            |((pk, _), blinds, instance, witness)| {
+                reblind_with_loaded_data(
+                    PrepareCircuit::default(),
+                    pk,
+                    instance,
+                    witness,
+                    blinds,
+                    PREPARE_INSTANCE,
+                    PREPARE_WITNESS,
+                    PREPARE_PROOF,
+                )
+                .map_err(prove_failed)
+            },
         );
-        let reblind_prepare_ms = start.elapsed().as_millis() as u64;
 
         // Step 6: Prove Show Circuit
-        let start = std::time::Instant::now();
         let show_circuit = ShowCircuit::new(input_path.as_ref().map(PathBuf::from));
-        prove_circuit_with_pk(
-            show_circuit,
-            &show_pk,
-            SHOW_INSTANCE,
-            SHOW_WITNESS,
-            SHOW_PROOF,
+        let show_proved = steps.resumable(
+            "prove_show",
+            Some(SHOW_PROOF),
+            show_keys.as_ref(),
+            || Ok(()),
+            |(pk, _)| {
+                prove_circuit_with_pk(show_circuit, pk, SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF)
+                    .map_err(prove_failed)
+            },
         );
-        let prove_show_ms = start.elapsed().as_millis() as u64;
 
         // Step 7: Reblind Show
         // Load data before timing (file I/O should not be part of reblind benchmark)
-        let show_instance =
-            load_instance(SHOW_INSTANCE).map_err(|e| ZkProofError::FileNotFound {
-                message: format!("Failed to load show instance: {}", e),
-            })?;
-        let show_witness = load_witness(SHOW_WITNESS).map_err(|e| ZkProofError::FileNotFound {
-            message: format!("Failed to load show witness: {}", e),
-        })?;
-        // Reuse shared_blinds from Prepare step (already loaded)
-
-        let start = std::time::Instant::now();
-        reblind_with_loaded_data(
-            ShowCircuit::default(),
-            &show_pk,
-            show_instance,
-            show_witness,
-            &shared_blinds,
-            SHOW_INSTANCE,
-            SHOW_WITNESS,
-            SHOW_PROOF,
+        let reblind_show = steps.step(
+            "reblind_show",
+            Some(SHOW_PROOF),
+            show_keys
+                .as_ref()
+                .zip(shared_blinds.as_ref())
+                .zip(show_proved),
+            |((keys, blinds), ())| {
+                let (instance, witness) = load_instance_and_witness(SHOW_INSTANCE, SHOW_WITNESS)?;
+                Ok((keys, blinds, instance, witness))
+            },
+            |((pk, _), blinds, instance, witness)| {
+                reblind_with_loaded_data(
+                    ShowCircuit::default(),
+                    pk,
+                    instance,
+                    witness,
+                    blinds,
+                    SHOW_INSTANCE,
+                    SHOW_WITNESS,
+                    SHOW_PROOF,
+                )
+                .map_err(prove_failed)
+            },
         );
-        let reblind_show_ms = start.elapsed().as_millis() as u64;
 
         // Step 8: Verify Prepare
         // Load proof before timing (file I/O should not be part of verify benchmark)
-        let prepare_proof =
-            load_proof(PREPARE_PROOF).map_err(|e| ZkProofError::FileNotFound {
-                message: format!("Failed to load prepare proof: {}", e),
-            })?;
-
-        let start = std::time::Instant::now();
-        verify_circuit_with_loaded_data(&prepare_proof, &prepare_vk);
-        let verify_prepare_ms = start.elapsed().as_millis() as u64;
+        // A failed reblind leaves the proof from step 4 in place, so it is still verified
+        steps.step(
+            "verify_prepare",
+            None,
+            prepare_keys.as_ref().zip(prepare_proved),
+            |((_, vk), ())| {
+                let proof = load_proof::<E>(PREPARE_PROOF).map_err(|e| {
+                    ZkProofError::file_not_found(format!("Failed to load prepare proof: {}", e))
+                        .with_source(&*e)
+                })?;
+                Ok((proof, vk))
+            },
+            |(proof, vk)| {
+                verify_circuit_with_loaded_data(&proof, vk);
+                Ok(())
+            },
+        );
 
         // Step 9: Verify Show
         // Load proof before timing (file I/O should not be part of verify benchmark)
-        let show_proof = load_proof(SHOW_PROOF).map_err(|e| ZkProofError::FileNotFound {
-            message: format!("Failed to load show proof: {}", e),
-        })?;
+        steps.step(
+            "verify_show",
+            None,
+            show_keys.as_ref().zip(show_proved),
+            |((_, vk), ())| {
+                let proof = load_proof::<E>(SHOW_PROOF).map_err(|e| {
+                    ZkProofError::file_not_found(format!("Failed to load show proof: {}", e))
+                        .with_source(&*e)
+                })?;
+                Ok((proof, vk))
+            },
+            |(proof, vk)| {
+                verify_circuit_with_loaded_data(&proof, vk);
+                Ok(())
+            },
+        );
 
-        let start = std::time::Instant::now();
-        verify_circuit_with_loaded_data(&show_proof, &show_vk);
-        let verify_show_ms = start.elapsed().as_millis() as u64;
-
-        // Measure file sizes
-        let prepare_proving_key_bytes = get_proof_size(PREPARE_PROVING_KEY)?;
-        let prepare_verifying_key_bytes = get_proof_size(PREPARE_VERIFYING_KEY)?;
-        let show_proving_key_bytes = get_proof_size(SHOW_PROVING_KEY)?;
-        let show_verifying_key_bytes = get_proof_size(SHOW_VERIFYING_KEY)?;
-        let prepare_proof_bytes = get_proof_size(PREPARE_PROOF)?;
-        let show_proof_bytes = get_proof_size(SHOW_PROOF)?;
-        let prepare_witness_bytes = get_proof_size(PREPARE_WITNESS)?;
-        let show_witness_bytes = get_proof_size(SHOW_WITNESS)?;
+        // Measure file sizes; artifacts a failed step did not write count as 0
+        let size = |path: &str| get_proof_size(path).unwrap_or_default();
+        let stripped_size = |keys: Option<&(SpartanProvingKey, SpartanVerifyingKey)>| {
+            keys.and_then(|(pk, _)| compact_size(pk).ok())
+                .unwrap_or_default()
+        };
+        let error_summary = steps.error_summary();
 
         Ok(BenchmarkResults {
-            prepare_setup_ms,
-            show_setup_ms,
-            generate_blinds_ms,
-            prove_prepare_ms,
-            reblind_prepare_ms,
-            prove_show_ms,
-            reblind_show_ms,
-            verify_prepare_ms,
-            verify_show_ms,
-            prepare_proving_key_bytes,
-            prepare_verifying_key_bytes,
-            show_proving_key_bytes,
-            show_verifying_key_bytes,
-            prepare_proof_bytes,
-            show_proof_bytes,
-            prepare_witness_bytes,
-            show_witness_bytes,
+            prepare_setup_ms: steps.duration_ms("setup_prepare"),
+            show_setup_ms: steps.duration_ms("setup_show"),
+            generate_blinds_ms: steps.duration_ms("generate_blinds"),
+            prove_prepare_ms: steps.duration_ms("prove_prepare"),
+            reblind_prepare_ms: steps.duration_ms("reblind_prepare"),
+            prove_show_ms: steps.duration_ms("prove_show"),
+            reblind_show_ms: steps.duration_ms("reblind_show"),
+            verify_prepare_ms: steps.duration_ms("verify_prepare"),
+            verify_show_ms: steps.duration_ms("verify_show"),
+            reblind_prepare_recommit_ms: reblind_prepare.map_or(0, |t| t.recommit_ms),
+            reblind_prepare_prove_ms: reblind_prepare.map_or(0, |t| t.prove_ms),
+            reblind_show_recommit_ms: reblind_show.map_or(0, |t| t.recommit_ms),
+            reblind_show_prove_ms: reblind_show.map_or(0, |t| t.prove_ms),
+            prepare_setup_synthesis_ms: prepare_setup.synthesis_ms as u64,
+            prepare_setup_keygen_ms: prepare_setup.keygen_ms as u64,
+            prepare_key_save_ms: prepare_setup.save_ms as u64,
+            prepare_key_load_ms: steps.duration_ms("load_prepare_keys"),
+            show_setup_synthesis_ms: show_setup.synthesis_ms as u64,
+            show_setup_keygen_ms: show_setup.keygen_ms as u64,
+            show_key_save_ms: show_setup.save_ms as u64,
+            show_key_load_ms: steps.duration_ms("load_show_keys"),
+            prepare_proving_key_bytes: size(PREPARE_PROVING_KEY),
+            prepare_verifying_key_bytes: size(PREPARE_VERIFYING_KEY),
+            show_proving_key_bytes: size(SHOW_PROVING_KEY),
+            show_verifying_key_bytes: size(SHOW_VERIFYING_KEY),
+            prepare_proof_bytes: size(PREPARE_PROOF),
+            show_proof_bytes: size(SHOW_PROOF),
+            prepare_witness_bytes: size(PREPARE_WITNESS),
+            show_witness_bytes: size(SHOW_WITNESS),
+            prepare_proving_key_stripped_bytes: stripped_size(prepare_keys.as_ref()),
+            show_proving_key_stripped_bytes: stripped_size(show_keys.as_ref()),
+            steps: steps.into_steps(),
+            error_summary,
         })
     })
 }
 
+/// `run_complete_benchmark` with `options` in force for this call only
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn run_complete_benchmark_with_options(
+    documents_path: String,
+    input_path: Option<String>,
+    resume: bool,
+    options: ProveOptions,
+) -> Result<BenchmarkResults, ZkProofError> {
+    with_prove_options(options, || {
+        run_complete_benchmark(documents_path, input_path, resume)
+    })
+}
+
+/// Input JSON for `circuit_type` ("prepare" or "show") over a test credential
+/// and nonce drawn from `seed`
+/// Equal seeds give equal inputs, so a benchmark can prove a fresh witness per
+/// run and still repeat a run; the Prepare and Show inputs of a seed belong
+/// together. The Prepare input is for the selected JWT variant.
+#[cfg(feature = "test-issuer")]
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn generate_demo_inputs(seed: u64, circuit_type: String) -> Result<String, ZkProofError> {
+    let circuit: CircuitKind = circuit_type.parse().map_err(ZkProofError::invalid_input)?;
+    ecdsa_spartan2::issuer::demo_inputs::generate_demo_inputs(seed, circuit)
+        .map_err(ZkProofError::invalid_input)
+}
+
 // ============================================================================
 // Inspection Operations
 // ============================================================================
@@ -559,12 +1747,10 @@ pub fn get_comm_w_shared(
             "prepare" => PREPARE_INSTANCE,
             "show" => SHOW_INSTANCE,
             _ => {
-                return Err(ZkProofError::InvalidInput {
-                    message: format!(
-                        "Invalid circuit_type '{}'. Must be 'prepare' or 'show'",
-                        circuit_type
-                    ),
-                })
+                return Err(ZkProofError::invalid_input(format!(
+                    "Invalid circuit_type '{}'. Must be 'prepare' or 'show'",
+                    circuit_type
+                )))
             }
         };
 
@@ -572,32 +1758,688 @@ pub fn get_comm_w_shared(
     })
 }
 
+/// Size breakdown of a stored proof: components, sumcheck rounds, commitments
+/// and format version, as text or (with `as_json`) JSON
+/// For tracking down proof size regressions and serialization mismatches.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn inspect_proof(
+    documents_path: String,
+    circuit_type: String,
+    as_json: bool,
+) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let circuit: CircuitKind = circuit_type.parse().map_err(ZkProofError::invalid_input)?;
+        let proof_path = match circuit {
+            CircuitKind::Prepare => PREPARE_PROOF,
+            CircuitKind::Show => SHOW_PROOF,
+        };
+
+        let proof_path = &artifact_path(proof_path);
+        let bytes = std::fs::read(proof_path).map_err(|e| {
+            ZkProofError::file_not_found(format!(
+                "Failed to read proof from '{}': {}",
+                proof_path, e
+            ))
+            .with_source(&e)
+        })?;
+        let inspection = inspect_proof_bytes(&bytes).map_err(|e| {
+            ZkProofError::invalid_input(format!("Failed to decode proof: {}", e)).with_source(&*e)
+        })?;
+        if as_json {
+            serde_json::to_string_pretty(&inspection).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to serialize inspection: {}", e))
+                    .with_source(&e)
+            })
+        } else {
+            Ok(inspection.to_string())
+        }
+    })
+}
+
+/// JSON description of Prepare and Show verification for independent verifiers
+/// Covers the curve and field moduli, transcript hash and label, artifact
+/// encoding, public inputs, verifying key layout, and the sumcheck rounds and
+/// commitment sizes of the stored proofs, so a verifier written in another
+/// language can be cross-tested against proofs from this build.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn export_verifier_spec(documents_path: String) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let spec = describe_verification().map_err(|e| {
+            ZkProofError::setup_required(format!("Failed to describe verification: {}", e))
+                .with_source(&*e)
+        })?;
+        serde_json::to_string_pretty(&spec).map_err(|e| {
+            ZkProofError::io_error(format!("Failed to serialize verifier spec: {}", e))
+                .with_source(&e)
+        })
+    })
+}
+
+/// Dump the witness for `input_json` to `debug/` under `documents_path`
+/// `signal_names` labels each value from the circuit's `.sym` in `../circom`,
+/// and `filter` keeps only signals whose name contains it. Writes CSV with
+/// `as_csv`, JSON otherwise, and returns the path written.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn export_witness_debug(
+    documents_path: String,
+    circuit_type: String,
+    input_json: String,
+    signal_names: bool,
+    filter: Option<String>,
+    as_csv: bool,
+) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let circuit: CircuitKind = circuit_type.parse().map_err(ZkProofError::invalid_input)?;
+        let (format, ext) = if as_csv {
+            (DumpFormat::Csv, "csv")
+        } else {
+            (DumpFormat::Json, "json")
+        };
+        let output = format!("debug/{}_witness.{}", circuit.name(), ext);
+
+        write_witness_dump(
+            circuit,
+            input_json.as_ref(),
+            "../circom".as_ref(),
+            signal_names,
+            filter.as_deref(),
+            format,
+            output.as_ref(),
+        )
+        .map_err(|e| {
+            ZkProofError::invalid_input(format!("Failed to dump the witness: {}", e))
+                .with_source(&*e)
+        })?;
+        Ok(PathBuf::from(&documents_path)
+            .join(output)
+            .to_string_lossy()
+            .into_owned())
+    })
+}
+
+/// Versions of the library, prover and circuits in this build
+/// Include in support tickets and benchmark reports to identify what ran on the device.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn get_version_info() -> VersionInfo {
+    VersionInfo {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        spartan2_rev: build_info::SPARTAN2_REV.to_string(),
+        circuit_digests: [CircuitKind::Prepare, CircuitKind::Show]
+            .into_iter()
+            .map(|circuit| CircuitDigest {
+                circuit: circuit.name().to_string(),
+                r1cs_sha256: build_info::r1cs_digest(circuit).to_string(),
+            })
+            .collect(),
+        key_format_version: build_info::KEY_FORMAT_VERSION,
+        proof_format_version: format::CURRENT_VERSION,
+    }
+}
+
+// ============================================================================
+// Maintenance Operations
+// ============================================================================
+
+/// Rewrite stored proofs, instances, witnesses and shared blinds in the current format
+/// Call after an app update; returns the artifact paths that were migrated
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn migrate_artifacts(documents_path: String) -> Result<Vec<String>, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("migrate_artifacts");
+        run.phase("migrate", None, || {
+            ecdsa_spartan2::migrate_artifacts::<E>().map_err(|e| {
+                ZkProofError::io_error(format!("Failed to migrate artifacts: {}", e))
+                    .with_source(&*e)
+            })
+        })
+    })
+}
+
+/// Rewrite stored artifacts from format `from_version` (any, if unset) to `to_version`
+/// Unlike `migrate_artifacts`, artifacts and keys this build can no longer
+/// decode are reported in `regenerate` instead of failing the call, so the app
+/// can prove or set up again. Also rolls artifacts back to an older format.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn migrate_artifacts_between(
+    documents_path: String,
+    from_version: Option<u32>,
+    to_version: u32,
+) -> Result<MigrationResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("migrate_artifacts_between");
+        let report = run.phase("migrate", None, || {
+            ecdsa_spartan2::migrate_artifacts_between::<E>(from_version, to_version).map_err(|e| {
+                ZkProofError::io_error(format!("Failed to migrate artifacts: {}", e))
+                    .with_source(&*e)
+            })
+        })?;
+        Ok(MigrationResult {
+            rewritten: report.rewritten,
+            regenerate: report
+                .regenerate
+                .into_iter()
+                .map(|(path, reason)| RegenerationNeeded { path, reason })
+                .collect(),
+        })
+    })
+}
+
+/// Delete cached witnesses
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn clear_witness_cache(documents_path: String) -> Result<(), ZkProofError> {
+    with_working_dir(&documents_path, || {
+        Ok(ecdsa_spartan2::clear_witness_cache()?)
+    })
+}
+
 // ============================================================================
 // Internal Helper Functions
 // ============================================================================
 
+/// Deadline `max_duration_ms` after `start`, if a limit is set
+fn deadline_after(start: Instant, max_duration_ms: Option<u64>) -> Option<Instant> {
+    max_duration_ms.map(|ms| start + Duration::from_millis(ms))
+}
+
+/// A prove or reblind failure as the error the FFI reports for it
+fn prove_failed(e: ProveError) -> ZkProofError {
+    let message = format!("Prover {}", e);
+    match &e {
+        ProveError::TimedOut(_) => ZkProofError::timed_out(message),
+        ProveError::Load { .. } => ZkProofError::setup_required(message).with_source(&e),
+        ProveError::Save { .. } => ZkProofError::io_error(message).with_source(&e),
+        ProveError::Synthesis(_) | ProveError::Spartan(_) => {
+            ZkProofError::proof_generation_failed(message).with_source(&e)
+        }
+    }
+}
+
+/// Set up `circuit`'s keys and save them to `pk_path` and `vk_path`
+pub(crate) fn setup_and_save_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk_path: &str,
+    vk_path: &str,
+) -> Result<(), ZkProofError> {
+    let (keys, _) = setup_circuit_keys_timed::<E, C>(circuit).map_err(|e| {
+        ZkProofError::proof_generation_failed(format!("Setup failed: {}", e)).with_source(&e)
+    })?;
+    save_keys_timed::<E>(pk_path, vk_path, &keys).map_err(|e| {
+        ZkProofError::io_error(format!("Failed to save keys to '{}': {}", pk_path, e))
+            .with_source(&*e)
+    })?;
+    Ok(())
+}
+
+/// Run a prove, turning a panic caused by a failed constraint check into an error
+/// Other panics propagate unchanged.
+fn locate_constraint_failure<T>(
+    prove: impl FnOnce() -> Result<T, ZkProofError>,
+) -> Result<T, ZkProofError> {
+    // Drop a failure left over from an earlier prove
+    take_constraint_failure();
+    catch_unwind(AssertUnwindSafe(prove)).unwrap_or_else(|panic| match take_constraint_failure() {
+        Some(failure) => Err(ZkProofError::proof_generation_failed(format!(
+            "Witness does not satisfy the circuit: {}",
+            failure
+        ))
+        .with_source(&failure)),
+        None => resume_unwind(panic),
+    })
+}
+
 /// Extract comm_W_shared from a saved instance file
 fn extract_comm_w_shared(instance_path: &str) -> Result<String, ZkProofError> {
-    use ecdsa_spartan2::setup::load_instance;
+    use ecdsa_spartan2::setup::{load_instance, load_public_instance};
 
-    let instance = load_instance(instance_path).map_err(|e| ZkProofError::FileNotFound {
-        message: format!("Failed to load instance from '{}': {}", instance_path, e),
+    // The public part holds comm_W_shared as a hex string; instances saved
+    // before the split only have the full instance
+    if let Ok(public) = load_public_instance::<E>(instance_path) {
+        return Ok(public.comm_w_shared);
+    }
+    let instance = load_instance::<E>(instance_path).map_err(|e| {
+        ZkProofError::file_not_found(format!(
+            "Failed to load instance from '{}': {}",
+            instance_path, e
+        ))
+        .with_source(&*e)
+    })?;
+    Ok(format!("{:?}", instance.comm_W_shared))
+}
+
+/// Sample fresh shared blinds and reblind both circuits in the current directory
+fn reblind_all_in_cwd(rng: &mut dyn CryptoRngCore) -> Result<ReblindAllResult, ZkProofError> {
+    let run = RunLog::start("reblind_all");
+    run.phase("generate_blinds", Some(SHARED_BLINDS), || {
+        generate_shared_blinds_with_rng::<E>(SHARED_BLINDS, NUM_SHARED, rng).map_err(prove_failed)
+    })?;
+    let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+            .with_source(&*e)
     })?;
 
-    // Convert comm_W_shared to hex string
-    let comm_w_shared_hex = format!("{:?}", instance.comm_W_shared);
-    Ok(comm_w_shared_hex)
+    let prepare = run.phase("reblind_prepare", Some(PREPARE_PROOF), || {
+        reblind_timed(
+            PrepareCircuit::default(),
+            PREPARE_PROVING_KEY,
+            &shared_blinds,
+            PREPARE_INSTANCE,
+            PREPARE_WITNESS,
+            PREPARE_PROOF,
+        )
+    })?;
+    let show = run.phase("reblind_show", Some(SHOW_PROOF), || {
+        reblind_timed(
+            ShowCircuit::default(),
+            SHOW_PROVING_KEY,
+            &shared_blinds,
+            SHOW_INSTANCE,
+            SHOW_WITNESS,
+            SHOW_PROOF,
+        )
+    })?;
+
+    if prepare.comm_w_shared != show.comm_w_shared {
+        return Err(ZkProofError::verification_failed(
+            "Prepare and Show comm_W_shared differ after reblinding".to_string(),
+        ));
+    }
+    reset_presentation_counter(&artifact_namespace())?;
+
+    Ok(ReblindAllResult {
+        comm_w_shared: prepare.comm_w_shared.clone(),
+        prepare,
+        show,
+    })
+}
+
+/// Load a proving key and reblind one circuit, timing only the reblind itself
+fn reblind_timed<C: SpartanCircuit<E>>(
+    circuit: C,
+    pk_path: &str,
+    shared_blinds: &[Scalar],
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+) -> Result<ProofResult, ZkProofError> {
+    let pk = load_spartan_proving_key::<E>(pk_path).map_err(|e| {
+        ZkProofError::setup_required(format!(
+            "Failed to load proving key from '{}': {}",
+            pk_path, e
+        ))
+        .with_source(&*e)
+    })?;
+    reblind_timed_with_pk(
+        circuit,
+        &pk,
+        shared_blinds,
+        instance_path,
+        witness_path,
+        proof_path,
+    )
+}
+
+/// `reblind_timed` with an already loaded proving key
+fn reblind_timed_with_pk<C: SpartanCircuit<E>>(
+    circuit: C,
+    pk: &SpartanProvingKey,
+    shared_blinds: &[Scalar],
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+) -> Result<ProofResult, ZkProofError> {
+    let start = std::time::Instant::now();
+    reblind_loaded(
+        circuit,
+        pk,
+        shared_blinds,
+        instance_path,
+        witness_path,
+        proof_path,
+    )?;
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+
+    Ok(ProofResult {
+        prep_ms: 0,
+        prove_ms: elapsed_ms,
+        total_ms: elapsed_ms,
+        proof_size_bytes: get_proof_size(proof_path)?,
+        comm_w_shared: extract_comm_w_shared(instance_path)?,
+    })
+}
+
+/// Apply the presentation policy and count the presentation
+/// Returns whether fresh blinds were sampled and the updated presentation count
+fn present_in_cwd() -> Result<(bool, u64), ZkProofError> {
+    let state = PresentationState::load()?;
+    let reblinded = state.should_reblind();
+    if reblinded {
+        reblind_all_in_cwd(&mut *get_prove_options().rng_source().into_rng())?;
+    }
+
+    // Reload: reblinding resets the counter
+    let mut state = PresentationState::load()?;
+    state.presentations_with_current_blinds += 1;
+    state.save()?;
+    Ok((reblinded, state.presentations_with_current_blinds))
+}
+
+/// Record that the shared blinds of the credential in `namespace` were just resampled
+fn reset_presentation_counter(namespace: &ArtifactNamespace) -> Result<(), ZkProofError> {
+    let mut state = PresentationState::load_in(namespace)?;
+    state.presentations_with_current_blinds = 0;
+    state.save_in(namespace)
+}
+
+/// Runs the steps of `run_complete_benchmark`, recording each outcome instead
+/// of stopping at the first failure
+struct BenchmarkSteps<'a> {
+    run: &'a RunLog,
+    steps: Vec<BenchmarkStep>,
+    checkpoint: Checkpoint,
+    resume: bool,
+}
+
+impl<'a> BenchmarkSteps<'a> {
+    fn new(run: &'a RunLog, checkpoint: Checkpoint, resume: bool) -> Self {
+        Self {
+            run,
+            steps: Vec::new(),
+            checkpoint,
+            resume,
+        }
+    }
+
+    /// `step`, unless resuming and the checkpoint shows an earlier run's result
+    /// is intact; `reload` then reads that result back, untimed
+    fn resumable<I, T>(
+        &mut self,
+        name: &str,
+        artifact: Option<&str>,
+        inputs: Option<I>,
+        reload: impl FnOnce() -> Result<T, ZkProofError>,
+        timed: impl FnOnce(I) -> Result<T, ZkProofError>,
+    ) -> Option<T> {
+        let reusable = if self.resume && inputs.is_some() {
+            self.checkpoint.reusable(name)
+        } else {
+            None
+        };
+        if let Some(duration_ms) = reusable {
+            match step_outcome(catch_unwind(AssertUnwindSafe(reload))) {
+                Ok(value) => {
+                    self.record(name, BenchmarkStepStatus::Reused, duration_ms, None);
+                    return Some(value);
+                }
+                Err(error) => tracing::warn!("Running benchmark step {} again: {}", name, error),
+            }
+        }
+        self.step(name, artifact, inputs, Ok, timed)
+    }
+
+    /// Run step `name` on `inputs`, or skip it if they are missing because a
+    /// step it depends on did not succeed
+    /// Only `timed` counts towards the step's duration; `load` is its file I/O.
+    fn step<I, P, T>(
+        &mut self,
+        name: &str,
+        artifact: Option<&str>,
+        inputs: Option<I>,
+        load: impl FnOnce(I) -> Result<P, ZkProofError>,
+        timed: impl FnOnce(P) -> Result<T, ZkProofError>,
+    ) -> Option<T> {
+        let Some(inputs) = inputs else {
+            self.record(name, BenchmarkStepStatus::Skipped, 0, None);
+            return None;
+        };
+        let run = self.run;
+        let mut timed_from = None;
+        let outcome = catch_unwind(AssertUnwindSafe(|| {
+            let loaded = load(inputs)?;
+            timed_from = Some(Instant::now());
+            run.phase(name, artifact, || timed(loaded))
+        }));
+        let duration_ms = timed_from.map_or(0, |t| t.elapsed().as_millis() as u64);
+
+        match step_outcome(outcome) {
+            Ok(value) => {
+                self.record(name, BenchmarkStepStatus::Succeeded, duration_ms, None);
+                self.checkpoint.record(name, duration_ms);
+                Some(value)
+            }
+            Err(error) => {
+                self.record(name, BenchmarkStepStatus::Failed, duration_ms, Some(error));
+                None
+            }
+        }
+    }
+
+    /// Untimed follow-up to the last step, e.g. saving what it produced; if it
+    /// fails, so does that step
+    fn then<T, U>(
+        &mut self,
+        value: Option<T>,
+        f: impl FnOnce(T) -> Result<U, ZkProofError>,
+    ) -> Option<U> {
+        let value = value?;
+        match step_outcome(catch_unwind(AssertUnwindSafe(|| f(value)))) {
+            Ok(value) => {
+                // Checkpoint what the follow-up wrote, unless the step was reused
+                if let Some(step) = self
+                    .steps
+                    .last()
+                    .filter(|step| step.status == BenchmarkStepStatus::Succeeded)
+                {
+                    let (name, duration_ms) = (step.name.clone(), step.duration_ms);
+                    self.checkpoint.record(&name, duration_ms);
+                }
+                Some(value)
+            }
+            Err(error) => {
+                if let Some(step) = self.steps.last_mut() {
+                    step.status = BenchmarkStepStatus::Failed;
+                    step.error = Some(error);
+                }
+                None
+            }
+        }
+    }
+
+    /// `then` for saving what the last step produced; a reused step's output
+    /// is already saved
+    fn save<T>(
+        &mut self,
+        value: Option<T>,
+        save: impl FnOnce(&T) -> Result<(), ZkProofError>,
+    ) -> Option<T> {
+        if self
+            .steps
+            .last()
+            .is_some_and(|step| step.status == BenchmarkStepStatus::Reused)
+        {
+            return value;
+        }
+        self.then(value, |value| {
+            save(&value)?;
+            Ok(value)
+        })
+    }
+
+    fn record(
+        &mut self,
+        name: &str,
+        status: BenchmarkStepStatus,
+        duration_ms: u64,
+        error: Option<String>,
+    ) {
+        self.steps.push(BenchmarkStep {
+            name: name.to_string(),
+            status,
+            duration_ms,
+            error,
+        });
+    }
+
+    /// Duration of step `name`, 0 unless it succeeded or was reused
+    fn duration_ms(&self, name: &str) -> u64 {
+        self.steps
+            .iter()
+            .find(|step| {
+                step.name == name
+                    && matches!(
+                        step.status,
+                        BenchmarkStepStatus::Succeeded | BenchmarkStepStatus::Reused
+                    )
+            })
+            .map_or(0, |step| step.duration_ms)
+    }
+
+    fn error_summary(&self) -> Option<String> {
+        let failures: Vec<String> = self
+            .steps
+            .iter()
+            .filter_map(|step| {
+                step.error
+                    .as_ref()
+                    .map(|error| format!("{}: {}", step.name, error))
+            })
+            .collect();
+        (!failures.is_empty()).then(|| failures.join("\n"))
+    }
+
+    fn into_steps(self) -> Vec<BenchmarkStep> {
+        self.steps
+    }
+}
+
+/// Keys of `circuit` set up in memory, with synthesis and key generation timed apart
+fn setup_benchmark_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+) -> Result<((SpartanProvingKey, SpartanVerifyingKey), SetupTimings), ZkProofError> {
+    setup_circuit_keys_timed::<E, C>(circuit).map_err(|e| {
+        ZkProofError::proof_generation_failed(format!("Setup failed: {:?}", e)).with_source(&e)
+    })
+}
+
+/// Proving and verifying keys saved by an earlier benchmark run
+fn load_benchmark_keys(
+    pk_path: &str,
+    vk_path: &str,
+) -> Result<(SpartanProvingKey, SpartanVerifyingKey), ZkProofError> {
+    load_keys::<E>(pk_path, vk_path).map_err(|e| {
+        ZkProofError::setup_required(format!("Failed to load keys from '{}': {}", pk_path, e))
+            .with_source(&*e)
+    })
+}
+
+/// The value of a step, or its error or panic message
+fn step_outcome<T>(outcome: std::thread::Result<Result<T, ZkProofError>>) -> Result<T, String> {
+    match outcome {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(panic) => Err(format!("panic: {}", run_log::panic_message(panic.as_ref()))),
+    }
+}
+
+/// Join a prover thread, turning a panic into an error
+fn join_prover(
+    handle: std::thread::ScopedJoinHandle<'_, Result<u64, ZkProofError>>,
+    name: &str,
+) -> Result<u64, ZkProofError> {
+    handle.join().map_err(|_| {
+        ZkProofError::proof_generation_failed(format!("{} prover thread panicked", name))
+    })?
+}
+
+/// Reblind a freshly proved circuit using an already-loaded proving key and blinds
+fn reblind_loaded<C: SpartanCircuit<E>>(
+    circuit: C,
+    pk: &SpartanProvingKey,
+    shared_blinds: &[Scalar],
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+) -> Result<(), ZkProofError> {
+    let (instance, witness) = load_instance_and_witness(instance_path, witness_path)?;
+
+    reblind_with_loaded_data(
+        circuit,
+        pk,
+        instance,
+        witness,
+        shared_blinds,
+        instance_path,
+        witness_path,
+        proof_path,
+    )
+    .map_err(prove_failed)?;
+    Ok(())
+}
+
+/// Load the instance and witness at `instance_path` and `witness_path` concurrently
+/// Each is a single bincode value, so only the two decodes overlap.
+fn load_instance_and_witness(
+    instance_path: &str,
+    witness_path: &str,
+) -> Result<(SplitR1CSInstance<E>, R1CSWitness<E>), ZkProofError> {
+    let (instance, witness) = rayon::join(
+        || {
+            load_instance::<E>(instance_path).map_err(|e| {
+                ZkProofError::file_not_found(format!(
+                    "Failed to load instance from '{}': {}",
+                    instance_path, e
+                ))
+                .with_source(&*e)
+            })
+        },
+        || {
+            load_witness::<E>(witness_path).map_err(|e| {
+                ZkProofError::file_not_found(format!(
+                    "Failed to load witness from '{}': {}",
+                    witness_path, e
+                ))
+                .with_source(&*e)
+            })
+        },
+    );
+    Ok((instance?, witness?))
 }
 
 /// Get the size of a proof file in bytes
 fn get_proof_size(proof_path: &str) -> Result<u64, ZkProofError> {
-    let metadata = std::fs::metadata(proof_path).map_err(|e| ZkProofError::FileNotFound {
-        message: format!("Failed to get proof size from '{}': {}", proof_path, e),
+    let proof_path = &artifact_path(proof_path);
+    let metadata = std::fs::metadata(proof_path).map_err(|e| {
+        ZkProofError::file_not_found(format!(
+            "Failed to get proof size from '{}': {}",
+            proof_path, e
+        ))
+        .with_source(&e)
     })?;
 
     Ok(metadata.len())
 }
 
+// ============================================================================
+// Memory Profiling
+// ============================================================================
+
+/// Start a dhat heap profile (requires the `mem-profile` feature)
+/// Per-phase snapshots and the dhat profile are written under `output_dir`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn start_memory_profile(output_dir: String) -> Result<(), ZkProofError> {
+    ecdsa_spartan2::mem_profile::start(&output_dir).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to start memory profile: {}", e))
+            .with_source(&*e)
+    })
+}
+
+/// Stop the running heap profile and write its output files
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn stop_memory_profile() -> Result<(), ZkProofError> {
+    ecdsa_spartan2::mem_profile::stop().map_err(|e| {
+        ZkProofError::io_error(format!("Failed to write memory profile: {}", e)).with_source(&*e)
+    })
+}
+
 // ============================================================================
 // Legacy Test Function
 // ============================================================================
@@ -639,9 +2481,36 @@ mod tests {
         assert_eq!(original_dir, after_dir);
     }
 
+    #[test]
+    fn test_spartan_failure_is_reported_with_its_cause() {
+        use ecdsa_spartan2::spartan2::errors::SpartanError;
+
+        let err = prove_failed(ProveError::Spartan(SpartanError::SynthesisError {
+            reason: "public IO missing".to_string(),
+        }));
+        assert!(matches!(err, ZkProofError::ProofGenerationFailed { .. }));
+        assert!(err
+            .causes()
+            .iter()
+            .any(|cause| cause.origin == ErrorOrigin::Spartan && cause.kind == "SynthesisError"));
+    }
+
     #[test]
     fn test_invalid_circuit_type() {
         let result = get_comm_w_shared(".".to_string(), "invalid".to_string());
         assert!(matches!(result, Err(ZkProofError::InvalidInput { .. })));
     }
+
+    #[test]
+    fn test_version_info() {
+        let info = get_version_info();
+        assert_eq!(info.crate_version, env!("CARGO_PKG_VERSION"));
+        let circuits: Vec<_> = info
+            .circuit_digests
+            .iter()
+            .map(|d| d.circuit.as_str())
+            .collect();
+        assert_eq!(circuits, ["prepare", "show"]);
+        assert_eq!(info.proof_format_version, format::CURRENT_VERSION);
+    }
 }
@@ -0,0 +1,202 @@
+//! Bridge `tracing` events to the host platform.
+//!
+//! The prover emits `tracing::info!` events that are invisible on mobile unless a
+//! subscriber is installed. `init_logging` installs one that forwards formatted
+//! events to a foreign callback and, with the `platform-log` feature, to os_log
+//! (iOS) or logcat (Android).
+
+use std::{
+    fmt::Write as _,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+use tracing::{
+    field::{Field, Visit},
+    Event, Subscriber,
+};
+use tracing_subscriber::{
+    filter::LevelFilter,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    reload,
+    util::SubscriberInitExt,
+    Layer, Registry,
+};
+
+use crate::ZkProofError;
+
+/// Receives formatted log events from the native library
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait LogCallback: Send + Sync {
+    fn on_log(&self, level: String, target: String, message: String);
+}
+
+static LEVEL_HANDLE: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+static CALLBACK: RwLock<Option<Arc<dyn LogCallback>>> = RwLock::new(None);
+
+/// Install the global log subscriber
+/// Safe to call more than once: later calls replace the callback and update the level
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn init_logging(
+    level: String,
+    callback: Option<Arc<dyn LogCallback>>,
+) -> Result<(), ZkProofError> {
+    let filter = parse_level(&level)?;
+
+    if let Ok(mut slot) = CALLBACK.write() {
+        *slot = callback;
+    }
+
+    if LEVEL_HANDLE.get().is_some() {
+        return set_log_level(level);
+    }
+
+    let (filter_layer, handle) = reload::Layer::new(filter);
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(CallbackLayer)
+        .with(platform_layer())
+        .try_init()
+        .map_err(|e| {
+            ZkProofError::invalid_input(format!("Failed to install log subscriber: {}", e))
+                .with_source(&e)
+        })?;
+
+    let _ = LEVEL_HANDLE.set(handle);
+    Ok(())
+}
+
+/// Change the log level at runtime
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_log_level(level: String) -> Result<(), ZkProofError> {
+    let filter = parse_level(&level)?;
+    let handle = LEVEL_HANDLE.get().ok_or_else(|| {
+        ZkProofError::setup_required("init_logging must be called before set_log_level".to_string())
+    })?;
+
+    handle.modify(|current| *current = filter).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to update log level: {}", e)).with_source(&e)
+    })
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, ZkProofError> {
+    level.trim().parse::<LevelFilter>().map_err(|_| {
+        ZkProofError::invalid_input(format!(
+            "Invalid log level '{}'. Must be one of off|error|warn|info|debug|trace",
+            level
+        ))
+    })
+}
+
+/// Forwards every event to the registered `LogCallback`
+struct CallbackLayer;
+
+impl<S> Layer<S> for CallbackLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let callback = match CALLBACK.read() {
+            Ok(slot) => match slot.as_ref() {
+                Some(callback) => callback.clone(),
+                None => return,
+            },
+            Err(_) => return,
+        };
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        callback.on_log(
+            metadata.level().to_string(),
+            metadata.target().to_string(),
+            visitor.finish(),
+        );
+    }
+}
+
+/// Formats the `message` field followed by the remaining fields as `key=value`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        if self.fields.is_empty() {
+            self.message
+        } else if self.message.is_empty() {
+            self.fields
+        } else {
+            format!("{} {}", self.message, self.fields)
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+}
+
+#[cfg(all(feature = "platform-log", target_os = "android"))]
+fn platform_layer<S>() -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    Some(Box::new(paranoid_android::layer("zkid")))
+}
+
+#[cfg(all(feature = "platform-log", any(target_os = "ios", target_os = "macos")))]
+fn platform_layer<S>() -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    Some(Box::new(tracing_oslog::OsLogger::new(
+        "org.pse.zkid",
+        "prover",
+    )))
+}
+
+#[cfg(not(all(
+    feature = "platform-log",
+    any(target_os = "android", target_os = "ios", target_os = "macos")
+)))]
+fn platform_layer<S>() -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("info").unwrap(), LevelFilter::INFO);
+        assert_eq!(parse_level(" DEBUG ").unwrap(), LevelFilter::DEBUG);
+        assert!(matches!(
+            parse_level("loud"),
+            Err(ZkProofError::InvalidInput { .. })
+        ));
+    }
+}
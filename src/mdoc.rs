@@ -0,0 +1,273 @@
+//! ISO 18013-5 style `DeviceResponse` carrying a ZK presentation.
+//!
+//! Lets proximity (mdoc) verifiers reuse their CBOR parsing and only swap the
+//! authentication checks. The response follows ISO 18013-5 §8.3.2.1.2.2, with
+//! two differences:
+//!
+//! - `issuerSigned` has no `issuerAuth`. Issuer authenticity comes from the
+//!   Prepare proof over the SD-JWT, not from a signed MSO, so the `random` of
+//!   each `IssuerSignedItem` is fresh and not matched against any digest.
+//! - `deviceAuth` holds a `zkProof` entry instead of `deviceSignature` or
+//!   `deviceMac`:
+//!
+//! ```text
+//! ZkProof = {
+//!   "version": 1,
+//!   "scheme": "spartan2-hyrax-sdjwt",
+//!   "verifierNonce": bstr,
+//!   "prepareProof": bstr, "prepareInstance": bstr,
+//!   "showProof": bstr, "showInstance": bstr,
+//!   "commWShared": tstr,
+//!   "validity": { "issuedAt": uint, "expiresAt": uint },
+//!   ? "additionalShows": [* { "label": tstr, "showProof": bstr, "showInstance": bstr }]
+//! }
+//! ```
+//!
+//! `additionalShows` is present only when the bundle carries more than one Show
+//! proof. A verifier checks `zkProof` like a `PresentationBundle`, and accepts
+//! the disclosed elements only if it does.
+
+use ciborium::Value;
+use rand_core::RngCore;
+
+use crate::{presentation_flow::PresentationBundle, RngSource, ZkProofError};
+
+/// `version` of the `zkProof` extension
+pub const ZK_PROOF_EXTENSION_VERSION: u64 = 1;
+
+/// `scheme` of the `zkProof` extension
+const ZK_PROOF_SCHEME: &str = "spartan2-hyrax-sdjwt";
+
+/// CBOR tag for an embedded encoded data item (`#6.24(bstr .cbor ...)`)
+const TAG_ENCODED_CBOR: u64 = 24;
+
+/// Bytes of `random` in each `IssuerSignedItem`, the minimum ISO 18013-5 allows
+const ITEM_RANDOM_BYTES: usize = 16;
+
+/// Value of a disclosed data element
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum MdocElementValue {
+    Bool { value: bool },
+    Int { value: i64 },
+    Text { value: String },
+    Bytes { value: Vec<u8> },
+}
+
+/// A data element disclosed alongside the proof, e.g. `age_over_18`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct MdocDataElement {
+    /// e.g. "org.iso.18013.5.1"
+    pub namespace: String,
+    pub identifier: String,
+    pub value: MdocElementValue,
+}
+
+/// Encode `bundle` and `elements` as a CBOR `DeviceResponse` with one document
+/// Elements are grouped by namespace in the order given.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn encode_device_response(
+    bundle: PresentationBundle,
+    doc_type: String,
+    elements: Vec<MdocDataElement>,
+) -> Result<Vec<u8>, ZkProofError> {
+    if doc_type.is_empty() {
+        return Err(ZkProofError::invalid_input("doc_type must not be empty"));
+    }
+
+    let mut rng = RngSource::Os.into_rng();
+    let mut namespaces: Vec<(String, Vec<Value>)> = Vec::new();
+    for (digest_id, element) in elements.into_iter().enumerate() {
+        let mut random = [0u8; ITEM_RANDOM_BYTES];
+        rng.fill_bytes(&mut random);
+        let item = map([
+            ("digestID", Value::from(digest_id as u64)),
+            ("random", Value::Bytes(random.to_vec())),
+            ("elementIdentifier", Value::Text(element.identifier)),
+            ("elementValue", element.value.into()),
+        ]);
+        let item = embedded(&item)?;
+        match namespaces
+            .iter_mut()
+            .find(|(ns, _)| *ns == element.namespace)
+        {
+            Some((_, items)) => items.push(item),
+            None => namespaces.push((element.namespace, vec![item])),
+        }
+    }
+
+    let additional_shows: Vec<Value> = bundle
+        .additional_shows
+        .into_iter()
+        .map(|show| {
+            map([
+                ("label", Value::Text(show.label)),
+                ("showProof", Value::Bytes(show.show_proof)),
+                ("showInstance", Value::Bytes(show.show_instance)),
+            ])
+        })
+        .collect();
+    let mut zk_proof = map([
+        ("version", Value::from(ZK_PROOF_EXTENSION_VERSION)),
+        ("scheme", Value::Text(ZK_PROOF_SCHEME.to_string())),
+        ("verifierNonce", Value::Bytes(bundle.verifier_nonce)),
+        ("prepareProof", Value::Bytes(bundle.prepare_proof)),
+        ("prepareInstance", Value::Bytes(bundle.prepare_instance)),
+        ("showProof", Value::Bytes(bundle.show_proof)),
+        ("showInstance", Value::Bytes(bundle.show_instance)),
+        ("commWShared", Value::Text(bundle.comm_w_shared)),
+        (
+            "validity",
+            map([
+                (
+                    "issuedAt",
+                    Value::from(bundle.prepare_freshness.issued_at_unix),
+                ),
+                (
+                    "expiresAt",
+                    Value::from(bundle.prepare_freshness.expires_at_unix),
+                ),
+            ]),
+        ),
+    ]);
+
+    if !additional_shows.is_empty() {
+        if let Value::Map(entries) = &mut zk_proof {
+            entries.push((
+                Value::Text("additionalShows".to_string()),
+                Value::Array(additional_shows),
+            ));
+        }
+    }
+
+    let document = map([
+        ("docType", Value::Text(doc_type)),
+        (
+            "issuerSigned",
+            map([(
+                "nameSpaces",
+                Value::Map(
+                    namespaces
+                        .into_iter()
+                        .map(|(ns, items)| (Value::Text(ns), Value::Array(items)))
+                        .collect(),
+                ),
+            )]),
+        ),
+        (
+            "deviceSigned",
+            map([
+                ("nameSpaces", embedded(&Value::Map(Vec::new()))?),
+                ("deviceAuth", map([("zkProof", zk_proof)])),
+            ]),
+        ),
+    ]);
+
+    let response = map([
+        ("version", Value::Text("1.0".to_string())),
+        ("documents", Value::Array(vec![document])),
+        ("status", Value::from(0u64)),
+    ]);
+    to_cbor(&response)
+}
+
+impl From<MdocElementValue> for Value {
+    fn from(value: MdocElementValue) -> Self {
+        match value {
+            MdocElementValue::Bool { value } => Value::Bool(value),
+            MdocElementValue::Int { value } => Value::from(value),
+            MdocElementValue::Text { value } => Value::Text(value),
+            MdocElementValue::Bytes { value } => Value::Bytes(value),
+        }
+    }
+}
+
+fn map<const N: usize>(entries: [(&str, Value); N]) -> Value {
+    Value::Map(
+        entries
+            .into_iter()
+            .map(|(key, value)| (Value::Text(key.to_string()), value))
+            .collect(),
+    )
+}
+
+/// `#6.24(bstr .cbor value)`
+fn embedded(value: &Value) -> Result<Value, ZkProofError> {
+    Ok(Value::Tag(
+        TAG_ENCODED_CBOR,
+        Box::new(Value::Bytes(to_cbor(value)?)),
+    ))
+}
+
+fn to_cbor(value: &Value) -> Result<Vec<u8>, ZkProofError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)
+        .map_err(|e| ZkProofError::io_error(format!("Failed to encode CBOR: {}", e)))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProofFreshness;
+
+    fn get<'a>(value: &'a Value, key: &str) -> &'a Value {
+        value
+            .as_map()
+            .unwrap()
+            .iter()
+            .find(|(k, _)| k.as_text() == Some(key))
+            .map(|(_, v)| v)
+            .unwrap_or_else(|| panic!("missing {}", key))
+    }
+
+    #[test]
+    fn test_device_response_layout() {
+        let bundle = PresentationBundle {
+            verifier_nonce: b"nonce".to_vec(),
+            prepare_proof: vec![1],
+            prepare_instance: vec![2],
+            show_proof: vec![3],
+            show_instance: vec![4],
+            additional_shows: Vec::new(),
+            comm_w_shared: "0xabc".to_string(),
+            reblinded: false,
+            prepare_freshness: ProofFreshness {
+                issued_at_unix: 10,
+                expires_at_unix: 20,
+            },
+        };
+        let elements = vec![MdocDataElement {
+            namespace: "org.iso.18013.5.1".to_string(),
+            identifier: "age_over_18".to_string(),
+            value: MdocElementValue::Bool { value: true },
+        }];
+
+        let bytes =
+            encode_device_response(bundle, "org.iso.18013.5.1.mDL".to_string(), elements).unwrap();
+        let response: Value = ciborium::from_reader(bytes.as_slice()).unwrap();
+        assert_eq!(get(&response, "version").as_text(), Some("1.0"));
+
+        let document = &get(&response, "documents").as_array().unwrap()[0];
+        let namespaces = get(get(document, "issuerSigned"), "nameSpaces");
+        let Value::Tag(TAG_ENCODED_CBOR, item) =
+            &get(namespaces, "org.iso.18013.5.1").as_array().unwrap()[0]
+        else {
+            panic!("IssuerSignedItem is not tagged as embedded CBOR");
+        };
+        let item: Value = ciborium::from_reader(item.as_bytes().unwrap().as_slice()).unwrap();
+        assert_eq!(
+            get(&item, "elementIdentifier").as_text(),
+            Some("age_over_18")
+        );
+        assert_eq!(
+            get(&item, "random").as_bytes().unwrap().len(),
+            ITEM_RANDOM_BYTES
+        );
+
+        let zk_proof = get(get(get(document, "deviceSigned"), "deviceAuth"), "zkProof");
+        assert_eq!(get(zk_proof, "showProof").as_bytes(), Some(&vec![3]));
+        assert_eq!(get(zk_proof, "commWShared").as_text(), Some("0xabc"));
+    }
+}
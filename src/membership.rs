@@ -0,0 +1,190 @@
+//! Set-membership proofs of a claim value against a published Merkle root.
+//!
+//! A verifier publishes a set (e.g. the issuers on a trust list) and the
+//! holder proves the Show input's claim value is one of its members with the
+//! Membership circuit, without revealing which. Members are passed as the
+//! strings the claim value is compared against; both sides build the same
+//! tree from them, so the verifier checks the proof against the root of the
+//! set it published. Like a Range proof, the Membership proof is reblinded
+//! under the shared blinds and must share comm_W_shared with the Prepare proof.
+
+use std::path::Path;
+
+use ecdsa_spartan2::{
+    claims::disclosure_value,
+    load_proof, load_proving_key, load_shared_blinds,
+    membership::root_from_public_values,
+    prover::prove_circuit_with_pk,
+    setup::{
+        MEMBERSHIP_INSTANCE, MEMBERSHIP_PROOF, MEMBERSHIP_PROVING_KEY, MEMBERSHIP_VERIFYING_KEY,
+        MEMBERSHIP_WITNESS, PREPARE_INSTANCE, PREPARE_PROOF, SHARED_BLINDS,
+    },
+    MembershipCircuit, MerkleSet, Scalar, E,
+};
+
+use crate::{
+    device_key::load_show_input, extract_comm_w_shared, get_proof_size, prove_failed,
+    reblind_loaded, run_log::RunLog, setup_and_save_keys, verify_result_with, with_working_dir,
+    ProofResult, VerifyResult, ZkProofError,
+};
+
+/// Setup Membership circuit keys
+/// The keys do not depend on the set; the Show input's own claim value is
+/// used as a one-member set to build the setup input.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_membership_keys(documents_path: String) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let show_input = load_show_input(None)?;
+        let value = show_input
+            .get("claim")
+            .ok_or_else(|| ZkProofError::invalid_input("Show input has no claim"))
+            .and_then(|claim| disclosure_value(claim).map_err(ZkProofError::invalid_input))?;
+        let circuit = MerkleSet::new(vec![value])
+            .and_then(|set| MembershipCircuit::from_show_input(&show_input, &set))
+            .map_err(ZkProofError::invalid_input)?;
+        let run = RunLog::start("setup_membership_keys");
+
+        let start = std::time::Instant::now();
+        run.phase("setup", Some(MEMBERSHIP_PROVING_KEY), || {
+            setup_and_save_keys(circuit, MEMBERSHIP_PROVING_KEY, MEMBERSHIP_VERIFYING_KEY)
+        })?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        Ok(format!(
+            "Membership circuit keys setup completed in {}ms",
+            elapsed_ms
+        ))
+    })
+}
+
+/// Merkle root of `members`, as the verifier publishes it
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn membership_root(members: Vec<String>) -> Result<String, ZkProofError> {
+    Ok(format!("{:?}", root_of(members)?))
+}
+
+/// Prove that the Show input's claim value is one of `members`
+/// Reblinds under the current shared blinds, so the proof links to the stored
+/// Prepare proof.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_claim_membership(
+    documents_path: String,
+    members: Vec<String>,
+) -> Result<ProofResult, ZkProofError> {
+    let set = merkle_set(members)?;
+
+    with_working_dir(&documents_path, || {
+        if !Path::new(MEMBERSHIP_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "Membership proving key not found; call setup_membership_keys first".to_string(),
+            ));
+        }
+        let circuit = MembershipCircuit::from_show_input(&load_show_input(None)?, &set)
+            .map_err(ZkProofError::invalid_input)?;
+        let pk = load_proving_key::<E>(MEMBERSHIP_PROVING_KEY).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load proving key from '{}': {}",
+                MEMBERSHIP_PROVING_KEY, e
+            ))
+            .with_source(&*e)
+        })?;
+        let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+            ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+                .with_source(&*e)
+        })?;
+        let run = RunLog::start("prove_claim_membership");
+
+        let start = std::time::Instant::now();
+        run.phase("prove", Some(MEMBERSHIP_PROOF), || {
+            prove_circuit_with_pk(
+                circuit.clone(),
+                &pk,
+                MEMBERSHIP_INSTANCE,
+                MEMBERSHIP_WITNESS,
+                MEMBERSHIP_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+        run.phase("reblind", Some(MEMBERSHIP_PROOF), || {
+            reblind_loaded(
+                circuit,
+                &pk,
+                &shared_blinds,
+                MEMBERSHIP_INSTANCE,
+                MEMBERSHIP_WITNESS,
+                MEMBERSHIP_PROOF,
+            )
+        })?;
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        let comm_w_shared = extract_comm_w_shared(MEMBERSHIP_INSTANCE)?;
+        if comm_w_shared != extract_comm_w_shared(PREPARE_INSTANCE)? {
+            return Err(ZkProofError::verification_failed(
+                "Membership and Prepare comm_W_shared differ; reblind Prepare under the current blinds"
+                    .to_string(),
+            ));
+        }
+
+        Ok(ProofResult {
+            prep_ms: 0,
+            prove_ms: total_ms,
+            total_ms,
+            proof_size_bytes: get_proof_size(MEMBERSHIP_PROOF)?,
+            comm_w_shared,
+        })
+    })
+}
+
+/// Verify the stored Membership proof against the set of `members`
+/// A valid proof against another set's root, or one whose comm_W_shared
+/// differs from the stored Prepare proof's, is rejected with the reason.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_claim_membership(
+    documents_path: String,
+    members: Vec<String>,
+) -> Result<VerifyResult, ZkProofError> {
+    let expected_root = root_of(members)?;
+
+    with_working_dir(&documents_path, || {
+        let prepare_proof = load_proof::<E>(PREPARE_PROOF).map_err(|e| {
+            ZkProofError::file_not_found(format!(
+                "Failed to load proof from '{}': {}",
+                PREPARE_PROOF, e
+            ))
+            .with_source(&*e)
+        })?;
+        let prepare_comm_w_shared = format!("{:?}", prepare_proof.comm_W_shared());
+
+        let run = RunLog::start("verify_claim_membership");
+        let mut result = run.phase("verify", None, || {
+            verify_result_with(
+                MEMBERSHIP_PROOF,
+                MEMBERSHIP_VERIFYING_KEY,
+                |public_values| {
+                    if root_from_public_values(public_values)? != expected_root {
+                        return Err("proof is for another set's root".to_string());
+                    }
+                    Ok(())
+                },
+            )
+        })?;
+        if result.valid && result.comm_w_shared != prepare_comm_w_shared {
+            result.valid = false;
+            result.failure_reason = Some(
+                "Membership proof does not share comm_W_shared with the Prepare proof".to_string(),
+            );
+        }
+        Ok(result)
+    })
+}
+
+fn merkle_set(members: Vec<String>) -> Result<MerkleSet, ZkProofError> {
+    MerkleSet::new(members.into_iter().map(String::into_bytes).collect())
+        .map_err(ZkProofError::invalid_input)
+}
+
+fn root_of(members: Vec<String>) -> Result<Scalar, ZkProofError> {
+    merkle_set(members)?
+        .root()
+        .map_err(ZkProofError::invalid_input)
+}
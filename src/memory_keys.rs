@@ -0,0 +1,343 @@
+//! Keys held in memory only, for contexts that keep none on disk.
+//!
+//! Verifier services, and other ephemeral deployments, do not want proving or
+//! verifying keys in the filesystem. `setup_keys_in_memory` runs Prepare or
+//! Show setup and returns a `CircuitKeys` handle instead of writing
+//! `keys/*.key`, and the `*_with_keys` functions prove, reblind and verify with
+//! the handle's keys instead of loading them from the documents directory.
+//! Proofs, instances and witnesses are still read and written there as by the
+//! path-based functions; `verify_presentation_with_keys` needs no directory at
+//! all. Keys from a separate setup are separate keys: proofs verify only under
+//! the handle, or stored keys, they were made with.
+
+use std::{path::PathBuf, sync::Arc, time::Instant};
+
+use ecdsa_spartan2::{
+    domain::check_domain,
+    jwt_variant, load_shared_blinds,
+    prover::prove_circuit_with_pk_until,
+    setup::{
+        PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_WITNESS, SHARED_BLINDS, SHOW_INSTANCE, SHOW_PROOF,
+        SHOW_WITNESS,
+    },
+    setup_circuit_keys_in_memory,
+    spartan2::traits::circuit::SpartanCircuit,
+    CircuitKind, JwtVariant, PrepareCircuit, ProvingKey, ShowCircuit, VerifyingKey, E,
+};
+
+use crate::{
+    credential::{issuer_key_set, IssuerPublicKey},
+    deadline_after, extract_comm_w_shared, get_proof_size, locate_constraint_failure,
+    presentation_flow::{verify_bundle, PresentationBundle, PresentationVerification},
+    prove_failed, reblind_timed_with_pk,
+    run_log::RunLog,
+    verifier_limits::{self, get_verifier_limits},
+    verify_result_with_vk, with_working_dir, ProofResult, VerifyResult, ZkProofError,
+};
+
+/// Proving and verifying keys of one circuit, never written to disk
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct CircuitKeys {
+    circuit: CircuitKind,
+    /// JWT circuit selected at setup; only meaningful for Prepare
+    variant: JwtVariant,
+    pk: ProvingKey,
+    vk: VerifyingKey,
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+impl CircuitKeys {
+    /// "prepare" or "show"
+    pub fn circuit(&self) -> String {
+        self.circuit.name().to_string()
+    }
+}
+
+impl CircuitKeys {
+    /// Error unless these are keys of `circuit`
+    fn check(&self, circuit: CircuitKind) -> Result<(), ZkProofError> {
+        check_circuit(circuit, self.circuit)
+    }
+
+    /// `check`, and for Prepare that the selected JWT variant is the one set up
+    fn check_proving(&self, circuit: CircuitKind) -> Result<(), ZkProofError> {
+        check_proving_key(circuit, self.circuit, self.variant)
+    }
+}
+
+/// Error unless keys of `actual` are the expected `circuit`'s
+pub(crate) fn check_circuit(circuit: CircuitKind, actual: CircuitKind) -> Result<(), ZkProofError> {
+    if actual != circuit {
+        return Err(ZkProofError::invalid_input(format!(
+            "Expected {} keys, got {} keys",
+            circuit.name(),
+            actual.name()
+        )));
+    }
+    Ok(())
+}
+
+/// `check_circuit`, and for Prepare that the selected JWT variant is `variant`
+pub(crate) fn check_proving_key(
+    circuit: CircuitKind,
+    actual: CircuitKind,
+    variant: JwtVariant,
+) -> Result<(), ZkProofError> {
+    check_circuit(circuit, actual)?;
+    if circuit == CircuitKind::Prepare && variant != jwt_variant() {
+        return Err(ZkProofError::invalid_input(format!(
+            "Prepare keys were set up for the {} circuit, but {} is selected",
+            variant.name(),
+            jwt_variant().name()
+        )));
+    }
+    Ok(())
+}
+
+/// Run setup for `circuit` ("prepare" or "show") without saving the keys
+/// Synthesizes the circuit from `documents_path` like `setup_prepare_keys` and
+/// `setup_show_keys`; Prepare keys are for the currently selected JWT variant.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_keys_in_memory(
+    documents_path: String,
+    circuit: String,
+    input_path: Option<String>,
+) -> Result<Arc<CircuitKeys>, ZkProofError> {
+    let circuit: CircuitKind = circuit.parse().map_err(ZkProofError::invalid_input)?;
+    let input_path = input_path.map(PathBuf::from);
+
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("setup_keys_in_memory");
+        let variant = jwt_variant();
+        let (pk, vk) = run.phase("setup", None, || {
+            match circuit {
+                CircuitKind::Prepare => {
+                    setup_circuit_keys_in_memory::<E, _>(PrepareCircuit::new(input_path))
+                }
+                CircuitKind::Show => {
+                    setup_circuit_keys_in_memory::<E, _>(ShowCircuit::new(input_path))
+                }
+            }
+            .map_err(|e| {
+                ZkProofError::proof_generation_failed(format!("Setup failed: {:?}", e))
+                    .with_source(&e)
+            })
+        })?;
+
+        Ok(Arc::new(CircuitKeys {
+            circuit,
+            variant,
+            pk,
+            vk,
+        }))
+    })
+}
+
+/// `prove_prepare` with the proving key of `keys`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_prepare_with_keys(
+    documents_path: String,
+    keys: Arc<CircuitKeys>,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    keys.check_proving(CircuitKind::Prepare)?;
+    with_working_dir(&documents_path, || {
+        prove_with_pk(
+            "prove_prepare_with_keys",
+            PrepareCircuit::new(input_path.map(PathBuf::from)),
+            &keys.pk,
+            [PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF],
+            max_duration_ms,
+        )
+    })
+}
+
+/// `prove_show` with the proving key of `keys`
+/// The presentation policy is not applied, since its reblind also needs the
+/// Prepare proving key; reblind both with the `reblind_*_with_keys` functions.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_show_with_keys(
+    documents_path: String,
+    keys: Arc<CircuitKeys>,
+    input_path: Option<String>,
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    keys.check_proving(CircuitKind::Show)?;
+    with_working_dir(&documents_path, || {
+        prove_with_pk(
+            "prove_show_with_keys",
+            ShowCircuit::new(input_path.map(PathBuf::from)),
+            &keys.pk,
+            [SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF],
+            max_duration_ms,
+        )
+    })
+}
+
+/// `reblind_prepare` with the proving key of `keys`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_prepare_with_keys(
+    documents_path: String,
+    keys: Arc<CircuitKeys>,
+) -> Result<ProofResult, ZkProofError> {
+    keys.check_proving(CircuitKind::Prepare)?;
+    with_working_dir(&documents_path, || {
+        reblind_with_pk(
+            "reblind_prepare_with_keys",
+            PrepareCircuit::default(),
+            &keys.pk,
+            [PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF],
+        )
+    })
+}
+
+/// `reblind_show` with the proving key of `keys`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reblind_show_with_keys(
+    documents_path: String,
+    keys: Arc<CircuitKeys>,
+) -> Result<ProofResult, ZkProofError> {
+    keys.check_proving(CircuitKind::Show)?;
+    with_working_dir(&documents_path, || {
+        reblind_with_pk(
+            "reblind_show_with_keys",
+            ShowCircuit::default(),
+            &keys.pk,
+            [SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF],
+        )
+    })
+}
+
+/// `verify_prepare` with the verifying key of `keys`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_prepare_with_keys(
+    documents_path: String,
+    keys: Arc<CircuitKeys>,
+) -> Result<VerifyResult, ZkProofError> {
+    keys.check(CircuitKind::Prepare)?;
+    verify_with_vk(
+        "verify_prepare_with_keys",
+        documents_path,
+        &keys.vk,
+        CircuitKind::Prepare,
+        PREPARE_PROOF,
+    )
+}
+
+/// `verify_show` with the verifying key of `keys`
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_show_with_keys(
+    documents_path: String,
+    keys: Arc<CircuitKeys>,
+) -> Result<VerifyResult, ZkProofError> {
+    keys.check(CircuitKind::Show)?;
+    verify_with_vk(
+        "verify_show_with_keys",
+        documents_path,
+        &keys.vk,
+        CircuitKind::Show,
+        SHOW_PROOF,
+    )
+}
+
+/// `verify_presentation` with the verifying keys of `prepare_keys` and `show_keys`
+/// Reads and writes nothing; errors mean the bundle exceeds the verifier limits,
+/// the issuer keys are malformed or the keys are of the wrong circuits.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_presentation_with_keys(
+    bundle: PresentationBundle,
+    verifier_nonce: Vec<u8>,
+    issuer_keys: Vec<IssuerPublicKey>,
+    prepare_keys: Arc<CircuitKeys>,
+    show_keys: Arc<CircuitKeys>,
+) -> Result<PresentationVerification, ZkProofError> {
+    verifier_limits::check_bundle(&bundle, &get_verifier_limits())?;
+    let issuer_keys = issuer_key_set(issuer_keys)?;
+    prepare_keys.check(CircuitKind::Prepare)?;
+    show_keys.check(CircuitKind::Show)?;
+    Ok(verify_bundle(
+        &bundle,
+        &verifier_nonce,
+        &prepare_keys.vk,
+        &show_keys.vk,
+        &issuer_keys,
+    ))
+}
+
+/// Prove `circuit` with `pk`, writing `[instance, witness, proof]`
+pub(crate) fn prove_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    operation: &str,
+    circuit: C,
+    pk: &ProvingKey,
+    [instance_path, witness_path, proof_path]: [&str; 3],
+    max_duration_ms: Option<u64>,
+) -> Result<ProofResult, ZkProofError> {
+    let run = RunLog::start(operation);
+    let start = Instant::now();
+    let deadline = deadline_after(start, max_duration_ms);
+    run.phase("prove", Some(proof_path), || {
+        locate_constraint_failure(|| {
+            prove_circuit_with_pk_until(
+                circuit,
+                pk,
+                instance_path,
+                witness_path,
+                proof_path,
+                deadline,
+            )
+            .map_err(prove_failed)
+        })
+    })?;
+    let total_ms = start.elapsed().as_millis() as u64;
+
+    Ok(ProofResult {
+        prep_ms: 0,
+        prove_ms: total_ms,
+        total_ms,
+        proof_size_bytes: get_proof_size(proof_path)?,
+        comm_w_shared: extract_comm_w_shared(instance_path)?,
+    })
+}
+
+/// Reblind the stored proof of `circuit` under the current shared blinds
+pub(crate) fn reblind_with_pk<C: SpartanCircuit<E>>(
+    operation: &str,
+    circuit: C,
+    pk: &ProvingKey,
+    [instance_path, witness_path, proof_path]: [&str; 3],
+) -> Result<ProofResult, ZkProofError> {
+    let run = RunLog::start(operation);
+    let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+        ZkProofError::setup_required(format!("Failed to load shared blinds: {}", e))
+            .with_source(&*e)
+    })?;
+    run.phase("reblind", Some(proof_path), || {
+        reblind_timed_with_pk(
+            circuit,
+            pk,
+            &shared_blinds,
+            instance_path,
+            witness_path,
+            proof_path,
+        )
+    })
+}
+
+/// Verify the stored proof of `circuit` in `documents_path` with `vk`
+pub(crate) fn verify_with_vk(
+    operation: &str,
+    documents_path: String,
+    vk: &VerifyingKey,
+    circuit: CircuitKind,
+    proof_path: &str,
+) -> Result<VerifyResult, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start(operation);
+        run.phase("verify", None, || {
+            verify_result_with_vk(proof_path, vk, |public_values| {
+                check_domain(circuit, public_values).map(|_| ())
+            })
+        })
+    })
+}
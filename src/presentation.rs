@@ -0,0 +1,141 @@
+//! Reblind policy applied when a Show proof is handed out for presentation.
+//!
+//! The policy and the number of presentations made under the current shared
+//! blinds are persisted next to the credential's shared blinds, so the counter
+//! survives app restarts and each credential namespace keeps its own. Paths
+//! are relative to the documents directory.
+
+use std::{
+    fs::{create_dir_all, File},
+    path::{Path, PathBuf},
+};
+
+use ecdsa_spartan2::{
+    layout::in_documents,
+    namespace::{artifact_namespace, ArtifactNamespace},
+    setup::SHARED_BLINDS,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{get_prove_options, ZkProofError};
+
+/// File name of the persisted presentation state, in the directory of the
+/// namespace's shared blinds (`keys/` in the flat layout)
+pub const PRESENTATION_STATE: &str = "presentation_state.json";
+
+/// When to sample fresh shared blinds before presenting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ReblindPolicy {
+    /// Reblind both proofs before every presentation
+    Always,
+    /// Reblind once the current blinds have been used for `n` presentations
+    EveryN { n: u64 },
+    /// Present the stored proof as is
+    #[default]
+    Never,
+}
+
+/// Presentation settings for a wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PresentationPolicy {
+    pub reblind: ReblindPolicy,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct PresentationState {
+    pub policy: PresentationPolicy,
+    /// Presentations made since the shared blinds were last sampled
+    pub presentations_with_current_blinds: u64,
+}
+
+impl PresentationState {
+    /// State of the active namespace
+    pub(crate) fn load() -> Result<Self, ZkProofError> {
+        Self::load_in(&artifact_namespace())
+    }
+
+    pub(crate) fn load_in(namespace: &ArtifactNamespace) -> Result<Self, ZkProofError> {
+        let path = state_path(namespace);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        serde_json::from_reader(File::open(&path)?).map_err(|e| {
+            ZkProofError::invalid_input(format!(
+                "Corrupt presentation state '{}': {}",
+                path.display(),
+                e
+            ))
+            .with_source(&e)
+        })
+    }
+
+    pub(crate) fn save(&self) -> Result<(), ZkProofError> {
+        self.save_in(&artifact_namespace())
+    }
+
+    pub(crate) fn save_in(&self, namespace: &ArtifactNamespace) -> Result<(), ZkProofError> {
+        let path = state_path(namespace);
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+        serde_json::to_writer(File::create(&path)?, self).map_err(|e| {
+            ZkProofError::io_error(format!("Failed to save presentation state: {}", e))
+                .with_source(&e)
+        })
+    }
+
+    /// Whether the next presentation must sample fresh blinds first
+    /// A reblind policy in the prove options takes precedence over the stored one.
+    pub(crate) fn should_reblind(&self) -> bool {
+        let policy = get_prove_options()
+            .reblind_policy
+            .unwrap_or(self.policy.reblind);
+        match policy {
+            ReblindPolicy::Always => true,
+            ReblindPolicy::EveryN { n } => self.presentations_with_current_blinds >= n.max(1),
+            ReblindPolicy::Never => false,
+        }
+    }
+}
+
+/// Where the presentation state of `namespace` is persisted
+fn state_path(namespace: &ArtifactNamespace) -> PathBuf {
+    let blinds = in_documents(&namespace.resolve(SHARED_BLINDS));
+    Path::new(&blinds).with_file_name(PRESENTATION_STATE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(reblind: ReblindPolicy, used: u64) -> PresentationState {
+        PresentationState {
+            policy: PresentationPolicy { reblind },
+            presentations_with_current_blinds: used,
+        }
+    }
+
+    #[test]
+    fn test_should_reblind() {
+        assert!(state(ReblindPolicy::Always, 0).should_reblind());
+        assert!(!state(ReblindPolicy::Never, 100).should_reblind());
+        assert!(!state(ReblindPolicy::EveryN { n: 3 }, 2).should_reblind());
+        assert!(state(ReblindPolicy::EveryN { n: 3 }, 3).should_reblind());
+        // n = 0 behaves like n = 1 rather than reblinding forever
+        assert!(!state(ReblindPolicy::EveryN { n: 0 }, 0).should_reblind());
+    }
+
+    #[test]
+    fn test_state_is_kept_per_namespace() {
+        assert_eq!(
+            state_path(&ArtifactNamespace::Legacy),
+            Path::new("keys/presentation_state.json")
+        );
+        assert_eq!(
+            state_path(&ArtifactNamespace::credential("abc").unwrap()),
+            Path::new("credentials/abc/presentation_state.json")
+        );
+    }
+}
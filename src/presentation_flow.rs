@@ -0,0 +1,617 @@
+//! Holder-binding challenge/response presentation.
+//!
+//! A presentation has to sign the verifier's nonce with the device key, prove
+//! Show over that signature, and reblind so the Show proof shares comm_W_shared
+//! with the stored Prepare proof. `start_presentation` and
+//! `complete_presentation` run those steps in order around the one step only the
+//! platform can do, signing the challenge in secure hardware.
+//!
+//! The Show circuit verifies the device signature over SHA-256 of the nonce and
+//! makes that message hash a public value (see `ShowStatement`). The bundle
+//! carries the nonce alongside the proofs, but `verify_presentation` takes the
+//! nonce the verifier issued and checks every Show's message hash against it,
+//! so a bundle answering another challenge is rejected.
+//!
+//! The Show proves its holder is at least 18 on a reference date it makes
+//! public; `age >= n` moves that date `n - 18` years back. Verification rejects
+//! a reference date after today (UTC) and reports the age every Show proves the
+//! holder has reached today, so the verifier compares it with the age it asked
+//! for instead of trusting the predicate the holder chose.
+//!
+//! A presentation may carry further Show proofs over other inputs (an age
+//! predicate and a residency claim, say), all signed with the same device
+//! signature and reblinded under the same shared blinds, so each one links to
+//! the single Prepare proof in the bundle. `verify_presentation` checks that
+//! link for every Show.
+//!
+//! The Prepare proof makes public the issuer key the credential was checked
+//! against. `verify_presentation` takes the issuer keys the verifier trusts and,
+//! as `verify_prepare_issuer` does, rejects a bundle whose Prepare proof names
+//! any other key or one that was not valid when the proof was issued.
+//!
+//! What a Show proves is set by its input. `set_show_predicate` writes that
+//! input from a claim predicate such as `age >= 21`, so the platform states the
+//! disclosure policy rather than assembling circuit inputs itself.
+
+use std::{
+    fs,
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::Datelike;
+use ecdsa_spartan2::{
+    artifact_path, compile_predicate,
+    domain::check_domain,
+    load_proving_key, load_shared_blinds, load_verifying_key,
+    prover::{prove_circuit, prove_circuit_with_pk},
+    setup::{
+        parse_proof, public_instance_path, PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_VERIFYING_KEY,
+        SHARED_BLINDS, SHOW_INSTANCE, SHOW_PROOF, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
+        SHOW_WITNESS,
+    },
+    show::{expected_message_hash, ShowStatement},
+    signature::{apply_device_signature, bound_device_key},
+    spartan2::traits::snark::R1CSSNARKTrait,
+    CalendarDate, CircuitKind, ClaimPredicate, IssuerKeySet, PreparePublicValues, Scalar,
+    ShowCircuit, VerifyingKey, E,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    credential::{issuer_key_set, IssuerPublicKey, PREPARE_INPUT},
+    device_key::{load_show_input, with_signed_show_input},
+    extract_comm_w_shared, get_prove_options, prepare_validity_in_cwd,
+    presentation::PresentationState,
+    prove_failed, reblind_all_in_cwd, reblind_loaded, reblind_timed,
+    run_log::{panic_message, RunLog},
+    verifier_limits::{self, get_verifier_limits},
+    with_working_dir, ProofFreshness, ZkProofError,
+};
+
+/// The challenge awaiting a device signature
+const PENDING_PRESENTATION: &str = "keys/pending_presentation.json";
+
+/// Working files for the additional Show proofs of the presentation in progress
+const ADDITIONAL_SHOWS_DIR: &str = "keys/additional_shows";
+
+/// Label of the bundle's primary Show proof in failure reasons
+pub(crate) const PRIMARY_SHOW_LABEL: &str = "show";
+
+/// Show input the next presentation proves over
+const SHOW_INPUT: &str = "show_input.json";
+
+/// What the device must sign to complete a presentation
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct Challenge {
+    /// Bytes to sign with ECDSA-SHA256 under the bound device key
+    pub challenge: Vec<u8>,
+    pub created_unix_ms: i64,
+}
+
+/// Proofs and public data handed to the verifier
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PresentationBundle {
+    pub verifier_nonce: Vec<u8>,
+    pub prepare_proof: Vec<u8>,
+    /// Public part of the Prepare instance (`PublicInstance`); the rest stays on the device
+    pub prepare_instance: Vec<u8>,
+    pub show_proof: Vec<u8>,
+    /// Public part of the Show instance
+    pub show_instance: Vec<u8>,
+    /// Further Show proofs linked to the same Prepare proof
+    pub additional_shows: Vec<ShowPresentation>,
+    /// Shared witness commitment common to all proofs
+    pub comm_w_shared: String,
+    /// Whether fresh shared blinds were sampled for this presentation
+    pub reblinded: bool,
+    /// Validity window bound into the Prepare proof, for the verifier's max-age check
+    /// Verification rejects a bundle where it differs from the proof's public values.
+    pub prepare_freshness: ProofFreshness,
+}
+
+/// A Show proof to present alongside the primary one
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ShowRequest {
+    /// Name the verifier sees for this proof, e.g. "residency"
+    pub label: String,
+    /// Show input to prove over, relative to the documents directory
+    pub input_path: String,
+}
+
+/// An additional Show proof in a `PresentationBundle`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ShowPresentation {
+    pub label: String,
+    pub show_proof: Vec<u8>,
+    /// Public part of the Show instance
+    pub show_instance: Vec<u8>,
+}
+
+/// Outcome of `verify_presentation`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PresentationVerification {
+    pub valid: bool,
+    /// Show proofs that verified and link to the Prepare proof, the primary one included
+    pub shows_verified: u32,
+    /// Age every Show proves the holder has reached today; `None` if rejected
+    pub proven_min_age: Option<u32>,
+    /// `kid` of the issuer key the Prepare proof was made against; `None` if rejected
+    pub issuer_kid: Option<String>,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PendingPresentation {
+    verifier_nonce: Vec<u8>,
+    created_unix_ms: i64,
+}
+
+/// Set the claim predicate the next presentation's Show proof attests
+/// Compiles `predicate` (e.g. `age >= 21`) against the stored credential input
+/// into the Show input. Fails with `InvalidInput` for predicates the Show
+/// circuit cannot express.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_show_predicate(documents_path: String, predicate: String) -> Result<(), ZkProofError> {
+    let predicate = predicate
+        .parse::<ClaimPredicate>()
+        .map_err(|e| ZkProofError::invalid_input(e.to_string()))?;
+
+    with_working_dir(&documents_path, || {
+        let prepare_input: serde_json::Value = fs::read(PREPARE_INPUT)
+            .map_err(|e| {
+                ZkProofError::file_not_found(format!(
+                    "Failed to read credential input '{}': {}",
+                    PREPARE_INPUT, e
+                ))
+                .with_source(&e)
+            })
+            .and_then(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|e| {
+                    ZkProofError::invalid_input(format!("Malformed credential input: {}", e))
+                        .with_source(&e)
+                })
+            })?;
+        let mut show_input = load_show_input(None)?;
+
+        compile_predicate(&predicate, &prepare_input, &mut show_input, utc_today())
+            .map_err(|e| ZkProofError::invalid_input(e.to_string()))?;
+
+        serde_json::to_writer(fs::File::create(SHOW_INPUT)?, &show_input).map_err(|e| {
+            ZkProofError::io_error(format!("Failed to save show input: {}", e)).with_source(&e)
+        })
+    })
+}
+
+/// Today's date in UTC, as Show predicates are compiled and checked against
+fn utc_today() -> CalendarDate {
+    let now = chrono::Utc::now().date_naive();
+    CalendarDate {
+        year: now.year() as i64,
+        month: now.month(),
+        day: now.day(),
+    }
+}
+
+/// Begin a presentation for `verifier_nonce`
+/// Returns the challenge to sign in secure hardware; a later call replaces any
+/// presentation still pending
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn start_presentation(
+    documents_path: String,
+    verifier_nonce: Vec<u8>,
+) -> Result<Challenge, ZkProofError> {
+    if verifier_nonce.is_empty() {
+        return Err(ZkProofError::invalid_input(
+            "Verifier nonce must not be empty".to_string(),
+        ));
+    }
+
+    with_working_dir(&documents_path, || {
+        let pending = PendingPresentation {
+            verifier_nonce,
+            created_unix_ms: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.as_millis() as i64)
+                .unwrap_or_default(),
+        };
+
+        if let Some(parent) = Path::new(PENDING_PRESENTATION).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        serde_json::to_writer(fs::File::create(PENDING_PRESENTATION)?, &pending).map_err(|e| {
+            ZkProofError::io_error(format!("Failed to save pending presentation: {}", e))
+                .with_source(&e)
+        })?;
+
+        Ok(Challenge {
+            challenge: pending.verifier_nonce,
+            created_unix_ms: pending.created_unix_ms,
+        })
+    })
+}
+
+/// Finish the pending presentation with the device's signature over the challenge
+/// Proves Show over the signature, reblinds per the presentation policy (or
+/// under the current blinds otherwise), and checks both proofs share comm_W_shared
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn complete_presentation(
+    documents_path: String,
+    device_signature: Vec<u8>,
+) -> Result<PresentationBundle, ZkProofError> {
+    complete_presentation_with_shows(documents_path, device_signature, Vec::new())
+}
+
+/// `complete_presentation`, also proving Show over each of `additional_shows`
+/// Every input must be bound to the same device key, which signs the challenge
+/// once for all of them.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn complete_presentation_with_shows(
+    documents_path: String,
+    device_signature: Vec<u8>,
+    additional_shows: Vec<ShowRequest>,
+) -> Result<PresentationBundle, ZkProofError> {
+    for (i, request) in additional_shows.iter().enumerate() {
+        if request.label.is_empty() || request.label == PRIMARY_SHOW_LABEL {
+            return Err(ZkProofError::invalid_input(format!(
+                "Additional Show label '{}' is empty or reserved",
+                request.label
+            )));
+        }
+        if additional_shows[..i]
+            .iter()
+            .any(|r| r.label == request.label)
+        {
+            return Err(ZkProofError::invalid_input(format!(
+                "Additional Show label '{}' is used twice",
+                request.label
+            )));
+        }
+    }
+
+    with_working_dir(&documents_path, || {
+        let pending: PendingPresentation = fs::File::open(PENDING_PRESENTATION)
+            .ok()
+            .and_then(|f| serde_json::from_reader(f).ok())
+            .ok_or_else(|| {
+                ZkProofError::setup_required(
+                    "No pending presentation; call start_presentation first".to_string(),
+                )
+            })?;
+        let run = RunLog::start("complete_presentation");
+
+        // 1. Bind the device signature over the nonce into the Show input
+        let mut show_input = load_show_input(None)?;
+        let pubkey = bound_device_key(&show_input).map_err(ZkProofError::invalid_input)?;
+        apply_device_signature(
+            &mut show_input,
+            &pending.verifier_nonce,
+            &device_signature,
+            &pubkey,
+        )
+        .map_err(ZkProofError::invalid_input)?;
+
+        // 2. Prove Show over the signed input
+        with_signed_show_input(&show_input, |input_path| {
+            run.phase("prove_show", Some(SHOW_PROOF), || {
+                prove_circuit(
+                    ShowCircuit::new(input_path),
+                    SHOW_PROVING_KEY,
+                    SHOW_INSTANCE,
+                    SHOW_WITNESS,
+                    SHOW_PROOF,
+                )
+                .map_err(prove_failed)
+            })
+        })?;
+
+        // 3. Reblind so Show carries the same comm_W_shared as Prepare
+        let state = PresentationState::load()?;
+        let reblinded = state.should_reblind();
+        if reblinded {
+            reblind_all_in_cwd(&mut *get_prove_options().rng_source().into_rng())?;
+        } else {
+            let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+                ZkProofError::setup_required(format!("Failed to load shared blinds: {}", e))
+                    .with_source(&*e)
+            })?;
+            run.phase("reblind_show", Some(SHOW_PROOF), || {
+                reblind_timed(
+                    ShowCircuit::default(),
+                    SHOW_PROVING_KEY,
+                    &shared_blinds,
+                    SHOW_INSTANCE,
+                    SHOW_WITNESS,
+                    SHOW_PROOF,
+                )
+            })?;
+        }
+        let mut state = PresentationState::load()?;
+        state.presentations_with_current_blinds += 1;
+        state.save()?;
+
+        // 4. Both proofs must open to the same shared commitment
+        let comm_w_shared = extract_comm_w_shared(SHOW_INSTANCE)?;
+        if comm_w_shared != extract_comm_w_shared(PREPARE_INSTANCE)? {
+            return Err(ZkProofError::verification_failed(
+                "Prepare and Show comm_W_shared differ; reblind Prepare first".to_string(),
+            ));
+        }
+
+        // 5. Prove the additional Shows under the same blinds
+        let additional_shows = prove_additional_shows(
+            &run,
+            &pending.verifier_nonce,
+            &device_signature,
+            &additional_shows,
+            &comm_w_shared,
+        )?;
+
+        // The nonce is single-use
+        let _ = fs::remove_file(PENDING_PRESENTATION);
+
+        Ok(PresentationBundle {
+            verifier_nonce: pending.verifier_nonce,
+            prepare_proof: fs::read(artifact_path(PREPARE_PROOF))?,
+            prepare_instance: fs::read(public_instance_path(PREPARE_INSTANCE))?,
+            show_proof: fs::read(artifact_path(SHOW_PROOF))?,
+            show_instance: fs::read(public_instance_path(SHOW_INSTANCE))?,
+            additional_shows,
+            comm_w_shared,
+            reblinded,
+            prepare_freshness: prepare_validity_in_cwd()?.into(),
+        })
+    })
+}
+
+/// Verify every proof in `bundle` and that each Show links to its Prepare proof
+/// Every Show must be signed over `verifier_nonce`, the nonce the verifier
+/// issued for this presentation, and the Prepare proof made against one of
+/// `issuer_keys`. Uses the verifying keys in `documents_path` and the
+/// configured domain. A rejected bundle is reported in the result; errors mean
+/// the bundle exceeds the verifier limits (see `set_verifier_limits`), the
+/// issuer keys are malformed or the verifying keys could not be loaded.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_presentation(
+    documents_path: String,
+    bundle: PresentationBundle,
+    verifier_nonce: Vec<u8>,
+    issuer_keys: Vec<IssuerPublicKey>,
+) -> Result<PresentationVerification, ZkProofError> {
+    verifier_limits::check_bundle(&bundle, &get_verifier_limits())?;
+    let issuer_keys = issuer_key_set(issuer_keys)?;
+    with_working_dir(&documents_path, || {
+        let run = RunLog::start("verify_presentation");
+        let load_vk = |vk_path: &str| {
+            load_verifying_key::<E>(vk_path).map_err(|e| {
+                ZkProofError::setup_required(format!(
+                    "Failed to load verifying key from '{}': {}",
+                    vk_path, e
+                ))
+                .with_source(&*e)
+            })
+        };
+        let prepare_vk = load_vk(PREPARE_VERIFYING_KEY)?;
+        let show_vk = load_vk(SHOW_VERIFYING_KEY)?;
+
+        run.phase("verify", None, || {
+            Ok(verify_bundle(
+                &bundle,
+                &verifier_nonce,
+                &prepare_vk,
+                &show_vk,
+                &issuer_keys,
+            ))
+        })
+    })
+}
+
+/// Verify `bundle` for `verifier_nonce` against loaded keys, reporting rejection in the result
+pub(crate) fn verify_bundle(
+    bundle: &PresentationBundle,
+    verifier_nonce: &[u8],
+    prepare_vk: &VerifyingKey,
+    show_vk: &VerifyingKey,
+    issuer_keys: &IssuerKeySet,
+) -> PresentationVerification {
+    let mut shows_verified = 0;
+    let outcome = check_bundle(
+        bundle,
+        verifier_nonce,
+        prepare_vk,
+        show_vk,
+        issuer_keys,
+        utc_today(),
+        &mut shows_verified,
+    );
+    PresentationVerification {
+        valid: outcome.is_ok(),
+        shows_verified,
+        proven_min_age: outcome.as_ref().ok().map(|(age, _)| *age),
+        issuer_kid: outcome.as_ref().ok().map(|(_, kid)| kid.clone()),
+        failure_reason: outcome.err(),
+    }
+}
+
+/// Prove, reblind and collect each additional Show, checking it against `comm_w_shared`
+fn prove_additional_shows(
+    run: &RunLog,
+    verifier_nonce: &[u8],
+    device_signature: &[u8],
+    requests: &[ShowRequest],
+    comm_w_shared: &str,
+) -> Result<Vec<ShowPresentation>, ZkProofError> {
+    if requests.is_empty() {
+        return Ok(Vec::new());
+    }
+    let pk = load_proving_key::<E>(SHOW_PROVING_KEY).map_err(|e| {
+        ZkProofError::setup_required(format!(
+            "Failed to load proving key from '{}': {}",
+            SHOW_PROVING_KEY, e
+        ))
+        .with_source(&*e)
+    })?;
+    let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+        ZkProofError::setup_required(format!("Failed to load shared blinds: {}", e))
+            .with_source(&*e)
+    })?;
+    fs::create_dir_all(ADDITIONAL_SHOWS_DIR)?;
+
+    let shows: Result<Vec<_>, ZkProofError> = requests
+        .iter()
+        .enumerate()
+        .map(|(i, request)| {
+            let [instance, witness, proof] = ["instance", "witness", "proof"]
+                .map(|ext| format!("{}/show_{}.{}", ADDITIONAL_SHOWS_DIR, i, ext));
+            let input_path = PathBuf::from(&request.input_path);
+
+            let mut show_input = load_show_input(Some(input_path.clone()))?;
+            let pubkey = bound_device_key(&show_input).map_err(ZkProofError::invalid_input)?;
+            apply_device_signature(&mut show_input, verifier_nonce, device_signature, &pubkey)
+                .map_err(ZkProofError::invalid_input)?;
+            with_signed_show_input(&show_input, |signed_path| {
+                run.phase("prove_additional_show", Some(&proof), || {
+                    prove_circuit_with_pk(
+                        ShowCircuit::new(signed_path),
+                        &pk,
+                        &instance,
+                        &witness,
+                        &proof,
+                    )
+                    .map_err(prove_failed)
+                })
+            })?;
+            run.phase("reblind_additional_show", Some(&proof), || {
+                reblind_loaded(
+                    ShowCircuit::new(Some(input_path)),
+                    &pk,
+                    &shared_blinds,
+                    &instance,
+                    &witness,
+                    &proof,
+                )
+            })?;
+
+            if extract_comm_w_shared(&instance)? != comm_w_shared {
+                return Err(ZkProofError::verification_failed(format!(
+                    "Show '{}' comm_W_shared differs from Prepare",
+                    request.label
+                )));
+            }
+            Ok(ShowPresentation {
+                label: request.label.clone(),
+                show_proof: fs::read(&proof)?,
+                show_instance: fs::read(public_instance_path(&instance))?,
+            })
+        })
+        .collect();
+
+    // The working files hold witnesses; none outlive the presentation
+    let _ = fs::remove_dir_all(ADDITIONAL_SHOWS_DIR);
+    shows
+}
+
+/// Check each proof of `bundle`, counting the Shows that pass in `shows_verified`
+/// Returns the age every Show proves the holder has reached on `today` and the
+/// `kid` of the issuer key the Prepare proof was made against.
+fn check_bundle(
+    bundle: &PresentationBundle,
+    verifier_nonce: &[u8],
+    prepare_vk: &VerifyingKey,
+    show_vk: &VerifyingKey,
+    issuer_keys: &IssuerKeySet,
+    today: CalendarDate,
+    shows_verified: &mut u32,
+) -> Result<(u32, String), String> {
+    if bundle.verifier_nonce != verifier_nonce {
+        return Err("Bundle answers a different verifier nonce".to_string());
+    }
+    let message_hash = expected_message_hash(verifier_nonce)?;
+    let mut proven_min_age = u32::MAX;
+    let (comm_w_shared, prepare_values) = verify_proof_bytes(
+        CircuitKind::Prepare,
+        "prepare",
+        &bundle.prepare_proof,
+        prepare_vk,
+    )?;
+    let prepare = PreparePublicValues::from_public_values(&prepare_values)
+        .map_err(|e| format!("Proof 'prepare': {}", e))?;
+    let issuer_key = issuer_keys
+        .identify(&prepare.issuer_key)
+        .ok_or("Prepare proof was made against an unknown issuer key")?;
+    if !issuer_key.is_valid_at(prepare.validity.issued_at) {
+        return Err(format!(
+            "Issuer key '{}' was not valid when the proof was issued",
+            issuer_key.kid
+        ));
+    }
+    if bundle.prepare_freshness != ProofFreshness::from(prepare.validity) {
+        return Err("Bundle freshness differs from the Prepare proof".to_string());
+    }
+    let shows = std::iter::once((PRIMARY_SHOW_LABEL, &bundle.show_proof)).chain(
+        bundle
+            .additional_shows
+            .iter()
+            .map(|show| (show.label.as_str(), &show.show_proof)),
+    );
+    for (label, proof) in shows {
+        let (show_comm_w_shared, public_values) =
+            verify_proof_bytes(CircuitKind::Show, label, proof, show_vk)?;
+        if show_comm_w_shared != comm_w_shared {
+            return Err(format!(
+                "Show '{}' does not share comm_W_shared with the Prepare proof",
+                label
+            ));
+        }
+        let statement = ShowStatement::from_public_values(&public_values)
+            .map_err(|e| format!("Proof '{}': {}", label, e))?;
+        if statement.message_hash != message_hash {
+            return Err(format!(
+                "Show '{}' was not signed over the verifier nonce",
+                label
+            ));
+        }
+        let age = statement.proven_min_age(today).ok_or_else(|| {
+            let date = statement.reference_date;
+            format!(
+                "Show '{}' proves an age on {}-{:02}-{:02}, after today",
+                label, date.year, date.month, date.day
+            )
+        })?;
+        proven_min_age = proven_min_age.min(age);
+        *shows_verified += 1;
+    }
+    Ok((proven_min_age, issuer_key.kid.clone()))
+}
+
+/// Verify serialized proof `bytes`, returning its comm_W_shared and the public
+/// values after the domain tag
+fn verify_proof_bytes(
+    circuit: CircuitKind,
+    label: &str,
+    bytes: &[u8],
+    vk: &VerifyingKey,
+) -> Result<(String, Vec<Scalar>), String> {
+    let proof =
+        parse_proof::<E>(bytes).map_err(|e| format!("Proof '{}' is malformed: {}", label, e))?;
+    let public_values = catch_unwind(AssertUnwindSafe(|| proof.verify(vk)))
+        .map_err(|panic| {
+            format!(
+                "Verifier panicked on '{}': {}",
+                label,
+                panic_message(panic.as_ref())
+            )
+        })?
+        .map_err(|e| format!("Proof '{}' rejected: {:?}", label, e))?;
+    let public_values =
+        check_domain(circuit, &public_values).map_err(|e| format!("Proof '{}': {}", label, e))?;
+    Ok((
+        format!("{:?}", proof.comm_W_shared()),
+        public_values.to_vec(),
+    ))
+}
@@ -0,0 +1,195 @@
+//! Prover settings shared by every prove and reblind.
+//!
+//! Devices with little RAM can select spartan2's low-memory path, which is
+//! slower but keeps the prover's peak memory down, either outright or by
+//! giving a memory budget. The thread count bounds the rayon pool a
+//! `*_with_options` call runs in, a seed makes the shared blinds sampled by
+//! reblinds reproducible, and the progress granularity decides whether the run
+//! log also records the prover's internal phases. The options set with
+//! `set_prove_options` apply to all entry points until replaced; the
+//! `*_with_options` variants apply theirs to a single call.
+
+use std::sync::RwLock;
+
+use ecdsa_spartan2::{
+    prover,
+    signpost::{self, PhaseObserver},
+};
+
+use crate::{session, ReblindPolicy, RngSource, ZkProofError};
+
+/// How finely proving progress is recorded in the run log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+pub enum ProgressGranularity {
+    /// One record per phase of an operation, e.g. "prove"
+    #[default]
+    Operation,
+    /// Also one record per prover phase: witness generation, commitment, prove, ...
+    Phase,
+}
+
+/// Settings applied to subsequent proves
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct ProveOptions {
+    /// Trade prove time for lower peak memory
+    pub small_memory: bool,
+    /// Take the low-memory path whenever the fast one is estimated to exceed this
+    pub memory_budget_bytes: Option<u64>,
+    /// Threads the prover may use in a `*_with_options` call; other calls, and
+    /// calls leaving this unset, use rayon's global pool (one thread per core)
+    pub num_threads: Option<u32>,
+    /// Seed for the shared blinds sampled by reblinds; the OS RNG when unset
+    pub seed: Option<Vec<u8>>,
+    pub progress: ProgressGranularity,
+    /// Overrides the stored presentation policy when a Show proof is handed out
+    pub reblind_policy: Option<ReblindPolicy>,
+}
+
+impl ProveOptions {
+    /// Where the shared blinds of a reblind come from under these options
+    pub(crate) fn rng_source(&self) -> RngSource {
+        match &self.seed {
+            Some(seed) => RngSource::Seed { seed: seed.clone() },
+            None => RngSource::Os,
+        }
+    }
+}
+
+impl From<&ProveOptions> for prover::ProveOptions {
+    fn from(options: &ProveOptions) -> Self {
+        Self {
+            small_memory: options.small_memory,
+            memory_budget_bytes: options.memory_budget_bytes,
+        }
+    }
+}
+
+static OPTIONS: RwLock<Option<ProveOptions>> = RwLock::new(None);
+
+fn install(options: ProveOptions) -> Option<ProveOptions> {
+    prover::set_prove_options((&options).into());
+    std::mem::replace(
+        &mut *OPTIONS.write().unwrap_or_else(|e| e.into_inner()),
+        Some(options),
+    )
+}
+
+/// Replace the settings used by subsequent proves
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_prove_options(options: ProveOptions) -> Result<(), ZkProofError> {
+    validate(&options)?;
+    session::run_exclusive(|| install(options));
+    Ok(())
+}
+
+/// The settings in force, `ProveOptions::default()` unless replaced
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn get_prove_options() -> ProveOptions {
+    OPTIONS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+fn validate(options: &ProveOptions) -> Result<(), ZkProofError> {
+    if options.num_threads == Some(0) {
+        return Err(ZkProofError::invalid_input(
+            "num_threads must be at least 1".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Run `f` with `options` in force, restoring the previous settings afterwards
+/// With `num_threads` set, `f` runs on a rayon pool of that size. It takes the
+/// operation lock on that pool, so it must not be called while holding it.
+pub(crate) fn with_prove_options<T: Send>(
+    options: ProveOptions,
+    f: impl FnOnce() -> Result<T, ZkProofError> + Send,
+) -> Result<T, ZkProofError> {
+    validate(&options)?;
+    let pool = options.num_threads.map(prover_pool).transpose()?;
+    let run = move || {
+        session::run_exclusive(|| {
+            let _restore = RestoreOptions(install(options));
+            f()
+        })
+    };
+    match pool {
+        Some(pool) => pool.install(run),
+        None => run(),
+    }
+}
+
+/// Puts back the settings replaced for one call, even if the call panics
+struct RestoreOptions(Option<ProveOptions>);
+
+impl Drop for RestoreOptions {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        prover::set_prove_options(previous.as_ref().map(Into::into).unwrap_or_default());
+        *OPTIONS.write().unwrap_or_else(|e| e.into_inner()) = previous;
+    }
+}
+
+fn prover_pool(num_threads: u32) -> Result<rayon::ThreadPool, ZkProofError> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads as usize)
+        .build()
+        .map_err(|e| {
+            ZkProofError::proof_generation_failed(format!(
+                "Failed to start {} prover threads: {}",
+                num_threads, e
+            ))
+            .with_source(&e)
+        })
+}
+
+/// Reports prover phases to `observer` until dropped, then restores the previous observer
+pub(crate) struct ObservePhases(Option<PhaseObserver>);
+
+impl ObservePhases {
+    /// `None` unless the options in force ask for per-phase progress
+    pub(crate) fn start(observer: impl FnOnce() -> PhaseObserver) -> Option<Self> {
+        (get_prove_options().progress == ProgressGranularity::Phase)
+            .then(|| Self(signpost::replace_phase_observer(Some(observer()))))
+    }
+}
+
+impl Drop for ObservePhases {
+    fn drop(&mut self) {
+        signpost::replace_phase_observer(self.0.take());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_options_are_restored() {
+        let before = get_prove_options();
+        let scoped = ProveOptions {
+            small_memory: true,
+            num_threads: Some(2),
+            seed: Some(b"seed".to_vec()),
+            ..ProveOptions::default()
+        };
+
+        let seen = with_prove_options(scoped.clone(), || {
+            Ok((get_prove_options(), rayon::current_num_threads()))
+        })
+        .unwrap();
+        assert_eq!(seen, (scoped, 2));
+        assert_eq!(get_prove_options(), before);
+
+        let zero_threads = ProveOptions {
+            num_threads: Some(0),
+            ..ProveOptions::default()
+        };
+        assert!(with_prove_options(zero_threads, || Ok(())).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! Verifier-scoped pseudonyms proved from the credential's device key.
+//!
+//! `prove_pseudonym` proves the holder's pseudonym for one service with the
+//! Pseudonym circuit, reblinded so it shares comm_W_shared with the Prepare
+//! proof. The service learns the same pseudonym on every visit, while two
+//! services cannot tell their pseudonyms belong to the same holder.
+
+use std::path::Path;
+
+use ecdsa_spartan2::{
+    load_proof, load_proving_key, load_shared_blinds,
+    prover::prove_circuit_with_pk,
+    setup::{
+        PREPARE_INSTANCE, PREPARE_PROOF, PSEUDONYM_INSTANCE, PSEUDONYM_PROOF,
+        PSEUDONYM_PROVING_KEY, PSEUDONYM_VERIFYING_KEY, PSEUDONYM_WITNESS, SHARED_BLINDS,
+    },
+    verifier_scope, PseudonymCircuit, PseudonymStatement, E,
+};
+
+use crate::{
+    device_key::load_show_input, extract_comm_w_shared, get_proof_size, prove_failed,
+    reblind_loaded, run_log::RunLog, setup_and_save_keys, verify_result_with, with_working_dir,
+    ProofResult, VerifyResult, ZkProofError,
+};
+
+/// Verifier id the setup input is scoped to; the keys do not depend on it
+const SETUP_VERIFIER_ID: &str = "setup";
+
+/// Outcome of `prove_pseudonym`
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PseudonymProof {
+    pub pseudonym: String,
+    pub proof: ProofResult,
+}
+
+/// Outcome of `verify_pseudonym`
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PseudonymVerification {
+    pub result: VerifyResult,
+    /// The proved pseudonym, when the proof is valid
+    pub pseudonym: Option<String>,
+}
+
+/// Setup Pseudonym circuit keys
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_pseudonym_keys(documents_path: String) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let circuit = PseudonymCircuit::from_show_input(&load_show_input(None)?, SETUP_VERIFIER_ID)
+            .map_err(ZkProofError::invalid_input)?;
+        let run = RunLog::start("setup_pseudonym_keys");
+
+        let start = std::time::Instant::now();
+        run.phase("setup", Some(PSEUDONYM_PROVING_KEY), || {
+            setup_and_save_keys(circuit, PSEUDONYM_PROVING_KEY, PSEUDONYM_VERIFYING_KEY)
+        })?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        Ok(format!(
+            "Pseudonym circuit keys setup completed in {}ms",
+            elapsed_ms
+        ))
+    })
+}
+
+/// Prove the holder's pseudonym for the service `verifier_id` (e.g. its origin)
+/// Reblinds under the current shared blinds, so the proof links to the stored
+/// Prepare proof.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_pseudonym(
+    documents_path: String,
+    verifier_id: String,
+) -> Result<PseudonymProof, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        if !Path::new(PSEUDONYM_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "Pseudonym proving key not found; call setup_pseudonym_keys first".to_string(),
+            ));
+        }
+        let show_input = load_show_input(None)?;
+        let statement = PseudonymStatement::from_show_input(&show_input, &verifier_id)
+            .map_err(ZkProofError::invalid_input)?;
+        let circuit = PseudonymCircuit::from_show_input(&show_input, &verifier_id)
+            .map_err(ZkProofError::invalid_input)?;
+        let pk = load_proving_key::<E>(PSEUDONYM_PROVING_KEY).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load proving key from '{}': {}",
+                PSEUDONYM_PROVING_KEY, e
+            ))
+            .with_source(&*e)
+        })?;
+        let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+            ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+                .with_source(&*e)
+        })?;
+        let run = RunLog::start("prove_pseudonym");
+
+        let start = std::time::Instant::now();
+        run.phase("prove", Some(PSEUDONYM_PROOF), || {
+            prove_circuit_with_pk(
+                circuit.clone(),
+                &pk,
+                PSEUDONYM_INSTANCE,
+                PSEUDONYM_WITNESS,
+                PSEUDONYM_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+        run.phase("reblind", Some(PSEUDONYM_PROOF), || {
+            reblind_loaded(
+                circuit,
+                &pk,
+                &shared_blinds,
+                PSEUDONYM_INSTANCE,
+                PSEUDONYM_WITNESS,
+                PSEUDONYM_PROOF,
+            )
+        })?;
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        let comm_w_shared = extract_comm_w_shared(PSEUDONYM_INSTANCE)?;
+        if comm_w_shared != extract_comm_w_shared(PREPARE_INSTANCE)? {
+            return Err(ZkProofError::verification_failed(
+                "Pseudonym and Prepare comm_W_shared differ; reblind Prepare under the current blinds"
+                    .to_string(),
+            ));
+        }
+
+        Ok(PseudonymProof {
+            pseudonym: format!("{:?}", statement.pseudonym),
+            proof: ProofResult {
+                prep_ms: 0,
+                prove_ms: total_ms,
+                total_ms,
+                proof_size_bytes: get_proof_size(PSEUDONYM_PROOF)?,
+                comm_w_shared,
+            },
+        })
+    })
+}
+
+/// Verify the stored Pseudonym proof was made for `verifier_id` and read its pseudonym
+/// A proof scoped to another service, or one whose comm_W_shared differs from
+/// the stored Prepare proof's, is rejected with the reason.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_pseudonym(
+    documents_path: String,
+    verifier_id: String,
+) -> Result<PseudonymVerification, ZkProofError> {
+    let scope = verifier_scope(&verifier_id);
+
+    with_working_dir(&documents_path, || {
+        let prepare_proof = load_proof::<E>(PREPARE_PROOF).map_err(|e| {
+            ZkProofError::file_not_found(format!(
+                "Failed to load proof from '{}': {}",
+                PREPARE_PROOF, e
+            ))
+            .with_source(&*e)
+        })?;
+        let prepare_comm_w_shared = format!("{:?}", prepare_proof.comm_W_shared());
+
+        let run = RunLog::start("verify_pseudonym");
+        let mut pseudonym = None;
+        let mut result = run.phase("verify", None, || {
+            verify_result_with(PSEUDONYM_PROOF, PSEUDONYM_VERIFYING_KEY, |public_values| {
+                let statement = PseudonymStatement::from_public_values(public_values)?;
+                if statement.scope != scope {
+                    return Err(format!(
+                        "proof is scoped to another verifier than '{}'",
+                        verifier_id
+                    ));
+                }
+                pseudonym = Some(format!("{:?}", statement.pseudonym));
+                Ok(())
+            })
+        })?;
+        if result.valid && result.comm_w_shared != prepare_comm_w_shared {
+            result.valid = false;
+            result.failure_reason = Some(
+                "Pseudonym proof does not share comm_W_shared with the Prepare proof".to_string(),
+            );
+        }
+        Ok(PseudonymVerification {
+            pseudonym: pseudonym.filter(|_| result.valid),
+            result,
+        })
+    })
+}
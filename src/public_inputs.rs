@@ -0,0 +1,162 @@
+//! Verification against public inputs the verifier supplies itself.
+//!
+//! A presentation's instance file is produced by the holder, so trusting the
+//! public values embedded in it lets the holder choose what the proof is
+//! checked against. `verify_with_public_inputs` instead takes the proof and
+//! verifying key as bytes and the expected public values from the verifier,
+//! and accepts the proof only if the values it proves are exactly those.
+//!
+//! A matching value only means something if the circuit constrains it. Every
+//! circuit below leads with its domain tag, which is not constrained and
+//! names the circuit rather than stating anything about the witness. After
+//! it, in order:
+//!
+//! ```text
+//! Prepare     issuedAt, expiresAt, issuerKeyX, issuerKeyY
+//! Show        messageHash, currentYear, currentMonth, currentDay
+//! Range       rangeMin, rangeMax
+//! Membership  root
+//! Pseudonym   scope, pseudonym
+//! ```
+//!
+//! - Prepare constrains the issuer key coordinates to the key the JWT
+//!   signature is checked against. `issuedAt` and `expiresAt` are NOT
+//!   constrained: they are whatever the prover put in its input (see
+//!   `ecdsa_spartan2::validity`), so matching them proves nothing.
+//! - Show constrains `messageHash` to the hash the device signature is checked
+//!   over (SHA-256 of the verifier nonce, see `show::expected_message_hash`),
+//!   the date to the one `ageAbove18` is computed on, and `ageAbove18` to 1.
+//! - Range constrains the bounds the claim is compared with, Membership the
+//!   Merkle root the claim is proved under, and Pseudonym the scope and the
+//!   pseudonym derived from it and the device key.
+//!
+//! Show, Range, Membership and Pseudonym also constrain the claim and device
+//! key they read to their shared witnesses, so they are about the credential
+//! the Prepare proof with the same `comm_W_shared` verified; compare that as
+//! well. The claim itself is never a public value. The demo and permutation
+//! circuits inputize their single output.
+
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::Instant,
+};
+
+use ecdsa_spartan2::{
+    setup::{deserialize_bounded, parse_proof},
+    Scalar, VerifyingKey, E,
+};
+use ff::PrimeField;
+
+use crate::{run_log::panic_message, VerifyResult, ZkProofError};
+
+/// Verify `proof_bytes` under the serialized verifying key `vk`, requiring its
+/// public values to equal `public_inputs` in order
+/// Each input is a field element, either `0x`-prefixed big-endian hex (as
+/// printed for comm_W_shared and pseudonyms) or decimal. A rejected proof or a
+/// mismatching value is reported in the result; errors mean the proof, key or
+/// inputs could not be decoded.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_with_public_inputs(
+    proof_bytes: Vec<u8>,
+    vk: Vec<u8>,
+    public_inputs: Vec<String>,
+) -> Result<VerifyResult, ZkProofError> {
+    let expected = public_inputs
+        .iter()
+        .map(|input| parse_public_input(input))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ZkProofError::invalid_input)?;
+    let proof = parse_proof::<E>(&proof_bytes).map_err(|e| {
+        ZkProofError::invalid_input(format!("Proof is malformed: {}", e)).with_source(&*e)
+    })?;
+    let vk: VerifyingKey = deserialize_bounded(&vk).map_err(|e| {
+        ZkProofError::invalid_input(format!("Verifying key is malformed: {}", e)).with_source(&*e)
+    })?;
+    let comm_w_shared = format!("{:?}", proof.comm_W_shared());
+
+    let start = Instant::now();
+    let outcome = catch_unwind(AssertUnwindSafe(|| proof.verify(&vk)));
+    let verify_ms = start.elapsed().as_millis() as u64;
+
+    let failure_reason = match outcome {
+        Ok(Ok(public_values)) => check_public_values(&public_values, &expected).err(),
+        Ok(Err(e)) => Some(format!("{:?}", e)),
+        Err(panic) => Some(format!(
+            "Verifier panicked: {}",
+            panic_message(panic.as_ref())
+        )),
+    };
+    Ok(VerifyResult {
+        valid: failure_reason.is_none(),
+        verify_ms,
+        failure_reason,
+        comm_w_shared,
+    })
+}
+
+/// Parse a field element written as `0x`-prefixed big-endian hex or decimal
+fn parse_public_input(input: &str) -> Result<Scalar, String> {
+    let input = input.trim();
+    let parsed = match input.strip_prefix("0x") {
+        Some(hex) => parse_hex(hex),
+        None => Scalar::from_str_vartime(input),
+    };
+    parsed.ok_or_else(|| format!("Public input '{}' is not a field element", input))
+}
+
+fn parse_hex(hex: &str) -> Option<Scalar> {
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    let bytes = repr.as_mut();
+    if hex.is_empty() || hex.len() > 2 * bytes.len() || !hex.is_ascii() {
+        return None;
+    }
+    // Left-pad to the full width, then store little-endian
+    let padded = format!("{:0>width$}", hex, width = 2 * bytes.len());
+    for (i, pair) in padded.as_bytes().chunks(2).rev().enumerate() {
+        bytes[i] = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Option::from(Scalar::from_repr(repr))
+}
+
+/// Compare proved `public_values` with the verifier's `expected` ones
+fn check_public_values(public_values: &[Scalar], expected: &[Scalar]) -> Result<(), String> {
+    if public_values.len() != expected.len() {
+        return Err(format!(
+            "Proof has {} public values, {} expected",
+            public_values.len(),
+            expected.len()
+        ));
+    }
+    match public_values.iter().zip(expected).position(|(a, b)| a != b) {
+        Some(index) => Err(format!(
+            "Public value {} differs from the expected input",
+            index
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_hex_and_decimal_inputs() {
+        let value = Scalar::from(0x1234u64);
+        assert_eq!(parse_public_input("4660").unwrap(), value);
+        assert_eq!(parse_public_input("0x1234").unwrap(), value);
+        assert_eq!(parse_public_input(&format!("{:?}", value)).unwrap(), value);
+        assert!(parse_public_input("0x").is_err());
+        assert!(parse_public_input("0xzz").is_err());
+        assert!(parse_public_input(&format!("0x{}", "f".repeat(64))).is_err());
+    }
+
+    #[test]
+    fn test_public_values_must_match_exactly() {
+        let values = [Scalar::from(1u64), Scalar::from(2u64)];
+        assert!(check_public_values(&values, &values).is_ok());
+        assert!(check_public_values(&values, &values[..1]).is_err());
+        let err = check_public_values(&values, &[Scalar::from(1u64), Scalar::from(3u64)]);
+        assert!(err.unwrap_err().contains("Public value 1"));
+    }
+}
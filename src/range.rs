@@ -0,0 +1,166 @@
+//! Range proofs over a numeric claim, linked to the stored Prepare proof.
+//!
+//! Proves `min <= value <= max` for the claim in the Show input with the
+//! dedicated Range circuit instead of the Show circuit, so income or score
+//! style predicates need no change to Show. The Range proof is reblinded under
+//! the credential's shared blinds; `verify_claim_range` accepts it only if its
+//! comm_W_shared matches the stored Prepare proof's, i.e. the range holds of
+//! the claim the issuer signed.
+
+use std::path::{Path, PathBuf};
+
+use ecdsa_spartan2::{
+    load_proof, load_proving_key, load_shared_blinds,
+    prover::prove_circuit_with_pk,
+    setup::{
+        PREPARE_INSTANCE, PREPARE_PROOF, RANGE_INSTANCE, RANGE_PROOF, RANGE_PROVING_KEY,
+        RANGE_VERIFYING_KEY, RANGE_WITNESS, SHARED_BLINDS,
+    },
+    RangeCircuit, RangeStatement, E,
+};
+
+use crate::{
+    device_key::load_show_input, extract_comm_w_shared, get_proof_size, prove_failed,
+    reblind_loaded, run_log::RunLog, setup_and_save_keys, verify_result_with, with_working_dir,
+    ProofResult, VerifyResult, ZkProofError,
+};
+
+/// Setup Range circuit keys
+/// Generates proving and verifying keys for the Range circuit
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn setup_range_keys(
+    documents_path: String,
+    input_path: Option<String>,
+) -> Result<String, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let circuit = RangeCircuit::new(input_path.map(PathBuf::from));
+        let run = RunLog::start("setup_range_keys");
+
+        let start = std::time::Instant::now();
+        run.phase("setup", Some(RANGE_PROVING_KEY), || {
+            setup_and_save_keys(circuit, RANGE_PROVING_KEY, RANGE_VERIFYING_KEY)
+        })?;
+        let elapsed_ms = start.elapsed().as_millis();
+
+        Ok(format!(
+            "Range circuit keys setup completed in {}ms",
+            elapsed_ms
+        ))
+    })
+}
+
+/// Prove that the Show input's claim value lies in `[min, max]`
+/// Proves over the same claim and device key as Show and reblinds under the
+/// current shared blinds, so the proof links to the stored Prepare proof.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn prove_claim_range(
+    documents_path: String,
+    min: u64,
+    max: u64,
+) -> Result<ProofResult, ZkProofError> {
+    let statement = RangeStatement::new(min, max).map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        if !Path::new(RANGE_PROVING_KEY).exists() {
+            return Err(ZkProofError::setup_required(
+                "Range proving key not found; call setup_range_keys first".to_string(),
+            ));
+        }
+        let circuit = RangeCircuit::from_show_input(&load_show_input(None)?, &statement)
+            .map_err(ZkProofError::invalid_input)?;
+        let pk = load_proving_key::<E>(RANGE_PROVING_KEY).map_err(|e| {
+            ZkProofError::setup_required(format!(
+                "Failed to load proving key from '{}': {}",
+                RANGE_PROVING_KEY, e
+            ))
+            .with_source(&*e)
+        })?;
+        let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+            ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+                .with_source(&*e)
+        })?;
+        let run = RunLog::start("prove_claim_range");
+
+        let start = std::time::Instant::now();
+        run.phase("prove", Some(RANGE_PROOF), || {
+            prove_circuit_with_pk(
+                circuit.clone(),
+                &pk,
+                RANGE_INSTANCE,
+                RANGE_WITNESS,
+                RANGE_PROOF,
+            )
+            .map_err(prove_failed)
+        })?;
+        run.phase("reblind", Some(RANGE_PROOF), || {
+            reblind_loaded(
+                circuit,
+                &pk,
+                &shared_blinds,
+                RANGE_INSTANCE,
+                RANGE_WITNESS,
+                RANGE_PROOF,
+            )
+        })?;
+        let total_ms = start.elapsed().as_millis() as u64;
+
+        let comm_w_shared = extract_comm_w_shared(RANGE_INSTANCE)?;
+        if comm_w_shared != extract_comm_w_shared(PREPARE_INSTANCE)? {
+            return Err(ZkProofError::verification_failed(
+                "Range and Prepare comm_W_shared differ; reblind Prepare under the current blinds"
+                    .to_string(),
+            ));
+        }
+
+        Ok(ProofResult {
+            prep_ms: 0,
+            prove_ms: total_ms,
+            total_ms,
+            proof_size_bytes: get_proof_size(RANGE_PROOF)?,
+            comm_w_shared,
+        })
+    })
+}
+
+/// Verify the stored Range proof states `[min, max]` and links to the Prepare proof
+/// A valid proof of another range, or one whose comm_W_shared differs from the
+/// stored Prepare proof's, is rejected with the reason.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn verify_claim_range(
+    documents_path: String,
+    min: u64,
+    max: u64,
+) -> Result<VerifyResult, ZkProofError> {
+    let expected = RangeStatement::new(min, max).map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || {
+        let prepare_proof = load_proof::<E>(PREPARE_PROOF).map_err(|e| {
+            ZkProofError::file_not_found(format!(
+                "Failed to load proof from '{}': {}",
+                PREPARE_PROOF, e
+            ))
+            .with_source(&*e)
+        })?;
+        let prepare_comm_w_shared = format!("{:?}", prepare_proof.comm_W_shared());
+
+        let run = RunLog::start("verify_claim_range");
+        let mut result = run.phase("verify", None, || {
+            verify_result_with(RANGE_PROOF, RANGE_VERIFYING_KEY, |public_values| {
+                let statement = RangeStatement::from_public_values(public_values)?;
+                if statement != expected {
+                    return Err(format!(
+                        "proof states [{}, {}], expected [{}, {}]",
+                        statement.min, statement.max, expected.min, expected.max
+                    ));
+                }
+                Ok(())
+            })
+        })?;
+        if result.valid && result.comm_w_shared != prepare_comm_w_shared {
+            result.valid = false;
+            result.failure_reason =
+                Some("Range proof does not share comm_W_shared with the Prepare proof".to_string());
+        }
+        Ok(result)
+    })
+}
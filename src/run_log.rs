@@ -0,0 +1,312 @@
+//! JSON-lines record of proving runs.
+//!
+//! When enabled, every phase of an FFI operation is appended to
+//! `logs/prover_runs.jsonl` under the documents directory, one JSON object per
+//! line, so failed in-field proofs can be diagnosed from user-submitted logs.
+//! With `ProgressGranularity::Phase` in the prove options, the prover's own
+//! phases are recorded too, named `prover.<phase>`. A run that starts with the
+//! log over `MAX_RUN_LOG_BYTES` first moves it to `logs/prover_runs.1.jsonl`,
+//! replacing the previous one, so the logs stay under about twice that size.
+
+use std::{
+    fs::{self, create_dir_all, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use ecdsa_spartan2::{layout::in_documents, signpost::Phase};
+use serde::{Deserialize, Serialize};
+
+use crate::{prove_options::ObservePhases, ZkProofError};
+
+/// Location of the run log, relative to the documents directory
+pub const RUN_LOG_PATH: &str = "logs/prover_runs.jsonl";
+
+/// The run log before the last rotation
+pub const ROTATED_RUN_LOG_PATH: &str = "logs/prover_runs.1.jsonl";
+
+/// Size at which the run log is rotated
+pub const MAX_RUN_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Bytes read at a time when reading the run log from its end
+const TAIL_CHUNK_BYTES: u64 = 64 * 1024;
+
+static RUN_LOG_ENABLED: AtomicBool = AtomicBool::new(false);
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A single timed phase of a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct PhaseRecord {
+    pub run_id: String,
+    pub operation: String,
+    pub phase: String,
+    pub start_unix_ms: i64,
+    pub duration_ms: u64,
+    pub bytes: Option<u64>,
+    pub result: String,
+}
+
+/// All phases recorded for the most recent run
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct RunReport {
+    pub run_id: String,
+    pub operation: String,
+    pub phases: Vec<PhaseRecord>,
+}
+
+/// Enable or disable the JSON-lines run log
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_run_log_enabled(enabled: bool) {
+    RUN_LOG_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Return the phases of the last recorded run, if any
+/// Only the end of the log is read.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn get_last_run_report(documents_path: String) -> Result<Option<RunReport>, ZkProofError> {
+    // A run that has just rotated the log may not have recorded a phase yet
+    for log in [RUN_LOG_PATH, ROTATED_RUN_LOG_PATH] {
+        let path = Path::new(&documents_path).join(log);
+        if !path.exists() {
+            continue;
+        }
+        if let Some(report) = last_run(&path, TAIL_CHUNK_BYTES)? {
+            return Ok(Some(report));
+        }
+    }
+    Ok(None)
+}
+
+/// The last run recorded in the log at `path`, read backwards `chunk_len`
+/// bytes at a time until a record of an earlier run turns up
+fn last_run(path: &Path, chunk_len: u64) -> std::io::Result<Option<RunReport>> {
+    let mut file = File::open(path)?;
+    let mut end = file.metadata()?.len();
+    // Start of the line the previous chunk began in the middle of
+    let mut partial_line = Vec::new();
+    let mut report: Option<RunReport> = None;
+
+    while end > 0 {
+        let start = end.saturating_sub(chunk_len);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut partial_line);
+
+        let mut lines = chunk.split(|b| *b == b'\n');
+        if start > 0 {
+            partial_line = lines.next().unwrap_or_default().to_vec();
+        }
+        for line in lines.rev() {
+            // Skip lines truncated by a crash mid-write
+            let Ok(record) = serde_json::from_slice::<PhaseRecord>(line) else {
+                continue;
+            };
+            match report.as_mut() {
+                Some(current) if current.run_id != record.run_id => {
+                    current.phases.reverse();
+                    return Ok(report);
+                }
+                Some(current) => current.phases.push(record),
+                None => {
+                    report = Some(RunReport {
+                        run_id: record.run_id.clone(),
+                        operation: record.operation.clone(),
+                        phases: vec![record],
+                    })
+                }
+            }
+        }
+        end = start;
+    }
+
+    if let Some(current) = report.as_mut() {
+        current.phases.reverse();
+    }
+    Ok(report)
+}
+
+/// Records the phases of one FFI operation
+/// Paths are resolved against the current working directory (the documents dir)
+pub(crate) struct RunLog {
+    run_id: String,
+    operation: String,
+    /// Records prover phases for as long as the run lasts, if asked to
+    _prover_phases: Option<ObservePhases>,
+}
+
+impl RunLog {
+    pub(crate) fn start(operation: &str) -> Self {
+        let counter = RUN_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let run_id = format!(
+            "{}-{}-{}",
+            operation,
+            chrono::Utc::now().timestamp_millis(),
+            counter
+        );
+        let prover_phases = if RUN_LOG_ENABLED.load(Ordering::Relaxed) {
+            rotate_if_full();
+            let (run_id, operation) = (run_id.clone(), operation.to_string());
+            ObservePhases::start(move || {
+                Arc::new(move |phase: Phase, duration: Duration| {
+                    let duration_ms = duration.as_millis() as u64;
+                    append(PhaseRecord {
+                        run_id: run_id.clone(),
+                        operation: operation.clone(),
+                        phase: format!("prover.{}", phase.name()),
+                        start_unix_ms: chrono::Utc::now().timestamp_millis() - duration_ms as i64,
+                        duration_ms,
+                        bytes: None,
+                        result: "ok".to_string(),
+                    })
+                })
+            })
+        } else {
+            None
+        };
+        Self {
+            run_id,
+            operation: operation.to_string(),
+            _prover_phases: prover_phases,
+        }
+    }
+
+    /// Run `f` as a named phase, recording its duration, result, and the size of
+    /// `artifact` (if given) once the phase has completed
+    pub(crate) fn phase<T, F>(
+        &self,
+        phase: &str,
+        artifact: Option<&str>,
+        f: F,
+    ) -> Result<T, ZkProofError>
+    where
+        F: FnOnce() -> Result<T, ZkProofError>,
+    {
+        if !RUN_LOG_ENABLED.load(Ordering::Relaxed) {
+            return f();
+        }
+
+        let start_unix_ms = chrono::Utc::now().timestamp_millis();
+        let start = Instant::now();
+        // Panics inside the prover are recorded before being propagated
+        let outcome = catch_unwind(AssertUnwindSafe(f));
+        let duration_ms = start.elapsed().as_millis() as u64;
+
+        let result = match &outcome {
+            Ok(Ok(_)) => "ok".to_string(),
+            Ok(Err(e)) => format!("error: {}", e),
+            Err(panic) => format!("panic: {}", panic_message(panic.as_ref())),
+        };
+        let bytes = match &outcome {
+            Ok(Ok(_)) => artifact
+                .and_then(|p| std::fs::metadata(in_documents(p)).ok())
+                .map(|m| m.len()),
+            _ => None,
+        };
+
+        append(PhaseRecord {
+            run_id: self.run_id.clone(),
+            operation: self.operation.clone(),
+            phase: phase.to_string(),
+            start_unix_ms,
+            duration_ms,
+            bytes,
+            result,
+        });
+
+        match outcome {
+            Ok(result) => result,
+            Err(panic) => resume_unwind(panic),
+        }
+    }
+}
+
+/// Move a run log grown past `MAX_RUN_LOG_BYTES` aside, so a run is never
+/// split between the two files
+fn rotate_if_full() {
+    let path = in_documents(RUN_LOG_PATH);
+    if fs::metadata(&path).is_ok_and(|m| m.len() >= MAX_RUN_LOG_BYTES) {
+        if let Err(e) = fs::rename(&path, in_documents(ROTATED_RUN_LOG_PATH)) {
+            tracing::warn!("Failed to rotate run log: {}", e);
+        }
+    }
+}
+
+fn append(record: PhaseRecord) {
+    // Logging must never fail the proof itself
+    let write = || -> Result<(), Box<dyn std::error::Error>> {
+        let path = in_documents(RUN_LOG_PATH);
+        if let Some(parent) = Path::new(&path).parent() {
+            create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&record)?)?;
+        Ok(())
+    };
+
+    if let Err(e) = write() {
+        tracing::warn!("Failed to append run log: {}", e);
+    }
+}
+
+pub(crate) fn panic_message(panic: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(run_id: &str, phase: &str) -> String {
+        serde_json::to_string(&PhaseRecord {
+            run_id: run_id.to_string(),
+            operation: "prove_show".to_string(),
+            phase: phase.to_string(),
+            start_unix_ms: 0,
+            duration_ms: 1,
+            bytes: None,
+            result: "ok".to_string(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_last_run_is_read_from_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("runs.jsonl");
+        let log = [
+            record("a", "prove"),
+            record("b", "load"),
+            "{\"run_id\":\"b\",\"trunc".to_string(),
+            record("b", "prove"),
+            record("b", "save"),
+        ]
+        .join("\n");
+        fs::write(&path, log + "\n").unwrap();
+
+        // Chunks smaller than a line as well as one holding the whole log
+        for chunk_len in [7, TAIL_CHUNK_BYTES] {
+            let report = last_run(&path, chunk_len).unwrap().unwrap();
+            assert_eq!(report.run_id, "b");
+            let phases: Vec<_> = report.phases.iter().map(|p| p.phase.as_str()).collect();
+            assert_eq!(phases, ["load", "prove", "save"]);
+        }
+
+        fs::write(&path, "").unwrap();
+        assert!(last_run(&path, TAIL_CHUNK_BYTES).unwrap().is_none());
+    }
+}
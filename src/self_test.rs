@@ -0,0 +1,160 @@
+//! Quick end-to-end health check of the native prover.
+//!
+//! Runs setup, prove and verify over a small `SyntheticCircuit` in the documents
+//! directory. That exercises the same prover, artifact I/O and verifier as the
+//! credential circuits in well under a second, so the app can detect a broken
+//! build, unwritable storage or low memory before starting a long JWT proof.
+
+use std::{
+    fs,
+    panic::{catch_unwind, AssertUnwindSafe},
+    time::Instant,
+};
+
+use ecdsa_spartan2::{
+    load_proof, prover::prove_circuit_with_pk, setup::setup_circuit_keys_no_save,
+    spartan2::traits::snark::R1CSSNARKTrait, SyntheticCircuit, E,
+};
+
+use crate::{run_log::panic_message, with_working_dir, ZkProofError};
+
+/// Constraints in the self-test circuit
+pub const SELF_TEST_CONSTRAINTS: usize = 1 << 10;
+
+const SELF_TEST_DIR: &str = "keys/self_test";
+const SELF_TEST_INSTANCE: &str = "keys/self_test/instance";
+const SELF_TEST_WITNESS: &str = "keys/self_test/witness";
+const SELF_TEST_PROOF: &str = "keys/self_test/proof";
+const SELF_TEST_PROBE: &str = "keys/self_test/probe";
+
+/// Diagnostics from `self_test`
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    /// The step that failed and why
+    pub failure_reason: Option<String>,
+    pub setup_ms: u64,
+    pub prove_ms: u64,
+    pub verify_ms: u64,
+    /// Whether a file could be written to and read back from the documents directory
+    pub storage_writable: bool,
+    /// Memory the OS reports as available, where it can be read (Linux/Android)
+    pub available_memory_bytes: Option<u64>,
+    pub rayon_threads: u32,
+}
+
+/// Run a tiny circuit through setup, prove and verify
+/// Failures inside the pipeline, including prover panics, are reported in the
+/// result; an error means `documents_path` could not be entered.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn self_test(documents_path: String) -> Result<SelfTestReport, ZkProofError> {
+    with_working_dir(&documents_path, || {
+        let storage = probe_storage();
+        let mut report = SelfTestReport {
+            passed: false,
+            failure_reason: None,
+            setup_ms: 0,
+            prove_ms: 0,
+            verify_ms: 0,
+            storage_writable: storage.is_ok(),
+            available_memory_bytes: available_memory_bytes(),
+            rayon_threads: rayon::current_num_threads() as u32,
+        };
+        if let Err(reason) = storage {
+            report.failure_reason = Some(reason);
+            let _ = fs::remove_dir_all(SELF_TEST_DIR);
+            return Ok(report);
+        }
+
+        if let Err(reason) = run_pipeline(&mut report) {
+            report.failure_reason = Some(reason);
+        } else {
+            report.passed = true;
+        }
+        let _ = fs::remove_dir_all(SELF_TEST_DIR);
+        Ok(report)
+    })
+}
+
+fn run_pipeline(report: &mut SelfTestReport) -> Result<(), String> {
+    let circuit = SyntheticCircuit::new(SELF_TEST_CONSTRAINTS);
+
+    let ((pk, vk), setup_ms) = step("setup", || {
+        Ok(setup_circuit_keys_no_save::<E, _>(circuit.clone()))
+    })?;
+    report.setup_ms = setup_ms;
+
+    let ((), prove_ms) = step("prove", || {
+        prove_circuit_with_pk::<E, _>(
+            circuit.clone(),
+            &pk,
+            SELF_TEST_INSTANCE,
+            SELF_TEST_WITNESS,
+            SELF_TEST_PROOF,
+        )
+        .map_err(|e| e.to_string())
+    })?;
+    report.prove_ms = prove_ms;
+
+    let proof = load_proof::<E>(SELF_TEST_PROOF)
+        .map_err(|e| format!("Failed to read back proof: {}", e))?;
+    let (_, verify_ms) = step("verify", || {
+        proof.verify(&vk).map_err(|e| format!("{:?}", e))
+    })?;
+    report.verify_ms = verify_ms;
+    Ok(())
+}
+
+/// Write a file to `SELF_TEST_DIR` and read it back
+/// Creating the directory can succeed on storage that then refuses writes
+/// (full disk, read-only mount, data protection locked), so a real write is
+/// the only reliable check.
+fn probe_storage() -> Result<(), String> {
+    const PROBE: &[u8] = b"self-test";
+    fs::create_dir_all(SELF_TEST_DIR)
+        .map_err(|e| format!("Cannot create '{}': {}", SELF_TEST_DIR, e))?;
+    fs::write(SELF_TEST_PROBE, PROBE)
+        .map_err(|e| format!("Cannot write '{}': {}", SELF_TEST_PROBE, e))?;
+    let read = fs::read(SELF_TEST_PROBE)
+        .map_err(|e| format!("Cannot read back '{}': {}", SELF_TEST_PROBE, e))?;
+    if read != PROBE {
+        return Err(format!("'{}' read back differently", SELF_TEST_PROBE));
+    }
+    fs::remove_file(SELF_TEST_PROBE)
+        .map_err(|e| format!("Cannot remove '{}': {}", SELF_TEST_PROBE, e))
+}
+
+/// Run `f`, timing it and turning errors and panics into a reason naming `name`
+fn step<T>(name: &str, f: impl FnOnce() -> Result<T, String>) -> Result<(T, u64), String> {
+    let start = Instant::now();
+    let result = catch_unwind(AssertUnwindSafe(f));
+    let elapsed_ms = start.elapsed().as_millis() as u64;
+    match result {
+        Ok(Ok(value)) => Ok((value, elapsed_ms)),
+        Ok(Err(e)) => Err(format!("{} failed: {}", name, e)),
+        Err(panic) => Err(format!(
+            "{} panicked: {}",
+            name,
+            panic_message(panic.as_ref())
+        )),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn available_memory_bytes() -> Option<u64> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let kib = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn available_memory_bytes() -> Option<u64> {
+    None
+}
@@ -0,0 +1,235 @@
+//! Sessions that carry their own documents directory, domain and namespace.
+//!
+//! The free functions run in the process working directory and read the global
+//! domain separator and artifact namespace, so two Dart isolates calling them
+//! with different documents directories or settings interfere: one changes the
+//! working directory or domain under the other mid-proof. A session created by
+//! `create_session` remembers its documents directory, domain and credential
+//! namespace and installs them around each of its operations:
+//!
+//! - every operation (sessions' and the free functions') holds the process-wide
+//!   operation lock while it changes the working directory, so operations never
+//!   interleave;
+//! - a session installs its domain and namespace only while it holds the lock
+//!   and restores the previous ones before releasing it.
+//!
+//! This serializes sessions; it does not isolate them. The directory, domain
+//! and namespace are still process-global while an operation runs, so code
+//! that reads or sets them without the operation lock (`set_domain_separator`,
+//! `set_artifact_namespace`, `verify_presentation_with_keys`) sees the running
+//! session's values, and the other globals (JWT variant, artifact layout,
+//! prove options, ...) are shared by every session. Circuits load their R1CS
+//! files relative to the working directory, so operations of parallel sessions
+//! run one after another rather than concurrently. The lock is reentrant, so an
+//! operation may call others.
+
+use std::{
+    path::Path,
+    sync::{Arc, Condvar, Mutex},
+    thread::{self, ThreadId},
+};
+
+use ecdsa_spartan2::{
+    domain::{domain_separator, set_domain_separator as set_domain},
+    namespace::{artifact_namespace, set_artifact_namespace as set_namespace, ArtifactNamespace},
+};
+
+use crate::{
+    complete_presentation, import_credential, prove_prepare, prove_show, reblind_all,
+    setup_prepare_keys, setup_show_keys, start_presentation, verify_prepare, verify_presentation,
+    verify_show, Challenge, CredentialReceipt, IssuerPublicKey, PresentationBundle,
+    PresentationVerification, ProofResult, ReblindAllResult, VerifyResult, ZkProofError,
+};
+
+/// Thread holding the operation lock and how many times it has entered it
+static OWNER: Mutex<Option<(ThreadId, usize)>> = Mutex::new(None);
+static RELEASED: Condvar = Condvar::new();
+
+/// Run `f` holding the process-wide operation lock
+pub(crate) fn run_exclusive<T>(f: impl FnOnce() -> T) -> T {
+    let _guard = OperationGuard::acquire();
+    f()
+}
+
+struct OperationGuard;
+
+impl OperationGuard {
+    fn acquire() -> Self {
+        let me = thread::current().id();
+        let mut owner = OWNER.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            match owner.as_mut() {
+                None => {
+                    *owner = Some((me, 1));
+                    return Self;
+                }
+                Some((thread, depth)) if *thread == me => {
+                    *depth += 1;
+                    return Self;
+                }
+                Some(_) => owner = RELEASED.wait(owner).unwrap_or_else(|e| e.into_inner()),
+            }
+        }
+    }
+}
+
+impl Drop for OperationGuard {
+    fn drop(&mut self) {
+        let mut owner = OWNER.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, depth)) = owner.as_mut() {
+            *depth -= 1;
+            if *depth == 0 {
+                *owner = None;
+                RELEASED.notify_one();
+            }
+        }
+    }
+}
+
+/// Settings of a session
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct SessionConfig {
+    /// Directory holding keys, proofs and credential inputs
+    pub documents_path: String,
+    /// Deployment domain bound into proofs; `None` uses the default
+    pub domain: Option<String>,
+    /// Credential namespace (see `set_artifact_namespace`); `None` uses `keys/`
+    pub credential_id: Option<String>,
+}
+
+/// Handle whose operations run under its own settings and restore the global ones
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct ZkSession {
+    documents_path: String,
+    domain: Option<String>,
+    namespace: ArtifactNamespace,
+}
+
+/// Open a session over `config.documents_path` with its own domain and namespace
+/// Sessions may be driven from several threads or Dart isolates; their
+/// operations are serialized, not isolated. See the module documentation.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn create_session(config: SessionConfig) -> Result<Arc<ZkSession>, ZkProofError> {
+    if !Path::new(&config.documents_path).is_dir() {
+        return Err(ZkProofError::file_not_found(format!(
+            "Documents directory '{}' does not exist",
+            config.documents_path
+        )));
+    }
+    if config.domain.as_deref().is_some_and(str::is_empty) {
+        return Err(ZkProofError::invalid_input(
+            "Domain separator must not be empty".to_string(),
+        ));
+    }
+    let namespace = match config.credential_id {
+        Some(id) => ArtifactNamespace::credential(&id).map_err(ZkProofError::invalid_input)?,
+        None => ArtifactNamespace::Legacy,
+    };
+
+    Ok(Arc::new(ZkSession {
+        documents_path: config.documents_path,
+        domain: config.domain,
+        namespace,
+    }))
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+impl ZkSession {
+    pub fn documents_path(&self) -> String {
+        self.documents_path.clone()
+    }
+
+    pub fn setup_prepare_keys(&self, input_path: Option<String>) -> Result<String, ZkProofError> {
+        self.run(|dir| setup_prepare_keys(dir, input_path))
+    }
+
+    pub fn setup_show_keys(&self, input_path: Option<String>) -> Result<String, ZkProofError> {
+        self.run(|dir| setup_show_keys(dir, input_path))
+    }
+
+    pub fn import_credential(
+        &self,
+        jwt: String,
+        issuer_public_key: Vec<u8>,
+    ) -> Result<CredentialReceipt, ZkProofError> {
+        self.run(|dir| import_credential(dir, jwt, issuer_public_key))
+    }
+
+    pub fn prove_prepare(
+        &self,
+        input_path: Option<String>,
+        max_duration_ms: Option<u64>,
+    ) -> Result<ProofResult, ZkProofError> {
+        self.run(|dir| prove_prepare(dir, input_path, max_duration_ms))
+    }
+
+    pub fn prove_show(
+        &self,
+        input_path: Option<String>,
+        max_duration_ms: Option<u64>,
+    ) -> Result<ProofResult, ZkProofError> {
+        self.run(|dir| prove_show(dir, input_path, max_duration_ms))
+    }
+
+    pub fn reblind_all(&self) -> Result<ReblindAllResult, ZkProofError> {
+        self.run(reblind_all)
+    }
+
+    pub fn verify_prepare(&self) -> Result<VerifyResult, ZkProofError> {
+        self.run(verify_prepare)
+    }
+
+    pub fn verify_show(&self) -> Result<VerifyResult, ZkProofError> {
+        self.run(verify_show)
+    }
+
+    pub fn start_presentation(&self, verifier_nonce: Vec<u8>) -> Result<Challenge, ZkProofError> {
+        self.run(|dir| start_presentation(dir, verifier_nonce))
+    }
+
+    pub fn complete_presentation(
+        &self,
+        device_signature: Vec<u8>,
+    ) -> Result<PresentationBundle, ZkProofError> {
+        self.run(|dir| complete_presentation(dir, device_signature))
+    }
+
+    pub fn verify_presentation(
+        &self,
+        bundle: PresentationBundle,
+        verifier_nonce: Vec<u8>,
+        issuer_keys: Vec<IssuerPublicKey>,
+    ) -> Result<PresentationVerification, ZkProofError> {
+        self.run(|dir| verify_presentation(dir, bundle, verifier_nonce, issuer_keys))
+    }
+}
+
+impl ZkSession {
+    /// Run `op` on the documents directory with this session's settings installed
+    pub(crate) fn run<T>(&self, op: impl FnOnce(String) -> T) -> T {
+        run_exclusive(|| {
+            let _saved = SavedSettings {
+                domain: domain_separator(),
+                namespace: artifact_namespace(),
+            };
+            set_domain(self.domain.clone());
+            set_namespace(self.namespace.clone());
+            op(self.documents_path.clone())
+        })
+    }
+}
+
+/// Global settings in force before a session operation, restored when dropped
+/// so that they come back even if the operation panics
+struct SavedSettings {
+    domain: String,
+    namespace: ArtifactNamespace,
+}
+
+impl Drop for SavedSettings {
+    fn drop(&mut self) {
+        set_domain(Some(std::mem::take(&mut self.domain)));
+        set_namespace(std::mem::take(&mut self.namespace));
+    }
+}
@@ -0,0 +1,154 @@
+//! Concurrent-session stress check for test builds.
+//!
+//! `stress_test_concurrent` drives several sessions from as many
+//! threads, each with its own documents subdirectory, domain and credential
+//! namespace, through rounds of proving and verifying the demo circuit. Every
+//! round proves a secret unique to its session and round and verifies against
+//! that secret, so a proof written into another session's directory, or under
+//! another session's domain, is caught. The process working directory and
+//! global domain must be unchanged afterwards. It guards the guarantees of
+//! `session` against reintroduced working-directory or thread-local state.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Instant,
+};
+
+use ecdsa_spartan2::domain::domain_separator;
+
+use crate::{
+    create_session, prove_demo, run_log::panic_message, setup_demo_keys, verify_demo,
+    SessionConfig, ZkProofError,
+};
+
+/// Working directory of the sessions, under the documents directory
+const STRESS_DIR: &str = "stress_test";
+
+/// Outcome of a passing `stress_test_concurrent`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct StressTestReport {
+    pub sessions: u32,
+    pub iterations: u32,
+    /// Demo proofs that verified, `sessions * iterations` on success
+    pub proofs_verified: u32,
+    pub total_ms: u64,
+}
+
+/// Prove and verify in `sessions` parallel sessions, `iterations` times each
+/// Fails with `VerificationFailed`, naming every session that went wrong, if a
+/// proof does not verify, a session panics or sees the wrong domain, or the
+/// process working directory or domain changed. Test builds only.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn stress_test_concurrent(
+    documents_path: String,
+    sessions: u32,
+    iterations: u32,
+) -> Result<StressTestReport, ZkProofError> {
+    if sessions == 0 || iterations == 0 {
+        return Err(ZkProofError::invalid_input(
+            "Stress test needs at least one session and one iteration".to_string(),
+        ));
+    }
+    let cwd = std::env::current_dir()?;
+    let global_domain = domain_separator();
+    let root = Path::new(&documents_path).join(STRESS_DIR);
+
+    let start = Instant::now();
+    let outcomes: Vec<Result<u32, String>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..sessions)
+            .map(|index| {
+                let root = &root;
+                scope.spawn(move || run_session(root, index, iterations))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .enumerate()
+            .map(|(index, handle)| {
+                handle.join().unwrap_or_else(|panic| {
+                    Err(format!(
+                        "session {} panicked: {}",
+                        index,
+                        panic_message(panic.as_ref())
+                    ))
+                })
+            })
+            .collect()
+    });
+    let total_ms = start.elapsed().as_millis() as u64;
+    let _ = fs::remove_dir_all(&root);
+
+    let mut proofs_verified = 0;
+    let mut failures = Vec::new();
+    for outcome in outcomes {
+        match outcome {
+            Ok(verified) => proofs_verified += verified,
+            Err(reason) => failures.push(reason),
+        }
+    }
+    if std::env::current_dir()? != cwd {
+        failures.push("process working directory changed".to_string());
+    }
+    if domain_separator() != global_domain {
+        failures.push("global domain separator changed".to_string());
+    }
+    if !failures.is_empty() {
+        return Err(ZkProofError::verification_failed(format!(
+            "Concurrent sessions failed: {}",
+            failures.join("; ")
+        )));
+    }
+
+    Ok(StressTestReport {
+        sessions,
+        iterations,
+        proofs_verified,
+        total_ms,
+    })
+}
+
+/// Set up, then prove and verify `iterations` times in session `index`
+fn run_session(root: &Path, index: u32, iterations: u32) -> Result<u32, String> {
+    let dir: PathBuf = root.join(format!("session-{}", index));
+    fs::create_dir_all(&dir).map_err(|e| format!("session {}: {}", index, e))?;
+    let domain = format!("stress-test-{}", index);
+    let session = create_session(SessionConfig {
+        documents_path: dir.to_string_lossy().into_owned(),
+        domain: Some(domain.clone()),
+        credential_id: Some(format!("stress-{}", index)),
+    })
+    .map_err(|e| format!("session {}: {}", index, e))?;
+    session
+        .run(setup_demo_keys)
+        .map_err(|e| format!("session {} setup: {}", index, e))?;
+
+    let mut verified = 0;
+    for round in 0..iterations {
+        let secret = (u64::from(index) << 32) | u64::from(round);
+        let result = session.run(|dir| {
+            if domain_separator() != domain {
+                return Err(ZkProofError::verification_failed(
+                    "session domain is not installed".to_string(),
+                ));
+            }
+            prove_demo(dir.clone(), Some(secret))?;
+            verify_demo(dir, Some(secret))
+        });
+        match result {
+            Ok(result) if result.valid => verified += 1,
+            Ok(result) => {
+                return Err(format!(
+                    "session {} round {}: {}",
+                    index,
+                    round,
+                    result.failure_reason.unwrap_or_default()
+                ))
+            }
+            Err(e) => return Err(format!("session {} round {}: {}", index, round, e)),
+        }
+    }
+    Ok(verified)
+}
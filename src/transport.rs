@@ -0,0 +1,251 @@
+//! Splitting presentation bundles into bounded frames for QR and BLE.
+//!
+//! In-person presentation moves the bundle over channels with small payloads:
+//! one QR code in an animated sequence, or one GATT write. Each frame is
+//!
+//! ```text
+//! version (1) | bundle id (8) | index (2) | total (2) | checksum (4) | data
+//! ```
+//!
+//! with integers big-endian. The bundle id is the start of the SHA-256 of the
+//! serialized bundle, so frames from another presentation are rejected and the
+//! reassembled bytes are checked end to end. The checksum is the start of the
+//! SHA-256 of the rest of the frame. Frames may arrive in any order and
+//! repeated, as QR loops replay them.
+
+use std::collections::BTreeMap;
+
+use sha2::{Digest, Sha256};
+
+use ecdsa_spartan2::setup::deserialize_bounded;
+
+use crate::{
+    get_verifier_limits, presentation_flow::PresentationBundle, verifier_limits::check_bundle_len,
+    ZkProofError,
+};
+
+/// Frame layout version
+/// Version 2 bundles carry `additional_shows`.
+const FRAME_VERSION: u8 = 2;
+
+const ID_LEN: usize = 8;
+const CHECKSUM_LEN: usize = 4;
+
+/// Bytes of framing in every chunk
+pub const CHUNK_HEADER_BYTES: u32 = (1 + ID_LEN + 2 + 2 + CHECKSUM_LEN) as u32;
+
+/// Split `bundle` into frames of at most `max_chunk_bytes` bytes each
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn chunk_presentation(
+    bundle: PresentationBundle,
+    max_chunk_bytes: u32,
+) -> Result<Vec<Vec<u8>>, ZkProofError> {
+    if max_chunk_bytes <= CHUNK_HEADER_BYTES {
+        return Err(ZkProofError::invalid_input(format!(
+            "max_chunk_bytes must exceed the {}-byte frame header",
+            CHUNK_HEADER_BYTES
+        )));
+    }
+    let payload = encode_presentation(bundle)?;
+    let id = bundle_id(&payload);
+
+    let data_len = (max_chunk_bytes - CHUNK_HEADER_BYTES) as usize;
+    let total = payload.len().div_ceil(data_len);
+    let total = u16::try_from(total).map_err(|_| {
+        ZkProofError::invalid_input(format!(
+            "Bundle needs {} chunks of {} bytes; at most {} are supported",
+            total,
+            max_chunk_bytes,
+            u16::MAX
+        ))
+    })?;
+
+    Ok(payload
+        .chunks(data_len)
+        .enumerate()
+        .map(|(index, data)| {
+            let mut frame = Vec::with_capacity(CHUNK_HEADER_BYTES as usize + data.len());
+            frame.push(FRAME_VERSION);
+            frame.extend_from_slice(&id);
+            frame.extend_from_slice(&(index as u16).to_be_bytes());
+            frame.extend_from_slice(&total.to_be_bytes());
+            let checksum = checksum(&frame, data);
+            frame.extend_from_slice(&checksum);
+            frame.extend_from_slice(data);
+            frame
+        })
+        .collect())
+}
+
+/// Rebuild a bundle from the frames produced by `chunk_presentation`
+/// Order does not matter and repeated frames are ignored; a missing or
+/// corrupted frame, or frames from two bundles, are an error.
+/// So is a bundle larger than the verifier limits' `max_bundle_bytes`.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn reassemble_presentation(chunks: Vec<Vec<u8>>) -> Result<PresentationBundle, ZkProofError> {
+    let frames = chunks
+        .iter()
+        .map(|chunk| Frame::parse(chunk))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = frames
+        .first()
+        .ok_or_else(|| ZkProofError::invalid_input("No chunks to reassemble"))?;
+    let (id, total) = (first.id, first.total);
+
+    let mut parts = BTreeMap::new();
+    for frame in frames {
+        if frame.id != id || frame.total != total {
+            return Err(ZkProofError::invalid_input(
+                "Chunks belong to different presentations",
+            ));
+        }
+        parts.insert(frame.index, frame.data);
+    }
+
+    let missing: Vec<String> = (0..total)
+        .filter(|index| !parts.contains_key(index))
+        .map(|index| index.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(ZkProofError::invalid_input(format!(
+            "Missing {} of {} chunks: {}",
+            missing.len(),
+            total,
+            missing.join(", ")
+        )));
+    }
+
+    let payload_len = parts.values().map(|data| data.len() as u64).sum();
+    check_bundle_len(payload_len, &get_verifier_limits())?;
+    let payload: Vec<u8> = parts.into_values().flatten().collect();
+    if bundle_id(&payload) != id {
+        return Err(ZkProofError::invalid_input(
+            "Reassembled bundle does not match its id",
+        ));
+    }
+    decode_presentation(payload)
+}
+
+/// Serialize `bundle` as one buffer, e.g. for an HTTP body or a JS `ArrayBuffer`
+/// This is the payload `chunk_presentation` splits into frames.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn encode_presentation(bundle: PresentationBundle) -> Result<Vec<u8>, ZkProofError> {
+    bincode::serialize(&bundle).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to serialize bundle: {}", e)).with_source(&*e)
+    })
+}
+
+/// Decode a buffer made by `encode_presentation`
+/// Buffers larger than the verifier limits' `max_bundle_bytes` are rejected
+/// before decoding.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn decode_presentation(bytes: Vec<u8>) -> Result<PresentationBundle, ZkProofError> {
+    check_bundle_len(bytes.len() as u64, &get_verifier_limits())?;
+    deserialize_bounded(&bytes).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to decode bundle: {}", e)).with_source(&*e)
+    })
+}
+
+struct Frame {
+    id: [u8; ID_LEN],
+    index: u16,
+    total: u16,
+    data: Vec<u8>,
+}
+
+impl Frame {
+    fn parse(chunk: &[u8]) -> Result<Self, ZkProofError> {
+        let header_len = CHUNK_HEADER_BYTES as usize;
+        if chunk.len() <= header_len {
+            return Err(ZkProofError::invalid_input("Chunk shorter than its header"));
+        }
+        if chunk[0] != FRAME_VERSION {
+            return Err(ZkProofError::invalid_input(format!(
+                "Unsupported chunk version {}",
+                chunk[0]
+            )));
+        }
+        let (header, data) = chunk.split_at(header_len);
+        let (fields, expected) = header.split_at(header_len - CHECKSUM_LEN);
+        if checksum(fields, data) != expected {
+            return Err(ZkProofError::invalid_input("Chunk checksum mismatch"));
+        }
+
+        let frame = Self {
+            id: fields[1..1 + ID_LEN].try_into().unwrap(),
+            index: u16::from_be_bytes([fields[1 + ID_LEN], fields[2 + ID_LEN]]),
+            total: u16::from_be_bytes([fields[3 + ID_LEN], fields[4 + ID_LEN]]),
+            data: data.to_vec(),
+        };
+        if frame.index >= frame.total {
+            return Err(ZkProofError::invalid_input(format!(
+                "Chunk index {} out of range for {} chunks",
+                frame.index, frame.total
+            )));
+        }
+        Ok(frame)
+    }
+}
+
+fn bundle_id(payload: &[u8]) -> [u8; ID_LEN] {
+    Sha256::digest(payload)[..ID_LEN].try_into().unwrap()
+}
+
+fn checksum(header: &[u8], data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    Sha256::new()
+        .chain_update(header)
+        .chain_update(data)
+        .finalize()[..CHECKSUM_LEN]
+        .try_into()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProofFreshness;
+
+    fn bundle() -> PresentationBundle {
+        PresentationBundle {
+            verifier_nonce: b"nonce".to_vec(),
+            prepare_proof: (0..=255).cycle().take(3000).collect(),
+            prepare_instance: vec![1; 200],
+            show_proof: vec![2; 1500],
+            show_instance: vec![3; 100],
+            additional_shows: Vec::new(),
+            comm_w_shared: "0xabc".to_string(),
+            reblinded: true,
+            prepare_freshness: ProofFreshness {
+                issued_at_unix: 1,
+                expires_at_unix: 2,
+            },
+        }
+    }
+
+    #[test]
+    fn test_round_trip_out_of_order_with_repeats() {
+        let chunks = chunk_presentation(bundle(), 256).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.len() <= 256));
+
+        let mut shuffled: Vec<Vec<u8>> = chunks.iter().rev().cloned().collect();
+        shuffled.push(chunks[0].clone());
+        let restored = reassemble_presentation(shuffled).unwrap();
+        assert_eq!(restored.prepare_proof, bundle().prepare_proof);
+        assert_eq!(restored.comm_w_shared, "0xabc");
+    }
+
+    #[test]
+    fn test_rejects_missing_and_corrupted_chunks() {
+        let mut chunks = chunk_presentation(bundle(), 256).unwrap();
+        let last = chunks.pop().unwrap();
+        let err = reassemble_presentation(chunks.clone()).unwrap_err();
+        assert!(err.message().contains("Missing 1"));
+
+        let mut corrupted = last;
+        *corrupted.last_mut().unwrap() ^= 1;
+        chunks.push(corrupted);
+        let err = reassemble_presentation(chunks).unwrap_err();
+        assert_eq!(err.message(), "Chunk checksum mismatch");
+    }
+}
@@ -0,0 +1,237 @@
+//! Runtime check that reblinding actually unlinks presentations.
+//!
+//! Reblinding is what keeps two presentations of one credential from being
+//! linked: every proof and every commitment except `comm_W_shared` must be
+//! fresh, while `comm_W_shared` must stay fixed so the Show proof still links
+//! to the stored Prepare proof. A build with a broken RNG or a reblind that
+//! silently reuses randomness still verifies, so `check_unlinkability` reblinds
+//! the stored proof repeatedly and compares the results directly.
+
+use std::{collections::HashSet, fs, time::Instant};
+
+use ecdsa_spartan2::{
+    artifact_path, load_instance, load_proving_key, load_shared_blinds,
+    setup::{
+        PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_PROVING_KEY, PREPARE_WITNESS, SHARED_BLINDS,
+        SHOW_INSTANCE, SHOW_PROOF, SHOW_PROVING_KEY, SHOW_WITNESS,
+    },
+    spartan2::traits::circuit::SpartanCircuit,
+    CircuitKind, PrepareCircuit, ShowCircuit, E,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{reblind_loaded, with_working_dir, ZkProofError};
+
+/// Fewest reblinds that make the comparison meaningful
+const MIN_REBLINDS: u32 = 2;
+
+/// Outcome of `check_unlinkability`
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct UnlinkabilityReport {
+    pub circuit: String,
+    pub reblinds: u32,
+    /// Whether every reblind kept the stored proof's comm_W_shared
+    pub comm_w_shared_constant: bool,
+    /// Distinct proofs among the stored proof and the reblinds
+    pub distinct_proofs: u32,
+    /// Distinct sets of non-shared commitments among the same
+    pub distinct_commitments: u32,
+    pub min_reblind_ms: u64,
+    pub mean_reblind_ms: u64,
+    pub max_reblind_ms: u64,
+    /// All of the above hold: shared commitment fixed, everything else fresh
+    pub passed: bool,
+    /// What did not hold
+    pub failures: Vec<String>,
+}
+
+/// Reblind the stored proof of `circuit_type` `reblinds` times and check each result
+/// Uses the stored shared blinds, so the stored proof is replaced by an
+/// equivalent one that still links to the other circuit's proof. Takes one
+/// reblind's time per iteration; a handful is enough.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn check_unlinkability(
+    documents_path: String,
+    circuit_type: String,
+    reblinds: u32,
+) -> Result<UnlinkabilityReport, ZkProofError> {
+    if reblinds < MIN_REBLINDS {
+        return Err(ZkProofError::invalid_input(format!(
+            "At least {} reblinds are needed, got {}",
+            MIN_REBLINDS, reblinds
+        )));
+    }
+    let circuit: CircuitKind = circuit_type.parse().map_err(ZkProofError::invalid_input)?;
+
+    with_working_dir(&documents_path, || match circuit {
+        CircuitKind::Prepare => run_check(
+            circuit,
+            PrepareCircuit::default,
+            PREPARE_PROVING_KEY,
+            [PREPARE_INSTANCE, PREPARE_WITNESS, PREPARE_PROOF],
+            reblinds,
+        ),
+        CircuitKind::Show => run_check(
+            circuit,
+            ShowCircuit::default,
+            SHOW_PROVING_KEY,
+            [SHOW_INSTANCE, SHOW_WITNESS, SHOW_PROOF],
+            reblinds,
+        ),
+    })
+}
+
+/// Digests of what one proof reveals
+struct Sample {
+    comm_w_shared: String,
+    commitments: [u8; 32],
+    proof: [u8; 32],
+}
+
+fn run_check<C: SpartanCircuit<E>>(
+    circuit: CircuitKind,
+    make_circuit: impl Fn() -> C,
+    pk_path: &str,
+    [instance_path, witness_path, proof_path]: [&str; 3],
+    reblinds: u32,
+) -> Result<UnlinkabilityReport, ZkProofError> {
+    let pk = load_proving_key::<E>(pk_path).map_err(|e| {
+        ZkProofError::setup_required(format!(
+            "Failed to load proving key from '{}': {}",
+            pk_path, e
+        ))
+        .with_source(&*e)
+    })?;
+    let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to load shared blinds: {}", e))
+            .with_source(&*e)
+    })?;
+
+    let mut samples = vec![sample(instance_path, proof_path)?];
+    let mut timings = Vec::with_capacity(reblinds as usize);
+    for _ in 0..reblinds {
+        let start = Instant::now();
+        reblind_loaded(
+            make_circuit(),
+            &pk,
+            &shared_blinds,
+            instance_path,
+            witness_path,
+            proof_path,
+        )?;
+        timings.push(start.elapsed().as_millis() as u64);
+        samples.push(sample(instance_path, proof_path)?);
+    }
+
+    let comm_w_shared_constant = samples
+        .iter()
+        .all(|s| s.comm_w_shared == samples[0].comm_w_shared);
+    let distinct_proofs = samples
+        .iter()
+        .map(|s| s.proof)
+        .collect::<HashSet<_>>()
+        .len() as u32;
+    let distinct_commitments = samples
+        .iter()
+        .map(|s| s.commitments)
+        .collect::<HashSet<_>>()
+        .len() as u32;
+
+    let total = samples.len() as u32;
+    let mut failures = Vec::new();
+    if !comm_w_shared_constant {
+        failures.push("comm_W_shared changed across reblinds".to_string());
+    }
+    if distinct_proofs != total {
+        failures.push(format!(
+            "{} of {} proofs repeat an earlier one",
+            total - distinct_proofs,
+            total
+        ));
+    }
+    if distinct_commitments != total {
+        failures.push(format!(
+            "{} of {} instances repeat an earlier set of non-shared commitments",
+            total - distinct_commitments,
+            total
+        ));
+    }
+
+    Ok(UnlinkabilityReport {
+        circuit: circuit.name().to_string(),
+        reblinds,
+        comm_w_shared_constant,
+        distinct_proofs,
+        distinct_commitments,
+        min_reblind_ms: timings.iter().copied().min().unwrap_or_default(),
+        mean_reblind_ms: timings.iter().sum::<u64>() / timings.len() as u64,
+        max_reblind_ms: timings.iter().copied().max().unwrap_or_default(),
+        passed: failures.is_empty(),
+        failures,
+    })
+}
+
+fn sample(instance_path: &str, proof_path: &str) -> Result<Sample, ZkProofError> {
+    let instance = load_instance::<E>(instance_path).map_err(|e| {
+        ZkProofError::file_not_found(format!(
+            "Failed to load instance from '{}': {}",
+            instance_path, e
+        ))
+        .with_source(&*e)
+    })?;
+    let proof_path = artifact_path(proof_path);
+    let proof = fs::read(&proof_path).map_err(|e| {
+        ZkProofError::file_not_found(format!("Failed to read proof from '{}': {}", proof_path, e))
+            .with_source(&e)
+    })?;
+
+    let fields = serde_json::to_value(&instance).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to serialize instance: {}", e)).with_source(&e)
+    })?;
+    Ok(Sample {
+        comm_w_shared: format!("{:?}", instance.comm_W_shared),
+        commitments: non_shared_commitments(&fields),
+        proof: Sha256::digest(&proof).into(),
+    })
+}
+
+/// Digest of every `comm_*` field of a serialized instance other than `comm_W_shared`
+fn non_shared_commitments(instance: &Value) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    if let Value::Object(fields) = instance {
+        for (name, value) in fields {
+            if name.starts_with("comm_") && name != "comm_W_shared" {
+                hasher.update(name.as_bytes());
+                hasher.update(value.to_string().as_bytes());
+            }
+        }
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_shared_commitments_ignore_shared_and_public_fields() {
+        let a = serde_json::json!({
+            "comm_W_shared": [1],
+            "comm_W_rest": [2],
+            "public_values": [3],
+        });
+        let b = serde_json::json!({
+            "comm_W_shared": [9],
+            "comm_W_rest": [2],
+            "public_values": [8],
+        });
+        let c = serde_json::json!({
+            "comm_W_shared": [1],
+            "comm_W_rest": [4],
+            "public_values": [3],
+        });
+        assert_eq!(non_shared_commitments(&a), non_shared_commitments(&b));
+        assert_ne!(non_shared_commitments(&a), non_shared_commitments(&c));
+    }
+}
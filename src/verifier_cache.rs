@@ -0,0 +1,320 @@
+//! Deduplicated presentation verification for verifier services.
+//!
+//! Wallets on flaky mobile networks retransmit the same presentation when an
+//! acknowledgement is lost, and each copy would otherwise cost a full
+//! multi-hundred-millisecond verification. `VerifierCache` keys outcomes by the
+//! SHA-256 of the serialized bundle, the trusted issuer keys, the documents
+//! directory holding the verifying keys and the configured domain, and
+//! replays an outcome for a repeated bundle until its time to live runs out.
+//! Only completed verifications are cached, rejections included; errors
+//! loading the keys are not. Call `clear` after replacing the verifying keys.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use ecdsa_spartan2::domain::domain_separator;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    credential::IssuerPublicKey,
+    presentation_flow::{PresentationBundle, PresentationVerification},
+    verify_presentation, ZkProofError,
+};
+
+type BundleDigest = [u8; 32];
+
+/// Counters of a `VerifierCache` since it was created or cleared
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct VerifierCacheStats {
+    /// Outcomes currently held, expired ones not yet evicted included
+    pub entries: u32,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+struct CachedOutcome {
+    stored_at: Instant,
+    outcome: PresentationVerification,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<BundleDigest, CachedOutcome>,
+    hits: u64,
+    misses: u64,
+}
+
+/// Recent verification outcomes keyed by bundle digest
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct VerifierCache {
+    ttl: Duration,
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+impl VerifierCache {
+    /// Cache of at most `capacity` outcomes, each replayed for `ttl_secs` seconds
+    /// When full, expired outcomes are evicted first, then the oldest.
+    #[cfg_attr(feature = "uniffi", uniffi::constructor)]
+    pub fn new(ttl_secs: u64, capacity: u32) -> Result<Arc<Self>, ZkProofError> {
+        if ttl_secs == 0 || capacity == 0 {
+            return Err(ZkProofError::invalid_input(
+                "VerifierCache needs a nonzero ttl_secs and capacity".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self {
+            ttl: Duration::from_secs(ttl_secs),
+            capacity: capacity as usize,
+            state: Mutex::new(CacheState::default()),
+        }))
+    }
+
+    /// `verify_presentation`, replaying the outcome of an identical recent bundle
+    /// verified for the same nonce and issuer keys
+    pub fn verify_presentation(
+        &self,
+        documents_path: String,
+        bundle: PresentationBundle,
+        verifier_nonce: Vec<u8>,
+        issuer_keys: Vec<IssuerPublicKey>,
+    ) -> Result<PresentationVerification, ZkProofError> {
+        let digest = bundle_digest(&documents_path, &bundle, &verifier_nonce, &issuer_keys)?;
+        if let Some(outcome) = self.lookup(&digest, Instant::now())? {
+            return Ok(outcome);
+        }
+
+        // Verify without holding the lock, so other bundles are not held up
+        let outcome = verify_presentation(documents_path, bundle, verifier_nonce, issuer_keys)?;
+        self.insert(digest, outcome.clone(), Instant::now())?;
+        Ok(outcome)
+    }
+
+    pub fn stats(&self) -> Result<VerifierCacheStats, ZkProofError> {
+        let state = self.state()?;
+        Ok(VerifierCacheStats {
+            entries: state.entries.len() as u32,
+            hits: state.hits,
+            misses: state.misses,
+        })
+    }
+
+    /// Drop every cached outcome and reset the counters
+    pub fn clear(&self) -> Result<(), ZkProofError> {
+        *self.state()? = CacheState::default();
+        Ok(())
+    }
+}
+
+impl VerifierCache {
+    fn state(&self) -> Result<MutexGuard<'_, CacheState>, ZkProofError> {
+        self.state.lock().map_err(|_| {
+            ZkProofError::io_error("Verifier cache lock poisoned by an earlier panic".to_string())
+        })
+    }
+
+    fn lookup(
+        &self,
+        digest: &BundleDigest,
+        now: Instant,
+    ) -> Result<Option<PresentationVerification>, ZkProofError> {
+        let mut state = self.state()?;
+        let fresh = match state.entries.get(digest) {
+            Some(cached) if now.duration_since(cached.stored_at) < self.ttl => {
+                Some(cached.outcome.clone())
+            }
+            Some(_) => {
+                state.entries.remove(digest);
+                None
+            }
+            None => None,
+        };
+        if fresh.is_some() {
+            state.hits += 1;
+        } else {
+            state.misses += 1;
+        }
+        Ok(fresh)
+    }
+
+    fn insert(
+        &self,
+        digest: BundleDigest,
+        outcome: PresentationVerification,
+        now: Instant,
+    ) -> Result<(), ZkProofError> {
+        let mut state = self.state()?;
+        if !state.entries.contains_key(&digest) && state.entries.len() >= self.capacity {
+            let ttl = self.ttl;
+            state
+                .entries
+                .retain(|_, cached| now.duration_since(cached.stored_at) < ttl);
+            if state.entries.len() >= self.capacity {
+                let oldest = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, cached)| cached.stored_at)
+                    .map(|(digest, _)| *digest);
+                if let Some(oldest) = oldest {
+                    state.entries.remove(&oldest);
+                }
+            }
+        }
+        state.entries.insert(
+            digest,
+            CachedOutcome {
+                stored_at: now,
+                outcome,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Digest identifying `bundle` as verified against `documents_path` and
+/// `issuer_keys` in the current domain
+fn bundle_digest(
+    documents_path: &str,
+    bundle: &PresentationBundle,
+    verifier_nonce: &[u8],
+    issuer_keys: &[IssuerPublicKey],
+) -> Result<BundleDigest, ZkProofError> {
+    let payload = bincode::serialize(bundle).map_err(|e| {
+        ZkProofError::invalid_input(format!("Failed to serialize presentation: {}", e))
+            .with_source(&*e)
+    })?;
+    let mut hasher = Sha256::new();
+    for field in [
+        documents_path.as_bytes(),
+        domain_separator().as_bytes(),
+        verifier_nonce,
+        &payload,
+    ] {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.update((issuer_keys.len() as u64).to_be_bytes());
+    for key in issuer_keys {
+        for field in [key.kid.as_bytes(), &key.public_key] {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+        hasher.update(key.not_before_unix.to_be_bytes());
+        hasher.update(key.not_after_unix.to_be_bytes());
+    }
+    Ok(hasher.finalize().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProofFreshness;
+
+    fn bundle(nonce: &[u8]) -> PresentationBundle {
+        PresentationBundle {
+            verifier_nonce: nonce.to_vec(),
+            prepare_proof: vec![1; 64],
+            prepare_instance: vec![2; 32],
+            show_proof: vec![3; 64],
+            show_instance: vec![4; 32],
+            additional_shows: Vec::new(),
+            comm_w_shared: "0xabc".to_string(),
+            reblinded: true,
+            prepare_freshness: ProofFreshness {
+                issued_at_unix: 1,
+                expires_at_unix: 2,
+            },
+        }
+    }
+
+    fn outcome(valid: bool) -> PresentationVerification {
+        PresentationVerification {
+            valid,
+            shows_verified: valid as u32,
+            proven_min_age: valid.then_some(18),
+            issuer_kid: valid.then(|| "key-1".to_string()),
+            failure_reason: (!valid).then(|| "rejected".to_string()),
+        }
+    }
+
+    fn issuer_keys(kid: &str) -> Vec<IssuerPublicKey> {
+        vec![IssuerPublicKey {
+            kid: kid.to_string(),
+            public_key: vec![4; 65],
+            not_before_unix: 0,
+            not_after_unix: 0,
+        }]
+    }
+
+    #[test]
+    fn test_digest_covers_bundle_and_keys_directory() {
+        let keys = issuer_keys("key-1");
+        let digest = bundle_digest("/keys", &bundle(b"a"), b"a", &keys).unwrap();
+        assert_eq!(
+            digest,
+            bundle_digest("/keys", &bundle(b"a"), b"a", &keys).unwrap()
+        );
+        assert_ne!(
+            digest,
+            bundle_digest("/keys", &bundle(b"b"), b"a", &keys).unwrap()
+        );
+        assert_ne!(
+            digest,
+            bundle_digest("/keys", &bundle(b"a"), b"b", &keys).unwrap()
+        );
+        assert_ne!(
+            digest,
+            bundle_digest("/other", &bundle(b"a"), b"a", &keys).unwrap()
+        );
+        assert_ne!(
+            digest,
+            bundle_digest("/keys", &bundle(b"a"), b"a", &issuer_keys("key-2")).unwrap()
+        );
+        assert_ne!(
+            digest,
+            bundle_digest("/keys", &bundle(b"a"), b"a", &[]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_outcomes_expire_and_oldest_is_evicted() {
+        let cache = VerifierCache::new(10, 2).unwrap();
+        let start = Instant::now();
+        let [a, b, c] = [[1u8; 32], [2u8; 32], [3u8; 32]];
+
+        cache.insert(a, outcome(true), start).unwrap();
+        let hit = cache.lookup(&a, start + Duration::from_secs(9)).unwrap();
+        assert!(hit.unwrap().valid);
+        assert!(cache
+            .lookup(&a, start + Duration::from_secs(10))
+            .unwrap()
+            .is_none());
+
+        cache.insert(a, outcome(true), start).unwrap();
+        cache
+            .insert(b, outcome(false), start + Duration::from_secs(1))
+            .unwrap();
+        cache
+            .insert(c, outcome(true), start + Duration::from_secs(2))
+            .unwrap();
+        let later = start + Duration::from_secs(3);
+        assert!(cache.lookup(&a, later).unwrap().is_none());
+        assert!(!cache.lookup(&b, later).unwrap().unwrap().valid);
+        assert!(cache.lookup(&c, later).unwrap().is_some());
+
+        assert_eq!(
+            cache.stats().unwrap(),
+            VerifierCacheStats {
+                entries: 2,
+                hits: 3,
+                misses: 2,
+            }
+        );
+        cache.clear().unwrap();
+        assert_eq!(cache.stats().unwrap().entries, 0);
+    }
+}
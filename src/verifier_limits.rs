@@ -0,0 +1,186 @@
+//! Size limits on presentations a verifier ingests.
+//!
+//! A verifier service decodes bundles from untrusted wallets. Before any
+//! allocation-heavy parsing, `verify_presentation` and `reassemble_presentation`
+//! check the bundle against these limits and reject it with `InvalidInput`:
+//! proof and instance byte lengths, the number of additional Show proofs (the
+//! only unbounded nesting in a bundle) and the size of a reassembled bundle.
+//! Proofs that pass are decoded with reads capped at their own length (see
+//! `ecdsa_spartan2::setup::deserialize_bounded`), so a forged length prefix
+//! cannot trigger a huge allocation either.
+
+use std::sync::RwLock;
+
+use crate::{
+    presentation_flow::{PresentationBundle, PRIMARY_SHOW_LABEL},
+    ZkProofError,
+};
+
+/// Limits applied to presentations before verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct VerifierLimits {
+    /// Longest serialized proof accepted, Prepare or Show
+    pub max_proof_bytes: u64,
+    /// Longest serialized instance accepted
+    pub max_instance_bytes: u64,
+    /// Most Show proofs presented beside the primary one
+    pub max_additional_shows: u32,
+    /// Largest serialized bundle `reassemble_presentation` rebuilds
+    pub max_bundle_bytes: u64,
+}
+
+impl Default for VerifierLimits {
+    /// Several times the largest proofs of the supported payload sizes
+    fn default() -> Self {
+        Self {
+            max_proof_bytes: 2 << 20,
+            max_instance_bytes: 256 << 10,
+            max_additional_shows: 16,
+            max_bundle_bytes: 32 << 20,
+        }
+    }
+}
+
+static LIMITS: RwLock<Option<VerifierLimits>> = RwLock::new(None);
+
+/// Replace the limits checked before verifying presentations
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_verifier_limits(limits: VerifierLimits) -> Result<(), ZkProofError> {
+    if limits.max_proof_bytes == 0 || limits.max_instance_bytes == 0 || limits.max_bundle_bytes == 0
+    {
+        return Err(ZkProofError::invalid_input(
+            "Verifier byte limits must be nonzero".to_string(),
+        ));
+    }
+    let mut slot = LIMITS.write().map_err(|_| {
+        ZkProofError::io_error("Verifier limits lock poisoned by an earlier panic".to_string())
+    })?;
+    *slot = Some(limits);
+    Ok(())
+}
+
+/// The limits in force, `VerifierLimits::default()` unless replaced
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn get_verifier_limits() -> VerifierLimits {
+    LIMITS
+        .read()
+        .ok()
+        .and_then(|slot| *slot)
+        .unwrap_or_default()
+}
+
+/// Reject `bundle` if any part exceeds `limits`
+pub(crate) fn check_bundle(
+    bundle: &PresentationBundle,
+    limits: &VerifierLimits,
+) -> Result<(), ZkProofError> {
+    if bundle.additional_shows.len() > limits.max_additional_shows as usize {
+        return Err(ZkProofError::invalid_input(format!(
+            "Presentation carries {} additional Show proofs, at most {} accepted",
+            bundle.additional_shows.len(),
+            limits.max_additional_shows
+        )));
+    }
+    let parts = [
+        ("prepare", &bundle.prepare_proof, &bundle.prepare_instance),
+        (
+            PRIMARY_SHOW_LABEL,
+            &bundle.show_proof,
+            &bundle.show_instance,
+        ),
+    ]
+    .into_iter()
+    .chain(
+        bundle
+            .additional_shows
+            .iter()
+            .map(|show| (show.label.as_str(), &show.show_proof, &show.show_instance)),
+    );
+    for (label, proof, instance) in parts {
+        check_len("proof", label, proof.len(), limits.max_proof_bytes)?;
+        check_len("instance", label, instance.len(), limits.max_instance_bytes)?;
+    }
+    Ok(())
+}
+
+/// Reject a bundle of `len` serialized bytes before it is rebuilt
+pub(crate) fn check_bundle_len(len: u64, limits: &VerifierLimits) -> Result<(), ZkProofError> {
+    if len > limits.max_bundle_bytes {
+        return Err(ZkProofError::invalid_input(format!(
+            "Presentation of {} bytes exceeds the {}-byte limit",
+            len, limits.max_bundle_bytes
+        )));
+    }
+    Ok(())
+}
+
+fn check_len(kind: &str, label: &str, len: usize, max: u64) -> Result<(), ZkProofError> {
+    if len as u64 > max {
+        return Err(ZkProofError::invalid_input(format!(
+            "'{}' {} of {} bytes exceeds the {}-byte limit",
+            label, kind, len, max
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{presentation_flow::ShowPresentation, ProofFreshness};
+
+    fn bundle() -> PresentationBundle {
+        PresentationBundle {
+            verifier_nonce: b"nonce".to_vec(),
+            prepare_proof: vec![1; 100],
+            prepare_instance: vec![2; 10],
+            show_proof: vec![3; 100],
+            show_instance: vec![4; 10],
+            additional_shows: Vec::new(),
+            comm_w_shared: "0xabc".to_string(),
+            reblinded: true,
+            prepare_freshness: ProofFreshness {
+                issued_at_unix: 1,
+                expires_at_unix: 2,
+            },
+        }
+    }
+
+    #[test]
+    fn test_rejects_oversized_parts() {
+        let limits = VerifierLimits {
+            max_proof_bytes: 100,
+            max_instance_bytes: 10,
+            max_additional_shows: 1,
+            max_bundle_bytes: 1000,
+        };
+        assert!(check_bundle(&bundle(), &limits).is_ok());
+
+        let mut long_show = bundle();
+        long_show.additional_shows.push(ShowPresentation {
+            label: "residency".to_string(),
+            show_proof: vec![5; 101],
+            show_instance: vec![6; 10],
+        });
+        let err = check_bundle(&long_show, &limits).unwrap_err();
+        assert!(matches!(err, ZkProofError::InvalidInput { .. }));
+        assert!(err.to_string().contains("residency"));
+
+        let mut many_shows = bundle();
+        many_shows.additional_shows = vec![
+            ShowPresentation {
+                label: "a".to_string(),
+                show_proof: Vec::new(),
+                show_instance: Vec::new(),
+            };
+            2
+        ];
+        assert!(check_bundle(&many_shows, &limits).is_err());
+
+        let mut long_instance = bundle();
+        long_instance.prepare_instance.push(0);
+        assert!(check_bundle(&long_instance, &limits).is_err());
+        assert!(check_bundle_len(1001, &limits).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! Object-oriented FFI surface: one `ZkWallet` per credential store.
+//!
+//! The free functions take a documents path on every call and leave global
+//! settings (domain, logging, sealing key) to separate calls. `ZkWallet` applies
+//! its configuration once at construction and wraps the same operations, so an
+//! app holds a single handle. Operations on one wallet are serialized, since
+//! they all run in its documents directory.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{
+    complete_presentation, enable_artifact_protection, import_credential, init_logging,
+    run_complete_benchmark, set_domain_separator, set_presentation_policy,
+    set_witness_cache_enabled, setup_prepare_keys, setup_show_keys, start_presentation,
+    verify_prepare, verify_show, BenchmarkResults, CredentialReceipt, DeviceSigner, KeyWrapper,
+    LogCallback, PresentationBundle, PresentationPolicy, ZkProofError,
+};
+
+/// Settings applied when a `ZkWallet` is created
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "uniffi", uniffi::record)]
+pub struct WalletConfig {
+    /// Directory holding keys, proofs and credential inputs
+    pub documents_path: String,
+    /// Deployment domain bound into proofs; `None` uses the default
+    pub domain: Option<String>,
+    /// Log level ("error" … "trace"); `None` leaves logging as it is
+    pub log_level: Option<String>,
+    /// Presentation policy to store; `None` keeps the stored one
+    pub presentation_policy: Option<PresentationPolicy>,
+    /// Reuse generated witnesses while inputs are unchanged
+    pub witness_cache: bool,
+}
+
+/// Wallet handle over one documents directory
+#[cfg_attr(feature = "uniffi", derive(uniffi::Object))]
+pub struct ZkWallet {
+    documents_path: String,
+    /// Serializes operations, which share the process working directory
+    lock: Mutex<()>,
+    /// Receipt of the credential imported through this handle
+    credential: Mutex<Option<CredentialReceipt>>,
+}
+
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+impl ZkWallet {
+    /// Apply `config` and open the wallet
+    /// With a `key_wrapper`, witnesses and shared blinds are sealed at rest under a
+    /// hardware-wrapped key (see `enable_artifact_protection`); otherwise they are
+    /// stored as plain files. `log_callback` receives log events when a level is set.
+    #[cfg_attr(feature = "uniffi", uniffi::constructor)]
+    pub fn new(
+        config: WalletConfig,
+        key_wrapper: Option<Arc<dyn KeyWrapper>>,
+        log_callback: Option<Arc<dyn LogCallback>>,
+    ) -> Result<Arc<Self>, ZkProofError> {
+        if let Some(level) = config.log_level {
+            init_logging(level, log_callback)?;
+        }
+        set_domain_separator(config.domain)?;
+        set_witness_cache_enabled(config.witness_cache);
+        if let Some(wrapper) = key_wrapper {
+            enable_artifact_protection(config.documents_path.clone(), wrapper)?;
+        }
+        if let Some(policy) = config.presentation_policy {
+            set_presentation_policy(config.documents_path.clone(), policy)?;
+        }
+
+        Ok(Arc::new(Self {
+            documents_path: config.documents_path,
+            lock: Mutex::new(()),
+            credential: Mutex::new(None),
+        }))
+    }
+
+    /// Generate Prepare and Show keys
+    pub fn setup(&self) -> Result<(), ZkProofError> {
+        let _guard = self.lock()?;
+        setup_prepare_keys(self.documents_path.clone(), None)?;
+        setup_show_keys(self.documents_path.clone(), None)?;
+        Ok(())
+    }
+
+    /// Import an SD-JWT credential and prove Prepare over it
+    pub fn import_credential(
+        &self,
+        jwt: String,
+        issuer_public_key: Vec<u8>,
+    ) -> Result<CredentialReceipt, ZkProofError> {
+        let _guard = self.lock()?;
+        let receipt = import_credential(self.documents_path.clone(), jwt, issuer_public_key)?;
+        if let Ok(mut slot) = self.credential.lock() {
+            *slot = Some(receipt.clone());
+        }
+        Ok(receipt)
+    }
+
+    /// Receipt of the credential imported through this handle, if any
+    pub fn credential(&self) -> Option<CredentialReceipt> {
+        self.credential.lock().ok().and_then(|slot| slot.clone())
+    }
+
+    /// Present the credential to a verifier
+    /// Has `signer` sign the verifier's nonce, then proves Show over the signature
+    pub fn present(
+        &self,
+        verifier_nonce: Vec<u8>,
+        signer: Arc<dyn DeviceSigner>,
+    ) -> Result<PresentationBundle, ZkProofError> {
+        let _guard = self.lock()?;
+        let challenge = start_presentation(self.documents_path.clone(), verifier_nonce)?;
+        let signature = signer.sign(challenge.challenge)?;
+        complete_presentation(self.documents_path.clone(), signature)
+    }
+
+    /// Verify the stored Prepare and Show proofs
+    /// Fails with `VerificationFailed` naming the first proof rejected
+    pub fn verify(&self) -> Result<(), ZkProofError> {
+        let _guard = self.lock()?;
+        for (name, result) in [
+            ("Prepare", verify_prepare(self.documents_path.clone())?),
+            ("Show", verify_show(self.documents_path.clone())?),
+        ] {
+            if let Some(reason) = result.failure_reason {
+                return Err(ZkProofError::verification_failed(format!(
+                    "{} proof rejected: {}",
+                    name, reason
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the complete benchmark pipeline over the default inputs
+    pub fn benchmark(&self) -> Result<BenchmarkResults, ZkProofError> {
+        let _guard = self.lock()?;
+        run_complete_benchmark(self.documents_path.clone(), None, false)
+    }
+}
+
+impl ZkWallet {
+    fn lock(&self) -> Result<MutexGuard<'_, ()>, ZkProofError> {
+        self.lock.lock().map_err(|_| {
+            ZkProofError::io_error("Wallet lock poisoned by an earlier panic".to_string())
+        })
+    }
+}
@@ -0,0 +1,91 @@
+//! Witness generation outside the app process.
+//!
+//! On devices with little RAM, rust-witness's peak memory on top of the
+//! prover's can get the app killed. A host can move witness generation out of
+//! the process either with `use_witness_helper_process`, which runs the
+//! `ecdsa-spartan2` CLI shipped with the app as a child process, or with
+//! `set_witness_source`, e.g. backed by an Android isolated service. Either
+//! way the witness comes back in the stream format described in
+//! `ecdsa_spartan2::witness_source` and applies to every Prepare and Show prove.
+//! A host source may also return a `.wtns` file, which is refused when it was
+//! computed over another curve's field; `check_witness_file` runs the same
+//! check on a file ahead of time.
+
+use std::{path::PathBuf, sync::Arc};
+
+use ecdsa_spartan2::{
+    binfile::FormatError,
+    witness_source::{self, decode_witness, HelperProcess},
+    wtns::read_wtns_file,
+    CircuitKind, Scalar,
+};
+
+use crate::ZkProofError;
+
+/// Generates witnesses on the host's behalf, e.g. in an isolated service
+#[cfg_attr(feature = "uniffi", uniffi::export(with_foreign))]
+pub trait WitnessSource: Send + Sync {
+    /// Witness for `circuit` ("prepare" or "show") on `input_json`, in the stream
+    /// format or as a `.wtns` file
+    fn generate_witness(
+        &self,
+        circuit: String,
+        input_json: Vec<u8>,
+    ) -> Result<Vec<u8>, ZkProofError>;
+}
+
+/// Adapts a host `WitnessSource` to the prover's
+struct ForeignSource(Arc<dyn WitnessSource>);
+
+impl witness_source::WitnessSource for ForeignSource {
+    fn generate(&self, circuit: CircuitKind, input_json: &[u8]) -> Result<Vec<Scalar>, String> {
+        let stream = self
+            .0
+            .generate_witness(circuit.name().to_string(), input_json.to_vec())
+            .map_err(|e| e.to_string())?;
+        decode_witness(&stream)
+    }
+}
+
+/// Generate witnesses with `source`; `None` generates them in-process again
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn set_witness_source(source: Option<Arc<dyn WitnessSource>>) {
+    witness_source::set_witness_source(
+        source.map(|source| {
+            Arc::new(ForeignSource(source)) as Arc<dyn witness_source::WitnessSource>
+        }),
+    );
+}
+
+/// Generate witnesses by running the `ecdsa-spartan2` CLI at `program` as a child process
+/// `args` precede the `<circuit> witness` arguments. The child's memory is
+/// returned to the system before proving starts.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn use_witness_helper_process(program: String, args: Vec<String>) -> Result<(), ZkProofError> {
+    let program = PathBuf::from(program);
+    if !program.is_file() {
+        return Err(ZkProofError::file_not_found(format!(
+            "Witness helper '{}' does not exist",
+            program.display()
+        )));
+    }
+    witness_source::set_witness_source(Some(Arc::new(HelperProcess { program, args })));
+    Ok(())
+}
+
+/// Check that the `.wtns` file at `path` was computed over the proving curve's field
+/// Returns the number of witness elements.
+#[cfg_attr(feature = "uniffi", uniffi::export)]
+pub fn check_witness_file(path: String) -> Result<u64, ZkProofError> {
+    let wtns = read_wtns_file(path.as_ref()).map_err(|e| {
+        let message = format!("Failed to read witness '{}': {}", path, e);
+        match e {
+            FormatError::Io(_) => ZkProofError::file_not_found(message),
+            _ => ZkProofError::invalid_input(message),
+        }
+    })?;
+    wtns.check_prime::<Scalar>().map_err(|e| {
+        ZkProofError::invalid_input(format!("witness field does not match proving curve: {}", e))
+    })?;
+    Ok(wtns.values.len() as u64)
+}
@@ -0,0 +1,259 @@
+//! The whole wallet flow through the public API: issue a credential, prove
+//! Prepare over it, reblind, present Show against a verifier nonce and verify.
+//!
+//! Sets up the JWT circuits, so it runs only with `test-issuer`, and is meant
+//! for desktops before device testing:
+//!
+//! ```text
+//! cargo test --release --features test-issuer,jwt-variants --test it_full_flow
+//! ```
+//!
+//! With `jwt-variants` the minted credential fits, and is proven in, the 1 KB
+//! Prepare circuit.
+#![cfg(feature = "test-issuer")]
+
+use std::fs;
+
+use ecdsa_spartan2::{
+    issuer::mint::{mint_sd_jwt, MintOptions, MintedCredential, TEST_ISSUER_KEY},
+    signature::apply_device_signature,
+    JwtVariant,
+};
+use spartan2_hyrax_mopro::{
+    complete_presentation, import_credential, jwt_variant_for, reblind_prepare, set_jwt_variant,
+    set_presentation_policy, set_show_predicate, setup_prepare_keys, setup_show_keys,
+    start_presentation, verify_prepare, verify_prepare_issuer, verify_presentation, verify_show,
+    IssuerPublicKey, PresentationBundle, PresentationPolicy, ReblindPolicy,
+};
+
+const SHOW_TEMPLATE: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/wallet-unit-poc/circom/inputs/show/default.json"
+);
+
+/// A holder born in ROC year 74 (1985), old enough for `age >= 18`
+fn mint() -> MintedCredential {
+    let options = MintOptions {
+        claims: vec![
+            ("name".to_string(), "John Doe".to_string()),
+            ("roc_birthday".to_string(), "0740605".to_string()),
+        ],
+        ..MintOptions::default()
+    };
+    mint_sd_jwt(&TEST_ISSUER_KEY, &options).unwrap()
+}
+
+/// Write the inputs setup synthesizes the circuits over
+fn write_setup_inputs(documents_path: &str, credential: &MintedCredential) {
+    let variant: JwtVariant = jwt_variant_for(credential.sd_jwt.clone())
+        .unwrap()
+        .parse()
+        .unwrap();
+    let prepare_input = credential.prepare_input(&variant.params()).unwrap();
+    fs::write(
+        format!("{}/jwt_input.json", documents_path),
+        prepare_input.to_string(),
+    )
+    .unwrap();
+
+    // The template's device key is not the credential's
+    let mut show_input: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(SHOW_TEMPLATE).unwrap()).unwrap();
+    let object = show_input.as_object_mut().unwrap();
+    object.remove("deviceKeyX");
+    object.remove("deviceKeyY");
+    let signature = credential.sign_challenge(b"setup").unwrap();
+    apply_device_signature(
+        &mut show_input,
+        b"setup",
+        &signature,
+        &credential.device_public_key,
+    )
+    .unwrap();
+    fs::write(
+        format!("{}/show_input.json", documents_path),
+        show_input.to_string(),
+    )
+    .unwrap();
+}
+
+/// Present the stored credential against `nonce`
+fn present(
+    documents_path: &str,
+    credential: &MintedCredential,
+    nonce: &[u8],
+) -> PresentationBundle {
+    let challenge = start_presentation(documents_path.to_string(), nonce.to_vec()).unwrap();
+    assert_eq!(challenge.challenge, nonce);
+    let signature = credential.sign_challenge(&challenge.challenge).unwrap();
+    complete_presentation(documents_path.to_string(), signature).unwrap()
+}
+
+/// `prepare_from`'s Prepare proof presented with `show_from`'s Show proof
+fn crossed(
+    prepare_from: &PresentationBundle,
+    show_from: &PresentationBundle,
+) -> PresentationBundle {
+    PresentationBundle {
+        verifier_nonce: show_from.verifier_nonce.clone(),
+        prepare_proof: prepare_from.prepare_proof.clone(),
+        prepare_instance: prepare_from.prepare_instance.clone(),
+        show_proof: show_from.show_proof.clone(),
+        show_instance: show_from.show_instance.clone(),
+        additional_shows: Vec::new(),
+        comm_w_shared: show_from.comm_w_shared.clone(),
+        reblinded: show_from.reblinded,
+        prepare_freshness: prepare_from.prepare_freshness.clone(),
+    }
+}
+
+#[test]
+fn test_issue_prepare_reblind_show_verify() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_string_lossy().into_owned();
+    let credential = mint();
+
+    // Setup
+    set_jwt_variant(Some(jwt_variant_for(credential.sd_jwt.clone()).unwrap())).unwrap();
+    write_setup_inputs(&path, &credential);
+    setup_prepare_keys(path.clone(), None).unwrap();
+    setup_show_keys(path.clone(), None).unwrap();
+
+    // Issue and prepare
+    let receipt = import_credential(
+        path.clone(),
+        credential.sd_jwt.clone(),
+        credential.issuer_public_key.clone(),
+    )
+    .unwrap();
+    let prepared = verify_prepare(path.clone()).unwrap();
+    assert!(prepared.valid, "{:?}", prepared.failure_reason);
+    assert_eq!(prepared.comm_w_shared, receipt.comm_w_shared);
+    let issuer_keys = vec![IssuerPublicKey {
+        kid: "key-1".to_string(),
+        public_key: credential.issuer_public_key.clone(),
+        not_before_unix: 0,
+        not_after_unix: 0,
+    }];
+    assert_eq!(
+        verify_prepare_issuer(path.clone(), issuer_keys.clone()).unwrap(),
+        "key-1"
+    );
+
+    // Reblinding under the same shared blinds keeps the link
+    let reblinded = reblind_prepare(path.clone()).unwrap();
+    assert_eq!(reblinded.comm_w_shared, receipt.comm_w_shared);
+
+    // Show against a verifier nonce
+    set_show_predicate(path.clone(), "age >= 18".to_string()).unwrap();
+    let nonce = b"verifier-nonce-1";
+    let bundle = present(&path, &credential, nonce);
+    assert_eq!(bundle.verifier_nonce, nonce);
+    assert_eq!(bundle.comm_w_shared, receipt.comm_w_shared);
+    assert!(verify_show(path.clone()).unwrap().valid);
+
+    // The nonce is single-use
+    let signature = credential.sign_challenge(nonce).unwrap();
+    assert!(complete_presentation(path.clone(), signature).is_err());
+    assert!(start_presentation(path.clone(), Vec::new()).is_err());
+
+    // The Show answers only the nonce the device signed
+    let other_nonce = b"verifier-nonce-x".to_vec();
+    let mut swapped = crossed(&bundle, &bundle);
+    swapped.verifier_nonce = other_nonce.clone();
+    let verification = verify_presentation(
+        path.clone(),
+        swapped,
+        other_nonce.clone(),
+        issuer_keys.clone(),
+    )
+    .unwrap();
+    assert!(!verification.valid);
+    assert!(verification
+        .failure_reason
+        .unwrap()
+        .contains("was not signed over the verifier nonce"));
+    let replayed = crossed(&bundle, &bundle);
+    let verification =
+        verify_presentation(path.clone(), replayed, other_nonce, issuer_keys.clone()).unwrap();
+    assert!(!verification.valid);
+    assert!(verification
+        .failure_reason
+        .unwrap()
+        .contains("Bundle answers a different verifier nonce"));
+
+    // Only a Prepare proof made against a trusted issuer key is accepted
+    let other_issuer = vec![IssuerPublicKey {
+        kid: "other".to_string(),
+        public_key: mint_sd_jwt(&[7; 32], &MintOptions::default())
+            .unwrap()
+            .issuer_public_key,
+        not_before_unix: 0,
+        not_after_unix: 0,
+    }];
+    let verification = verify_presentation(
+        path.clone(),
+        crossed(&bundle, &bundle),
+        nonce.to_vec(),
+        other_issuer,
+    )
+    .unwrap();
+    assert!(!verification.valid);
+    assert_eq!(verification.issuer_kid, None);
+    assert!(verification
+        .failure_reason
+        .unwrap()
+        .contains("unknown issuer key"));
+
+    // The freshness the bundle reports is the one the Prepare proof carries
+    let mut extended = crossed(&bundle, &bundle);
+    extended.prepare_freshness.expires_at_unix += 1;
+    let verification =
+        verify_presentation(path.clone(), extended, nonce.to_vec(), issuer_keys.clone()).unwrap();
+    assert!(!verification.valid);
+    assert!(verification
+        .failure_reason
+        .unwrap()
+        .contains("freshness differs"));
+
+    let verification =
+        verify_presentation(path.clone(), bundle, nonce.to_vec(), issuer_keys.clone()).unwrap();
+    assert!(verification.valid, "{:?}", verification.failure_reason);
+    assert_eq!(verification.shows_verified, 1);
+    assert_eq!(verification.proven_min_age, Some(18));
+    assert_eq!(verification.issuer_kid.as_deref(), Some("key-1"));
+
+    // Fresh blinds unlink the next presentation from the first
+    let first = present(&path, &credential, b"verifier-nonce-2");
+    set_presentation_policy(
+        path.clone(),
+        PresentationPolicy {
+            reblind: ReblindPolicy::Always,
+        },
+    )
+    .unwrap();
+    let second_nonce = b"verifier-nonce-3".to_vec();
+    let second = present(&path, &credential, &second_nonce);
+    assert!(second.reblinded);
+    assert_ne!(second.comm_w_shared, first.comm_w_shared);
+
+    // A Show from one presentation does not link to another's Prepare
+    let crossed = crossed(&first, &second);
+    assert!(
+        verify_presentation(
+            path.clone(),
+            second,
+            second_nonce.clone(),
+            issuer_keys.clone()
+        )
+        .unwrap()
+        .valid
+    );
+    let verification =
+        verify_presentation(path.clone(), crossed, second_nonce, issuer_keys).unwrap();
+    assert!(!verification.valid);
+    assert!(verification
+        .failure_reason
+        .unwrap()
+        .contains("does not share comm_W_shared"));
+}
@@ -0,0 +1,78 @@
+//! Parallel sessions over separate documents directories must not see each
+//! other's working directory or settings.
+
+use std::{fs, thread};
+
+use ecdsa_spartan2::domain::domain_separator;
+use spartan2_hyrax_mopro::{create_session, SessionConfig};
+
+const SESSIONS: usize = 8;
+const ROUNDS: usize = 50;
+
+#[test]
+fn test_parallel_sessions_stay_in_their_directories() {
+    let cwd = std::env::current_dir().unwrap();
+    let global_domain = domain_separator();
+    let dirs: Vec<_> = (0..SESSIONS)
+        .map(|_| tempfile::tempdir().unwrap())
+        .collect();
+
+    thread::scope(|scope| {
+        for (i, dir) in dirs.iter().enumerate() {
+            scope.spawn(move || {
+                let session = create_session(SessionConfig {
+                    documents_path: dir.path().to_string_lossy().into_owned(),
+                    domain: Some(format!("session-test-{}", i)),
+                    credential_id: Some(format!("credential-{}", i)),
+                })
+                .unwrap();
+                for round in 0..ROUNDS {
+                    let nonce = format!("session-{}-round-{}", i, round).into_bytes();
+                    let challenge = session.start_presentation(nonce.clone()).unwrap();
+                    assert_eq!(challenge.challenge, nonce);
+                }
+            });
+        }
+    });
+
+    for (i, dir) in dirs.iter().enumerate() {
+        let pending = fs::read(dir.path().join("keys/pending_presentation.json")).unwrap();
+        let pending: serde_json::Value = serde_json::from_slice(&pending).unwrap();
+        let nonce: Vec<u8> = serde_json::from_value(pending["verifier_nonce"].clone()).unwrap();
+        assert_eq!(
+            nonce,
+            format!("session-{}-round-{}", i, ROUNDS - 1).into_bytes()
+        );
+    }
+    assert_eq!(std::env::current_dir().unwrap(), cwd);
+    assert_eq!(domain_separator(), global_domain);
+}
+
+#[test]
+fn test_rejects_missing_directory_and_bad_settings() {
+    let config =
+        |documents_path: &str, domain: Option<&str>, credential_id: Option<&str>| SessionConfig {
+            documents_path: documents_path.to_string(),
+            domain: domain.map(str::to_string),
+            credential_id: credential_id.map(str::to_string),
+        };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_str().unwrap();
+
+    assert!(create_session(config("/nonexistent/session", None, None)).is_err());
+    assert!(create_session(config(path, Some(""), None)).is_err());
+    assert!(create_session(config(path, None, Some("../keys"))).is_err());
+    assert!(create_session(config(path, None, None)).is_ok());
+}
+
+#[cfg(feature = "stress-test")]
+#[test]
+fn test_stress_concurrent_sessions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().to_string_lossy().into_owned();
+
+    let report = spartan2_hyrax_mopro::stress_test_concurrent(path.clone(), 4, 3).unwrap();
+    assert_eq!(report.proofs_verified, 12);
+    assert!(!dir.path().join("stress_test").exists());
+    assert!(spartan2_hyrax_mopro::stress_test_concurrent(path, 0, 3).is_err());
+}
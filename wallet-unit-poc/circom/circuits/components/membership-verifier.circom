@@ -0,0 +1,81 @@
+pragma circom 2.1.6;
+
+include "circomlib/circuits/comparators.circom";
+include "circomlib/circuits/poseidon.circom";
+
+template MembershipVerifier(decodedLen, depth) {
+    signal input claim[decodedLen];  // ASCII codes of decoded base64: ["…","nationality","DE"]
+    signal input root;
+    signal input pathElements[depth];
+    signal input pathIndices[depth];
+
+    component isQuoteCmp[decodedLen];
+    signal    isQuote[decodedLen];
+    for (var i = 0; i < decodedLen; i++) {
+        isQuoteCmp[i] = IsEqual();
+        isQuoteCmp[i].in[0] <== claim[i];
+        isQuoteCmp[i].in[1] <== 34;
+        isQuote[i]    <== isQuoteCmp[i].out;
+    }
+
+    signal quoteCount[decodedLen];
+    quoteCount[0] <== isQuote[0];
+    for (var i = 1; i < decodedLen; i++) {
+        quoteCount[i] <== quoteCount[i-1] + isQuote[i];
+    }
+    quoteCount[decodedLen-1] === 6;
+
+    // The value is the third string: after the fifth quote, before the sixth
+    component isFifthCmp[decodedLen];
+    signal    isValue[decodedLen];
+    for (var i = 0; i < decodedLen; i++) {
+        isFifthCmp[i] = IsEqual();
+        isFifthCmp[i].in[0] <== quoteCount[i];
+        isFifthCmp[i].in[1] <== 5;
+        isValue[i] <== isFifthCmp[i].out * (1 - isQuote[i]);
+    }
+
+    // value bytes read as one big-endian number
+    signal step[decodedLen];
+    signal packed[decodedLen];
+    signal valueLen[decodedLen];
+    step[0]     <== isValue[0] * claim[0];
+    packed[0]   <== step[0];
+    valueLen[0] <== isValue[0];
+    for (var i = 1; i < decodedLen; i++) {
+        step[i]     <== isValue[i] * (packed[i-1] * 255 + claim[i]);
+        packed[i]   <== packed[i-1] + step[i];
+        valueLen[i] <== valueLen[i-1] + isValue[i];
+    }
+
+    // 1 to 31 bytes, so the packing is one-to-one
+    component lenOk = LessEqThan(8);
+    lenOk.in[0] <== valueLen[decodedLen-1];
+    lenOk.in[1] <== 31;
+    lenOk.out === 1;
+    component lenZero = IsZero();
+    lenZero.in <== valueLen[decodedLen-1];
+    lenZero.out === 0;
+
+    component leaf = Poseidon(1);
+    leaf.inputs[0] <== packed[decodedLen-1];
+
+    component hashers[depth];
+    signal node[depth + 1];
+    signal left[depth];
+    signal right[depth];
+    node[0] <== leaf.out;
+    for (var i = 0; i < depth; i++) {
+        pathIndices[i] * (1 - pathIndices[i]) === 0;
+
+        // pathIndices[i] = 1: the node is the right child
+        left[i]  <== node[i] + pathIndices[i] * (pathElements[i] - node[i]);
+        right[i] <== pathElements[i] + node[i] - left[i];
+
+        hashers[i] = Poseidon(2);
+        hashers[i].inputs[0] <== left[i];
+        hashers[i].inputs[1] <== right[i];
+        node[i + 1] <== hashers[i].out;
+    }
+    root === node[depth];
+}
@@ -0,0 +1,17 @@
+pragma circom 2.1.6;
+
+include "circomlib/circuits/poseidon.circom";
+
+// Verifier-scoped pseudonym of a device key
+template Pseudonym() {
+    signal input deviceKeyX;
+    signal input deviceKeyY;
+    signal input scope;
+    signal output pseudonym;
+
+    component hasher = Poseidon(3);
+    hasher.inputs[0] <== deviceKeyX;
+    hasher.inputs[1] <== deviceKeyY;
+    hasher.inputs[2] <== scope;
+    pseudonym <== hasher.out;
+}
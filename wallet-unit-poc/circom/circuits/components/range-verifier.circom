@@ -0,0 +1,88 @@
+pragma circom 2.1.6;
+
+include "circomlib/circuits/bitify.circom";
+include "circomlib/circuits/comparators.circom";
+
+template RangeVerifier(decodedLen, maxDigits) {
+    signal input claim[decodedLen];  // ASCII codes of decoded base64: ["…","income","52000"]
+    signal input rangeMin;
+    signal input rangeMax;
+
+    // 10^maxDigits < 2^(4 * maxDigits)
+    var nBits = 4 * maxDigits;
+
+    component isQuoteCmp[decodedLen];
+    signal    isQuote[decodedLen];
+    for (var i = 0; i < decodedLen; i++) {
+        isQuoteCmp[i] = IsEqual();
+        isQuoteCmp[i].in[0] <== claim[i];
+        isQuoteCmp[i].in[1] <== 34;
+        isQuote[i]    <== isQuoteCmp[i].out;
+    }
+
+    signal quoteCount[decodedLen];
+    quoteCount[0] <== isQuote[0];
+    for (var i = 1; i < decodedLen; i++) {
+        quoteCount[i] <== quoteCount[i-1] + isQuote[i];
+    }
+    quoteCount[decodedLen-1] === 6;
+
+    // The value is the third string: after the fifth quote, before the sixth
+    component isFifthCmp[decodedLen];
+    signal    isValue[decodedLen];
+    for (var i = 0; i < decodedLen; i++) {
+        isFifthCmp[i] = IsEqual();
+        isFifthCmp[i].in[0] <== quoteCount[i];
+        isFifthCmp[i].in[1] <== 5;
+        isValue[i] <== isFifthCmp[i].out * (1 - isQuote[i]);
+    }
+
+    // Every value character is a decimal digit
+    component geZero[decodedLen];
+    component leNine[decodedLen];
+    signal    isDigit[decodedLen];
+    for (var i = 0; i < decodedLen; i++) {
+        geZero[i] = GreaterEqThan(8);
+        geZero[i].in[0] <== claim[i];
+        geZero[i].in[1] <== 48;
+        leNine[i] = LessEqThan(8);
+        leNine[i].in[0] <== claim[i];
+        leNine[i].in[1] <== 57;
+        isDigit[i] <== geZero[i].out * leNine[i].out;
+        isValue[i] * (1 - isDigit[i]) === 0;
+    }
+
+    // value = digits read most significant first
+    signal step[decodedLen];
+    signal value[decodedLen];
+    signal valueLen[decodedLen];
+    step[0]     <== isValue[0] * (claim[0] - 48);
+    value[0]    <== step[0];
+    valueLen[0] <== isValue[0];
+    for (var i = 1; i < decodedLen; i++) {
+        step[i]     <== isValue[i] * (value[i-1] * 9 + claim[i] - 48);
+        value[i]    <== value[i-1] + step[i];
+        valueLen[i] <== valueLen[i-1] + isValue[i];
+    }
+
+    component lenOk = LessEqThan(8);
+    lenOk.in[0] <== valueLen[decodedLen-1];
+    lenOk.in[1] <== maxDigits;
+    lenOk.out === 1;
+
+    // The comparators below assume both bounds fit in nBits
+    component minBits = Num2Bits(nBits);
+    minBits.in <== rangeMin;
+    component maxBits = Num2Bits(nBits);
+    maxBits.in <== rangeMax;
+
+    component aboveMin = GreaterEqThan(nBits);
+    aboveMin.in[0] <== value[decodedLen-1];
+    aboveMin.in[1] <== rangeMin;
+    aboveMin.out === 1;
+
+    component belowMax = LessEqThan(nBits);
+    belowMax.in[0] <== value[decodedLen-1];
+    belowMax.in[1] <== rangeMax;
+    belowMax.out === 1;
+}
@@ -80,3 +80,79 @@ template JWT(
     signal output KeyBindingX <== ecExtractor.pubKeyX;
     signal output KeyBindingY <== ecExtractor.pubKeyY;
 }
+
+// JWT with the blocks before the first matched payload byte hashed outside the
+// circuit. Only the tail is decoded and matched; preHash is public so the
+// prover can tie it to the value it precommits.
+template JWTPartial(
+    maxTailLength,
+    maxB64PayloadLength,
+    maxMatches,
+    maxSubstringLength,
+    maxClaimsLength
+) {
+    var decodedLen = (maxClaimsLength * 3) / 4;
+    var maxPayloadLength = (maxB64PayloadLength * 3) / 4;
+
+    signal input tail[maxTailLength];
+    signal input tailLength;
+    signal input payloadOffset;
+    signal input preHash[32];
+
+    signal input sig_r;
+    signal input sig_s_inverse;
+    signal input pubKeyX;
+    signal input pubKeyY;
+
+    signal input matchesCount;
+    signal input matchSubstring[maxMatches][maxSubstringLength];
+    signal input matchLength[maxMatches];
+    signal input matchIndex[maxMatches];
+
+    signal input claims[maxMatches][maxClaimsLength];
+    signal input claimLengths[maxMatches];
+    signal input decodeFlags[maxMatches];
+    signal input ageClaimIndex;
+
+    signal decodedClaims[maxMatches][decodedLen] <== ClaimDecoder(maxMatches, maxClaimsLength)(claims, claimLengths, decodeFlags);
+    signal claimHashes[maxMatches][32] <== ClaimHasher(maxMatches, maxClaimsLength)(claims);
+
+    // Compare the claim hashes with the match substrings
+    ClaimComparator(maxMatches, maxSubstringLength)(claimHashes ,claimLengths, matchSubstring, matchLength);
+
+    // Verify the signature
+    ES256Partial(maxTailLength)(tail, tailLength, preHash, sig_r, sig_s_inverse, pubKeyX, pubKeyY);
+
+    // Extract the part of the payload in the tail
+    signal payload[maxPayloadLength] <== PayloadTailExtractor(maxTailLength, maxB64PayloadLength)(
+        tail,
+        tailLength,
+        payloadOffset
+    );
+
+    // Check if the match substrings are in the payload
+    signal payloadHash <== PayloadSubstringMatcher(maxPayloadLength, maxMatches, maxSubstringLength)(
+        payload,
+        matchesCount,
+        matchSubstring,
+        matchLength,
+        matchIndex
+    );
+
+    // Extract the device binding public key
+    component ecExtractor = ECPublicKeyExtractor_Optimized(maxPayloadLength, 32);
+    ecExtractor.payload <== payload;
+    ecExtractor.xStartIndex <== matchIndex[0] + matchLength[0];
+    ecExtractor.yStartIndex <== matchIndex[1] + matchLength[1];
+
+    component ageSelector = Multiplexer(decodedLen, maxMatches);
+    ageSelector.sel <== ageClaimIndex;
+    ageSelector.inp <== decodedClaims;
+
+    // Output the age claim
+    signal output ageClaim[decodedLen] <== ageSelector.out;
+
+    // Output the key binding public key
+    signal output KeyBindingX <== ecExtractor.pubKeyX;
+    signal output KeyBindingY <== ecExtractor.pubKeyY;
+}
@@ -98,3 +98,43 @@ template PayloadExtractor(
     payload <== Base64Decode(maxPayloadLength)(b64Payload);
 }
 
+
+// PayloadTailExtractor decodes the payload from the blocks of the message left
+// after a SHA-256 midstate was computed outside the circuit. A tail without a
+// period lies wholly inside the payload; payloadOffset skips the characters up
+// to the next Base64 quantum of the payload.
+template PayloadTailExtractor(
+    maxTailLength,
+    maxB64PayloadLength
+) {
+    signal input tail[maxTailLength]; // Message blocks after the precomputed prefix
+    signal input tailLength; // Padded length of the tail
+    signal input payloadOffset; // Characters before the first whole Base64 quantum
+
+    var maxPayloadLength = (maxB64PayloadLength * 3) \ 4;
+
+    signal output payload[maxPayloadLength];
+
+    // Assert tail length fits in ceil(log2(maxTailLength))
+    component n2bTailLength = Num2Bits(log2Ceil(maxTailLength));
+    n2bTailLength.in <== tailLength;
+
+    // Assert tail data after tailLength are zeros
+    AssertZeroPadding(maxTailLength)(tail, tailLength);
+
+    // Assert that the tail holds no period, so the header is entirely in the prefix
+    signal periodCount <== CountCharOccurrences(maxTailLength)(tail, 46);
+    periodCount === 0;
+
+    // Assert payloadOffset is 0..3
+    component n2bPayloadOffset = Num2Bits(2);
+    n2bPayloadOffset.in <== payloadOffset;
+
+    // Find the real tail length
+    signal realTailLength <== FindRealMessageLength(maxTailLength)(tail);
+
+    // Extract and decode the Base64 payload from the first whole quantum on
+    signal b64PayloadLength <== realTailLength - payloadOffset;
+    signal b64Payload[maxB64PayloadLength] <== SelectSubArrayBase64(maxTailLength, maxB64PayloadLength)(tail, payloadOffset, b64PayloadLength);
+    payload <== Base64Decode(maxPayloadLength)(b64Payload);
+}
@@ -0,0 +1,6 @@
+// auto-generated by circomkit
+pragma circom 2.2.3;
+
+include "../jwt.circom";
+
+component main {public[pubKeyX, pubKeyY]} = JWT(4096, 4000, 4, 50, 128);
@@ -0,0 +1,6 @@
+// auto-generated by circomkit
+pragma circom 2.2.3;
+
+include "../jwt.circom";
+
+component main {public[preHash, pubKeyX, pubKeyY]} = JWTPartial(1280, 1280, 4, 50, 128);
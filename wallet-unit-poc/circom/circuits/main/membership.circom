@@ -0,0 +1,6 @@
+// auto-generated by circomkit
+pragma circom 2.2.3;
+
+include "../membership.circom";
+
+component main {public[claim, root]} = Membership(128, 16);
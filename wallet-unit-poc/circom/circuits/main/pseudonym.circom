@@ -0,0 +1,6 @@
+// auto-generated by circomkit
+pragma circom 2.2.3;
+
+include "../pseudonym.circom";
+
+component main {public[deviceKeyX, deviceKeyY, scope, pseudonym]} = ScopedPseudonym();
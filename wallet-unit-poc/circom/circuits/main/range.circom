@@ -0,0 +1,6 @@
+// auto-generated by circomkit
+pragma circom 2.2.3;
+
+include "../range.circom";
+
+component main {public[claim, rangeMin, rangeMax]} = Range(128, 16);
@@ -0,0 +1,20 @@
+pragma circom 2.2.3;
+
+include "components/membership-verifier.circom";
+
+// The claim is the shared witness also committed by the Prepare proof; the
+// device key is shared too but not constrained here.
+template Membership(maxClaimsLength, depth) {
+    var decodedLen = (maxClaimsLength * 3) / 4;
+
+    signal input claim[decodedLen];
+    signal input root;
+    signal input pathElements[depth];
+    signal input pathIndices[depth];
+
+    component membershipVerifier = MembershipVerifier(decodedLen, depth);
+    membershipVerifier.claim <== claim;
+    membershipVerifier.root <== root;
+    membershipVerifier.pathElements <== pathElements;
+    membershipVerifier.pathIndices <== pathIndices;
+}
@@ -0,0 +1,17 @@
+pragma circom 2.2.3;
+
+include "components/pseudonym.circom";
+
+// The device key is the shared witness also committed by the Prepare proof.
+template ScopedPseudonym() {
+    signal input deviceKeyX;
+    signal input deviceKeyY;
+    signal input scope;
+    signal input pseudonym;
+
+    component derived = Pseudonym();
+    derived.deviceKeyX <== deviceKeyX;
+    derived.deviceKeyY <== deviceKeyY;
+    derived.scope <== scope;
+    pseudonym === derived.pseudonym;
+}
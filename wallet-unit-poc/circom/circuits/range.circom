@@ -0,0 +1,18 @@
+pragma circom 2.2.3;
+
+include "components/range-verifier.circom";
+
+// The claim is the shared witness also committed by the Prepare proof; the
+// device key is shared too but not constrained here.
+template Range(maxClaimsLength, maxDigits) {
+    var decodedLen = (maxClaimsLength * 3) / 4;
+
+    signal input claim[decodedLen];
+    signal input rangeMin;
+    signal input rangeMax;
+
+    component rangeVerifier = RangeVerifier(decodedLen, maxDigits);
+    rangeVerifier.claim <== claim;
+    rangeVerifier.rangeMin <== rangeMin;
+    rangeVerifier.rangeMax <== rangeMax;
+}
@@ -44,3 +44,42 @@ template ES256(
     ecdsa.pubKeyX <== pubKeyX;
     ecdsa.pubKeyY <== pubKeyY;
 }
+
+// ES256 over a message whose leading blocks were hashed outside the circuit
+// preHash is the SHA-256 state after those blocks, big-endian
+template ES256Partial(
+    maxTailLength
+) {
+    signal input tail[maxTailLength];
+    signal input tailLength;
+    signal input preHash[32];
+
+    signal input sig_r;
+    signal input sig_s_inverse;
+    signal input pubKeyX;
+    signal input pubKeyY;
+
+    signal sha[256];
+
+    // Assert tail length fits in ceil(log2(maxTailLength))
+    component n2bTailLength = Num2Bits(log2Ceil(maxTailLength));
+    n2bTailLength.in <== tailLength;
+
+    // Assert tail data after tailLength are zeros
+    AssertZeroPadding(maxTailLength)(tail, tailLength);
+
+    // Finish the SHA256 hash from the precomputed state
+    sha <== Sha256BytesPartial(maxTailLength)(tail, tailLength, preHash);
+
+    // Reduce message hash modulo scalar field order q
+    component message_hash_mod_q = HashModScalarField();
+    message_hash_mod_q.hash <== sha;
+
+    // Verify the signature
+    component ecdsa = ECDSA();
+    ecdsa.s_inverse <== sig_s_inverse;
+    ecdsa.r <== sig_r;
+    ecdsa.m <== message_hash_mod_q.out;
+    ecdsa.pubKeyX <== pubKeyX;
+    ecdsa.pubKeyY <== pubKeyY;
+}
@@ -1,10 +1,15 @@
 #!/bin/bash
 
 usage() {
-  echo "Usage: $0 {jwt|show|ecdsa|all}"
+  echo "Usage: $0 {jwt|jwt_variants|jwt_partial|show|ecdsa|range|membership|pseudonym|all}"
   echo "  jwt: Compile files for JWT."
+  echo "  jwt_variants: Compile the 1 KB and 4 KB JWT circuits."
+  echo "  jwt_partial: Compile the JWT circuit that takes a SHA-256 midstate."
   echo "  show: Compile files for Show."
   echo "  ecdsa: Compile files for ECDSA."
+  echo "  range: Compile files for Range."
+  echo "  membership: Compile files for Membership."
+  echo "  pseudonym: Compile files for Pseudonym."
   echo "  all: Compile all circuits."
   exit 1
 }
@@ -22,6 +27,20 @@ case "$1" in
     cd jwt_js || { echo "Error: 'jwt_js' directory not found inside 'build/jwt/'."; exit 1; }
     echo "JWT file processing complete."
     ;;
+  jwt_variants)
+    for variant in jwt_1k jwt_4k; do
+      npx circomkit compile $variant || { echo "Error: Failed to compile $variant."; exit 1; }
+      mv build/$variant/$variant.r1cs build/$variant/${variant}_js/ || { echo "Error: Failed to move $variant.r1cs."; exit 1; }
+    done
+    echo "JWT variant file processing complete."
+    ;;
+  jwt_partial)
+    npx circomkit compile jwt_partial || { echo "Error: Failed to compile JWT partial."; exit 1; }
+    cd build/jwt_partial/ || { echo "Error: 'build/jwt_partial/' directory not found."; exit 1; }
+    mv jwt_partial.r1cs jwt_partial_js/ || { echo "Error: Failed to move jwt_partial.r1cs."; exit 1; }
+    cd jwt_partial_js || { echo "Error: 'jwt_partial_js' directory not found inside 'build/jwt_partial/'."; exit 1; }
+    echo "JWT partial file processing complete."
+    ;;
   show)
     npx circomkit compile show || { echo "Error: Failed to compile Show."; exit 1; }
     cd build/show/ || { echo "Error: 'build/show/' directory not found."; exit 1; }
@@ -36,6 +55,27 @@ case "$1" in
     cd ecdsa_js || { echo "Error: 'ecdsa_js' directory not found inside 'build/ecdsa/'."; exit 1; }
     echo "ECDSA file processing complete."
     ;;
+  range)
+    npx circomkit compile range || { echo "Error: Failed to compile Range."; exit 1; }
+    cd build/range/ || { echo "Error: 'build/range/' directory not found."; exit 1; }
+    mv range.r1cs range_js/ || { echo "Error: Failed to move range.r1cs."; exit 1; }
+    cd range_js || { echo "Error: 'range_js' directory not found inside 'build/range/'."; exit 1; }
+    echo "Range file processing complete."
+    ;;
+  membership)
+    npx circomkit compile membership || { echo "Error: Failed to compile Membership."; exit 1; }
+    cd build/membership/ || { echo "Error: 'build/membership/' directory not found."; exit 1; }
+    mv membership.r1cs membership_js/ || { echo "Error: Failed to move membership.r1cs."; exit 1; }
+    cd membership_js || { echo "Error: 'membership_js' directory not found inside 'build/membership/'."; exit 1; }
+    echo "Membership file processing complete."
+    ;;
+  pseudonym)
+    npx circomkit compile pseudonym || { echo "Error: Failed to compile Pseudonym."; exit 1; }
+    cd build/pseudonym/ || { echo "Error: 'build/pseudonym/' directory not found."; exit 1; }
+    mv pseudonym.r1cs pseudonym_js/ || { echo "Error: Failed to move pseudonym.r1cs."; exit 1; }
+    cd pseudonym_js || { echo "Error: 'pseudonym_js' directory not found inside 'build/pseudonym/'."; exit 1; }
+    echo "Pseudonym file processing complete."
+    ;;
   all)
     echo "Compiling all circuits..."
     npx circomkit compile jwt || { echo "Error: Failed to compile JWT."; exit 1; }
@@ -44,6 +84,12 @@ case "$1" in
     cd build/show/ && mv show.r1cs show_js/ && cd ../.. || { echo "Error: Failed to process Show."; exit 1; }
     npx circomkit compile ecdsa || { echo "Error: Failed to compile ECDSA."; exit 1; }
     cd build/ecdsa/ && mv ecdsa.r1cs ecdsa_js/ && cd ../.. || { echo "Error: Failed to process ECDSA."; exit 1; }
+    npx circomkit compile range || { echo "Error: Failed to compile Range."; exit 1; }
+    cd build/range/ && mv range.r1cs range_js/ && cd ../.. || { echo "Error: Failed to process Range."; exit 1; }
+    npx circomkit compile membership || { echo "Error: Failed to compile Membership."; exit 1; }
+    cd build/membership/ && mv membership.r1cs membership_js/ && cd ../.. || { echo "Error: Failed to process Membership."; exit 1; }
+    npx circomkit compile pseudonym || { echo "Error: Failed to compile Pseudonym."; exit 1; }
+    cd build/pseudonym/ && mv pseudonym.r1cs pseudonym_js/ && cd ../.. || { echo "Error: Failed to process Pseudonym."; exit 1; }
     echo "All circuits compiled successfully."
     ;;
   *)
@@ -0,0 +1,229 @@
+//! Criterion benchmarks for the Prepare and Show proving pipeline.
+//!
+//! Run from the crate root after compiling the circom circuits:
+//!   cargo bench --bench prover
+//!   cargo bench --bench prover -- show/
+//!   cargo bench --bench prover -- load_
+//!
+//! Circuits and inputs are read from `../circom` next to the crate, whatever
+//! the working directory.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bellpepper_core::SynthesisError;
+use circom_scotia::reader::load_r1cs;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ecdsa_spartan2::{
+    layout::set_assets_dir,
+    load_instance, load_instance_and_witness, load_proving_key, load_verifying_key, load_witness,
+    parse_jwt_inputs, parse_show_inputs,
+    precommit::PrecommitCache,
+    r1cs_arena::load_arena_r1cs,
+    save_keys,
+    setup::{load_keys, save_instance, save_witness},
+    setup_circuit_keys_no_save, PrepareCircuit, Scalar, ShowCircuit, E,
+};
+use rust_witness::BigInt;
+use serde::Deserialize;
+use serde_json::Value;
+use spartan2::{
+    bellpepper::{solver::SatisfyingAssignment, zk_r1cs::SpartanWitness},
+    r1cs::R1CSWitness,
+    traits::{
+        circuit::SpartanCircuit, pcs::PCSEngineTrait, snark::R1CSSNARKTrait,
+        transcript::TranscriptEngineTrait, Engine,
+    },
+    zk_spartan::R1CSSNARK,
+};
+
+/// The `circom` directory, holding the compiled circuits and their inputs
+const CIRCOM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../circom");
+
+type ParseFn = fn(&Value) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError>;
+
+struct CircuitBench<C> {
+    name: &'static str,
+    circuit: C,
+    /// Paths inside `CIRCOM_DIR`
+    input_json: &'static str,
+    r1cs: &'static str,
+    parse: ParseFn,
+}
+
+/// The witness vector and its blinds
+/// spartan2 keeps the fields of `R1CSWitness` private, so they are read back
+/// from its serialization, which starts with them.
+#[derive(Deserialize)]
+struct WitnessParts {
+    w: Vec<Scalar>,
+    r_w: <<E as Engine>::PCS as PCSEngineTrait<E>>::Blind,
+}
+
+impl WitnessParts {
+    fn of(witness: &R1CSWitness<E>) -> Self {
+        bincode::deserialize(&bincode::serialize(witness).expect("serialize witness"))
+            .expect("witness parts")
+    }
+}
+
+fn new_transcript<C: SpartanCircuit<E>>(
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    circuit: &C,
+) -> <E as Engine>::TE {
+    let mut transcript = <E as Engine>::TE::new(b"R1CSSNARK");
+    transcript.absorb(b"vk", &pk.vk_digest);
+    let public_values = circuit.public_values().expect("public values");
+    transcript.absorb(b"public_values", &public_values.as_slice());
+    transcript
+}
+
+fn bench_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    c: &mut Criterion,
+    bench: CircuitBench<C>,
+) {
+    let mut group = c.benchmark_group(bench.name);
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(30));
+    let input_json = Path::new(CIRCOM_DIR).join(bench.input_json);
+    let r1cs = Path::new(CIRCOM_DIR).join(bench.r1cs);
+
+    let json: Value = serde_json::from_reader(File::open(input_json).expect("open input json"))
+        .expect("parse input json");
+    group.bench_function("witness_parsing", |b| {
+        b.iter(|| (bench.parse)(&json).expect("parse inputs"))
+    });
+
+    group.bench_function("r1cs_loading", |b| b.iter(|| load_r1cs::<Scalar>(&r1cs)));
+    group.bench_function("r1cs_loading_arena", |b| {
+        b.iter(|| load_arena_r1cs(&r1cs).expect("load arena r1cs"))
+    });
+
+    let (pk, vk) = setup_circuit_keys_no_save(bench.circuit.clone());
+
+    group.bench_function("prep_prove", |b| {
+        b.iter(|| {
+            R1CSSNARK::<E>::prep_prove(&pk, bench.circuit.clone(), false).expect("prep_prove")
+        })
+    });
+
+    // Repeat proofs of the same credential: shared and precommitted values are unchanged
+    let mut cache = PrecommitCache::<E>::new();
+    group.bench_function("prep_prove_cached", |b| {
+        b.iter(|| {
+            cache
+                .prep_prove(&pk, &bench.circuit, false)
+                .expect("prep_prove")
+        })
+    });
+
+    let mut prep =
+        R1CSSNARK::<E>::prep_prove(&pk, bench.circuit.clone(), false).expect("prep_prove");
+    let mut transcript = new_transcript(&pk, &bench.circuit);
+    let (instance, witness) = SatisfyingAssignment::r1cs_instance_and_witness(
+        &mut prep.ps,
+        &pk.S,
+        &pk.ck,
+        &bench.circuit,
+        false,
+        &mut transcript,
+    )
+    .expect("commit");
+
+    // Synthesis and witness generation are done above; only the commitment is timed
+    let parts = WitnessParts::of(&witness);
+    group.bench_function("hyrax_commit", |b| {
+        b.iter(|| <E as Engine>::PCS::commit(&pk.ck, &parts.w, &parts.r_w, false).expect("commit"))
+    });
+
+    // The transcript prefix matches the prover's, so timing reflects a real prove
+    group.bench_function("prove_inner", |b| {
+        b.iter_batched(
+            || new_transcript(&pk, &bench.circuit),
+            |mut transcript| {
+                R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript)
+                    .expect("prove_inner")
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let proof = R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript)
+        .expect("prove_inner");
+    group.bench_function("verify", |b| b.iter(|| proof.verify(&vk).expect("verify")));
+
+    // Loading one artifact after another against the concurrent loaders
+    let dir = std::env::temp_dir().join(format!("ecdsa-spartan2-bench-{}", bench.name));
+    fs::create_dir_all(&dir).expect("create artifact dir");
+    let path = |file: &str| dir.join(file).to_string_lossy().into_owned();
+    let (pk_path, vk_path) = (path("proving.key"), path("verifying.key"));
+    let (instance_path, witness_path) = (path("instance.bin"), path("witness.bin"));
+    save_keys(&pk_path, &vk_path, &pk, &vk).expect("save keys");
+    let public_values = bench.circuit.public_values().expect("public values");
+    save_instance(&instance_path, &instance, &public_values).expect("save instance");
+    save_witness(&witness_path, &witness).expect("save witness");
+
+    group.bench_function("load_keys_serial", |b| {
+        b.iter(|| {
+            (
+                load_proving_key::<E>(&pk_path).expect("load proving key"),
+                load_verifying_key::<E>(&vk_path).expect("load verifying key"),
+            )
+        })
+    });
+    group.bench_function("load_keys_parallel", |b| {
+        b.iter(|| load_keys::<E>(&pk_path, &vk_path).expect("load keys"))
+    });
+    group.bench_function("load_instance_witness_serial", |b| {
+        b.iter(|| {
+            (
+                load_instance::<E>(&instance_path).expect("load instance"),
+                load_witness::<E>(&witness_path).expect("load witness"),
+            )
+        })
+    });
+    group.bench_function("load_instance_witness_parallel", |b| {
+        b.iter(|| {
+            load_instance_and_witness::<E>(&instance_path, &witness_path)
+                .expect("load instance and witness")
+        })
+    });
+
+    group.finish();
+    let _ = fs::remove_dir_all(&dir);
+}
+
+fn prepare_benches(c: &mut Criterion) {
+    set_assets_dir(Some(PathBuf::from(CIRCOM_DIR)));
+    bench_circuit(
+        c,
+        CircuitBench {
+            name: "prepare",
+            circuit: PrepareCircuit::default(),
+            input_json: "inputs/jwt/default.json",
+            r1cs: "build/jwt/jwt_js/jwt.r1cs",
+            parse: parse_jwt_inputs,
+        },
+    );
+}
+
+fn show_benches(c: &mut Criterion) {
+    set_assets_dir(Some(PathBuf::from(CIRCOM_DIR)));
+    bench_circuit(
+        c,
+        CircuitBench {
+            name: "show",
+            circuit: ShowCircuit::default(),
+            input_json: "inputs/show/default.json",
+            r1cs: "build/show/show_js/show.r1cs",
+            parse: parse_show_inputs,
+        },
+    );
+}
+
+criterion_group!(benches, prepare_benches, show_benches);
+criterion_main!(benches);
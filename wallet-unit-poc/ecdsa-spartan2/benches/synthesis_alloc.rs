@@ -0,0 +1,81 @@
+//! Heap allocations of R1CS loading and synthesis, circom-scotia against `ArenaR1cs`.
+//!
+//! Counts rather than times, so it runs once without criterion. Run after
+//! compiling the circom circuits, which are read from `../circom` next to the
+//! crate:
+//!   cargo bench --bench synthesis_alloc
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use circom_scotia::{reader::load_r1cs, synthesize};
+use ecdsa_spartan2::{r1cs_arena::load_arena_r1cs, witness_stream::synthesize_streamed, Scalar, E};
+use spartan2::bellpepper::shape_cs::ShapeCS;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// The `circom` directory, holding the compiled circuits
+const CIRCOM_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../circom");
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Result of `f` and the allocations it made
+fn counted<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let value = f();
+    (value, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+/// Allocations for the circuit at `r1cs`, a path inside `CIRCOM_DIR`
+fn report(name: &str, r1cs: &str) {
+    let r1cs_path = Path::new(CIRCOM_DIR).join(r1cs);
+    let (scotia, scotia_load) = counted(|| load_r1cs::<Scalar>(&r1cs_path));
+    let (arena, arena_load) = counted(|| load_arena_r1cs(&r1cs_path).expect("load arena r1cs"));
+
+    // The shape pass setup runs, where every constraint is kept
+    let (_, scotia_synth) = counted(|| {
+        let mut cs = ShapeCS::<E>::new();
+        synthesize(&mut cs, scotia, None).expect("circom-scotia synthesis")
+    });
+    let (_, arena_synth) = counted(|| {
+        let mut cs = ShapeCS::<E>::new();
+        synthesize_streamed(&mut cs, &arena, None::<std::iter::Empty<_>>).expect("arena synthesis")
+    });
+
+    println!("{} ({} constraints)", name, arena.num_constraints());
+    println!(
+        "  r1cs loading:    circom-scotia {:>10}   arena {:>10}",
+        scotia_load, arena_load
+    );
+    println!(
+        "  shape synthesis: circom-scotia {:>10}   arena {:>10}",
+        scotia_synth, arena_synth
+    );
+}
+
+fn main() {
+    report("prepare", "build/jwt/jwt_js/jwt.r1cs");
+    report("show", "build/show/show_js/show.r1cs");
+}
@@ -1,7 +1,149 @@
+use std::{fs, path::Path};
+
 use rust_witness::transpile::transpile_wasm;
+use sha2::{Digest, Sha256};
+
+/// Constraint systems whose digests are reported by `build_info`
+const R1CS_FILES: &[(&str, &str)] = &[
+    ("PREPARE_R1CS_SHA256", "../circom/build/jwt/jwt_js/jwt.r1cs"),
+    (
+        "PREPARE_1K_R1CS_SHA256",
+        "../circom/build/jwt_1k/jwt_1k_js/jwt_1k.r1cs",
+    ),
+    (
+        "PREPARE_4K_R1CS_SHA256",
+        "../circom/build/jwt_4k/jwt_4k_js/jwt_4k.r1cs",
+    ),
+    ("SHOW_R1CS_SHA256", "../circom/build/show/show_js/show.r1cs"),
+];
+
+/// circomlib's Poseidon constants, as installed by `yarn` for compiling the circuits
+const POSEIDON_CONSTANTS: &str =
+    "../circom/node_modules/circomlib/circuits/poseidon_constants.circom";
+
+/// Constant tables of circomlib's optimized Poseidon, in the order they are written out
+const POSEIDON_TABLES: &[&str] = &["POSEIDON_C", "POSEIDON_S", "POSEIDON_M", "POSEIDON_P"];
+
+/// Repository of the spartan2 dependency in Cargo.toml; the root crate locks another fork too
+const SPARTAN2_REPO: &str = "therealyingtong/Spartan2";
 
 fn main() {
     // // Transpile WASM files from the circom build directory to C
     // // This will transpile both ECDSA and JWT circuits
     transpile_wasm("../circom/build/".to_string());
+
+    emit_r1cs_digests();
+    emit_spartan2_rev();
+    emit_poseidon_constants();
+}
+
+/// SHA-256 of each compiled circuit, "unknown" if it has not been built
+fn emit_r1cs_digests() {
+    for (var, path) in R1CS_FILES {
+        println!("cargo:rerun-if-changed={}", path);
+        let digest = fs::read(path).map_or_else(
+            |_| "unknown".to_string(),
+            |bytes| {
+                Sha256::digest(bytes)
+                    .iter()
+                    .map(|b| format!("{:02x}", b))
+                    .collect()
+            },
+        );
+        println!("cargo:rustc-env={}={}", var, digest);
+    }
+}
+
+/// Commit of the spartan2 dependency, read from the nearest Cargo.lock
+fn emit_spartan2_rev() {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let lock = Path::new(&manifest_dir)
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.exists());
+    let rev = lock
+        .as_ref()
+        .and_then(|path| {
+            println!("cargo:rerun-if-changed={}", path.display());
+            fs::read_to_string(path).ok()
+        })
+        .and_then(|lock| spartan2_rev(&lock))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=SPARTAN2_REV={}", rev);
+}
+
+fn spartan2_rev(lock: &str) -> Option<String> {
+    lock.split("[[package]]")
+        .filter(|package| package.contains("name = \"spartan2\""))
+        .filter_map(|package| {
+            package
+                .lines()
+                .find_map(|line| line.strip_prefix("source = \""))
+        })
+        .find(|source| source.contains(SPARTAN2_REPO))
+        .and_then(|source| source.rsplit_once('#'))
+        .map(|(_, rev)| rev.trim_end_matches('"').to_string())
+}
+
+/// circomlib's Poseidon constants as 32-byte big-endian values in `OUT_DIR`
+/// For each width `t` from 2 to 17: the C, S, M and P tables of that width.
+/// Left empty when circomlib is not installed; `hashing` then reports it.
+fn emit_poseidon_constants() {
+    println!("cargo:rerun-if-changed={}", POSEIDON_CONSTANTS);
+    let out = Path::new(&std::env::var("OUT_DIR").unwrap()).join("poseidon_constants.bin");
+    let bytes = fs::read_to_string(POSEIDON_CONSTANTS)
+        .map(|source| poseidon_constant_bytes(&source))
+        .unwrap_or_default();
+    fs::write(out, bytes).expect("write poseidon_constants.bin");
+}
+
+fn poseidon_constant_bytes(source: &str) -> Vec<u8> {
+    // Widths per table, each with its hex literals in declaration order
+    let tables: Vec<Vec<(usize, Vec<[u8; 32]>)>> = POSEIDON_TABLES
+        .iter()
+        .map(|name| {
+            let start = source
+                .find(&format!("function {}(", name))
+                .unwrap_or_else(|| panic!("{} missing from {}", name, POSEIDON_CONSTANTS));
+            let body = &source[start + 1..];
+            let body = &body[..body.find("function ").unwrap_or(body.len())];
+            body.split("t==")
+                .skip(1)
+                .map(|block| {
+                    let digits: String = block.chars().take_while(char::is_ascii_digit).collect();
+                    (digits.parse().unwrap(), hex_literals(block))
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut bytes = Vec::new();
+    for t in 2..=17 {
+        for table in &tables {
+            let (_, values) = table
+                .iter()
+                .find(|(width, _)| *width == t)
+                .unwrap_or_else(|| panic!("no Poseidon constants for t={}", t));
+            bytes.extend(values.iter().flatten());
+        }
+    }
+    bytes
+}
+
+fn hex_literals(text: &str) -> Vec<[u8; 32]> {
+    text.split("0x")
+        .skip(1)
+        .map(|literal| {
+            let hex: String = literal
+                .chars()
+                .take_while(char::is_ascii_hexdigit)
+                .collect();
+            let hex = format!("{:0>64}", hex);
+            let mut value = [0u8; 32];
+            for (i, byte) in value.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+            }
+            value
+        })
+        .collect()
 }
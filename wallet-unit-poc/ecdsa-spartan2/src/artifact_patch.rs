@@ -0,0 +1,354 @@
+//! Binary patches between artifact versions, for cheap key updates on devices.
+//!
+//! A circuit version bump changes only part of the serialized keys, so shipping
+//! the whole key package again costs hundreds of MB where a patch against the
+//! installed copy costs a few. `diff_artifact` produces
+//!
+//! ```text
+//! ZKAP || patch version (u32 LE) || bincode(ArtifactPatch)
+//! ```
+//!
+//! where the operations copy ranges of the old artifact or insert new bytes.
+//! Matches are found rsync-style: the old artifact is indexed in fixed blocks
+//! by a rolling checksum, which is slid over the new one byte by byte.
+//! `apply_artifact_patch` only returns a result whose SHA-256 matches the one
+//! recorded in the patch, and refuses a base whose digest differs from the
+//! artifact the patch was made against.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{setup::deserialize_bounded, vectors::sha256_hex};
+
+const MAGIC: &[u8; 4] = b"ZKAP";
+
+/// Layout version of the patch itself
+pub const ARTIFACT_PATCH_VERSION: u32 = 1;
+
+/// Length of the old-artifact blocks matches are searched for
+const BLOCK: usize = 64;
+
+/// How many times the old artifact a patch may copy out; a key update rewrites
+/// a key rather than multiplying it, and a forged `new_len` must not size the
+/// result buffer
+const MAX_COPY_FACTOR: u64 = 4;
+
+/// Old offsets kept per checksum; repetitive keys would otherwise index every block under a few checksums
+const MAX_CANDIDATES: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum PatchOp {
+    /// Copy `len` bytes of the old artifact starting at `offset`
+    Copy { offset: u64, len: u64 },
+    /// Bytes absent from the old artifact
+    Insert(Vec<u8>),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArtifactPatch {
+    /// SHA-256 of the artifact the patch applies to
+    pub old_sha256: String,
+    /// SHA-256 of the artifact the patch produces
+    pub new_sha256: String,
+    pub new_len: u64,
+    pub ops: Vec<PatchOp>,
+}
+
+/// rsync's weak checksum over a `BLOCK`-byte window, updatable one byte at a time
+struct Rolling {
+    a: u32,
+    b: u32,
+}
+
+impl Rolling {
+    fn new(window: &[u8]) -> Self {
+        let mut rolling = Self { a: 0, b: 0 };
+        for (k, &x) in window.iter().enumerate() {
+            rolling.a = rolling.a.wrapping_add(x as u32);
+            rolling.b = rolling
+                .b
+                .wrapping_add(((window.len() - k) as u32).wrapping_mul(x as u32));
+        }
+        rolling
+    }
+
+    /// Slide the window one byte: drop `out` at its start, append `incoming`
+    fn roll(&mut self, out: u8, incoming: u8) {
+        self.a = self
+            .a
+            .wrapping_sub(out as u32)
+            .wrapping_add(incoming as u32);
+        self.b = self
+            .b
+            .wrapping_sub((BLOCK as u32).wrapping_mul(out as u32))
+            .wrapping_add(self.a);
+    }
+
+    fn digest(&self) -> u32 {
+        (self.a & 0xffff) | (self.b << 16)
+    }
+}
+
+/// Patch turning `old` into `new`
+pub fn diff_artifact(old: &[u8], new: &[u8]) -> Result<Vec<u8>, bincode::Error> {
+    let mut index: HashMap<u32, Vec<usize>> = HashMap::new();
+    for offset in (0..old.len().saturating_sub(BLOCK - 1)).step_by(BLOCK) {
+        let candidates = index
+            .entry(Rolling::new(&old[offset..offset + BLOCK]).digest())
+            .or_default();
+        if candidates.len() < MAX_CANDIDATES {
+            candidates.push(offset);
+        }
+    }
+
+    let mut ops = Vec::new();
+    let mut literal = Vec::new();
+    let mut pos = 0;
+    let mut rolling = (new.len() >= BLOCK).then(|| Rolling::new(&new[..BLOCK]));
+    while pos + BLOCK <= new.len() {
+        let window = &new[pos..pos + BLOCK];
+        let digest = rolling.as_ref().map(Rolling::digest).unwrap_or_default();
+        let found = index.get(&digest).and_then(|candidates| {
+            candidates
+                .iter()
+                .copied()
+                .find(|&offset| &old[offset..offset + BLOCK] == window)
+        });
+
+        let Some(mut offset) = found else {
+            literal.push(new[pos]);
+            if pos + BLOCK < new.len() {
+                if let Some(rolling) = rolling.as_mut() {
+                    rolling.roll(new[pos], new[pos + BLOCK]);
+                }
+            }
+            pos += 1;
+            continue;
+        };
+
+        // Grow the match backwards over bytes already queued as literal, then forwards
+        let mut len = BLOCK;
+        while offset > 0 && literal.last() == Some(&old[offset - 1]) {
+            literal.pop();
+            offset -= 1;
+            len += 1;
+        }
+        let tail = old[offset + len..]
+            .iter()
+            .zip(&new[pos + BLOCK..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        len += tail;
+        pos += BLOCK + tail;
+
+        if !literal.is_empty() {
+            ops.push(PatchOp::Insert(std::mem::take(&mut literal)));
+        }
+        match ops.last_mut() {
+            Some(PatchOp::Copy {
+                offset: last_offset,
+                len: last_len,
+            }) if *last_offset + *last_len == offset as u64 => *last_len += len as u64,
+            _ => ops.push(PatchOp::Copy {
+                offset: offset as u64,
+                len: len as u64,
+            }),
+        }
+        rolling = (pos + BLOCK <= new.len()).then(|| Rolling::new(&new[pos..pos + BLOCK]));
+    }
+    literal.extend_from_slice(&new[pos..]);
+    if !literal.is_empty() {
+        ops.push(PatchOp::Insert(literal));
+    }
+
+    let patch = ArtifactPatch {
+        old_sha256: sha256_hex(old),
+        new_sha256: sha256_hex(new),
+        new_len: new.len() as u64,
+        ops,
+    };
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&ARTIFACT_PATCH_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, &patch)?;
+    Ok(bytes)
+}
+
+/// Decode a patch, checking its header and version
+pub fn parse_artifact_patch(bytes: &[u8]) -> Result<ArtifactPatch, String> {
+    let header_len = MAGIC.len() + 4;
+    if bytes.len() < header_len || !bytes.starts_with(MAGIC) {
+        return Err("Not an artifact patch".to_string());
+    }
+    let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len].try_into().unwrap());
+    if version != ARTIFACT_PATCH_VERSION {
+        return Err(format!(
+            "Artifact patch version {} is not supported (expected {})",
+            version, ARTIFACT_PATCH_VERSION
+        ));
+    }
+    deserialize_bounded(&bytes[header_len..])
+        .map_err(|e| format!("Malformed artifact patch: {}", e))
+}
+
+/// Apply a patch from `diff_artifact` to `old`, returning the new artifact
+/// Fails without producing anything if `old` is not the artifact the patch was
+/// made against or the result does not hash to the recorded digest.
+pub fn apply_artifact_patch(old: &[u8], patch: &[u8]) -> Result<Vec<u8>, String> {
+    let patch = parse_artifact_patch(patch)?;
+    let old_sha256 = sha256_hex(old);
+    if old_sha256 != patch.old_sha256 {
+        return Err(format!(
+            "Patch applies to artifact {}, the installed one is {}",
+            patch.old_sha256, old_sha256
+        ));
+    }
+
+    // Check every copy and the total length before allocating for the result
+    let inserted: u64 = patch
+        .ops
+        .iter()
+        .map(|op| match op {
+            PatchOp::Insert(bytes) => bytes.len() as u64,
+            PatchOp::Copy { .. } => 0,
+        })
+        .sum();
+    let max_len = (old.len() as u64)
+        .saturating_mul(MAX_COPY_FACTOR)
+        .saturating_add(inserted);
+    if patch.new_len > max_len {
+        return Err(format!(
+            "Patch produces {} bytes, more than the {}-byte limit for a {}-byte artifact",
+            patch.new_len,
+            max_len,
+            old.len()
+        ));
+    }
+    let mut total = 0u64;
+    for op in &patch.ops {
+        let len = match op {
+            PatchOp::Copy { offset, len } => {
+                if !matches!(offset.checked_add(*len), Some(end) if end <= old.len() as u64) {
+                    return Err(format!(
+                        "Patch copies {} bytes at {} past the end of the {}-byte artifact",
+                        len,
+                        offset,
+                        old.len()
+                    ));
+                }
+                *len
+            }
+            PatchOp::Insert(bytes) => bytes.len() as u64,
+        };
+        total = total.saturating_add(len);
+    }
+    if total != patch.new_len {
+        return Err(format!(
+            "Patch operations produce {} bytes, expected {}",
+            total, patch.new_len
+        ));
+    }
+
+    let mut new = Vec::with_capacity(total as usize);
+    for op in &patch.ops {
+        match op {
+            PatchOp::Copy { offset, len } => {
+                new.extend_from_slice(&old[*offset as usize..(*offset + *len) as usize])
+            }
+            PatchOp::Insert(bytes) => new.extend_from_slice(bytes),
+        }
+    }
+
+    if sha256_hex(&new) != patch.new_sha256 {
+        return Err("Patched artifact digest mismatch".to_string());
+    }
+    Ok(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(len: usize, seed: u32) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_patch_round_trips_shifted_edits() {
+        let old = artifact(64 * 1024, 1);
+        let mut new = old.clone();
+        // An insertion shifts everything after it off the block grid
+        new.splice(1000..1000, artifact(37, 2));
+        new[40_000] ^= 0xff;
+        new.truncate(60_000);
+        new.extend(artifact(500, 3));
+
+        let patch = diff_artifact(&old, &new).unwrap();
+        assert!(patch.len() < 2 * 1024, "patch is {} bytes", patch.len());
+        assert_eq!(apply_artifact_patch(&old, &patch).unwrap(), new);
+
+        let unrelated = artifact(100, 4);
+        let patch = diff_artifact(&old, &unrelated).unwrap();
+        assert_eq!(apply_artifact_patch(&old, &patch).unwrap(), unrelated);
+        let patch = diff_artifact(&[], &new).unwrap();
+        assert_eq!(apply_artifact_patch(&[], &patch).unwrap(), new);
+    }
+
+    #[test]
+    fn test_rejects_wrong_base_and_tampered_patches() {
+        let old = artifact(4096, 1);
+        let mut new = old.clone();
+        new[100] ^= 1;
+        let patch = diff_artifact(&old, &new).unwrap();
+
+        let mut other = old.clone();
+        other[0] ^= 1;
+        assert!(apply_artifact_patch(&other, &patch)
+            .unwrap_err()
+            .starts_with("Patch applies to artifact"));
+
+        let mut parsed = parse_artifact_patch(&patch).unwrap();
+        for op in &mut parsed.ops {
+            if let PatchOp::Insert(bytes) = op {
+                bytes[0] ^= 1;
+            }
+        }
+        let mut tampered = MAGIC.to_vec();
+        tampered.extend_from_slice(&ARTIFACT_PATCH_VERSION.to_le_bytes());
+        tampered.extend(bincode::serialize(&parsed).unwrap());
+        assert_eq!(
+            apply_artifact_patch(&old, &tampered).unwrap_err(),
+            "Patched artifact digest mismatch"
+        );
+        assert!(apply_artifact_patch(&old, b"ZKKP\x01\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_new_len() {
+        let old = artifact(4096, 1);
+        let patch = ArtifactPatch {
+            old_sha256: sha256_hex(&old),
+            new_sha256: String::new(),
+            new_len: u64::MAX,
+            ops: (0..=MAX_COPY_FACTOR)
+                .map(|_| PatchOp::Copy {
+                    offset: 0,
+                    len: old.len() as u64,
+                })
+                .collect(),
+        };
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&ARTIFACT_PATCH_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(&patch).unwrap());
+        assert!(apply_artifact_patch(&old, &bytes)
+            .unwrap_err()
+            .contains("-byte limit"));
+    }
+}
@@ -0,0 +1,107 @@
+//! Shipped artifacts read through the platform rather than the filesystem.
+//!
+//! On Android the compiled circuits and keys bundled with an app stay inside
+//! the APK, where only `AssetManager` can read them, so the assets root of an
+//! `ArtifactLayout` has no directory to point at, and copying them out at first
+//! launch keeps ~100 MB twice. An installed `AssetSource` reads them in chunks
+//! instead. Keys and circuits are still looked up on disk first, through
+//! `in_assets` and `circuits_dir`; only one found in neither the documents nor
+//! the assets directory is asked of the source, by its path relative to the
+//! assets root (`keys/prepare_proving.key`, `build/jwt/jwt_js/jwt.r1cs`).
+
+use std::{
+    io,
+    sync::{Arc, RwLock},
+};
+
+/// Bytes read per call into the source
+const CHUNK_LEN: usize = 1 << 20;
+
+/// Reads bundled assets by their path relative to the assets root
+pub trait AssetSource: Send + Sync {
+    /// Up to `max_len` bytes of the asset at `path` from `offset`, empty past
+    /// its end, or `None` if there is no such asset
+    fn read(&self, path: &str, offset: u64, max_len: usize) -> Result<Option<Vec<u8>>, String>;
+}
+
+static SOURCE: RwLock<Option<Arc<dyn AssetSource>>> = RwLock::new(None);
+
+/// Install `source` for bundled assets; `None` reads them from disk only
+pub fn set_asset_source(source: Option<Arc<dyn AssetSource>>) {
+    let mut slot = SOURCE.write().unwrap_or_else(|e| e.into_inner());
+    *slot = source;
+}
+
+pub fn asset_source() -> Option<Arc<dyn AssetSource>> {
+    SOURCE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// The whole asset at `path` from the installed source
+/// `NotFound` without a source or if it has no such asset.
+pub fn read_asset(path: &str) -> io::Result<Vec<u8>> {
+    match asset_source() {
+        Some(source) => read_all(&*source, path),
+        None => Err(no_asset(path)),
+    }
+}
+
+fn read_all(source: &dyn AssetSource, path: &str) -> io::Result<Vec<u8>> {
+    let read = |offset: usize| {
+        source
+            .read(path, offset as u64, CHUNK_LEN)
+            .map_err(|e| io::Error::other(format!("Failed to read asset '{}': {}", path, e)))
+    };
+
+    let mut bytes = read(0)?.ok_or_else(|| no_asset(path))?;
+    loop {
+        let chunk = read(bytes.len())?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("asset '{}' disappeared while read", path),
+            )
+        })?;
+        if chunk.is_empty() {
+            return Ok(bytes);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+}
+
+fn no_asset(path: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("no asset '{}'", path))
+}
+
+/// Whether the installed source has an asset at `path`
+pub fn has_asset(path: &str) -> bool {
+    asset_source().is_some_and(|source| matches!(source.read(path, 0, 0), Ok(Some(_))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One asset, handed out in chunks of at most three bytes
+    struct Chunked(Vec<u8>);
+
+    impl AssetSource for Chunked {
+        fn read(&self, path: &str, offset: u64, max_len: usize) -> Result<Option<Vec<u8>>, String> {
+            if path != "keys/a.key" {
+                return Ok(None);
+            }
+            let start = (offset as usize).min(self.0.len());
+            let end = (start + max_len.min(3)).min(self.0.len());
+            Ok(Some(self.0[start..end].to_vec()))
+        }
+    }
+
+    #[test]
+    fn test_asset_read_in_chunks() {
+        let source = Chunked((0..10).collect());
+        assert_eq!(
+            read_all(&source, "keys/a.key").unwrap(),
+            (0..10).collect::<Vec<u8>>()
+        );
+        let missing = read_all(&source, "keys/b.key").unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+    }
+}
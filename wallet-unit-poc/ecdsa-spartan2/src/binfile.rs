@@ -0,0 +1,168 @@
+//! Bounds-checked reader for the circom binary container format shared by
+//! `.wtns` and `.r1cs` files: a 4-byte magic, a u32 version and a u32 section
+//! count, followed by sections of `(u32 type, u64 size, payload)`.
+//!
+//! Every read is checked against the input length so malformed or hostile files
+//! surface as `FormatError` instead of panicking.
+
+use std::{fmt, io};
+
+use bellpepper_core::SynthesisError;
+
+#[derive(Debug)]
+pub enum FormatError {
+    Io(io::Error),
+    Malformed(String),
+    /// Well-formed, but uses a format version or feature this crate cannot prove with
+    Unsupported(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Io(e) => write!(f, "failed to read file: {}", e),
+            FormatError::Malformed(msg) => write!(f, "malformed file: {}", msg),
+            FormatError::Unsupported(msg) => write!(f, "unsupported file: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<io::Error> for FormatError {
+    fn from(e: io::Error) -> Self {
+        FormatError::Io(e)
+    }
+}
+
+impl From<FormatError> for SynthesisError {
+    fn from(e: FormatError) -> Self {
+        match e {
+            FormatError::Io(e) => SynthesisError::IoError(e),
+            FormatError::Malformed(msg) => {
+                SynthesisError::IoError(io::Error::new(io::ErrorKind::InvalidData, msg))
+            }
+            FormatError::Unsupported(msg) => {
+                SynthesisError::IoError(io::Error::new(io::ErrorKind::Unsupported, msg))
+            }
+        }
+    }
+}
+
+pub(crate) fn malformed(msg: impl Into<String>) -> FormatError {
+    FormatError::Malformed(msg.into())
+}
+
+pub(crate) fn unsupported(msg: impl Into<String>) -> FormatError {
+    FormatError::Unsupported(msg.into())
+}
+
+pub(crate) struct BinFile<'a> {
+    pub version: u32,
+    pub sections: Vec<(u32, &'a [u8])>,
+}
+
+impl<'a> BinFile<'a> {
+    pub(crate) fn parse(bytes: &'a [u8], magic: &[u8; 4]) -> Result<Self, FormatError> {
+        Self::parse_with(bytes, magic, false)
+    }
+
+    /// `parse`, also skipping zero bytes that pad a section out to 8-byte alignment
+    /// Section types are small and nonzero, so padding cannot be mistaken for one.
+    pub(crate) fn parse_padded(bytes: &'a [u8], magic: &[u8; 4]) -> Result<Self, FormatError> {
+        Self::parse_with(bytes, magic, true)
+    }
+
+    fn parse_with(bytes: &'a [u8], magic: &[u8; 4], padded: bool) -> Result<Self, FormatError> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(4)? != magic {
+            return Err(malformed(format!(
+                "missing '{}' magic",
+                String::from_utf8_lossy(magic)
+            )));
+        }
+        let version = reader.u32()?;
+        let num_sections = reader.u32()?;
+
+        // Not preallocated from `num_sections`: the count is untrusted
+        let mut sections = Vec::new();
+        for _ in 0..num_sections {
+            if padded {
+                reader.skip_padding(8);
+            }
+            let section_type = reader.u32()?;
+            let size = usize::try_from(reader.u64()?)
+                .map_err(|_| malformed("section size overflows usize"))?;
+            sections.push((section_type, reader.take(size)?));
+        }
+
+        Ok(Self { version, sections })
+    }
+
+    /// Payload of the first section of `section_type`
+    pub(crate) fn section(&self, section_type: u32) -> Option<&'a [u8]> {
+        self.sections
+            .iter()
+            .find(|(ty, _)| *ty == section_type)
+            .map(|(_, payload)| *payload)
+    }
+
+    pub(crate) fn require_section(
+        &self,
+        section_type: u32,
+        name: &str,
+    ) -> Result<&'a [u8], FormatError> {
+        self.section(section_type)
+            .ok_or_else(|| malformed(format!("missing {} section", name)))
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], FormatError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| {
+                malformed(format!(
+                    "unexpected end of data reading {} bytes at offset {}",
+                    len, self.pos
+                ))
+            })?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Skip zero bytes up to the next multiple of `align`, if they are all zero
+    pub(crate) fn skip_padding(&mut self, align: usize) {
+        let end = self.pos.next_multiple_of(align).min(self.bytes.len());
+        if self.bytes[self.pos..end].iter().all(|b| *b == 0) {
+            self.pos = end;
+        }
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, FormatError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, FormatError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
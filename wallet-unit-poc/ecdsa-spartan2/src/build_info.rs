@@ -0,0 +1,23 @@
+//! What this build was compiled from, recorded by `build.rs`.
+
+use crate::{
+    variant::{jwt_variant, JwtVariant},
+    witness::CircuitKind,
+};
+
+/// spartan2 commit from Cargo.lock, or "unknown"
+pub const SPARTAN2_REV: &str = env!("SPARTAN2_REV");
+
+/// Keys are stored as bare bincode; setup regenerates them rather than migrating
+pub const KEY_FORMAT_VERSION: u32 = crate::format::LEGACY_VERSION;
+
+/// Hex SHA-256 of the `.r1cs` a circuit was compiled from, or "unknown" if it
+/// was not built when this crate was; Prepare is the active `JwtVariant`
+pub fn r1cs_digest(circuit: CircuitKind) -> &'static str {
+    match (circuit, jwt_variant()) {
+        (CircuitKind::Prepare, JwtVariant::Jwt1k) => env!("PREPARE_1K_R1CS_SHA256"),
+        (CircuitKind::Prepare, JwtVariant::Jwt2k) => env!("PREPARE_R1CS_SHA256"),
+        (CircuitKind::Prepare, JwtVariant::Jwt4k) => env!("PREPARE_4K_R1CS_SHA256"),
+        (CircuitKind::Show, _) => env!("SHOW_R1CS_SHA256"),
+    }
+}
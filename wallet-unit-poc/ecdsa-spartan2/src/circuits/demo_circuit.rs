@@ -0,0 +1,113 @@
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use spartan2::traits::{circuit::SpartanCircuit, Engine};
+
+/// Rounds of the demo chain, one constraint each
+pub const DEMO_ROUNDS: usize = 4096;
+
+/// Secret proved over when the caller does not pick one
+pub const DEFAULT_DEMO_SECRET: u64 = 42;
+
+// Small built-in circuit for integration and on-device testing.
+// Proves knowledge of a shared secret `x` that maps to the public output through
+// `DEMO_ROUNDS` rounds of `acc = acc^2 + x` starting from `acc = x`. Needs no circom
+// artifacts, and shares `x` like the credential circuits share claims, so setup,
+// prove, reblind and verify all run as they do for Prepare and Show.
+#[derive(Debug, Clone)]
+pub struct DemoCircuit {
+    secret: u64,
+}
+
+impl DemoCircuit {
+    pub fn new(secret: u64) -> Self {
+        Self { secret }
+    }
+
+    /// Public output of the chain for `secret`
+    pub fn output<F: PrimeField>(secret: u64) -> F {
+        let x = F::from(secret);
+        (0..DEMO_ROUNDS).fold(x, |acc, _| acc.square() + x)
+    }
+}
+
+impl Default for DemoCircuit {
+    fn default() -> Self {
+        Self::new(DEFAULT_DEMO_SECRET)
+    }
+}
+
+impl<E: Engine> SpartanCircuit<E> for DemoCircuit {
+    fn synthesize<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<E::Scalar>],
+        _: &[AllocatedNum<E::Scalar>],
+        _: Option<&[E::Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let secret = shared.first().ok_or(SynthesisError::AssignmentMissing)?;
+        let mut acc = secret.clone();
+        for i in 0..DEMO_ROUNDS {
+            let next = AllocatedNum::alloc(cs.namespace(|| format!("acc{i}")), || {
+                let acc = acc.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+                let x = secret
+                    .get_value()
+                    .ok_or(SynthesisError::AssignmentMissing)?;
+                Ok(acc.square() + x)
+            })?;
+            // acc * acc = next - x
+            cs.enforce(
+                || format!("round{i}"),
+                |lc| lc + acc.get_variable(),
+                |lc| lc + acc.get_variable(),
+                |lc| lc + next.get_variable() - secret.get_variable(),
+            );
+            acc = next;
+        }
+        acc.inputize(cs.namespace(|| "Output"))
+    }
+
+    fn public_values(&self) -> Result<Vec<E::Scalar>, SynthesisError> {
+        Ok(vec![Self::output(self.secret)])
+    }
+    fn shared<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        let secret = AllocatedNum::alloc(cs.namespace(|| "Secret"), || {
+            Ok(E::Scalar::from(self.secret))
+        })?;
+        Ok(vec![secret])
+    }
+    fn precommitted<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<E::Scalar>],
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper_core::test_cs::TestConstraintSystem;
+
+    use super::*;
+    use crate::{Scalar, E};
+
+    #[test]
+    fn test_demo_circuit_is_satisfied() {
+        let circuit = DemoCircuit::new(7);
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let shared = SpartanCircuit::<E>::shared(&circuit, &mut cs).unwrap();
+        SpartanCircuit::<E>::synthesize(&circuit, &mut cs, &shared, &[], None).unwrap();
+
+        assert!(cs.is_satisfied());
+        // One constraint per round plus the one binding the output to the public input
+        assert_eq!(cs.num_constraints(), DEMO_ROUNDS + 1);
+        let public = SpartanCircuit::<E>::public_values(&circuit).unwrap();
+        assert!(cs.verify(&public));
+    }
+}
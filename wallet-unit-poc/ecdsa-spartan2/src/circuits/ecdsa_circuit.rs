@@ -0,0 +1,133 @@
+use crate::{
+    constraint_check::synthesize_checked,
+    layout::{circuits_dir, documents_dir},
+    signature::ecdsa_inputs,
+    utils::{convert_bigint_to_scalar, parse_ecdsa_inputs},
+    Scalar, E,
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use serde_json::{json, Value};
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{any::type_name, fs::File, path::PathBuf};
+use tracing::info;
+
+rust_witness::witness!(ecdsa);
+
+// ecdsa.circom
+#[derive(Debug, Clone, Default)]
+pub struct ECDSACircuit {
+    input_path: Option<PathBuf>,
+    /// Circom input built in memory, taking precedence over `input_path`
+    input: Option<Value>,
+}
+
+impl ECDSACircuit {
+    pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self {
+            input_path: path.into(),
+            input: None,
+        }
+    }
+
+    /// Build the circuit input from a caller-supplied P-256 signature
+    /// See `signature::ecdsa_inputs` for the accepted encodings
+    pub fn from_signature(
+        signature: &[u8],
+        msg_hash: &[u8],
+        pubkey: &[u8],
+    ) -> Result<Self, String> {
+        let inputs = ecdsa_inputs(signature, msg_hash, pubkey)?;
+
+        Ok(Self {
+            input_path: None,
+            input: Some(json!({
+                "s_inverse": inputs.s_inverse.to_string(),
+                "r": inputs.r.to_string(),
+                "m": inputs.m.to_string(),
+                "pubKeyX": inputs.pub_key_x.to_string(),
+                "pubKeyY": inputs.pub_key_y.to_string(),
+            })),
+        })
+    }
+
+    fn load_inputs(&self, documents: &PathBuf) -> Result<Value, SynthesisError> {
+        if let Some(input) = &self.input {
+            return Ok(input.clone());
+        }
+
+        let path = self
+            .input_path
+            .as_ref()
+            .map(|p| {
+                if p.is_absolute() {
+                    p.clone()
+                } else {
+                    documents.join(p)
+                }
+            })
+            .unwrap_or_else(|| {
+                // Try mobile flat path first, fall back to development nested path
+                let mobile_path = documents.join("ecdsa_input.json");
+                if mobile_path.exists() {
+                    mobile_path
+                } else {
+                    circuits_dir().join("inputs/ecdsa/default.json")
+                }
+            });
+        info!("Loading ecdsa inputs from {}", path.display());
+        let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
+        serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)
+    }
+}
+
+impl SpartanCircuit<E> for ECDSACircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        _: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let documents = documents_dir();
+        let root = circuits_dir();
+        let witness_dir = root.join("build/ecdsa/ecdsa_js");
+        let r1cs = witness_dir.join("ecdsa.r1cs");
+
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
+        // During setup, we only need constraint structure instead of actual witness values
+        let cs_type = type_name::<CS>();
+        let is_setup_phase = cs_type.contains("ShapeCS");
+
+        if is_setup_phase {
+            // Pass None for witness during setup
+            synthesize_checked(cs, &r1cs, None)?;
+            return Ok(());
+        }
+
+        let inputs = parse_ecdsa_inputs(&self.load_inputs(&documents)?)?;
+        let witness: Vec<Scalar> = convert_bigint_to_scalar(ecdsa_witness(inputs))?;
+
+        synthesize_checked(cs, &r1cs, Some(witness))?;
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
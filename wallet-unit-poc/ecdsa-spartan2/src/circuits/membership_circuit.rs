@@ -0,0 +1,212 @@
+use crate::{
+    constraint_check::synthesize_checked,
+    layout::{circuits_dir, documents_dir},
+    membership::{membership_input, membership_public_values, MerkleSet, MEMBERSHIP_PUBLIC_VALUES},
+    public_io::{alloc_public_values, enforce_equal, signal_slice},
+    utils::{
+        bigint_to_scalar, convert_bigint_to_scalar, parse_inputs, parse_membership_inputs,
+        FieldParser,
+    },
+    Scalar, E,
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use serde_json::Value;
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{any::type_name, fs::File, path::PathBuf};
+use tracing::info;
+
+rust_witness::witness!(membership);
+
+// membership.circom
+#[derive(Debug, Clone, Default)]
+pub struct MembershipCircuit {
+    input_path: Option<PathBuf>,
+    /// Circom input built in memory, taking precedence over `input_path`
+    input: Option<Value>,
+}
+
+impl MembershipCircuit {
+    pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self {
+            input_path: path.into(),
+            input: None,
+        }
+    }
+
+    /// Prove the claim in a Show input is a member of `set`
+    /// See `membership::membership_input` for what the Show input must hold
+    pub fn from_show_input(show_input: &Value, set: &MerkleSet) -> Result<Self, String> {
+        Ok(Self {
+            input_path: None,
+            input: Some(membership_input(show_input, set)?),
+        })
+    }
+
+    fn load_inputs(&self, documents: &PathBuf) -> Result<Value, SynthesisError> {
+        if let Some(input) = &self.input {
+            return Ok(input.clone());
+        }
+
+        // No development default: a root and path only exist for a published set
+        let path = self
+            .input_path
+            .as_ref()
+            .map(|p| {
+                if p.is_absolute() {
+                    p.clone()
+                } else {
+                    documents.join(p)
+                }
+            })
+            .unwrap_or_else(|| documents.join("membership_input.json"));
+        info!("Loading membership inputs from {}", path.display());
+        let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
+        serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)
+    }
+}
+
+/// Allocate the public values and constrain them and the shared claim to the circuit
+/// Signals: claim, root. Shared: KeyBindingX, KeyBindingY, claim.
+fn bind_signals<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    shared: &[AllocatedNum<Scalar>],
+    signals: &[AllocatedNum<Scalar>],
+    values: Option<&[Scalar]>,
+) -> Result<(), SynthesisError> {
+    let public = alloc_public_values(cs, values, MEMBERSHIP_PUBLIC_VALUES)?;
+    let claim_len = shared.len().saturating_sub(2);
+    enforce_equal(
+        cs,
+        "Claim",
+        signal_slice(shared, 2, claim_len)?,
+        signal_slice(signals, 0, claim_len)?,
+    )?;
+    enforce_equal(
+        cs,
+        "Root",
+        &public[1..],
+        signal_slice(signals, claim_len, 1)?,
+    )
+}
+
+impl SpartanCircuit<E> for MembershipCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let documents = documents_dir();
+        let root = circuits_dir();
+        let witness_dir = root.join("build/membership/membership_js");
+        let r1cs = witness_dir.join("membership.r1cs");
+
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
+        // During setup, we only need constraint structure instead of actual witness values
+        let cs_type = type_name::<CS>();
+        let is_setup_phase = cs_type.contains("ShapeCS");
+
+        // Pass None for witness during setup
+        let (witness, values) = if is_setup_phase {
+            (None, None)
+        } else {
+            let inputs = parse_membership_inputs(&self.load_inputs(&documents)?)?;
+            let witness = convert_bigint_to_scalar(membership_witness(inputs))?;
+            (Some(witness), Some(self.public_values()?))
+        };
+        let signals = synthesize_checked(cs, &r1cs, witness)?;
+        bind_signals(cs, shared, &signals, values.as_deref())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        let documents = documents_dir();
+        let root = parse_membership_inputs(&self.load_inputs(&documents)?)?
+            .remove("root")
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(membership_public_values(bigint_to_scalar(root[0].clone())?))
+    }
+    /// The same shared witnesses as Show: device key, then the decoded claim
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        let documents = documents_dir();
+        let json_value = self.load_inputs(&documents)?;
+
+        let mut inputs = parse_inputs(
+            &json_value,
+            &[
+                ("deviceKeyX", FieldParser::BigIntScalar),
+                ("deviceKeyY", FieldParser::BigIntScalar),
+                ("claim", FieldParser::BigIntArray),
+            ],
+        )?;
+        let mut take = |name: &str| inputs.remove(name).ok_or(SynthesisError::AssignmentMissing);
+        let keybinding_x = bigint_to_scalar(take("deviceKeyX")?[0].clone())?;
+        let keybinding_y = bigint_to_scalar(take("deviceKeyY")?[0].clone())?;
+        let claim_scalars = convert_bigint_to_scalar(take("claim")?)?;
+
+        let kb_x = AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
+        let kb_y = AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
+
+        let mut shared_values = Vec::with_capacity(2 + claim_scalars.len());
+        shared_values.push(kb_x);
+        shared_values.push(kb_y);
+
+        for (idx, claim_scalar) in claim_scalars.into_iter().enumerate() {
+            let claim_alloc =
+                AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), move || {
+                    Ok(claim_scalar)
+                })?;
+            shared_values.push(claim_alloc);
+        }
+
+        Ok(shared_values)
+    }
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+
+    /// Whether `root` and `claim` bind to a circuit that checked claim [2, 3] under root 7
+    fn binds(root: u64, claim: [u64; 2]) -> bool {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let mut alloc = |name: String, value: u64| {
+            AllocatedNum::alloc(cs.namespace(|| name), || Ok(Scalar::from(value))).unwrap()
+        };
+        let shared: Vec<_> = [0, 1, claim[0], claim[1]]
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| alloc(format!("shared{i}"), v))
+            .collect();
+        let signals: Vec<_> = [2, 3, 7]
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| alloc(format!("signal{i}"), v))
+            .collect();
+
+        let values = membership_public_values(Scalar::from(root));
+        bind_signals(&mut cs, &shared, &signals, Some(&values)).unwrap();
+        cs.is_satisfied()
+    }
+
+    #[test]
+    fn test_root_and_claim_must_match_the_circuit() {
+        assert!(binds(7, [2, 3]));
+        assert!(!binds(8, [2, 3]));
+        assert!(!binds(7, [2, 4]));
+    }
+}
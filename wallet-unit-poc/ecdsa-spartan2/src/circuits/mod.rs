@@ -1,2 +1,15 @@
+pub mod demo_circuit;
+#[cfg(feature = "ecdsa")]
+pub mod ecdsa_circuit;
+#[cfg(feature = "membership")]
+pub mod membership_circuit;
+pub mod permutation_circuit;
 pub mod prepare_circuit;
+#[cfg(feature = "sha-midstate")]
+pub mod prepare_partial_circuit;
+#[cfg(feature = "pseudonym")]
+pub mod pseudonym_circuit;
+#[cfg(feature = "range")]
+pub mod range_circuit;
 pub mod show_circuit;
+pub mod synthetic_circuit;
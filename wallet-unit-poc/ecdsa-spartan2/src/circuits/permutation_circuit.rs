@@ -0,0 +1,236 @@
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+use spartan2::traits::{circuit::SpartanCircuit, Engine};
+
+/// Length of the secret list, fixed so every list proves under the same keys
+pub const PERMUTATION_LEN: usize = 8;
+
+/// List proved over when the caller does not pick one
+pub const DEFAULT_PERMUTATION_VALUES: [u64; PERMUTATION_LEN] = [5, 3, 9, 1, 7, 2, 8, 4];
+
+// Small built-in circuit that consumes a verifier challenge.
+// Proves that a secret list contains the public output, by showing that the
+// precommitted list (the same values with the revealed one moved to the front) is
+// a permutation of the shared one. Both lists are committed before the challenge
+// `r` is drawn, so neither can be chosen to fit it, and prod(r - values[i]) =
+// prod(r - permuted[i]) then holds for a non-permutation with probability at most
+// PERMUTATION_LEN / |F|. This is the grand-product check circom lookup and
+// RAM-checking gadgets are built on.
+#[derive(Debug, Clone)]
+pub struct PermutationCircuit {
+    values: [u64; PERMUTATION_LEN],
+    reveal: usize,
+}
+
+impl PermutationCircuit {
+    /// Panics if `reveal` is not an index into `values`
+    pub fn new(values: [u64; PERMUTATION_LEN], reveal: usize) -> Self {
+        assert!(reveal < PERMUTATION_LEN, "reveal index out of range");
+        Self { values, reveal }
+    }
+
+    /// The public output
+    pub fn revealed(&self) -> u64 {
+        self.values[self.reveal]
+    }
+
+    fn permuted(&self) -> [u64; PERMUTATION_LEN] {
+        let mut permuted = self.values;
+        permuted.swap(0, self.reveal);
+        permuted
+    }
+}
+
+impl Default for PermutationCircuit {
+    fn default() -> Self {
+        Self::new(DEFAULT_PERMUTATION_VALUES, 0)
+    }
+}
+
+/// prod(challenge - values[i]), one constraint per value
+fn grand_product<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    name: &str,
+    challenge: &AllocatedNum<F>,
+    values: &[AllocatedNum<F>],
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let mut acc: Option<AllocatedNum<F>> = None;
+    for (i, value) in values.iter().enumerate() {
+        let next = AllocatedNum::alloc(cs.namespace(|| format!("{name}{i}")), || {
+            let r = challenge
+                .get_value()
+                .ok_or(SynthesisError::AssignmentMissing)?;
+            let v = value.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+            let prev = match &acc {
+                Some(acc) => acc.get_value().ok_or(SynthesisError::AssignmentMissing)?,
+                None => F::ONE,
+            };
+            Ok(prev * (r - v))
+        })?;
+        // prev * (r - value) = next
+        cs.enforce(
+            || format!("{name}{i} step"),
+            |lc| match &acc {
+                Some(acc) => lc + acc.get_variable(),
+                None => lc + CS::one(),
+            },
+            |lc| lc + challenge.get_variable() - value.get_variable(),
+            |lc| lc + next.get_variable(),
+        );
+        acc = Some(next);
+    }
+    acc.ok_or(SynthesisError::Unsatisfiable)
+}
+
+impl<E: Engine> SpartanCircuit<E> for PermutationCircuit {
+    fn synthesize<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<E::Scalar>],
+        precommitted: &[AllocatedNum<E::Scalar>],
+        challenges: Option<&[E::Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        if shared.len() != PERMUTATION_LEN || precommitted.len() != PERMUTATION_LEN {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        precommitted[0].inputize(cs.namespace(|| "Revealed"))?;
+
+        // spartan2 places the challenges in the public IO after `public_values`;
+        // setup passes `None`, where only the allocation matters
+        let challenge = AllocatedNum::alloc_input(cs.namespace(|| "Challenge"), || {
+            challenges
+                .and_then(|challenges| challenges.first().copied())
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let values = grand_product(cs, "Values", &challenge, shared)?;
+        let permuted = grand_product(cs, "Permuted", &challenge, precommitted)?;
+        cs.enforce(
+            || "products match",
+            |lc| lc + values.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + permuted.get_variable(),
+        );
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<E::Scalar>, SynthesisError> {
+        Ok(vec![E::Scalar::from(self.revealed())])
+    }
+    fn shared<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("Value{i}")), || {
+                    Ok(E::Scalar::from(value))
+                })
+            })
+            .collect()
+    }
+    /// The reordered list, committed before the challenge is drawn
+    fn precommitted<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+        _shared: &[AllocatedNum<E::Scalar>],
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        self.permuted()
+            .iter()
+            .enumerate()
+            .map(|(i, &value)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("Permuted{i}")), || {
+                    Ok(E::Scalar::from(value))
+                })
+            })
+            .collect()
+    }
+    fn num_challenges(&self) -> usize {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use bellpepper_core::test_cs::TestConstraintSystem;
+
+    use super::*;
+    use crate::{
+        prover::{prove_circuit_with_pk, reblind_with_loaded_data},
+        setup::{load_instance, load_proof, load_witness, setup_circuit_keys_no_save},
+        Scalar, E,
+    };
+
+    fn synthesize_with(permuted: [u64; PERMUTATION_LEN], challenge: u64) -> bool {
+        let circuit = PermutationCircuit::new(DEFAULT_PERMUTATION_VALUES, 3);
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let shared = SpartanCircuit::<E>::shared(&circuit, &mut cs).unwrap();
+        let precommitted: Vec<_> = permuted
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                AllocatedNum::alloc(cs.namespace(|| format!("p{i}")), || Ok(Scalar::from(v)))
+                    .unwrap()
+            })
+            .collect();
+        let challenge = Scalar::from(challenge);
+        SpartanCircuit::<E>::synthesize(
+            &circuit,
+            &mut cs,
+            &shared,
+            &precommitted,
+            Some(&[challenge]),
+        )
+        .unwrap();
+        cs.is_satisfied() && cs.verify(&[Scalar::from(permuted[0]), challenge])
+    }
+
+    #[test]
+    fn test_permutation_circuit_checks_the_multiset() {
+        let circuit = PermutationCircuit::new(DEFAULT_PERMUTATION_VALUES, 3);
+        assert!(synthesize_with(circuit.permuted(), 1_000_003));
+
+        // Revealing a value the list does not hold
+        let mut forged = circuit.permuted();
+        forged[0] = 6;
+        assert!(!synthesize_with(forged, 1_000_003));
+    }
+
+    #[test]
+    fn test_challenge_circuit_proves_and_refuses_reblind() {
+        let circuit = PermutationCircuit::new(DEFAULT_PERMUTATION_VALUES, 2);
+        let (pk, vk) = setup_circuit_keys_no_save(circuit.clone());
+
+        let dir = std::env::temp_dir().join(format!("permutation_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().into_owned();
+        let (instance, witness, proof) =
+            (path("instance.bin"), path("witness.bin"), path("proof.bin"));
+
+        prove_circuit_with_pk(circuit.clone(), &pk, &instance, &witness, &proof).unwrap();
+        let public_values = load_proof::<E>(&proof).unwrap().verify(&vk).unwrap();
+        assert_eq!(
+            public_values.first(),
+            Some(&Scalar::from(circuit.revealed()))
+        );
+
+        let reblind = catch_unwind(AssertUnwindSafe(|| {
+            reblind_with_loaded_data(
+                circuit,
+                &pk,
+                load_instance::<E>(&instance).unwrap(),
+                load_witness::<E>(&witness).unwrap(),
+                &[Scalar::from(1u64); PERMUTATION_LEN],
+                &instance,
+                &witness,
+                &proof,
+            )
+        }));
+        assert!(reblind.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
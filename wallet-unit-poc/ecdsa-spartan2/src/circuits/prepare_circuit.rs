@@ -1,17 +1,31 @@
 use crate::{
-    prover::generate_prepare_witness,
-    utils::{compute_prepare_shared_scalars, PrepareSharedScalars},
+    domain::domain_tag,
+    issuer::PreparePublicValues,
+    layout::{circuits_dir, documents_dir},
+    precommit::{alloc_static_inputs, enforce_precommitted},
+    prover::prepare_synthesis_witness,
+    public_io::{alloc_public_values, enforce_equal},
+    utils::{compute_prepare_shared_scalars, issuer_key_scalars, PrepareSharedScalars},
+    variant::jwt_variant,
+    witness::CircuitKind,
+    witness_stream::synthesize_witness,
     Scalar, E,
 };
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
-use circom_scotia::{reader::load_r1cs, synthesize};
 use serde_json::Value;
 use spartan2::traits::circuit::SpartanCircuit;
-use std::{any::type_name, env::current_dir, fs::File, path::PathBuf};
+use std::{any::type_name, fs::File, path::PathBuf};
+
+/// Issuer key coordinates, precommitted and public in the circom circuit
+pub(crate) const ISSUER_KEY_LEN: usize = 2;
 
 rust_witness::witness!(jwt);
+#[cfg(feature = "jwt-variants")]
+rust_witness::witness!(jwt_1k);
+#[cfg(feature = "jwt-variants")]
+rust_witness::witness!(jwt_4k);
 
-// jwt.circom
+// jwt.circom, compiled at the size of the active `JwtVariant`
 #[derive(Debug, Clone, Default)]
 pub struct PrepareCircuit {
     input_path: Option<PathBuf>,
@@ -24,24 +38,24 @@ impl PrepareCircuit {
         }
     }
 
-    fn input_path_absolute(&self, cwd: &PathBuf) -> Option<PathBuf> {
+    fn input_path_absolute(&self, documents: &PathBuf) -> Option<PathBuf> {
         self.input_path.as_ref().map(|p| {
             if p.is_absolute() {
                 p.clone()
             } else {
-                cwd.join(p)
+                documents.join(p)
             }
         })
     }
 
-    fn resolve_input_json(&self, cwd: &PathBuf) -> PathBuf {
-        self.input_path_absolute(cwd).unwrap_or_else(|| {
+    pub(crate) fn resolve_input_json(&self, documents: &PathBuf) -> PathBuf {
+        self.input_path_absolute(documents).unwrap_or_else(|| {
             // Try mobile flat path first, fall back to development nested path
-            let mobile_path = cwd.join("jwt_input.json");
+            let mobile_path = documents.join("jwt_input.json");
             if mobile_path.exists() {
                 mobile_path
             } else {
-                cwd.join("../circom/inputs/jwt/default.json")
+                circuits_dir().join("inputs/jwt/default.json")
             }
         })
     }
@@ -52,44 +66,73 @@ impl SpartanCircuit<E> for PrepareCircuit {
         &self,
         cs: &mut CS,
         _: &[AllocatedNum<Scalar>],
-        _: &[AllocatedNum<Scalar>],
+        precommitted: &[AllocatedNum<Scalar>],
         _: Option<&[Scalar]>,
     ) -> Result<(), SynthesisError> {
-        let cwd = current_dir().unwrap();
-        let root = cwd.join("../circom");
-        let witness_dir = root.join("build/jwt/jwt_js");
-        let r1cs = witness_dir.join("jwt.r1cs");
+        let documents = documents_dir();
+        let root = circuits_dir();
+        let r1cs = root.join(jwt_variant().r1cs_path());
 
         // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
         // During setup, we only need constraint structure instead of actual witness values
         let cs_type = type_name::<CS>();
         let is_setup_phase = cs_type.contains("ShapeCS");
 
-        if is_setup_phase {
-            let r1cs = load_r1cs(r1cs);
+        let witness = if is_setup_phase {
             // Pass None for witness during setup
-            synthesize(cs, r1cs, None)?;
-            return Ok(());
-        }
-
-        // Generate witness using the dedicated function
-        let input_path = self.input_path_absolute(&cwd);
-        let witness = generate_prepare_witness(input_path.as_ref().map(|p| p.as_path()))?;
-
-        let r1cs = load_r1cs(r1cs);
-        synthesize(cs, r1cs, Some(witness))?;
-        Ok(())
+            None
+        } else {
+            // Streamed from rust-witness when nothing needs the converted witness whole
+            let input_path = self.input_path_absolute(&documents);
+            Some(prepare_synthesis_witness(
+                input_path.as_ref().map(|p| p.as_path()),
+            )?)
+        };
+
+        // Public signals: the outputs, then the public inputs `pubKeyX`, `pubKeyY`
+        let signals = synthesize_witness(cs, &r1cs, witness)?;
+        let issuer_key = signals
+            .len()
+            .checked_sub(ISSUER_KEY_LEN)
+            .map(|start| &signals[start..])
+            .ok_or(SynthesisError::Unsatisfiable)?;
+        enforce_precommitted(cs, "IssuerKey", issuer_key, precommitted)?;
+
+        // The issuer key is the last of the public values; the validity window
+        // before it is not checked by the circuit (see `validity`)
+        let values = if is_setup_phase {
+            None
+        } else {
+            Some(self.public_values()?)
+        };
+        let count = 1 + PreparePublicValues::default().to_public_values().len();
+        let public = alloc_public_values(cs, values.as_deref(), count)?;
+        enforce_equal(
+            cs,
+            "IssuerKeyValue",
+            &public[count - ISSUER_KEY_LEN..],
+            issuer_key,
+        )
     }
 
     fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
-        Ok(vec![])
+        // Domain tag, issued-at, expiry, issuer key; zeros keep the shape fixed when the input is absent
+        let documents = documents_dir();
+        let public = File::open(self.resolve_input_json(&documents))
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, Value>(file).ok())
+            .map(|input| PreparePublicValues::from_input(&input))
+            .unwrap_or_default();
+        let mut values = vec![domain_tag(CircuitKind::Prepare)];
+        values.extend(public.to_public_values());
+        Ok(values)
     }
     fn shared<CS: ConstraintSystem<Scalar>>(
         &self,
         cs: &mut CS,
     ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
-        let cwd = current_dir().unwrap();
-        let json_path = self.resolve_input_json(&cwd);
+        let documents = documents_dir();
+        let json_path = self.resolve_input_json(&documents);
 
         let json_file = File::open(&json_path).map_err(|_| SynthesisError::AssignmentMissing)?;
 
@@ -103,30 +146,41 @@ impl SpartanCircuit<E> for PrepareCircuit {
         } = compute_prepare_shared_scalars(&json_value)?;
 
         let keybinding_x_alloc =
-            AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
+            AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(*keybinding_x))?;
         let keybinding_y_alloc =
-            AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
+            AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(*keybinding_y))?;
 
         let mut shared_values = Vec::with_capacity(2 + claim_scalars.len());
         shared_values.push(keybinding_x_alloc);
         shared_values.push(keybinding_y_alloc);
 
-        for (idx, claim_scalar) in claim_scalars.into_iter().enumerate() {
-            let claim_alloc =
-                AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), move || {
-                    Ok(claim_scalar)
-                })?;
+        for (idx, claim_scalar) in claim_scalars.iter().enumerate() {
+            let claim_alloc = AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), || {
+                Ok(**claim_scalar)
+            })?;
             shared_values.push(claim_alloc);
         }
 
         Ok(shared_values)
     }
+    /// The issuer key, which stays the same across a credential's proofs
     fn precommitted<CS: ConstraintSystem<Scalar>>(
         &self,
-        _cs: &mut CS,
+        cs: &mut CS,
         _shared: &[AllocatedNum<Scalar>],
     ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
-        Ok(vec![])
+        // Setup allocates without values, so a missing input is only an error when proving
+        let documents = documents_dir();
+        let issuer_key = File::open(self.resolve_input_json(&documents))
+            .ok()
+            .and_then(|file| serde_json::from_reader::<_, Value>(file).ok())
+            .and_then(|input| issuer_key_scalars(&input).ok());
+        alloc_static_inputs(
+            cs,
+            "IssuerKey",
+            issuer_key.as_ref().map(|key| &key[..]),
+            ISSUER_KEY_LEN,
+        )
     }
     fn num_challenges(&self) -> usize {
         0
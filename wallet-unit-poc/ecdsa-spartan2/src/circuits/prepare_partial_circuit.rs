@@ -0,0 +1,222 @@
+use crate::{
+    circuits::prepare_circuit::{PrepareCircuit, ISSUER_KEY_LEN},
+    constraint_check::synthesize_checked,
+    issuer::PreparePublicValues,
+    layout::{circuits_dir, documents_dir},
+    midstate::partial_prepare_input,
+    precommit::{alloc_static_inputs, enforce_precommitted},
+    public_io::{alloc_public_values, enforce_equal},
+    utils::{convert_bigint_to_scalar, issuer_key_scalars, parse_jwt_partial_inputs},
+    Scalar, E,
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use serde_json::Value;
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{any::type_name, fs::File, path::PathBuf};
+use tracing::info;
+
+rust_witness::witness!(jwt_partial);
+
+/// Bytes of the SHA-256 midstate `preHash`
+const PRE_HASH_LEN: usize = 32;
+
+// jwt.circom JWTPartial: Prepare with the blocks before the matched payload hashed natively
+/// Takes the same input file as `PrepareCircuit`, proves the same statement,
+/// and shares the same witnesses; the midstate is precommitted and tied to the
+/// circuit's public `preHash`, and the issuer key is precommitted and bound to
+/// the public values as in `PrepareCircuit`. See `midstate` for the soundness
+/// argument.
+#[derive(Debug, Clone, Default)]
+pub struct PreparePartialCircuit {
+    prepare: PrepareCircuit,
+}
+
+impl PreparePartialCircuit {
+    pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self {
+            prepare: PrepareCircuit::new(path),
+        }
+    }
+
+    fn load_partial_input(&self) -> Result<Value, SynthesisError> {
+        let documents = documents_dir();
+        let path = self.prepare.resolve_input_json(&documents);
+        info!("Loading prepare inputs from {}", path.display());
+        let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
+        let input: Value =
+            serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)?;
+        let (partial, split) =
+            partial_prepare_input(&input).map_err(|_| SynthesisError::AssignmentMissing)?;
+        info!("Hashing {} message bytes natively", split.prefix_len);
+        Ok(partial)
+    }
+}
+
+impl SpartanCircuit<E> for PreparePartialCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        _: &[AllocatedNum<Scalar>],
+        precommitted: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let root = circuits_dir();
+        let witness_dir = root.join("build/jwt_partial/jwt_partial_js");
+        let r1cs = witness_dir.join("jwt_partial.r1cs");
+
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
+        // During setup, we only need constraint structure instead of actual witness values
+        let cs_type = type_name::<CS>();
+        let is_setup_phase = cs_type.contains("ShapeCS");
+
+        let witness = if is_setup_phase {
+            None
+        } else {
+            let inputs = parse_jwt_partial_inputs(&self.load_partial_input()?)?;
+            Some(convert_bigint_to_scalar(jwt_partial_witness(inputs))?)
+        };
+
+        // Public signals: the outputs, then the public inputs `preHash`, `pubKeyX`, `pubKeyY`
+        let signals = synthesize_checked(cs, &r1cs, witness)?;
+        let values = if is_setup_phase {
+            None
+        } else {
+            Some(self.public_values()?)
+        };
+        bind_signals(cs, &signals, precommitted, values.as_deref())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        SpartanCircuit::<E>::public_values(&self.prepare)
+    }
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        SpartanCircuit::<E>::shared(&self.prepare, cs)
+    }
+    /// The midstate bytes, then the issuer key, committed before the rest of the witness
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        // Setup allocates without values, so a missing input is only an error when proving
+        let input = self.load_partial_input().ok();
+        let pre_hash: Option<Vec<Scalar>> = input
+            .as_ref()
+            .and_then(|input| serde_json::from_value::<Vec<u64>>(input["preHash"].clone()).ok())
+            .map(|bytes| bytes.into_iter().map(Scalar::from).collect());
+        let issuer_key = input
+            .as_ref()
+            .and_then(|input| issuer_key_scalars(input).ok());
+        let mut precommitted =
+            alloc_static_inputs(cs, "PreHash", pre_hash.as_deref(), PRE_HASH_LEN)?;
+        precommitted.extend(alloc_static_inputs(
+            cs,
+            "IssuerKey",
+            issuer_key.as_ref().map(|key| &key[..]),
+            ISSUER_KEY_LEN,
+        )?);
+        Ok(precommitted)
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+/// Tie the trailing public signals to the precommitted values and the public values
+/// The signals end with the public inputs `preHash`, `pubKeyX`, `pubKeyY`, and
+/// `precommitted` holds the midstate then the issuer key. The issuer key is the
+/// last of the public values, as in `PrepareCircuit`.
+fn bind_signals<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    signals: &[AllocatedNum<Scalar>],
+    precommitted: &[AllocatedNum<Scalar>],
+    values: Option<&[Scalar]>,
+) -> Result<(), SynthesisError> {
+    let start = signals
+        .len()
+        .checked_sub(PRE_HASH_LEN + ISSUER_KEY_LEN)
+        .ok_or(SynthesisError::Unsatisfiable)?;
+    let (pre_hash, issuer_key) = signals[start..].split_at(PRE_HASH_LEN);
+    if precommitted.len() != PRE_HASH_LEN + ISSUER_KEY_LEN {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let (committed_pre_hash, committed_key) = precommitted.split_at(PRE_HASH_LEN);
+    enforce_precommitted(cs, "PreHash", pre_hash, committed_pre_hash)?;
+    enforce_precommitted(cs, "IssuerKey", issuer_key, committed_key)?;
+
+    let count = 1 + PreparePublicValues::default().to_public_values().len();
+    let public = alloc_public_values(cs, values, count)?;
+    enforce_equal(
+        cs,
+        "IssuerKeyValue",
+        &public[count - ISSUER_KEY_LEN..],
+        issuer_key,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validity::ProofValidity;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use spartan2::{traits::snark::R1CSSNARKTrait, zk_spartan::R1CSSNARK};
+    use std::path::Path;
+
+    /// Whether public values naming `issuer_key` bind to a circuit that checked key [5, 6]
+    fn binds(issuer_key: [u64; 2]) -> bool {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let mut alloc = |name: String, value: u64| {
+            AllocatedNum::alloc(cs.namespace(|| name), || Ok(Scalar::from(value))).unwrap()
+        };
+        // One output, then `preHash`, `pubKeyX`, `pubKeyY`
+        let values: Vec<u64> = (0..1 + PRE_HASH_LEN as u64).chain([5, 6]).collect();
+        let signals: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| alloc(format!("signal{i}"), v))
+            .collect();
+        let precommitted: Vec<_> = values[1..]
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| alloc(format!("precommitted{i}"), v))
+            .collect();
+
+        let public = PreparePublicValues {
+            validity: ProofValidity::default(),
+            issuer_key: issuer_key.map(Scalar::from),
+        };
+        let mut public_values = vec![Scalar::from(0)];
+        public_values.extend(public.to_public_values());
+        bind_signals(&mut cs, &signals, &precommitted, Some(&public_values)).unwrap();
+        cs.is_satisfied()
+    }
+
+    #[test]
+    fn test_issuer_key_must_match_the_circuit() {
+        assert!(binds([5, 6]));
+        assert!(!binds([5, 7]));
+        assert!(!binds([0, 0]));
+    }
+
+    #[test]
+    #[ignore = "needs the compiled jwt_partial circuit and witness"]
+    fn test_partial_proof_verifies_with_its_issuer_key() {
+        let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("../circom/inputs/jwt/default.json");
+        let circuit = PreparePartialCircuit::new(input.clone());
+        let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).unwrap();
+        let mut prep = R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).unwrap();
+        let proof = R1CSSNARK::<E>::prove(&pk, circuit.clone(), &mut prep, false).unwrap();
+
+        let public_values = proof.verify(&vk).unwrap();
+        assert_eq!(
+            public_values,
+            SpartanCircuit::<E>::public_values(&circuit).unwrap()
+        );
+        let input: Value = serde_json::from_reader(File::open(input).unwrap()).unwrap();
+        let public = PreparePublicValues::from_public_values(&public_values[1..]).unwrap();
+        assert_eq!(public.issuer_key, issuer_key_scalars(&input).unwrap());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    constraint_check::synthesize_checked,
+    layout::{circuits_dir, documents_dir},
+    pseudonym::{pseudonym_input, PseudonymStatement, PSEUDONYM_PUBLIC_VALUES},
+    public_io::{alloc_public_values, enforce_equal, signal_slice},
+    utils::{
+        bigint_to_scalar, convert_bigint_to_scalar, parse_inputs, parse_pseudonym_inputs,
+        FieldParser,
+    },
+    Scalar, E,
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use serde_json::Value;
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{any::type_name, fs::File, path::PathBuf};
+use tracing::info;
+
+rust_witness::witness!(pseudonym);
+
+// pseudonym.circom
+#[derive(Debug, Clone, Default)]
+pub struct PseudonymCircuit {
+    input_path: Option<PathBuf>,
+    /// Circom input built in memory, taking precedence over `input_path`
+    input: Option<Value>,
+}
+
+impl PseudonymCircuit {
+    pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self {
+            input_path: path.into(),
+            input: None,
+        }
+    }
+
+    /// Derive the Show input's pseudonym for the service `verifier_id`
+    pub fn from_show_input(show_input: &Value, verifier_id: &str) -> Result<Self, String> {
+        Ok(Self {
+            input_path: None,
+            input: Some(pseudonym_input(show_input, verifier_id)?),
+        })
+    }
+
+    fn load_inputs(&self, documents: &PathBuf) -> Result<Value, SynthesisError> {
+        if let Some(input) = &self.input {
+            return Ok(input.clone());
+        }
+
+        // No development default: the scope only exists for a given service
+        let path = self
+            .input_path
+            .as_ref()
+            .map(|p| {
+                if p.is_absolute() {
+                    p.clone()
+                } else {
+                    documents.join(p)
+                }
+            })
+            .unwrap_or_else(|| documents.join("pseudonym_input.json"));
+        info!("Loading pseudonym inputs from {}", path.display());
+        let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
+        serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)
+    }
+}
+
+/// Allocate the public values and constrain them and the shared device key to the circuit
+/// Signals: deviceKeyX, deviceKeyY, scope, pseudonym. Shared: KeyBindingX,
+/// KeyBindingY, claim.
+fn bind_signals<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    shared: &[AllocatedNum<Scalar>],
+    signals: &[AllocatedNum<Scalar>],
+    values: Option<&[Scalar]>,
+) -> Result<(), SynthesisError> {
+    let public = alloc_public_values(cs, values, PSEUDONYM_PUBLIC_VALUES)?;
+    enforce_equal(
+        cs,
+        "KeyBinding",
+        signal_slice(shared, 0, 2)?,
+        signal_slice(signals, 0, 2)?,
+    )?;
+    enforce_equal(cs, "Statement", &public[1..], signal_slice(signals, 2, 2)?)
+}
+
+impl SpartanCircuit<E> for PseudonymCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let documents = documents_dir();
+        let root = circuits_dir();
+        let witness_dir = root.join("build/pseudonym/pseudonym_js");
+        let r1cs = witness_dir.join("pseudonym.r1cs");
+
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
+        // During setup, we only need constraint structure instead of actual witness values
+        let cs_type = type_name::<CS>();
+        let is_setup_phase = cs_type.contains("ShapeCS");
+
+        // Pass None for witness during setup
+        let (witness, values) = if is_setup_phase {
+            (None, None)
+        } else {
+            let inputs = parse_pseudonym_inputs(&self.load_inputs(&documents)?)?;
+            let witness = convert_bigint_to_scalar(pseudonym_witness(inputs))?;
+            (Some(witness), Some(self.public_values()?))
+        };
+        let signals = synthesize_checked(cs, &r1cs, witness)?;
+        bind_signals(cs, shared, &signals, values.as_deref())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        let documents = documents_dir();
+        let mut inputs = parse_pseudonym_inputs(&self.load_inputs(&documents)?)?;
+        let mut scalar = |name: &str| {
+            let value = inputs
+                .remove(name)
+                .ok_or(SynthesisError::AssignmentMissing)?;
+            bigint_to_scalar(value[0].clone())
+        };
+        let statement = PseudonymStatement {
+            scope: scalar("scope")?,
+            pseudonym: scalar("pseudonym")?,
+        };
+        Ok(statement.to_public_values())
+    }
+    /// The same shared witnesses as Show: device key, then the decoded claim
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        let documents = documents_dir();
+        let json_value = self.load_inputs(&documents)?;
+
+        let mut inputs = parse_inputs(
+            &json_value,
+            &[
+                ("deviceKeyX", FieldParser::BigIntScalar),
+                ("deviceKeyY", FieldParser::BigIntScalar),
+                ("claim", FieldParser::BigIntArray),
+            ],
+        )?;
+        let mut take = |name: &str| inputs.remove(name).ok_or(SynthesisError::AssignmentMissing);
+        let keybinding_x = bigint_to_scalar(take("deviceKeyX")?[0].clone())?;
+        let keybinding_y = bigint_to_scalar(take("deviceKeyY")?[0].clone())?;
+        let claim_scalars = convert_bigint_to_scalar(take("claim")?)?;
+
+        let kb_x = AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
+        let kb_y = AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
+
+        let mut shared_values = Vec::with_capacity(2 + claim_scalars.len());
+        shared_values.push(kb_x);
+        shared_values.push(kb_y);
+
+        for (idx, claim_scalar) in claim_scalars.into_iter().enumerate() {
+            let claim_alloc =
+                AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), move || {
+                    Ok(claim_scalar)
+                })?;
+            shared_values.push(claim_alloc);
+        }
+
+        Ok(shared_values)
+    }
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
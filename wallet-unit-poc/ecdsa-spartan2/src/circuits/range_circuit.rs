@@ -0,0 +1,222 @@
+use crate::{
+    constraint_check::synthesize_checked,
+    layout::{circuits_dir, documents_dir},
+    public_io::{alloc_public_values, enforce_equal, signal_slice},
+    range::{range_input, RangeStatement, RANGE_PUBLIC_VALUES},
+    utils::{
+        bigint_to_scalar, convert_bigint_to_scalar, parse_inputs, parse_range_inputs, FieldParser,
+    },
+    Scalar, E,
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use serde_json::Value;
+use spartan2::traits::circuit::SpartanCircuit;
+use std::{any::type_name, fs::File, path::PathBuf};
+use tracing::info;
+
+rust_witness::witness!(range);
+
+// range.circom
+#[derive(Debug, Clone, Default)]
+pub struct RangeCircuit {
+    input_path: Option<PathBuf>,
+    /// Circom input built in memory, taking precedence over `input_path`
+    input: Option<Value>,
+}
+
+impl RangeCircuit {
+    pub fn new<P: Into<Option<PathBuf>>>(path: P) -> Self {
+        Self {
+            input_path: path.into(),
+            input: None,
+        }
+    }
+
+    /// Prove `statement` of the claim in a Show input
+    /// See `range::range_input` for what the Show input must hold
+    pub fn from_show_input(show_input: &Value, statement: &RangeStatement) -> Result<Self, String> {
+        Ok(Self {
+            input_path: None,
+            input: Some(range_input(show_input, statement)?),
+        })
+    }
+
+    fn load_inputs(&self, documents: &PathBuf) -> Result<Value, SynthesisError> {
+        if let Some(input) = &self.input {
+            return Ok(input.clone());
+        }
+
+        let path = self
+            .input_path
+            .as_ref()
+            .map(|p| {
+                if p.is_absolute() {
+                    p.clone()
+                } else {
+                    documents.join(p)
+                }
+            })
+            .unwrap_or_else(|| {
+                // Try mobile flat path first, fall back to development nested path
+                let mobile_path = documents.join("range_input.json");
+                if mobile_path.exists() {
+                    mobile_path
+                } else {
+                    circuits_dir().join("inputs/range/default.json")
+                }
+            });
+        info!("Loading range inputs from {}", path.display());
+        let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
+        serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)
+    }
+
+    fn statement(&self, json_value: &Value) -> Result<RangeStatement, SynthesisError> {
+        let bound = |name: &str| {
+            json_value
+                .get(name)
+                .and_then(Value::as_str)
+                .and_then(|s| s.parse::<u64>().ok())
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+        RangeStatement::new(bound("rangeMin")?, bound("rangeMax")?)
+            .map_err(|_| SynthesisError::Unsatisfiable)
+    }
+}
+
+/// Allocate the public values and constrain them and the shared claim to the circuit
+/// Signals: claim, rangeMin, rangeMax. Shared: KeyBindingX, KeyBindingY, claim.
+fn bind_signals<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    shared: &[AllocatedNum<Scalar>],
+    signals: &[AllocatedNum<Scalar>],
+    values: Option<&[Scalar]>,
+) -> Result<(), SynthesisError> {
+    let public = alloc_public_values(cs, values, RANGE_PUBLIC_VALUES)?;
+    let claim_len = shared.len().saturating_sub(2);
+    enforce_equal(
+        cs,
+        "Claim",
+        signal_slice(shared, 2, claim_len)?,
+        signal_slice(signals, 0, claim_len)?,
+    )?;
+    enforce_equal(
+        cs,
+        "Bound",
+        &public[1..],
+        signal_slice(signals, claim_len, 2)?,
+    )
+}
+
+impl SpartanCircuit<E> for RangeCircuit {
+    fn synthesize<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<Scalar>],
+        _: &[AllocatedNum<Scalar>],
+        _: Option<&[Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let documents = documents_dir();
+        let root = circuits_dir();
+        let witness_dir = root.join("build/range/range_js");
+        let r1cs = witness_dir.join("range.r1cs");
+
+        // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
+        // During setup, we only need constraint structure instead of actual witness values
+        let cs_type = type_name::<CS>();
+        let is_setup_phase = cs_type.contains("ShapeCS");
+
+        // Pass None for witness during setup
+        let (witness, values) = if is_setup_phase {
+            (None, None)
+        } else {
+            let inputs = parse_range_inputs(&self.load_inputs(&documents)?)?;
+            let witness = convert_bigint_to_scalar(range_witness(inputs))?;
+            (Some(witness), Some(self.public_values()?))
+        };
+        let signals = synthesize_checked(cs, &r1cs, witness)?;
+        bind_signals(cs, shared, &signals, values.as_deref())
+    }
+
+    fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
+        let documents = documents_dir();
+        let json_value = self.load_inputs(&documents)?;
+        Ok(self.statement(&json_value)?.to_public_values())
+    }
+    /// The same shared witnesses as Show: device key, then the decoded claim
+    fn shared<CS: ConstraintSystem<Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        let documents = documents_dir();
+        let json_value = self.load_inputs(&documents)?;
+
+        let mut inputs = parse_inputs(
+            &json_value,
+            &[
+                ("deviceKeyX", FieldParser::BigIntScalar),
+                ("deviceKeyY", FieldParser::BigIntScalar),
+                ("claim", FieldParser::BigIntArray),
+            ],
+        )?;
+        let mut take = |name: &str| inputs.remove(name).ok_or(SynthesisError::AssignmentMissing);
+        let keybinding_x = bigint_to_scalar(take("deviceKeyX")?[0].clone())?;
+        let keybinding_y = bigint_to_scalar(take("deviceKeyY")?[0].clone())?;
+        let claim_scalars = convert_bigint_to_scalar(take("claim")?)?;
+
+        let kb_x = AllocatedNum::alloc(cs.namespace(|| "KeyBindingX"), || Ok(keybinding_x))?;
+        let kb_y = AllocatedNum::alloc(cs.namespace(|| "KeyBindingY"), || Ok(keybinding_y))?;
+
+        let mut shared_values = Vec::with_capacity(2 + claim_scalars.len());
+        shared_values.push(kb_x);
+        shared_values.push(kb_y);
+
+        for (idx, claim_scalar) in claim_scalars.into_iter().enumerate() {
+            let claim_alloc =
+                AllocatedNum::alloc(cs.namespace(|| format!("Claim{idx}")), move || {
+                    Ok(claim_scalar)
+                })?;
+            shared_values.push(claim_alloc);
+        }
+
+        Ok(shared_values)
+    }
+    fn precommitted<CS: ConstraintSystem<Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<Scalar>],
+    ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+
+    /// Whether `statement` binds to a circuit that checked the range [10, 20]
+    fn binds(statement: RangeStatement) -> bool {
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let mut alloc = |name: String, value: u64| {
+            AllocatedNum::alloc(cs.namespace(|| name), || Ok(Scalar::from(value))).unwrap()
+        };
+        let shared: Vec<_> = (0..4).map(|i| alloc(format!("shared{i}"), i)).collect();
+        let mut signals: Vec<_> = (2..4).map(|i| alloc(format!("claim{i}"), i)).collect();
+        signals.push(alloc("rangeMin".to_string(), 10));
+        signals.push(alloc("rangeMax".to_string(), 20));
+
+        let values = statement.to_public_values();
+        bind_signals(&mut cs, &shared, &signals, Some(&values)).unwrap();
+        cs.is_satisfied()
+    }
+
+    #[test]
+    fn test_public_bounds_must_match_the_circuit() {
+        assert!(binds(RangeStatement::new(10, 20).unwrap()));
+        assert!(!binds(RangeStatement::new(10, 21).unwrap()));
+        assert!(!binds(RangeStatement::new(0, 20).unwrap()));
+    }
+}
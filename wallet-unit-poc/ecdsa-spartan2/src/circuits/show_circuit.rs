@@ -1,9 +1,24 @@
-use crate::{utils::*, Scalar, E};
+use crate::{
+    constraint_check::synthesize_checked,
+    layout::{circuits_dir, documents_dir},
+    mem_profile,
+    public_io::{alloc_public_values, enforce_equal, signal_slice},
+    show::{ShowStatement, SHOW_PUBLIC_VALUES},
+    signpost::{section, Phase},
+    utils::*,
+    witness::CircuitKind,
+    witness_cache::cached_witness,
+    witness_source::generate_witness,
+    Scalar, E,
+};
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
-use circom_scotia::{reader::load_r1cs, synthesize};
 use serde_json::Value;
 use spartan2::traits::circuit::SpartanCircuit;
-use std::{any::type_name, env::current_dir, fs::File, path::PathBuf};
+use std::{
+    any::type_name,
+    fs::{self, File},
+    path::PathBuf,
+};
 use tracing::info;
 
 rust_witness::witness!(show);
@@ -21,29 +36,51 @@ impl ShowCircuit {
         }
     }
 
-    fn input_path_absolute(&self, cwd: &PathBuf) -> PathBuf {
+    fn input_path_absolute(&self, documents: &PathBuf) -> PathBuf {
         self.input_path
             .as_ref()
             .map(|p| {
                 if p.is_absolute() {
                     p.clone()
                 } else {
-                    cwd.join(p)
+                    documents.join(p)
                 }
             })
             .unwrap_or_else(|| {
                 // Try mobile flat path first, fall back to development nested path
-                let mobile_path = cwd.join("show_input.json");
+                let mobile_path = documents.join("show_input.json");
                 if mobile_path.exists() {
                     mobile_path
                 } else {
-                    cwd.join("../circom/inputs/show/default.json")
+                    circuits_dir().join("inputs/show/default.json")
                 }
             })
     }
 
-    fn load_inputs(&self, cwd: &PathBuf) -> Result<Value, SynthesisError> {
-        let path = self.input_path_absolute(cwd);
+    fn witness(&self, documents: &PathBuf) -> Result<Vec<Scalar>, SynthesisError> {
+        let input_bytes = fs::read(self.input_path_absolute(documents))
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        cached_witness(CircuitKind::Show, &input_bytes, || {
+            generate_witness(CircuitKind::Show, &input_bytes, || {
+                let json_value: Value = serde_json::from_slice(&input_bytes)
+                    .map_err(|_| SynthesisError::AssignmentMissing)?;
+
+                // Parse inputs using declarative field definitions
+                let inputs = parse_show_inputs(&json_value)?;
+
+                // Generate witness using native Rust (rust-witness)
+                let witness_bigint = {
+                    let _section = section(Phase::WitnessGen);
+                    show_witness(inputs)
+                };
+                mem_profile::snapshot("witness_gen");
+                convert_bigint_to_scalar(witness_bigint)
+            })
+        })
+    }
+
+    fn load_inputs(&self, documents: &PathBuf) -> Result<Value, SynthesisError> {
+        let path = self.input_path_absolute(documents);
         info!("Loading show inputs from {}", path.display());
         let file = File::open(&path).map_err(|_| SynthesisError::AssignmentMissing)?;
         serde_json::from_reader(file).map_err(|_| SynthesisError::AssignmentMissing)
@@ -54,49 +91,83 @@ impl SpartanCircuit<E> for ShowCircuit {
     fn synthesize<CS: ConstraintSystem<Scalar>>(
         &self,
         cs: &mut CS,
-        _: &[AllocatedNum<Scalar>],
+        shared: &[AllocatedNum<Scalar>],
         _: &[AllocatedNum<Scalar>],
         _: Option<&[Scalar]>,
     ) -> Result<(), SynthesisError> {
-        let cwd = current_dir().unwrap();
-        let root = cwd.join("../circom");
+        let documents = documents_dir();
+        let root = circuits_dir();
         let witness_dir = root.join("build/show/show_js");
         let r1cs = witness_dir.join("show.r1cs");
-        let json_value = self.load_inputs(&cwd)?;
-
-        // Parse inputs using declarative field definitions
-        let inputs = parse_show_inputs(&json_value)?;
-
         // Detect if we're in setup phase (ShapeCS) or prove phase (SatisfyingAssignment)
         // During setup, we only need constraint structure instead of actual witness values
         let cs_type = type_name::<CS>();
         let is_setup_phase = cs_type.contains("ShapeCS");
 
-        if is_setup_phase {
-            let r1cs = load_r1cs(r1cs);
-            // Pass None for witness during setup
-            synthesize(cs, r1cs, None)?;
-            return Ok(());
-        }
-
-        // Generate witness using native Rust (rust-witness)
-        let witness_bigint = show_witness(inputs);
-        let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
-
-        let r1cs = load_r1cs(r1cs);
-        synthesize(cs, r1cs, Some(witness))?;
+        // Pass None for witness during setup
+        let witness = if is_setup_phase {
+            None
+        } else {
+            Some(self.witness(&documents)?)
+        };
+        let signals = synthesize_checked(cs, &r1cs, witness)?;
+
+        // Signals: ageAbove18, deviceKeyX, deviceKeyY, messageHash, claim,
+        // currentYear, currentMonth, currentDay. Shared: KeyBindingX,
+        // KeyBindingY, claim.
+        let values = if is_setup_phase {
+            None
+        } else {
+            Some(self.public_values()?)
+        };
+        let public = alloc_public_values(cs, values.as_deref(), SHOW_PUBLIC_VALUES)?;
+        let claim_len = shared.len().saturating_sub(2);
+
+        let age_above_18 = signal_slice(&signals, 0, 1)?[0].get_variable();
+        cs.enforce(
+            || "ageAbove18 is 1",
+            |lc| lc + age_above_18,
+            |lc| lc + CS::one(),
+            |lc| lc + CS::one(),
+        );
+        enforce_equal(
+            cs,
+            "KeyBinding",
+            signal_slice(shared, 0, 2)?,
+            signal_slice(&signals, 1, 2)?,
+        )?;
+        enforce_equal(
+            cs,
+            "MessageHash",
+            &public[1..2],
+            signal_slice(&signals, 3, 1)?,
+        )?;
+        enforce_equal(
+            cs,
+            "Claim",
+            signal_slice(shared, 2, claim_len)?,
+            signal_slice(&signals, 4, claim_len)?,
+        )?;
+        enforce_equal(
+            cs,
+            "ReferenceDate",
+            &public[2..],
+            signal_slice(&signals, 4 + claim_len, 3)?,
+        )?;
         Ok(())
     }
 
     fn public_values(&self) -> Result<Vec<Scalar>, SynthesisError> {
-        Ok(vec![])
+        let statement = ShowStatement::from_show_input(&self.load_inputs(&documents_dir())?)
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        Ok(statement.to_public_values())
     }
     fn shared<CS: ConstraintSystem<Scalar>>(
         &self,
         cs: &mut CS,
     ) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
-        let cwd = current_dir().unwrap();
-        let json_value = self.load_inputs(&cwd)?;
+        let documents = documents_dir();
+        let json_value = self.load_inputs(&documents)?;
 
         let inputs = parse_show_inputs(&json_value)?;
         let keybinding_x_bigint = inputs.get("deviceKeyX").unwrap()[0].clone();
@@ -0,0 +1,70 @@
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use spartan2::traits::{circuit::SpartanCircuit, Engine};
+
+/// Constraint count used when a benchmark does not pick one
+pub const DEFAULT_SYNTHETIC_CONSTRAINTS: usize = 1 << 18;
+
+// Engine-agnostic squaring chain for comparing proving engines.
+// The circom circuits are compiled for T256's scalar field, so they cannot run
+// on another curve; this circuit has the same shape over every field, one shared
+// value feeding `num_constraints` multiplication constraints.
+#[derive(Debug, Clone)]
+pub struct SyntheticCircuit {
+    num_constraints: usize,
+}
+
+impl SyntheticCircuit {
+    pub fn new(num_constraints: usize) -> Self {
+        Self { num_constraints }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+}
+
+impl Default for SyntheticCircuit {
+    fn default() -> Self {
+        Self::new(DEFAULT_SYNTHETIC_CONSTRAINTS)
+    }
+}
+
+impl<E: Engine> SpartanCircuit<E> for SyntheticCircuit {
+    fn synthesize<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<E::Scalar>],
+        _: &[AllocatedNum<E::Scalar>],
+        _: Option<&[E::Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        let mut acc = shared
+            .first()
+            .cloned()
+            .ok_or(SynthesisError::AssignmentMissing)?;
+        for i in 0..self.num_constraints {
+            acc = acc.square(cs.namespace(|| format!("square{i}")))?;
+        }
+        Ok(())
+    }
+
+    fn public_values(&self) -> Result<Vec<E::Scalar>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn shared<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        let seed = AllocatedNum::alloc(cs.namespace(|| "Seed"), || Ok(E::Scalar::from(3u64)))?;
+        Ok(vec![seed])
+    }
+    fn precommitted<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        _cs: &mut CS,
+        _shared: &[AllocatedNum<E::Scalar>],
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        Ok(vec![])
+    }
+    fn num_challenges(&self) -> usize {
+        0
+    }
+}
@@ -0,0 +1,121 @@
+//! Claim hashes and shared-witness values, computed the way the circuits do.
+//!
+//! Issuers and verifiers can derive what Prepare and Show enforce without
+//! synthesizing either circuit. Prepare binds each disclosure to the credential
+//! through its SD-JWT `_sd` digest, SHA-256 in `ClaimHasher`; no Poseidon is
+//! involved. Prepare and Show are linked by `comm_W_shared`, the Hyrax
+//! commitment to the key-binding point and the decoded birthday claim returned
+//! by `commit_claims`.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    utils::{decode_base64, keybinding_coordinate},
+    Scalar,
+};
+
+/// The `_sd` digest of each disclosure: base64url(SHA-256(disclosure)), unpadded
+pub fn hash_claims<S: AsRef<str>>(disclosures: &[S]) -> Vec<String> {
+    use base64::Engine;
+    disclosures
+        .iter()
+        .map(|disclosure| {
+            base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .encode(Sha256::digest(disclosure.as_ref().as_bytes()))
+        })
+        .collect()
+}
+
+/// Shared witness values committed to in `comm_W_shared`, in circuit order
+/// `KeyBindingX`, `KeyBindingY`, then the decoded bytes of `disclosure` zero-padded
+/// to `(max_claim_length * 3) / 4`. The key-binding coordinates are the base64url
+/// `x` and `y` of the credential's `cnf.jwk`.
+pub fn commit_claims(
+    disclosure: &str,
+    keybinding_x: &str,
+    keybinding_y: &str,
+    max_claim_length: usize,
+) -> Result<Vec<Scalar>, String> {
+    let coordinate = |b64: &str, name: &str| {
+        keybinding_coordinate(b64).map_err(|_| format!("malformed key-binding {}", name))
+    };
+    let decoded = decode_base64(disclosure).map_err(|_| "malformed disclosure")?;
+    let claim = claim_scalars(&decoded, max_claim_length * 3 / 4).ok_or_else(|| {
+        format!(
+            "disclosure exceeds the circuit limit of {} characters",
+            max_claim_length
+        )
+    })?;
+
+    let mut shared = Vec::with_capacity(2 + claim.len());
+    shared.push(coordinate(keybinding_x, "x")?);
+    shared.push(coordinate(keybinding_y, "y")?);
+    shared.extend(claim);
+    Ok(shared)
+}
+
+/// Value of a decoded disclosure given as circuit input (one byte per entry)
+/// The disclosure is `["salt","name","value"]`; the value lies between its
+/// fifth and sixth quotes, which is where the circuits read it from.
+pub fn disclosure_value(claim: &Value) -> Result<Vec<u8>, String> {
+    let bytes = claim
+        .as_array()
+        .ok_or("claim must be an array")?
+        .iter()
+        .map(|byte| match byte {
+            Value::String(s) => s.parse::<u8>().ok(),
+            other => other.as_u64().and_then(|b| u8::try_from(b).ok()),
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or("claim entries must be bytes")?;
+
+    if bytes.iter().filter(|b| **b == b'"').count() != 6 {
+        return Err(
+            "claim is not a [salt, name, value] disclosure with a string value".to_string(),
+        );
+    }
+    Ok(bytes
+        .split(|b| *b == b'"')
+        .nth(5)
+        .unwrap_or_default()
+        .to_vec())
+}
+
+/// Decoded claim bytes as scalars, zero-padded to `decoded_len`
+/// `None` if the claim does not fit.
+pub fn claim_scalars(decoded: &[u8], decoded_len: usize) -> Option<Vec<Scalar>> {
+    if decoded.len() > decoded_len {
+        return None;
+    }
+    let mut scalars: Vec<Scalar> = decoded.iter().map(|&b| Scalar::from(b as u64)).collect();
+    scalars.resize(decoded_len, Scalar::from(0u64));
+    Some(scalars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_claims_matches_sd_digest() {
+        // Example disclosure from the SD-JWT specification
+        let digests = hash_claims(&["WyI2cU1RdlJMNWhhaiIsICJmYW1pbHlfbmFtZSIsICJNw7ZiaXVzIl0"]);
+        assert_eq!(digests, vec!["uutlBuYeMDyjLLTpf6Jxi7yNkEF35jdyWMn9U7b_RYY"]);
+    }
+
+    #[test]
+    fn test_commit_claims_layout() {
+        use base64::Engine;
+        let encode = |bytes: &[u8]| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+        let disclosure = encode(br#"["salt","roc_birthday","1040605"]"#);
+
+        let shared = commit_claims(&disclosure, &encode(&[1]), &encode(&[2]), 128).unwrap();
+        assert_eq!(shared.len(), 2 + 96);
+        assert_eq!(shared[0], Scalar::from(1u64));
+        assert_eq!(shared[1], Scalar::from(2u64));
+        assert_eq!(shared[2], Scalar::from(b'[' as u64));
+        assert_eq!(shared[97], Scalar::from(0u64));
+        assert!(commit_claims(&disclosure, "AQ", "Ag", 8).is_err());
+    }
+}
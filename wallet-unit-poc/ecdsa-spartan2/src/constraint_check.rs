@@ -0,0 +1,221 @@
+//! Locating the constraint a witness fails.
+//!
+//! circom-scotia assigns the witness without evaluating any constraint, so a
+//! bad witness only surfaces later as an opaque prover failure. With the check
+//! enabled, `synthesize_checked` first evaluates every constraint of the R1CS
+//! against the witness. The first one with (A·w)(B·w) != C·w is kept as a
+//! `ConstraintFailure`, with its wires named from the circuit's `.sym` when the
+//! circom build has one, and synthesis fails with `Unsatisfiable`; callers then
+//! collect the details with `take_constraint_failure`. The pass costs about as
+//! much as synthesis itself, so it is off by default. Without it, synthesis
+//! goes through `witness_stream::synthesize_unchecked` instead.
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use circom_scotia::{r1cs::R1CS, synthesize};
+use ff::{Field, PrimeField};
+use rayon::prelude::*;
+use tracing::warn;
+
+use crate::{
+    r1cs::load_r1cs_checked, witness::le_bytes_to_decimal, witness_debug::parse_sym,
+    witness_stream::synthesize_unchecked, Scalar,
+};
+
+static CHECK_ENABLED: AtomicBool = AtomicBool::new(false);
+static LAST_FAILURE: Mutex<Option<ConstraintFailure>> = Mutex::new(None);
+
+/// Enable or disable evaluating constraints before synthesis (disabled by default)
+pub fn set_constraint_check_enabled(enabled: bool) {
+    CHECK_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_constraint_check_enabled() -> bool {
+    CHECK_ENABLED.load(Ordering::Relaxed)
+}
+
+/// The failure recorded by the last failed check, if not taken yet
+pub fn take_constraint_failure() -> Option<ConstraintFailure> {
+    LAST_FAILURE.lock().ok().and_then(|mut slot| slot.take())
+}
+
+/// A wire of a failing constraint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintSignal {
+    pub wire: usize,
+    /// `.sym` name, when the circom build has one
+    pub name: Option<String>,
+    /// Decimal value
+    pub value: String,
+}
+
+/// The first constraint a witness does not satisfy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstraintFailure {
+    pub index: usize,
+    /// A·w, B·w and C·w, in decimal
+    pub a: String,
+    pub b: String,
+    pub c: String,
+    /// Wires of A, B and C, each listed once
+    pub signals: Vec<ConstraintSignal>,
+}
+
+impl fmt::Display for ConstraintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "constraint {} fails: {} * {} != {}",
+            self.index, self.a, self.b, self.c
+        )?;
+        for signal in &self.signals {
+            match &signal.name {
+                Some(name) => write!(f, "; {} (wire {}) = {}", name, signal.wire, signal.value)?,
+                None => write!(f, "; wire {} = {}", signal.wire, signal.value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConstraintFailure {}
+
+fn decimal(value: &Scalar) -> String {
+    le_bytes_to_decimal(value.to_repr().as_ref())
+}
+
+/// A linear combination at `witness`; `None` if it references a missing wire
+fn evaluate(terms: &[(usize, Scalar)], witness: &[Scalar]) -> Option<Scalar> {
+    terms.iter().try_fold(Scalar::ZERO, |acc, (wire, coeff)| {
+        witness.get(*wire).map(|value| acc + *coeff * value)
+    })
+}
+
+/// The first constraint of `r1cs` that `witness` fails, with unnamed signals
+pub fn find_unsatisfied(r1cs: &R1CS<Scalar>, witness: &[Scalar]) -> Option<ConstraintFailure> {
+    let index = r1cs.constraints.par_iter().position_first(|(a, b, c)| {
+        match (
+            evaluate(a, witness),
+            evaluate(b, witness),
+            evaluate(c, witness),
+        ) {
+            (Some(a), Some(b), Some(c)) => a * b != c,
+            _ => true,
+        }
+    })?;
+
+    let (a, b, c) = &r1cs.constraints[index];
+    let show = |terms: &[(usize, Scalar)]| {
+        evaluate(terms, witness).map_or_else(|| "<missing wire>".to_string(), |v| decimal(&v))
+    };
+    let mut wires: Vec<usize> = a.iter().chain(b).chain(c).map(|(wire, _)| *wire).collect();
+    wires.sort_unstable();
+    wires.dedup();
+
+    Some(ConstraintFailure {
+        index,
+        a: show(a),
+        b: show(b),
+        c: show(c),
+        signals: wires
+            .into_iter()
+            .map(|wire| ConstraintSignal {
+                wire,
+                name: None,
+                value: witness
+                    .get(wire)
+                    .map_or_else(|| "<missing>".to_string(), decimal),
+            })
+            .collect(),
+    })
+}
+
+/// `build/<name>/<name>.sym` for `build/<name>/<name>_js/<name>.r1cs`
+fn sym_path_for(r1cs_path: &Path) -> Option<PathBuf> {
+    let stem = r1cs_path.file_stem()?;
+    let build_dir = r1cs_path.parent()?.parent()?;
+    Some(build_dir.join(stem).with_extension("sym"))
+}
+
+/// Name the failure's signals from the `.sym` beside the R1CS, if there is one
+fn name_signals(failure: &mut ConstraintFailure, r1cs_path: &Path) {
+    let Some(text) = sym_path_for(r1cs_path).and_then(|path| fs::read_to_string(path).ok()) else {
+        return;
+    };
+    if let Ok(names) = parse_sym(&text) {
+        for signal in &mut failure.signals {
+            signal.name = names.get(&signal.wire).cloned();
+        }
+    }
+}
+
+/// Load the R1CS at `r1cs_path` and synthesize it, checking `witness` first when
+/// the check is enabled. Returns circom-scotia's public signals.
+pub fn synthesize_checked<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    r1cs_path: &Path,
+    witness: Option<Vec<Scalar>>,
+) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+    if witness.is_none() || !is_constraint_check_enabled() {
+        return synthesize_unchecked(cs, r1cs_path, witness.map(|w| w.into_iter().map(Ok)));
+    }
+
+    let r1cs = load_r1cs_checked(r1cs_path)?;
+    if let Some(mut failure) = witness
+        .as_deref()
+        .and_then(|witness| find_unsatisfied(&r1cs, witness))
+    {
+        name_signals(&mut failure, r1cs_path);
+        warn!(
+            "Witness does not satisfy {}: {}",
+            r1cs_path.display(),
+            failure
+        );
+        if let Ok(mut slot) = LAST_FAILURE.lock() {
+            *slot = Some(failure);
+        }
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    synthesize(cs, r1cs, witness)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finds_failing_constraint() {
+        let one = Scalar::ONE;
+        // w1 * w2 = w3, then w3 * 1 = w4
+        let r1cs = R1CS {
+            num_inputs: 1,
+            num_aux: 4,
+            num_variables: 5,
+            constraints: vec![
+                (vec![(1, one)], vec![(2, one)], vec![(3, one)]),
+                (vec![(3, one)], vec![(0, one)], vec![(4, one)]),
+            ],
+        };
+        let witness = |w4: u64| [1u64, 2, 3, 6, w4].map(Scalar::from);
+        assert_eq!(find_unsatisfied(&r1cs, &witness(6)), None);
+
+        let failure = find_unsatisfied(&r1cs, &witness(7)).unwrap();
+        assert_eq!(failure.index, 1);
+        assert_eq!((failure.a.as_str(), failure.c.as_str()), ("6", "7"));
+        let wires: Vec<usize> = failure.signals.iter().map(|s| s.wire).collect();
+        assert_eq!(wires, [0, 3, 4]);
+
+        assert_eq!(
+            sym_path_for(Path::new("build/show/show_js/show.r1cs")),
+            Some(PathBuf::from("build/show/show.sym"))
+        );
+    }
+}
@@ -0,0 +1,271 @@
+//! Prepare circuit inputs built directly from an issued SD-JWT.
+//!
+//! Mirrors `generateJwtInputs` in `circom/src/jwt.ts`, so a wallet can go from
+//! the credential string it received to a Prepare witness without the Node
+//! tooling. The issuer signature is checked here first, since an invalid one
+//! would otherwise only surface as an unsatisfiable circuit during proving.
+
+use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{claims::hash_claims, signature::ecdsa_inputs, utils::decode_base64};
+
+/// Disclosure name of the birthday claim the Prepare circuit outputs to Show
+pub const AGE_CLAIM_NAME: &str = "roc_birthday";
+
+/// Payload patterns locating the device key binding (`cnf.jwk.x` / `cnf.jwk.y`)
+const KEY_BINDING_PATTERNS: [&str; 2] = ["\"x\":\"", "\"y\":\""];
+
+/// JWT circuit parameters, as compiled from `circom/circuits.json`
+#[derive(Debug, Clone, Copy)]
+pub struct PrepareParams {
+    pub max_message_length: usize,
+    pub max_b64_payload_length: usize,
+    pub max_matches: usize,
+    pub max_substring_length: usize,
+    pub max_claim_length: usize,
+}
+
+impl Default for PrepareParams {
+    fn default() -> Self {
+        Self {
+            max_message_length: 1920,
+            max_b64_payload_length: 1900,
+            max_matches: 4,
+            max_substring_length: 50,
+            max_claim_length: 128,
+        }
+    }
+}
+
+/// Stable identifier for a credential: hex SHA-256 of its issuer-signed JWT
+pub fn credential_id(sd_jwt: &str) -> String {
+    let jwt = sd_jwt.split('~').next().unwrap_or_default();
+    Sha256::digest(jwt.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Build the Prepare circuit input JSON for an SD-JWT (`jwt~disclosure~...~`)
+///
+/// `issuer_pubkey` is the issuer's P-256 key, 65-byte uncompressed SEC1 or
+/// 64-byte `X || Y`. The `roc_birthday` disclosure is always included; other
+/// disclosures fill the remaining match slots in order.
+pub fn build_prepare_input(
+    sd_jwt: &str,
+    issuer_pubkey: &[u8],
+    params: &PrepareParams,
+) -> Result<Value, String> {
+    let mut parts = sd_jwt.split('~');
+    let jwt = parts.next().unwrap_or_default();
+    let disclosures: Vec<&str> = parts.filter(|d| !d.is_empty()).collect();
+
+    let segments: Vec<&str> = jwt.split('.').collect();
+    let [b64_header, b64_payload, b64_signature] = segments[..] else {
+        return Err("credential is not a compact JWT".to_string());
+    };
+    if b64_payload.len() > params.max_b64_payload_length {
+        return Err(format!(
+            "JWT payload is {} base64 chars, circuit supports {}",
+            b64_payload.len(),
+            params.max_b64_payload_length
+        ));
+    }
+
+    // ES256 inputs over `header.payload`
+    let signed = format!("{}.{}", b64_header, b64_payload);
+    let signature = decode_base64(b64_signature).map_err(|_| "malformed JWT signature")?;
+    verify_issuer_signature(signed.as_bytes(), &signature, issuer_pubkey)?;
+    let es256 = ecdsa_inputs(
+        &signature,
+        &Sha256::digest(signed.as_bytes()),
+        issuer_pubkey,
+    )?;
+    let (message, message_length) = sha256_pad(signed.as_bytes(), params.max_message_length)?;
+
+    // Substring matches are located in the decoded payload
+    let payload = decode_base64(b64_payload).map_err(|_| "malformed JWT payload")?;
+    let claims = select_disclosures(
+        &disclosures,
+        params.max_matches - KEY_BINDING_PATTERNS.len(),
+    )?;
+    let digests = hash_claims(&claims);
+    let patterns: Vec<&str> = KEY_BINDING_PATTERNS
+        .iter()
+        .copied()
+        .chain(digests.iter().map(String::as_str))
+        .collect();
+
+    let mut match_substring = Vec::with_capacity(params.max_matches);
+    let mut match_length = Vec::with_capacity(params.max_matches);
+    let mut match_index = Vec::with_capacity(params.max_matches);
+    for pattern in &patterns {
+        if pattern.len() > params.max_substring_length {
+            return Err(format!("match pattern '{}' is too long", pattern));
+        }
+        let index = find(&payload, pattern.as_bytes())
+            .ok_or_else(|| format!("'{}' not found in JWT payload", pattern))?;
+        match_substring.push(padded_bytes(
+            pattern.as_bytes(),
+            params.max_substring_length,
+        ));
+        match_length.push(pattern.len());
+        match_index.push(index);
+    }
+    while match_index.len() < params.max_matches {
+        match_substring.push(padded_bytes(&[], params.max_substring_length));
+        match_length.push(0);
+        match_index.push(0);
+    }
+
+    // Claim slots line up with the patterns; the key binding slots stay empty
+    let mut claim_array = vec![padded_bytes(&[], params.max_claim_length); params.max_matches];
+    let mut claim_lengths = vec![0usize; params.max_matches];
+    let mut decode_flags = vec![0u8; params.max_matches];
+    let mut age_claim_index = None;
+    for (offset, claim) in claims.iter().enumerate() {
+        let slot = KEY_BINDING_PATTERNS.len() + offset;
+        let (padded, _) = sha256_pad(claim.as_bytes(), params.max_claim_length)?;
+        claim_array[slot] = padded;
+        claim_lengths[slot] = claim.len();
+        if is_age_claim(claim) {
+            decode_flags[slot] = 1;
+            age_claim_index = Some(slot);
+        }
+    }
+    let age_claim_index = age_claim_index.ok_or("roc_birthday disclosure not found")?;
+
+    Ok(json!({
+        "sig_r": es256.r.to_string(),
+        "sig_s_inverse": es256.s_inverse.to_string(),
+        "pubKeyX": es256.pub_key_x.to_string(),
+        "pubKeyY": es256.pub_key_y.to_string(),
+        "message": to_strings(&message),
+        "messageLength": message_length,
+        "periodIndex": b64_header.len(),
+        "matchesCount": patterns.len(),
+        "matchSubstring": match_substring.iter().map(|s| to_strings(s)).collect::<Vec<_>>(),
+        "matchLength": match_length,
+        "matchIndex": match_index,
+        "claims": claim_array.iter().map(|c| to_strings(c)).collect::<Vec<_>>(),
+        "claimLengths": claim_lengths.iter().map(|l| l.to_string()).collect::<Vec<_>>(),
+        "decodeFlags": decode_flags,
+        "ageClaimIndex": age_claim_index,
+    }))
+}
+
+pub(crate) fn verify_issuer_signature(message: &[u8], signature: &[u8], pubkey: &[u8]) -> Result<(), String> {
+    let sec1 = match pubkey.len() {
+        64 => [&[0x04][..], pubkey].concat(),
+        _ => pubkey.to_vec(),
+    };
+    let key = VerifyingKey::from_sec1_bytes(&sec1).map_err(|_| "invalid issuer public key")?;
+    let signature =
+        Signature::from_slice(signature).map_err(|_| "JWT signature must be 64-byte r || s")?;
+    key.verify(message, &signature)
+        .map_err(|_| "issuer signature does not verify".to_string())
+}
+
+/// Pick the disclosures to prove: the age claim plus others, in order, up to `capacity`
+fn select_disclosures<'a>(
+    disclosures: &[&'a str],
+    capacity: usize,
+) -> Result<Vec<&'a str>, String> {
+    let age = disclosures
+        .iter()
+        .position(|d| is_age_claim(d))
+        .ok_or("roc_birthday disclosure not found")?;
+    let mut others = capacity.saturating_sub(1);
+    Ok(disclosures
+        .iter()
+        .enumerate()
+        .filter(|&(i, _)| {
+            if i == age {
+                return true;
+            }
+            let keep = others > 0;
+            others = others.saturating_sub(1);
+            keep
+        })
+        .map(|(_, d)| *d)
+        .collect())
+}
+
+/// Whether a base64url disclosure decodes to `[salt, "roc_birthday", value]`
+fn is_age_claim(disclosure: &str) -> bool {
+    decode_base64(disclosure)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+        .and_then(|v| {
+            v.get(1)
+                .and_then(Value::as_str)
+                .map(|name| name == AGE_CLAIM_NAME)
+        })
+        .unwrap_or(false)
+}
+
+/// SHA-256 padding (`0x80`, zeros, 64-bit bit length), then zeros up to `max_len`
+/// Returns the padded bytes and the padded length, like zk-email's `sha256Pad`
+fn sha256_pad(message: &[u8], max_len: usize) -> Result<(Vec<u8>, usize), String> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+    let padded_len = padded.len();
+    if padded_len > max_len {
+        return Err(format!(
+            "{} bytes exceed the circuit limit of {} after padding",
+            message.len(),
+            max_len
+        ));
+    }
+    padded.resize(max_len, 0);
+    Ok((padded, padded_len))
+}
+
+fn padded_bytes(bytes: &[u8], len: usize) -> Vec<u8> {
+    let mut padded = bytes.to_vec();
+    padded.resize(len, 0);
+    padded
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn to_strings(bytes: &[u8]) -> Vec<String> {
+    bytes.iter().map(|b| b.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256_pad() {
+        let (padded, len) = sha256_pad(b"abc", 128).unwrap();
+        assert_eq!(len, 64);
+        assert_eq!(padded.len(), 128);
+        assert_eq!(padded[3], 0x80);
+        assert_eq!(padded[63], 24);
+        assert!(sha256_pad(&[0; 56], 64).is_err());
+    }
+
+    #[test]
+    fn test_age_claim_always_selected() {
+        use base64::Engine;
+        let encode = |claim: &str| base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claim);
+        let name = encode(r#"["salt","name","John Doe"]"#);
+        let email = encode(r#"["salt","email","j@example.com"]"#);
+        let age = encode(r#"["salt","roc_birthday","1040605"]"#);
+
+        let selected =
+            select_disclosures(&[name.as_str(), email.as_str(), age.as_str()], 2).unwrap();
+        assert_eq!(selected, vec![name.as_str(), age.as_str()]);
+        assert!(select_disclosures(&[name.as_str()], 2).is_err());
+    }
+}
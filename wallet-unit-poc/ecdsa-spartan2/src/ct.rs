@@ -0,0 +1,132 @@
+//! Constant-time handling of the secret bytes that become shared witness values.
+//!
+//! The key-binding coordinates and the birthday claim are decoded from base64
+//! and turned into scalars on the device, and end up blinded in `comm_W_shared`.
+//! A table-driven decoder indexes memory by each character, so the cache lines
+//! it touches depend on the secret; the decoder here computes every sextet with
+//! arithmetic masks instead, and branches only on the input length and its
+//! trailing `=` padding. Scalars are built from fixed-size big-endian buffers
+//! rather than through a `BigInt`, whose arithmetic depends on the value.
+//!
+//! What this does not cover: JSON parsing of the JWT and disclosures, the circom
+//! witness calculator, the `BigInt` witness it returns, and spartan2's
+//! multi-scalar multiplications in commit and reblind are all outside this
+//! module. The `ct-test` feature runs a dudect-style timing check of blind
+//! application (`tests/constant_time.rs`) to see where that last one stands.
+
+use crate::Scalar;
+
+/// Decode standard or URL-safe base64, padded or not, like `utils::decode_base64`
+/// `None` on malformed input: a character outside the alphabet, characters from
+/// both alphabets, a length of 1 mod 4, or non-zero trailing bits.
+pub fn decode_base64(encoded: &[u8]) -> Option<Vec<u8>> {
+    let unpadded = strip_padding(encoded);
+    if unpadded.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+    // Sign bits set on any failure, so no branch is taken per character
+    let mut invalid = 0i16;
+    let mut url_safe = 0i16;
+    let mut standard = 0i16;
+    for chunk in unpadded.chunks(4) {
+        let mut acc = 0u32;
+        for &c in chunk {
+            let sextet = decode_sextet(c, &mut url_safe, &mut standard);
+            invalid |= sextet;
+            acc = (acc << 6) | (sextet as u32 & 0x3f);
+        }
+        acc <<= 6 * (4 - chunk.len());
+        let len = chunk.len() * 3 / 4;
+        let unused = acc & ((1u32 << (8 * (3 - len))) - 1);
+        invalid |= -(((unused | unused.wrapping_neg()) >> 31) as i16);
+        decoded.extend_from_slice(&acc.to_be_bytes()[1..1 + len]);
+    }
+
+    ((invalid | (url_safe & standard)) >= 0).then_some(decoded)
+}
+
+/// Up to two trailing `=`; how many only reveals the decoded length
+fn strip_padding(encoded: &[u8]) -> &[u8] {
+    let mut unpadded = encoded;
+    for _ in 0..2 {
+        if let [rest @ .., b'='] = unpadded {
+            unpadded = rest;
+        }
+    }
+    unpadded
+}
+
+/// The 6-bit value of `c`, or -1 outside both alphabets
+/// Sets the sign bit of `url_safe` or `standard` for `-`/`_` or `+`/`/`.
+fn decode_sextet(c: u8, url_safe: &mut i16, standard: &mut i16) -> i16 {
+    let c = c as i16;
+    // -1 if lo <= c <= hi, else 0
+    let within = |lo: u8, hi: u8| ((lo as i16 - 1 - c) & (c - hi as i16 - 1)) >> 8;
+    let (plus, slash) = (within(b'+', b'+'), within(b'/', b'/'));
+    let (minus, underscore) = (within(b'-', b'-'), within(b'_', b'_'));
+    *url_safe |= minus | underscore;
+    *standard |= plus | slash;
+
+    -1 + (within(b'A', b'Z') & (c - 64))
+        + (within(b'a', b'z') & (c - 70))
+        + (within(b'0', b'9') & (c + 5))
+        + ((plus | minus) & 63)
+        + ((slash | underscore) & 64)
+}
+
+/// Big-endian bytes as a scalar, `None` if the value is not below the modulus
+/// Leading zero bytes beyond 32 are accepted, as a `BigInt` conversion would.
+pub fn be_bytes_to_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let (excess, value) = bytes.split_at(bytes.len().saturating_sub(32));
+    let overflow = excess.iter().fold(0u8, |acc, byte| acc | byte);
+
+    let mut repr = [0u8; 32];
+    for (dst, src) in repr.iter_mut().zip(value.iter().rev()) {
+        *dst = *src;
+    }
+    let scalar = Scalar::from_bytes(&repr);
+    if overflow != 0 {
+        return None;
+    }
+    scalar.into_option()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{
+        engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+        Engine,
+    };
+
+    #[test]
+    fn test_decode_matches_base64_crate() {
+        for len in 0..70 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 251) as u8).collect();
+            for encoded in [URL_SAFE_NO_PAD.encode(&bytes), STANDARD.encode(&bytes)] {
+                assert_eq!(decode_base64(encoded.as_bytes()), Some(bytes.clone()));
+            }
+        }
+        assert_eq!(decode_base64(b"-_8"), URL_SAFE_NO_PAD.decode("-_8").ok());
+        assert_eq!(decode_base64(b"+/8="), STANDARD.decode("+/8=").ok());
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed() {
+        for encoded in ["A", "AB=C", "QR", "QUJ", "-+AA", "A_/A", "AA*A", "AA=A"] {
+            assert_eq!(decode_base64(encoded.as_bytes()), None, "{}", encoded);
+        }
+    }
+
+    #[test]
+    fn test_be_bytes_to_scalar() {
+        assert_eq!(be_bytes_to_scalar(&[1, 0]), Some(Scalar::from(256u64)));
+        assert_eq!(be_bytes_to_scalar(&[0; 40]), Some(Scalar::from(0u64)));
+        let mut wide = [0u8; 33];
+        wide[0] = 1;
+        assert_eq!(be_bytes_to_scalar(&wide), None);
+        assert_eq!(be_bytes_to_scalar(&[0xff; 32]), None);
+    }
+}
@@ -0,0 +1,104 @@
+//! Per-deployment domain separation for Prepare and Show proofs.
+//!
+//! Spartan2 seeds the verifier transcript itself, so a custom label cannot be
+//! threaded into `R1CSSNARK::verify`. Instead each circuit's first public value
+//! is a tag derived from the configured domain and the circuit name. Public
+//! values are absorbed into the Fiat-Shamir transcript, so the tag cannot be
+//! changed without invalidating the proof, and `check_domain` rejects proofs
+//! made under another deployment's domain (e.g. a test build's proofs presented
+//! to a production verifier).
+
+use std::sync::RwLock;
+
+use ff::PrimeField;
+use sha2::{Digest, Sha256};
+
+use crate::{witness::CircuitKind, Scalar};
+
+/// Domain used until `set_domain_separator` is called
+pub const DEFAULT_DOMAIN: &str = "zkid-v1";
+
+static DOMAIN: RwLock<Option<String>> = RwLock::new(None);
+
+/// Set the deployment domain (e.g. `"zkid-v1-myapp-staging"`), or restore the default with `None`
+pub fn set_domain_separator(domain: Option<String>) {
+    if let Ok(mut slot) = DOMAIN.write() {
+        *slot = domain;
+    }
+}
+
+pub fn domain_separator() -> String {
+    DOMAIN
+        .read()
+        .ok()
+        .and_then(|d| d.clone())
+        .unwrap_or_else(|| DEFAULT_DOMAIN.to_string())
+}
+
+/// Public-value tag for `circuit` under the current domain, e.g. `zkid-v1/prepare`
+pub fn domain_tag(circuit: CircuitKind) -> Scalar {
+    domain_tag_for(circuit.name())
+}
+
+/// Public-value tag for a circuit outside `CircuitKind`, by name
+pub fn domain_tag_for(circuit_name: &str) -> Scalar {
+    domain_tag_under(&domain_separator(), circuit_name)
+}
+
+/// Public-value tag for a circuit under an explicit domain, e.g. one from a verifier package
+pub fn domain_tag_under(domain: &str, circuit_name: &str) -> Scalar {
+    let digest = Sha256::digest(format!("{}/{}", domain, circuit_name));
+    // 31 bytes always fit below the field modulus
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    repr.as_mut()[..31].copy_from_slice(&digest[..31]);
+    Scalar::from_repr(repr).unwrap()
+}
+
+/// Check a verified proof's domain tag, returning the public values after it
+pub fn check_domain(circuit: CircuitKind, public_values: &[Scalar]) -> Result<&[Scalar], String> {
+    check_domain_under(&domain_separator(), circuit, public_values)
+}
+
+/// `check_domain` against an explicit domain rather than the configured one
+pub fn check_domain_under<'a>(
+    domain: &str,
+    circuit: CircuitKind,
+    public_values: &'a [Scalar],
+) -> Result<&'a [Scalar], String> {
+    match public_values.split_first() {
+        Some((tag, rest)) if *tag == domain_tag_under(domain, circuit.name()) => Ok(rest),
+        Some(_) => Err(format!(
+            "{} proof was not made for domain '{}'",
+            circuit.name(),
+            domain
+        )),
+        None => Err(format!("{} proof carries no domain tag", circuit.name())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Works on explicit domains: setting the global one would change the tags
+    // every concurrently running prover test commits to
+    #[test]
+    fn test_domains_and_circuits_are_separated() {
+        let production = domain_tag_under(DEFAULT_DOMAIN, CircuitKind::Prepare.name());
+        assert_ne!(
+            production,
+            domain_tag_under(DEFAULT_DOMAIN, CircuitKind::Show.name())
+        );
+        assert!(check_domain_under(
+            DEFAULT_DOMAIN,
+            CircuitKind::Prepare,
+            &[production, Scalar::from(7)]
+        )
+        .is_ok());
+
+        assert!(
+            check_domain_under("zkid-v1-staging", CircuitKind::Prepare, &[production]).is_err()
+        );
+        assert!(check_domain_under(DEFAULT_DOMAIN, CircuitKind::Prepare, &[]).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Versioned envelope for stored proofs, instances, witnesses and shared blinds.
+//!
+//! Each artifact is written as `ZKAF || kind (u8) || version (u32 LE) || payload`,
+//! the payload being the bincode encoding for that version. Files written before
+//! the envelope existed are version 0 and carry bare bincode. When a spartan2
+//! bump changes an encoding, `CURRENT_VERSION` goes up and the previous layout
+//! keeps a decoder here, so artifacts already stored on a device stay readable
+//! and `migrate_file` can rewrite them in the current format.
+//!
+//! `PINNED_FORMATS` lists every layout this build reads and writes. Migration
+//! runs between any two of them (`migrate_file_between`), which also allows
+//! rolling an app back to the previous layout. A payload that no longer decodes,
+//! because a spartan2 bump changed the encoding without a version bump being
+//! possible, cannot be re-serialized; it is reported as `Migration::Regenerate`
+//! so the caller can prove again instead of failing on the artifact later.
+
+use std::fs;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    namespace::artifact_path,
+    sealed,
+    setup::{deserialize_bounded, write_atomic},
+};
+
+const MAGIC: &[u8; 4] = b"ZKAF";
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4;
+
+/// Version written by this build
+pub const CURRENT_VERSION: u32 = 1;
+/// Bare bincode, before artifacts were versioned
+pub const LEGACY_VERSION: u32 = 0;
+
+/// Layouts this build can read and write, oldest first
+pub const PINNED_FORMATS: &[(u32, &str)] = &[
+    (LEGACY_VERSION, "bare bincode"),
+    (1, "ZKAF envelope around the legacy bincode payload"),
+];
+
+/// Outcome of migrating one stored artifact
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Migration {
+    Missing,
+    /// Already at the target version, or not at the requested source version
+    Unchanged,
+    Rewritten,
+    /// The payload does not decode with this build; the artifact must be produced again
+    Regenerate(String),
+}
+
+/// Error unless `version` is in `PINNED_FORMATS`
+pub fn check_pinned(version: u32) -> Result<(), String> {
+    if PINNED_FORMATS.iter().any(|(pinned, _)| *pinned == version) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Artifact format version {} is not one this build reads (0..={})",
+            version, CURRENT_VERSION
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    Proof = 1,
+    Instance = 2,
+    Witness = 3,
+    SharedBlinds = 4,
+    PublicInstance = 5,
+}
+
+impl ArtifactKind {
+    fn name(self) -> &'static str {
+        match self {
+            ArtifactKind::Proof => "proof",
+            ArtifactKind::Instance => "instance",
+            ArtifactKind::Witness => "witness",
+            ArtifactKind::SharedBlinds => "shared blinds",
+            ArtifactKind::PublicInstance => "public instance",
+        }
+    }
+
+    /// Witnesses and shared blinds are sealed at rest when sealing is enabled
+    fn is_secret(self) -> bool {
+        matches!(self, ArtifactKind::Witness | ArtifactKind::SharedBlinds)
+    }
+}
+
+/// Encode `value` in the current format
+pub fn encode<T: Serialize>(kind: ArtifactKind, value: &T) -> Result<Vec<u8>, bincode::Error> {
+    encode_version(kind, value, CURRENT_VERSION)
+}
+
+/// Encode `value` in the pinned format `version`
+pub fn encode_version<T: Serialize>(
+    kind: ArtifactKind,
+    value: &T,
+    version: u32,
+) -> Result<Vec<u8>, bincode::Error> {
+    match version {
+        LEGACY_VERSION => bincode::serialize(value),
+        1 => {
+            let mut bytes = Vec::with_capacity(HEADER_LEN);
+            bytes.extend_from_slice(MAGIC);
+            bytes.push(kind as u8);
+            bytes.extend_from_slice(&version.to_le_bytes());
+            bincode::serialize_into(&mut bytes, value)?;
+            Ok(bytes)
+        }
+        _ => Err(custom(format!(
+            "cannot write {} format version {}",
+            kind.name(),
+            version
+        ))),
+    }
+}
+
+/// The format version of encoded bytes, `LEGACY_VERSION` if they carry no envelope
+pub fn version_of(bytes: &[u8]) -> u32 {
+    split_header(bytes).map_or(LEGACY_VERSION, |(_, version, _)| version)
+}
+
+/// Decode any supported version of an artifact
+pub fn decode<T: DeserializeOwned>(kind: ArtifactKind, bytes: &[u8]) -> Result<T, bincode::Error> {
+    let Some((stored_kind, version, payload)) = split_header(bytes) else {
+        return deserialize_bounded(bytes);
+    };
+    if stored_kind != kind as u8 {
+        return Err(custom(format!(
+            "expected a {} artifact, found kind {}",
+            kind.name(),
+            stored_kind
+        )));
+    }
+    match version {
+        // Version 1 wrapped the legacy bincode layout unchanged
+        1 => deserialize_bounded(payload),
+        _ => Err(custom(format!(
+            "{} format version {} is newer than this build supports ({})",
+            kind.name(),
+            version,
+            CURRENT_VERSION
+        ))),
+    }
+}
+
+/// Rewrite the artifact at `path` in the current format
+/// Returns whether the file was rewritten; missing and current files are left alone.
+/// `path` is resolved in the active artifact namespace.
+pub fn migrate_file<T: Serialize + DeserializeOwned>(
+    kind: ArtifactKind,
+    path: &str,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    match migrate_file_between::<T>(kind, path, None, CURRENT_VERSION)? {
+        Migration::Regenerate(reason) => Err(reason.into()),
+        migration => Ok(migration == Migration::Rewritten),
+    }
+}
+
+/// Rewrite the artifact at `path` from format `from` (any, if `None`) to `to`
+/// `path` is resolved in the active artifact namespace.
+pub fn migrate_file_between<T: Serialize + DeserializeOwned>(
+    kind: ArtifactKind,
+    path: &str,
+    from: Option<u32>,
+    to: u32,
+) -> Result<Migration, Box<dyn std::error::Error>> {
+    check_pinned(to)?;
+    let path = &artifact_path(path);
+    let Ok(stored) = fs::read(path) else {
+        return Ok(Migration::Missing);
+    };
+    let bytes = if kind.is_secret() {
+        sealed::open(stored)?
+    } else {
+        stored
+    };
+    let version = version_of(&bytes);
+    if version == to || from.is_some_and(|from| from != version) {
+        return Ok(Migration::Unchanged);
+    }
+
+    let value: T = match decode(kind, &bytes) {
+        Ok(value) => value,
+        Err(e) => {
+            return Ok(Migration::Regenerate(format!(
+                "{} at {} (format v{}) does not decode with this build: {}",
+                kind.name(),
+                path,
+                version,
+                e
+            )))
+        }
+    };
+    let mut encoded = encode_version(kind, &value, to)?;
+    if kind.is_secret() {
+        encoded = sealed::seal_if_enabled(encoded)?;
+    }
+    write_atomic(path, &encoded)?;
+    Ok(Migration::Rewritten)
+}
+
+fn split_header(bytes: &[u8]) -> Option<(u8, u32, &[u8])> {
+    if bytes.len() < HEADER_LEN || !bytes.starts_with(MAGIC) {
+        return None;
+    }
+    let kind = bytes[MAGIC.len()];
+    let version = u32::from_le_bytes(bytes[MAGIC.len() + 1..HEADER_LEN].try_into().unwrap());
+    Some((kind, version, &bytes[HEADER_LEN..]))
+}
+
+fn custom(message: String) -> bincode::Error {
+    Box::new(bincode::ErrorKind::Custom(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_legacy_and_current_decode() {
+        let value: Vec<u64> = vec![1, 2, 3];
+        let legacy = bincode::serialize(&value).unwrap();
+        let current = encode(ArtifactKind::SharedBlinds, &value).unwrap();
+
+        assert_eq!(version_of(&legacy), LEGACY_VERSION);
+        assert_eq!(version_of(&current), CURRENT_VERSION);
+        for bytes in [&legacy, &current] {
+            let decoded: Vec<u64> = decode(ArtifactKind::SharedBlinds, bytes).unwrap();
+            assert_eq!(decoded, value);
+        }
+        assert!(decode::<Vec<u64>>(ArtifactKind::Proof, &current).is_err());
+
+        let mut future = current.clone();
+        future[MAGIC.len() + 1] = CURRENT_VERSION as u8 + 1;
+        assert!(decode::<Vec<u64>>(ArtifactKind::SharedBlinds, &future).is_err());
+    }
+
+    #[test]
+    fn test_encode_every_pinned_version() {
+        let value: Vec<u64> = vec![4, 5];
+        for &(version, _) in PINNED_FORMATS {
+            let bytes = encode_version(ArtifactKind::Proof, &value, version).unwrap();
+            assert_eq!(version_of(&bytes), version);
+            assert_eq!(
+                decode::<Vec<u64>>(ArtifactKind::Proof, &bytes).unwrap(),
+                value
+            );
+        }
+        assert!(encode_version(ArtifactKind::Proof, &value, CURRENT_VERSION + 1).is_err());
+        assert!(check_pinned(CURRENT_VERSION + 1).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+//! Entry points for `cargo fuzz`, compiled only under `--cfg fuzzing`.
+//!
+//! Each target feeds arbitrary bytes to a parser that reads untrusted files.
+//! Returning an error is fine; panicking or aborting is a bug.
+
+use crate::{r1cs::parse_r1cs_header, setup, wtns::parse_wtns, E};
+
+pub fn fuzz_wtns(data: &[u8]) {
+    let _ = parse_wtns(data);
+}
+
+pub fn fuzz_r1cs(data: &[u8]) {
+    let _ = parse_r1cs_header(data);
+}
+
+pub fn fuzz_instance(data: &[u8]) {
+    let _ = setup::parse_instance::<E>(data);
+}
+
+pub fn fuzz_proof(data: &[u8]) {
+    let _ = setup::parse_proof::<E>(data);
+}
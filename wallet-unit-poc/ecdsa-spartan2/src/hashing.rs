@@ -0,0 +1,266 @@
+//! Poseidon hashing exactly as the circom circuits compute it.
+//!
+//! Prepare hashes the decoded JWT payload with `HashBytesToFieldWithLen` from
+//! `keyless_zk_proofs/hashtofield.circom` and uses the result as the challenge
+//! for substring matching. That is circomlib's optimized Poseidon (x^5, 8 full
+//! rounds, partial rounds by width) with its BN254-generated constants,
+//! evaluated over the circuits' secq256r1 field. No library implements that
+//! instance, so it is mirrored here; the constants are read from circomlib at
+//! build time (see `build.rs`).
+
+use std::{iter::once, sync::OnceLock};
+
+use ff::Field;
+
+use crate::{credential::PrepareParams, Scalar};
+
+#[cfg(feature = "poseidon2")]
+pub mod poseidon2;
+
+/// Full rounds of every circomlib Poseidon width
+pub const POSEIDON_FULL_ROUNDS: usize = 8;
+
+/// Partial rounds of circomlib Poseidon for widths 2 to 17
+pub const POSEIDON_PARTIAL_ROUNDS: [usize; 16] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+/// Most inputs circomlib's `Poseidon` template accepts
+pub const POSEIDON_MAX_INPUTS: usize = 16;
+
+/// Bytes packed little-endian into each field element by `HashBytesToField*`
+pub const BYTES_PER_ELEM: usize = 31;
+
+/// Most field elements `HashElemsToField` accepts
+pub const MAX_HASHED_ELEMS: usize = POSEIDON_MAX_INPUTS * POSEIDON_MAX_INPUTS;
+
+static POSEIDON_CONSTANTS: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/poseidon_constants.bin"));
+
+/// circomlib's tables for one width; `m` and `p` are row-major `t` x `t`
+struct PoseidonConstants {
+    c: Vec<Scalar>,
+    s: Vec<Scalar>,
+    m: Vec<Scalar>,
+    p: Vec<Scalar>,
+}
+
+fn constants(t: usize) -> Result<&'static PoseidonConstants, String> {
+    static TABLES: OnceLock<Result<Vec<PoseidonConstants>, String>> = OnceLock::new();
+    let tables = TABLES.get_or_init(load_constants).as_ref()?;
+    Ok(&tables[t - 2])
+}
+
+fn load_constants() -> Result<Vec<PoseidonConstants>, String> {
+    if POSEIDON_CONSTANTS.is_empty() {
+        return Err(
+            "circomlib Poseidon constants missing at build time; run `yarn` in circom/".to_string(),
+        );
+    }
+    let mut values = POSEIDON_CONSTANTS.chunks_exact(32).map(|be| {
+        let mut le = [0u8; 32];
+        le.copy_from_slice(be);
+        le.reverse();
+        Scalar::from_bytes(&le)
+            .into_option()
+            .ok_or_else(|| "Poseidon constant is not a field element".to_string())
+    });
+    let mut take = |n: usize| -> Result<Vec<Scalar>, String> {
+        let table = values.by_ref().take(n).collect::<Result<Vec<_>, _>>()?;
+        if table.len() != n {
+            return Err("truncated Poseidon constants".to_string());
+        }
+        Ok(table)
+    };
+
+    (2..=POSEIDON_MAX_INPUTS + 1)
+        .map(|t| {
+            let partial = POSEIDON_PARTIAL_ROUNDS[t - 2];
+            Ok(PoseidonConstants {
+                c: take(t * POSEIDON_FULL_ROUNDS + partial)?,
+                s: take(partial * (2 * t - 1))?,
+                m: take(t * t)?,
+                p: take(t * t)?,
+            })
+        })
+        .collect()
+}
+
+/// circomlib `Poseidon(inputs.len())`
+pub fn poseidon(inputs: &[Scalar]) -> Result<Scalar, String> {
+    if inputs.is_empty() || inputs.len() > POSEIDON_MAX_INPUTS {
+        return Err(format!(
+            "Poseidon takes 1 to {} inputs, got {}",
+            POSEIDON_MAX_INPUTS,
+            inputs.len()
+        ));
+    }
+    let t = inputs.len() + 1;
+    let k = constants(t)?;
+    let partial = POSEIDON_PARTIAL_ROUNDS[t - 2];
+    let half = POSEIDON_FULL_ROUNDS / 2;
+
+    let mut state: Vec<Scalar> = once(Scalar::ZERO).chain(inputs.iter().copied()).collect();
+    add_constants(&mut state, &k.c[..t]);
+    for r in 0..half - 1 {
+        sbox_all(&mut state);
+        add_constants(&mut state, &k.c[(r + 1) * t..]);
+        state = mix(&state, &k.m);
+    }
+    sbox_all(&mut state);
+    add_constants(&mut state, &k.c[half * t..]);
+    state = mix(&state, &k.p);
+
+    for r in 0..partial {
+        state[0] = sbox(state[0]) + k.c[(half + 1) * t + r];
+        let s = &k.s[(2 * t - 1) * r..];
+        let first: Scalar = state.iter().zip(s).map(|(x, s)| *x * s).sum();
+        let head = state[0];
+        for (x, s) in state[1..].iter_mut().zip(&s[t..]) {
+            *x += head * s;
+        }
+        state[0] = first;
+    }
+
+    for r in 0..half - 1 {
+        sbox_all(&mut state);
+        add_constants(&mut state, &k.c[(half + 1) * t + partial + r * t..]);
+        state = mix(&state, &k.m);
+    }
+    sbox_all(&mut state);
+    Ok(mix(&state, &k.m)[0])
+}
+
+fn sbox(x: Scalar) -> Scalar {
+    x.square().square() * x
+}
+
+fn sbox_all(state: &mut [Scalar]) {
+    state.iter_mut().for_each(|x| *x = sbox(*x));
+}
+
+fn add_constants(state: &mut [Scalar], c: &[Scalar]) {
+    state.iter_mut().zip(c).for_each(|(x, c)| *x += c);
+}
+
+/// circomlib `Mix`: `out[i] = sum_j M[j][i] * in[j]`
+fn mix(state: &[Scalar], matrix: &[Scalar]) -> Vec<Scalar> {
+    let t = state.len();
+    (0..t)
+        .map(|i| (0..t).map(|j| matrix[j * t + i] * state[j]).sum())
+        .collect()
+}
+
+/// `HashElemsToField`: one Poseidon up to 16 elements, else Poseidon over per-16 chunk hashes
+pub fn hash_elems_to_field(elems: &[Scalar]) -> Result<Scalar, String> {
+    match elems.len() {
+        0..=POSEIDON_MAX_INPUTS => poseidon(elems),
+        n if n <= MAX_HASHED_ELEMS => {
+            let chunk_hashes = elems
+                .chunks(POSEIDON_MAX_INPUTS)
+                .map(poseidon)
+                .collect::<Result<Vec<_>, _>>()?;
+            poseidon(&chunk_hashes)
+        }
+        n => Err(format!(
+            "cannot hash {} elements, the circuits support at most {}",
+            n, MAX_HASHED_ELEMS
+        )),
+    }
+}
+
+/// `ChunksToFieldElems(_, 31, 8)`: 31 bytes per element, little-endian
+pub fn pack_bytes(bytes: &[u8]) -> Vec<Scalar> {
+    bytes
+        .chunks(BYTES_PER_ELEM)
+        .map(|chunk| {
+            let mut le = [0u8; 32];
+            le[..chunk.len()].copy_from_slice(chunk);
+            Scalar::from_bytes(&le).unwrap()
+        })
+        .collect()
+}
+
+/// `HashBytesToField`
+pub fn hash_bytes_to_field(bytes: &[u8]) -> Result<Scalar, String> {
+    hash_elems_to_field(&pack_bytes(bytes))
+}
+
+/// `HashBytesToFieldWithLen`: the packed bytes followed by `len`
+pub fn hash_bytes_to_field_with_len(bytes: &[u8], len: u64) -> Result<Scalar, String> {
+    let elems: Vec<Scalar> = pack_bytes(bytes)
+        .into_iter()
+        .chain(once(Scalar::from(len)))
+        .collect();
+    hash_elems_to_field(&elems)
+}
+
+/// `payloadHash` of the Prepare circuit for a decoded JWT payload
+/// The circuit zero-pads the payload to its maximum decoded length and hashes
+/// that length, not the payload's own.
+pub fn payload_hash(payload: &[u8], params: &PrepareParams) -> Result<Scalar, String> {
+    let max_payload_length = params.max_b64_payload_length * 3 / 4;
+    if payload.len() > max_payload_length {
+        return Err(format!(
+            "payload of {} bytes exceeds the circuit limit of {}",
+            payload.len(),
+            max_payload_length
+        ));
+    }
+    let mut padded = payload.to_vec();
+    padded.resize(max_payload_length, 0);
+    hash_bytes_to_field_with_len(&padded, max_payload_length as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, path::Path};
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        utils::{convert_bigint_to_scalar, decode_base64, parse_byte},
+        witness::{generate_witness_bigints, CircuitKind},
+    };
+
+    #[test]
+    fn test_pack_bytes_is_little_endian() {
+        let packed = pack_bytes(&[1u8; 32]);
+        assert_eq!(packed.len(), 2);
+        assert_eq!(packed[1], Scalar::ONE);
+        assert_eq!(pack_bytes(&[0, 1])[0], Scalar::from(256u64));
+    }
+
+    #[test]
+    #[ignore = "needs the circomlib Poseidon constants and the compiled Prepare witness"]
+    fn test_payload_hash_matches_prepare_witness() {
+        let input = Path::new(env!("CARGO_MANIFEST_DIR")).join("../circom/inputs/jwt/default.json");
+        assert!(
+            !POSEIDON_CONSTANTS.is_empty(),
+            "circomlib Poseidon constants were not generated"
+        );
+        let json: Value = serde_json::from_reader(File::open(input).unwrap()).unwrap();
+        let length = json["messageLength"].as_u64().unwrap() as usize;
+        let message: Vec<u8> = json["message"].as_array().unwrap()[..length]
+            .iter()
+            .map(|b| parse_byte(b).unwrap())
+            .collect();
+        let b64_payload = std::str::from_utf8(&message)
+            .unwrap()
+            .split('.')
+            .nth(1)
+            .unwrap();
+        let payload = decode_base64(b64_payload).unwrap();
+
+        let expected = payload_hash(&payload, &PrepareParams::default()).unwrap();
+        let witness = convert_bigint_to_scalar(
+            generate_witness_bigints(CircuitKind::Prepare, &json).unwrap(),
+        )
+        .unwrap();
+        assert!(
+            witness.contains(&expected),
+            "payloadHash not found in the Prepare witness"
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! Poseidon2 over the circuits' field, for circuit versions not compiled yet.
+//!
+//! No deployed circuit uses it. Width 3 with x^7 (5 divides p - 1 for this
+//! field, so x^5 is not a permutation), 8 full and 57 partial rounds, the
+//! width-3 matrices from the Poseidon2 paper, and round constants from the
+//! Grain LFSR of the Poseidon reference implementation. Freeze these before a
+//! circuit is compiled against them.
+
+use std::sync::OnceLock;
+
+use ff::{Field, PrimeField};
+
+use crate::Scalar;
+
+pub const WIDTH: usize = 3;
+pub const SBOX_DEGREE: u64 = 7;
+pub const FULL_ROUNDS: usize = 8;
+pub const PARTIAL_ROUNDS: usize = 57;
+
+/// Round constants, `WIDTH` per round; partial rounds use only the first
+fn round_constants() -> &'static [[Scalar; WIDTH]] {
+    static CONSTANTS: OnceLock<Vec<[Scalar; WIDTH]>> = OnceLock::new();
+    CONSTANTS.get_or_init(|| {
+        let mut grain = Grain::new(
+            Scalar::NUM_BITS as u16,
+            WIDTH as u16,
+            FULL_ROUNDS as u16,
+            PARTIAL_ROUNDS as u16,
+        );
+        (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|_| std::array::from_fn(|_| grain.field_element()))
+            .collect()
+    })
+}
+
+/// The Poseidon2 permutation
+pub fn permute(state: &mut [Scalar; WIDTH]) {
+    let constants = round_constants();
+    let half = FULL_ROUNDS / 2;
+
+    external_mix(state);
+    for rc in &constants[..half] {
+        full_round(state, rc);
+    }
+    for rc in &constants[half..half + PARTIAL_ROUNDS] {
+        state[0] = sbox(state[0] + rc[0]);
+        internal_mix(state);
+    }
+    for rc in &constants[half + PARTIAL_ROUNDS..] {
+        full_round(state, rc);
+    }
+}
+
+/// 2-to-1 compression: first element of the permuted `[left, right, 0]`
+pub fn compress(left: Scalar, right: Scalar) -> Scalar {
+    let mut state = [left, right, Scalar::ZERO];
+    permute(&mut state);
+    state[0]
+}
+
+/// Rate-2 sponge over `inputs`, with the input length in the capacity element
+pub fn hash(inputs: &[Scalar]) -> Scalar {
+    let mut state = [
+        Scalar::ZERO,
+        Scalar::ZERO,
+        Scalar::from(inputs.len() as u64),
+    ];
+    for block in inputs.chunks(WIDTH - 1) {
+        state.iter_mut().zip(block).for_each(|(x, v)| *x += v);
+        permute(&mut state);
+    }
+    if inputs.is_empty() {
+        permute(&mut state);
+    }
+    state[0]
+}
+
+fn sbox(x: Scalar) -> Scalar {
+    x.pow_vartime([SBOX_DEGREE])
+}
+
+fn full_round(state: &mut [Scalar; WIDTH], rc: &[Scalar; WIDTH]) {
+    for (x, c) in state.iter_mut().zip(rc) {
+        *x = sbox(*x + c);
+    }
+    external_mix(state);
+}
+
+/// circ(2, 1, 1)
+fn external_mix(state: &mut [Scalar; WIDTH]) {
+    let sum: Scalar = state.iter().sum();
+    state.iter_mut().for_each(|x| *x += sum);
+}
+
+/// The all-ones matrix plus diag(1, 1, 2)
+fn internal_mix(state: &mut [Scalar; WIDTH]) {
+    let sum: Scalar = state.iter().sum();
+    state[0] += sum;
+    state[1] += sum;
+    state[2] = state[2].double() + sum;
+}
+
+/// Grain LFSR from the Poseidon reference parameter scripts
+struct Grain {
+    bits: Vec<bool>,
+}
+
+impl Grain {
+    fn new(field_bits: u16, width: u16, full_rounds: u16, partial_rounds: u16) -> Self {
+        let mut bits = Vec::with_capacity(80);
+        let mut push = |value: u64, len: usize| {
+            bits.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        push(1, 2); // prime field
+        push(0, 4); // x^alpha S-box
+        push(field_bits as u64, 12);
+        push(width as u64, 12);
+        push(full_rounds as u64, 10);
+        push(partial_rounds as u64, 10);
+        push((1 << 30) - 1, 30);
+
+        let mut grain = Self { bits };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let b = &self.bits;
+        let bit = b[62] ^ b[51] ^ b[38] ^ b[23] ^ b[13] ^ b[0];
+        self.bits.remove(0);
+        self.bits.push(bit);
+        bit
+    }
+
+    /// Output bit: each pair of steps yields the second only if the first is set
+    fn next_bit(&mut self) -> bool {
+        loop {
+            if self.step() {
+                return self.step();
+            }
+            self.step();
+        }
+    }
+
+    /// `len` bits, most significant first, as a big-endian 32-byte integer
+    fn next_int(&mut self, len: usize) -> [u8; 32] {
+        let mut be = [0u8; 32];
+        for i in 256 - len..256 {
+            if self.next_bit() {
+                be[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        be
+    }
+
+    /// Rejection-sampled field element
+    fn field_element(&mut self) -> Scalar {
+        loop {
+            let mut le = self.next_int(Scalar::NUM_BITS as usize);
+            le.reverse();
+            if let Some(value) = Scalar::from_bytes(&le).into_option() {
+                return value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grain_matches_reference_constants() {
+        // First round constant of the reference BN254 instance (x^5, t = 3, R_F = 8, R_P = 57)
+        let mut grain = Grain::new(254, 3, 8, 57);
+        let first = grain.next_int(254);
+        assert_eq!(first[..8], [0x0e, 0xe9, 0xa5, 0x92, 0xba, 0x9a, 0x95, 0x18]);
+        assert_eq!(first[31], 0x6e);
+    }
+
+    #[test]
+    fn test_compress_depends_on_order() {
+        let (a, b) = (Scalar::from(1u64), Scalar::from(2u64));
+        assert_eq!(compress(a, b), compress(a, b));
+        assert_ne!(compress(a, b), compress(b, a));
+        assert_ne!(hash(&[a]), hash(&[a, Scalar::ZERO]));
+    }
+}
@@ -0,0 +1,586 @@
+//! Structure and size breakdown of stored proofs.
+//!
+//! spartan2 does not expose the fields of `R1CSSNARK`, so the proof is walked
+//! through its `Serialize` implementation instead. Every struct field, sequence
+//! and enum becomes a `SizeNode` carrying the bytes it takes in the stored
+//! (fixint bincode) encoding, which makes size regressions attributable to a
+//! component and shows where a serialization mismatch starts. Sumcheck rounds
+//! and commitments are then read off the tree by type and field name.
+
+use std::fmt;
+
+use serde::{
+    ser::{
+        SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize, Serializer,
+};
+
+use crate::{
+    build_info::SPARTAN2_REV,
+    format::{self, CURRENT_VERSION},
+    setup::parse_proof,
+    E,
+};
+
+/// Bincode length prefix of sequences, maps, strings and byte arrays
+pub(crate) const LEN_PREFIX: u64 = 8;
+/// Bincode enum variant index
+pub(crate) const VARIANT_TAG: u64 = 4;
+
+/// One component of a serialized value
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeNode {
+    /// Field name, or `[i]` for the representative element of a sequence
+    pub label: String,
+    /// Rust type (struct or enum) name, where serde reports one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    pub bytes: u64,
+    /// Element count of sequences and maps
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub len: Option<u64>,
+    /// Struct fields, or the first element of a sequence whose elements have structure
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SizeNode>,
+}
+
+/// Sumcheck instance found in a proof
+#[derive(Debug, Clone, Serialize)]
+pub struct SumcheckSummary {
+    pub path: String,
+    pub rounds: u64,
+}
+
+/// Commitment field found in a proof
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitmentSummary {
+    pub path: String,
+    /// Group elements in the commitment (rows, for Hyrax)
+    pub count: u64,
+    pub bytes: u64,
+}
+
+/// Everything `inspect_proof_bytes` reports
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofInspection {
+    /// Format version of the envelope, `LEGACY_VERSION` for bare bincode
+    pub format_version: u32,
+    /// Version this build writes
+    pub current_format_version: u32,
+    pub spartan2_rev: String,
+    /// Size of the stored bytes, envelope included
+    pub total_bytes: u64,
+    pub sumchecks: Vec<SumcheckSummary>,
+    pub commitments: Vec<CommitmentSummary>,
+    pub root: SizeNode,
+}
+
+/// Decode a stored proof and break it down by component
+pub fn inspect_proof_bytes(bytes: &[u8]) -> Result<ProofInspection, bincode::Error> {
+    let proof = parse_proof::<E>(bytes)?;
+    let root = measure("proof", &proof)?;
+
+    let mut sumchecks = Vec::new();
+    let mut commitments = Vec::new();
+    collect(&root, "", &mut sumchecks, &mut commitments);
+
+    Ok(ProofInspection {
+        format_version: format::version_of(bytes),
+        current_format_version: CURRENT_VERSION,
+        spartan2_rev: SPARTAN2_REV.to_string(),
+        total_bytes: bytes.len() as u64,
+        sumchecks,
+        commitments,
+        root,
+    })
+}
+
+/// Size tree of `value` in the fixint bincode encoding
+pub fn measure<T: Serialize + ?Sized>(label: &str, value: &T) -> Result<SizeNode, bincode::Error> {
+    let mut node = value.serialize(Measure)?;
+    node.label = label.to_string();
+    Ok(node)
+}
+
+fn collect(
+    node: &SizeNode,
+    parent: &str,
+    sumchecks: &mut Vec<SumcheckSummary>,
+    commitments: &mut Vec<CommitmentSummary>,
+) {
+    let path = if parent.is_empty() {
+        node.label.clone()
+    } else {
+        format!("{}.{}", parent, node.label)
+    };
+
+    // Only the outermost `comm*` field counts; Hyrax nests a `comm` vector inside
+    if node.label.starts_with("comm") {
+        commitments.push(CommitmentSummary {
+            path,
+            count: first_len(node).unwrap_or(1),
+            bytes: node.bytes,
+        });
+        return;
+    }
+    if node
+        .type_name
+        .as_deref()
+        .is_some_and(|name| name.contains("Sumcheck"))
+    {
+        if let Some(rounds) = first_len(node) {
+            sumchecks.push(SumcheckSummary {
+                path: path.clone(),
+                rounds,
+            });
+        }
+    }
+    for child in &node.children {
+        collect(child, &path, sumchecks, commitments);
+    }
+}
+
+/// Length of `node` or of the first sequence below it
+fn first_len(node: &SizeNode) -> Option<u64> {
+    node.len
+        .or_else(|| node.children.iter().find_map(first_len))
+}
+
+impl SizeNode {
+    fn leaf(bytes: u64) -> Self {
+        Self {
+            label: String::new(),
+            type_name: None,
+            bytes,
+            len: None,
+            children: Vec::new(),
+        }
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.label, indent = depth * 2)?;
+        if let Some(name) = &self.type_name {
+            write!(f, " ({})", name)?;
+        }
+        write!(f, ": {} bytes", self.bytes)?;
+        if let Some(len) = self.len {
+            write!(f, ", {} items", len)?;
+        }
+        writeln!(f)?;
+        self.children
+            .iter()
+            .try_for_each(|child| child.write_tree(f, depth + 1))
+    }
+}
+
+impl fmt::Display for ProofInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Proof: {} bytes, format v{} (this build writes v{}), spartan2 {}",
+            self.total_bytes, self.format_version, self.current_format_version, self.spartan2_rev
+        )?;
+        writeln!(f, "Sumchecks:")?;
+        for sumcheck in &self.sumchecks {
+            writeln!(f, "  {}: {} rounds", sumcheck.path, sumcheck.rounds)?;
+        }
+        writeln!(f, "Commitments:")?;
+        for commitment in &self.commitments {
+            writeln!(
+                f,
+                "  {}: {} elements, {} bytes",
+                commitment.path, commitment.count, commitment.bytes
+            )?;
+        }
+        writeln!(f, "Layout:")?;
+        self.root.write_tree(f, 1)
+    }
+}
+
+/// Serializer producing a `SizeNode` instead of bytes
+struct Measure;
+
+/// How a compound value's parts are kept
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    /// Struct or variant fields, each kept as a child
+    Fields,
+    /// Length-prefixed sequence or map; one element is kept as a representative
+    Seq,
+    /// Fixed-size tuple or array, e.g. the bytes of a field element; folded like `Seq`
+    Tuple,
+}
+
+/// Parts of a compound value being measured
+struct Compound {
+    shape: Shape,
+    type_name: Option<String>,
+    /// Framing bytes: length prefix or variant tag
+    overhead: u64,
+    bytes: u64,
+    count: u64,
+    children: Vec<SizeNode>,
+}
+
+impl Compound {
+    fn new(shape: Shape, type_name: Option<String>, overhead: u64) -> Self {
+        Self {
+            shape,
+            type_name,
+            overhead,
+            bytes: 0,
+            count: 0,
+            children: Vec::new(),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(
+        &mut self,
+        label: &str,
+        value: &T,
+    ) -> Result<(), bincode::Error> {
+        let node = measure(label, value)?;
+        self.bytes += node.bytes;
+        let is_leaf = node.children.is_empty() && node.type_name.is_none() && node.len.is_none();
+        match self.shape {
+            Shape::Fields => self.children.push(node),
+            // One element shows the layout; the rest repeat it
+            Shape::Seq | Shape::Tuple => {
+                if self.children.is_empty() && !is_leaf {
+                    self.children.push(node);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> SizeNode {
+        SizeNode {
+            label: String::new(),
+            type_name: self.type_name,
+            bytes: self.overhead + self.bytes,
+            len: (self.shape == Shape::Seq).then_some(self.count),
+            children: self.children,
+        }
+    }
+}
+
+macro_rules! fixed {
+    ($($method:ident: $ty:ty => $bytes:expr),* $(,)?) => {
+        $(fn $method(self, _: $ty) -> Result<SizeNode, bincode::Error> {
+            Ok(SizeNode::leaf($bytes))
+        })*
+    };
+}
+
+impl Serializer for Measure {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+    type SerializeSeq = Compound;
+    type SerializeTuple = Compound;
+    type SerializeTupleStruct = Compound;
+    type SerializeTupleVariant = Compound;
+    type SerializeMap = Compound;
+    type SerializeStruct = Compound;
+    type SerializeStructVariant = Compound;
+
+    fixed! {
+        serialize_bool: bool => 1,
+        serialize_i8: i8 => 1,
+        serialize_i16: i16 => 2,
+        serialize_i32: i32 => 4,
+        serialize_i64: i64 => 8,
+        serialize_i128: i128 => 16,
+        serialize_u8: u8 => 1,
+        serialize_u16: u16 => 2,
+        serialize_u32: u32 => 4,
+        serialize_u64: u64 => 8,
+        serialize_u128: u128 => 16,
+        serialize_f32: f32 => 4,
+        serialize_f64: f64 => 8,
+    }
+
+    fn serialize_char(self, v: char) -> Result<SizeNode, bincode::Error> {
+        Ok(SizeNode::leaf(v.len_utf8() as u64))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<SizeNode, bincode::Error> {
+        Ok(SizeNode::leaf(LEN_PREFIX + v.len() as u64))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<SizeNode, bincode::Error> {
+        Ok(SizeNode::leaf(LEN_PREFIX + v.len() as u64))
+    }
+
+    fn serialize_none(self) -> Result<SizeNode, bincode::Error> {
+        Ok(SizeNode::leaf(1))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<SizeNode, bincode::Error> {
+        let mut node = value.serialize(Measure)?;
+        node.bytes += 1;
+        Ok(node)
+    }
+
+    fn serialize_unit(self) -> Result<SizeNode, bincode::Error> {
+        Ok(SizeNode::leaf(0))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<SizeNode, bincode::Error> {
+        let mut node = SizeNode::leaf(0);
+        node.type_name = Some(name.to_string());
+        Ok(node)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<SizeNode, bincode::Error> {
+        let mut node = SizeNode::leaf(VARIANT_TAG);
+        node.type_name = Some(format!("{}::{}", name, variant));
+        Ok(node)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<SizeNode, bincode::Error> {
+        let mut node = value.serialize(Measure)?;
+        node.type_name.get_or_insert_with(|| name.to_string());
+        Ok(node)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<SizeNode, bincode::Error> {
+        let mut node = value.serialize(Measure)?;
+        node.bytes += VARIANT_TAG;
+        node.type_name = Some(format!("{}::{}", name, variant));
+        Ok(node)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(Shape::Seq, None, LEN_PREFIX))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(Shape::Tuple, None, 0))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(Shape::Fields, Some(name.to_string()), 0))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(
+            Shape::Fields,
+            Some(format!("{}::{}", name, variant)),
+            VARIANT_TAG,
+        ))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(Shape::Seq, None, LEN_PREFIX))
+    }
+
+    fn serialize_struct(self, name: &'static str, _: usize) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(Shape::Fields, Some(name.to_string()), 0))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound, bincode::Error> {
+        Ok(Compound::new(
+            Shape::Fields,
+            Some(format!("{}::{}", name, variant)),
+            VARIANT_TAG,
+        ))
+    }
+}
+
+impl SerializeSeq for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), bincode::Error> {
+        let label = format!("[{}]", self.count);
+        self.count += 1;
+        self.push(&label, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTuple for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(
+        &mut self,
+        value: &T,
+    ) -> Result<(), bincode::Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleStruct for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), bincode::Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleVariant for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), bincode::Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeMap for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), bincode::Error> {
+        let label = format!("[{}].key", self.count);
+        self.push(&label, key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), bincode::Error> {
+        let label = format!("[{}]", self.count);
+        self.count += 1;
+        self.push(&label, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStruct for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), bincode::Error> {
+        self.count += 1;
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStructVariant for Compound {
+    type Ok = SizeNode;
+    type Error = bincode::Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), bincode::Error> {
+        SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<SizeNode, bincode::Error> {
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Round {
+        coeffs: Vec<u64>,
+    }
+
+    #[derive(Serialize)]
+    struct FakeSumcheckProof {
+        rounds: Vec<Round>,
+    }
+
+    #[allow(non_snake_case)]
+    #[derive(Serialize)]
+    struct FakeProof {
+        comm_W: Vec<[u8; 33]>,
+        sc_proof: FakeSumcheckProof,
+        eval: Option<u64>,
+        label: String,
+    }
+
+    #[test]
+    fn test_measure_matches_bincode_and_finds_components() {
+        let proof = FakeProof {
+            comm_W: vec![[7; 33]; 4],
+            sc_proof: FakeSumcheckProof {
+                rounds: (0..5).map(|i| Round { coeffs: vec![i; 3] }).collect(),
+            },
+            eval: Some(9),
+            label: "abc".to_string(),
+        };
+        let root = measure("proof", &proof).unwrap();
+        assert_eq!(root.bytes, bincode::serialized_size(&proof).unwrap());
+        assert_eq!(root.children.len(), 4);
+
+        let mut sumchecks = Vec::new();
+        let mut commitments = Vec::new();
+        collect(&root, "", &mut sumchecks, &mut commitments);
+        assert_eq!(sumchecks.len(), 1);
+        assert_eq!(sumchecks[0].path, "proof.sc_proof");
+        assert_eq!(sumchecks[0].rounds, 5);
+        assert_eq!(commitments.len(), 1);
+        assert_eq!(commitments[0].count, 4);
+        assert_eq!(commitments[0].bytes, 8 + 4 * 33);
+    }
+}
@@ -0,0 +1,229 @@
+//! Issuer keys a credential may be signed with, and which one a proof used.
+//!
+//! Issuers rotate their signing keys, so a wallet holds an `IssuerKeySet`: each
+//! key with the `kid` the issuer puts in the JWT header and the window in which
+//! it signs. `select_issuer_key` picks the key a credential was signed with.
+//!
+//! So that verifiers can tell which key a proof was made against, the issuer
+//! key's coordinates are the last Prepare public values:
+//!
+//! ```text
+//! domain tag || issued-at || expiry || issuer key X || issuer key Y
+//! ```
+//!
+//! `PrepareCircuit` constrains them to `pubKeyX` / `pubKeyY`, the signals the
+//! circuit checks the signature under, so a proof cannot name a key other than
+//! the one that signed the credential. They are zero when the input has no
+//! key. Exposing the coordinates changed the Prepare shape, so keys from before
+//! must be set up again.
+
+use ff::Field;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    credential::verify_issuer_signature,
+    ct::be_bytes_to_scalar,
+    signature::{input_public_key, pubkey_coordinates},
+    utils::decode_base64,
+    validity::ProofValidity,
+    Scalar,
+};
+
+#[cfg(feature = "test-issuer")]
+pub mod demo_inputs;
+#[cfg(feature = "test-issuer")]
+pub mod mint;
+
+/// Prepare input fields holding the issuer key
+pub const ISSUER_KEY_FIELDS: [&str; 2] = ["pubKeyX", "pubKeyY"];
+
+/// One issuer signing key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuerKey {
+    /// `kid` the issuer sets in the JWT header for this key
+    pub kid: String,
+    /// 65-byte uncompressed SEC1 or 64-byte `X || Y`
+    pub public_key: Vec<u8>,
+    /// Unix seconds from which the key signs, 0 for no lower bound
+    pub not_before: u64,
+    /// Unix seconds after which the key no longer signs, 0 for no upper bound
+    pub not_after: u64,
+}
+
+impl IssuerKey {
+    pub fn is_valid_at(&self, now: u64) -> bool {
+        now >= self.not_before && (self.not_after == 0 || now <= self.not_after)
+    }
+
+    /// The Prepare public values identifying this key
+    pub fn coordinates(&self) -> Result<[Scalar; 2], String> {
+        issuer_key_coordinates(&self.public_key)
+    }
+}
+
+/// The keys of one issuer, current and retired
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuerKeySet {
+    keys: Vec<IssuerKey>,
+}
+
+impl IssuerKeySet {
+    /// Fails if a key is malformed or a `kid` is used twice
+    pub fn new(keys: Vec<IssuerKey>) -> Result<Self, String> {
+        for (i, key) in keys.iter().enumerate() {
+            pubkey_coordinates(&key.public_key).map_err(|e| format!("key '{}': {}", key.kid, e))?;
+            if keys[..i].iter().any(|k| k.kid == key.kid) {
+                return Err(format!("kid '{}' is used by two keys", key.kid));
+            }
+        }
+        Ok(Self { keys })
+    }
+
+    pub fn keys(&self) -> &[IssuerKey] {
+        &self.keys
+    }
+
+    /// Keys that may have signed a JWT naming `kid` at time `now`
+    /// A JWT without a `kid` may have been signed by any key valid at `now`.
+    pub fn candidates(&self, kid: Option<&str>, now: u64) -> Vec<&IssuerKey> {
+        self.keys
+            .iter()
+            .filter(|key| (kid.is_none() || kid == Some(key.kid.as_str())) && key.is_valid_at(now))
+            .collect()
+    }
+
+    /// The key whose coordinates a verified Prepare proof carries
+    pub fn identify(&self, coordinates: &[Scalar; 2]) -> Option<&IssuerKey> {
+        self.keys
+            .iter()
+            .find(|key| key.coordinates().ok().as_ref() == Some(coordinates))
+    }
+}
+
+/// The key in `keys` that signed `sd_jwt`, among those its header allows at `now`
+pub fn select_issuer_key<'a>(
+    sd_jwt: &str,
+    keys: &'a IssuerKeySet,
+    now: u64,
+) -> Result<&'a IssuerKey, String> {
+    let jwt = sd_jwt.split('~').next().unwrap_or_default();
+    let segments: Vec<&str> = jwt.split('.').collect();
+    let [b64_header, b64_payload, b64_signature] = segments[..] else {
+        return Err("credential is not a compact JWT".to_string());
+    };
+    let header: Value = decode_base64(b64_header)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or("malformed JWT header")?;
+    let kid = header.get("kid").and_then(Value::as_str);
+
+    let candidates = keys.candidates(kid, now);
+    if candidates.is_empty() {
+        return Err(match kid {
+            Some(kid) => format!("no issuer key '{}' is valid at {}", kid, now),
+            None => format!("no issuer key is valid at {}", now),
+        });
+    }
+    let signed = format!("{}.{}", b64_header, b64_payload);
+    let signature = decode_base64(b64_signature).map_err(|_| "malformed JWT signature")?;
+    candidates
+        .into_iter()
+        .find(|key| verify_issuer_signature(signed.as_bytes(), &signature, &key.public_key).is_ok())
+        .ok_or_else(|| "no candidate issuer key verifies the credential signature".to_string())
+}
+
+/// Coordinates of a P-256 key, 65-byte uncompressed SEC1 or 64-byte `X || Y`
+pub fn issuer_key_coordinates(public_key: &[u8]) -> Result<[Scalar; 2], String> {
+    let coordinates = pubkey_coordinates(public_key)?;
+    let scalar = |bytes: &[u8]| {
+        be_bytes_to_scalar(bytes)
+            .ok_or_else(|| "issuer key coordinate exceeds the field".to_string())
+    };
+    Ok([scalar(&coordinates[..32])?, scalar(&coordinates[32..])?])
+}
+
+/// The Prepare circuit's public values after the domain tag
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreparePublicValues {
+    pub validity: ProofValidity,
+    /// `issuer_key_coordinates` of the key the credential was signed with
+    pub issuer_key: [Scalar; 2],
+}
+
+impl PreparePublicValues {
+    /// Read the values from a Prepare input, zero where a field is missing
+    pub fn from_input(input: &Value) -> Self {
+        Self {
+            validity: ProofValidity::from_input(input),
+            issuer_key: input_public_key(input, ISSUER_KEY_FIELDS)
+                .and_then(|key| issuer_key_coordinates(&key))
+                .unwrap_or([Scalar::ZERO; 2]),
+        }
+    }
+
+    pub fn to_public_values(&self) -> Vec<Scalar> {
+        let mut values = self.validity.to_public_values();
+        values.extend(self.issuer_key);
+        values
+    }
+
+    /// Recover the values from a verified proof's public values, minus the domain tag
+    pub fn from_public_values(values: &[Scalar]) -> Result<Self, String> {
+        let [validity @ .., x, y] = values else {
+            return Err(format!(
+                "expected Prepare public values, got {}",
+                values.len()
+            ));
+        };
+        Ok(Self {
+            validity: ProofValidity::from_public_values(validity)?,
+            issuer_key: [*x, *y],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(kid: &str, byte: u8, not_before: u64, not_after: u64) -> IssuerKey {
+        IssuerKey {
+            kid: kid.to_string(),
+            public_key: [vec![0x04], vec![byte; 64]].concat(),
+            not_before,
+            not_after,
+        }
+    }
+
+    #[test]
+    fn test_candidates_follow_kid_and_validity() {
+        let keys =
+            IssuerKeySet::new(vec![key("2024", 1, 0, 1_000), key("2025", 2, 900, 0)]).unwrap();
+        let kids = |kid, now| -> Vec<String> {
+            keys.candidates(kid, now)
+                .iter()
+                .map(|k| k.kid.clone())
+                .collect()
+        };
+        assert_eq!(kids(None, 950), ["2024", "2025"]);
+        assert_eq!(kids(Some("2025"), 950), ["2025"]);
+        assert!(kids(Some("2024"), 2_000).is_empty());
+        assert!(IssuerKeySet::new(vec![key("a", 1, 0, 0), key("a", 2, 0, 0)]).is_err());
+
+        let coordinates = keys.keys()[1].coordinates().unwrap();
+        assert_eq!(keys.identify(&coordinates).unwrap().kid, "2025");
+    }
+
+    #[test]
+    fn test_public_values_roundtrip() {
+        let values = PreparePublicValues {
+            validity: ProofValidity::starting_at(1_700_000_000, 60),
+            issuer_key: issuer_key_coordinates(&[3; 64]).unwrap(),
+        };
+        let recovered =
+            PreparePublicValues::from_public_values(&values.to_public_values()).unwrap();
+        assert_eq!(recovered, values);
+        assert!(PreparePublicValues::from_public_values(&[]).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+//! Fresh but repeatable circuit inputs for benchmarks and demos.
+//!
+//! Proving the same `default.json` every run measures a witness the caches
+//! have already seen. `generate_demo_inputs` instead mints a credential for a
+//! holder drawn from `seed` (a name and an adult `roc_birthday`) with
+//! `mint_sd_jwt`, and builds the Prepare input for the selected JWT variant or
+//! the Show input proving `age >= 18` on `DEMO_DATE`, signed by the derived
+//! device key over a nonce drawn from the same seed. Equal seeds give equal
+//! inputs, and the Prepare and Show inputs of one seed belong together.
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    credential::AGE_CLAIM_NAME,
+    issuer::mint::{mint_sd_jwt, MintOptions, MintedCredential, TEST_ISSUER_KEY},
+    predicate::{compile_predicate, CalendarDate, ClaimPredicate, AGE_CLAIM},
+    signature::apply_device_signature,
+    variant::jwt_variant,
+    witness::CircuitKind,
+};
+
+/// Date the Show input proves the age on, as in `circom/inputs/show/default.json`
+pub const DEMO_DATE: CalendarDate = CalendarDate {
+    year: 2025,
+    month: 1,
+    day: 1,
+};
+
+const FIRST_NAMES: [&str; 8] = [
+    "Alice", "Bob", "Chen", "Dana", "Emeka", "Farah", "Goro", "Hana",
+];
+const LAST_NAMES: [&str; 8] = [
+    "Doe", "Lin", "Okafor", "Silva", "Tanaka", "Weber", "Nguyen", "Haddad",
+];
+
+/// 32 bytes derived from `seed` for `purpose`
+fn derive(seed: u64, purpose: &str) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"demo-inputs")
+        .chain_update(seed.to_le_bytes())
+        .chain_update(purpose.as_bytes())
+        .finalize()
+        .into()
+}
+
+/// One of `n` values, drawn from `seed` for `purpose`
+fn draw(seed: u64, purpose: &str, n: usize) -> usize {
+    (u64::from_le_bytes(derive(seed, purpose)[..8].try_into().unwrap()) % n as u64) as usize
+}
+
+/// The credential of the holder drawn from `seed`
+pub fn demo_credential(seed: u64) -> Result<MintedCredential, String> {
+    let name = format!(
+        "{} {}",
+        FIRST_NAMES[draw(seed, "first", FIRST_NAMES.len())],
+        LAST_NAMES[draw(seed, "last", LAST_NAMES.len())]
+    );
+    // ROC years 40-89 are 1951-2000, so the holder is an adult on `DEMO_DATE`
+    let birthday = format!(
+        "{:03}{:02}{:02}",
+        40 + draw(seed, "year", 50),
+        1 + draw(seed, "month", 12),
+        1 + draw(seed, "day", 28)
+    );
+    let options = MintOptions {
+        claims: vec![
+            ("name".to_string(), name),
+            (AGE_CLAIM_NAME.to_string(), birthday),
+        ],
+        seed,
+        ..MintOptions::default()
+    };
+    mint_sd_jwt(&TEST_ISSUER_KEY, &options)
+}
+
+/// Input JSON for `circuit` over the credential and nonce drawn from `seed`
+pub fn generate_demo_inputs(seed: u64, circuit: CircuitKind) -> Result<String, String> {
+    let credential = demo_credential(seed)?;
+    let prepare_input = credential.prepare_input(&jwt_variant().params())?;
+    let input = match circuit {
+        CircuitKind::Prepare => prepare_input,
+        CircuitKind::Show => demo_show_input(seed, &credential, &prepare_input)?,
+    };
+    serde_json::to_string_pretty(&input).map_err(|e| e.to_string())
+}
+
+fn demo_show_input(
+    seed: u64,
+    credential: &MintedCredential,
+    prepare_input: &Value,
+) -> Result<Value, String> {
+    let mut show_input = json!({});
+    let predicate = ClaimPredicate::Range {
+        claim: AGE_CLAIM.to_string(),
+        min: Some(18),
+        max: None,
+    };
+    compile_predicate(&predicate, prepare_input, &mut show_input, DEMO_DATE)
+        .map_err(|e| e.to_string())?;
+
+    let nonce = &derive(seed, "nonce")[..16];
+    let signature = credential.sign_challenge(nonce)?;
+    apply_device_signature(
+        &mut show_input,
+        nonce,
+        &signature,
+        &credential.device_public_key,
+    )?;
+    Ok(show_input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::parse_show_inputs;
+
+    #[test]
+    fn test_demo_inputs_are_repeatable_per_seed() {
+        let prepare = generate_demo_inputs(7, CircuitKind::Prepare).unwrap();
+        assert_eq!(
+            prepare,
+            generate_demo_inputs(7, CircuitKind::Prepare).unwrap()
+        );
+        assert_ne!(
+            prepare,
+            generate_demo_inputs(8, CircuitKind::Prepare).unwrap()
+        );
+
+        let show = generate_demo_inputs(7, CircuitKind::Show).unwrap();
+        assert_eq!(show, generate_demo_inputs(7, CircuitKind::Show).unwrap());
+        let show: Value = serde_json::from_str(&show).unwrap();
+        assert!(parse_show_inputs(&show).is_ok());
+        assert_eq!(show["currentYear"], "2025");
+    }
+}
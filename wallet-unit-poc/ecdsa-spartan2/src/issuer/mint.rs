@@ -0,0 +1,242 @@
+//! Test credentials signed inside the crate.
+//!
+//! `mint_sd_jwt` plays the issuer of `circom/src/mock-vc-generator.ts`: it
+//! signs an ES256 `vc+sd-jwt` with a provided P-256 key and returns
+//! `jwt~disclosure~...` in exactly the layout `build_prepare_input` reads, with
+//! the device key as `cnf.jwk` and the disclosure digests in
+//! `vc.credentialSubject._sd`. Salts, the nonce and the default device key are
+//! derived from `MintOptions::seed`, and ES256 signing is RFC 6979, so the same
+//! options always mint the same credential. For tests and demos only.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use p256::ecdsa::{signature::Signer, Signature, SigningKey};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    claims::hash_claims,
+    credential::{build_prepare_input, PrepareParams, AGE_CLAIM_NAME},
+};
+
+/// Private key of `key-1` in `circom/keys.json`
+pub const TEST_ISSUER_KEY: [u8; 32] = [
+    0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+    0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef,
+];
+
+/// What to put in a minted credential
+#[derive(Debug, Clone)]
+pub struct MintOptions {
+    /// Disclosed claims as `(name, value)`, in disclosure order
+    pub claims: Vec<(String, String)>,
+    pub kid: String,
+    pub issuer: String,
+    pub subject: String,
+    /// `nbf`, Unix seconds
+    pub not_before: u64,
+    /// `exp`, Unix seconds
+    pub expires_at: u64,
+    /// Device public key as 64-byte `X || Y`; derived from `seed` when `None`
+    pub device_key: Option<Vec<u8>>,
+    /// Source of the salts, the nonce and the derived device key
+    pub seed: u64,
+}
+
+impl Default for MintOptions {
+    fn default() -> Self {
+        Self {
+            claims: vec![
+                ("name".to_string(), "John Doe".to_string()),
+                (AGE_CLAIM_NAME.to_string(), "1040605".to_string()),
+            ],
+            kid: "key-1".to_string(),
+            issuer: "https://issuer.example".to_string(),
+            subject: "did:example:holder".to_string(),
+            not_before: 1_700_000_000,
+            expires_at: 1_700_003_600,
+            device_key: None,
+            seed: 0,
+        }
+    }
+}
+
+/// A minted credential and the keys needed to use it
+#[derive(Debug, Clone)]
+pub struct MintedCredential {
+    /// `jwt~disclosure~...`
+    pub sd_jwt: String,
+    /// Base64url disclosures, in the order of `MintOptions::claims`
+    pub disclosures: Vec<String>,
+    /// Issuer public key as 64-byte `X || Y`
+    pub issuer_public_key: Vec<u8>,
+    /// Device public key as 64-byte `X || Y`
+    pub device_public_key: Vec<u8>,
+    /// Device private key when it was derived from the seed
+    pub device_private_key: Option<[u8; 32]>,
+}
+
+impl MintedCredential {
+    /// Prepare circuit input for this credential
+    pub fn prepare_input(&self, params: &PrepareParams) -> Result<Value, String> {
+        build_prepare_input(&self.sd_jwt, &self.issuer_public_key, params)
+    }
+
+    /// Sign `challenge` with the derived device key, as the wallet's hardware would
+    /// Returns the 64-byte `r || s` ECDSA-SHA256 signature.
+    pub fn sign_challenge(&self, challenge: &[u8]) -> Result<Vec<u8>, String> {
+        let key = self
+            .device_private_key
+            .ok_or("device key was provided, not derived")?;
+        let key = SigningKey::from_slice(&key).map_err(|_| "invalid device private key")?;
+        let signature: Signature = key.sign(challenge);
+        Ok(signature.to_bytes().to_vec())
+    }
+}
+
+/// `X || Y` of a signing key's public point
+fn public_key_bytes(key: &SigningKey) -> Vec<u8> {
+    key.verifying_key().to_encoded_point(false).as_bytes()[1..].to_vec()
+}
+
+/// 32 bytes derived from `seed` for `purpose`
+fn derive(seed: u64, purpose: &str, index: usize) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"mint")
+        .chain_update(seed.to_le_bytes())
+        .chain_update(purpose.as_bytes())
+        .chain_update((index as u64).to_le_bytes())
+        .finalize()
+        .into()
+}
+
+/// Derive a device signing key from `seed`
+fn device_signing_key(seed: u64) -> SigningKey {
+    (0..)
+        .find_map(|attempt| SigningKey::from_slice(&derive(seed, "device", attempt)).ok())
+        .expect("a derived scalar is a valid key")
+}
+
+/// Sign a test SD-JWT with the P-256 private key `issuer_key`
+pub fn mint_sd_jwt(issuer_key: &[u8], options: &MintOptions) -> Result<MintedCredential, String> {
+    let signing_key =
+        SigningKey::from_slice(issuer_key).map_err(|_| "invalid issuer private key")?;
+    if !options
+        .claims
+        .iter()
+        .any(|(name, _)| name == AGE_CLAIM_NAME)
+    {
+        return Err(format!("claims must include '{}'", AGE_CLAIM_NAME));
+    }
+
+    let (device_public_key, device_private_key) = match &options.device_key {
+        Some(key) if key.len() == 64 => (key.clone(), None),
+        Some(_) => return Err("device key must be 64-byte X || Y".to_string()),
+        None => {
+            let key = device_signing_key(options.seed);
+            (public_key_bytes(&key), Some(key.to_bytes().into()))
+        }
+    };
+
+    let disclosures: Vec<String> = options
+        .claims
+        .iter()
+        .enumerate()
+        .map(|(i, (name, value))| {
+            let salt = URL_SAFE_NO_PAD.encode(&derive(options.seed, "salt", i)[..16]);
+            URL_SAFE_NO_PAD.encode(json!([salt, name, value]).to_string())
+        })
+        .collect();
+
+    let header = json!({
+        "kid": options.kid,
+        "typ": "vc+sd-jwt",
+        "alg": "ES256",
+    });
+    let payload = json!({
+        "sub": options.subject,
+        "nbf": options.not_before,
+        "iss": options.issuer,
+        "cnf": {
+            "jwk": {
+                "kty": "EC",
+                "crv": "P-256",
+                "x": URL_SAFE_NO_PAD.encode(&device_public_key[..32]),
+                "y": URL_SAFE_NO_PAD.encode(&device_public_key[32..]),
+            }
+        },
+        "exp": options.expires_at,
+        "vc": {
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "type": ["VerifiableCredential", "MockCredential"],
+            "credentialSubject": {
+                "_sd": hash_claims(&disclosures),
+                "_sd_alg": "sha-256",
+            }
+        },
+        "nonce": URL_SAFE_NO_PAD.encode(&derive(options.seed, "nonce", 0)[..16]),
+    });
+
+    let signed = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(payload.to_string())
+    );
+    let signature: Signature = signing_key.sign(signed.as_bytes());
+    let jwt = format!(
+        "{}.{}",
+        signed,
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    );
+
+    Ok(MintedCredential {
+        sd_jwt: [jwt]
+            .into_iter()
+            .chain(disclosures.clone())
+            .collect::<Vec<_>>()
+            .join("~"),
+        disclosures,
+        issuer_public_key: public_key_bytes(&signing_key),
+        device_public_key,
+        device_private_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        credential::credential_id,
+        issuer::{issuer_key_coordinates, PreparePublicValues},
+    };
+
+    #[test]
+    fn test_minted_credential_builds_prepare_input() {
+        let minted = mint_sd_jwt(&TEST_ISSUER_KEY, &MintOptions::default()).unwrap();
+        let input = minted.prepare_input(&PrepareParams::default()).unwrap();
+        assert_eq!(input["ageClaimIndex"], 3);
+        assert_eq!(
+            issuer_key_coordinates(&minted.issuer_public_key).unwrap(),
+            PreparePublicValues::from_input(&input).issuer_key
+        );
+
+        // Deterministic per seed
+        let again = mint_sd_jwt(&TEST_ISSUER_KEY, &MintOptions::default()).unwrap();
+        assert_eq!(credential_id(&again.sd_jwt), credential_id(&minted.sd_jwt));
+        let other = MintOptions {
+            seed: 1,
+            ..MintOptions::default()
+        };
+        let other = mint_sd_jwt(&TEST_ISSUER_KEY, &other).unwrap();
+        assert_ne!(other.device_public_key, minted.device_public_key);
+    }
+
+    #[test]
+    fn test_mint_requires_age_claim() {
+        let options = MintOptions {
+            claims: vec![("name".to_string(), "John Doe".to_string())],
+            ..MintOptions::default()
+        };
+        assert!(mint_sd_jwt(&TEST_ISSUER_KEY, &options).is_err());
+        assert!(mint_sd_jwt(&[0u8; 32], &MintOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,234 @@
+//! Key packages: Prepare and Show keys generated on a desktop, installed on phones.
+//!
+//! Setup is too slow and memory-hungry to run on a phone, and each device
+//! running it would end up with keys no verifier knows. `export_key_package`
+//! bundles the stored keys with the digests needed to check them on arrival:
+//!
+//! ```text
+//! ZKKP || package version (u32 LE) || bincode(KeyPackage)
+//! ```
+//!
+//! `import_key_package` accepts a package only if its versions and transcript
+//! hash match this build, every key hashes to its recorded digest, each circuit's R1CS digest
+//! matches the one this build was compiled with (when both are known), and the
+//! verifying keys decode. Nothing is written unless the whole package passes.
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    build_info::{r1cs_digest, KEY_FORMAT_VERSION, SPARTAN2_REV},
+    setup::{
+        deserialize_bounded, write_atomic, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY,
+        SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
+    },
+    transcript::{record_transcript_hash, TRANSCRIPT_HASH},
+    variant::{key_path, stored_key_path},
+    vectors::sha256_hex,
+    witness::CircuitKind,
+    VerifyingKey,
+};
+
+const MAGIC: &[u8; 4] = b"ZKKP";
+
+/// Where `export-keys` writes the package by default
+pub const DEFAULT_KEY_PACKAGE: &str = "keys/key_package.bin";
+
+/// Layout version of the package itself
+pub const KEY_PACKAGE_VERSION: u32 = 2;
+
+/// Digest `build_info` reports for circuits not built with this crate
+const UNKNOWN_DIGEST: &str = "unknown";
+
+/// Keys for one circuit
+#[derive(Serialize, Deserialize)]
+pub struct PackagedKeys {
+    pub circuit: String,
+    /// SHA-256 of the `.r1cs` the keys were generated from
+    pub r1cs_sha256: String,
+    pub proving_key_sha256: String,
+    pub verifying_key_sha256: String,
+    pub proving_key: Vec<u8>,
+    pub verifying_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct KeyPackage {
+    /// `KEY_FORMAT_VERSION` of the build that wrote the keys
+    pub key_format_version: u32,
+    /// spartan2 revision that generated the keys
+    pub spartan2_rev: String,
+    pub circuits: Vec<PackagedKeys>,
+    /// Fiat-Shamir transcript hash the keys were set up with
+    pub transcript_hash: String,
+}
+
+/// Paths of the keys of `circuit`, through `key_path` or `stored_key_path`
+fn key_paths(circuit: CircuitKind, resolve: fn(&str) -> String) -> (String, String) {
+    let (pk_path, vk_path) = match circuit {
+        CircuitKind::Prepare => (PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY),
+        CircuitKind::Show => (SHOW_PROVING_KEY, SHOW_VERIFYING_KEY),
+    };
+    (resolve(pk_path), resolve(vk_path))
+}
+
+/// Package the stored keys of `circuits`
+pub fn export_key_package(circuits: &[CircuitKind]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let circuits = circuits
+        .iter()
+        .map(|&circuit| {
+            let (pk_path, vk_path) = key_paths(circuit, stored_key_path);
+            let proving_key =
+                fs::read(&pk_path).map_err(|e| format!("Failed to read '{}': {}", pk_path, e))?;
+            let verifying_key =
+                fs::read(&vk_path).map_err(|e| format!("Failed to read '{}': {}", vk_path, e))?;
+            Ok(PackagedKeys {
+                circuit: circuit.name().to_string(),
+                r1cs_sha256: r1cs_digest(circuit).to_string(),
+                proving_key_sha256: sha256_hex(&proving_key),
+                verifying_key_sha256: sha256_hex(&verifying_key),
+                proving_key,
+                verifying_key,
+            })
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+    let package = KeyPackage {
+        key_format_version: KEY_FORMAT_VERSION,
+        spartan2_rev: SPARTAN2_REV.to_string(),
+        circuits,
+        transcript_hash: TRANSCRIPT_HASH.to_string(),
+    };
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&KEY_PACKAGE_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, &package)?;
+    Ok(bytes)
+}
+
+/// Validate a package and install its keys, returning the paths written
+pub fn import_key_package(bytes: &[u8]) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let package = parse_key_package(bytes)?;
+    let mut installs = Vec::with_capacity(package.circuits.len());
+    for keys in &package.circuits {
+        installs.push((validate_keys(keys)?, keys));
+    }
+
+    let mut written = Vec::new();
+    for (circuit, keys) in installs {
+        let (pk_path, vk_path) = key_paths(circuit, key_path);
+        if let Some(parent) = std::path::Path::new(&pk_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&pk_path, &keys.proving_key)?;
+        write_atomic(&vk_path, &keys.verifying_key)?;
+        record_transcript_hash(&vk_path)?;
+        info!("Installed {} keys from key package", circuit.name());
+        written.extend([pk_path, vk_path]);
+    }
+    Ok(written)
+}
+
+/// Decode a package, checking its header and versions
+pub fn parse_key_package(bytes: &[u8]) -> Result<KeyPackage, String> {
+    let header_len = MAGIC.len() + 4;
+    if bytes.len() < header_len || !bytes.starts_with(MAGIC) {
+        return Err("Not a key package".to_string());
+    }
+    let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len].try_into().unwrap());
+    if version != KEY_PACKAGE_VERSION {
+        return Err(format!(
+            "Key package version {} is not supported (expected {})",
+            version, KEY_PACKAGE_VERSION
+        ));
+    }
+    let package: KeyPackage = deserialize_bounded(&bytes[header_len..])
+        .map_err(|e| format!("Malformed key package: {}", e))?;
+    if package.key_format_version != KEY_FORMAT_VERSION {
+        return Err(format!(
+            "Keys are in format version {}, this build reads {}",
+            package.key_format_version, KEY_FORMAT_VERSION
+        ));
+    }
+    if package.transcript_hash != TRANSCRIPT_HASH {
+        return Err(format!(
+            "Keys were set up with a {} transcript, this build uses {}",
+            package.transcript_hash, TRANSCRIPT_HASH
+        ));
+    }
+    if package.circuits.is_empty() {
+        return Err("Key package holds no keys".to_string());
+    }
+    Ok(package)
+}
+
+fn validate_keys(keys: &PackagedKeys) -> Result<CircuitKind, String> {
+    let circuit: CircuitKind = keys.circuit.parse()?;
+    if sha256_hex(&keys.proving_key) != keys.proving_key_sha256 {
+        return Err(format!("{} proving key digest mismatch", circuit.name()));
+    }
+    if sha256_hex(&keys.verifying_key) != keys.verifying_key_sha256 {
+        return Err(format!("{} verifying key digest mismatch", circuit.name()));
+    }
+
+    let expected = r1cs_digest(circuit);
+    if expected != UNKNOWN_DIGEST
+        && keys.r1cs_sha256 != UNKNOWN_DIGEST
+        && keys.r1cs_sha256 != expected
+    {
+        return Err(format!(
+            "{} keys were generated for R1CS {}, this build uses {}",
+            circuit.name(),
+            keys.r1cs_sha256,
+            expected
+        ));
+    }
+
+    deserialize_bounded::<VerifyingKey>(&keys.verifying_key)
+        .map_err(|e| format!("{} verifying key does not decode: {}", circuit.name(), e))?;
+    Ok(circuit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package_bytes(package: &KeyPackage) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&KEY_PACKAGE_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(package).unwrap());
+        bytes
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_mismatched_packages() {
+        let mut package = KeyPackage {
+            key_format_version: KEY_FORMAT_VERSION,
+            spartan2_rev: SPARTAN2_REV.to_string(),
+            circuits: vec![PackagedKeys {
+                circuit: "show".to_string(),
+                r1cs_sha256: r1cs_digest(CircuitKind::Show).to_string(),
+                proving_key_sha256: sha256_hex(b"pk"),
+                verifying_key_sha256: sha256_hex(b"vk"),
+                proving_key: b"pk".to_vec(),
+                verifying_key: b"vk".to_vec(),
+            }],
+            transcript_hash: TRANSCRIPT_HASH.to_string(),
+        };
+        assert!(parse_key_package(&package_bytes(&package)).is_ok());
+
+        package.circuits[0].proving_key[0] ^= 1;
+        let err = import_key_package(&package_bytes(&package)).unwrap_err();
+        assert_eq!(err.to_string(), "show proving key digest mismatch");
+
+        package.transcript_hash = "blake3".to_string();
+        assert!(parse_key_package(&package_bytes(&package)).is_err());
+
+        package.transcript_hash = TRANSCRIPT_HASH.to_string();
+        package.key_format_version += 1;
+        assert!(parse_key_package(&package_bytes(&package)).is_err());
+        assert!(parse_key_package(b"ZKAF\x01\x00\x00\x00").is_err());
+    }
+}
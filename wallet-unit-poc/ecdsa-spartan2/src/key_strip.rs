@@ -0,0 +1,116 @@
+//! Compact proving keys for bundling with mobile apps.
+//!
+//! `save_keys` writes keys as fixed-width bincode, every length and integer
+//! taking 8 bytes. `strip_proving_key` re-encodes a stored proving key with
+//! variable-width integers behind a magic:
+//!
+//! ```text
+//! ZKPC || bincode-varint(ProverKey)
+//! ```
+//!
+//! keeping whichever encoding is smaller, and `load_proving_key` reads either.
+//! spartan2's `ProverKey` exposes no fields a phone could do without, so
+//! nothing is dropped: the saving comes from the encoding alone. Key packages
+//! record the digest of whichever encoding they were exported with.
+
+use std::fs;
+
+use bincode::Options;
+use serde::{de::DeserializeOwned, Serialize};
+use spartan2::{
+    traits::{snark::R1CSSNARKTrait, Engine},
+    zk_spartan::R1CSSNARK,
+};
+use tracing::info;
+
+use crate::{
+    setup::{deserialize_bounded, write_atomic},
+    variant::{key_path, stored_key_path},
+};
+
+const MAGIC: &[u8; 4] = b"ZKPC";
+
+/// Sizes of a stored proving key in both encodings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripReport {
+    /// Fixed-width encoding, as written by `save_keys`
+    pub full_bytes: u64,
+    /// Size of the key as stored after stripping
+    pub stripped_bytes: u64,
+}
+
+fn compact_options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_varint_encoding()
+        .allow_trailing_bytes()
+}
+
+/// Size `value` would take in the compact encoding, magic included
+pub fn compact_size<T: Serialize>(value: &T) -> Result<u64, bincode::Error> {
+    Ok(MAGIC.len() as u64 + compact_options().serialized_size(value)?)
+}
+
+/// Decode a proving key stored in either encoding
+pub fn decode_proving_key<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, bincode::Error> {
+    match bytes.strip_prefix(MAGIC) {
+        Some(payload) => compact_options()
+            .with_limit(payload.len() as u64)
+            .deserialize(payload),
+        None => deserialize_bounded(bytes),
+    }
+}
+
+/// Rewrite the proving key at `pk_path` in the compact encoding if that is smaller
+/// Stripping an already stripped key leaves it unchanged. A key shipped with
+/// the app is written stripped to the documents directory.
+pub fn strip_proving_key<E: Engine>(
+    pk_path: &str,
+) -> Result<StripReport, Box<dyn std::error::Error>> {
+    let bytes = fs::read(stored_key_path(pk_path))?;
+    let pk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey = decode_proving_key(&bytes)?;
+    let full_bytes = bincode::serialized_size(&pk)?;
+
+    let mut compact = MAGIC.to_vec();
+    compact_options().serialize_into(&mut compact, &pk)?;
+    if compact.len() < bytes.len() {
+        let pk_path = key_path(pk_path);
+        if let Some(parent) = std::path::Path::new(&pk_path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&pk_path, &compact)?;
+        info!(
+            "Stripped proving key {}: {} -> {} bytes",
+            pk_path,
+            bytes.len(),
+            compact.len()
+        );
+    }
+
+    Ok(StripReport {
+        full_bytes,
+        stripped_bytes: compact.len().min(bytes.len()) as u64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Value = (Vec<u64>, String);
+
+    #[test]
+    fn test_compact_encoding_round_trips_and_shrinks() {
+        let value: Value = ((0..100).collect(), "key".to_string());
+        let full = bincode::serialize(&value).unwrap();
+        let mut compact = MAGIC.to_vec();
+        compact_options()
+            .serialize_into(&mut compact, &value)
+            .unwrap();
+
+        assert_eq!(compact_size(&value).unwrap(), compact.len() as u64);
+        assert!(compact.len() < full.len());
+        assert_eq!(decode_proving_key::<Value>(&compact).unwrap(), value);
+        assert_eq!(decode_proving_key::<Value>(&full).unwrap(), value);
+        assert!(decode_proving_key::<Value>(&compact[..compact.len() - 1]).is_err());
+    }
+}
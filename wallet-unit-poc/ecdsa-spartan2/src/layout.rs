@@ -0,0 +1,160 @@
+//! Explicit directories in place of the process working directory.
+//!
+//! Circuits read their compiled files from `../circom` and artifacts live under
+//! `keys/`, both relative to the working directory, so callers change into the
+//! documents directory around every operation. That breaks down on a phone,
+//! where what ships with the app (compiled circuits, keys set up ahead of time)
+//! sits in a read-only bundle or Android assets apart from the writable
+//! documents directory. An `ArtifactLayout` names both roots: while one is
+//! installed, compiled circuits and the development inputs resolve against
+//! `assets_dir`, keys are loaded from `assets_dir/keys` unless set up or
+//! imported into `documents_dir` since, and everything saved, together with
+//! proofs, witnesses and relative input paths, resolves against `documents_dir`.
+//! Without one, everything resolves against the working directory as before,
+//! except that `set_assets_dir` can still name the read-only root for every
+//! operation.
+
+use std::{
+    env::current_dir,
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+/// Where shipped artifacts are read from and everything else written to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactLayout {
+    /// The `circom` directory: `build/` with the compiled circuits, `inputs/`
+    /// with the development inputs and optionally `keys/` with bundled keys;
+    /// only ever read
+    pub assets_dir: PathBuf,
+    /// Keys, proofs, witnesses and credential inputs; must be writable
+    pub documents_dir: PathBuf,
+}
+
+static LAYOUT: RwLock<Option<ArtifactLayout>> = RwLock::new(None);
+static ASSETS_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+impl ArtifactLayout {
+    /// `path` inside `documents_dir`; an absolute `path` is returned unchanged
+    pub fn resolve(&self, path: &str) -> String {
+        self.documents_dir
+            .join(Path::new(path))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Stored artifact `path` to load: the copy in `documents_dir` if there is
+    /// one, else the one shipped in `assets_dir`
+    pub fn resolve_stored(&self, path: &str) -> String {
+        let written = self.resolve(path);
+        if Path::new(&written).exists() {
+            written
+        } else {
+            self.assets_dir.join(path).to_string_lossy().into_owned()
+        }
+    }
+}
+
+/// Install `layout`, or with `None` go back to resolving against the working directory
+pub fn set_artifact_layout(layout: Option<ArtifactLayout>) {
+    if let Ok(mut slot) = LAYOUT.write() {
+        *slot = layout;
+    }
+}
+
+pub fn artifact_layout() -> Option<ArtifactLayout> {
+    LAYOUT.read().ok().and_then(|layout| layout.clone())
+}
+
+/// Read shipped circuits and keys from `dir` when no layout is installed, or
+/// with `None` from `../circom` and the working directory
+pub fn set_assets_dir(dir: Option<PathBuf>) {
+    if let Ok(mut slot) = ASSETS_DIR.write() {
+        *slot = dir;
+    }
+}
+
+pub fn assets_dir() -> Option<PathBuf> {
+    ASSETS_DIR.read().ok().and_then(|dir| dir.clone())
+}
+
+/// Layout without an explicit documents directory: the working directory
+/// with the assets root set by `set_assets_dir`
+fn default_layout() -> Option<ArtifactLayout> {
+    Some(ArtifactLayout {
+        assets_dir: assets_dir()?,
+        documents_dir: PathBuf::new(),
+    })
+}
+
+/// Directory that relative artifact and input paths resolve against
+pub fn documents_dir() -> PathBuf {
+    match artifact_layout() {
+        Some(layout) => layout.documents_dir,
+        None => current_dir().unwrap(),
+    }
+}
+
+/// The `circom` directory holding the compiled circuits
+pub fn circuits_dir() -> PathBuf {
+    match artifact_layout().or_else(default_layout) {
+        Some(layout) => layout.assets_dir,
+        None => current_dir().unwrap().join("../circom"),
+    }
+}
+
+/// `path` inside the documents directory of the installed layout
+/// Without a layout, or for an absolute `path`, it is returned unchanged.
+pub fn in_documents(path: &str) -> String {
+    match artifact_layout() {
+        Some(layout) => layout.resolve(path),
+        None => path.to_string(),
+    }
+}
+
+/// Stored artifact `path` to load, which may have shipped with the app
+/// Without a layout or assets root it is returned unchanged.
+pub fn in_assets(path: &str) -> String {
+    match artifact_layout().or_else(default_layout) {
+        Some(layout) => layout.resolve_stored(path),
+        None => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_resolves_into_documents_dir() {
+        let layout = ArtifactLayout {
+            assets_dir: PathBuf::from("/bundle/circom"),
+            documents_dir: PathBuf::from("/data/docs"),
+        };
+        assert_eq!(layout.resolve("keys/a.bin"), "/data/docs/keys/a.bin");
+        assert_eq!(layout.resolve("/abs/a.bin"), "/abs/a.bin");
+    }
+
+    #[test]
+    fn test_stored_artifacts_prefer_documents_over_assets() {
+        let dir = std::env::temp_dir().join(format!("layout_{}", std::process::id()));
+        let layout = ArtifactLayout {
+            assets_dir: dir.join("assets"),
+            documents_dir: dir.join("documents"),
+        };
+        let bundled = dir.join("assets/keys/a.bin");
+        assert_eq!(
+            layout.resolve_stored("keys/a.bin"),
+            bundled.to_string_lossy()
+        );
+
+        let written = dir.join("documents/keys/a.bin");
+        std::fs::create_dir_all(written.parent().unwrap()).unwrap();
+        std::fs::write(&written, b"key").unwrap();
+        assert_eq!(
+            layout.resolve_stored("keys/a.bin"),
+            written.to_string_lossy()
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -6,29 +6,146 @@
 //!
 //! The circuits use Spartan2's ZK-SNARK protocol with Hyrax polynomial commitment scheme.
 
-use spartan2::{provider::T256HyraxEngine, traits::Engine};
+use spartan2::{
+    traits::{snark::R1CSSNARKTrait, Engine},
+    zk_spartan::R1CSSNARK,
+};
+
+pub use spartan2;
 
-pub type E = T256HyraxEngine;
+/// Engine the circuits are compiled for and the FFI proves with
+/// The prover, setup and serialization code is generic over `Engine`; this picks
+/// the backend, `T256HyraxEngine` unless another is selected by feature, with
+/// the transcript hash `sha256-transcript` selects (see `transcript`).
+#[cfg(not(feature = "p256-hyrax"))]
+pub type BaseEngine = spartan2::provider::T256HyraxEngine;
+#[cfg(feature = "p256-hyrax")]
+pub type BaseEngine = spartan2::provider::P256HyraxEngine;
+#[cfg(not(feature = "sha256-transcript"))]
+pub type E = BaseEngine;
+#[cfg(feature = "sha256-transcript")]
+pub type E = transcript::Sha256Engine<BaseEngine>;
 pub type Scalar = <E as Engine>::Scalar;
+pub type ProvingKey = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey;
+pub type VerifyingKey = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey;
+
+/// Second engine for comparison benchmarks over `SyntheticCircuit`
+#[cfg(feature = "pallas-hyrax")]
+pub type PallasE = spartan2::provider::PallasHyraxEngine;
 
+pub mod artifact_patch;
+pub mod asset_source;
+pub mod binfile;
+pub mod build_info;
 pub mod circuits;
+pub mod claims;
+pub mod constraint_check;
+pub mod credential;
+pub mod ct;
+pub mod domain;
+pub mod format;
+pub mod hashing;
+#[cfg(fuzzing)]
+pub mod fuzz;
+pub mod inspect;
+pub mod issuer;
+pub mod key_package;
+pub mod key_strip;
+pub mod layout;
+pub mod mem_profile;
+pub mod membership;
+pub mod midstate;
+pub mod namespace;
+pub mod precommit;
+pub mod predicate;
 pub mod prover;
+pub mod pseudonym;
+pub mod public_io;
+pub mod r1cs;
+pub mod r1cs_arena;
+pub mod range;
+pub mod sealed;
+pub mod secret;
 pub mod setup;
+pub mod setup_timing;
+pub mod show;
+pub mod signature;
+pub mod signpost;
+pub mod transcript;
 pub mod utils;
+pub mod validity;
+pub mod variant;
+pub mod verifier_package;
+pub mod verifier_spec;
+pub mod vectors;
+pub mod witness;
+pub mod witness_cache;
+pub mod witness_debug;
+pub mod witness_source;
+pub mod witness_stream;
+pub mod wtns;
 
 // Re-export commonly used types and functions
-pub use circuits::{prepare_circuit::PrepareCircuit, show_circuit::ShowCircuit};
+pub use artifact_patch::{apply_artifact_patch, diff_artifact};
+pub use asset_source::{set_asset_source, AssetSource};
+#[cfg(feature = "ecdsa")]
+pub use circuits::ecdsa_circuit::ECDSACircuit;
+#[cfg(feature = "membership")]
+pub use circuits::membership_circuit::MembershipCircuit;
+#[cfg(feature = "sha-midstate")]
+pub use circuits::prepare_partial_circuit::PreparePartialCircuit;
+#[cfg(feature = "pseudonym")]
+pub use circuits::pseudonym_circuit::PseudonymCircuit;
+#[cfg(feature = "range")]
+pub use circuits::range_circuit::RangeCircuit;
+pub use circuits::{
+    demo_circuit::DemoCircuit, permutation_circuit::PermutationCircuit,
+    prepare_circuit::PrepareCircuit, show_circuit::ShowCircuit,
+    synthetic_circuit::SyntheticCircuit,
+};
+pub use claims::{commit_claims, hash_claims};
+pub use constraint_check::{
+    set_constraint_check_enabled, take_constraint_failure, ConstraintFailure,
+};
+pub use credential::{build_prepare_input, credential_id, PrepareParams};
+pub use inspect::{inspect_proof_bytes, ProofInspection};
+pub use issuer::{select_issuer_key, IssuerKey, IssuerKeySet, PreparePublicValues};
+pub use key_package::{export_key_package, import_key_package};
+pub use key_strip::{strip_proving_key, StripReport};
+pub use layout::{set_artifact_layout, set_assets_dir, ArtifactLayout};
+pub use membership::{MerklePath, MerkleSet};
+pub use namespace::{artifact_path, set_artifact_namespace, ArtifactNamespace};
+pub use predicate::{compile_predicate, CalendarDate, ClaimPredicate, PredicateError};
 pub use prover::{
-    generate_prepare_witness, generate_shared_blinds, prove_circuit, prove_circuit_with_pk,
-    reblind, reblind_with_loaded_data, run_circuit, verify_circuit,
-    verify_circuit_with_loaded_data,
+    generate_prepare_witness, generate_shared_blinds, generate_shared_blinds_with_rng,
+    prefill_prepare_witness, prove_circuit, prove_circuit_until, prove_circuit_with_pk,
+    prove_circuit_with_pk_until, prove_options, reblind, reblind_with_loaded_data, run_circuit,
+    set_prove_options, verify_circuit, verify_circuit_with_loaded_data, ProveError, ProveOptions,
+    ReblindTimings, TimedOut,
 };
+pub use pseudonym::{derive_pseudonym, verifier_scope, PseudonymStatement};
+pub use range::{range_input, RangeStatement};
+pub use secret::{SecretBlinds, SecretScalar};
 pub use setup::{
-    load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
-    load_witness, save_keys, setup_circuit_keys, setup_circuit_keys_no_save, PREPARE_PROVING_KEY,
-    PREPARE_VERIFYING_KEY, SHOW_PROVING_KEY, SHOW_VERIFYING_KEY,
+    load_instance, load_instance_and_witness, load_proof, load_proving_key, load_public_instance,
+    load_shared_blinds, load_verifying_key, load_witness, migrate_artifacts,
+    migrate_artifacts_between, parse_instance, parse_proof, parse_public_instance, parse_witness,
+    save_keys, setup_circuit_keys, setup_circuit_keys_in_memory, setup_circuit_keys_no_save,
+    MigrationReport, PublicInstance, PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY, SHOW_PROVING_KEY,
+    SHOW_VERIFYING_KEY,
 };
+pub use setup_timing::{load_keys_timed, save_keys_timed, setup_circuit_keys_timed, SetupTimings};
 pub use utils::{
     bigint_to_scalar, calculate_jwt_output_indices, convert_bigint_to_scalar, parse_jwt_inputs,
-    parse_show_inputs,
+    parse_membership_inputs, parse_pseudonym_inputs, parse_range_inputs, parse_show_inputs,
+};
+pub use validity::ProofValidity;
+pub use variant::{jwt_variant, set_jwt_variant, JwtVariant};
+pub use verifier_package::{export_verifier_package, VerifierContext};
+pub use verifier_spec::{export_verifier_spec, VerifierSpec};
+pub use witness::{cross_check_witness, CircuitKind, CrossCheckReport};
+pub use witness_cache::{
+    clear_witness_cache, remove_cached_witness, remove_prefilled_witness, set_witness_cache_enabled,
 };
+pub use witness_debug::{export_witness_debug, DumpFormat};
+pub use witness_source::{set_witness_source, HelperProcess, WitnessSource};
@@ -18,21 +18,29 @@
 //!
 //! Every proof emitted in this sequence (including the reblinded variants) should verify successfully.
 
+use ecdsa_spartan2::witness_source::{generate_witness_in_process, write_witness_stream};
 use ecdsa_spartan2::{
-    generate_shared_blinds, load_instance, load_proof, load_shared_blinds, load_witness,
+    diff_artifact, export_key_package, export_verifier_package, format::CURRENT_VERSION,
+    generate_shared_blinds, inspect_proof_bytes, key_package::DEFAULT_KEY_PACKAGE, load_instance,
+    load_keys_timed, load_proof, load_shared_blinds, load_witness, migrate_artifacts_between,
     prove_circuit, prove_circuit_with_pk, reblind, reblind_with_loaded_data, run_circuit,
-    save_keys, setup::PREPARE_INSTANCE, setup::PREPARE_PROOF, setup::PREPARE_PROVING_KEY,
+    save_keys_timed, setup::PREPARE_INSTANCE, setup::PREPARE_PROOF, setup::PREPARE_PROVING_KEY,
     setup::PREPARE_VERIFYING_KEY, setup::PREPARE_WITNESS, setup::SHARED_BLINDS,
     setup::SHOW_INSTANCE, setup::SHOW_PROOF, setup::SHOW_PROVING_KEY, setup::SHOW_VERIFYING_KEY,
-    setup::SHOW_WITNESS, setup_circuit_keys, setup_circuit_keys_no_save, verify_circuit,
-    verify_circuit_with_loaded_data, PrepareCircuit, ShowCircuit, E,
+    setup::SHOW_WITNESS, setup_circuit_keys, setup_circuit_keys_timed, vectors::generate_vectors,
+    verifier_package::DEFAULT_VERIFIER_PACKAGE, verify_circuit, verify_circuit_with_loaded_data,
+    PrepareCircuit, ProvingKey, SetupTimings, ShowCircuit, VerifyingKey, E,
 };
-use std::{env::args, fs, path::PathBuf, process, time::Instant};
+use std::{env::args, fs, io::Read, path::PathBuf, process, time::Instant};
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
 const NUM_SHARED: usize = 1;
 
+#[cfg(feature = "mem-profile")]
+#[global_allocator]
+static ALLOC: ecdsa_spartan2::mem_profile::Alloc = ecdsa_spartan2::mem_profile::Alloc;
+
 /// Helper function to get file size in bytes
 fn get_file_size(path: &str) -> u64 {
     fs::metadata(path).map(|m| m.len()).unwrap_or(0)
@@ -40,8 +48,8 @@ fn get_file_size(path: &str) -> u64 {
 
 #[derive(Debug)]
 struct BenchmarkResults {
-    prepare_setup_ms: u128,
-    show_setup_ms: u128,
+    prepare_setup: SetupTimings,
+    show_setup: SetupTimings,
     generate_blinds_ms: u128,
     prove_prepare_ms: u128,
     reblind_prepare_ms: u128,
@@ -71,20 +79,26 @@ impl BenchmarkResults {
         }
     }
 
+    fn print_setup(label: &str, timings: &SetupTimings) {
+        println!("║ {:<23} {:>10} ms      ║", label, timings.setup_ms());
+        for (phase, ms) in [
+            ("synthesis", timings.synthesis_ms),
+            ("key generation", timings.keygen_ms),
+            ("key save", timings.save_ms),
+            ("key load", timings.load_ms),
+        ] {
+            println!("║   {:<21} {:>10} ms      ║", phase, ms);
+        }
+    }
+
     fn print_summary(&self) {
         println!("\n╔════════════════════════════════════════════════╗");
         println!("║        BENCHMARK RESULTS SUMMARY               ║");
         println!("╠════════════════════════════════════════════════╣");
         println!("║ TIMING MEASUREMENTS                            ║");
         println!("╠════════════════════════════════════════════════╣");
-        println!(
-            "║ Prepare Setup:          {:>10} ms      ║",
-            self.prepare_setup_ms
-        );
-        println!(
-            "║ Show Setup:             {:>10} ms      ║",
-            self.show_setup_ms
-        );
+        Self::print_setup("Prepare Setup:", &self.prepare_setup);
+        Self::print_setup("Show Setup:", &self.show_setup);
         println!(
             "║ Generate Blinds:        {:>10} ms      ║",
             self.generate_blinds_ms
@@ -167,11 +181,25 @@ enum CircuitAction {
     Reblind,
     GenerateSharedBlinds,
     Benchmark,
+    GenerateVectors,
+    ExportKeys,
+    ExportVerifier,
+    Inspect,
+    Witness,
+    Migrate,
+    DiffArtifact,
 }
 
 #[derive(Debug, Default, Clone)]
 struct CommandOptions {
     input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    json: bool,
+    /// Artifact format versions for `migrate`
+    from: Option<u32>,
+    to: Option<u32>,
+    /// Previous artifact version for `diff-artifact`
+    base: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -182,12 +210,6 @@ struct ParsedCommand {
 }
 
 fn main() {
-    tracing_subscriber::fmt()
-        .with_target(false)
-        .with_ansi(true)
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
     let args: Vec<String> = args().collect();
     let command_args: &[String] = if args.len() > 1 { &args[1..] } else { &[] };
 
@@ -200,10 +222,55 @@ fn main() {
         }
     };
 
+    let subscriber = tracing_subscriber::fmt()
+        .with_target(false)
+        .with_ansi(true)
+        .with_env_filter(EnvFilter::from_default_env());
+    if command.action == CircuitAction::Witness {
+        // stdout carries the witness stream
+        subscriber.with_writer(std::io::stderr).init();
+    } else {
+        subscriber.init();
+    }
+
+    #[cfg(feature = "mem-profile")]
+    {
+        let dir = std::env::var("MEM_PROFILE_DIR").unwrap_or_else(|_| "mem_profile".into());
+        if let Err(e) = ecdsa_spartan2::mem_profile::start(&dir) {
+            eprintln!("Failed to start memory profile: {}", e);
+            process::exit(1);
+        }
+    }
+
     match command.circuit {
         CircuitKind::Prepare => execute_prepare(command.action, command.options),
         CircuitKind::Show => execute_show(command.action, command.options),
     }
+
+    #[cfg(feature = "mem-profile")]
+    if let Err(e) = ecdsa_spartan2::mem_profile::stop() {
+        eprintln!("Failed to write memory profile: {}", e);
+    }
+}
+
+/// The value of `result`, or exit after printing its error after `context`
+fn exit_on_error<T, D: std::fmt::Display>(result: Result<T, D>, context: &str) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("{}: {}", context, e);
+        process::exit(1);
+    })
+}
+
+/// Save `keys`, then load them back, returning how long each took
+/// The loaded keys are dropped; the pipeline goes on with the ones from setup.
+fn save_and_load_keys(
+    pk_path: &str,
+    vk_path: &str,
+    keys: &(ProvingKey, VerifyingKey),
+) -> Result<(u128, u128), Box<dyn std::error::Error>> {
+    let save_ms = save_keys_timed::<E>(pk_path, vk_path, keys)?;
+    let (_, load_ms) = load_keys_timed::<E>(pk_path, vk_path)?;
+    Ok((save_ms, load_ms))
 }
 
 /// Run the complete benchmark pipeline for a given input file
@@ -215,40 +282,56 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     // Step 1: Setup Prepare Circuit
     info!("Step 1/9: Setting up Prepare circuit...");
     let prepare_circuit = PrepareCircuit::new(input_path.clone());
-    let t0 = Instant::now();
-    let (prepare_pk, prepare_vk) = setup_circuit_keys_no_save(prepare_circuit);
-    let prepare_setup_ms = t0.elapsed().as_millis();
-    println!("✓ Prepare setup completed: {} ms\n", prepare_setup_ms);
-
-    // Save Prepare keys after timing
-    if let Err(e) = save_keys(
-        PREPARE_PROVING_KEY,
-        PREPARE_VERIFYING_KEY,
-        &prepare_pk,
-        &prepare_vk,
-    ) {
-        eprintln!("Failed to save Prepare keys: {}", e);
-        std::process::exit(1);
-    }
+    let (prepare_keys, mut prepare_setup) =
+        setup_circuit_keys_timed::<E, _>(prepare_circuit).expect("setup failed");
+    println!(
+        "✓ Prepare setup completed: {} ms (synthesis {} ms, key generation {} ms)\n",
+        prepare_setup.setup_ms(),
+        prepare_setup.synthesis_ms,
+        prepare_setup.keygen_ms
+    );
+
+    // Save the Prepare keys and read them back, each timed apart from setup
+    (prepare_setup.save_ms, prepare_setup.load_ms) =
+        save_and_load_keys(PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY, &prepare_keys)
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to save Prepare keys: {}", e);
+                std::process::exit(1);
+            });
+    println!(
+        "✓ Prepare keys saved: {} ms, loaded: {} ms\n",
+        prepare_setup.save_ms, prepare_setup.load_ms
+    );
+    let (prepare_pk, prepare_vk) = prepare_keys;
 
     // Step 2: Setup Show Circuit
     info!("Step 2/9: Setting up Show circuit...");
     let show_circuit = ShowCircuit::new(input_path.clone());
-    let t0 = Instant::now();
-    let (show_pk, show_vk) = setup_circuit_keys_no_save(show_circuit);
-    let show_setup_ms = t0.elapsed().as_millis();
-    println!("✓ Show setup completed: {} ms\n", show_setup_ms);
-
-    // Save Show keys after timing
-    if let Err(e) = save_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, &show_pk, &show_vk) {
-        eprintln!("Failed to save Show keys: {}", e);
-        std::process::exit(1);
-    }
+    let (show_keys, mut show_setup) =
+        setup_circuit_keys_timed::<E, _>(show_circuit).expect("setup failed");
+    println!(
+        "✓ Show setup completed: {} ms (synthesis {} ms, key generation {} ms)\n",
+        show_setup.setup_ms(),
+        show_setup.synthesis_ms,
+        show_setup.keygen_ms
+    );
+
+    // Save the Show keys and read them back, each timed apart from setup
+    (show_setup.save_ms, show_setup.load_ms) =
+        save_and_load_keys(SHOW_PROVING_KEY, SHOW_VERIFYING_KEY, &show_keys).unwrap_or_else(|e| {
+            eprintln!("Failed to save Show keys: {}", e);
+            std::process::exit(1);
+        });
+    println!(
+        "✓ Show keys saved: {} ms, loaded: {} ms\n",
+        show_setup.save_ms, show_setup.load_ms
+    );
+    let (show_pk, show_vk) = show_keys;
 
     // Step 3: Generate Shared Blinds
     info!("Step 3/9: Generating shared blinds...");
     let t0 = Instant::now();
-    generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
+    generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED).expect("generate shared blinds failed");
     let generate_blinds_ms = t0.elapsed().as_millis();
     println!("✓ Shared blinds generated: {} ms\n", generate_blinds_ms);
 
@@ -264,19 +347,21 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         PREPARE_INSTANCE,
         PREPARE_WITNESS,
         PREPARE_PROOF,
-    );
+    )
+    .expect("prove prepare failed");
     let prove_prepare_ms = t0.elapsed().as_millis();
     println!("✓ Prepare proof generated: {} ms\n", prove_prepare_ms);
 
     // Step 5: Reblind Prepare
     info!("Step 5/9: Reblinding Prepare proof...");
     // Load data before timing (file I/O should not be part of reblind benchmark)
-    let prepare_instance = load_instance(PREPARE_INSTANCE).expect("load prepare instance failed");
-    let prepare_witness = load_witness(PREPARE_WITNESS).expect("load prepare witness failed");
+    let prepare_instance =
+        load_instance::<E>(PREPARE_INSTANCE).expect("load prepare instance failed");
+    let prepare_witness = load_witness::<E>(PREPARE_WITNESS).expect("load prepare witness failed");
     let shared_blinds = load_shared_blinds::<E>(SHARED_BLINDS).expect("load shared_blinds failed");
 
     let t0 = Instant::now();
-    reblind_with_loaded_data(
+    let reblind_prepare_timings = reblind_with_loaded_data(
         PrepareCircuit::default(),
         &prepare_pk,
         prepare_instance,
@@ -285,9 +370,13 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         PREPARE_INSTANCE,
         PREPARE_WITNESS,
         PREPARE_PROOF,
-    );
+    )
+    .expect("reblind prepare failed");
     let reblind_prepare_ms = t0.elapsed().as_millis();
-    println!("✓ Prepare proof reblinded: {} ms\n", reblind_prepare_ms);
+    println!(
+        "✓ Prepare proof reblinded: {} ms (recommit {} ms, prove {} ms)\n",
+        reblind_prepare_ms, reblind_prepare_timings.recommit_ms, reblind_prepare_timings.prove_ms
+    );
 
     // Step 6: Prove Show Circuit
     info!("Step 6/9: Proving Show circuit...");
@@ -299,19 +388,20 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         SHOW_INSTANCE,
         SHOW_WITNESS,
         SHOW_PROOF,
-    );
+    )
+    .expect("prove show failed");
     let prove_show_ms = t0.elapsed().as_millis();
     println!("✓ Show proof generated: {} ms\n", prove_show_ms);
 
     // Step 7: Reblind Show
     info!("Step 7/9: Reblinding Show proof...");
     // Load data before timing (file I/O should not be part of reblind benchmark)
-    let show_instance = load_instance(SHOW_INSTANCE).expect("load show instance failed");
-    let show_witness = load_witness(SHOW_WITNESS).expect("load show witness failed");
+    let show_instance = load_instance::<E>(SHOW_INSTANCE).expect("load show instance failed");
+    let show_witness = load_witness::<E>(SHOW_WITNESS).expect("load show witness failed");
     // Reuse shared_blinds from Prepare step (already loaded)
 
     let t0 = Instant::now();
-    reblind_with_loaded_data(
+    let reblind_show_timings = reblind_with_loaded_data(
         ShowCircuit::default(),
         &show_pk,
         show_instance,
@@ -320,14 +410,18 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
         SHOW_INSTANCE,
         SHOW_WITNESS,
         SHOW_PROOF,
-    );
+    )
+    .expect("reblind show failed");
     let reblind_show_ms = t0.elapsed().as_millis();
-    println!("✓ Show proof reblinded: {} ms\n", reblind_show_ms);
+    println!(
+        "✓ Show proof reblinded: {} ms (recommit {} ms, prove {} ms)\n",
+        reblind_show_ms, reblind_show_timings.recommit_ms, reblind_show_timings.prove_ms
+    );
 
     // Step 8: Verify Prepare
     info!("Step 8/9: Verifying Prepare proof...");
     // Load proof and verifying key before timing (file I/O should not be part of verify benchmark)
-    let prepare_proof = load_proof(PREPARE_PROOF).expect("load prepare proof failed");
+    let prepare_proof = load_proof::<E>(PREPARE_PROOF).expect("load prepare proof failed");
     // Reuse prepare_vk from setup step (already in memory)
 
     let t0 = Instant::now();
@@ -338,7 +432,7 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     // Step 9: Verify Show
     info!("Step 9/9: Verifying Show proof...");
     // Load proof and verifying key before timing (file I/O should not be part of verify benchmark)
-    let show_proof = load_proof(SHOW_PROOF).expect("load show proof failed");
+    let show_proof = load_proof::<E>(SHOW_PROOF).expect("load show proof failed");
     // Reuse show_vk from setup step (already in memory)
 
     let t0 = Instant::now();
@@ -358,8 +452,8 @@ fn run_complete_pipeline(input_path: Option<PathBuf>) -> BenchmarkResults {
     let show_witness_bytes = get_file_size(SHOW_WITNESS);
 
     BenchmarkResults {
-        prepare_setup_ms,
-        show_setup_ms,
+        prepare_setup,
+        show_setup,
         generate_blinds_ms,
         prove_prepare_ms,
         reblind_prepare_ms,
@@ -396,37 +490,53 @@ fn execute_prepare(action: CircuitAction, options: CommandOptions) {
         CircuitAction::Prove => {
             let circuit = PrepareCircuit::new(options.input.clone());
             info!("Proving Prepare circuit with ZK-Spartan");
-            prove_circuit(
-                circuit,
-                PREPARE_PROVING_KEY,
-                PREPARE_INSTANCE,
-                PREPARE_WITNESS,
-                PREPARE_PROOF,
+            exit_on_error(
+                prove_circuit(
+                    circuit,
+                    PREPARE_PROVING_KEY,
+                    PREPARE_INSTANCE,
+                    PREPARE_WITNESS,
+                    PREPARE_PROOF,
+                ),
+                "Failed to prove Prepare",
             );
         }
         CircuitAction::Verify => {
             info!("Verifying Prepare proof with ZK-Spartan");
-            verify_circuit(PREPARE_PROOF, PREPARE_VERIFYING_KEY);
+            verify_circuit::<E>(PREPARE_PROOF, PREPARE_VERIFYING_KEY);
         }
         CircuitAction::Reblind => {
             info!("Reblind Spartan sumcheck + Hyrax PCS Prepare");
-            reblind(
-                PrepareCircuit::default(),
-                PREPARE_PROVING_KEY,
-                PREPARE_INSTANCE,
-                PREPARE_WITNESS,
-                PREPARE_PROOF,
-                SHARED_BLINDS,
+            exit_on_error(
+                reblind(
+                    PrepareCircuit::default(),
+                    PREPARE_PROVING_KEY,
+                    PREPARE_INSTANCE,
+                    PREPARE_WITNESS,
+                    PREPARE_PROOF,
+                    SHARED_BLINDS,
+                ),
+                "Failed to reblind Prepare",
             );
         }
         CircuitAction::GenerateSharedBlinds => {
             info!("Generating shared blinds for Spartan-2 circuits");
-            generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED);
+            exit_on_error(
+                generate_shared_blinds::<E>(SHARED_BLINDS, NUM_SHARED),
+                "Failed to generate shared blinds",
+            );
         }
         CircuitAction::Benchmark => {
             let results = run_complete_pipeline(options.input);
             results.print_summary();
         }
+        CircuitAction::GenerateVectors => generate_golden_vectors(options),
+        CircuitAction::ExportKeys => export_keys(options),
+        CircuitAction::ExportVerifier => export_verifier(options),
+        CircuitAction::Migrate => migrate(options),
+        CircuitAction::DiffArtifact => diff_artifact_file(options),
+        CircuitAction::Inspect => inspect_proof_file(PREPARE_PROOF, options),
+        CircuitAction::Witness => stream_witness(ecdsa_spartan2::CircuitKind::Prepare),
     }
 }
 
@@ -445,27 +555,33 @@ fn execute_show(action: CircuitAction, options: CommandOptions) {
         CircuitAction::Prove => {
             let circuit = ShowCircuit::new(options.input.clone());
             info!("Proving Show circuit with ZK-Spartan");
-            prove_circuit(
-                circuit,
-                SHOW_PROVING_KEY,
-                SHOW_INSTANCE,
-                SHOW_WITNESS,
-                SHOW_PROOF,
+            exit_on_error(
+                prove_circuit(
+                    circuit,
+                    SHOW_PROVING_KEY,
+                    SHOW_INSTANCE,
+                    SHOW_WITNESS,
+                    SHOW_PROOF,
+                ),
+                "Failed to prove Show",
             );
         }
         CircuitAction::Verify => {
             info!("Verifying Show proof with ZK-Spartan");
-            verify_circuit(SHOW_PROOF, SHOW_VERIFYING_KEY);
+            verify_circuit::<E>(SHOW_PROOF, SHOW_VERIFYING_KEY);
         }
         CircuitAction::Reblind => {
             info!("Reblind Spartan sumcheck + Hyrax PCS Show");
-            reblind(
-                ShowCircuit::default(),
-                SHOW_PROVING_KEY,
-                SHOW_INSTANCE,
-                SHOW_WITNESS,
-                SHOW_PROOF,
-                SHARED_BLINDS,
+            exit_on_error(
+                reblind(
+                    ShowCircuit::default(),
+                    SHOW_PROVING_KEY,
+                    SHOW_INSTANCE,
+                    SHOW_WITNESS,
+                    SHOW_PROOF,
+                    SHARED_BLINDS,
+                ),
+                "Failed to reblind Show",
             );
         }
         CircuitAction::GenerateSharedBlinds => {
@@ -476,6 +592,191 @@ fn execute_show(action: CircuitAction, options: CommandOptions) {
             let results = run_complete_pipeline(options.input);
             results.print_summary();
         }
+        CircuitAction::GenerateVectors => generate_golden_vectors(options),
+        CircuitAction::ExportKeys => export_keys(options),
+        CircuitAction::ExportVerifier => export_verifier(options),
+        CircuitAction::Migrate => migrate(options),
+        CircuitAction::DiffArtifact => diff_artifact_file(options),
+        CircuitAction::Inspect => inspect_proof_file(SHOW_PROOF, options),
+        CircuitAction::Witness => stream_witness(ecdsa_spartan2::CircuitKind::Show),
+    }
+}
+
+/// Generate the witness for the input JSON on stdin and stream it to stdout
+/// Run by `HelperProcess` to keep witness generation out of the app process.
+fn stream_witness(circuit: ecdsa_spartan2::CircuitKind) {
+    let mut input = Vec::new();
+    if let Err(e) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("Failed to read input JSON from stdin: {}", e);
+        process::exit(1);
+    }
+    let witness = match generate_witness_in_process(circuit, &input) {
+        Ok(witness) => witness,
+        Err(e) => {
+            eprintln!("Failed to generate {} witness: {}", circuit.name(), e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = write_witness_stream(&witness, std::io::stdout().lock()) {
+        eprintln!("Failed to write witness: {}", e);
+        process::exit(1);
+    }
+}
+
+/// Write the Prepare and Show keys as a package for `import_key_package` on devices
+fn export_keys(options: CommandOptions) {
+    let output = options
+        .output
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_KEY_PACKAGE));
+    let circuits = [
+        ecdsa_spartan2::CircuitKind::Prepare,
+        ecdsa_spartan2::CircuitKind::Show,
+    ];
+    let package = match export_key_package(&circuits) {
+        Ok(package) => package,
+        Err(e) => {
+            eprintln!(
+                "Error: failed to package keys (run `prepare setup` and `show setup` first): {}",
+                e
+            );
+            process::exit(1);
+        }
+    };
+    if let Err(e) = fs::write(&output, &package) {
+        eprintln!("Error: failed to write {}: {}", output.display(), e);
+        process::exit(1);
+    }
+    println!(
+        "✓ Key package: {} ({})",
+        output.display(),
+        BenchmarkResults::format_size(package.len() as u64)
+    );
+}
+
+/// Write the verifying keys as a package for `VerifierContext::from_package` at relying parties
+fn export_verifier(options: CommandOptions) {
+    let output = options
+        .output
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_VERIFIER_PACKAGE));
+    let package = match export_verifier_package() {
+        Ok(package) => package,
+        Err(e) => {
+            eprintln!(
+                "Error: failed to package verifying keys (run `prepare setup` and `show setup` first): {}",
+                e
+            );
+            process::exit(1);
+        }
+    };
+    if let Err(e) = fs::write(&output, &package) {
+        eprintln!("Error: failed to write {}: {}", output.display(), e);
+        process::exit(1);
+    }
+    println!(
+        "✓ Verifier package: {} ({})",
+        output.display(),
+        BenchmarkResults::format_size(package.len() as u64)
+    );
+}
+
+/// Write a patch from `--base` to `--input` for `apply_artifact_patch` on devices
+fn diff_artifact_file(options: CommandOptions) {
+    let (Some(base), Some(input), Some(output)) = (options.base, options.input, options.output)
+    else {
+        eprintln!("Error: diff-artifact needs --base, --input and --output");
+        process::exit(1);
+    };
+    let read = |path: &PathBuf| {
+        fs::read(path).unwrap_or_else(|e| {
+            eprintln!("Error: failed to read {}: {}", path.display(), e);
+            process::exit(1);
+        })
+    };
+    let (old, new) = (read(&base), read(&input));
+    let patch = match diff_artifact(&old, &new) {
+        Ok(patch) => patch,
+        Err(e) => {
+            eprintln!("Error: failed to encode patch: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = fs::write(&output, &patch) {
+        eprintln!("Error: failed to write {}: {}", output.display(), e);
+        process::exit(1);
+    }
+    println!(
+        "✓ Artifact patch: {} ({} for a {} artifact)",
+        output.display(),
+        BenchmarkResults::format_size(patch.len() as u64),
+        BenchmarkResults::format_size(new.len() as u64)
+    );
+}
+
+/// Rewrite stored artifacts between format versions and list what must be regenerated
+fn migrate(options: CommandOptions) {
+    let to = options.to.unwrap_or(CURRENT_VERSION);
+    let report = match migrate_artifacts_between::<E>(options.from, to) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: migration failed: {}", e);
+            process::exit(1);
+        }
+    };
+    for path in &report.rewritten {
+        println!("✓ Migrated {} to format v{}", path, to);
+    }
+    if report.rewritten.is_empty() {
+        println!("Nothing to migrate");
+    }
+    for (path, reason) in &report.regenerate {
+        eprintln!("✗ {} must be regenerated: {}", path, reason);
+    }
+    if !report.regenerate.is_empty() {
+        process::exit(2);
+    }
+}
+
+/// Print the component breakdown of a stored proof; `--input` selects another proof file
+fn inspect_proof_file(default_path: &str, options: CommandOptions) {
+    let path = options.input.unwrap_or_else(|| PathBuf::from(default_path));
+    let inspection = fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| inspect_proof_bytes(&bytes).map_err(|e| e.to_string()));
+    match inspection {
+        Ok(inspection) if options.json => match serde_json::to_string_pretty(&inspection) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("Error: failed to encode inspection: {}", e);
+                process::exit(1);
+            }
+        },
+        Ok(inspection) => print!("{}", inspection),
+        Err(e) => {
+            eprintln!("Error: failed to inspect {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn generate_golden_vectors(options: CommandOptions) {
+    let output = options
+        .output
+        .unwrap_or_else(|| PathBuf::from("tests/vectors"));
+    info!(output = %output.display(), "Generating golden test vectors");
+    // --input overrides the Prepare input only; Show uses its default input
+    match generate_vectors(&output, options.input, None) {
+        Ok(manifest) => {
+            for vector in &manifest.circuits {
+                println!(
+                    "✓ {}: proof {} / vk {}",
+                    vector.name, vector.proof_sha256, vector.verifying_key_sha256
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to generate golden vectors: {}", e);
+            process::exit(1);
+        }
     }
 }
 
@@ -491,6 +792,31 @@ fn parse_command(args: &[String]) -> Result<ParsedCommand, String> {
         }
         "prepare" => parse_circuit_command(CircuitKind::Prepare, &args[1..]),
         "show" => parse_circuit_command(CircuitKind::Show, &args[1..]),
+        "gen-vectors" | "gen_vectors" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Vectors cover both circuits
+            action: CircuitAction::GenerateVectors,
+            options: parse_options(&args[1..])?,
+        }),
+        "export-keys" | "export_keys" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Packages cover both circuits
+            action: CircuitAction::ExportKeys,
+            options: parse_options(&args[1..])?,
+        }),
+        "export-verifier" | "export_verifier" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Packages cover both circuits
+            action: CircuitAction::ExportVerifier,
+            options: parse_options(&args[1..])?,
+        }),
+        "diff-artifact" | "diff_artifact" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Patches cover any artifact file
+            action: CircuitAction::DiffArtifact,
+            options: parse_options(&args[1..])?,
+        }),
+        "migrate" => Ok(ParsedCommand {
+            circuit: CircuitKind::Prepare, // Migration covers every stored artifact
+            action: CircuitAction::Migrate,
+            options: parse_options(&args[1..])?,
+        }),
         "benchmark" => Ok(ParsedCommand {
             circuit: CircuitKind::Prepare, // Benchmark runs both circuits, but we need to pick one for the enum
             action: CircuitAction::Benchmark,
@@ -563,10 +889,12 @@ fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<Parsed
         "reblind" => (CircuitAction::Reblind, 1),
         "generate_shared_blinds" => (CircuitAction::GenerateSharedBlinds, 1),
         "benchmark" => (CircuitAction::Benchmark, 1),
+        "inspect" => (CircuitAction::Inspect, 1),
+        "witness" => (CircuitAction::Witness, 1),
         s if s.starts_with('-') => (CircuitAction::Run, 0),
         other => {
             return Err(format!(
-                "Unknown action '{other}' for {:?}. Expected one of run|setup|prove|verify|reblind|generate_shared_blinds|benchmark.",
+                "Unknown action '{other}' for {:?}. Expected one of run|setup|prove|verify|reblind|generate_shared_blinds|benchmark|inspect|witness.",
                 circuit
             ))
         }
@@ -583,10 +911,17 @@ fn parse_circuit_command(circuit: CircuitKind, tail: &[String]) -> Result<Parsed
         CircuitAction::Run
         | CircuitAction::Prove
         | CircuitAction::Setup
-        | CircuitAction::Benchmark => parse_options(options_slice)?,
-        CircuitAction::Verify | CircuitAction::Reblind | CircuitAction::GenerateSharedBlinds => {
-            ensure_no_options(options_slice)?
-        }
+        | CircuitAction::Benchmark
+        | CircuitAction::GenerateVectors
+        | CircuitAction::ExportKeys
+        | CircuitAction::ExportVerifier
+        | CircuitAction::Migrate
+        | CircuitAction::DiffArtifact
+        | CircuitAction::Inspect => parse_options(options_slice)?,
+        CircuitAction::Verify
+        | CircuitAction::Reblind
+        | CircuitAction::GenerateSharedBlinds
+        | CircuitAction::Witness => ensure_no_options(options_slice)?,
     };
 
     Ok(ParsedCommand {
@@ -621,6 +956,37 @@ fn parse_options(args: &[String]) -> Result<CommandOptions, String> {
                 return Err("Missing value for --input".into());
             }
             options.input = Some(PathBuf::from(value));
+        } else if arg == "--output" || arg == "-o" {
+            index += 1;
+            let value = args
+                .get(index)
+                .ok_or_else(|| "Missing value for --output".to_string())?;
+            options.output = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--output=") {
+            if value.is_empty() {
+                return Err("Missing value for --output".into());
+            }
+            options.output = Some(PathBuf::from(value));
+        } else if arg == "--base" {
+            index += 1;
+            let value = args
+                .get(index)
+                .ok_or_else(|| "Missing value for --base".to_string())?;
+            options.base = Some(PathBuf::from(value));
+        } else if arg == "--from" || arg == "--to" {
+            index += 1;
+            let version = args
+                .get(index)
+                .ok_or_else(|| format!("Missing value for {arg}"))?
+                .parse::<u32>()
+                .map_err(|_| format!("{arg} expects a format version number"))?;
+            if arg == "--from" {
+                options.from = Some(version);
+            } else {
+                options.to = Some(version);
+            }
+        } else if arg == "--json" {
+            options.json = true;
         } else if arg == "--help" || arg == "-h" {
             print_usage();
             process::exit(0);
@@ -638,9 +1004,20 @@ fn print_usage() {
         "Usage:
   ecdsa-spartan2 <prepare|show> [run|setup|prove|verify] [options]
   ecdsa-spartan2 benchmark [options]
+  ecdsa-spartan2 gen-vectors [options]
+  ecdsa-spartan2 export-keys [--output <file>]
+  ecdsa-spartan2 export-verifier [--output <file>]
+  ecdsa-spartan2 migrate [--from <ver>] [--to <ver>]
+  ecdsa-spartan2 diff-artifact --base <old> --input <new> --output <patch>
 
 Commands:
   benchmark            Run complete pipeline with full metrics (setup, prove, reblind, verify)
+  gen-vectors          Write golden test vectors (inputs, vks, proofs, digests)
+  export-keys          Package the Prepare and Show keys for installing on devices
+  export-verifier      Package the verifying keys and public-input schema for relying parties
+  migrate              Rewrite stored proofs, instances, witnesses and blinds between format
+                       versions; exits with 2 if any artifact or key must be regenerated
+  diff-artifact        Write a binary patch from an old artifact (e.g. a key package) to a new one
   prepare <action>     Run action on Prepare circuit
   show <action>        Run action on Show circuit
 
@@ -651,15 +1028,25 @@ Actions:
   verify               Verify proof
   reblind              Reblind proof
   benchmark            Run complete benchmark pipeline
+  inspect              Print the size breakdown of the stored proof
+  witness              Read input JSON on stdin, stream the witness to stdout
 
 Options:
-  --input, -i <path>   Override the circuit input JSON (run/prove/setup/benchmark)
+  --input, -i <path>   Override the circuit input JSON (run/prove/setup/benchmark),
+                       or the proof file (inspect)
+  --json               Print inspect output as JSON
+  --from, --to <ver>   Format versions for migrate (default: any stored version, to the current)
+  --base <path>        Previous artifact version for diff-artifact
+  --output, -o <path>  Output directory for gen-vectors (default: tests/vectors),
+                       or package file for export-keys (default: keys/key_package.bin)
+                       and export-verifier (default: keys/verifier_package.bin)
 
 Examples:
   cargo run --release -- benchmark --input ../circom/inputs/jwt/generated.json
   cargo run --release -- prepare run --input ../circom/inputs/jwt/generated.json
   cargo run --release -- show prove --input ../circom/inputs/show/generated.json
   cargo run --release -- show verify
+  cargo run --release -- show inspect --json
 
 Legacy commands like `prepare`, `show`, `prove_prepare`, etc. are still supported."
     );
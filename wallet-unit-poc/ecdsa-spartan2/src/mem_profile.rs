@@ -0,0 +1,189 @@
+//! Heap profiling of the prover phases.
+//!
+//! With the `mem-profile` feature, `start` begins a dhat heap profile and
+//! `snapshot` records the live heap at each prover phase boundary and the peak
+//! reached since the previous one, so the peak can be attributed to witness
+//! storage, polynomial tables, or MSM buffers. dhat only knows the peak of the
+//! whole profile, which every phase after the largest would repeat, so `Alloc`
+//! tracks the phase peak alongside it. `stop` writes the dhat profile plus a
+//! JSON dump of the per-phase snapshots.
+//!
+//! The final binary must install `mem_profile::Alloc` as its global allocator.
+//! Without the feature every function is a no-op.
+
+#[cfg(feature = "mem-profile")]
+pub use enabled::*;
+
+#[cfg(not(feature = "mem-profile"))]
+pub fn start(_output_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Err("built without the mem-profile feature".into())
+}
+
+#[cfg(not(feature = "mem-profile"))]
+pub fn snapshot(_phase: &str) {}
+
+#[cfg(not(feature = "mem-profile"))]
+pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
+    Ok(())
+}
+
+#[cfg(feature = "mem-profile")]
+mod enabled {
+    use serde::Serialize;
+    use std::{
+        alloc::{GlobalAlloc, Layout},
+        fs::{create_dir_all, File},
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex,
+        },
+    };
+    use tracing::info;
+
+    #[derive(Debug, Clone, Serialize)]
+    pub struct PhaseSnapshot {
+        pub phase: String,
+        pub curr_bytes: usize,
+        pub curr_blocks: usize,
+        /// Peak heap since the previous snapshot, or the start of the profile
+        pub max_bytes: usize,
+        /// Peak heap of the whole profile so far
+        pub profile_max_bytes: usize,
+        pub total_bytes: u64,
+    }
+
+    /// Live heap bytes, counted by `Alloc`
+    static CURR_BYTES: AtomicUsize = AtomicUsize::new(0);
+    /// Peak of `CURR_BYTES` since the last phase boundary
+    static PHASE_MAX_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+    /// `dhat::Alloc`, also tracking the peak heap between phase boundaries
+    pub struct Alloc;
+
+    fn grew(bytes: usize) {
+        let curr = CURR_BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        PHASE_MAX_BYTES.fetch_max(curr, Ordering::Relaxed);
+    }
+
+    fn shrank(bytes: usize) {
+        CURR_BYTES.fetch_sub(bytes, Ordering::Relaxed);
+    }
+
+    /// Start a new phase, returning the peak of the one that ended
+    fn reset_phase_peak() -> usize {
+        PHASE_MAX_BYTES.swap(CURR_BYTES.load(Ordering::Relaxed), Ordering::Relaxed)
+    }
+
+    unsafe impl GlobalAlloc for Alloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = dhat::Alloc.alloc(layout);
+            if !ptr.is_null() {
+                grew(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = dhat::Alloc.alloc_zeroed(layout);
+            if !ptr.is_null() {
+                grew(layout.size());
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            dhat::Alloc.dealloc(ptr, layout);
+            shrank(layout.size());
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = dhat::Alloc.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                if new_size > layout.size() {
+                    grew(new_size - layout.size());
+                } else {
+                    shrank(layout.size() - new_size);
+                }
+            }
+            new_ptr
+        }
+    }
+
+    struct Session {
+        profiler: dhat::Profiler,
+        output_dir: PathBuf,
+        snapshots: Vec<PhaseSnapshot>,
+    }
+
+    static SESSION: Mutex<Option<Session>> = Mutex::new(None);
+
+    /// Start a heap profile writing its output under `output_dir`
+    pub fn start(output_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut session = SESSION.lock().map_err(|_| "mem-profile lock poisoned")?;
+        if session.is_some() {
+            return Err("a memory profile is already running".into());
+        }
+
+        let output_dir = PathBuf::from(output_dir);
+        create_dir_all(&output_dir)?;
+        let profiler = dhat::Profiler::builder()
+            .file_name(output_dir.join("dhat-heap.json"))
+            .build();
+
+        reset_phase_peak();
+        *session = Some(Session {
+            profiler,
+            output_dir,
+            snapshots: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Record heap usage at the end of `phase`; ignored when no profile is running
+    pub fn snapshot(phase: &str) {
+        let Ok(mut session) = SESSION.lock() else {
+            return;
+        };
+        let Some(session) = session.as_mut() else {
+            return;
+        };
+
+        let stats = dhat::HeapStats::get();
+        let max_bytes = reset_phase_peak();
+        info!(
+            phase,
+            curr_bytes = stats.curr_bytes,
+            max_bytes,
+            profile_max_bytes = stats.max_bytes,
+            "Heap snapshot"
+        );
+        session.snapshots.push(PhaseSnapshot {
+            phase: phase.to_string(),
+            curr_bytes: stats.curr_bytes,
+            curr_blocks: stats.curr_blocks,
+            max_bytes,
+            profile_max_bytes: stats.max_bytes,
+            total_bytes: stats.total_bytes,
+        });
+    }
+
+    /// Stop the profile, writing `dhat-heap.json` and `mem_phases.json`
+    pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
+        let session = SESSION
+            .lock()
+            .map_err(|_| "mem-profile lock poisoned")?
+            .take();
+        let Some(session) = session else {
+            return Ok(());
+        };
+
+        let phases_path = session.output_dir.join("mem_phases.json");
+        serde_json::to_writer_pretty(File::create(&phases_path)?, &session.snapshots)?;
+        info!("Saved per-phase heap snapshots to: {}", phases_path.display());
+
+        // Dropping the profiler writes dhat-heap.json
+        drop(session.profiler);
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+//! Set membership of a claim value, proved against a published Merkle root.
+//!
+//! A verifier publishes a set (an issuer trust list, the accepted
+//! nationalities) as the root of a Poseidon Merkle tree of depth
+//! `MEMBERSHIP_TREE_DEPTH`. The Membership circuit (`membership.circom`) reads
+//! the claim's value the way the Range circuit does, hashes it to a leaf and
+//! checks the path to the root. It shares the device key and claim with
+//! Prepare, so once reblinded under the credential's shared blinds the proof
+//! links to the Prepare proof like Show does. Its public values are
+//!
+//! ```text
+//! membership domain tag || root
+//! ```
+//!
+//! and `MembershipCircuit` constrains the root to the one the path was checked
+//! against, and the shared claim to the one the circuit hashed into the leaf.
+//!
+//! A leaf is `Poseidon(value)`, the value's bytes read as one big-endian number,
+//! and an inner node `Poseidon(left, right)`; empty leaves are zero. Both are
+//! circomlib's Poseidon, computed here with `hashing::poseidon`.
+
+use ff::{Field, PrimeField};
+use serde_json::{json, Value};
+
+use crate::{
+    claims::disclosure_value, domain::domain_tag_for, hashing::poseidon,
+    witness::le_bytes_to_decimal, Scalar,
+};
+
+/// Name the Membership circuit's domain tag is derived from
+pub const MEMBERSHIP_CIRCUIT: &str = "membership";
+
+/// Public values of a Membership proof, domain tag included
+pub const MEMBERSHIP_PUBLIC_VALUES: usize = 2;
+
+/// Levels of the Merkle tree; sets hold at most `2^MEMBERSHIP_TREE_DEPTH` members
+pub const MEMBERSHIP_TREE_DEPTH: usize = 16;
+
+/// Longest member value, in bytes, that fits one field element
+pub const MAX_MEMBER_BYTES: usize = 31;
+
+/// Leaf committing to one member value
+pub fn member_leaf(value: &[u8]) -> Result<Scalar, String> {
+    if value.is_empty() || value.len() > MAX_MEMBER_BYTES {
+        return Err(format!(
+            "member must be 1 to {} bytes, got {}",
+            MAX_MEMBER_BYTES,
+            value.len()
+        ));
+    }
+    let packed = value.iter().fold(Scalar::ZERO, |acc, b| {
+        acc * Scalar::from(256) + Scalar::from(*b as u64)
+    });
+    poseidon(&[packed])
+}
+
+/// A published set: its members and the Merkle tree over their leaves
+#[derive(Debug, Clone)]
+pub struct MerkleSet {
+    members: Vec<Vec<u8>>,
+    /// Nodes per level, leaves first; positions past the end are empty subtrees
+    levels: Vec<Vec<Scalar>>,
+}
+
+/// Path from a member's leaf to the root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerklePath {
+    /// Sibling at each level, leaf level first
+    pub siblings: Vec<Scalar>,
+    /// Whether the node at each level is the right child
+    pub is_right: Vec<bool>,
+}
+
+impl MerkleSet {
+    /// Fails on an empty, oversized or duplicated member, or more members than leaves
+    pub fn new(members: Vec<Vec<u8>>) -> Result<Self, String> {
+        if members.len() > 1 << MEMBERSHIP_TREE_DEPTH {
+            return Err(format!(
+                "a set holds at most {} members, got {}",
+                1usize << MEMBERSHIP_TREE_DEPTH,
+                members.len()
+            ));
+        }
+        for (i, member) in members.iter().enumerate() {
+            if members[..i].contains(member) {
+                return Err(format!(
+                    "member '{}' appears twice",
+                    String::from_utf8_lossy(member)
+                ));
+            }
+        }
+
+        let leaves = members
+            .iter()
+            .map(|member| member_leaf(member))
+            .collect::<Result<Vec<_>, _>>()?;
+        let empty = empty_subtrees()?;
+        let mut levels = vec![leaves];
+        for level in 0..MEMBERSHIP_TREE_DEPTH {
+            let nodes = &levels[level];
+            let parents = nodes
+                .chunks(2)
+                .map(|pair| poseidon(&[pair[0], pair.get(1).copied().unwrap_or(empty[level])]))
+                .collect::<Result<Vec<_>, _>>()?;
+            levels.push(parents);
+        }
+        Ok(Self { members, levels })
+    }
+
+    pub fn members(&self) -> &[Vec<u8>] {
+        &self.members
+    }
+
+    pub fn root(&self) -> Result<Scalar, String> {
+        match self.levels[MEMBERSHIP_TREE_DEPTH].first() {
+            Some(root) => Ok(*root),
+            None => Ok(empty_subtrees()?[MEMBERSHIP_TREE_DEPTH]),
+        }
+    }
+
+    /// Path for `member`, or `None` if it is not in the set
+    pub fn path(&self, member: &[u8]) -> Result<Option<MerklePath>, String> {
+        let Some(mut index) = self.members.iter().position(|m| m == member) else {
+            return Ok(None);
+        };
+        let empty = empty_subtrees()?;
+        let mut path = MerklePath {
+            siblings: Vec::with_capacity(MEMBERSHIP_TREE_DEPTH),
+            is_right: Vec::with_capacity(MEMBERSHIP_TREE_DEPTH),
+        };
+        for (level, nodes) in self.levels[..MEMBERSHIP_TREE_DEPTH].iter().enumerate() {
+            let sibling = nodes.get(index ^ 1).copied().unwrap_or(empty[level]);
+            path.siblings.push(sibling);
+            path.is_right.push(index & 1 == 1);
+            index >>= 1;
+        }
+        Ok(Some(path))
+    }
+}
+
+/// Root reached from `leaf` along `path`
+pub fn path_root(leaf: Scalar, path: &MerklePath) -> Result<Scalar, String> {
+    path.siblings
+        .iter()
+        .zip(&path.is_right)
+        .try_fold(leaf, |node, (sibling, is_right)| {
+            if *is_right {
+                poseidon(&[*sibling, node])
+            } else {
+                poseidon(&[node, *sibling])
+            }
+        })
+}
+
+/// Root of an empty subtree at each height, from a zero leaf up to the full tree
+fn empty_subtrees() -> Result<Vec<Scalar>, String> {
+    let mut empty = vec![Scalar::ZERO];
+    for level in 0..MEMBERSHIP_TREE_DEPTH {
+        empty.push(poseidon(&[empty[level], empty[level]])?);
+    }
+    Ok(empty)
+}
+
+/// The Membership circuit's public values, domain tag first
+pub fn membership_public_values(root: Scalar) -> Vec<Scalar> {
+    vec![domain_tag_for(MEMBERSHIP_CIRCUIT), root]
+}
+
+/// Recover the root from a verified Membership proof's public values
+pub fn root_from_public_values(values: &[Scalar]) -> Result<Scalar, String> {
+    let [tag, root] = values else {
+        return Err(format!(
+            "expected 2 Membership public values, got {}",
+            values.len()
+        ));
+    };
+    if *tag != domain_tag_for(MEMBERSHIP_CIRCUIT) {
+        return Err("membership proof was not made for this domain".to_string());
+    }
+    Ok(*root)
+}
+
+/// Membership circuit input proving the claim in `show_input` is in `set`
+/// Takes the device key and claim from the Show input, so the proof shares the
+/// Show proof's witnesses. Fails if the claim value is not a member.
+pub fn membership_input(show_input: &Value, set: &MerkleSet) -> Result<Value, String> {
+    let field = |name: &str| {
+        show_input
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("show input has no {}", name))
+    };
+    let claim = field("claim")?;
+    let value = disclosure_value(&claim)?;
+    let path = set
+        .path(&value)?
+        .ok_or("claim value is not a member of the set")?;
+
+    let decimal = |scalar: &Scalar| le_bytes_to_decimal(scalar.to_repr().as_ref());
+    Ok(json!({
+        "deviceKeyX": field("deviceKeyX")?,
+        "deviceKeyY": field("deviceKeyY")?,
+        "claim": claim,
+        "root": decimal(&set.root()?),
+        "pathElements": path.siblings.iter().map(decimal).collect::<Vec<_>>(),
+        "pathIndices": path.is_right.iter().map(|r| (*r as u8).to_string()).collect::<Vec<_>>(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_reach_the_root() {
+        let set = MerkleSet::new(vec![b"DE".to_vec(), b"FR".to_vec(), b"NL".to_vec()]).unwrap();
+        let root = set.root().unwrap();
+        for member in set.members() {
+            let path = set.path(member).unwrap().unwrap();
+            assert_eq!(path.siblings.len(), MEMBERSHIP_TREE_DEPTH);
+            assert_eq!(path_root(member_leaf(member).unwrap(), &path), Ok(root));
+        }
+        assert_eq!(set.path(b"US").unwrap(), None);
+
+        let other = MerkleSet::new(vec![b"DE".to_vec(), b"FR".to_vec()]).unwrap();
+        assert_ne!(other.root().unwrap(), root);
+        assert!(MerkleSet::new(vec![b"DE".to_vec(), b"DE".to_vec()]).is_err());
+    }
+
+    #[test]
+    fn test_root_roundtrips_through_public_values() {
+        let root = MerkleSet::new(vec![b"DE".to_vec()])
+            .unwrap()
+            .root()
+            .unwrap();
+        let values = membership_public_values(root);
+        assert_eq!(root_from_public_values(&values), Ok(root));
+        assert!(root_from_public_values(&values[1..]).is_err());
+    }
+}
@@ -0,0 +1,253 @@
+//! SHA-256 midstates for proving Prepare with the JWT's leading blocks hashed natively.
+//!
+//! Most of the JWT circuit's constraints hash and decode the signed message,
+//! yet the header and the start of the payload hold nothing the circuit checks.
+//! `partial_prepare_input` cuts the SHA-padded message at the last 64-byte block
+//! boundary before the first matched payload byte, compresses the blocks before
+//! the cut natively, and hands the circuit the resulting state (`preHash`) and
+//! the remaining blocks (`tail`). `JWTPartial` finishes the hash from that state,
+//! verifies the signature, and decodes and matches only the tail.
+//!
+//! Soundness: the prover picks the midstate, so a forged tail must hash to the
+//! issuer-signed digest from a chosen chaining value, which is a SHA-256
+//! pseudo-second-preimage. The circuit asserts that the tail holds no period,
+//! so every byte it decodes is issuer-signed payload. What it gives up is the
+//! position of the tail within the payload: `payloadOffset`, which aligns
+//! decoding to a Base64 quantum, is not checked against the hidden header.
+//! Claims are only matched as digests and key-binding labels, so a misaligned
+//! decode would need the issuer to have signed bytes that decode to them.
+
+use serde_json::{json, Value};
+use sha2::{
+    compress256,
+    digest::generic_array::{typenum::U64, GenericArray},
+};
+
+/// Tail length `jwt_partial` is compiled for, as in `circom/circuits.json`
+pub const MAX_TAIL_LENGTH: usize = 1280;
+
+/// Base64 payload characters `jwt_partial` decodes from the tail
+pub const MAX_B64_TAIL_PAYLOAD_LENGTH: usize = 1280;
+
+const BLOCK: usize = 64;
+
+/// SHA-256 initial hash value
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 state after compressing `blocks`, big-endian, as `Sha256BytesPartial` takes it
+/// `blocks` must be a whole number of 64-byte blocks.
+pub fn sha256_midstate(blocks: &[u8]) -> [u8; 32] {
+    assert_eq!(blocks.len() % BLOCK, 0, "midstate of a partial block");
+    let mut state = IV;
+    let blocks: Vec<GenericArray<u8, U64>> = blocks
+        .chunks(BLOCK)
+        .map(GenericArray::clone_from_slice)
+        .collect();
+    compress256(&mut state, &blocks);
+
+    let mut midstate = [0u8; 32];
+    for (bytes, word) in midstate.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    midstate
+}
+
+/// Where a Prepare message is cut between native and in-circuit hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidstateSplit {
+    /// Message bytes hashed natively, a multiple of 64
+    pub prefix_len: usize,
+    /// Tail characters before the first whole Base64 quantum of the payload
+    pub payload_offset: usize,
+    /// Decoded payload bytes before the tail's first whole quantum
+    pub skipped_payload_bytes: usize,
+}
+
+impl MidstateSplit {
+    /// The latest cut that keeps the period and every match before `first_match`
+    /// out of the prefix; `None` if no whole block precedes the matched claims
+    pub fn new(period_index: usize, first_match: usize) -> Option<Self> {
+        let payload_start = period_index + 1;
+        // First character of the quantum holding the first matched byte
+        let match_quantum = payload_start + first_match / 3 * 4;
+        let prefix_len = match_quantum / BLOCK * BLOCK;
+        if prefix_len < payload_start {
+            return None;
+        }
+        let payload_offset = (4 - (prefix_len - payload_start) % 4) % 4;
+        let skipped_chars = prefix_len - payload_start + payload_offset;
+        Some(Self {
+            prefix_len,
+            payload_offset,
+            skipped_payload_bytes: skipped_chars / 4 * 3,
+        })
+    }
+}
+
+fn byte_array(input: &Value, field: &str) -> Result<Vec<u8>, String> {
+    input
+        .get(field)
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("Prepare input has no '{}' array", field))?
+        .iter()
+        .map(|value| {
+            value
+                .as_u64()
+                .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+                .and_then(|v| u8::try_from(v).ok())
+                .ok_or_else(|| format!("'{}' holds a value that is not a byte", field))
+        })
+        .collect()
+}
+
+fn usize_field(value: Option<&Value>, field: &str) -> Result<usize, String> {
+    value
+        .and_then(|v| {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        })
+        .map(|v| v as usize)
+        .ok_or_else(|| format!("Prepare input has no numeric '{}'", field))
+}
+
+/// The `jwt_partial` input for a full Prepare input, and where it was cut
+/// Keeps the signature, claims and match patterns; replaces the message with
+/// `tail`, `tailLength`, `payloadOffset` and `preHash`, and rebases `matchIndex`
+/// onto the decoded tail.
+pub fn partial_prepare_input(input: &Value) -> Result<(Value, MidstateSplit), String> {
+    let message = byte_array(input, "message")?;
+    let message_length = usize_field(input.get("messageLength"), "messageLength")?;
+    let period_index = usize_field(input.get("periodIndex"), "periodIndex")?;
+    let matches_count = usize_field(input.get("matchesCount"), "matchesCount")?;
+    let match_index = input
+        .get("matchIndex")
+        .and_then(Value::as_array)
+        .ok_or("Prepare input has no 'matchIndex' array")?
+        .iter()
+        .map(|value| usize_field(Some(value), "matchIndex"))
+        .collect::<Result<Vec<_>, _>>()?;
+    if message_length > message.len() || matches_count > match_index.len() {
+        return Err("Prepare input lengths exceed its arrays".to_string());
+    }
+
+    let first_match = match_index[..matches_count]
+        .iter()
+        .copied()
+        .min()
+        .unwrap_or_default();
+    let split = MidstateSplit::new(period_index, first_match)
+        .ok_or("No whole SHA-256 block precedes the matched payload")?;
+
+    let tail = &message[split.prefix_len..message_length];
+    if tail.len() > MAX_TAIL_LENGTH {
+        return Err(format!(
+            "Message tail of {} bytes exceeds the partial circuit's {}",
+            tail.len(),
+            MAX_TAIL_LENGTH
+        ));
+    }
+    let mut padded_tail = tail.to_vec();
+    padded_tail.resize(MAX_TAIL_LENGTH, 0);
+
+    let rebased: Vec<usize> = match_index
+        .iter()
+        .enumerate()
+        .map(|(i, &index)| {
+            if i < matches_count {
+                index - split.skipped_payload_bytes
+            } else {
+                index
+            }
+        })
+        .collect();
+
+    let mut partial = input.clone();
+    let fields = partial
+        .as_object_mut()
+        .ok_or("Prepare input is not a JSON object")?;
+    for field in ["message", "messageLength", "periodIndex"] {
+        fields.remove(field);
+    }
+    fields.insert(
+        "tail".to_string(),
+        json!(padded_tail.iter().map(u8::to_string).collect::<Vec<_>>()),
+    );
+    fields.insert("tailLength".to_string(), json!(tail.len()));
+    fields.insert("payloadOffset".to_string(), json!(split.payload_offset));
+    fields.insert(
+        "preHash".to_string(),
+        json!(sha256_midstate(&message[..split.prefix_len]).to_vec()),
+    );
+    fields.insert("matchIndex".to_string(), json!(rebased));
+    Ok((partial, split))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use sha2::{Digest, Sha256};
+
+    fn sha256_pad(message: &[u8]) -> Vec<u8> {
+        let mut padded = message.to_vec();
+        padded.push(0x80);
+        while padded.len() % BLOCK != 56 {
+            padded.push(0);
+        }
+        padded.extend_from_slice(&((message.len() as u64) * 8).to_be_bytes());
+        padded
+    }
+
+    #[test]
+    fn test_midstate_finishes_to_full_digest() {
+        let message = vec![b'a'; 300];
+        let padded = sha256_pad(&message);
+        let midstate = sha256_midstate(&padded[..128]);
+
+        let mut state = [0u32; 8];
+        for (word, bytes) in state.iter_mut().zip(midstate.chunks(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        let tail: Vec<GenericArray<u8, U64>> = padded[128..]
+            .chunks(BLOCK)
+            .map(GenericArray::clone_from_slice)
+            .collect();
+        compress256(&mut state, &tail);
+        let digest: Vec<u8> = state.iter().flat_map(|w| w.to_be_bytes()).collect();
+        assert_eq!(digest, Sha256::digest(&message).to_vec());
+    }
+
+    #[test]
+    fn test_partial_input_decodes_to_same_matches() {
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"ES256","typ":"dc+sd-jwt"}"#);
+        let payload = format!(
+            r#"{{"iss":"{}","cnf":{{"jwk":{{"x":"X"}}}}}}"#,
+            "i".repeat(150)
+        );
+        let signed = format!("{}.{}", header, URL_SAFE_NO_PAD.encode(&payload));
+        let padded = sha256_pad(signed.as_bytes());
+        let first_match = payload.find(r#""x":""#).unwrap();
+        let input = json!({
+            "message": padded.iter().map(u8::to_string).collect::<Vec<_>>(),
+            "messageLength": padded.len(),
+            "periodIndex": header.len(),
+            "matchesCount": 1,
+            "matchIndex": [first_match, 0, 0, 0],
+        });
+
+        let (partial, split) = partial_prepare_input(&input).unwrap();
+        assert_eq!(split.prefix_len % BLOCK, 0);
+        assert!(split.prefix_len > header.len());
+
+        let tail = byte_array(&partial, "tail").unwrap();
+        let tail_len = partial["tailLength"].as_u64().unwrap() as usize;
+        let b64 = &tail[split.payload_offset..tail_len];
+        let b64_end = b64.iter().position(|&b| b == 0x80).unwrap();
+        let decoded = URL_SAFE_NO_PAD.decode(&b64[..b64_end]).unwrap();
+        let rebased = partial["matchIndex"][0].as_u64().unwrap() as usize;
+        assert!(decoded[rebased..].starts_with(br#""x":""#));
+        assert_eq!(partial["matchIndex"][1], 0);
+    }
+}
@@ -0,0 +1,128 @@
+//! Per-credential directories for proofs, instances, witnesses and blinds.
+//!
+//! The artifact paths in `setup` are fixed, so a second credential's Prepare
+//! proof would overwrite the first's. Selecting a credential namespace moves
+//! the per-credential artifacts under `credentials/<id>/`, e.g.
+//! `credentials/<id>/prepare.proof`. The save and load helpers resolve the
+//! `setup` constants through the active namespace, so callers keep passing
+//! them unchanged. Proving and verifying keys belong to the circuit rather
+//! than the credential and stay in `keys/`. The default namespace is the flat
+//! layout used before namespaces existed.
+
+use std::{path::Path, sync::RwLock};
+
+use crate::{
+    layout::in_documents,
+    setup::{
+        PREPARE_INSTANCE, PREPARE_PROOF, PREPARE_WITNESS, SHARED_BLINDS, SHOW_INSTANCE, SHOW_PROOF,
+        SHOW_WITNESS,
+    },
+};
+
+/// Directory holding one subdirectory per credential namespace
+pub const CREDENTIALS_DIR: &str = "credentials";
+
+/// Longest credential id accepted as a directory name
+const MAX_ID_LEN: usize = 128;
+
+/// Per-credential artifacts and their file names inside a credential directory
+const CREDENTIAL_FILES: [(&str, &str); 7] = [
+    (PREPARE_PROOF, "prepare.proof"),
+    (PREPARE_INSTANCE, "prepare.instance"),
+    (PREPARE_WITNESS, "prepare.witness"),
+    (SHOW_PROOF, "show.proof"),
+    (SHOW_INSTANCE, "show.instance"),
+    (SHOW_WITNESS, "show.witness"),
+    (SHARED_BLINDS, "shared.blinds"),
+];
+
+/// Where per-credential artifacts are stored
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ArtifactNamespace {
+    /// The flat `keys/` layout
+    #[default]
+    Legacy,
+    /// `credentials/<id>/`
+    Credential(String),
+}
+
+static NAMESPACE: RwLock<ArtifactNamespace> = RwLock::new(ArtifactNamespace::Legacy);
+
+impl ArtifactNamespace {
+    /// Namespace for credential `id` (e.g. from `credential_id`)
+    /// The id becomes a directory name, so only ASCII letters, digits, `-` and
+    /// `_` are accepted.
+    pub fn credential(id: &str) -> Result<Self, String> {
+        if id.is_empty() || id.len() > MAX_ID_LEN {
+            return Err(format!(
+                "credential id must be 1 to {} characters",
+                MAX_ID_LEN
+            ));
+        }
+        if !id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        {
+            return Err(format!(
+                "credential id '{}' may only contain letters, digits, '-' and '_'",
+                id
+            ));
+        }
+        Ok(Self::Credential(id.to_string()))
+    }
+
+    /// Where `path`, one of the `setup` artifact constants, lives in this namespace
+    /// Paths that are not per-credential artifacts are returned unchanged.
+    pub fn resolve(&self, path: &str) -> String {
+        let Self::Credential(id) = self else {
+            return path.to_string();
+        };
+        match CREDENTIAL_FILES.iter().find(|(legacy, _)| *legacy == path) {
+            Some((_, file)) => Path::new(CREDENTIALS_DIR)
+                .join(id)
+                .join(file)
+                .to_string_lossy()
+                .into_owned(),
+            None => path.to_string(),
+        }
+    }
+}
+
+/// Select the namespace used by the save and load helpers
+pub fn set_artifact_namespace(namespace: ArtifactNamespace) {
+    if let Ok(mut slot) = NAMESPACE.write() {
+        *slot = namespace;
+    }
+}
+
+pub fn artifact_namespace() -> ArtifactNamespace {
+    NAMESPACE.read().map(|ns| ns.clone()).unwrap_or_default()
+}
+
+/// `path` resolved in the active namespace
+pub fn artifact_path(path: &str) -> String {
+    in_documents(&artifact_namespace().resolve(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::PREPARE_PROVING_KEY;
+
+    #[test]
+    fn test_credential_namespace_layout() {
+        let namespace = ArtifactNamespace::credential("abc123").unwrap();
+        assert_eq!(
+            namespace.resolve(PREPARE_PROOF),
+            Path::new("credentials/abc123/prepare.proof").to_string_lossy()
+        );
+        assert_eq!(namespace.resolve(PREPARE_PROVING_KEY), PREPARE_PROVING_KEY);
+        assert_eq!(
+            ArtifactNamespace::Legacy.resolve(SHOW_WITNESS),
+            SHOW_WITNESS
+        );
+
+        assert!(ArtifactNamespace::credential("").is_err());
+        assert!(ArtifactNamespace::credential("../keys").is_err());
+    }
+}
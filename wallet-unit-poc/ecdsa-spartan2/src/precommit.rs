@@ -0,0 +1,187 @@
+//! Committing large, rarely changing inputs once and reusing the commitment.
+//!
+//! spartan2's `prep_prove` synthesizes a circuit's shared and precommitted
+//! witness and commits to both, before any challenge is drawn. Inputs that stay
+//! the same from one proof to the next (the issuer key, credential constants)
+//! belong in `SpartanCircuit::precommitted`, allocated with
+//! `alloc_static_inputs` and tied to the circuit's signals with
+//! `enforce_precommitted`. A `PrecommitCache` keeps the result of `prep_prove`
+//! and hands out a copy while the shared and precommitted values hash the same,
+//! so those commitments are computed once rather than on every prove
+//! (`prover::prove_circuit_with_cache`).
+//!
+//! Proofs made from one cache entry carry the same shared and precommitted
+//! commitments, exactly as proofs of one credential share `comm_W_shared`
+//! today; reblind before showing them to parties that must not link them.
+//! The entry holds witness values in memory and is not wiped; `clear` drops it.
+
+use bellpepper_core::{
+    num::AllocatedNum, test_cs::TestConstraintSystem, ConstraintSystem, SynthesisError,
+};
+use ff::PrimeField;
+use sha2::{Digest, Sha256};
+use spartan2::{
+    errors::SpartanError,
+    traits::{circuit::SpartanCircuit, snark::R1CSSNARKTrait, Engine},
+    zk_spartan::R1CSSNARK,
+};
+use tracing::info;
+
+pub type PrepSnark<E> = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::PrepSNARK;
+type ProverKey<E> = <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey;
+
+struct Entry<E: Engine> {
+    /// SHA-256 of the proving key's digest, the shared and the precommitted values
+    key: [u8; 32],
+    small_memory: bool,
+    prep: PrepSnark<E>,
+}
+
+/// The latest `prep_prove` result, reused while its inputs are unchanged
+pub struct PrecommitCache<E: Engine> {
+    entry: Option<Entry<E>>,
+}
+
+impl<E: Engine> Default for PrecommitCache<E> {
+    fn default() -> Self {
+        Self { entry: None }
+    }
+}
+
+impl<E: Engine> PrecommitCache<E>
+where
+    PrepSnark<E>: Clone,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `prep_prove`, or a copy of the cached result for the same key and values
+    /// Circuits whose shared or precommitted values cannot be computed up front
+    /// are prepared afresh and not cached.
+    pub fn prep_prove<C: SpartanCircuit<E> + Clone>(
+        &mut self,
+        pk: &ProverKey<E>,
+        circuit: &C,
+        small_memory: bool,
+    ) -> Result<PrepSnark<E>, SpartanError> {
+        let Some(key) = cache_key::<E, C>(pk, circuit) else {
+            return R1CSSNARK::<E>::prep_prove(pk, circuit.clone(), small_memory);
+        };
+        if let Some(entry) = &self.entry {
+            if entry.key == key && entry.small_memory == small_memory {
+                info!("Reusing cached shared and precommitted commitments");
+                return Ok(entry.prep.clone());
+            }
+        }
+
+        let prep = R1CSSNARK::<E>::prep_prove(pk, circuit.clone(), small_memory)?;
+        self.entry = Some(Entry {
+            key,
+            small_memory,
+            prep: prep.clone(),
+        });
+        Ok(prep)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entry.is_none()
+    }
+
+    /// Drop the cached result
+    pub fn clear(&mut self) {
+        self.entry = None;
+    }
+}
+
+fn digest_scalars<F: PrimeField>(hasher: &mut Sha256, values: &[AllocatedNum<F>]) -> bool {
+    hasher.update((values.len() as u64).to_le_bytes());
+    values.iter().all(|value| match value.get_value() {
+        Some(value) => {
+            hasher.update(value.to_repr().as_ref());
+            true
+        }
+        None => false,
+    })
+}
+
+/// SHA-256 of the proving key's digest and the circuit's shared and
+/// precommitted values, `None` if any value is missing
+fn cache_key<E: Engine, C: SpartanCircuit<E>>(pk: &ProverKey<E>, circuit: &C) -> Option<[u8; 32]> {
+    let mut cs = TestConstraintSystem::<E::Scalar>::new();
+    let shared = circuit.shared(&mut cs.namespace(|| "shared")).ok()?;
+    let precommitted = circuit
+        .precommitted(&mut cs.namespace(|| "precommitted"), &shared)
+        .ok()?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(bincode::serialize(&pk.vk_digest).ok()?);
+    (digest_scalars(&mut hasher, &shared) && digest_scalars(&mut hasher, &precommitted))
+        .then(|| hasher.finalize().into())
+}
+
+/// Allocate precommitted inputs, valued from `values` when proving
+/// During setup `values` may be `None`; only the count matters then.
+pub fn alloc_static_inputs<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    name: &str,
+    values: Option<&[F]>,
+    count: usize,
+) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+    (0..count)
+        .map(|i| {
+            AllocatedNum::alloc(cs.namespace(|| format!("{name}{i}")), || {
+                values
+                    .and_then(|values| values.get(i).copied())
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect()
+}
+
+/// Constrain circuit `signals` to equal the `precommitted` variables, pairwise
+pub fn enforce_precommitted<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    name: &str,
+    signals: &[AllocatedNum<F>],
+    precommitted: &[AllocatedNum<F>],
+) -> Result<(), SynthesisError> {
+    if signals.len() != precommitted.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    for (i, (signal, committed)) in signals.iter().zip(precommitted).enumerate() {
+        cs.enforce(
+            || format!("{name}{i} is precommitted"),
+            |lc| lc + signal.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + committed.get_variable(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scalar;
+
+    #[test]
+    fn test_precommitted_inputs_are_bound() {
+        let values = [Scalar::from(7u64), Scalar::from(9u64)];
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let committed = alloc_static_inputs(&mut cs, "Key", Some(&values), 2).unwrap();
+        let signals = alloc_static_inputs(&mut cs, "Signal", Some(&values), 2).unwrap();
+        enforce_precommitted(&mut cs, "Key", &signals, &committed).unwrap();
+        assert!(cs.is_satisfied());
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let committed = alloc_static_inputs(&mut cs, "Key", Some(&values), 2).unwrap();
+        let other = [values[0], Scalar::from(10u64)];
+        let signals = alloc_static_inputs(&mut cs, "Signal", Some(&other), 2).unwrap();
+        enforce_precommitted(&mut cs, "Key", &signals, &committed).unwrap();
+        assert!(!cs.is_satisfied());
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        assert!(alloc_static_inputs(&mut cs, "Key", None, 1).is_err());
+    }
+}
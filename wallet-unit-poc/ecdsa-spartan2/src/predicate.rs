@@ -0,0 +1,389 @@
+//! Relying-party claim predicates and the Show inputs that prove them.
+//!
+//! A request is written against claim names, not circuit slots:
+//!
+//! ```text
+//! age >= 21
+//! nationality == DE
+//! citizenship in {DE, FR}
+//! birth_year in [1990, 2000]
+//! ```
+//!
+//! `compile_predicate` resolves the claim among the disclosures bound into the
+//! Prepare input and fills the Show input fields that prove the predicate.
+//! The compiled Show circuit proves one statement: the holder, by the
+//! `roc_birthday` claim, is at least 18 on the date in `currentYear`,
+//! `currentMonth` and `currentDay`. `age >= n` is proved by moving that date
+//! `n - 18` years back; the date is a public value of the Show proof, so the
+//! verifier recovers `n` from it (see `ShowStatement::proven_min_age`) rather
+//! than taking the holder's word for the predicate. Other predicates parse and
+//! resolve but are rejected as `Unsupported` rather than proved by something
+//! weaker; numeric ranges can be proved instead by the separate Range circuit
+//! (see `range`).
+
+use std::{fmt, str::FromStr};
+
+use serde_json::Value;
+
+use crate::{credential::AGE_CLAIM_NAME, utils::decode_base64};
+
+/// Derived claim compared by `age >= n`, computed from `roc_birthday`
+pub const AGE_CLAIM: &str = "age";
+
+/// Age the Show circuit's `AgeVerifier` checks against
+pub const CIRCUIT_MIN_AGE: i64 = 18;
+
+/// Offset between Gregorian and ROC (Minguo) years
+const ROC_YEAR_OFFSET: i64 = 1911;
+
+/// A condition on one disclosed claim
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClaimPredicate {
+    /// `claim == value`
+    Equals { claim: String, value: String },
+    /// `claim >= min`, `claim <= max`, or `claim in [min, max]`
+    Range {
+        claim: String,
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    /// `claim in {a, b, ...}`
+    OneOf { claim: String, values: Vec<String> },
+}
+
+/// Date the predicate is evaluated on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PredicateError {
+    /// The predicate text does not parse
+    Syntax(String),
+    /// The claim is not among the credential's disclosures bound into Prepare
+    UnknownClaim(String),
+    /// The Show circuit cannot prove this predicate
+    Unsupported(String),
+    /// The Prepare input is missing or malformed
+    Input(String),
+}
+
+impl fmt::Display for PredicateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(msg) => write!(f, "invalid predicate: {}", msg),
+            Self::UnknownClaim(claim) => {
+                write!(f, "claim '{}' is not disclosed to the Prepare proof", claim)
+            }
+            Self::Unsupported(msg) => {
+                write!(f, "predicate not supported by the Show circuit: {}", msg)
+            }
+            Self::Input(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for PredicateError {}
+
+impl ClaimPredicate {
+    pub fn claim(&self) -> &str {
+        match self {
+            Self::Equals { claim, .. } | Self::Range { claim, .. } | Self::OneOf { claim, .. } => {
+                claim
+            }
+        }
+    }
+}
+
+impl FromStr for ClaimPredicate {
+    type Err = PredicateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let syntax = |msg: &str| PredicateError::Syntax(format!("{} in '{}'", msg, s));
+        let int = |v: &str| {
+            v.trim()
+                .parse::<i64>()
+                .map_err(|_| syntax("expected an integer"))
+        };
+
+        for (op, min) in [(">=", true), ("<=", false)] {
+            if let Some((claim, bound)) = s.split_once(op) {
+                let claim = claim_name(claim).ok_or_else(|| syntax("expected a claim name"))?;
+                let bound = Some(int(bound)?);
+                let (min, max) = if min { (bound, None) } else { (None, bound) };
+                return Ok(Self::Range { claim, min, max });
+            }
+        }
+        if let Some((claim, value)) = s.split_once("==") {
+            let claim = claim_name(claim).ok_or_else(|| syntax("expected a claim name"))?;
+            let value = literal(value).ok_or_else(|| syntax("expected a value"))?;
+            return Ok(Self::Equals { claim, value });
+        }
+        if let Some((claim, set)) = s.split_once(" in ") {
+            let claim = claim_name(claim).ok_or_else(|| syntax("expected a claim name"))?;
+            let set = set.trim();
+            if let Some(values) = set.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+                let values = values
+                    .split(',')
+                    .map(literal)
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|v| !v.is_empty())
+                    .ok_or_else(|| syntax("expected {value, ...}"))?;
+                return Ok(Self::OneOf { claim, values });
+            }
+            if let Some(bounds) = set.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                let (min, max) = bounds
+                    .split_once(',')
+                    .ok_or_else(|| syntax("expected [min, max]"))?;
+                let (min, max) = (int(min)?, int(max)?);
+                if min > max {
+                    return Err(syntax("empty range"));
+                }
+                return Ok(Self::Range {
+                    claim,
+                    min: Some(min),
+                    max: Some(max),
+                });
+            }
+            return Err(syntax("expected {values} or [min, max] after 'in'"));
+        }
+        Err(syntax("expected ==, >=, <= or in"))
+    }
+}
+
+impl fmt::Display for ClaimPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Equals { claim, value } => write!(f, "{} == {}", claim, value),
+            Self::Range { claim, min, max } => match (min, max) {
+                (Some(min), Some(max)) => write!(f, "{} in [{}, {}]", claim, min, max),
+                (Some(min), None) => write!(f, "{} >= {}", claim, min),
+                (None, Some(max)) => write!(f, "{} <= {}", claim, max),
+                (None, None) => write!(f, "{} in [..]", claim),
+            },
+            Self::OneOf { claim, values } => write!(f, "{} in {{{}}}", claim, values.join(", ")),
+        }
+    }
+}
+
+fn claim_name(s: &str) -> Option<String> {
+    let s = s.trim();
+    let valid = !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'.');
+    valid.then(|| s.to_string())
+}
+
+/// A bare or double-quoted value
+fn literal(s: &str) -> Option<String> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(s);
+    (!s.is_empty()).then(|| s.to_string())
+}
+
+/// A disclosure bound into the Prepare input
+struct Disclosure {
+    slot: usize,
+    name: String,
+    /// Decoded `[salt, name, value]` JSON, as the Show circuit takes it
+    decoded: Vec<u8>,
+}
+
+/// The disclosures in a Prepare input's claim slots
+fn prepare_disclosures(prepare_input: &Value) -> Result<Vec<Disclosure>, PredicateError> {
+    let input_err = |msg: &str| PredicateError::Input(format!("prepare input: {}", msg));
+    let claims = prepare_input
+        .get("claims")
+        .and_then(Value::as_array)
+        .ok_or_else(|| input_err("no claims"))?;
+    let lengths = prepare_input
+        .get("claimLengths")
+        .and_then(Value::as_array)
+        .ok_or_else(|| input_err("no claimLengths"))?;
+
+    let mut disclosures = Vec::new();
+    for (slot, (claim, length)) in claims.iter().zip(lengths).enumerate() {
+        let length = match length {
+            Value::String(s) => s.parse::<usize>().ok(),
+            other => other.as_u64().map(|n| n as usize),
+        }
+        .ok_or_else(|| input_err("malformed claimLengths"))?;
+        if length == 0 {
+            continue;
+        }
+        let encoded = claim
+            .as_array()
+            .and_then(|bytes| {
+                bytes
+                    .iter()
+                    .take(length)
+                    .map(|b| match b {
+                        Value::String(s) => s.parse::<u8>().ok(),
+                        other => other.as_u64().and_then(|n| u8::try_from(n).ok()),
+                    })
+                    .collect::<Option<Vec<u8>>>()
+            })
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| input_err("malformed claims"))?;
+        let decoded = decode_base64(&encoded).map_err(|_| input_err("claim is not base64"))?;
+        let name = serde_json::from_slice::<Value>(&decoded)
+            .ok()
+            .and_then(|v| v.get(1).and_then(Value::as_str).map(str::to_string))
+            .ok_or_else(|| input_err("claim is not a [salt, name, value] disclosure"))?;
+        disclosures.push(Disclosure {
+            slot,
+            name,
+            decoded,
+        });
+    }
+    Ok(disclosures)
+}
+
+/// Fill `show_input` so the Show proof proves `predicate` on `today`
+/// `prepare_input` is the input the stored Prepare proof was made from; the
+/// Show claim must match the one it passed on.
+pub fn compile_predicate(
+    predicate: &ClaimPredicate,
+    prepare_input: &Value,
+    show_input: &mut Value,
+    today: CalendarDate,
+) -> Result<(), PredicateError> {
+    let source = match predicate.claim() {
+        AGE_CLAIM => AGE_CLAIM_NAME,
+        claim => claim,
+    };
+    let disclosures = prepare_disclosures(prepare_input)?;
+    let disclosure = disclosures
+        .iter()
+        .find(|d| d.name == source)
+        .ok_or_else(|| PredicateError::UnknownClaim(source.to_string()))?;
+
+    let min_age = match predicate {
+        ClaimPredicate::Range {
+            claim,
+            min: Some(min),
+            max: None,
+        } if claim == AGE_CLAIM => *min,
+        _ => {
+            return Err(PredicateError::Unsupported(format!(
+                "'{}'; the circuit only proves '{} >= n'",
+                predicate, AGE_CLAIM
+            )))
+        }
+    };
+    let age_slot = prepare_input.get("ageClaimIndex").and_then(Value::as_u64);
+    if age_slot != Some(disclosure.slot as u64) {
+        return Err(PredicateError::Input(
+            "prepare input does not pass its roc_birthday claim to Show".to_string(),
+        ));
+    }
+
+    // `claims` holds base64 characters; Show takes the decoded bytes
+    let decoded_len = prepare_input["claims"][disclosure.slot]
+        .as_array()
+        .map_or(0, |bytes| bytes.len() * 3 / 4);
+    if disclosure.decoded.len() > decoded_len {
+        return Err(PredicateError::Input(
+            "decoded claim exceeds the Show claim length".to_string(),
+        ));
+    }
+    let mut claim: Vec<String> = disclosure.decoded.iter().map(u8::to_string).collect();
+    claim.resize(decoded_len, "0".to_string());
+
+    // Over 18 on `today - (n - 18) years` is over n today
+    let year = today.year - (min_age - CIRCUIT_MIN_AGE);
+    if year <= ROC_YEAR_OFFSET {
+        return Err(PredicateError::Unsupported(format!(
+            "'{}' reaches before year {}",
+            predicate, ROC_YEAR_OFFSET
+        )));
+    }
+
+    let object = show_input
+        .as_object_mut()
+        .ok_or_else(|| PredicateError::Input("show input must be a JSON object".to_string()))?;
+    object.insert("claim".into(), claim.into());
+    object.insert("currentYear".into(), year.to_string().into());
+    object.insert("currentMonth".into(), today.month.to_string().into());
+    object.insert("currentDay".into(), today.day.to_string().into());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_predicates() {
+        let parse = |s: &str| s.parse::<ClaimPredicate>().unwrap();
+        assert_eq!(
+            parse("citizenship in {DE, \"FR\"}"),
+            ClaimPredicate::OneOf {
+                claim: "citizenship".to_string(),
+                values: vec!["DE".to_string(), "FR".to_string()],
+            }
+        );
+        assert_eq!(
+            parse("age >= 21"),
+            ClaimPredicate::Range {
+                claim: "age".to_string(),
+                min: Some(21),
+                max: None,
+            }
+        );
+        assert_eq!(
+            parse("birth_year in [1990, 2000]").to_string(),
+            "birth_year in [1990, 2000]"
+        );
+        assert_eq!(parse("nationality == DE").to_string(), "nationality == DE");
+        assert!("age > 18".parse::<ClaimPredicate>().is_err());
+        assert!("x in [3, 1]".parse::<ClaimPredicate>().is_err());
+    }
+
+    #[test]
+    fn test_compile_age_predicate() {
+        let encode = |claim: &str| {
+            let encoded = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claim);
+            let mut bytes: Vec<String> = encoded.bytes().map(|b| b.to_string()).collect();
+            let len = bytes.len();
+            bytes.resize(128, "0".to_string());
+            (bytes, len)
+        };
+        let (name, name_len) = encode(r#"["salt","nationality","DE"]"#);
+        let (age, age_len) = encode(r#"["salt","roc_birthday","0750101"]"#);
+        let prepare = json!({
+            "claims": [name, age],
+            "claimLengths": [name_len.to_string(), age_len.to_string()],
+            "ageClaimIndex": 1,
+        });
+        let today = CalendarDate {
+            year: 2025,
+            month: 6,
+            day: 1,
+        };
+
+        let mut show = json!({});
+        compile_predicate(&"age >= 21".parse().unwrap(), &prepare, &mut show, today).unwrap();
+        assert_eq!(show["currentYear"], "2022");
+        assert_eq!(show["claim"].as_array().unwrap().len(), 96);
+        assert_eq!(show["claim"][0], "91");
+
+        let unsupported = compile_predicate(
+            &"nationality in {DE, FR}".parse().unwrap(),
+            &prepare,
+            &mut show,
+            today,
+        );
+        assert!(matches!(unsupported, Err(PredicateError::Unsupported(_))));
+        let unknown = compile_predicate(&"email == x".parse().unwrap(), &prepare, &mut show, today);
+        assert!(matches!(unknown, Err(PredicateError::UnknownClaim(_))));
+    }
+}
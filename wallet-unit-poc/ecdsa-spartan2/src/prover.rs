@@ -1,31 +1,45 @@
-use std::{env::current_dir, fs::File, time::Instant};
+use std::{fs, sync::RwLock, time::Instant};
 
 use crate::{
-    circuits::prepare_circuit::jwt_witness,
+    layout::{circuits_dir, documents_dir},
+    mem_profile,
+    precommit::{PrecommitCache, PrepSnark},
+    secret::SecretBlinds,
+    signpost::{section, Phase},
     setup::{
-        load_instance, load_proof, load_proving_key, load_shared_blinds, load_verifying_key,
-        load_witness, save_instance, save_proof, save_shared_blinds, save_witness,
+        load_instance_and_witness, load_proof, load_proving_key, load_public_instance,
+        load_shared_blinds, load_verifying_key, save_instance, save_proof, save_shared_blinds,
+        save_witness,
     },
     utils::{convert_bigint_to_scalar, parse_jwt_inputs},
-    Scalar, E,
+    variant::{jwt_variant, JwtVariant},
+    witness::CircuitKind,
+    witness_cache::{cached_witness, prefill_witness},
+    witness_source::generate_witness,
+    witness_stream::{can_stream, SynthesisWitness},
+    Scalar,
 };
 
 use bellpepper_core::SynthesisError;
-use ff::{derive::rand_core::OsRng, Field};
+use ff::{
+    derive::rand_core::{CryptoRngCore, OsRng},
+    Field,
+};
+use rust_witness::BigInt;
 use serde_json::Value;
 use spartan2::{
     bellpepper::{solver::SatisfyingAssignment, zk_r1cs::SpartanWitness},
     errors::SpartanError,
-    provider::traits::DlogGroup,
+    r1cs::{R1CSWitness, SplitR1CSInstance},
     traits::{
         circuit::SpartanCircuit, snark::R1CSSNARKTrait, transcript::TranscriptEngineTrait, Engine,
     },
     zk_spartan::R1CSSNARK,
 };
-use tracing::info;
+use tracing::{error, info};
 
 /// Run circuit using ZK-Spartan (setup, prepare, prove, verify)
-pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
+pub fn run_circuit<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
     // SETUP using ZK-Spartan
     let t0 = Instant::now();
     let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed");
@@ -35,14 +49,20 @@ pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
     // PREPARE
     let t0 = Instant::now();
     let mut prep_snark =
-        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).expect("prep_prove failed");
+        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), prove_options().use_small_memory(&pk))
+            .expect("prep_prove failed");
     let prep_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = prep_ms, "ZK-Spartan prep_prove");
 
     // PROVE
     let t0 = Instant::now();
-    let proof =
-        R1CSSNARK::<E>::prove(&pk, circuit.clone(), &mut prep_snark, false).expect("prove failed");
+    let proof = R1CSSNARK::<E>::prove(
+        &pk,
+        circuit.clone(),
+        &mut prep_snark,
+        prove_options().use_small_memory(&pk),
+    )
+    .expect("prove failed");
     let prove_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = prove_ms, "ZK-Spartan prove");
 
@@ -61,71 +81,408 @@ pub fn run_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(circuit: C) {
     info!("comm_W_shared: {:?}", proof.comm_W_shared());
 }
 
-pub fn generate_shared_blinds<E: Engine>(shared_blinds_path: &str, n: usize) {
-    let blinds: Vec<_> = (0..n).map(|_| E::Scalar::random(OsRng)).collect();
-    if let Err(e) = save_shared_blinds::<E>(shared_blinds_path, &blinds) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
+/// Seed of `deterministic_rng`
+#[cfg(feature = "test-determinism")]
+pub const DETERMINISTIC_SEED: [u8; 32] = *b"zkid-spartan2-test-determinism!!";
+
+/// RNG used wherever the caller does not supply one: the operating system RNG
+pub fn default_rng() -> Box<dyn CryptoRngCore + Send> {
+    Box::new(OsRng)
+}
+
+/// ChaCha20 stream from `DETERMINISTIC_SEED`, for test vectors only
+///
+/// Only randomness this crate draws can be fixed this way: shared blinds fed
+/// from it, and therefore `comm_W_shared`, are identical across runs and
+/// platforms. The masking randomness spartan2 samples internally is not
+/// seedable, so proof bytes still differ between runs.
+#[cfg(feature = "test-determinism")]
+pub fn deterministic_rng() -> Box<dyn CryptoRngCore + Send> {
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+    Box::new(ChaCha20Rng::from_seed(DETERMINISTIC_SEED))
+}
+
+pub fn generate_shared_blinds<E: Engine>(
+    shared_blinds_path: &str,
+    n: usize,
+) -> Result<(), ProveError> {
+    generate_shared_blinds_with_rng::<E>(shared_blinds_path, n, &mut *default_rng())
+}
+
+/// Generate shared blinds from a caller-supplied RNG (hardware-seeded DRBG, test seed, ...)
+/// The blinds are the only reblinding randomness chosen by this crate; the
+/// remaining commitment randomness is sampled inside spartan2.
+pub fn generate_shared_blinds_with_rng<E: Engine>(
+    shared_blinds_path: &str,
+    n: usize,
+    rng: &mut dyn CryptoRngCore,
+) -> Result<(), ProveError> {
+    let blinds: SecretBlinds<_> = (0..n).map(|_| E::Scalar::random(&mut *rng)).collect();
+    save_output(shared_blinds_path, || {
+        save_shared_blinds::<E>(shared_blinds_path, &blinds)
+    })
+}
+
+/// A prove that passed its deadline and stopped before writing any output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimedOut {
+    /// Last phase completed before the deadline was noticed
+    pub phase: &'static str,
+}
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "deadline passed after {}", self.phase)
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Why a prove or reblind stopped without writing all of its outputs
+#[derive(Debug)]
+pub enum ProveError {
+    /// The deadline passed before any output was written
+    TimedOut(TimedOut),
+    /// An input artifact, such as the proving key, could not be read
+    Load {
+        path: String,
+        source: Box<dyn std::error::Error>,
+    },
+    /// An output could not be written; outputs written before it are left in place
+    Save {
+        path: String,
+        source: Box<dyn std::error::Error>,
+    },
+    /// The circuit could not give its public values
+    Synthesis(SynthesisError),
+    /// Spartan failed to commit to the witness or to prove
+    Spartan(SpartanError),
+}
+
+impl std::fmt::Display for ProveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TimedOut(e) => e.fmt(f),
+            Self::Load { path, source } => write!(f, "failed to load '{}': {}", path, source),
+            Self::Save { path, source } => write!(f, "failed to save '{}': {}", path, source),
+            Self::Synthesis(e) => write!(f, "failed to synthesize the circuit: {}", e),
+            Self::Spartan(e) => write!(f, "failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ProveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::TimedOut(e) => Some(e),
+            Self::Load { source, .. } | Self::Save { source, .. } => Some(&**source),
+            Self::Synthesis(e) => Some(e),
+            Self::Spartan(e) => Some(e),
+        }
+    }
+}
+
+impl From<TimedOut> for ProveError {
+    fn from(e: TimedOut) -> Self {
+        Self::TimedOut(e)
+    }
+}
+
+impl From<SynthesisError> for ProveError {
+    fn from(e: SynthesisError) -> Self {
+        Self::Synthesis(e)
+    }
+}
+
+impl From<SpartanError> for ProveError {
+    fn from(e: SpartanError) -> Self {
+        Self::Spartan(e)
+    }
+}
+
+/// Save one prove output with `save`, reporting `path` if it fails
+fn save_output(
+    path: &str,
+    save: impl FnOnce() -> Result<(), Box<dyn std::error::Error>>,
+) -> Result<(), ProveError> {
+    save().map_err(|source| ProveError::Save {
+        path: path.to_string(),
+        source,
+    })
+}
+
+/// Prover settings applied to every prove
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProveOptions {
+    /// Take spartan2's low-memory path in `prep_prove` and the witness
+    /// commitment: lower peak memory, slower proving
+    pub small_memory: bool,
+    /// Take the low-memory path whenever the fast one is estimated to need more
+    pub memory_budget_bytes: Option<u64>,
+}
+
+/// Rough peak memory of the fast path, in multiples of the proving key's size
+const FAST_PATH_MEMORY_FACTOR: u64 = 4;
+
+impl ProveOptions {
+    /// Whether proving with `pk` should take the low-memory path
+    pub fn use_small_memory<E: Engine>(
+        &self,
+        pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    ) -> bool {
+        self.small_memory
+            || self.memory_budget_bytes.is_some_and(|budget| {
+                bincode::serialized_size(pk).map_or(true, |size| {
+                    size.saturating_mul(FAST_PATH_MEMORY_FACTOR) > budget
+                })
+            })
+    }
+}
+
+static PROVE_OPTIONS: RwLock<ProveOptions> = RwLock::new(ProveOptions {
+    small_memory: false,
+    memory_budget_bytes: None,
+});
+
+/// Replace the settings used by subsequent proves
+pub fn set_prove_options(options: ProveOptions) {
+    *PROVE_OPTIONS.write().unwrap_or_else(|e| e.into_inner()) = options;
+}
+
+pub fn prove_options() -> ProveOptions {
+    *PROVE_OPTIONS.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Runs the computation of a proof, which reads no stored artifact but its inputs
+/// A scope must call the computation exactly once. Hosts holding a lock on the
+/// artifact store across processes install one that releases it meanwhile, so
+/// the lock is not held for the seconds a proof takes; a scope error means the
+/// outputs cannot be written.
+pub type ComputeScope = fn(&mut dyn FnMut()) -> std::io::Result<()>;
+
+static COMPUTE_SCOPE: RwLock<Option<ComputeScope>> = RwLock::new(None);
+
+/// Install the scope every proof computation runs in; `None` runs it directly
+pub fn set_compute_scope(scope: Option<ComputeScope>) {
+    *COMPUTE_SCOPE.write().unwrap_or_else(|e| e.into_inner()) = scope;
+}
+
+fn run_compute(compute: &mut dyn FnMut()) -> std::io::Result<()> {
+    let scope = *COMPUTE_SCOPE.read().unwrap_or_else(|e| e.into_inner());
+    match scope {
+        Some(scope) => scope(compute),
+        None => {
+            compute();
+            Ok(())
+        }
+    }
+}
+
+fn check_deadline(deadline: Option<Instant>, phase: &'static str) -> Result<(), TimedOut> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => {
+            info!("ZK-Spartan prove stopped at deadline after {}", phase);
+            Err(TimedOut { phase })
+        }
+        _ => Ok(()),
     }
 }
 
 /// Only run the proving part of the circuit using ZK-Spartan (prep_prove, prove)
-pub fn prove_circuit<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+pub fn prove_circuit<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
     pk_path: &str,
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
-) {
+) -> Result<(), ProveError> {
+    prove_circuit_until(
+        circuit,
+        pk_path,
+        instance_path,
+        witness_path,
+        proof_path,
+        None,
+    )
+}
+
+/// `prove_circuit`, giving up with `TimedOut` once `deadline` has passed
+/// Spartan phases cannot be interrupted, so the deadline is checked between
+/// them; the outputs are only written if it has not passed by then.
+pub fn prove_circuit_until<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk_path: &str,
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+    deadline: Option<Instant>,
+) -> Result<(), ProveError> {
     let t0 = Instant::now();
-    let pk = load_proving_key(pk_path).expect("load proving key failed");
+    let pk = load_proving_key::<E>(pk_path).map_err(|source| ProveError::Load {
+        path: pk_path.to_string(),
+        source,
+    })?;
     let load_pk_ms = t0.elapsed().as_millis();
 
     info!("ZK-Spartan load proving key: {} ms", load_pk_ms);
+    check_deadline(deadline, "load_pk")?;
 
-    prove_circuit_with_pk(circuit, &pk, instance_path, witness_path, proof_path);
+    prove_circuit_with_pk_until(
+        circuit,
+        &pk,
+        instance_path,
+        witness_path,
+        proof_path,
+        deadline,
+    )
 }
 
 /// Only run the proving part of the circuit using ZK-Spartan with a pre-loaded proving key
 /// This is useful for benchmarking to exclude file I/O from timing measurements
-pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+pub fn prove_circuit_with_pk<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
     pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
-) {
+) -> Result<(), ProveError> {
+    prove_circuit_with_pk_until(circuit, pk, instance_path, witness_path, proof_path, None)
+}
+
+/// `prove_circuit_with_pk`, giving up with `TimedOut` once `deadline` has passed
+pub fn prove_circuit_with_pk_until<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+    deadline: Option<Instant>,
+) -> Result<(), ProveError> {
+    prove_with_prep_until(
+        circuit,
+        pk,
+        |circuit, small_memory| R1CSSNARK::<E>::prep_prove(pk, circuit.clone(), small_memory),
+        instance_path,
+        witness_path,
+        proof_path,
+        deadline,
+    )
+}
+
+/// `prove_circuit_with_pk`, taking the shared and precommitted commitments from `cache`
+/// See `precommit` for when reusing them across proofs is appropriate.
+pub fn prove_circuit_with_cache<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    cache: &mut PrecommitCache<E>,
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+) -> Result<(), ProveError>
+where
+    PrepSnark<E>: Clone,
+{
+    prove_with_prep_until(
+        circuit,
+        pk,
+        |circuit, small_memory| cache.prep_prove(pk, circuit, small_memory),
+        instance_path,
+        witness_path,
+        proof_path,
+        None,
+    )
+}
+
+fn prove_with_prep_until<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    prep_prove: impl FnOnce(&C, bool) -> Result<PrepSnark<E>, SpartanError>,
+    instance_path: &str,
+    witness_path: &str,
+    proof_path: &str,
+    deadline: Option<Instant>,
+) -> Result<(), ProveError> {
+    let mut prep_prove = Some(prep_prove);
+    let mut computed = None;
+    run_compute(&mut || {
+        if let Some(prep_prove) = prep_prove.take() {
+            computed = Some(compute_proof(&circuit, pk, prep_prove, deadline));
+        }
+    })
+    .map_err(|source| ProveError::Save {
+        path: proof_path.to_string(),
+        source: Box::new(source),
+    })?;
+    let (instance, witness, public_values, res) =
+        computed.expect("compute scope must run the computation")?;
+
+    save_output(instance_path, || {
+        save_instance(instance_path, &instance, &public_values)
+    })?;
+    save_output(witness_path, || save_witness(witness_path, &witness))?;
+    save_output(proof_path, || save_proof(proof_path, &res))?;
+    Ok(())
+}
+
+type ComputedProof<E> = (
+    SplitR1CSInstance<E>,
+    R1CSWitness<E>,
+    Vec<<E as Engine>::Scalar>,
+    R1CSSNARK<E>,
+);
+
+/// prep_prove through `prove_inner`, on what is already loaded
+fn compute_proof<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: &C,
+    pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    prep_prove: impl FnOnce(&C, bool) -> Result<PrepSnark<E>, SpartanError>,
+    deadline: Option<Instant>,
+) -> Result<ComputedProof<E>, ProveError> {
+    let small_memory = prove_options().use_small_memory(pk);
+    let num_challenges = SpartanCircuit::<E>::num_challenges(circuit);
+    if num_challenges > 0 {
+        // Squeezed by r1cs_instance_and_witness once shared and precommitted are absorbed
+        info!("Circuit draws {} verifier challenges", num_challenges);
+    }
     let t0 = Instant::now();
-    let mut prep_snark =
-        R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).expect("prep_prove failed");
+    let mut prep_snark = {
+        let _section = section(Phase::PrepProve);
+        prep_prove(circuit, small_memory)?
+    };
     let prep_ms = t0.elapsed().as_millis();
     info!("ZK-Spartan prep_prove: {} ms", prep_ms);
+    mem_profile::snapshot("prep_prove");
+    check_deadline(deadline, "prep_prove")?;
 
     let t0 = Instant::now();
     let mut transcript = <E as Engine>::TE::new(b"R1CSSNARK");
     transcript.absorb(b"vk", &pk.vk_digest);
 
-    let public_values = SpartanCircuit::<E>::public_values(&circuit)
-        .map_err(|e| SpartanError::SynthesisError {
-            reason: format!("Circuit does not provide public IO: {e}"),
-        })
-        .unwrap();
+    let public_values = SpartanCircuit::<E>::public_values(circuit)?;
 
     // absorb the public values into the transcript
     transcript.absorb(b"public_values", &public_values.as_slice());
 
-    let (instance, witness) = SatisfyingAssignment::r1cs_instance_and_witness(
-        &mut prep_snark.ps,
-        &pk.S,
-        &pk.ck,
-        &circuit,
-        false,
-        &mut transcript,
-    )
-    .unwrap();
+    let (instance, witness) = {
+        let _section = section(Phase::Commit);
+        SatisfyingAssignment::r1cs_instance_and_witness(
+            &mut prep_snark.ps,
+            &pk.S,
+            &pk.ck,
+            circuit,
+            small_memory,
+            &mut transcript,
+        )?
+    };
+    mem_profile::snapshot("pcs_commit");
+    check_deadline(deadline, "pcs_commit")?;
 
     // generate a witness and proof
-    let res = R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript).unwrap();
+    let res = {
+        let _section = section(Phase::Prove);
+        R1CSSNARK::<E>::prove_inner(&pk, &instance, &witness, &mut transcript)?
+    };
+    mem_profile::snapshot("prove");
     let prove_ms = t0.elapsed().as_millis();
 
     info!("ZK-Spartan prove: {} ms", prove_ms);
@@ -136,39 +493,28 @@ pub fn prove_circuit_with_pk<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
         "ZK-Spartan prep_prove: ({} ms) + prove: ({} ms) = TOTAL: {} ms",
         prep_ms, prove_ms, total_ms
     );
+    check_deadline(deadline, "prove")?;
 
-    // Save the instance to file
-    if let Err(e) = save_instance(instance_path, &instance) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
-    }
-
-    // Save the witness to file
-    if let Err(e) = save_witness(witness_path, &witness) {
-        eprintln!("Failed to save witness: {}", e);
-        std::process::exit(1);
-    }
-
-    // Save the proof to file
-    if let Err(e) = save_proof(proof_path, &res) {
-        eprintln!("Failed to save proof: {}", e);
-        std::process::exit(1);
-    }
+    Ok((instance, witness, public_values, res))
 }
 
-pub fn reblind<C: SpartanCircuit<E>>(
+pub fn reblind<E: Engine, C: SpartanCircuit<E>>(
     circuit: C,
     pk_path: &str,
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
     shared_blinds_path: &str,
-) {
-    let pk = load_proving_key(pk_path).expect("load proving key failed");
-    let instance = load_instance(instance_path).expect("load instance failed");
-    let witness = load_witness(witness_path).expect("load witness failed");
+) -> Result<ReblindTimings, ProveError> {
+    let load_failed = |path: &str| {
+        let path = path.to_string();
+        move |source| ProveError::Load { path, source }
+    };
+    let pk = load_proving_key::<E>(pk_path).map_err(load_failed(pk_path))?;
+    let (instance, witness) = load_instance_and_witness::<E>(instance_path, witness_path)
+        .map_err(load_failed(instance_path))?;
     let randomness =
-        load_shared_blinds::<E>(shared_blinds_path).expect("load shared_blinds failed");
+        load_shared_blinds::<E>(shared_blinds_path).map_err(load_failed(shared_blinds_path))?;
 
     reblind_with_loaded_data(
         circuit,
@@ -179,91 +525,117 @@ pub fn reblind<C: SpartanCircuit<E>>(
         instance_path,
         witness_path,
         proof_path,
-    );
+    )
+}
+
+/// Where the time of a reblind went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReblindTimings {
+    /// Applying the shared blinds and recomputing the blinded commitments
+    pub recommit_ms: u64,
+    /// Regenerating the sumcheck and PCS evaluation proof for the new commitments
+    pub prove_ms: u64,
 }
 
 /// Reblind with pre-loaded data - useful for benchmarking to exclude file I/O
-pub fn reblind_with_loaded_data<C: SpartanCircuit<E>>(
+/// `instance_path` must hold the public instance of `instance`, whose public
+/// values the reblinded proof keeps; the new instance replaces it.
+pub fn reblind_with_loaded_data<E: Engine, C: SpartanCircuit<E>>(
     circuit: C,
     pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
-    instance: spartan2::r1cs::SplitR1CSInstance<E>,
-    witness: spartan2::r1cs::R1CSWitness<E>,
+    instance: SplitR1CSInstance<E>,
+    witness: R1CSWitness<E>,
     randomness: &[<E as Engine>::Scalar],
     instance_path: &str,
     witness_path: &str,
     proof_path: &str,
-) {
+) -> Result<ReblindTimings, ProveError> {
+    // The challenges are squeezed after the shared commitment, so new blinds would
+    // change them and leave the witness unsatisfying; such circuits must prove afresh
+    assert_eq!(
+        SpartanCircuit::<E>::num_challenges(&circuit),
+        0,
+        "Cannot reblind a circuit that draws verifier challenges"
+    );
     assert_eq!(randomness.len(), instance.num_shared_rows());
 
     // Reblind instance and witness
     let mut reblind_transcript = <E as Engine>::TE::new(b"R1CSSNARK");
     reblind_transcript.absorb(b"vk", &pk.vk_digest);
 
-    let public_values = SpartanCircuit::<E>::public_values(&circuit)
-        .map_err(|e| SpartanError::SynthesisError {
-            reason: format!("Circuit does not provide public IO: {e}"),
-        })
-        .unwrap();
+    // The witness still carries the public values it was proved with (a Show
+    // proof's nonce hash among them), which the circuit's inputs may no longer
+    // hold, so they are read back from the stored instance
+    let public_values = load_public_instance::<E>(instance_path)
+        .map_err(|source| ProveError::Load {
+            path: instance_path.to_string(),
+            source,
+        })?
+        .public_values;
 
     // absorb the public values into the reblind_transcript
     reblind_transcript.absorb(b"public_values", &public_values.as_slice());
 
-    let (new_instance, new_witness) = SatisfyingAssignment::reblind_r1cs_instance_and_witness(
-        &randomness,
-        instance,
-        witness,
-        &pk.ck,
-        &mut reblind_transcript,
-    )
-    .unwrap();
-
-    println!(
-        "new instance: {:?}",
-        new_instance
-            .clone()
-            .comm_W_shared
-            .map(|v| v.comm.iter().for_each(|v| println!("v: {:?}", v.affine())))
-    );
+    let t0 = Instant::now();
+    let (new_instance, new_witness) = {
+        let _section = section(Phase::Reblind);
+        SatisfyingAssignment::reblind_r1cs_instance_and_witness(
+            &randomness,
+            instance,
+            witness,
+            &pk.ck,
+            &mut reblind_transcript,
+        )?
+    };
+    let recommit_ms = t0.elapsed().as_millis() as u64;
+    mem_profile::snapshot("reblind");
+
+    info!("Reblinded comm_W_shared: {:?}", new_instance.comm_W_shared);
 
     // generate a witness and proof
-    let res =
-        R1CSSNARK::<E>::prove_inner(&pk, &new_instance, &new_witness, &mut reblind_transcript)
-            .unwrap();
-
-    // Save the instance to file
-    if let Err(e) = save_instance(instance_path, &new_instance) {
-        eprintln!("Failed to save instance: {}", e);
-        std::process::exit(1);
-    }
+    let t0 = Instant::now();
+    let res = {
+        let _section = section(Phase::Prove);
+        R1CSSNARK::<E>::prove_inner(&pk, &new_instance, &new_witness, &mut reblind_transcript)?
+    };
+    let prove_ms = t0.elapsed().as_millis() as u64;
+    mem_profile::snapshot("reblind_prove");
+    info!(
+        "ZK-Spartan reblind recommit: ({} ms) + prove: ({} ms)",
+        recommit_ms, prove_ms
+    );
 
-    // Save the witness to file
-    if let Err(e) = save_witness(witness_path, &new_witness) {
-        eprintln!("Failed to save witness: {}", e);
-        std::process::exit(1);
-    }
+    save_output(instance_path, || {
+        save_instance(instance_path, &new_instance, &public_values)
+    })?;
+    save_output(witness_path, || save_witness(witness_path, &new_witness))?;
+    save_output(proof_path, || save_proof(proof_path, &res))?;
 
-    // Save the proof to file
-    if let Err(e) = save_proof(proof_path, &res) {
-        eprintln!("Failed to save proof: {}", e);
-        std::process::exit(1);
-    }
+    Ok(ReblindTimings {
+        recommit_ms,
+        prove_ms,
+    })
 }
 
 /// Only run the verification part using ZK-Spartan
-pub fn verify_circuit(proof_path: &str, vk_path: &str) {
-    let proof = load_proof(proof_path).expect("load proof failed");
-    let vk = load_verifying_key(vk_path).expect("load verifying key failed");
+pub fn verify_circuit<E: Engine>(proof_path: &str, vk_path: &str) {
+    let proof = load_proof::<E>(proof_path).expect("load proof failed");
+    let vk = load_verifying_key::<E>(vk_path).expect("load verifying key failed");
 
     verify_circuit_with_loaded_data(&proof, &vk);
 }
 
 /// Verify circuit with pre-loaded data - useful for benchmarking to exclude file I/O
-pub fn verify_circuit_with_loaded_data(
+pub fn verify_circuit_with_loaded_data<E: Engine>(
     proof: &R1CSSNARK<E>,
     vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
 ) {
     let t0 = Instant::now();
-    proof.verify(&vk).expect("verify errored");
+    {
+        let _section = section(Phase::Verify);
+        proof.verify(&vk).expect("verify errored");
+    }
+    mem_profile::snapshot("verify");
     let verify_ms = t0.elapsed().as_millis();
     info!(elapsed_ms = verify_ms, "ZK-Spartan verify");
 
@@ -275,25 +647,77 @@ pub fn verify_circuit_with_loaded_data(
 pub fn generate_prepare_witness(
     input_json_path: Option<&std::path::Path>,
 ) -> Result<Vec<Scalar>, SynthesisError> {
-    let json_path = input_json_path
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| {
-            let cwd = current_dir().unwrap();
-            // Try mobile flat path first, fall back to development nested path
-            let mobile_path = cwd.join("jwt_input.json");
-            if mobile_path.exists() {
-                mobile_path
-            } else {
-                cwd.join("../circom/inputs/jwt/default.json")
-            }
-        });
+    let json_bytes = read_prepare_input(input_json_path)?;
+    cached_witness(CircuitKind::Prepare, &json_bytes, || {
+        generate_witness(CircuitKind::Prepare, &json_bytes, || {
+            prepare_witness_from_json(&json_bytes)
+        })
+    })
+}
+
+/// The Prepare witness for synthesis, left as rust-witness output when it can be streamed
+/// See `witness_stream`; otherwise the same as `generate_prepare_witness`.
+pub fn prepare_synthesis_witness(
+    input_json_path: Option<&std::path::Path>,
+) -> Result<SynthesisWitness, SynthesisError> {
+    if !can_stream(CircuitKind::Prepare) {
+        return generate_prepare_witness(input_json_path).map(SynthesisWitness::Scalars);
+    }
+    let json_bytes = read_prepare_input(input_json_path)?;
+    prepare_witness_bigints(&json_bytes).map(SynthesisWitness::Streamed)
+}
+
+/// Generate the Prepare witness ahead of proving and store it as the prefilled witness
+/// Prepare proves over the same input then skip witness generation, whether
+/// or not caching is enabled, until `remove_prefilled_witness` deletes it.
+pub fn prefill_prepare_witness(
+    input_json_path: Option<&std::path::Path>,
+) -> Result<(), SynthesisError> {
+    let json_bytes = read_prepare_input(input_json_path)?;
+    prefill_witness(CircuitKind::Prepare, &json_bytes, || {
+        generate_witness(CircuitKind::Prepare, &json_bytes, || {
+            prepare_witness_from_json(&json_bytes)
+        })
+    })
+}
+
+fn read_prepare_input(
+    input_json_path: Option<&std::path::Path>,
+) -> Result<Vec<u8>, SynthesisError> {
+    let json_path = input_json_path.map(|p| p.to_path_buf()).unwrap_or_else(|| {
+        let documents = documents_dir();
+        // Try mobile flat path first, fall back to development nested path
+        let mobile_path = documents.join("jwt_input.json");
+        if mobile_path.exists() {
+            mobile_path
+        } else {
+            circuits_dir().join("inputs/jwt/default.json")
+        }
+    });
 
     info!("Loading prepare inputs from {}", json_path.display());
 
-    let json_file = File::open(&json_path).map_err(|_| SynthesisError::AssignmentMissing)?;
+    fs::read(&json_path).map_err(|_| SynthesisError::AssignmentMissing)
+}
+
+fn prepare_witness_from_json(json_bytes: &[u8]) -> Result<Vec<Scalar>, SynthesisError> {
+    convert_bigint_to_scalar(prepare_witness_bigints(json_bytes)?)
+}
 
+fn prepare_witness_bigints(json_bytes: &[u8]) -> Result<Vec<BigInt>, SynthesisError> {
     let json_value: Value =
-        serde_json::from_reader(json_file).map_err(|_| SynthesisError::AssignmentMissing)?;
+        serde_json::from_slice(json_bytes).map_err(|_| SynthesisError::AssignmentMissing)?;
+
+    // The witness must come from the circuit size the R1CS and keys were loaded for
+    let variant = JwtVariant::for_input(&json_value).unwrap_or_default();
+    if variant != jwt_variant() {
+        error!(
+            "Input was built for {} but the active circuit is {}",
+            variant.name(),
+            jwt_variant().name()
+        );
+        return Err(SynthesisError::AssignmentMissing);
+    }
 
     // Parse inputs using declarative field definitions
     let inputs = parse_jwt_inputs(&json_value)?;
@@ -301,9 +725,12 @@ pub fn generate_prepare_witness(
     // Generate witness using native Rust (rust-witness)
     info!("Generating witness using native Rust (rust-witness)...");
     let t0 = Instant::now();
-    let witness_bigint = jwt_witness(inputs);
+    let witness_bigint = {
+        let _section = section(Phase::WitnessGen);
+        variant.witness(inputs)?
+    };
     info!("rust-witness time: {} ms", t0.elapsed().as_millis());
+    mem_profile::snapshot("witness_gen");
 
-    let witness: Vec<Scalar> = convert_bigint_to_scalar(witness_bigint)?;
-    Ok(witness)
+    Ok(witness_bigint)
 }
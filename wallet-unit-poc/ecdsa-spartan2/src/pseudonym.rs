@@ -0,0 +1,149 @@
+//! Verifier-scoped pseudonyms derived from the device key binding.
+//!
+//! A service that wants to recognize returning holders gets a pseudonym
+//! `Poseidon(deviceKeyX, deviceKeyY, scope)`, where `scope` is derived from the
+//! service's identifier. The Pseudonym circuit (`pseudonym.circom`) computes it
+//! from the key-binding values it shares with Prepare, so the pseudonym belongs
+//! to the credential the issuer signed, and outputs it with the scope:
+//!
+//! ```text
+//! pseudonym domain tag || scope || pseudonym
+//! ```
+//!
+//! `PseudonymCircuit` constrains both to the circuit's signals, and the device
+//! key the circuit hashed to the shared key binding.
+//!
+//! The same holder gets the same pseudonym from one service every time and
+//! unrelated pseudonyms from different services; the device key itself is not
+//! revealed.
+
+use std::str::FromStr;
+
+use ff::PrimeField;
+use rust_witness::BigInt;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    domain::domain_tag_for, hashing::poseidon, utils::bigint_to_scalar,
+    witness::le_bytes_to_decimal, Scalar,
+};
+
+/// Name the Pseudonym circuit's domain tag is derived from
+pub const PSEUDONYM_CIRCUIT: &str = "pseudonym";
+
+/// Public values of a Pseudonym proof, domain tag included
+pub const PSEUDONYM_PUBLIC_VALUES: usize = 3;
+
+/// Scope of the service identified by `verifier_id` (e.g. its origin)
+pub fn verifier_scope(verifier_id: &str) -> Scalar {
+    let digest = Sha256::digest(format!("{}/{}", PSEUDONYM_CIRCUIT, verifier_id));
+    // 31 bytes always fit below the field modulus
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    repr.as_mut()[..31].copy_from_slice(&digest[..31]);
+    Scalar::from_repr(repr).unwrap()
+}
+
+/// The pseudonym of the device key `(x, y)` under `scope`
+pub fn derive_pseudonym(x: Scalar, y: Scalar, scope: Scalar) -> Result<Scalar, String> {
+    poseidon(&[x, y, scope])
+}
+
+/// What a verified Pseudonym proof states
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PseudonymStatement {
+    pub scope: Scalar,
+    pub pseudonym: Scalar,
+}
+
+impl PseudonymStatement {
+    /// Statement for the device key in a Show input, scoped to `verifier_id`
+    pub fn from_show_input(show_input: &Value, verifier_id: &str) -> Result<Self, String> {
+        let coordinate = |name: &str| {
+            show_input
+                .get(name)
+                .and_then(Value::as_str)
+                .and_then(|s| BigInt::from_str(s).ok())
+                .ok_or_else(|| format!("show input has no valid {}", name))
+                .and_then(|b| bigint_to_scalar(b).map_err(|e| format!("{}: {}", name, e)))
+        };
+        let scope = verifier_scope(verifier_id);
+        Ok(Self {
+            scope,
+            pseudonym: derive_pseudonym(
+                coordinate("deviceKeyX")?,
+                coordinate("deviceKeyY")?,
+                scope,
+            )?,
+        })
+    }
+
+    /// The Pseudonym circuit's public values, domain tag first
+    pub fn to_public_values(&self) -> Vec<Scalar> {
+        vec![
+            domain_tag_for(PSEUDONYM_CIRCUIT),
+            self.scope,
+            self.pseudonym,
+        ]
+    }
+
+    /// Recover the statement from a verified Pseudonym proof's public values
+    pub fn from_public_values(values: &[Scalar]) -> Result<Self, String> {
+        let [tag, scope, pseudonym] = values else {
+            return Err(format!(
+                "expected 3 Pseudonym public values, got {}",
+                values.len()
+            ));
+        };
+        if *tag != domain_tag_for(PSEUDONYM_CIRCUIT) {
+            return Err("pseudonym proof was not made for this domain".to_string());
+        }
+        Ok(Self {
+            scope: *scope,
+            pseudonym: *pseudonym,
+        })
+    }
+}
+
+/// Pseudonym circuit input for the device key and claim in `show_input`
+/// The claim is carried along so the proof shares the Show proof's witnesses.
+pub fn pseudonym_input(show_input: &Value, verifier_id: &str) -> Result<Value, String> {
+    let field = |name: &str| {
+        show_input
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("show input has no {}", name))
+    };
+    let statement = PseudonymStatement::from_show_input(show_input, verifier_id)?;
+    let decimal = |scalar: &Scalar| le_bytes_to_decimal(scalar.to_repr().as_ref());
+    Ok(json!({
+        "deviceKeyX": field("deviceKeyX")?,
+        "deviceKeyY": field("deviceKeyY")?,
+        "claim": field("claim")?,
+        "scope": decimal(&statement.scope),
+        "pseudonym": decimal(&statement.pseudonym),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudonyms_are_scoped() {
+        let show_input = json!({
+            "deviceKeyX": "11",
+            "deviceKeyY": "22",
+            "claim": ["91"],
+        });
+        let a = PseudonymStatement::from_show_input(&show_input, "https://a.example").unwrap();
+        let again = PseudonymStatement::from_show_input(&show_input, "https://a.example").unwrap();
+        let b = PseudonymStatement::from_show_input(&show_input, "https://b.example").unwrap();
+        assert_eq!(a, again);
+        assert_ne!(a.pseudonym, b.pseudonym);
+
+        let values = a.to_public_values();
+        assert_eq!(PseudonymStatement::from_public_values(&values), Ok(a));
+        assert!(PseudonymStatement::from_public_values(&values[1..]).is_err());
+    }
+}
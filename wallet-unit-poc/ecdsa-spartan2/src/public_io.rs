@@ -0,0 +1,101 @@
+//! Public values constrained to the circom signals that carry them.
+//!
+//! A verifier checks a proof against `SpartanCircuit::public_values`, but a
+//! value only says something about the witness if `synthesize` allocates it as
+//! a public input and constrains it to the circuit. The circom circuits mark
+//! the signals a statement is about as public (`component main {public[...]}`)
+//! so that `synthesize_checked` returns them: the outputs first, then the
+//! public inputs in the order the template declares them. They stay private
+//! in Spartan; `alloc_public_values` allocates the Spartan public inputs and
+//! `enforce_equal` ties each one to its signal, and each shared witness to the
+//! signal the circom circuit reads it from.
+//!
+//! The domain tag leading every circuit's public values is a label rather than
+//! a fact about the witness, so it is allocated but left unconstrained; the
+//! verifier compares it with `check_domain`.
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+/// Allocate `count` public inputs, valued from `values` when proving
+/// During setup `values` is `None`; only the count matters then.
+pub fn alloc_public_values<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    values: Option<&[F]>,
+    count: usize,
+) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+    if values.is_some_and(|values| values.len() != count) {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    (0..count)
+        .map(|i| {
+            AllocatedNum::alloc_input(cs.namespace(|| format!("PublicValue{i}")), || {
+                values
+                    .and_then(|values| values.get(i).copied())
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })
+        })
+        .collect()
+}
+
+/// The `count` signals from `start`, failing if the circuit returned fewer
+pub fn signal_slice<F: PrimeField>(
+    signals: &[AllocatedNum<F>],
+    start: usize,
+    count: usize,
+) -> Result<&[AllocatedNum<F>], SynthesisError> {
+    signals
+        .get(start..start + count)
+        .ok_or(SynthesisError::Unsatisfiable)
+}
+
+/// Constrain `a` and `b` to be equal, pairwise
+pub fn enforce_equal<F: PrimeField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    name: &str,
+    a: &[AllocatedNum<F>],
+    b: &[AllocatedNum<F>],
+) -> Result<(), SynthesisError> {
+    if a.len() != b.len() {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    for (i, (a, b)) in a.iter().zip(b).enumerate() {
+        cs.enforce(
+            || format!("{name}{i} is bound"),
+            |lc| lc + a.get_variable(),
+            |lc| lc + CS::one(),
+            |lc| lc + b.get_variable(),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scalar;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+
+    #[test]
+    fn test_public_values_are_bound_to_signals() {
+        let values = [Scalar::from(3u64), Scalar::from(5u64)];
+        let bind = |signal_values: [Scalar; 2]| {
+            let mut cs = TestConstraintSystem::<Scalar>::new();
+            let signals = signal_values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| AllocatedNum::alloc(cs.namespace(|| format!("s{i}")), || Ok(*v)))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            let public = alloc_public_values(&mut cs, Some(&values), 2).unwrap();
+            enforce_equal(&mut cs, "Value", &public, &signals).unwrap();
+            cs.is_satisfied()
+        };
+        assert!(bind(values));
+        assert!(!bind([values[0], Scalar::from(6u64)]));
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        assert!(alloc_public_values(&mut cs, Some(&values), 3).is_err());
+        assert!(signal_slice::<Scalar>(&[], 0, 1).is_err());
+    }
+}
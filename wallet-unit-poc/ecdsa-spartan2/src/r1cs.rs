@@ -0,0 +1,538 @@
+//! Checked loading of circom `.r1cs` files.
+//!
+//! circom-scotia's reader unwraps on malformed input, so the file is first walked
+//! with bounds-checked reads: header fields, every constraint term, and every
+//! wire index are validated before the bytes are handed over. `load_r1cs_checked`
+//! streams the file through `read_r1cs_header` for this, reading only the small
+//! sections whole, so a circuit of hundreds of megabytes is not held in memory
+//! just to be validated; `parse_r1cs_header` does the same on bytes in memory.
+//!
+//! Besides the header and constraints, circom 2.x writes a wire-to-label map
+//! (section 3) and, for circuits with `pragma custom_templates`, the custom
+//! gates and their uses (sections 4 and 5). The map is checked against the wire
+//! count and can be read with `parse_wire_map`. Custom gates are only defined
+//! for PLONK and their constraints are missing from the R1CS, so a file that
+//! uses any is rejected as `FormatError::Unsupported` rather than proved
+//! without them. Unknown section types are skipped, as circom-scotia does.
+//!
+//! A circuit only the asset source has (see `asset_source`) is validated from
+//! the bundled bytes, then written to a temporary file in the documents
+//! directory for circom-scotia, which reads from a path, and removed once loaded.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    panic,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use circom_scotia::{r1cs::R1CS, reader::load_r1cs};
+
+use crate::{
+    asset_source::{asset_source, read_asset},
+    binfile::{malformed, unsupported, BinFile, FormatError, Reader},
+    layout::{circuits_dir, documents_dir},
+    Scalar,
+};
+
+pub(crate) const MAGIC: &[u8; 4] = b"r1cs";
+pub(crate) const HEADER_SECTION: u32 = 1;
+pub(crate) const CONSTRAINTS_SECTION: u32 = 2;
+const WIRE_MAP_SECTION: u32 = 3;
+const CUSTOM_GATES_LIST_SECTION: u32 = 4;
+const CUSTOM_GATES_USES_SECTION: u32 = 5;
+
+/// The `.r1cs` format version circom writes
+const SUPPORTED_VERSION: u32 = 1;
+
+/// Largest header or custom gates list section `read_r1cs_header` reads whole
+const MAX_SMALL_SECTION_BYTES: u64 = 1 << 20;
+
+/// Field element width of the T256 scalar field
+pub(crate) const FIELD_SIZE: usize = 32;
+
+/// Header fields of an `.r1cs` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csHeader {
+    pub version: u32,
+    pub field_size: usize,
+    /// Field modulus, little-endian
+    pub prime: Vec<u8>,
+    pub num_wires: u32,
+    pub num_pub_out: u32,
+    pub num_pub_in: u32,
+    pub num_prv_in: u32,
+    pub num_labels: u64,
+    pub num_constraints: u32,
+    /// Whether the file carries the wire-to-label map
+    pub has_wire_map: bool,
+}
+
+/// Validate the structure of an `.r1cs` file without building the constraint system
+pub fn parse_r1cs_header(bytes: &[u8]) -> Result<R1csHeader, FormatError> {
+    let file = BinFile::parse(bytes, MAGIC)?;
+    let uses = file
+        .section(CUSTOM_GATES_USES_SECTION)
+        .map_or(Ok(0), |payload| Reader::new(payload).u32())?;
+    let parsed = check_header(
+        file.version,
+        file.require_section(HEADER_SECTION, "header")?,
+        file.section(CUSTOM_GATES_LIST_SECTION),
+        uses,
+        file.section(WIRE_MAP_SECTION).map(|map| map.len() as u64),
+    )?;
+
+    let mut constraints = file.require_section(CONSTRAINTS_SECTION, "constraints")?;
+    let len = constraints.len() as u64;
+    check_constraints(&mut constraints, len, &parsed)?;
+    Ok(parsed)
+}
+
+/// `parse_r1cs_header` for the file at `path`, without reading it into memory
+/// Only the section table and the header and custom gate sections, at most
+/// `MAX_SMALL_SECTION_BYTES` each, are read whole; the constraints are walked
+/// through a buffered reader.
+pub fn read_r1cs_header(path: &Path) -> Result<R1csHeader, FormatError> {
+    let mut file = BufReader::new(File::open(path)?);
+    let file_len = file.get_ref().metadata()?.len();
+
+    if read_array::<4>(&mut file)? != *MAGIC {
+        return Err(malformed("missing 'r1cs' magic"));
+    }
+    let version = u32::from_le_bytes(read_array(&mut file)?);
+    let num_sections = u32::from_le_bytes(read_array(&mut file)?);
+
+    // (type, offset, size) of each section; not preallocated, the count is untrusted
+    let mut sections = Vec::new();
+    let mut pos = 12u64;
+    for _ in 0..num_sections {
+        let section_type = u32::from_le_bytes(read_array(&mut file)?);
+        let size = u64::from_le_bytes(read_array(&mut file)?);
+        pos += 12;
+        if size > file_len.saturating_sub(pos) {
+            return Err(malformed(format!(
+                "section of {} bytes at offset {} runs past the end of the file",
+                size, pos
+            )));
+        }
+        sections.push((section_type, pos, size));
+        pos += size;
+        file.seek(SeekFrom::Start(pos))?;
+    }
+    let find = |section_type: u32| {
+        sections
+            .iter()
+            .find(|(ty, _, _)| *ty == section_type)
+            .map(|&(_, offset, size)| (offset, size))
+    };
+    // The first `max` bytes of a section, or `None` if the file has none
+    let mut read_prefix = |section_type: u32, max: u64| -> Result<Option<Vec<u8>>, FormatError> {
+        let Some((offset, size)) = find(section_type) else {
+            return Ok(None);
+        };
+        file.seek(SeekFrom::Start(offset))?;
+        let mut payload = vec![0; size.min(max) as usize];
+        file.read_exact(&mut payload).map_err(truncated)?;
+        Ok(Some(payload))
+    };
+    for (section_type, name) in [
+        (HEADER_SECTION, "header"),
+        (CUSTOM_GATES_LIST_SECTION, "custom gates list"),
+    ] {
+        if find(section_type).is_some_and(|(_, size)| size > MAX_SMALL_SECTION_BYTES) {
+            return Err(malformed(format!("{} section is too large", name)));
+        }
+    }
+
+    let header = read_prefix(HEADER_SECTION, MAX_SMALL_SECTION_BYTES)?
+        .ok_or_else(|| malformed("missing header section"))?;
+    let gates = read_prefix(CUSTOM_GATES_LIST_SECTION, MAX_SMALL_SECTION_BYTES)?;
+    let uses = read_prefix(CUSTOM_GATES_USES_SECTION, 4)?
+        .map_or(Ok(0), |payload| Reader::new(&payload).u32())?;
+    let parsed = check_header(
+        version,
+        &header,
+        gates.as_deref(),
+        uses,
+        find(WIRE_MAP_SECTION).map(|(_, size)| size),
+    )?;
+
+    let (offset, size) =
+        find(CONSTRAINTS_SECTION).ok_or_else(|| malformed("missing constraints section"))?;
+    file.seek(SeekFrom::Start(offset))?;
+    check_constraints(&mut file.take(size), size, &parsed)?;
+    Ok(parsed)
+}
+
+/// Check the header section and the custom gates, given the wire map's length if any
+fn check_header(
+    version: u32,
+    header: &[u8],
+    custom_gates: Option<&[u8]>,
+    custom_gate_uses: u32,
+    wire_map_len: Option<u64>,
+) -> Result<R1csHeader, FormatError> {
+    if version != SUPPORTED_VERSION {
+        return Err(unsupported(format!(
+            "r1cs format version {}, expected {}",
+            version, SUPPORTED_VERSION
+        )));
+    }
+
+    let mut header = Reader::new(header);
+    let field_size = header.u32()? as usize;
+    if field_size != FIELD_SIZE {
+        return Err(malformed(format!(
+            "field size {} does not match the {}-byte scalar field",
+            field_size, FIELD_SIZE
+        )));
+    }
+    check_custom_gates(custom_gates, custom_gate_uses, field_size)?;
+    let parsed = R1csHeader {
+        version,
+        field_size,
+        prime: header.take(field_size)?.to_vec(),
+        num_wires: header.u32()?,
+        num_pub_out: header.u32()?,
+        num_pub_in: header.u32()?,
+        num_prv_in: header.u32()?,
+        num_labels: header.u64()?,
+        num_constraints: header.u32()?,
+        has_wire_map: wire_map_len.is_some(),
+    };
+
+    let io_wires =
+        1u64 + parsed.num_pub_out as u64 + parsed.num_pub_in as u64 + parsed.num_prv_in as u64;
+    if io_wires > parsed.num_wires as u64 {
+        return Err(malformed(format!(
+            "{} input/output wires exceed the {} declared wires",
+            io_wires, parsed.num_wires
+        )));
+    }
+
+    if let Some(len) = wire_map_len {
+        if len != parsed.num_wires as u64 * 8 {
+            return Err(malformed(format!(
+                "wire map holds {} bytes for {} wires",
+                len, parsed.num_wires
+            )));
+        }
+    }
+    Ok(parsed)
+}
+
+/// Walk the `len`-byte constraints section, checking every term and wire index
+fn check_constraints(
+    constraints: &mut impl Read,
+    len: u64,
+    parsed: &R1csHeader,
+) -> Result<(), FormatError> {
+    let mut remaining = len;
+    let mut coefficient = [0u8; FIELD_SIZE];
+    for constraint in 0..parsed.num_constraints {
+        // A, B, and C linear combinations
+        for _ in 0..3 {
+            let num_terms = u32::from_le_bytes(read_array(constraints)?) as u64;
+            remaining = remaining.saturating_sub(4);
+            let terms_len = num_terms.saturating_mul(4 + FIELD_SIZE as u64);
+            if terms_len > remaining {
+                return Err(malformed(format!(
+                    "constraint {} declares {} terms past the end of the section",
+                    constraint, num_terms
+                )));
+            }
+            remaining -= terms_len;
+            for _ in 0..num_terms {
+                let wire = u32::from_le_bytes(read_array(constraints)?);
+                if wire >= parsed.num_wires {
+                    return Err(malformed(format!(
+                        "constraint {} references wire {} of {}",
+                        constraint, wire, parsed.num_wires
+                    )));
+                }
+                constraints
+                    .read_exact(&mut coefficient)
+                    .map_err(truncated)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], FormatError> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes).map_err(truncated)?;
+    Ok(bytes)
+}
+
+fn truncated(e: io::Error) -> FormatError {
+    match e.kind() {
+        io::ErrorKind::UnexpectedEof => malformed("unexpected end of data"),
+        _ => FormatError::Io(e),
+    }
+}
+
+/// Reject files whose circuit uses custom gates, naming them
+fn check_custom_gates(
+    list: Option<&[u8]>,
+    uses: u32,
+    field_size: usize,
+) -> Result<(), FormatError> {
+    let gates = list.map_or(Ok(0), |payload| Reader::new(payload).u32())?;
+    if gates == 0 && uses == 0 {
+        return Ok(());
+    }
+
+    let names = list
+        .map(|payload| custom_gate_names(payload, field_size))
+        .unwrap_or_default();
+    Err(unsupported(format!(
+        "circuit uses {} custom gate template(s){} in {} place(s); custom gates have no \
+         R1CS form, so compile without `pragma custom_templates`",
+        gates,
+        if names.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", names.join(", "))
+        },
+        uses
+    )))
+}
+
+/// Template names of the custom gates list, as far as they can be read
+/// Each entry is a NUL-terminated name, a u32 parameter count and the parameters.
+fn custom_gate_names(payload: &[u8], field_size: usize) -> Vec<String> {
+    let mut reader = Reader::new(payload);
+    let count = reader.u32().unwrap_or_default();
+    let mut names = Vec::new();
+    for _ in 0..count {
+        let rest = &payload[payload.len() - reader.remaining()..];
+        let Some(len) = rest.iter().position(|b| *b == 0) else {
+            break;
+        };
+        let entry = reader.take(len + 1).and_then(|name| {
+            let params = reader.u32()? as usize;
+            reader.take(params.saturating_mul(field_size))?;
+            Ok(String::from_utf8_lossy(&name[..len]).into_owned())
+        });
+        match entry {
+            Ok(name) => names.push(name),
+            Err(_) => break,
+        }
+    }
+    names
+}
+
+/// Label of every wire, from the wire-to-label map (section 3)
+pub fn parse_wire_map(bytes: &[u8]) -> Result<Vec<u64>, FormatError> {
+    let header = parse_r1cs_header(bytes)?;
+    let file = BinFile::parse(bytes, MAGIC)?;
+    let mut map = Reader::new(file.require_section(WIRE_MAP_SECTION, "wire map")?);
+    (0..header.num_wires).map(|_| map.u64()).collect()
+}
+
+/// Load an `.r1cs` file, returning an error instead of panicking on malformed input
+pub fn load_r1cs_checked(path: &Path) -> Result<R1CS<Scalar>, FormatError> {
+    if let Some(asset) = bundled_circuit(path) {
+        return load_bundled_r1cs(&asset);
+    }
+    read_r1cs_header(path)?;
+
+    // Anything circom-scotia still rejects after validation is reported, not propagated
+    panic::catch_unwind(|| load_r1cs(path))
+        .map_err(|_| malformed(format!("failed to load r1cs '{}'", path.display())))
+}
+
+/// Name to ask the asset source for, if the circuit file at `path` exists only there
+pub(crate) fn bundled_circuit(path: &Path) -> Option<String> {
+    if asset_source().is_none() || path.exists() {
+        return None;
+    }
+    let relative = path.strip_prefix(circuits_dir()).ok()?;
+    Some(relative.to_string_lossy().into_owned())
+}
+
+fn load_bundled_r1cs(asset: &str) -> Result<R1CS<Scalar>, FormatError> {
+    static LOADS: AtomicU64 = AtomicU64::new(0);
+
+    let bytes = read_asset(asset)?;
+    parse_r1cs_header(&bytes)?;
+    let tmp = documents_dir().join(format!(
+        "asset_{}_{}.r1cs",
+        std::process::id(),
+        LOADS.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&tmp, &bytes)?;
+    drop(bytes);
+
+    let r1cs = panic::catch_unwind(|| load_r1cs(&tmp))
+        .map_err(|_| malformed(format!("failed to load r1cs asset '{}'", asset)));
+    let _ = fs::remove_file(&tmp);
+    r1cs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Header section of a file with `num_wires` wires, one of them a public input
+    fn header(field_size: u32, num_wires: u32, num_constraints: u32) -> Vec<u8> {
+        let mut header = field_size.to_le_bytes().to_vec();
+        header.extend(vec![0u8; field_size as usize]);
+        for value in [num_wires, 0, 0, 1] {
+            header.extend(value.to_le_bytes());
+        }
+        header.extend(2u64.to_le_bytes());
+        header.extend(num_constraints.to_le_bytes());
+        header
+    }
+
+    /// A constraint `wire * wire = wire`
+    fn constraint(wire: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for _ in 0..3 {
+            bytes.extend(1u32.to_le_bytes());
+            bytes.extend(wire.to_le_bytes());
+            bytes.extend([1u8; FIELD_SIZE]);
+        }
+        bytes
+    }
+
+    fn container(version: u32, sections: Vec<(u32, Vec<u8>)>) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(version.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (ty, payload) in sections {
+            bytes.extend(ty.to_le_bytes());
+            bytes.extend((payload.len() as u64).to_le_bytes());
+            bytes.extend(payload);
+        }
+        bytes
+    }
+
+    /// An `.r1cs` with one wire beyond the constant and no constraints, plus `extra` sections
+    fn r1cs_bytes(extra: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut sections = vec![
+            (HEADER_SECTION, header(FIELD_SIZE as u32, 2, 0)),
+            (CONSTRAINTS_SECTION, vec![]),
+        ];
+        sections.extend(extra.iter().cloned());
+        container(SUPPORTED_VERSION, sections)
+    }
+
+    /// `read_r1cs_header` of `bytes` written to a file
+    fn read_from_file(bytes: &[u8]) -> Result<R1csHeader, FormatError> {
+        static FILES: AtomicU64 = AtomicU64::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "r1cs_test_{}_{}.r1cs",
+            std::process::id(),
+            FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&path, bytes).unwrap();
+        let read = read_r1cs_header(&path);
+        let _ = fs::remove_file(&path);
+        read
+    }
+
+    /// `parse_r1cs_header` of `bytes`, checking `read_r1cs_header` agrees on it
+    fn parse_both(bytes: &[u8]) -> Result<R1csHeader, FormatError> {
+        let parsed = parse_r1cs_header(bytes);
+        let read = read_from_file(bytes);
+        match (&parsed, &read) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(a), Err(b)) => {
+                assert_eq!(std::mem::discriminant(a), std::mem::discriminant(b))
+            }
+            _ => panic!("bounded read disagrees: {:?} vs {:?}", parsed, read),
+        }
+        parsed
+    }
+
+    #[test]
+    fn test_r1cs_sections() {
+        let wire_map: Vec<u8> = [0u64, 1].iter().flat_map(|l| l.to_le_bytes()).collect();
+        let no_gates = 0u32.to_le_bytes().to_vec();
+        let bytes = r1cs_bytes(&[
+            (WIRE_MAP_SECTION, wire_map),
+            (CUSTOM_GATES_LIST_SECTION, no_gates.clone()),
+            (CUSTOM_GATES_USES_SECTION, no_gates),
+            (42, vec![1, 2, 3]),
+        ]);
+        assert!(parse_both(&bytes).unwrap().has_wire_map);
+        assert_eq!(parse_wire_map(&bytes).unwrap(), [0, 1]);
+
+        let mut gates = 1u32.to_le_bytes().to_vec();
+        gates.extend(b"CMul\0");
+        gates.extend(0u32.to_le_bytes());
+        let err = parse_both(&r1cs_bytes(&[(CUSTOM_GATES_LIST_SECTION, gates)]));
+        assert!(matches!(err, Err(FormatError::Unsupported(msg)) if msg.contains("CMul")));
+
+        let short_map = r1cs_bytes(&[(WIRE_MAP_SECTION, vec![0; 8])]);
+        assert!(matches!(
+            parse_both(&short_map),
+            Err(FormatError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_header_validation() {
+        let file = |version, header, constraints| {
+            container(
+                version,
+                vec![(HEADER_SECTION, header), (CONSTRAINTS_SECTION, constraints)],
+            )
+        };
+        let is_malformed =
+            |bytes: Vec<u8>| matches!(parse_both(&bytes), Err(FormatError::Malformed(_)));
+
+        let valid = parse_both(&file(1, header(32, 2, 1), constraint(1))).unwrap();
+        assert_eq!((valid.num_wires, valid.num_constraints), (2, 1));
+
+        let mut bad_magic = file(1, header(32, 2, 0), vec![]);
+        bad_magic[0] = b'x';
+        assert!(is_malformed(bad_magic));
+        assert!(matches!(
+            parse_both(&file(2, header(32, 2, 0), vec![])),
+            Err(FormatError::Unsupported(_))
+        ));
+        assert!(is_malformed(file(1, header(16, 2, 0), vec![])));
+        // The constant and the public input need two wires
+        assert!(is_malformed(file(1, header(32, 1, 0), vec![])));
+        assert!(is_malformed(file(1, header(32, 2, 1), constraint(2))));
+        assert!(is_malformed(file(1, header(32, 2, 2), constraint(1))));
+        assert!(is_malformed(
+            file(1, header(32, 2, 0), vec![])[..40].to_vec()
+        ));
+        assert!(is_malformed(container(
+            1,
+            vec![(CONSTRAINTS_SECTION, vec![])]
+        )));
+
+        let mut oversized = constraint(1);
+        oversized[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(is_malformed(file(1, header(32, 2, 1), oversized)));
+    }
+
+    #[test]
+    fn test_bounded_read_rejects_huge_sections() {
+        let mut huge_header = header(32, 2, 0);
+        huge_header.resize(MAX_SMALL_SECTION_BYTES as usize + 1, 0);
+        let bytes = container(
+            SUPPORTED_VERSION,
+            vec![(HEADER_SECTION, huge_header), (CONSTRAINTS_SECTION, vec![])],
+        );
+        assert!(matches!(
+            read_from_file(&bytes),
+            Err(FormatError::Malformed(msg)) if msg.contains("too large")
+        ));
+
+        // A section size past the end of the file is caught before seeking to it
+        let mut truncated = r1cs_bytes(&[]);
+        truncated.truncate(truncated.len() - 1);
+        assert!(matches!(
+            read_from_file(&truncated),
+            Err(FormatError::Malformed(_))
+        ));
+    }
+}
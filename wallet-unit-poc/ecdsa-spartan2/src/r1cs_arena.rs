@@ -0,0 +1,273 @@
+//! Constraint systems loaded into flat arenas for synthesis.
+//!
+//! circom-scotia's `R1CS` keeps three `Vec`s of terms per constraint, so loading
+//! a circuit with a few million constraints makes as many small heap
+//! allocations, all freed again after synthesis; on phones that churn shows up
+//! in both setup and prove time. `ArenaR1cs` holds every term of every linear
+//! combination in one `Vec`, with a second recording where each one ends, and
+//! is parsed straight from the `.r1cs` bytes after `parse_r1cs_header` has
+//! validated them (a bundled circuit needs no temporary file either).
+//! `witness_stream::synthesize_streamed` synthesizes from it into `Variable`s
+//! rather than cloned `AllocatedNum`s.
+//!
+//! Proving synthesizes into a witness generator, which drops every constraint:
+//! the constraints spartan2 proves against are the matrices in the proving key.
+//! There only the wire counts are needed, so `load_wire_counts` keeps those of
+//! each circuit loaded by setup or an earlier prove in this process, and reads
+//! just the header of a circuit not seen yet, or changed since.
+//!
+//! `benches/synthesis_alloc.rs` reports the allocation counts of both loaders
+//! and synthesis paths.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use circom_scotia::r1cs::R1CS;
+use ff::PrimeField;
+
+use crate::{
+    asset_source::read_asset,
+    binfile::{malformed, BinFile, FormatError, Reader},
+    r1cs::{
+        bundled_circuit, parse_r1cs_header, CONSTRAINTS_SECTION, FIELD_SIZE, HEADER_SECTION, MAGIC,
+    },
+    Scalar,
+};
+
+/// One linear combination: `(wire, coefficient)` terms
+pub type Terms<'a> = &'a [(usize, Scalar)];
+
+/// The wires of an R1CS, all a witness generator needs of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireCounts {
+    /// The constant wire and the public signals
+    pub num_inputs: usize,
+    pub num_aux: usize,
+}
+
+/// Length and modification time of a circuit file when its counts were read
+type FileStamp = Option<(u64, SystemTime)>;
+
+static WIRE_COUNTS: Mutex<Option<HashMap<PathBuf, (FileStamp, WireCounts)>>> = Mutex::new(None);
+
+/// An R1CS whose terms live in a single allocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaR1cs {
+    /// The constant wire and the public signals
+    pub num_inputs: usize,
+    pub num_aux: usize,
+    /// A, B and C of each constraint in turn
+    terms: Vec<(usize, Scalar)>,
+    /// End of each linear combination in `terms`
+    ends: Vec<usize>,
+}
+
+impl ArenaR1cs {
+    pub fn wire_counts(&self) -> WireCounts {
+        WireCounts {
+            num_inputs: self.num_inputs,
+            num_aux: self.num_aux,
+        }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        self.ends.len() / 3
+    }
+
+    /// A, B and C of every constraint, in order
+    pub fn constraints(&self) -> impl Iterator<Item = [Terms<'_>; 3]> {
+        self.ends.chunks_exact(3).enumerate().map(|(i, ends)| {
+            let start = if i == 0 { 0 } else { self.ends[3 * i - 1] };
+            [
+                &self.terms[start..ends[0]],
+                &self.terms[ends[0]..ends[1]],
+                &self.terms[ends[1]..ends[2]],
+            ]
+        })
+    }
+}
+
+impl From<R1CS<Scalar>> for ArenaR1cs {
+    fn from(r1cs: R1CS<Scalar>) -> Self {
+        let mut terms = Vec::new();
+        let mut ends = Vec::with_capacity(3 * r1cs.constraints.len());
+        for (a, b, c) in r1cs.constraints {
+            for lc in [a, b, c] {
+                terms.extend(lc);
+                ends.push(terms.len());
+            }
+        }
+        Self {
+            num_inputs: r1cs.num_inputs,
+            num_aux: r1cs.num_aux,
+            terms,
+            ends,
+        }
+    }
+}
+
+/// Parse an `.r1cs` file into an arena, validating it first
+pub fn parse_arena_r1cs(bytes: &[u8]) -> Result<ArenaR1cs, FormatError> {
+    let header = parse_r1cs_header(bytes)?;
+    let file = BinFile::parse(bytes, MAGIC)?;
+    let section = file.require_section(CONSTRAINTS_SECTION, "constraints")?;
+
+    // Every term takes at least a wire index and a coefficient, so this bounds the count
+    let mut terms = Vec::with_capacity(section.len() / (4 + FIELD_SIZE));
+    let mut ends = Vec::with_capacity(3 * header.num_constraints as usize);
+    let mut reader = Reader::new(section);
+    for constraint in 0..header.num_constraints {
+        for _ in 0..3 {
+            let num_terms = reader.u32()?;
+            for _ in 0..num_terms {
+                let wire = reader.u32()? as usize;
+                let mut repr = <Scalar as PrimeField>::Repr::default();
+                repr.as_mut().copy_from_slice(reader.take(FIELD_SIZE)?);
+                let coeff = Option::from(Scalar::from_repr(repr)).ok_or_else(|| {
+                    malformed(format!(
+                        "constraint {} has a coefficient outside the field",
+                        constraint
+                    ))
+                })?;
+                terms.push((wire, coeff));
+            }
+            ends.push(terms.len());
+        }
+    }
+
+    let num_inputs = 1 + header.num_pub_out as usize + header.num_pub_in as usize;
+    Ok(ArenaR1cs {
+        num_inputs,
+        num_aux: header.num_wires as usize - num_inputs,
+        terms,
+        ends,
+    })
+}
+
+/// Wire counts from the header of an `.r1cs` file, without reading its constraints
+pub fn parse_wire_counts(bytes: &[u8]) -> Result<WireCounts, FormatError> {
+    let file = BinFile::parse(bytes, MAGIC)?;
+    let mut header = Reader::new(file.require_section(HEADER_SECTION, "header")?);
+    let field_size = header.u32()? as usize;
+    header.take(field_size)?;
+    let num_wires = header.u32()? as usize;
+    let num_inputs = 1 + header.u32()? as usize + header.u32()? as usize;
+    if num_inputs > num_wires {
+        return Err(malformed(format!(
+            "{} public wires exceed the {} declared wires",
+            num_inputs, num_wires
+        )));
+    }
+    Ok(WireCounts {
+        num_inputs,
+        num_aux: num_wires - num_inputs,
+    })
+}
+
+/// Load an `.r1cs` file into an arena, from the asset source if only it has the file
+pub fn load_arena_r1cs(path: &Path) -> Result<ArenaR1cs, FormatError> {
+    let stamp = file_stamp(path);
+    let arena = parse_arena_r1cs(&read_circuit(path)?)?;
+    remember_wire_counts(path, stamp, arena.wire_counts());
+    Ok(arena)
+}
+
+/// Wire counts of the `.r1cs` file at `path`, from memory unless the file changed
+pub fn load_wire_counts(path: &Path) -> Result<WireCounts, FormatError> {
+    let stamp = file_stamp(path);
+    let known = WIRE_COUNTS
+        .lock()
+        .ok()
+        .and_then(|cache| cache.as_ref()?.get(path).copied());
+    if let Some((known_stamp, counts)) = known {
+        if known_stamp == stamp {
+            return Ok(counts);
+        }
+    }
+    let counts = parse_wire_counts(&read_circuit(path)?)?;
+    remember_wire_counts(path, stamp, counts);
+    Ok(counts)
+}
+
+fn remember_wire_counts(path: &Path, stamp: FileStamp, counts: WireCounts) {
+    if let Ok(mut cache) = WIRE_COUNTS.lock() {
+        cache
+            .get_or_insert_with(HashMap::new)
+            .insert(path.to_path_buf(), (stamp, counts));
+    }
+}
+
+/// `None` for a circuit only the asset source has, which cannot change
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
+fn read_circuit(path: &Path) -> Result<Vec<u8>, FormatError> {
+    Ok(match bundled_circuit(path) {
+        Some(asset) => read_asset(&asset)?,
+        None => fs::read(path)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+
+    #[test]
+    fn test_arena_matches_circom_scotia_layout() {
+        let (one, two) = (Scalar::ONE, Scalar::from(2));
+        let mut bytes = (FIELD_SIZE as u32).to_le_bytes().to_vec();
+        bytes.extend([0u8; FIELD_SIZE]);
+        // 4 wires, 1 public output, 0 public inputs, 1 private input, 4 labels, 2 constraints
+        for value in [4u32, 1, 0, 1] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(4u64.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        let header = bytes;
+
+        let constraints = [
+            (vec![(2, one)], vec![(3, two)], vec![(1, one)]),
+            (vec![(3, one), (0, two)], vec![(0, one)], vec![]),
+        ];
+        let mut section = Vec::new();
+        for (a, b, c) in &constraints {
+            for lc in [a, b, c] {
+                section.extend((lc.len() as u32).to_le_bytes());
+                for (wire, coeff) in lc {
+                    section.extend((*wire as u32).to_le_bytes());
+                    section.extend(coeff.to_repr().as_ref());
+                }
+            }
+        }
+
+        let mut file = MAGIC.to_vec();
+        file.extend(1u32.to_le_bytes());
+        file.extend(2u32.to_le_bytes());
+        for (ty, payload) in [(HEADER_SECTION, header), (CONSTRAINTS_SECTION, section)] {
+            file.extend(ty.to_le_bytes());
+            file.extend((payload.len() as u64).to_le_bytes());
+            file.extend(payload);
+        }
+
+        let arena = parse_arena_r1cs(&file).unwrap();
+        let expected = ArenaR1cs::from(R1CS {
+            num_inputs: 2,
+            num_aux: 2,
+            num_variables: 4,
+            constraints: constraints.to_vec(),
+        });
+        assert_eq!(arena, expected);
+        assert_eq!(parse_wire_counts(&file).unwrap(), arena.wire_counts());
+        assert_eq!(arena.num_constraints(), 2);
+        let last = arena.constraints().last().unwrap().map(<[_]>::len);
+        assert_eq!(last, [2, 1, 0]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Range statements over a numeric claim, proved by the Range circuit.
+//!
+//! The Range circuit (`range.circom`) shares the device key and decoded claim
+//! with Prepare, so once reblinded under the credential's shared blinds its
+//! comm_W_shared equals the Prepare proof's and the range is known to hold for
+//! the claim the issuer signed. It proves `min <= value <= max`, where `value`
+//! is the claim's third JSON string (`["salt","income","52000"]`) read as a
+//! decimal number of at most `MAX_RANGE_DIGITS` digits. Its public values are
+//!
+//! ```text
+//! range domain tag || min || max
+//! ```
+//!
+//! which `RangeCircuit` constrains to the bounds the circuit checked, as it
+//! constrains the shared claim to the one the circuit reads, so a verifier
+//! reads the range from the verified proof rather than trusting the prover's
+//! word for it.
+
+use ff::PrimeField;
+use serde_json::{json, Value};
+
+use crate::{claims::disclosure_value, domain::domain_tag_for, Scalar};
+
+/// Name the Range circuit's domain tag is derived from
+pub const RANGE_CIRCUIT: &str = "range";
+
+/// Public values of a Range proof, domain tag included
+pub const RANGE_PUBLIC_VALUES: usize = 3;
+
+/// Most digits the Range circuit reads from a claim value
+pub const MAX_RANGE_DIGITS: usize = 16;
+
+/// Inclusive bounds proved of a claim value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeStatement {
+    pub min: u64,
+    pub max: u64,
+}
+
+impl RangeStatement {
+    /// Fails if `min > max` or `max` has more than `MAX_RANGE_DIGITS` digits
+    pub fn new(min: u64, max: u64) -> Result<Self, String> {
+        if min > max {
+            return Err(format!("range minimum {} exceeds maximum {}", min, max));
+        }
+        if max >= 10u64.pow(MAX_RANGE_DIGITS as u32) {
+            return Err(format!(
+                "range maximum {} has more than {} digits",
+                max, MAX_RANGE_DIGITS
+            ));
+        }
+        Ok(Self { min, max })
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// The Range circuit's public values, domain tag first
+    pub fn to_public_values(&self) -> Vec<Scalar> {
+        vec![
+            domain_tag_for(RANGE_CIRCUIT),
+            Scalar::from(self.min),
+            Scalar::from(self.max),
+        ]
+    }
+
+    /// Recover the statement from a verified Range proof's public values
+    pub fn from_public_values(values: &[Scalar]) -> Result<Self, String> {
+        let [tag, min, max] = values else {
+            return Err(format!(
+                "expected 3 Range public values, got {}",
+                values.len()
+            ));
+        };
+        if *tag != domain_tag_for(RANGE_CIRCUIT) {
+            return Err("range proof was not made for this domain".to_string());
+        }
+        Self::new(scalar_to_u64(min)?, scalar_to_u64(max)?)
+    }
+}
+
+/// Range circuit input proving `statement` of the claim in `show_input`
+/// Takes the device key and claim from the Show input, so the Range proof
+/// shares the Show proof's witnesses. Fails if the claim value is not a decimal
+/// string or lies outside the range, which the circuit could not prove.
+pub fn range_input(show_input: &Value, statement: &RangeStatement) -> Result<Value, String> {
+    let field = |name: &str| {
+        show_input
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("show input has no {}", name))
+    };
+    let claim = field("claim")?;
+    let value = claim_value(&claim)?;
+    if !statement.contains(value) {
+        return Err(format!(
+            "claim value is outside [{}, {}]",
+            statement.min, statement.max
+        ));
+    }
+
+    Ok(json!({
+        "deviceKeyX": field("deviceKeyX")?,
+        "deviceKeyY": field("deviceKeyY")?,
+        "claim": claim,
+        "rangeMin": statement.min.to_string(),
+        "rangeMax": statement.max.to_string(),
+    }))
+}
+
+/// Numeric value of a decoded disclosure given as circuit input (one byte per entry)
+pub fn claim_value(claim: &Value) -> Result<u64, String> {
+    let value = disclosure_value(claim)?;
+    if value.is_empty() || value.len() > MAX_RANGE_DIGITS || !value.iter().all(u8::is_ascii_digit) {
+        return Err(format!(
+            "claim value is not a decimal number of at most {} digits",
+            MAX_RANGE_DIGITS
+        ));
+    }
+    Ok(value
+        .iter()
+        .fold(0, |acc, digit| acc * 10 + u64::from(digit - b'0')))
+}
+
+fn scalar_to_u64(value: &Scalar) -> Result<u64, String> {
+    let repr = value.to_repr();
+    let (low, high) = repr.as_ref().split_at(8);
+    if high.iter().any(|b| *b != 0) {
+        return Err("range bound does not fit in 64 bits".to_string());
+    }
+    Ok(u64::from_le_bytes(low.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claim(text: &str) -> Value {
+        let mut bytes: Vec<Value> = text.bytes().map(|b| b.to_string().into()).collect();
+        bytes.resize(96, "0".into());
+        Value::Array(bytes)
+    }
+
+    #[test]
+    fn test_range_input_checks_claim_value() {
+        let show_input = json!({
+            "deviceKeyX": "1",
+            "deviceKeyY": "2",
+            "claim": claim(r#"["LpS42b6mbszsa","income","52000"]"#),
+        });
+        assert_eq!(claim_value(&show_input["claim"]), Ok(52_000));
+
+        let input =
+            range_input(&show_input, &RangeStatement::new(50_000, 60_000).unwrap()).unwrap();
+        assert_eq!(input["rangeMin"], "50000");
+        assert_eq!(input["claim"], show_input["claim"]);
+        assert!(range_input(&show_input, &RangeStatement::new(0, 51_999).unwrap()).is_err());
+        assert!(claim_value(&claim(r#"["s","name","DE"]"#)).is_err());
+        assert!(RangeStatement::new(2, 1).is_err());
+    }
+
+    #[test]
+    fn test_public_values_roundtrip() {
+        let statement = RangeStatement::new(18, 65).unwrap();
+        let values = statement.to_public_values();
+        assert_eq!(RangeStatement::from_public_values(&values), Ok(statement));
+        assert!(RangeStatement::from_public_values(&values[1..]).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+//! Containers that wipe shared blinds and witness values when dropped.
+//!
+//! A scalar left in a freed heap page can be recovered from a memory image of
+//! the device, and the shared blinds together with the key-binding point and
+//! birthday claim they hide are exactly what unlinkability rests on. Values
+//! held in `SecretScalar` or `SecretBlinds` are overwritten with zero on drop;
+//! the byte buffers they are read from and written to are held in
+//! `zeroize::Zeroizing`.
+//!
+//! Copies made by code outside this crate are not covered: the `BigInt` witness
+//! from rust-witness, the witness vector circom-scotia takes ownership of, and
+//! the `R1CSWitness` and allocated variables inside spartan2.
+
+use std::{
+    fmt,
+    ops::Deref,
+    ptr,
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use ff::Field;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Overwrite `value` with zero in a way the compiler cannot elide
+fn wipe<F: Field>(value: &mut F) {
+    // SAFETY: `value` is a valid, aligned, exclusive reference
+    unsafe { ptr::write_volatile(value, F::ZERO) };
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// A secret field element, zeroed on drop
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretScalar<F: Field>(F);
+
+impl<F: Field> SecretScalar<F> {
+    pub fn new(value: F) -> Self {
+        Self(value)
+    }
+}
+
+impl<F: Field> Deref for SecretScalar<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: Field> Zeroize for SecretScalar<F> {
+    fn zeroize(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+impl<F: Field> Drop for SecretScalar<F> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<F: Field> ZeroizeOnDrop for SecretScalar<F> {}
+
+impl<F: Field> fmt::Debug for SecretScalar<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar(..)")
+    }
+}
+
+/// Shared blinds, zeroed on drop
+/// Dereferences to the slice `reblind_with_loaded_data` and `save_shared_blinds` take.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretBlinds<F: Field>(Vec<F>);
+
+impl<F: Field> From<Vec<F>> for SecretBlinds<F> {
+    /// Takes the vector's buffer, so no unwiped copy is left behind
+    fn from(blinds: Vec<F>) -> Self {
+        Self(blinds)
+    }
+}
+
+impl<F: Field> FromIterator<F> for SecretBlinds<F> {
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<F: Field> Deref for SecretBlinds<F> {
+    type Target = [F];
+
+    fn deref(&self) -> &[F] {
+        &self.0
+    }
+}
+
+impl<F: Field> Zeroize for SecretBlinds<F> {
+    fn zeroize(&mut self) {
+        self.0.iter_mut().for_each(wipe);
+        self.0.clear();
+    }
+}
+
+impl<F: Field> Drop for SecretBlinds<F> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<F: Field> ZeroizeOnDrop for SecretBlinds<F> {}
+
+impl<F: Field> fmt::Debug for SecretBlinds<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBlinds({} blinds)", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        setup::{load_shared_blinds, save_shared_blinds},
+        Scalar, E,
+    };
+
+    #[test]
+    fn test_zeroize_wipes_values() {
+        let mut scalar = SecretScalar::new(Scalar::from(7u64));
+        scalar.zeroize();
+        assert_eq!(*scalar, Scalar::ZERO);
+
+        let mut blinds: SecretBlinds<Scalar> = (1..=3u64).map(Scalar::from).collect();
+        let buffer = blinds.as_ptr();
+        blinds.zeroize();
+        assert!(blinds.is_empty());
+        // SAFETY: clearing keeps the buffer allocated and its contents in place
+        let wiped = unsafe { std::slice::from_raw_parts(buffer, 3) };
+        assert!(wiped.iter().all(|blind| *blind == Scalar::ZERO));
+    }
+
+    #[test]
+    fn test_debug_does_not_print_values() {
+        let value = Scalar::from(123456789u64);
+        let scalar = SecretScalar::new(value);
+        let blinds: SecretBlinds<Scalar> = vec![value; 2].into();
+        assert_eq!(format!("{:?}", scalar), "SecretScalar(..)");
+        assert_eq!(format!("{:?}", blinds), "SecretBlinds(2 blinds)");
+    }
+
+    #[test]
+    fn test_blinds_round_trip_through_the_saved_file() {
+        let path = std::env::temp_dir().join(format!("secret_blinds_{}.bin", std::process::id()));
+        let path = path.to_string_lossy();
+        let blinds: SecretBlinds<Scalar> = (1..=4u64).map(Scalar::from).collect();
+
+        save_shared_blinds::<E>(&path, &blinds).unwrap();
+        let loaded = load_shared_blinds::<E>(&path).unwrap();
+        let _ = std::fs::remove_file(&*path);
+        assert_eq!(loaded, blinds);
+    }
+}
@@ -1,21 +1,41 @@
 use std::{
-    fs::{create_dir_all, File},
-    io::{BufReader, Cursor, Write},
+    fs::{self, create_dir_all, File},
+    io::Write,
     time::Instant,
 };
 
+use bincode::Options;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
 use spartan2::{
+    errors::SpartanError,
     r1cs::{R1CSWitness, SplitR1CSInstance},
     traits::{circuit::SpartanCircuit, snark::R1CSSNARKTrait, Engine},
     zk_spartan::R1CSSNARK,
 };
 use tracing::info;
 
-use crate::E;
+use crate::{
+    asset_source::read_asset,
+    format::{self, ArtifactKind},
+    key_strip::decode_proving_key,
+    namespace::artifact_path,
+    sealed,
+    secret::SecretBlinds,
+    setup_timing::{save_keys_timed, setup_circuit_keys_timed},
+    transcript::{
+        check_transcript_hash, check_transcript_record, record_transcript_hash,
+        transcript_record_path,
+    },
+    variant::{bundled_key_path, has_stored_key, key_path, stored_key_path},
+};
 use memmap2::MmapOptions;
+use zeroize::Zeroizing;
 
 pub const PREPARE_PROVING_KEY: &str = "keys/prepare_proving.key";
 pub const PREPARE_VERIFYING_KEY: &str = "keys/prepare_verifying.key";
+pub const PREPARE_PARTIAL_PROVING_KEY: &str = "keys/prepare_partial_proving.key";
+pub const PREPARE_PARTIAL_VERIFYING_KEY: &str = "keys/prepare_partial_verifying.key";
 pub const SHOW_PROVING_KEY: &str = "keys/show_proving.key";
 pub const SHOW_VERIFYING_KEY: &str = "keys/show_verifying.key";
 pub const PREPARE_PROOF: &str = "keys/prepare_proof.bin";
@@ -25,13 +45,89 @@ pub const SHOW_PROOF: &str = "keys/show_proof.bin";
 pub const SHOW_WITNESS: &str = "keys/show_witness.bin";
 pub const SHOW_INSTANCE: &str = "keys/show_instance.bin";
 pub const SHARED_BLINDS: &str = "keys/shared_blinds.bin";
+pub const ECDSA_PROVING_KEY: &str = "keys/ecdsa_proving.key";
+pub const ECDSA_VERIFYING_KEY: &str = "keys/ecdsa_verifying.key";
+pub const ECDSA_PROOF: &str = "keys/ecdsa_proof.bin";
+pub const ECDSA_WITNESS: &str = "keys/ecdsa_witness.bin";
+pub const ECDSA_INSTANCE: &str = "keys/ecdsa_instance.bin";
+pub const RANGE_PROVING_KEY: &str = "keys/range_proving.key";
+pub const RANGE_VERIFYING_KEY: &str = "keys/range_verifying.key";
+pub const RANGE_PROOF: &str = "keys/range_proof.bin";
+pub const RANGE_WITNESS: &str = "keys/range_witness.bin";
+pub const RANGE_INSTANCE: &str = "keys/range_instance.bin";
+pub const MEMBERSHIP_PROVING_KEY: &str = "keys/membership_proving.key";
+pub const MEMBERSHIP_VERIFYING_KEY: &str = "keys/membership_verifying.key";
+pub const MEMBERSHIP_PROOF: &str = "keys/membership_proof.bin";
+pub const MEMBERSHIP_WITNESS: &str = "keys/membership_witness.bin";
+pub const MEMBERSHIP_INSTANCE: &str = "keys/membership_instance.bin";
+pub const PSEUDONYM_PROVING_KEY: &str = "keys/pseudonym_proving.key";
+pub const PSEUDONYM_VERIFYING_KEY: &str = "keys/pseudonym_verifying.key";
+pub const PSEUDONYM_PROOF: &str = "keys/pseudonym_proof.bin";
+pub const PSEUDONYM_WITNESS: &str = "keys/pseudonym_witness.bin";
+pub const PSEUDONYM_INSTANCE: &str = "keys/pseudonym_instance.bin";
+pub const SYNTHETIC_PROVING_KEY: &str = "keys/synthetic_proving.key";
+pub const SYNTHETIC_VERIFYING_KEY: &str = "keys/synthetic_verifying.key";
+pub const SYNTHETIC_PROOF: &str = "keys/synthetic_proof.bin";
+pub const SYNTHETIC_WITNESS: &str = "keys/synthetic_witness.bin";
+pub const SYNTHETIC_INSTANCE: &str = "keys/synthetic_instance.bin";
+pub const SYNTHETIC_SHARED_BLINDS: &str = "keys/synthetic_shared_blinds.bin";
+pub const DEMO_PROVING_KEY: &str = "keys/demo_proving.key";
+pub const DEMO_VERIFYING_KEY: &str = "keys/demo_verifying.key";
+pub const DEMO_PROOF: &str = "keys/demo_proof.bin";
+pub const DEMO_WITNESS: &str = "keys/demo_witness.bin";
+pub const DEMO_INSTANCE: &str = "keys/demo_instance.bin";
+pub const DEMO_SHARED_BLINDS: &str = "keys/demo_shared_blinds.bin";
+pub const PERMUTATION_PROVING_KEY: &str = "keys/permutation_proving.key";
+pub const PERMUTATION_VERIFYING_KEY: &str = "keys/permutation_verifying.key";
+pub const PERMUTATION_PROOF: &str = "keys/permutation_proof.bin";
+pub const PERMUTATION_WITNESS: &str = "keys/permutation_witness.bin";
+pub const PERMUTATION_INSTANCE: &str = "keys/permutation_instance.bin";
+
+/// Decode bincode's default (fixint, trailing bytes allowed) encoding with the read
+/// size capped at the input length, so a forged length prefix in a hostile file
+/// fails instead of attempting a huge allocation
+pub fn deserialize_bounded<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, bincode::Error> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(bytes.len() as u64)
+        .deserialize(bytes)
+}
+
+/// Write `bytes` to a temporary file and rename it over `path`
+/// A process killed mid-write (e.g. by the platform watchdog) then leaves the
+/// previous artifact intact instead of a truncated one.
+pub fn write_atomic(path: &str, bytes: &[u8]) -> std::io::Result<()> {
+    let tmp_path = format!("{}.tmp", path);
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
 
-pub fn save_keys(
+pub fn parse_proof<E: Engine>(bytes: &[u8]) -> Result<R1CSSNARK<E>, bincode::Error> {
+    format::decode(ArtifactKind::Proof, bytes)
+}
+
+pub fn parse_instance<E: Engine>(bytes: &[u8]) -> Result<SplitR1CSInstance<E>, bincode::Error> {
+    format::decode(ArtifactKind::Instance, bytes)
+}
+
+pub fn parse_public_instance<E: Engine>(bytes: &[u8]) -> Result<PublicInstance<E>, bincode::Error> {
+    format::decode(ArtifactKind::PublicInstance, bytes)
+}
+
+pub fn parse_witness<E: Engine>(bytes: &[u8]) -> Result<R1CSWitness<E>, bincode::Error> {
+    format::decode(ArtifactKind::Witness, bytes)
+}
+
+pub fn save_keys<E: Engine>(
     pk_path: &str,
     vk_path: &str,
     pk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
     vk: &<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let (pk_path, vk_path) = (&key_path(pk_path), &key_path(vk_path));
     if let Some(parent) = std::path::Path::new(pk_path).parent() {
         create_dir_all(parent)?;
     }
@@ -48,13 +144,14 @@ pub fn save_keys(
     let vk_bytes = bincode::serialize(vk)?;
     let mut vk_file = File::create(vk_path)?;
     vk_file.write_all(&vk_bytes)?;
+    record_transcript_hash(vk_path)?;
     info!("Saved ZK-Spartan verifying key to: {}", vk_path);
 
     Ok(())
 }
 
 #[allow(dead_code)]
-pub fn load_keys(
+pub fn load_keys<E: Engine>(
     pk_path: &str,
     vk_path: &str,
 ) -> Result<
@@ -64,35 +161,63 @@ pub fn load_keys(
     ),
     Box<dyn std::error::Error>,
 > {
-    let pk_file = File::open(pk_path)?;
-    let pk = bincode::deserialize_from(&mut BufReader::new(pk_file))?;
-
-    info!("Loaded ZK-Spartan proving key from: {}", pk_path);
-
-    let vk_file = File::open(vk_path)?;
-    let vk = bincode::deserialize_from(&mut BufReader::new(vk_file))?;
-    info!("Loaded ZK-Spartan verifying key from: {}", vk_path);
+    let (pk_asset, vk_asset) = (bundled_key_path(pk_path), bundled_key_path(vk_path));
+    let (pk_path, vk_path) = (&stored_key_path(pk_path), &stored_key_path(vk_path));
+    // The two keys are single bincode values in spartan2's own layout, so they
+    // decode concurrently rather than in chunks. bincode errors are `Send` and
+    // wrap I/O errors, so each side keeps its source for the caller.
+    let (pk, vk) = rayon::join(
+        || -> Result<_, bincode::Error> {
+            let bytes = match &pk_asset {
+                Some(asset) => read_asset(asset),
+                None => fs::read(pk_path),
+            }?;
+            let pk = decode_proving_key(&bytes)?;
+            info!("Loaded ZK-Spartan proving key from: {}", pk_path);
+            Ok(pk)
+        },
+        || -> Result<_, bincode::Error> {
+            let bytes = match &vk_asset {
+                Some(asset) => read_asset(asset),
+                None => fs::read(vk_path),
+            }?;
+            let vk = deserialize_bounded(&bytes)?;
+            info!("Loaded ZK-Spartan verifying key from: {}", vk_path);
+            Ok(vk)
+        },
+    );
 
-    Ok((pk, vk))
+    Ok((pk?, vk?))
 }
 
-pub fn load_proving_key(
+/// Load a proving key through a read-only mapping of `pk_path`
+/// A key only the asset source has is read from it whole instead.
+pub fn load_proving_key<E: Engine>(
     pk_path: &str,
 ) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey, Box<dyn std::error::Error>> {
-    let pk_file = File::open(pk_path)?;
+    if let Some(asset) = bundled_key_path(pk_path) {
+        return Ok(decode_proving_key(&read_asset(&asset)?)?);
+    }
+    let pk_file = File::open(stored_key_path(pk_path))?;
     let pk_mmap = unsafe { MmapOptions::new().map(&pk_file)? };
-    let pk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey =
-        bincode::deserialize_from(Cursor::new(&pk_mmap[..]))?;
+    let pk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey = decode_proving_key(&pk_mmap[..])?;
     Ok(pk)
 }
 
-pub fn load_verifying_key(
+pub fn load_verifying_key<E: Engine>(
     vk_path: &str,
 ) -> Result<<R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey, Box<dyn std::error::Error>> {
-    let vk_file = File::open(vk_path)?;
+    if let Some(asset) = bundled_key_path(vk_path) {
+        let record = read_asset(&transcript_record_path(&asset))
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+        check_transcript_record(&asset, record)?;
+        return Ok(deserialize_bounded(&read_asset(&asset)?)?);
+    }
+    let vk_path = stored_key_path(vk_path);
+    check_transcript_hash(&vk_path)?;
+    let vk_file = File::open(&vk_path)?;
     let vk_mmap = unsafe { MmapOptions::new().map(&vk_file)? };
-    let vk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey =
-        bincode::deserialize_from(Cursor::new(&vk_mmap[..]))?;
+    let vk: <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey = deserialize_bounded(&vk_mmap[..])?;
     Ok(vk)
 }
 
@@ -100,61 +225,121 @@ pub fn save_shared_blinds<E: Engine>(
     shared_blinds_path: &str,
     shared_blinds: &[E::Scalar],
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let shared_blinds_path = &artifact_path(shared_blinds_path);
     if let Some(parent) = std::path::Path::new(shared_blinds_path).parent() {
         create_dir_all(parent)?;
     }
 
-    let shared_blinds_bytes = bincode::serialize(shared_blinds)?;
-    let mut shared_blinds_file = File::create(shared_blinds_path)?;
-    shared_blinds_file.write_all(&shared_blinds_bytes)?;
+    let shared_blinds_bytes = Zeroizing::new(sealed::seal_if_enabled(format::encode(
+        ArtifactKind::SharedBlinds,
+        &shared_blinds,
+    )?)?);
+    write_atomic(shared_blinds_path, &shared_blinds_bytes)?;
     info!("Saved ZK-Spartan shared_blinds to: {}", shared_blinds_path);
 
     Ok(())
 }
 
-pub fn save_proof(
+pub fn save_proof<E: Engine>(
     proof_path: &str,
     proof: &R1CSSNARK<E>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let proof_path = &artifact_path(proof_path);
     if let Some(parent) = std::path::Path::new(proof_path).parent() {
         create_dir_all(parent)?;
     }
 
-    let proof_bytes = bincode::serialize(proof)?;
-    let mut proof_file = File::create(proof_path)?;
-    proof_file.write_all(&proof_bytes)?;
+    let proof_bytes = format::encode(ArtifactKind::Proof, proof)?;
+    write_atomic(proof_path, &proof_bytes)?;
     info!("Saved ZK-Spartan proof to: {}", proof_path);
 
     Ok(())
 }
 
-pub fn save_instance(
+/// Extension of the part of a saved instance that is sent to verifiers
+pub const PUBLIC_INSTANCE_EXT: &str = "public";
+/// Extension of the part of a saved instance that stays on the holder's device
+pub const PRIVATE_INSTANCE_EXT: &str = "private";
+
+/// The part of an instance a verifier may see: comm_W_shared and the public IO
+/// Saved beside the full instance, which is kept on the device for reblinding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PublicInstance<E: Engine> {
+    /// comm_W_shared, formatted as in `ProofResult::comm_w_shared`
+    pub comm_w_shared: String,
+    pub public_values: Vec<E::Scalar>,
+}
+
+/// Where a part of the instance stored at the already resolved `instance_path` lives
+/// e.g. `keys/prepare_instance.bin` splits into `keys/prepare_instance.public`
+/// and `keys/prepare_instance.private`.
+pub fn instance_part_path(instance_path: &str, ext: &str) -> String {
+    std::path::Path::new(instance_path)
+        .with_extension(ext)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// `instance_path`'s public part, resolved in the active namespace
+pub fn public_instance_path(instance_path: &str) -> String {
+    instance_part_path(&artifact_path(instance_path), PUBLIC_INSTANCE_EXT)
+}
+
+/// `instance_path`'s private part, resolved in the active namespace
+pub fn private_instance_path(instance_path: &str) -> String {
+    instance_part_path(&artifact_path(instance_path), PRIVATE_INSTANCE_EXT)
+}
+
+/// Save `instance` split into its public and private parts
+/// `public_values` are the circuit's public values the instance was proved
+/// with. A file left at `instance_path` by a build before the split is removed.
+pub fn save_instance<E: Engine>(
     instance_path: &str,
     instance: &SplitR1CSInstance<E>,
+    public_values: &[E::Scalar],
 ) -> Result<(), Box<dyn std::error::Error>> {
-    if let Some(parent) = std::path::Path::new(instance_path).parent() {
+    let private_path = &private_instance_path(instance_path);
+    if let Some(parent) = std::path::Path::new(private_path).parent() {
         create_dir_all(parent)?;
     }
 
-    let instance_bytes = bincode::serialize(instance)?;
-    let mut instance_file = File::create(instance_path)?;
-    instance_file.write_all(&instance_bytes)?;
-    info!("Saved ZK-Spartan instance to: {}", instance_path);
+    let public = PublicInstance::<E> {
+        comm_w_shared: format!("{:?}", instance.comm_W_shared),
+        public_values: public_values.to_vec(),
+    };
+    let public_path = &public_instance_path(instance_path);
+    write_atomic(
+        private_path,
+        &format::encode(ArtifactKind::Instance, instance)?,
+    )?;
+    write_atomic(
+        public_path,
+        &format::encode(ArtifactKind::PublicInstance, &public)?,
+    )?;
+    let _ = fs::remove_file(artifact_path(instance_path));
+    info!(
+        "Saved ZK-Spartan instance to: {} and {}",
+        public_path, private_path
+    );
 
     Ok(())
 }
 
-pub fn save_witness(
+pub fn save_witness<E: Engine>(
     witness_path: &str,
     witness: &R1CSWitness<E>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let witness_path = &artifact_path(witness_path);
     if let Some(parent) = std::path::Path::new(witness_path).parent() {
         create_dir_all(parent)?;
     }
 
-    let witness_bytes = bincode::serialize(witness)?;
-    let mut witness_file = File::create(witness_path)?;
-    witness_file.write_all(&witness_bytes)?;
+    let witness_bytes = Zeroizing::new(sealed::seal_if_enabled(format::encode(
+        ArtifactKind::Witness,
+        witness,
+    )?)?);
+    write_atomic(witness_path, &witness_bytes)?;
     info!("Saved ZK-Spartan witness to: {}", witness_path);
 
     Ok(())
@@ -162,58 +347,229 @@ pub fn save_witness(
 
 pub fn load_shared_blinds<E: Engine>(
     shared_blinds_path: &str,
-) -> Result<Vec<E::Scalar>, Box<dyn std::error::Error>> {
-    let shared_blinds_file = File::open(shared_blinds_path)?;
-    let shared_blinds: Vec<E::Scalar> =
-        bincode::deserialize_from(&mut BufReader::new(shared_blinds_file))?;
+) -> Result<SecretBlinds<E::Scalar>, Box<dyn std::error::Error>> {
+    let shared_blinds_path = &artifact_path(shared_blinds_path);
+    let bytes = Zeroizing::new(sealed::open(fs::read(shared_blinds_path)?)?);
+    let shared_blinds: Vec<E::Scalar> = format::decode(ArtifactKind::SharedBlinds, &bytes)?;
     info!(
         "Loaded ZK-Spartan shared_blinds from: {}",
         shared_blinds_path
     );
-    Ok(shared_blinds)
+    Ok(shared_blinds.into())
 }
 
-pub fn load_proof(proof_path: &str) -> Result<R1CSSNARK<E>, Box<dyn std::error::Error>> {
-    let proof_file = File::open(proof_path)?;
-    let proof: R1CSSNARK<E> = bincode::deserialize_from(&mut BufReader::new(proof_file))?;
+pub fn load_proof<E: Engine>(proof_path: &str) -> Result<R1CSSNARK<E>, Box<dyn std::error::Error>> {
+    let proof_path = &artifact_path(proof_path);
+    let proof = parse_proof::<E>(&fs::read(proof_path)?)?;
     info!("Loaded ZK-Spartan proof from: {}", proof_path);
     Ok(proof)
 }
 
-pub fn load_instance(
+/// Load the full instance (its private part) saved at `instance_path`
+/// Falls back to the single file written by builds before the split.
+pub fn load_instance<E: Engine>(
     instance_path: &str,
 ) -> Result<SplitR1CSInstance<E>, Box<dyn std::error::Error>> {
-    let instance_file = File::open(instance_path)?;
-    let instance: SplitR1CSInstance<E> =
-        bincode::deserialize_from(&mut BufReader::new(instance_file))?;
+    let (instance_path, bytes) = read_instance(instance_path)?;
+    let instance = parse_instance::<E>(&bytes)?;
     info!("Loaded ZK-Spartan instance from: {}", instance_path);
     Ok(instance)
 }
 
-pub fn load_witness(witness_path: &str) -> Result<R1CSWitness<E>, Box<dyn std::error::Error>> {
-    let witness_file = File::open(witness_path)?;
-    let witness: R1CSWitness<E> = bincode::deserialize_from(&mut BufReader::new(witness_file))?;
+/// The resolved path and bytes of the instance saved at `instance_path`
+fn read_instance(instance_path: &str) -> std::io::Result<(String, Vec<u8>)> {
+    let private_path = private_instance_path(instance_path);
+    let instance_path = if std::path::Path::new(&private_path).exists() {
+        private_path
+    } else {
+        artifact_path(instance_path)
+    };
+    let bytes = fs::read(&instance_path)?;
+    Ok((instance_path, bytes))
+}
+
+/// Load the public part of the instance saved at `instance_path`
+pub fn load_public_instance<E: Engine>(
+    instance_path: &str,
+) -> Result<PublicInstance<E>, Box<dyn std::error::Error>> {
+    let public_path = &public_instance_path(instance_path);
+    let bytes = fs::read(public_path).map_err(|e| {
+        if std::path::Path::new(&artifact_path(instance_path)).exists() {
+            format!(
+                "'{}' was saved before instances were split; prove or reblind again",
+                artifact_path(instance_path)
+            )
+        } else {
+            format!("Failed to read '{}': {}", public_path, e)
+        }
+    })?;
+    let instance = parse_public_instance::<E>(&bytes)?;
+    info!("Loaded ZK-Spartan public instance from: {}", public_path);
+    Ok(instance)
+}
+
+pub fn load_witness<E: Engine>(
+    witness_path: &str,
+) -> Result<R1CSWitness<E>, Box<dyn std::error::Error>> {
+    let (witness_path, bytes) = read_witness(witness_path)?;
+    let witness = parse_witness::<E>(&bytes)?;
     info!("Loaded ZK-Spartan witness from: {}", witness_path);
     Ok(witness)
 }
 
-pub fn setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+/// The resolved path and unsealed bytes of the witness saved at `witness_path`
+fn read_witness(
+    witness_path: &str,
+) -> Result<(String, Zeroizing<Vec<u8>>), Box<dyn std::error::Error>> {
+    let witness_path = artifact_path(witness_path);
+    let bytes = Zeroizing::new(sealed::open(fs::read(&witness_path)?)?);
+    Ok((witness_path, bytes))
+}
+
+/// `load_instance` and `load_witness`, decoding the two concurrently
+/// Each artifact is a single bincode value in spartan2's layout, so there is no
+/// chunked format to split further: the files are read and unsealed on the
+/// calling thread and only the two decodes overlap.
+pub fn load_instance_and_witness<E: Engine>(
+    instance_path: &str,
+    witness_path: &str,
+) -> Result<(SplitR1CSInstance<E>, R1CSWitness<E>), Box<dyn std::error::Error>> {
+    let (instance_path, instance_bytes) = read_instance(instance_path)?;
+    let (witness_path, witness_bytes) = read_witness(witness_path)?;
+    let (instance, witness) = rayon::join(
+        || parse_instance::<E>(&instance_bytes),
+        || parse_witness::<E>(&witness_bytes),
+    );
+    let (instance, witness) = (instance?, witness?);
+    info!("Loaded ZK-Spartan instance from: {}", instance_path);
+    info!("Loaded ZK-Spartan witness from: {}", witness_path);
+    Ok((instance, witness))
+}
+
+/// Artifacts rewritten by a migration, and those that must be produced again
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub rewritten: Vec<String>,
+    /// Path and reason; keys are regenerated with setup, the rest by proving again
+    pub regenerate: Vec<(String, String)>,
+}
+
+/// Rewrite stored proofs, instances, witnesses and shared blinds in the current format
+/// Returns the paths that were migrated; missing or already-current files are skipped.
+/// Only the active artifact namespace is migrated.
+pub fn migrate_artifacts<E: Engine>() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let report = migrate_artifacts_between::<E>(None, format::CURRENT_VERSION)?;
+    if let Some((_, reason)) = report.regenerate.first() {
+        return Err(reason.clone().into());
+    }
+    Ok(report.rewritten)
+}
+
+/// Rewrite stored artifacts from format `from` (any, if `None`) to `to`
+/// Keys are not versioned and are only checked: one that no longer decodes is
+/// reported for regeneration along with any artifact that cannot be
+/// re-serialized. Only the active artifact namespace is migrated.
+pub fn migrate_artifacts_between<E: Engine>(
+    from: Option<u32>,
+    to: u32,
+) -> Result<MigrationReport, Box<dyn std::error::Error>> {
+    use format::{migrate_file_between, Migration};
+
+    format::check_pinned(to)?;
+    if let Some(from) = from {
+        format::check_pinned(from)?;
+    }
+
+    let mut results = Vec::new();
+    for path in [PREPARE_PROOF, SHOW_PROOF, ECDSA_PROOF] {
+        results.push((
+            path.to_string(),
+            migrate_file_between::<R1CSSNARK<E>>(ArtifactKind::Proof, path, from, to)?,
+        ));
+    }
+    for path in [PREPARE_INSTANCE, SHOW_INSTANCE, ECDSA_INSTANCE] {
+        let private_path = private_instance_path(path);
+        results.push((
+            path.to_string(),
+            migrate_file_between::<SplitR1CSInstance<E>>(ArtifactKind::Instance, path, from, to)?,
+        ));
+        results.push((
+            private_path.clone(),
+            migrate_file_between::<SplitR1CSInstance<E>>(
+                ArtifactKind::Instance,
+                &private_path,
+                from,
+                to,
+            )?,
+        ));
+    }
+    for path in [PREPARE_WITNESS, SHOW_WITNESS, ECDSA_WITNESS] {
+        results.push((
+            path.to_string(),
+            migrate_file_between::<R1CSWitness<E>>(ArtifactKind::Witness, path, from, to)?,
+        ));
+    }
+    results.push((
+        SHARED_BLINDS.to_string(),
+        migrate_file_between::<Vec<<E as Engine>::Scalar>>(
+            ArtifactKind::SharedBlinds,
+            SHARED_BLINDS,
+            from,
+            to,
+        )?,
+    ));
+
+    let mut report = MigrationReport::default();
+    for (path, migration) in results {
+        match migration {
+            Migration::Rewritten => {
+                info!("Migrated {} to format v{}", artifact_path(&path), to);
+                report.rewritten.push(artifact_path(&path));
+            }
+            Migration::Regenerate(reason) => report.regenerate.push((artifact_path(&path), reason)),
+            Migration::Missing | Migration::Unchanged => {}
+        }
+    }
+
+    // The verifying key stands in for its proving key, which is too large to decode here
+    for vk_path in [
+        PREPARE_VERIFYING_KEY,
+        SHOW_VERIFYING_KEY,
+        ECDSA_VERIFYING_KEY,
+    ] {
+        let path = stored_key_path(vk_path);
+        if !has_stored_key(vk_path) {
+            continue;
+        }
+        if let Err(e) = load_verifying_key::<E>(vk_path) {
+            let reason = format!("keys do not decode with this build, run setup: {}", e);
+            report.regenerate.push((path, reason));
+        }
+    }
+    Ok(report)
+}
+
+pub fn setup_circuit_keys<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
     pk_path: &str,
     vk_path: &str,
 ) {
-    let t0 = Instant::now();
-    let (pk, vk) = R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed");
-    let setup_ms = t0.elapsed().as_millis();
+    let (keys, timings) = setup_circuit_keys_timed::<E, C>(circuit).expect("setup failed");
+    let setup_ms = timings.setup_ms();
     info!(
         elapsed_ms = setup_ms,
+        synthesis_ms = timings.synthesis_ms,
+        keygen_ms = timings.keygen_ms,
         "Setup completed (~{:.1}s)",
         setup_ms as f64 / 1000.0
     );
 
-    if let Err(e) = save_keys(pk_path, vk_path, &pk, &vk) {
-        eprintln!("Failed to save keys: {}", e);
-        std::process::exit(1);
+    match save_keys_timed::<E>(pk_path, vk_path, &keys) {
+        Ok(save_ms) => info!(elapsed_ms = save_ms, "Keys saved"),
+        Err(e) => {
+            eprintln!("Failed to save keys: {}", e);
+            std::process::exit(1);
+        }
     }
 
     info!("Keys generated and saved successfully!");
@@ -223,11 +579,32 @@ pub fn setup_circuit_keys<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
 
 /// Setup circuit keys without saving to file - useful for benchmarking
 /// Returns the proving and verifying keys
-pub fn setup_circuit_keys_no_save<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+pub fn setup_circuit_keys_no_save<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
     circuit: C,
 ) -> (
     <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
     <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
 ) {
-    R1CSSNARK::<E>::setup(circuit.clone()).expect("setup failed")
+    setup_circuit_keys_in_memory(circuit).expect("setup failed")
+}
+
+/// Setup circuit keys that are only ever held in memory
+/// For verifier services and other contexts that keep no keys on disk; the
+/// keys are used directly by `prove_circuit_with_pk` and `R1CSSNARK::verify`.
+pub fn setup_circuit_keys_in_memory<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+) -> Result<
+    (
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+        <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+    ),
+    SpartanError,
+> {
+    let t0 = Instant::now();
+    let keys = R1CSSNARK::<E>::setup(circuit)?;
+    info!(
+        elapsed_ms = t0.elapsed().as_millis(),
+        "In-memory setup completed"
+    );
+    Ok(keys)
 }
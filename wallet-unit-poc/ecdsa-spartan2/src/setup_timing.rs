@@ -0,0 +1,144 @@
+//! Setup timed phase by phase: shape synthesis, key generation, saving, loading.
+//!
+//! `R1CSSNARK::setup` synthesizes the circuit into a shape and then derives
+//! the commitment key and key matrices from it, all in one call. To tell the
+//! two apart, `setup_circuit_keys_timed` hands spartan2 a `TimedCircuit`,
+//! which forwards to the real circuit and adds up the time spent in its
+//! `shared`, `precommitted` and `synthesize`; the rest of the call is key
+//! generation. Writing and reading the keys back are timed around `save_keys`
+//! and `load_keys`, so a benchmark can report the one-time setup cost on a
+//! device separately from the cost of installing and opening the keys.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use spartan2::{
+    errors::SpartanError,
+    traits::{circuit::SpartanCircuit, snark::R1CSSNARKTrait, Engine},
+    zk_spartan::R1CSSNARK,
+};
+
+use crate::setup::{load_keys, save_keys};
+
+type Keys<E> = (
+    <R1CSSNARK<E> as R1CSSNARKTrait<E>>::ProverKey,
+    <R1CSSNARK<E> as R1CSSNARKTrait<E>>::VerifierKey,
+);
+
+/// Milliseconds spent in each phase of setting up a circuit's keys
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SetupTimings {
+    /// Synthesizing the circuit into its shape
+    pub synthesis_ms: u128,
+    /// The rest of `R1CSSNARK::setup`: commitment key and key matrices
+    pub keygen_ms: u128,
+    /// Serializing and writing both keys; 0 if they were not saved
+    pub save_ms: u128,
+    /// Reading and decoding both keys; 0 if they were not loaded
+    pub load_ms: u128,
+}
+
+impl SetupTimings {
+    /// Synthesis and key generation, what setup took before saving
+    pub fn setup_ms(&self) -> u128 {
+        self.synthesis_ms + self.keygen_ms
+    }
+}
+
+/// A circuit that records how long its synthesis methods take
+#[derive(Debug, Clone)]
+struct TimedCircuit<C> {
+    inner: C,
+    synthesis_ns: Arc<AtomicU64>,
+}
+
+impl<C> TimedCircuit<C> {
+    fn timed<T>(&self, f: impl FnOnce() -> T) -> T {
+        let t0 = Instant::now();
+        let value = f();
+        self.synthesis_ns
+            .fetch_add(t0.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        value
+    }
+}
+
+impl<E: Engine, C: SpartanCircuit<E>> SpartanCircuit<E> for TimedCircuit<C> {
+    fn synthesize<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<E::Scalar>],
+        precommitted: &[AllocatedNum<E::Scalar>],
+        challenges: Option<&[E::Scalar]>,
+    ) -> Result<(), SynthesisError> {
+        self.timed(|| self.inner.synthesize(cs, shared, precommitted, challenges))
+    }
+
+    fn public_values(&self) -> Result<Vec<E::Scalar>, SynthesisError> {
+        self.inner.public_values()
+    }
+    fn shared<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        self.timed(|| self.inner.shared(cs))
+    }
+    fn precommitted<CS: ConstraintSystem<E::Scalar>>(
+        &self,
+        cs: &mut CS,
+        shared: &[AllocatedNum<E::Scalar>],
+    ) -> Result<Vec<AllocatedNum<E::Scalar>>, SynthesisError> {
+        self.timed(|| self.inner.precommitted(cs, shared))
+    }
+    fn num_challenges(&self) -> usize {
+        self.inner.num_challenges()
+    }
+}
+
+/// Set up the keys of `circuit` in memory, timing synthesis and key generation apart
+pub fn setup_circuit_keys_timed<E: Engine, C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    circuit: C,
+) -> Result<(Keys<E>, SetupTimings), SpartanError> {
+    let synthesis_ns = Arc::new(AtomicU64::new(0));
+    let timed = TimedCircuit {
+        inner: circuit,
+        synthesis_ns: synthesis_ns.clone(),
+    };
+    let t0 = Instant::now();
+    let keys = R1CSSNARK::<E>::setup(timed)?;
+    let setup_ms = t0.elapsed().as_millis();
+
+    let synthesis_ms = (synthesis_ns.load(Ordering::Relaxed) / 1_000_000) as u128;
+    let timings = SetupTimings {
+        synthesis_ms,
+        keygen_ms: setup_ms.saturating_sub(synthesis_ms),
+        ..SetupTimings::default()
+    };
+    Ok((keys, timings))
+}
+
+/// `save_keys`, returning how long it took
+pub fn save_keys_timed<E: Engine>(
+    pk_path: &str,
+    vk_path: &str,
+    keys: &Keys<E>,
+) -> Result<u128, Box<dyn std::error::Error>> {
+    let t0 = Instant::now();
+    save_keys::<E>(pk_path, vk_path, &keys.0, &keys.1)?;
+    Ok(t0.elapsed().as_millis())
+}
+
+/// `load_keys`, returning the keys and how long it took
+pub fn load_keys_timed<E: Engine>(
+    pk_path: &str,
+    vk_path: &str,
+) -> Result<(Keys<E>, u128), Box<dyn std::error::Error>> {
+    let t0 = Instant::now();
+    let keys = load_keys::<E>(pk_path, vk_path)?;
+    Ok((keys, t0.elapsed().as_millis()))
+}
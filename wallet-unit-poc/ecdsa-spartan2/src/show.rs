@@ -0,0 +1,182 @@
+//! What a verified Show proof states about the presentation it was made for.
+//!
+//! The Show circuit checks the device signature over `messageHash`, and
+//! `apply_device_signature` sets that to SHA-256 of the verifier's nonce
+//! reduced modulo the group order. It also checks that the holder, by the
+//! `roc_birthday` claim, is at least 18 on the date in `currentYear`,
+//! `currentMonth` and `currentDay`; `ShowCircuit` constrains `ageAbove18` to
+//! 1 and binds the signals to its public values
+//!
+//! ```text
+//! show domain tag || messageHash || year || month || day
+//! ```
+//!
+//! so a verifier recomputes the hash from the nonce it issued and rejects a
+//! proof signed over any other challenge, and reads the reference date from the
+//! proof: `age >= n` is proved by moving that date `n - 18` years back (see
+//! `compile_predicate`), and `proven_min_age` turns it back into `n`.
+
+use std::str::FromStr;
+
+use ff::PrimeField;
+use rust_witness::BigInt;
+use serde_json::Value;
+
+use crate::{
+    domain::domain_tag,
+    predicate::{CalendarDate, CIRCUIT_MIN_AGE},
+    signature::challenge_message_hash,
+    utils::bigint_to_scalar,
+    witness::CircuitKind,
+    Scalar,
+};
+
+/// Public values of a Show proof, domain tag included
+pub const SHOW_PUBLIC_VALUES: usize = 5;
+
+/// What a verified Show proof states
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShowStatement {
+    /// Hash of the challenge the device signed, reduced modulo the group order
+    pub message_hash: Scalar,
+    /// Date on which the holder is proved to be at least 18
+    pub reference_date: CalendarDate,
+}
+
+/// The `messageHash` of a presentation made for the verifier nonce `challenge`
+pub fn expected_message_hash(challenge: &[u8]) -> Result<Scalar, String> {
+    bigint_to_scalar(challenge_message_hash(challenge)).map_err(|e| e.to_string())
+}
+
+impl ShowStatement {
+    /// Statement of the signature and date filled into a Show input
+    pub fn from_show_input(show_input: &Value) -> Result<Self, String> {
+        let field = |name: &str| {
+            show_input
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| format!("show input has no valid {}", name))
+        };
+        let number = |name: &str| {
+            field(name)?
+                .parse::<u32>()
+                .map_err(|_| format!("show input has no valid {}", name))
+        };
+        let message_hash = BigInt::from_str(field("messageHash")?)
+            .map_err(|_| "show input has no valid messageHash".to_string())?;
+        Ok(Self {
+            message_hash: bigint_to_scalar(message_hash).map_err(|e| e.to_string())?,
+            reference_date: CalendarDate {
+                year: number("currentYear")? as i64,
+                month: number("currentMonth")?,
+                day: number("currentDay")?,
+            },
+        })
+    }
+
+    /// The Show circuit's public values, domain tag first
+    pub fn to_public_values(&self) -> Vec<Scalar> {
+        let date = self.reference_date;
+        vec![
+            domain_tag(CircuitKind::Show),
+            self.message_hash,
+            Scalar::from(date.year as u64),
+            Scalar::from(date.month as u64),
+            Scalar::from(date.day as u64),
+        ]
+    }
+
+    /// Recover the statement from the public values after the domain tag,
+    /// as `check_domain` returns them
+    pub fn from_public_values(values: &[Scalar]) -> Result<Self, String> {
+        let [message_hash, year, month, day] = values else {
+            return Err(format!(
+                "expected {} Show public values after the domain tag, got {}",
+                SHOW_PUBLIC_VALUES - 1,
+                values.len()
+            ));
+        };
+        let reference_date = CalendarDate {
+            year: scalar_to_u32(year)? as i64,
+            month: scalar_to_u32(month)?,
+            day: scalar_to_u32(day)?,
+        };
+        Ok(Self {
+            message_hash: *message_hash,
+            reference_date,
+        })
+    }
+
+    /// Age the holder is proved to have reached on `today`
+    /// `None` if the reference date is after `today`, where the proof says
+    /// nothing about the holder's age now.
+    pub fn proven_min_age(&self, today: CalendarDate) -> Option<u32> {
+        let reference = self.reference_date;
+        let key = |date: CalendarDate| (date.year, date.month, date.day);
+        if key(reference) > key(today) {
+            return None;
+        }
+        let birthday_passed = (today.month, today.day) >= (reference.month, reference.day);
+        let years = today.year - reference.year - i64::from(!birthday_passed);
+        u32::try_from(CIRCUIT_MIN_AGE + years).ok()
+    }
+}
+
+fn scalar_to_u32(value: &Scalar) -> Result<u32, String> {
+    let repr = value.to_repr();
+    let (low, high) = repr.as_ref().split_at(4);
+    if high.iter().any(|b| *b != 0) {
+        return Err("reference date does not fit in 32 bits".to_string());
+    }
+    Ok(u32::from_le_bytes(low.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn date(year: i64, month: u32, day: u32) -> CalendarDate {
+        CalendarDate { year, month, day }
+    }
+
+    #[test]
+    fn test_statement_matches_the_signed_challenge() {
+        let input = json!({
+            "messageHash": challenge_message_hash(b"nonce").to_string(),
+            "currentYear": "2004",
+            "currentMonth": "6",
+            "currentDay": "1",
+        });
+        let statement = ShowStatement::from_show_input(&input).unwrap();
+        assert_eq!(
+            statement.message_hash,
+            expected_message_hash(b"nonce").unwrap()
+        );
+        assert_ne!(
+            statement.message_hash,
+            expected_message_hash(b"other").unwrap()
+        );
+        assert_eq!(statement.reference_date, date(2004, 6, 1));
+
+        let values = statement.to_public_values();
+        assert_eq!(values.len(), SHOW_PUBLIC_VALUES);
+        assert_eq!(
+            ShowStatement::from_public_values(&values[1..]),
+            Ok(statement)
+        );
+        assert!(ShowStatement::from_public_values(&values).is_err());
+    }
+
+    #[test]
+    fn test_proven_min_age_counts_full_years_since_the_reference_date() {
+        let statement = ShowStatement {
+            message_hash: Scalar::from(1u64),
+            reference_date: date(2004, 6, 1),
+        };
+        assert_eq!(statement.proven_min_age(date(2004, 6, 1)), Some(18));
+        assert_eq!(statement.proven_min_age(date(2007, 5, 31)), Some(20));
+        assert_eq!(statement.proven_min_age(date(2007, 6, 1)), Some(21));
+        assert_eq!(statement.proven_min_age(date(2004, 5, 31)), None);
+    }
+}
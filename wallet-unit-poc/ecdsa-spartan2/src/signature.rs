@@ -0,0 +1,286 @@
+//! P-256 ECDSA signature decoding into circuit inputs.
+//!
+//! The circuits verify `R = r·s⁻¹·Q + m·s⁻¹·G` with the inverse supplied as a
+//! witness, so signatures produced outside the prover (a host key store, Secure
+//! Enclave, StrongBox) are converted here into `r`, `s⁻¹ mod n`, and `m mod n`.
+
+use rust_witness::BigInt;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+/// Order of the P-256 group
+const P256_N: &str =
+    "115792089210356248762697446949407573529996955224135760342422259061068512044369";
+
+/// Decoded ECDSA verification inputs, as circuit field values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EcdsaInputs {
+    pub r: BigInt,
+    pub s_inverse: BigInt,
+    /// Message hash reduced modulo the group order
+    pub m: BigInt,
+    pub pub_key_x: BigInt,
+    pub pub_key_y: BigInt,
+}
+
+/// Decode a caller-supplied P-256 signature
+///
+/// - `signature`: 64-byte `r || s` or an ASN.1 DER `ECDSA-Sig-Value`
+/// - `msg_hash`: the big-endian message digest (e.g. SHA-256 of the message)
+/// - `pubkey`: 65-byte uncompressed SEC1 point (`0x04 || X || Y`) or 64-byte `X || Y`,
+///   which must lie on P-256
+pub fn ecdsa_inputs(
+    signature: &[u8],
+    msg_hash: &[u8],
+    pubkey: &[u8],
+) -> Result<EcdsaInputs, String> {
+    let n = BigInt::from_str(P256_N).unwrap();
+
+    let (r, s) = parse_signature(signature)?;
+    if r == BigInt::from(0) || r >= n || s == BigInt::from(0) || s >= n {
+        return Err("signature scalars must be in [1, n)".to_string());
+    }
+    // n is prime, so s^-1 = s^(n-2) mod n
+    let s_inverse = s.modpow(&(&n - BigInt::from(2)), &n);
+
+    if msg_hash.is_empty() || msg_hash.len() > 32 {
+        return Err(format!(
+            "message hash must be 1-32 bytes, got {}",
+            msg_hash.len()
+        ));
+    }
+    // Only m mod n enters the verification equation
+    let m = be_bytes_to_bigint(msg_hash) % &n;
+
+    let (pub_key_x, pub_key_y) = parse_pubkey(pubkey)?;
+
+    Ok(EcdsaInputs {
+        r,
+        s_inverse,
+        m,
+        pub_key_x,
+        pub_key_y,
+    })
+}
+
+/// Fill the device-binding fields of a Show input from a signature over `challenge`
+///
+/// The platform signs with ECDSA-SHA256, so the message hash is SHA-256 of the
+/// challenge. If the input already names a device key (from the credential's key
+/// binding), the signer's key must match it.
+pub fn apply_device_signature(
+    show_input: &mut Value,
+    challenge: &[u8],
+    signature: &[u8],
+    pubkey: &[u8],
+) -> Result<(), String> {
+    let inputs = ecdsa_inputs(signature, &Sha256::digest(challenge), pubkey)?;
+
+    let object = show_input
+        .as_object_mut()
+        .ok_or("show input must be a JSON object")?;
+    for (field, expected) in [
+        ("deviceKeyX", &inputs.pub_key_x),
+        ("deviceKeyY", &inputs.pub_key_y),
+    ] {
+        if let Some(existing) = object.get(field).and_then(Value::as_str) {
+            if BigInt::from_str(existing).ok().as_ref() != Some(expected) {
+                return Err(format!(
+                    "device key does not match the credential key binding ({})",
+                    field
+                ));
+            }
+        }
+    }
+
+    object.insert("deviceKeyX".into(), inputs.pub_key_x.to_string().into());
+    object.insert("deviceKeyY".into(), inputs.pub_key_y.to_string().into());
+    object.insert("sig_r".into(), inputs.r.to_string().into());
+    object.insert("sig_s_inverse".into(), inputs.s_inverse.to_string().into());
+    object.insert("messageHash".into(), inputs.m.to_string().into());
+    Ok(())
+}
+
+/// The Show circuit's `messageHash` for a device signature over `challenge`
+/// SHA-256 of the challenge reduced modulo the group order, as
+/// `apply_device_signature` fills it in, so a verifier can recompute the value
+/// a presentation made for its nonce must carry.
+pub fn challenge_message_hash(challenge: &[u8]) -> BigInt {
+    let n = BigInt::from_str(P256_N).unwrap();
+    be_bytes_to_bigint(&Sha256::digest(challenge)) % &n
+}
+
+/// The device key a Show input is bound to, as 64-byte `X || Y`
+pub fn bound_device_key(show_input: &Value) -> Result<Vec<u8>, String> {
+    input_public_key(show_input, ["deviceKeyX", "deviceKeyY"]).map_err(|e| format!("show {}", e))
+}
+
+/// A P-256 key stored in a circuit input as decimal `[x, y]` fields, as 64-byte `X || Y`
+pub fn input_public_key(input: &Value, fields: [&str; 2]) -> Result<Vec<u8>, String> {
+    let mut pubkey = Vec::with_capacity(64);
+    for field in fields {
+        let coordinate = input
+            .get(field)
+            .and_then(Value::as_str)
+            .and_then(|v| BigInt::from_str(v).ok())
+            .ok_or_else(|| format!("input has no valid {}", field))?;
+        let (_, bytes) = coordinate.to_bytes_be();
+        if bytes.len() > 32 {
+            return Err(format!("input {} exceeds 32 bytes", field));
+        }
+        pubkey.resize(pubkey.len() + 32 - bytes.len(), 0);
+        pubkey.extend_from_slice(&bytes);
+    }
+    Ok(pubkey)
+}
+
+fn be_bytes_to_bigint(bytes: &[u8]) -> BigInt {
+    // A leading zero byte keeps the two's-complement value non-negative
+    let mut unsigned = Vec::with_capacity(bytes.len() + 1);
+    unsigned.push(0);
+    unsigned.extend_from_slice(bytes);
+    BigInt::from_signed_bytes_be(&unsigned)
+}
+
+fn parse_signature(signature: &[u8]) -> Result<(BigInt, BigInt), String> {
+    if signature.len() == 64 {
+        return Ok((
+            be_bytes_to_bigint(&signature[..32]),
+            be_bytes_to_bigint(&signature[32..]),
+        ));
+    }
+
+    // SEQUENCE { INTEGER r, INTEGER s }, as produced by Secure Enclave and Keystore
+    let body = der_element(signature, 0x30)
+        .filter(|(_, rest)| rest.is_empty())
+        .map(|(body, _)| body)
+        .ok_or("signature is neither 64-byte r || s nor DER")?;
+    let (r, rest) = der_element(body, 0x02).ok_or("malformed DER signature: r")?;
+    let (s, rest) = der_element(rest, 0x02).ok_or("malformed DER signature: s")?;
+    if !rest.is_empty() || r.len() > 33 || s.len() > 33 {
+        return Err("malformed DER signature".to_string());
+    }
+    Ok((be_bytes_to_bigint(r), be_bytes_to_bigint(s)))
+}
+
+/// Split a short-form DER element with the expected tag into (contents, remainder)
+fn der_element(bytes: &[u8], tag: u8) -> Option<(&[u8], &[u8])> {
+    let (&actual_tag, rest) = bytes.split_first()?;
+    let (&len, rest) = rest.split_first()?;
+    if actual_tag != tag || len >= 0x80 || rest.len() < len as usize {
+        return None;
+    }
+    Some(rest.split_at(len as usize))
+}
+
+/// The `X || Y` coordinates of a 65-byte uncompressed SEC1 or 64-byte `X || Y` key
+pub fn pubkey_coordinates(pubkey: &[u8]) -> Result<&[u8], String> {
+    match pubkey.len() {
+        65 if pubkey[0] == 0x04 => Ok(&pubkey[1..]),
+        64 => Ok(pubkey),
+        len => Err(format!(
+            "public key must be 65-byte uncompressed SEC1 or 64-byte X || Y, got {} bytes",
+            len
+        )),
+    }
+}
+
+/// Coordinates of a P-256 public key, rejecting points off the curve
+/// The circuits do not check the key themselves, and a verification equation
+/// over a point outside the group says nothing about who signed.
+fn parse_pubkey(pubkey: &[u8]) -> Result<(BigInt, BigInt), String> {
+    let coordinates = pubkey_coordinates(pubkey)?;
+    let sec1 = [&[0x04][..], coordinates].concat();
+    p256::PublicKey::from_sec1_bytes(&sec1)
+        .map_err(|_| "public key is not a point on P-256".to_string())?;
+    Ok((
+        be_bytes_to_bigint(&coordinates[..32]),
+        be_bytes_to_bigint(&coordinates[32..]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::{elliptic_curve::sec1::ToEncodedPoint, ProjectivePoint};
+
+    /// The P-256 generator, as a 65-byte uncompressed SEC1 point
+    fn generator() -> Vec<u8> {
+        ProjectivePoint::GENERATOR
+            .to_affine()
+            .to_encoded_point(false)
+            .as_bytes()
+            .to_vec()
+    }
+
+    fn signature_bytes() -> (Vec<u8>, Vec<u8>) {
+        let mut r = vec![0u8; 32];
+        r[31] = 7;
+        let mut s = vec![0u8; 32];
+        s[0] = 0x80; // high bit set, so DER needs a leading zero
+        s[31] = 3;
+        (r, s)
+    }
+
+    #[test]
+    fn test_raw_and_der_signatures_agree() {
+        let (r, s) = signature_bytes();
+        let raw = [r, s.clone()].concat();
+
+        let mut der: Vec<u8> = vec![0x30, 3 + 35, 0x02, 1, 7, 0x02, 33, 0];
+        der.extend_from_slice(&s);
+
+        let pubkey = generator();
+        let a = ecdsa_inputs(&raw, &[9; 32], &pubkey).unwrap();
+        let b = ecdsa_inputs(&der, &[9; 32], &pubkey[1..]).unwrap();
+        assert_eq!(a, b);
+
+        let n = BigInt::from_str(P256_N).unwrap();
+        let s = be_bytes_to_bigint(&s);
+        assert_eq!((s * a.s_inverse) % n, BigInt::from(1));
+    }
+
+    #[test]
+    fn test_device_key_must_match_binding() {
+        let (r, s) = signature_bytes();
+        let raw = [r, s].concat();
+        let pubkey = generator()[1..].to_vec();
+
+        let mut input = serde_json::json!({ "deviceKeyX": "1", "deviceKeyY": "2" });
+        assert!(apply_device_signature(&mut input, b"nonce", &raw, &pubkey).is_err());
+
+        let mut input = serde_json::json!({});
+        apply_device_signature(&mut input, b"nonce", &raw, &pubkey).unwrap();
+        assert!(input.get("sig_s_inverse").is_some());
+        assert_eq!(
+            input["messageHash"],
+            challenge_message_hash(b"nonce").to_string()
+        );
+    }
+
+    #[test]
+    fn test_public_key_must_be_on_the_curve() {
+        let (r, s) = signature_bytes();
+        let raw = [r, s].concat();
+        assert!(ecdsa_inputs(&raw, &[9; 32], &generator()).is_ok());
+
+        // Off the curve, the point at infinity, and a coordinate beyond the field
+        let mut off_curve = generator();
+        off_curve[64] ^= 1;
+        let infinity = [0u8; 64].to_vec();
+        let mut beyond_field = generator();
+        beyond_field[1..33].fill(0xff);
+        for pubkey in [off_curve, infinity, beyond_field] {
+            assert_eq!(
+                ecdsa_inputs(&raw, &[9; 32], &pubkey),
+                Err("public key is not a point on P-256".to_string())
+            );
+        }
+
+        let mut input = serde_json::json!({});
+        let off_curve = [vec![1; 32], vec![2; 32]].concat();
+        assert!(apply_device_signature(&mut input, b"nonce", &raw, &off_curve).is_err());
+        assert!(input.get("deviceKeyX").is_none());
+    }
+}
@@ -0,0 +1,219 @@
+//! Native profiler intervals around prover phases.
+//!
+//! With the `signpost` feature enabled, `section` emits an os_signpost interval on
+//! Apple platforms and an ATrace section on Android, so Instruments and Perfetto
+//! traces show the prover phases natively. Without the feature, sections are no-ops.
+//! Independently of the feature, a `PhaseObserver` can be told when each phase
+//! ends, e.g. to record prover progress.
+
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Prover phases reported to the platform profiler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// rust-witness generation from the circuit inputs
+    WitnessGen,
+    /// `prep_prove` (constraint synthesis and precommitted witness)
+    PrepProve,
+    /// Witness synthesis and Hyrax commitment of the split instance
+    Commit,
+    /// `prove_inner`: the sumcheck rounds and the Hyrax PCS opening, which
+    /// spartan2 runs as one call and so cannot be told apart
+    Prove,
+    /// Re-randomization of the instance and witness commitments
+    Reblind,
+    /// Proof verification
+    Verify,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::WitnessGen => "witness_gen",
+            Phase::PrepProve => "prep_prove",
+            Phase::Commit => "pcs_commit",
+            Phase::Prove => "prove",
+            Phase::Reblind => "reblind",
+            Phase::Verify => "verify",
+        }
+    }
+
+    #[cfg(all(feature = "signpost", target_vendor = "apple"))]
+    fn c_name(self) -> &'static std::ffi::CStr {
+        match self {
+            Phase::WitnessGen => c"witness_gen",
+            Phase::PrepProve => c"prep_prove",
+            Phase::Commit => c"pcs_commit",
+            Phase::Prove => c"prove",
+            Phase::Reblind => c"reblind",
+            Phase::Verify => c"verify",
+        }
+    }
+}
+
+/// Receives each prover phase as it ends, with its duration
+pub type PhaseObserver = Arc<dyn Fn(Phase, Duration) + Send + Sync>;
+
+static OBSERVER: RwLock<Option<PhaseObserver>> = RwLock::new(None);
+
+/// Report every completed phase to `observer`, or to none, returning the one it replaces
+pub fn replace_phase_observer(observer: Option<PhaseObserver>) -> Option<PhaseObserver> {
+    std::mem::replace(
+        &mut *OBSERVER.write().unwrap_or_else(|e| e.into_inner()),
+        observer,
+    )
+}
+
+/// Open an interval for `phase` that closes when the returned guard is dropped
+pub fn section(phase: Phase) -> PhaseGuard {
+    PhaseGuard {
+        phase,
+        start: Instant::now(),
+        _section: Section::begin(phase),
+    }
+}
+
+/// An open phase; reports it to the observer and closes the interval on drop
+pub struct PhaseGuard {
+    phase: Phase,
+    start: Instant,
+    _section: Section,
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        let observer = OBSERVER.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(observer) = observer {
+            observer(self.phase, self.start.elapsed());
+        }
+    }
+}
+
+#[cfg(not(all(
+    feature = "signpost",
+    any(target_vendor = "apple", target_os = "android")
+)))]
+pub struct Section;
+
+#[cfg(not(all(
+    feature = "signpost",
+    any(target_vendor = "apple", target_os = "android")
+)))]
+impl Section {
+    fn begin(_phase: Phase) -> Self {
+        Section
+    }
+}
+
+#[cfg(all(feature = "signpost", target_os = "android"))]
+pub struct Section {
+    _guard: Option<ndk::trace::Section>,
+}
+
+#[cfg(all(feature = "signpost", target_os = "android"))]
+impl Section {
+    fn begin(phase: Phase) -> Self {
+        // ATrace sections end when the ndk guard is dropped
+        Section {
+            _guard: ndk::trace::Section::new(phase.name()).ok(),
+        }
+    }
+}
+
+#[cfg(all(feature = "signpost", target_vendor = "apple"))]
+pub use apple::Section;
+
+#[cfg(all(feature = "signpost", target_vendor = "apple"))]
+mod apple {
+    use super::Phase;
+    use std::{
+        ffi::{c_char, c_void},
+        sync::OnceLock,
+    };
+
+    #[repr(C)]
+    struct OsLog {
+        _private: [u8; 0],
+    }
+
+    const OS_SIGNPOST_INTERVAL_BEGIN: u8 = 1;
+    const OS_SIGNPOST_INTERVAL_END: u8 = 2;
+
+    extern "C" {
+        static __dso_handle: c_void;
+
+        fn os_log_create(subsystem: *const c_char, category: *const c_char) -> *mut OsLog;
+        fn os_signpost_id_generate(log: *mut OsLog) -> u64;
+        fn os_signpost_enabled(log: *mut OsLog) -> bool;
+        fn _os_signpost_emit_with_name_impl(
+            dso: *const c_void,
+            log: *mut OsLog,
+            kind: u8,
+            spid: u64,
+            name: *const c_char,
+            format: *const c_char,
+            buf: *mut u8,
+            size: u32,
+        );
+    }
+
+    struct LogHandle(*mut OsLog);
+
+    // os_log_t handles are immutable and thread-safe
+    unsafe impl Send for LogHandle {}
+    unsafe impl Sync for LogHandle {}
+
+    fn log() -> *mut OsLog {
+        static LOG: OnceLock<LogHandle> = OnceLock::new();
+        LOG.get_or_init(|| unsafe {
+            LogHandle(os_log_create(
+                c"org.pse.zkid".as_ptr(),
+                c"PointsOfInterest".as_ptr(),
+            ))
+        })
+        .0
+    }
+
+    pub struct Section {
+        phase: Phase,
+        id: u64,
+    }
+
+    impl Section {
+        pub(super) fn begin(phase: Phase) -> Self {
+            let log = log();
+            let id = unsafe { os_signpost_id_generate(log) };
+            emit(log, OS_SIGNPOST_INTERVAL_BEGIN, id, phase);
+            Section { phase, id }
+        }
+    }
+
+    impl Drop for Section {
+        fn drop(&mut self) {
+            emit(log(), OS_SIGNPOST_INTERVAL_END, self.id, self.phase);
+        }
+    }
+
+    fn emit(log: *mut OsLog, kind: u8, id: u64, phase: Phase) {
+        unsafe {
+            if !os_signpost_enabled(log) {
+                return;
+            }
+            // Empty os_log argument buffer: summary byte followed by argument count
+            let mut buf = [0u8; 2];
+            _os_signpost_emit_with_name_impl(
+                &__dso_handle,
+                log,
+                kind,
+                id,
+                phase.c_name().as_ptr(),
+                c"".as_ptr(),
+                buf.as_mut_ptr(),
+                buf.len() as u32,
+            );
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Fiat-Shamir transcript hash per deployment.
+//!
+//! spartan2 fixes the transcript as the `TE` type of an `Engine`, and its
+//! engines all use Keccak-256, which is also what an on-chain verifier can
+//! recompute cheaply. Deployments that standardize on SHA-256 instead build
+//! with `sha256-transcript`, which proves over `Sha256Engine`: the same curve,
+//! field and Hyrax commitments as the base engine, with `Sha256Transcript`
+//! squeezing the challenges. Proofs made under one hash never verify under the
+//! other, so `save_keys` records the hash beside each verifying key
+//! (`<vk>.transcript`) and `load_verifying_key` refuses a key set up for the
+//! other one instead of failing verification without a reason. Keys without the
+//! record predate it and were set up with Keccak-256.
+
+use std::{fs, io, marker::PhantomData};
+
+use sha2::{Digest, Sha256};
+use spartan2::{
+    errors::SpartanError,
+    provider::pcs::hyrax_pc::HyraxPCS,
+    traits::{
+        transcript::{TranscriptEngineTrait, TranscriptReprTrait},
+        Engine, PrimeFieldExt,
+    },
+};
+
+/// Transcript hash of keys set up before the hash was recorded
+pub const LEGACY_TRANSCRIPT_HASH: &str = "keccak256";
+
+/// Transcript hash of this build's `E`
+#[cfg(not(feature = "sha256-transcript"))]
+pub const TRANSCRIPT_HASH: &str = "keccak256";
+#[cfg(feature = "sha256-transcript")]
+pub const TRANSCRIPT_HASH: &str = "sha256";
+
+const PERSONA_TAG: &[u8] = b"Sha256Transcript";
+const DOM_SEP_TAG: &[u8] = b"DOMSEP";
+
+/// `B` with the transcript hashed by SHA-256
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sha256Engine<B: Engine>(PhantomData<B>);
+
+impl<B: Engine> Engine for Sha256Engine<B> {
+    type Base = B::Base;
+    type Scalar = B::Scalar;
+    type GE = B::GE;
+    type TE = Sha256Transcript<Self>;
+    type PCS = HyraxPCS<Self>;
+}
+
+/// spartan2's Keccak transcript construction with SHA-256 as the hash
+/// Each squeeze hashes the absorbed bytes together with the round and the
+/// previous 64-byte state, and reduces the new state into a scalar.
+#[derive(Clone, Debug)]
+pub struct Sha256Transcript<E: Engine> {
+    round: u64,
+    state: [u8; 64],
+    transcript: Sha256,
+    _p: PhantomData<E>,
+}
+
+impl<E: Engine> Sha256Transcript<E> {
+    /// 64 bytes of state: the hash of `input` with a 0 and with a 1 appended
+    fn updated_state(&self, input: &[u8]) -> [u8; 64] {
+        let mut transcript = self.transcript.clone();
+        transcript.update(input);
+        let mut state = [0u8; 64];
+        for (half, suffix) in state.chunks_exact_mut(32).zip([0u8, 1]) {
+            let mut hasher = transcript.clone();
+            hasher.update([suffix]);
+            half.copy_from_slice(&hasher.finalize());
+        }
+        state
+    }
+}
+
+impl<E: Engine> TranscriptEngineTrait<E> for Sha256Transcript<E> {
+    fn new(label: &'static [u8]) -> Self {
+        let empty = Self {
+            round: 0,
+            state: [0u8; 64],
+            transcript: Sha256::new(),
+            _p: PhantomData,
+        };
+        let state = empty.updated_state(&[PERSONA_TAG, label].concat());
+        Self { state, ..empty }
+    }
+
+    fn squeeze(&mut self, label: &'static [u8]) -> Result<E::Scalar, SpartanError> {
+        let input = [DOM_SEP_TAG, &self.round.to_le_bytes(), &self.state, label].concat();
+        let output = self.updated_state(&input);
+
+        self.round += 1;
+        self.state = output;
+        self.transcript = Sha256::new();
+        Ok(E::Scalar::from_uniform(&output))
+    }
+
+    fn absorb<T: TranscriptReprTrait<E::GE>>(&mut self, label: &'static [u8], o: &T) {
+        self.transcript.update(label);
+        self.transcript.update(o.to_transcript_bytes());
+    }
+
+    fn dom_sep(&mut self, bytes: &'static [u8]) {
+        self.transcript.update(DOM_SEP_TAG);
+        self.transcript.update(bytes);
+    }
+}
+
+/// Where the transcript hash of the verifying key at `vk_path` is recorded
+pub fn transcript_record_path(vk_path: &str) -> String {
+    format!("{}.transcript", vk_path)
+}
+
+/// Record this build's transcript hash for the verifying key at `vk_path`
+pub fn record_transcript_hash(vk_path: &str) -> io::Result<()> {
+    fs::write(transcript_record_path(vk_path), TRANSCRIPT_HASH)
+}
+
+/// Error unless the verifying key at `vk_path` was set up with this build's hash
+pub fn check_transcript_hash(vk_path: &str) -> Result<(), String> {
+    check_transcript_record(vk_path, fs::read_to_string(transcript_record_path(vk_path)))
+}
+
+/// `check_transcript_hash` on the record of `vk_path` as read by the caller,
+/// e.g. from a bundled asset
+pub fn check_transcript_record(vk_path: &str, record: io::Result<String>) -> Result<(), String> {
+    let recorded = match record {
+        Ok(recorded) => recorded.trim().to_string(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => LEGACY_TRANSCRIPT_HASH.to_string(),
+        Err(e) => return Err(format!("Failed to read transcript record: {}", e)),
+    };
+    if recorded != TRANSCRIPT_HASH {
+        return Err(format!(
+            "Verifying key '{}' was set up with a {} transcript, but this build uses {}",
+            vk_path, recorded, TRANSCRIPT_HASH
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scalar;
+
+    type S = Sha256Engine<crate::BaseEngine>;
+
+    #[test]
+    fn test_sha256_transcript_is_deterministic_and_bound_to_input() {
+        let squeeze = |absorbed: &Scalar| {
+            let mut transcript = <S as Engine>::TE::new(b"test");
+            transcript.absorb(b"value", absorbed);
+            (
+                transcript.squeeze(b"c").unwrap(),
+                transcript.squeeze(b"c").unwrap(),
+            )
+        };
+        let (first, second) = squeeze(&Scalar::from(7u64));
+        assert_eq!(squeeze(&Scalar::from(7u64)), (first, second));
+        assert_ne!(first, second);
+        assert_ne!(squeeze(&Scalar::from(8u64)).0, first);
+    }
+
+    #[test]
+    fn test_transcript_record() {
+        let dir = std::env::temp_dir().join(format!("transcript_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let vk = dir.join("vk.key").to_string_lossy().into_owned();
+
+        // No record: a key from before the hash was recorded
+        assert_eq!(
+            check_transcript_hash(&vk).is_ok(),
+            TRANSCRIPT_HASH == LEGACY_TRANSCRIPT_HASH
+        );
+        record_transcript_hash(&vk).unwrap();
+        assert!(check_transcript_hash(&vk).is_ok());
+
+        fs::write(transcript_record_path(&vk), "blake3").unwrap();
+        assert!(check_transcript_hash(&vk)
+            .unwrap_err()
+            .contains("blake3 transcript"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,11 +1,11 @@
-use base64::engine::general_purpose::{STANDARD, URL_SAFE, URL_SAFE_NO_PAD};
-use base64::Engine;
 use bellpepper_core::SynthesisError;
+use rayon::prelude::*;
 use rust_witness::BigInt;
 use serde_json::Value;
 use std::{collections::HashMap, ops::Range, str::FromStr};
+use zeroize::Zeroizing;
 
-use crate::Scalar;
+use crate::{ct, issuer::ISSUER_KEY_FIELDS, secret::SecretScalar, Scalar};
 
 #[derive(Clone, Copy)]
 pub enum FieldParser {
@@ -76,6 +76,36 @@ pub fn parse_jwt_inputs(
     parse_inputs(json_value, field_defs)
 }
 
+/// Parse JWTPartial circuit inputs from JSON (see `midstate::partial_prepare_input`)
+pub fn parse_jwt_partial_inputs(
+    json_value: &Value,
+) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
+    let field_defs: &[(&str, FieldParser)] = &[
+        // BigInt scalar fields (wrapped in vec)
+        ("sig_r", FieldParser::BigIntScalar),
+        ("sig_s_inverse", FieldParser::BigIntScalar),
+        ("pubKeyX", FieldParser::BigIntScalar),
+        ("pubKeyY", FieldParser::BigIntScalar),
+        // U64 scalar fields (wrapped in vec)
+        ("tailLength", FieldParser::U64Scalar),
+        ("payloadOffset", FieldParser::U64Scalar),
+        ("matchesCount", FieldParser::U64Scalar),
+        // Array fields
+        ("tail", FieldParser::BigIntArray),
+        ("preHash", FieldParser::U64Array),
+        ("matchIndex", FieldParser::U64Array),
+        ("matchLength", FieldParser::U64Array),
+        ("claimLengths", FieldParser::BigIntArray),
+        ("decodeFlags", FieldParser::U64Array),
+        // 2D array fields (flattened)
+        ("matchSubstring", FieldParser::BigInt2DArray),
+        ("claims", FieldParser::BigInt2DArray),
+        ("ageClaimIndex", FieldParser::U64Scalar),
+    ];
+
+    parse_inputs(json_value, field_defs)
+}
+
 /// Parse Show circuit inputs from JSON
 pub fn parse_show_inputs(
     json_value: &Value,
@@ -96,6 +126,62 @@ pub fn parse_show_inputs(
     parse_inputs(json_value, field_defs)
 }
 
+/// Parse Range circuit inputs from JSON
+pub fn parse_range_inputs(
+    json_value: &Value,
+) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
+    let field_defs: &[(&str, FieldParser)] = &[
+        ("claim", FieldParser::BigIntArray),
+        ("rangeMin", FieldParser::BigIntScalar),
+        ("rangeMax", FieldParser::BigIntScalar),
+    ];
+
+    parse_inputs(json_value, field_defs)
+}
+
+/// Parse Membership circuit inputs from JSON
+pub fn parse_membership_inputs(
+    json_value: &Value,
+) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
+    let field_defs: &[(&str, FieldParser)] = &[
+        ("claim", FieldParser::BigIntArray),
+        ("root", FieldParser::BigIntScalar),
+        ("pathElements", FieldParser::BigIntArray),
+        ("pathIndices", FieldParser::BigIntArray),
+    ];
+
+    parse_inputs(json_value, field_defs)
+}
+
+/// Parse Pseudonym circuit inputs from JSON
+pub fn parse_pseudonym_inputs(
+    json_value: &Value,
+) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
+    let field_defs: &[(&str, FieldParser)] = &[
+        ("deviceKeyX", FieldParser::BigIntScalar),
+        ("deviceKeyY", FieldParser::BigIntScalar),
+        ("scope", FieldParser::BigIntScalar),
+        ("pseudonym", FieldParser::BigIntScalar),
+    ];
+
+    parse_inputs(json_value, field_defs)
+}
+
+/// Parse ECDSA circuit inputs from JSON
+pub fn parse_ecdsa_inputs(
+    json_value: &Value,
+) -> Result<HashMap<String, Vec<BigInt>>, SynthesisError> {
+    let field_defs: &[(&str, FieldParser)] = &[
+        ("s_inverse", FieldParser::BigIntScalar),
+        ("r", FieldParser::BigIntScalar),
+        ("m", FieldParser::BigIntScalar),
+        ("pubKeyX", FieldParser::BigIntScalar),
+        ("pubKeyY", FieldParser::BigIntScalar),
+    ];
+
+    parse_inputs(json_value, field_defs)
+}
+
 /// Convert a single BigInt to Scalar
 pub fn bigint_to_scalar(bigint_val: BigInt) -> Result<Scalar, SynthesisError> {
     let bytes = bigint_val.to_bytes_le().1;
@@ -113,17 +199,26 @@ pub fn bigint_to_scalar(bigint_val: BigInt) -> Result<Scalar, SynthesisError> {
         .ok_or(SynthesisError::Unsatisfiable)
 }
 
+/// Witness elements converted per rayon task; small enough to balance across
+/// cores, large enough that task overhead stays negligible
+pub(crate) const CONVERT_CHUNK: usize = 4096;
+
+/// Convert a witness to scalars, in parallel chunks, preserving order
 pub fn convert_bigint_to_scalar(
     bigint_witness: Vec<BigInt>,
 ) -> Result<Vec<Scalar>, SynthesisError> {
-    bigint_witness.into_iter().map(bigint_to_scalar).collect()
+    bigint_witness
+        .into_par_iter()
+        .with_min_len(CONVERT_CHUNK)
+        .map(bigint_to_scalar)
+        .collect()
 }
 
 #[derive(Debug, Clone)]
 pub struct PrepareSharedScalars {
-    pub keybinding_x: Scalar,
-    pub keybinding_y: Scalar,
-    pub claim_scalars: Vec<Scalar>,
+    pub keybinding_x: SecretScalar<Scalar>,
+    pub keybinding_y: SecretScalar<Scalar>,
+    pub claim_scalars: Vec<SecretScalar<Scalar>>,
 }
 
 pub fn compute_prepare_shared_scalars(
@@ -185,8 +280,8 @@ pub fn extract_prepare_shared_data(
         .and_then(|value| value.as_str())
         .ok_or(SynthesisError::AssignmentMissing)?;
 
-    let keybinding_x_bigint = bytes_to_bigint(&decode_base64(keybinding_x_b64)?);
-    let keybinding_y_bigint = bytes_to_bigint(&decode_base64(keybinding_y_b64)?);
+    let keybinding_x = SecretScalar::new(keybinding_coordinate(keybinding_x_b64)?);
+    let keybinding_y = SecretScalar::new(keybinding_coordinate(keybinding_y_b64)?);
 
     let age_claim_index = root_json
         .get("ageClaimIndex")
@@ -240,24 +335,17 @@ pub fn extract_prepare_shared_data(
     let encoded_claim = String::from_utf8(claim_bytes[..encoded_claim_len].to_vec())
         .map_err(|_| SynthesisError::AssignmentMissing)?;
 
-    let decoded_claim_bytes = decode_base64(&encoded_claim)?;
+    let decoded_claim_bytes = Zeroizing::new(decode_base64(&encoded_claim)?);
     let decoded_len = (max_claim_length * 3) / 4;
-
     if decoded_claim_bytes.len() > decoded_len {
         return Err(SynthesisError::AssignmentMissing);
     }
-
-    let mut claim_scalars: Vec<Scalar> = decoded_claim_bytes
-        .into_iter()
-        .map(|byte| Scalar::from(byte as u64))
+    // Built element by element so no unwiped `Vec<Scalar>` of the claim is freed
+    let mut claim_scalars: Vec<_> = decoded_claim_bytes
+        .iter()
+        .map(|&b| SecretScalar::new(Scalar::from(b as u64)))
         .collect();
-
-    while claim_scalars.len() < decoded_len {
-        claim_scalars.push(Scalar::from(0u64));
-    }
-
-    let keybinding_x = bigint_to_scalar(keybinding_x_bigint)?;
-    let keybinding_y = bigint_to_scalar(keybinding_y_bigint)?;
+    claim_scalars.resize_with(decoded_len, SecretScalar::default);
 
     Ok(PrepareSharedScalars {
         keybinding_x,
@@ -266,6 +354,22 @@ pub fn extract_prepare_shared_data(
     })
 }
 
+/// The issuer key `pubKeyX`, `pubKeyY` of a Prepare input, as the circuit takes them
+pub fn issuer_key_scalars(root_json: &Value) -> Result<[Scalar; 2], SynthesisError> {
+    let coordinate = |field: &str| {
+        parse_bigint_scalar(root_json, field)
+            .map_err(|_| SynthesisError::AssignmentMissing)
+            .and_then(bigint_to_scalar)
+    };
+    let [x, y] = ISSUER_KEY_FIELDS;
+    Ok([coordinate(x)?, coordinate(y)?])
+}
+
+/// A base64url `cnf.jwk` coordinate as a scalar
+pub(crate) fn keybinding_coordinate(b64: &str) -> Result<Scalar, SynthesisError> {
+    ct::be_bytes_to_scalar(&decode_base64(b64)?).ok_or(SynthesisError::Unsatisfiable)
+}
+
 pub fn parse_byte(value: &Value) -> Result<u8, SynthesisError> {
     if let Some(as_str) = value.as_str() {
         let parsed = as_str
@@ -281,38 +385,9 @@ pub fn parse_byte(value: &Value) -> Result<u8, SynthesisError> {
     Err(SynthesisError::AssignmentMissing)
 }
 
+/// Decode standard or URL-safe base64, padded or not, in constant time (see `ct`)
 pub fn decode_base64(encoded: &str) -> Result<Vec<u8>, SynthesisError> {
-    if encoded.len() % 4 == 1 {
-        return Err(SynthesisError::AssignmentMissing);
-    }
-
-    let mut candidates = vec![encoded.to_string()];
-
-    let mut padded = encoded.to_string();
-    match encoded.len() % 4 {
-        0 => {}
-        2 => padded.push_str("=="),
-        3 => padded.push('='),
-        _ => {}
-    }
-
-    if padded != encoded {
-        candidates.push(padded);
-    }
-
-    for candidate in candidates {
-        if let Ok(decoded) = URL_SAFE_NO_PAD.decode(candidate.as_bytes()) {
-            return Ok(decoded);
-        }
-        if let Ok(decoded) = URL_SAFE.decode(candidate.as_bytes()) {
-            return Ok(decoded);
-        }
-        if let Ok(decoded) = STANDARD.decode(candidate.as_bytes()) {
-            return Ok(decoded);
-        }
-    }
-
-    Err(SynthesisError::AssignmentMissing)
+    ct::decode_base64(encoded.as_bytes()).ok_or(SynthesisError::AssignmentMissing)
 }
 
 // JSON Parsing Helpers
@@ -395,14 +470,6 @@ fn parse_2d_bigint_array(json: &Value, key: &str) -> Result<Vec<BigInt>, String>
     Ok(result)
 }
 
-fn bytes_to_bigint(bytes: &[u8]) -> BigInt {
-    let mut acc = BigInt::from(0u8);
-    for &byte in bytes {
-        acc = (acc << 8) + BigInt::from(byte);
-    }
-    acc
-}
-
 /// Layout information for the JWT circuit outputs within the witness vector.
 #[derive(Debug, Clone, Copy)]
 pub struct JwtOutputLayout {
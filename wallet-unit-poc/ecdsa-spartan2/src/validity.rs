@@ -0,0 +1,126 @@
+//! Issued-at / expiry metadata carried by Prepare proofs.
+//!
+//! The timestamps follow the domain tag in the Prepare circuit's public values,
+//! so they are absorbed into the proof transcript and a proof cannot be
+//! re-labelled with another window after the fact. They are not constrained by
+//! the circuit, though: the prover picks them when proving, so they state when
+//! the prover claims the proof was made, not when it was. `check_fresh` lets a
+//! relying party reject proofs whose own window is stale, but gives no
+//! freshness guarantee against a prover willing to lie about the time; bind
+//! the presentation to a verifier nonce (the Show `messageHash`) for that.
+//!
+//! They are read from the Prepare input JSON (`proofIssuedAt`,
+//! `proofExpiresAt`, Unix seconds) and default to zero when absent, which
+//! `check_fresh` always rejects. Adding them changed the Prepare shape, so keys
+//! from before must be set up again.
+
+use ff::PrimeField;
+use serde_json::Value;
+
+use crate::Scalar;
+
+pub const ISSUED_AT_FIELD: &str = "proofIssuedAt";
+pub const EXPIRES_AT_FIELD: &str = "proofExpiresAt";
+
+/// Validity window of a Prepare proof, in Unix seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProofValidity {
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+impl ProofValidity {
+    /// Window starting at `now` and lasting `ttl_secs`
+    pub fn starting_at(now: u64, ttl_secs: u64) -> Self {
+        Self {
+            issued_at: now,
+            expires_at: now.saturating_add(ttl_secs),
+        }
+    }
+
+    /// Read the window from a Prepare input, zero where a field is missing
+    pub fn from_input(input: &Value) -> Self {
+        let field = |name| input.get(name).and_then(Value::as_u64).unwrap_or(0);
+        Self {
+            issued_at: field(ISSUED_AT_FIELD),
+            expires_at: field(EXPIRES_AT_FIELD),
+        }
+    }
+
+    /// Write the window into a Prepare input
+    pub fn apply_to_input(&self, input: &mut Value) -> Result<(), String> {
+        let object = input
+            .as_object_mut()
+            .ok_or("prepare input must be a JSON object")?;
+        object.insert(ISSUED_AT_FIELD.into(), self.issued_at.into());
+        object.insert(EXPIRES_AT_FIELD.into(), self.expires_at.into());
+        Ok(())
+    }
+
+    /// The Prepare circuit's public values after the domain tag
+    pub fn to_public_values(&self) -> Vec<Scalar> {
+        vec![Scalar::from(self.issued_at), Scalar::from(self.expires_at)]
+    }
+
+    /// Recover the window from a verified proof's public values, minus the domain tag
+    pub fn from_public_values(values: &[Scalar]) -> Result<Self, String> {
+        let [issued_at, expires_at] = values else {
+            return Err(format!(
+                "expected 2 public values (issued-at, expiry), got {}",
+                values.len()
+            ));
+        };
+        Ok(Self {
+            issued_at: scalar_to_u64(issued_at)?,
+            expires_at: scalar_to_u64(expires_at)?,
+        })
+    }
+
+    /// Accept only proofs issued at most `max_age_secs` before `now` and not yet expired
+    /// The window is the prover's claim; see the module docs.
+    pub fn check_fresh(&self, now: u64, max_age_secs: u64) -> Result<(), String> {
+        if self.issued_at == 0 {
+            return Err("proof carries no issued-at time".to_string());
+        }
+        if self.issued_at > now {
+            return Err(format!("proof issued in the future ({})", self.issued_at));
+        }
+        if now - self.issued_at > max_age_secs {
+            return Err(format!(
+                "proof is {}s old, max age is {}s",
+                now - self.issued_at,
+                max_age_secs
+            ));
+        }
+        if now >= self.expires_at {
+            return Err(format!("proof expired at {}", self.expires_at));
+        }
+        Ok(())
+    }
+}
+
+fn scalar_to_u64(value: &Scalar) -> Result<u64, String> {
+    let repr = value.to_repr();
+    let (low, high) = repr.as_ref().split_at(8);
+    if high.iter().any(|b| *b != 0) {
+        return Err("timestamp does not fit in 64 bits".to_string());
+    }
+    Ok(u64::from_le_bytes(low.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_values_roundtrip_and_freshness() {
+        let validity = ProofValidity::starting_at(1_700_000_000, 3600);
+        let recovered = ProofValidity::from_public_values(&validity.to_public_values()).unwrap();
+        assert_eq!(recovered, validity);
+
+        assert!(validity.check_fresh(1_700_000_060, 600).is_ok());
+        assert!(validity.check_fresh(1_700_001_000, 600).is_err());
+        assert!(validity.check_fresh(1_700_003_600, 7200).is_err());
+        assert!(ProofValidity::default().check_fresh(1, u64::MAX).is_err());
+    }
+}
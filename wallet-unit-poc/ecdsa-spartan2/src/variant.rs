@@ -0,0 +1,231 @@
+//! Prepare circuit variants compiled for different maximum JWT lengths.
+//!
+//! The JWT circuit's constraint count grows with the longest token it accepts,
+//! so a short credential proved in a circuit sized for long ones wastes most of
+//! the prover's work. Each variant is its own circom build (`jwt_1k`, `jwt`,
+//! `jwt_4k` in `circom/circuits.json`) with its own keys. `JwtVariant::for_jwt`
+//! picks the smallest compiled-in variant a credential fits. The Prepare input
+//! built for it pads `message` to exactly that variant's maximum, so
+//! `JwtVariant::for_input` recovers the variant from the input alone.
+//!
+//! The active variant, set with `set_jwt_variant`, decides which R1CS
+//! `PrepareCircuit` synthesizes and, through `key_path`, which Prepare keys are
+//! loaded and saved. The 2 KB variant is the default and keeps the original
+//! file names; the 1 KB and 4 KB variants need the `jwt-variants` feature,
+//! which requires `yarn compile:jwt_variants`. Show is the same for every
+//! variant, since the claim it receives has the same length in all of them.
+
+use std::{path::Path, sync::RwLock};
+
+use bellpepper_core::SynthesisError;
+use rust_witness::BigInt;
+use serde_json::Value;
+
+use crate::{
+    asset_source::{asset_source, has_asset},
+    circuits::prepare_circuit,
+    credential::PrepareParams,
+    layout::{in_assets, in_documents},
+    setup::{PREPARE_PROVING_KEY, PREPARE_VERIFYING_KEY},
+};
+
+/// A compiled JWT circuit size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JwtVariant {
+    /// Tokens up to 1 KB
+    Jwt1k,
+    /// Tokens up to 1920 bytes, the circuit the project has always shipped
+    #[default]
+    Jwt2k,
+    /// Tokens up to 4 KB
+    Jwt4k,
+}
+
+static VARIANT: RwLock<JwtVariant> = RwLock::new(JwtVariant::Jwt2k);
+
+impl JwtVariant {
+    /// Every variant, smallest first
+    pub const ALL: [JwtVariant; 3] = [JwtVariant::Jwt1k, JwtVariant::Jwt2k, JwtVariant::Jwt4k];
+
+    /// Name of the circom build, as in `circom/circuits.json`
+    pub fn name(self) -> &'static str {
+        match self {
+            JwtVariant::Jwt1k => "jwt_1k",
+            JwtVariant::Jwt2k => "jwt",
+            JwtVariant::Jwt4k => "jwt_4k",
+        }
+    }
+
+    /// Template parameters the variant was compiled with
+    pub fn params(self) -> PrepareParams {
+        let (max_message_length, max_b64_payload_length) = match self {
+            JwtVariant::Jwt1k => (1024, 1000),
+            JwtVariant::Jwt2k => (1920, 1900),
+            JwtVariant::Jwt4k => (4096, 4000),
+        };
+        PrepareParams {
+            max_message_length,
+            max_b64_payload_length,
+            ..PrepareParams::default()
+        }
+    }
+
+    /// Whether this build links the variant's witness generator
+    pub fn is_available(self) -> bool {
+        self == JwtVariant::Jwt2k || cfg!(feature = "jwt-variants")
+    }
+
+    /// Variants this build can prove, smallest first
+    pub fn available() -> Vec<JwtVariant> {
+        Self::ALL.into_iter().filter(|v| v.is_available()).collect()
+    }
+
+    /// The R1CS, relative to the circom project directory
+    pub fn r1cs_path(self) -> String {
+        format!("build/{0}/{0}_js/{0}.r1cs", self.name())
+    }
+
+    /// Smallest available variant whose limits fit the issuer-signed JWT of `sd_jwt`
+    pub fn for_jwt(sd_jwt: &str) -> Result<JwtVariant, String> {
+        let jwt = sd_jwt.split('~').next().unwrap_or_default();
+        let signed = jwt.rsplit_once('.').map_or(jwt, |(signed, _)| signed);
+        let payload_len = signed.split('.').nth(1).map_or(0, str::len);
+        // SHA-256 padding: a 0x80 byte and the 64-bit length, rounded up to a block
+        let padded_len = (signed.len() + 9).div_ceil(64) * 64;
+
+        Self::available()
+            .into_iter()
+            .find(|variant| {
+                let params = variant.params();
+                padded_len <= params.max_message_length
+                    && payload_len <= params.max_b64_payload_length
+            })
+            .ok_or_else(|| {
+                format!(
+                    "JWT of {} bytes exceeds the largest available circuit",
+                    signed.len()
+                )
+            })
+    }
+
+    /// The variant a Prepare input was built for, from the length of its `message`
+    pub fn for_input(input: &Value) -> Option<JwtVariant> {
+        let len = input.get("message")?.as_array()?.len();
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.params().max_message_length == len)
+    }
+
+    /// Run the variant's witness generator
+    pub fn witness(
+        self,
+        inputs: std::collections::HashMap<String, Vec<BigInt>>,
+    ) -> Result<Vec<BigInt>, SynthesisError> {
+        match self {
+            JwtVariant::Jwt2k => Ok(prepare_circuit::jwt_witness(inputs)),
+            #[cfg(feature = "jwt-variants")]
+            JwtVariant::Jwt1k => Ok(prepare_circuit::jwt_1k_witness(inputs)),
+            #[cfg(feature = "jwt-variants")]
+            JwtVariant::Jwt4k => Ok(prepare_circuit::jwt_4k_witness(inputs)),
+            #[cfg(not(feature = "jwt-variants"))]
+            JwtVariant::Jwt1k | JwtVariant::Jwt4k => Err(SynthesisError::AssignmentMissing),
+        }
+    }
+
+    /// Where this variant keeps the Prepare key at `path`; other paths are unchanged
+    pub fn resolve_key(self, path: &str) -> String {
+        let suffix = match self {
+            JwtVariant::Jwt1k => "1k",
+            JwtVariant::Jwt2k => return path.to_string(),
+            JwtVariant::Jwt4k => "4k",
+        };
+        match path {
+            PREPARE_PROVING_KEY => format!("keys/prepare_{}_proving.key", suffix),
+            PREPARE_VERIFYING_KEY => format!("keys/prepare_{}_verifying.key", suffix),
+            _ => path.to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for JwtVariant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|variant| variant.name() == s)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid JWT variant '{}'. Must be 'jwt_1k', 'jwt' or 'jwt_4k'",
+                    s
+                )
+            })
+    }
+}
+
+/// Select the variant used by `PrepareCircuit` and the Prepare keys
+pub fn set_jwt_variant(variant: JwtVariant) {
+    if let Ok(mut slot) = VARIANT.write() {
+        *slot = variant;
+    }
+}
+
+pub fn jwt_variant() -> JwtVariant {
+    VARIANT.read().map(|v| *v).unwrap_or_default()
+}
+
+/// Key `path` resolved for the active variant, to save
+pub fn key_path(path: &str) -> String {
+    in_documents(&jwt_variant().resolve_key(path))
+}
+
+/// Key `path` resolved for the active variant, to load: a key shipped with the
+/// app unless one was set up or imported since
+pub fn stored_key_path(path: &str) -> String {
+    in_assets(&jwt_variant().resolve_key(path))
+}
+
+/// Name to ask the asset source for, if key `path` exists only there
+pub fn bundled_key_path(path: &str) -> Option<String> {
+    let key = jwt_variant().resolve_key(path);
+    (asset_source().is_some() && !Path::new(&in_assets(&key)).exists()).then_some(key)
+}
+
+/// Whether key `path` can be loaded, from disk or the asset source
+pub fn has_stored_key(path: &str) -> bool {
+    Path::new(&stored_key_path(path)).exists()
+        || bundled_key_path(path).is_some_and(|asset| has_asset(&asset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variant_selection_and_key_paths() {
+        let jwt =
+            |payload_len: usize| format!("eyJhbGciOiJFUzI1NiJ9.{}.sig", "A".repeat(payload_len));
+        assert_eq!(JwtVariant::for_jwt(&jwt(1500)), Ok(JwtVariant::Jwt2k));
+        assert!(JwtVariant::for_jwt(&jwt(8000)).is_err());
+        if JwtVariant::Jwt1k.is_available() {
+            assert_eq!(JwtVariant::for_jwt(&jwt(100)), Ok(JwtVariant::Jwt1k));
+        }
+
+        let input = serde_json::json!({ "message": vec![0; 4096] });
+        assert_eq!(JwtVariant::for_input(&input), Some(JwtVariant::Jwt4k));
+
+        assert_eq!(
+            JwtVariant::Jwt2k.resolve_key(PREPARE_PROVING_KEY),
+            PREPARE_PROVING_KEY
+        );
+        assert_eq!(
+            JwtVariant::Jwt1k.resolve_key(PREPARE_VERIFYING_KEY),
+            "keys/prepare_1k_verifying.key"
+        );
+        assert_eq!(
+            JwtVariant::Jwt4k.r1cs_path(),
+            "build/jwt_4k/jwt_4k_js/jwt_4k.r1cs"
+        );
+        assert_eq!("jwt_1k".parse(), Ok(JwtVariant::Jwt1k));
+    }
+}
@@ -0,0 +1,183 @@
+//! Golden test vectors pinning the serialized wire format.
+//!
+//! `generate_vectors` writes canonical inputs, verifying keys, proofs, and
+//! instances for both circuits plus a `manifest.json` of their digests. The
+//! integration tests in `tests/golden_vectors.rs` check committed vectors still
+//! deserialize, re-serialize byte-for-byte, and verify.
+
+use std::{
+    fs::{self, create_dir_all, File},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use spartan2::traits::circuit::SpartanCircuit;
+use tracing::info;
+
+use crate::{
+    load_instance, load_shared_blinds, load_witness,
+    prover::{generate_shared_blinds_with_rng, prove_circuit_with_pk, reblind_with_loaded_data},
+    setup::{setup_circuit_keys_no_save, PRIVATE_INSTANCE_EXT},
+    PrepareCircuit, Scalar, ShowCircuit, E,
+};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Bumped whenever the manifest layout itself changes
+pub const MANIFEST_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorManifest {
+    pub format: u32,
+    pub circuits: Vec<CircuitVector>,
+}
+
+/// One circuit's vector; file names are relative to the manifest directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitVector {
+    pub name: String,
+    pub input: String,
+    pub verifying_key: String,
+    pub proof: String,
+    pub instance: String,
+    /// The proving key is too large to commit, so only its digest is pinned
+    pub proving_key_sha256: String,
+    pub verifying_key_sha256: String,
+    pub proof_sha256: String,
+    pub instance_sha256: String,
+    pub comm_w_shared: String,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Randomness for the vectors' shared blinds
+/// Under `test-determinism` it is `deterministic_rng`, so the blinds, and so
+/// comm_W_shared, are reproducible; otherwise the OS RNG.
+fn vector_rng() -> Box<dyn ff::derive::rand_core::CryptoRngCore + Send> {
+    #[cfg(feature = "test-determinism")]
+    {
+        crate::prover::deterministic_rng()
+    }
+    #[cfg(not(feature = "test-determinism"))]
+    {
+        crate::prover::default_rng()
+    }
+}
+
+/// Generate golden vectors for both circuits into `output_dir`
+/// Inputs default to the development inputs under `../circom/inputs`
+pub fn generate_vectors(
+    output_dir: &Path,
+    prepare_input: Option<PathBuf>,
+    show_input: Option<PathBuf>,
+) -> Result<VectorManifest, Box<dyn std::error::Error>> {
+    create_dir_all(output_dir.join("inputs"))?;
+
+    // Both proofs are reblinded under the same blinds so their comm_W_shared match.
+    let blinds_path = std::env::temp_dir().join("golden_shared_blinds.bin");
+    generate_shared_blinds_with_rng::<E>(&blinds_path.to_string_lossy(), 1, &mut *vector_rng())?;
+    let shared_blinds = load_shared_blinds::<E>(&blinds_path.to_string_lossy())?;
+    let _ = fs::remove_file(&blinds_path);
+
+    let prepare_input =
+        prepare_input.unwrap_or_else(|| PathBuf::from("../circom/inputs/jwt/default.json"));
+    let show_input =
+        show_input.unwrap_or_else(|| PathBuf::from("../circom/inputs/show/default.json"));
+
+    let circuits = vec![
+        generate_circuit_vector(
+            output_dir,
+            "prepare",
+            PrepareCircuit::new(prepare_input.clone()),
+            &prepare_input,
+            &shared_blinds,
+        )?,
+        generate_circuit_vector(
+            output_dir,
+            "show",
+            ShowCircuit::new(show_input.clone()),
+            &show_input,
+            &shared_blinds,
+        )?,
+    ];
+
+    let manifest = VectorManifest {
+        format: MANIFEST_FORMAT,
+        circuits,
+    };
+    serde_json::to_writer_pretty(File::create(output_dir.join(MANIFEST_FILE))?, &manifest)?;
+    info!("Golden vectors written to: {}", output_dir.display());
+
+    Ok(manifest)
+}
+
+fn generate_circuit_vector<C: SpartanCircuit<E> + Clone + std::fmt::Debug>(
+    output_dir: &Path,
+    name: &str,
+    circuit: C,
+    input_path: &Path,
+    shared_blinds: &[Scalar],
+) -> Result<CircuitVector, Box<dyn std::error::Error>> {
+    let input = format!("inputs/{}.json", name);
+    fs::copy(input_path, output_dir.join(&input))?;
+
+    let (pk, vk) = setup_circuit_keys_no_save(circuit.clone());
+
+    let verifying_key = format!("{}_verifying.key", name);
+    let vk_bytes = bincode::serialize(&vk)?;
+    fs::write(output_dir.join(&verifying_key), &vk_bytes)?;
+
+    let proof = format!("{}_proof.bin", name);
+    let instance_path = output_dir
+        .join(format!("{}_instance.bin", name))
+        .to_string_lossy()
+        .into_owned();
+    // The full instance; its public part is written beside it
+    let instance = format!("{}_instance.{}", name, PRIVATE_INSTANCE_EXT);
+    let proof_path = output_dir.join(&proof).to_string_lossy().into_owned();
+    // The witness is required by the prover API but is secret, so it is discarded
+    let witness_path = std::env::temp_dir()
+        .join(format!("{}_golden_witness.bin", name))
+        .to_string_lossy()
+        .into_owned();
+
+    prove_circuit_with_pk(
+        circuit.clone(),
+        &pk,
+        &instance_path,
+        &witness_path,
+        &proof_path,
+    )?;
+    reblind_with_loaded_data(
+        circuit,
+        &pk,
+        load_instance::<E>(&instance_path)?,
+        load_witness::<E>(&witness_path)?,
+        shared_blinds,
+        &instance_path,
+        &witness_path,
+        &proof_path,
+    )?;
+    let _ = fs::remove_file(&witness_path);
+
+    let split_instance = load_instance::<E>(&instance_path)?;
+
+    Ok(CircuitVector {
+        name: name.to_string(),
+        input,
+        proving_key_sha256: sha256_hex(&bincode::serialize(&pk)?),
+        verifying_key_sha256: sha256_hex(&vk_bytes),
+        proof_sha256: sha256_hex(&fs::read(output_dir.join(&proof))?),
+        instance_sha256: sha256_hex(&fs::read(output_dir.join(&instance))?),
+        comm_w_shared: format!("{:?}", split_instance.comm_W_shared),
+        verifying_key,
+        proof,
+        instance,
+    })
+}
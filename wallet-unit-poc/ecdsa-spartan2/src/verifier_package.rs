@@ -0,0 +1,342 @@
+//! Verifier packages: what a relying party needs to check Prepare and Show proofs.
+//!
+//! `export_verifier_package` bundles the stored verifying keys with their
+//! digests, the R1CS digests and format versions of this build, the domain the
+//! proofs are made under, and the public values each circuit exposes:
+//!
+//! ```text
+//! ZKVP || package version (u32 LE) || bincode(VerifierPackage)
+//! ```
+//!
+//! A relying party loads the one file with `VerifierContext::from_package`,
+//! which checks the versions and key digests and decodes both keys up front,
+//! and then verifies proofs against it. No proving keys are included.
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use spartan2::zk_spartan::R1CSSNARK;
+
+use crate::{
+    build_info::{r1cs_digest, KEY_FORMAT_VERSION, SPARTAN2_REV},
+    domain::{domain_separator, domain_tag_under},
+    format,
+    setup::{deserialize_bounded, PREPARE_VERIFYING_KEY, SHOW_VERIFYING_KEY},
+    variant::stored_key_path,
+    vectors::sha256_hex,
+    witness::CircuitKind,
+    Scalar, VerifyingKey, E,
+};
+
+const MAGIC: &[u8; 4] = b"ZKVP";
+
+/// Where `export-verifier` writes the package by default
+pub const DEFAULT_VERIFIER_PACKAGE: &str = "keys/verifier_package.bin";
+
+/// Layout version of the package itself
+pub const VERIFIER_PACKAGE_VERSION: u32 = 1;
+
+/// One public value of a circuit, in the order the proof exposes them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputField {
+    pub name: String,
+    pub description: String,
+}
+
+/// Verifying key and public-input schema for one circuit
+#[derive(Serialize, Deserialize)]
+pub struct PackagedVerifyingKey {
+    pub circuit: String,
+    /// SHA-256 of the `.r1cs` the key was generated from
+    pub r1cs_sha256: String,
+    pub verifying_key_sha256: String,
+    pub verifying_key: Vec<u8>,
+    pub public_inputs: Vec<PublicInputField>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VerifierPackage {
+    /// `KEY_FORMAT_VERSION` of the build that wrote the keys
+    pub key_format_version: u32,
+    /// Newest proof and instance format the exporting build writes
+    pub artifact_format_version: u32,
+    /// spartan2 revision that generated the keys
+    pub spartan2_rev: String,
+    /// Domain separator the proofs' domain tags are derived from
+    pub domain: String,
+    pub circuits: Vec<PackagedVerifyingKey>,
+}
+
+/// The public values `circuit` exposes, in order
+pub fn public_input_schema(circuit: CircuitKind) -> Vec<PublicInputField> {
+    let field = |name: &str, description: &str| PublicInputField {
+        name: name.to_string(),
+        description: description.to_string(),
+    };
+    let mut fields = vec![field(
+        "domain_tag",
+        "SHA-256 of '<domain>/<circuit>', first 31 bytes, little-endian",
+    )];
+    if circuit == CircuitKind::Prepare {
+        fields.extend([
+            field("issued_at", "Credential issued-at time, Unix seconds"),
+            field("expires_at", "Credential expiry time, Unix seconds"),
+            field("issuer_key_x", "X coordinate of the issuer's signing key"),
+            field("issuer_key_y", "Y coordinate of the issuer's signing key"),
+        ]);
+    }
+    if circuit == CircuitKind::Show {
+        fields.extend([
+            field(
+                "message_hash",
+                "SHA-256 of the verifier nonce the device signed, modulo the P-256 order",
+            ),
+            field(
+                "reference_year",
+                "Year of the date the holder is at least 18 on",
+            ),
+            field("reference_month", "Month of that date, 1-12"),
+            field("reference_day", "Day of that date, 1-31"),
+        ]);
+    }
+    fields
+}
+
+pub(crate) fn vk_path(circuit: CircuitKind) -> String {
+    stored_key_path(match circuit {
+        CircuitKind::Prepare => PREPARE_VERIFYING_KEY,
+        CircuitKind::Show => SHOW_VERIFYING_KEY,
+    })
+}
+
+/// Package the stored Prepare and Show verifying keys under the current domain
+pub fn export_verifier_package() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let circuits = [CircuitKind::Prepare, CircuitKind::Show]
+        .into_iter()
+        .map(|circuit| {
+            let path = vk_path(circuit);
+            let verifying_key =
+                fs::read(&path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+            Ok(PackagedVerifyingKey {
+                circuit: circuit.name().to_string(),
+                r1cs_sha256: r1cs_digest(circuit).to_string(),
+                verifying_key_sha256: sha256_hex(&verifying_key),
+                verifying_key,
+                public_inputs: public_input_schema(circuit),
+            })
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+    let package = VerifierPackage {
+        key_format_version: KEY_FORMAT_VERSION,
+        artifact_format_version: format::CURRENT_VERSION,
+        spartan2_rev: SPARTAN2_REV.to_string(),
+        domain: domain_separator(),
+        circuits,
+    };
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&VERIFIER_PACKAGE_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, &package)?;
+    Ok(bytes)
+}
+
+/// Decode a package, checking its header and versions
+pub fn parse_verifier_package(bytes: &[u8]) -> Result<VerifierPackage, String> {
+    let header_len = MAGIC.len() + 4;
+    if bytes.len() < header_len || !bytes.starts_with(MAGIC) {
+        return Err("Not a verifier package".to_string());
+    }
+    let version = u32::from_le_bytes(bytes[MAGIC.len()..header_len].try_into().unwrap());
+    if version != VERIFIER_PACKAGE_VERSION {
+        return Err(format!(
+            "Verifier package version {} is not supported (expected {})",
+            version, VERIFIER_PACKAGE_VERSION
+        ));
+    }
+    let package: VerifierPackage = deserialize_bounded(&bytes[header_len..])
+        .map_err(|e| format!("Malformed verifier package: {}", e))?;
+    if package.key_format_version != KEY_FORMAT_VERSION {
+        return Err(format!(
+            "Keys are in format version {}, this build reads {}",
+            package.key_format_version, KEY_FORMAT_VERSION
+        ));
+    }
+    if package.artifact_format_version > format::CURRENT_VERSION {
+        return Err(format!(
+            "Proofs are in format version {}, this build reads up to {}",
+            package.artifact_format_version,
+            format::CURRENT_VERSION
+        ));
+    }
+    Ok(package)
+}
+
+/// A circuit's decoded verifying key and the public values its proofs expose
+struct CircuitVerifier {
+    vk: VerifyingKey,
+    public_inputs: Vec<PublicInputField>,
+}
+
+/// Everything needed to verify Prepare and Show proofs, loaded from a verifier package
+pub struct VerifierContext {
+    domain: String,
+    spartan2_rev: String,
+    prepare: CircuitVerifier,
+    show: CircuitVerifier,
+}
+
+impl VerifierContext {
+    /// Validate `bytes` and decode both verifying keys
+    /// The package must hold exactly one key per circuit, each matching its digest.
+    pub fn from_package(bytes: &[u8]) -> Result<Self, String> {
+        let package = parse_verifier_package(bytes)?;
+        let mut prepare = None;
+        let mut show = None;
+        for packaged in package.circuits {
+            let circuit: CircuitKind = packaged.circuit.parse()?;
+            let slot = match circuit {
+                CircuitKind::Prepare => &mut prepare,
+                CircuitKind::Show => &mut show,
+            };
+            if slot.is_some() {
+                return Err(format!("{} verifying key appears twice", circuit.name()));
+            }
+            *slot = Some(decode_verifier(circuit, packaged)?);
+        }
+
+        let missing = |circuit: CircuitKind| format!("{} verifying key is missing", circuit.name());
+        Ok(Self {
+            domain: package.domain,
+            spartan2_rev: package.spartan2_rev,
+            prepare: prepare.ok_or_else(|| missing(CircuitKind::Prepare))?,
+            show: show.ok_or_else(|| missing(CircuitKind::Show))?,
+        })
+    }
+
+    /// Domain separator the relying party expects proofs to be made under
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// spartan2 revision the keys were generated with
+    pub fn spartan2_rev(&self) -> &str {
+        &self.spartan2_rev
+    }
+
+    fn circuit(&self, circuit: CircuitKind) -> &CircuitVerifier {
+        match circuit {
+            CircuitKind::Prepare => &self.prepare,
+            CircuitKind::Show => &self.show,
+        }
+    }
+
+    pub fn verifying_key(&self, circuit: CircuitKind) -> &VerifyingKey {
+        &self.circuit(circuit).vk
+    }
+
+    pub fn public_inputs(&self, circuit: CircuitKind) -> &[PublicInputField] {
+        &self.circuit(circuit).public_inputs
+    }
+
+    /// Verify `proof` as a `circuit` proof made under the package's domain
+    /// Returns its public values after the domain tag, in schema order.
+    pub fn verify(
+        &self,
+        circuit: CircuitKind,
+        proof: &R1CSSNARK<E>,
+    ) -> Result<Vec<Scalar>, String> {
+        let verifier = self.circuit(circuit);
+        let public_values = proof
+            .verify(&verifier.vk)
+            .map_err(|e| format!("{} proof rejected: {}", circuit.name(), e))?;
+        if public_values.len() != verifier.public_inputs.len() {
+            return Err(format!(
+                "{} proof has {} public values, the package describes {}",
+                circuit.name(),
+                public_values.len(),
+                verifier.public_inputs.len()
+            ));
+        }
+        match public_values.split_first() {
+            Some((tag, rest)) if *tag == domain_tag_under(&self.domain, circuit.name()) => {
+                Ok(rest.to_vec())
+            }
+            _ => Err(format!(
+                "{} proof was not made for domain '{}'",
+                circuit.name(),
+                self.domain
+            )),
+        }
+    }
+}
+
+fn decode_verifier(
+    circuit: CircuitKind,
+    packaged: PackagedVerifyingKey,
+) -> Result<CircuitVerifier, String> {
+    if sha256_hex(&packaged.verifying_key) != packaged.verifying_key_sha256 {
+        return Err(format!("{} verifying key digest mismatch", circuit.name()));
+    }
+    let vk = deserialize_bounded::<VerifyingKey>(&packaged.verifying_key)
+        .map_err(|e| format!("{} verifying key does not decode: {}", circuit.name(), e))?;
+    Ok(CircuitVerifier {
+        vk,
+        public_inputs: packaged.public_inputs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package_bytes(package: &VerifierPackage) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERIFIER_PACKAGE_VERSION.to_le_bytes());
+        bytes.extend(bincode::serialize(package).unwrap());
+        bytes
+    }
+
+    #[test]
+    fn test_rejects_tampered_and_incomplete_packages() {
+        let mut package = VerifierPackage {
+            key_format_version: KEY_FORMAT_VERSION,
+            artifact_format_version: format::CURRENT_VERSION,
+            spartan2_rev: SPARTAN2_REV.to_string(),
+            domain: "zkid-v1".to_string(),
+            circuits: vec![PackagedVerifyingKey {
+                circuit: "show".to_string(),
+                r1cs_sha256: r1cs_digest(CircuitKind::Show).to_string(),
+                verifying_key_sha256: sha256_hex(b"vk"),
+                verifying_key: b"vk".to_vec(),
+                public_inputs: public_input_schema(CircuitKind::Show),
+            }],
+        };
+        assert!(parse_verifier_package(&package_bytes(&package)).is_ok());
+
+        package.circuits[0].verifying_key[0] ^= 1;
+        let err = VerifierContext::from_package(&package_bytes(&package)).err();
+        assert_eq!(err.as_deref(), Some("show verifying key digest mismatch"));
+
+        package.circuits.clear();
+        let err = VerifierContext::from_package(&package_bytes(&package)).err();
+        assert_eq!(err.as_deref(), Some("prepare verifying key is missing"));
+
+        package.artifact_format_version = format::CURRENT_VERSION + 1;
+        assert!(parse_verifier_package(&package_bytes(&package)).is_err());
+        assert!(parse_verifier_package(b"ZKKP\x01\x00\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_schema_matches_public_values() {
+        assert_eq!(
+            public_input_schema(CircuitKind::Show).len(),
+            crate::show::SHOW_PUBLIC_VALUES
+        );
+        let prepare = crate::PreparePublicValues::default().to_public_values();
+        assert_eq!(
+            public_input_schema(CircuitKind::Prepare).len(),
+            1 + prepare.len()
+        );
+    }
+}
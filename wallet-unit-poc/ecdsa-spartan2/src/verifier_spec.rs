@@ -0,0 +1,265 @@
+//! Machine-readable description of Prepare and Show verification.
+//!
+//! `export_verifier_spec` describes, as JSON, everything an independent
+//! verifier (in Go, TypeScript, ...) needs beyond the spartan2 paper to check
+//! the proofs this build writes: the curve and field moduli, the transcript
+//! hash and its initial label, the artifact envelope and bincode encoding, and
+//! per circuit the public inputs, the verifying key layout and, when a proof is
+//! stored, its sumcheck round counts and commitment sizes. Layouts are the
+//! `inspect` size trees with byte offsets added, so a reimplementation can
+//! decode the same files and be cross-tested against them.
+//!
+//! Hyrax generators are not derived by the verifier: they are serialized in
+//! the verifying key, under the `ck*` paths listed as `generator_paths`, and
+//! the key's SHA-256 pins them.
+
+use std::fs;
+
+use ff::PrimeField;
+use serde::Serialize;
+use spartan2::traits::Engine;
+
+use crate::{
+    build_info::{r1cs_digest, KEY_FORMAT_VERSION, SPARTAN2_REV},
+    domain::domain_separator,
+    format,
+    inspect::{
+        inspect_proof_bytes, measure, CommitmentSummary, SizeNode, SumcheckSummary, LEN_PREFIX,
+        VARIANT_TAG,
+    },
+    namespace::artifact_path,
+    setup::{deserialize_bounded, PREPARE_PROOF, SHOW_PROOF},
+    transcript::TRANSCRIPT_HASH,
+    vectors::sha256_hex,
+    verifier_package::{public_input_schema, vk_path, PublicInputField},
+    witness::CircuitKind,
+    VerifyingKey, E,
+};
+
+/// Layout version of the spec itself
+pub const VERIFIER_SPEC_VERSION: u32 = 1;
+
+/// Label the prover and verifier transcripts are created with
+pub const TRANSCRIPT_LABEL: &str = "R1CSSNARK";
+
+/// Curve, field and Fiat-Shamir choices of the engine
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineSpec {
+    pub name: String,
+    /// Scalar field modulus, hex
+    pub scalar_modulus: String,
+    /// Base field modulus, hex
+    pub base_modulus: String,
+    pub transcript_hash: String,
+    pub transcript_label: String,
+}
+
+/// How stored artifacts are encoded
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodingSpec {
+    pub serialization: String,
+    pub envelope: String,
+    pub artifact_format_version: u32,
+    pub key_format_version: u32,
+}
+
+/// One component of a serialized value, at its offset in the bincode payload
+/// (after the envelope, for proofs)
+#[derive(Debug, Clone, Serialize)]
+pub struct LayoutEntry {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    pub offset: u64,
+    pub bytes: u64,
+    /// Element count of sequences; the entries below describe the first element
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub len: Option<u64>,
+}
+
+/// Round counts and commitment sizes read off a stored proof
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofSpec {
+    pub bytes: u64,
+    pub sumchecks: Vec<SumcheckSummary>,
+    pub commitments: Vec<CommitmentSummary>,
+    pub layout: Vec<LayoutEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitSpec {
+    pub circuit: String,
+    pub r1cs_sha256: String,
+    pub verifying_key_sha256: String,
+    /// The domain tag first, then the circuit's public values
+    pub public_inputs: Vec<PublicInputField>,
+    /// Verifying key fields holding the Hyrax generators
+    pub generator_paths: Vec<String>,
+    pub verifying_key_layout: Vec<LayoutEntry>,
+    /// `None` when no proof of the circuit is stored
+    pub proof: Option<ProofSpec>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifierSpec {
+    pub spec_version: u32,
+    pub spartan2_rev: String,
+    pub engine: EngineSpec,
+    pub encoding: EncodingSpec,
+    /// Domain separator the domain tags are derived from
+    pub domain: String,
+    pub circuits: Vec<CircuitSpec>,
+}
+
+/// Flatten a size tree into entries with offsets
+/// Offsets below a sequence are those of its first element.
+pub fn layout_entries(root: &SizeNode) -> Vec<LayoutEntry> {
+    let mut entries = Vec::new();
+    push_entries(root, "", 0, &mut entries);
+    entries
+}
+
+fn push_entries(node: &SizeNode, parent: &str, offset: u64, entries: &mut Vec<LayoutEntry>) {
+    let path = if parent.is_empty() {
+        node.label.clone()
+    } else {
+        format!("{}.{}", parent, node.label)
+    };
+    entries.push(LayoutEntry {
+        path: path.clone(),
+        type_name: node.type_name.clone(),
+        offset,
+        bytes: node.bytes,
+        len: node.len,
+    });
+
+    // Sequences start with their length, enum variants with their tag and
+    // `Some` with a byte. Anything else unaccounted for is the elements of a
+    // fixed-size array repeating the first.
+    let unaccounted = node.bytes - node.children.iter().map(|c| c.bytes).sum::<u64>();
+    let framing = match node.len {
+        Some(_) => LEN_PREFIX,
+        None if unaccounted == VARIANT_TAG || unaccounted == 1 => unaccounted,
+        None => 0,
+    };
+    let mut child_offset = offset + framing;
+    for child in &node.children {
+        push_entries(child, &path, child_offset, entries);
+        child_offset += child.bytes;
+    }
+}
+
+fn engine_spec() -> EngineSpec {
+    let hex = |modulus: &str| modulus.trim_start_matches("0x").to_lowercase();
+    EngineSpec {
+        name: std::any::type_name::<E>().to_string(),
+        scalar_modulus: hex(<E as Engine>::Scalar::MODULUS),
+        base_modulus: hex(<E as Engine>::Base::MODULUS),
+        transcript_hash: TRANSCRIPT_HASH.to_string(),
+        transcript_label: TRANSCRIPT_LABEL.to_string(),
+    }
+}
+
+fn circuit_spec(circuit: CircuitKind) -> Result<CircuitSpec, Box<dyn std::error::Error>> {
+    let path = vk_path(circuit);
+    let vk_bytes = fs::read(&path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let vk: VerifyingKey = deserialize_bounded(&vk_bytes)?;
+    let vk_layout = layout_entries(&measure("vk", &vk)?);
+    let generator_paths = vk_layout
+        .iter()
+        .filter(|entry| {
+            entry
+                .path
+                .rsplit('.')
+                .next()
+                .is_some_and(|label| label.starts_with("ck"))
+        })
+        .map(|entry| entry.path.clone())
+        .collect();
+
+    let proof_path = artifact_path(match circuit {
+        CircuitKind::Prepare => PREPARE_PROOF,
+        CircuitKind::Show => SHOW_PROOF,
+    });
+    let proof = match fs::read(&proof_path) {
+        Ok(bytes) => {
+            let inspection = inspect_proof_bytes(&bytes)?;
+            Some(ProofSpec {
+                bytes: inspection.total_bytes,
+                sumchecks: inspection.sumchecks,
+                commitments: inspection.commitments,
+                layout: layout_entries(&inspection.root),
+            })
+        }
+        Err(_) => None,
+    };
+
+    Ok(CircuitSpec {
+        circuit: circuit.name().to_string(),
+        r1cs_sha256: r1cs_digest(circuit).to_string(),
+        verifying_key_sha256: sha256_hex(&vk_bytes),
+        public_inputs: public_input_schema(circuit),
+        generator_paths,
+        verifying_key_layout: vk_layout,
+        proof,
+    })
+}
+
+/// Describe verification of the stored Prepare and Show keys and proofs
+pub fn export_verifier_spec() -> Result<VerifierSpec, Box<dyn std::error::Error>> {
+    Ok(VerifierSpec {
+        spec_version: VERIFIER_SPEC_VERSION,
+        spartan2_rev: SPARTAN2_REV.to_string(),
+        engine: engine_spec(),
+        encoding: EncodingSpec {
+            serialization: "bincode 1, fixed-width little-endian integers, u64 lengths".to_string(),
+            envelope: "ZKAF || kind (u8) || version (u32 LE) || payload; keys are bare".to_string(),
+            artifact_format_version: format::CURRENT_VERSION,
+            key_format_version: KEY_FORMAT_VERSION,
+        },
+        domain: domain_separator(),
+        circuits: [CircuitKind::Prepare, CircuitKind::Show]
+            .into_iter()
+            .map(circuit_spec)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Commitment {
+        comm: Vec<[u8; 2]>,
+        tag: u32,
+    }
+
+    #[derive(Serialize)]
+    struct Proof {
+        version: u16,
+        blind: Option<Commitment>,
+    }
+
+    #[test]
+    fn test_layout_offsets() {
+        let value = Proof {
+            version: 7,
+            blind: Some(Commitment {
+                comm: vec![[1, 2], [3, 4]],
+                tag: 9,
+            }),
+        };
+        let entries = layout_entries(&measure("proof", &value).unwrap());
+        let offset = |path: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.path.ends_with(path))
+                .map(|entry| (entry.offset, entry.bytes))
+        };
+        assert_eq!(offset("proof"), Some((0, 2 + 1 + 8 + 4 + 4)));
+        assert_eq!(offset(".blind"), Some((2, 1 + 8 + 4 + 4)));
+        assert_eq!(offset(".comm"), Some((3, 8 + 4)));
+        assert_eq!(offset(".tag"), Some((3 + 8 + 4, 4)));
+    }
+}
@@ -0,0 +1,133 @@
+//! Witness generation helpers shared by the circuits and debugging tools.
+
+use std::{fs::File, path::Path};
+
+use bellpepper_core::SynthesisError;
+use rust_witness::BigInt;
+use serde_json::Value;
+
+use crate::{
+    circuits::show_circuit::show_witness,
+    utils::{parse_jwt_inputs, parse_show_inputs},
+    variant::JwtVariant,
+    wtns::read_wtns_file,
+    Scalar,
+};
+
+/// The circuits served by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitKind {
+    Prepare,
+    Show,
+}
+
+impl CircuitKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            CircuitKind::Prepare => "prepare",
+            CircuitKind::Show => "show",
+        }
+    }
+}
+
+impl std::str::FromStr for CircuitKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "prepare" => Ok(CircuitKind::Prepare),
+            "show" => Ok(CircuitKind::Show),
+            other => Err(format!(
+                "Invalid circuit_type '{}'. Must be 'prepare' or 'show'",
+                other
+            )),
+        }
+    }
+}
+
+/// Run rust-witness for `circuit` on already-parsed input JSON
+pub fn generate_witness_bigints(
+    circuit: CircuitKind,
+    json_value: &Value,
+) -> Result<Vec<BigInt>, SynthesisError> {
+    match circuit {
+        CircuitKind::Prepare => JwtVariant::for_input(json_value)
+            .unwrap_or_default()
+            .witness(parse_jwt_inputs(json_value)?),
+        CircuitKind::Show => Ok(show_witness(parse_show_inputs(json_value)?)),
+    }
+}
+
+/// First signal where the rust-witness and snarkjs witnesses disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WitnessDivergence {
+    pub index: usize,
+    pub rust_value: String,
+    pub snarkjs_value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossCheckReport {
+    pub rust_len: usize,
+    pub snarkjs_len: usize,
+    /// Signals compared before the first divergence (or all of them)
+    pub matched: usize,
+    pub first_divergence: Option<WitnessDivergence>,
+}
+
+impl CrossCheckReport {
+    pub fn is_match(&self) -> bool {
+        self.first_divergence.is_none() && self.rust_len == self.snarkjs_len
+    }
+}
+
+/// Compare the rust-witness output for `input_json` element-by-element against a
+/// snarkjs-generated `.wtns` for the same input, reporting the first divergent
+/// signal. Used to catch bugs in the WASM-to-C transpilation.
+pub fn cross_check_witness(
+    circuit: CircuitKind,
+    input_json: &Path,
+    snarkjs_wtns_path: &Path,
+) -> Result<CrossCheckReport, Box<dyn std::error::Error>> {
+    let json_value: Value = serde_json::from_reader(File::open(input_json)?)?;
+    let rust_witness = generate_witness_bigints(circuit, &json_value)
+        .map_err(|e| format!("rust-witness generation failed: {}", e))?;
+    let snarkjs = read_wtns_file(snarkjs_wtns_path)?;
+    snarkjs.check_prime::<Scalar>()?;
+
+    let mut report = CrossCheckReport {
+        rust_len: rust_witness.len(),
+        snarkjs_len: snarkjs.values.len(),
+        matched: 0,
+        first_divergence: None,
+    };
+
+    for (index, (rust_value, snarkjs_value)) in
+        rust_witness.iter().zip(snarkjs.values.iter()).enumerate()
+    {
+        if trimmed(&rust_value.to_bytes_le().1) != trimmed(snarkjs_value) {
+            report.first_divergence = Some(WitnessDivergence {
+                index,
+                rust_value: rust_value.to_string(),
+                snarkjs_value: le_bytes_to_decimal(snarkjs_value),
+            });
+            break;
+        }
+        report.matched += 1;
+    }
+
+    Ok(report)
+}
+
+/// Little-endian magnitude without trailing zero bytes
+fn trimmed(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+pub(crate) fn le_bytes_to_decimal(bytes: &[u8]) -> String {
+    // A trailing zero byte keeps the two's-complement value non-negative
+    let mut unsigned = bytes.to_vec();
+    unsigned.push(0);
+    BigInt::from_signed_bytes_le(&unsigned).to_string()
+}
@@ -0,0 +1,197 @@
+//! Reuse of generated witnesses across proves over unchanged inputs.
+//!
+//! Witness generation dominates repeated Prepare proves over the same
+//! credential. With the cache enabled, the witness for each circuit is kept as
+//! `SHA-256(input) || reprs` (32-byte field element encodings), sealed
+//! like other witnesses, and reused whenever the input hashes the same. Only
+//! the latest input per circuit is kept, so stale witnesses do not accumulate.
+//! With the cache disabled, stored entries are neither read nor written.
+//!
+//! `prefill_witness` hands a witness to a later prove regardless of the
+//! setting, for callers that generate the witness in one step and prove in
+//! another. It is kept apart from the cache, as `<circuit>.prefill.bin`, until
+//! the caller removes it with `remove_prefilled_witness`. Neither is keyed on
+//! the circuit itself: clear both after recompiling.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use bellpepper_core::SynthesisError;
+use ff::PrimeField;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+use zeroize::Zeroizing;
+
+use crate::{layout::in_documents, sealed, witness::CircuitKind, Scalar};
+
+pub const WITNESS_CACHE_DIR: &str = "keys/witness_cache";
+
+const HASH_LEN: usize = 32;
+const SCALAR_LEN: usize = 32;
+
+static CACHE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable witness caching (disabled by default)
+pub fn set_witness_cache_enabled(enabled: bool) {
+    CACHE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_witness_cache_enabled() -> bool {
+    CACHE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Delete all cached witnesses
+pub fn clear_witness_cache() -> std::io::Result<()> {
+    match fs::remove_dir_all(in_documents(WITNESS_CACHE_DIR)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// The witness for `input`, prefilled or from the cache when it matches, otherwise from `generate`
+/// The cache is only read, and new witnesses only stored, when caching is
+/// enabled; a prefilled witness is used either way. A cache that cannot be read
+/// or written is logged and bypassed.
+pub fn cached_witness<F>(
+    circuit: CircuitKind,
+    input: &[u8],
+    generate: F,
+) -> Result<Vec<Scalar>, SynthesisError>
+where
+    F: FnOnce() -> Result<Vec<Scalar>, SynthesisError>,
+{
+    let input_hash: [u8; HASH_LEN] = Sha256::digest(input).into();
+    if let Some(witness) = read_entry(&prefill_path(circuit), &input_hash) {
+        info!("Using prefilled {} witness", circuit.name());
+        return Ok(witness);
+    }
+    if !is_witness_cache_enabled() {
+        return generate();
+    }
+
+    let path = cache_path(circuit);
+    if let Some(witness) = read_entry(&path, &input_hash) {
+        info!("Reusing cached {} witness", circuit.name());
+        return Ok(witness);
+    }
+    let witness = generate()?;
+    if let Err(e) = write_entry(&path, &input_hash, &witness) {
+        warn!("Failed to cache {} witness: {}", circuit.name(), e);
+    }
+    Ok(witness)
+}
+
+/// Generate and store the witness for `input` for the next prove over it
+/// Works whether or not caching is enabled, so a witness can be produced in one
+/// step and consumed by a later prove. Nothing is generated if the prefilled
+/// witness is already for `input`.
+pub fn prefill_witness<F>(
+    circuit: CircuitKind,
+    input: &[u8],
+    generate: F,
+) -> Result<(), SynthesisError>
+where
+    F: FnOnce() -> Result<Vec<Scalar>, SynthesisError>,
+{
+    let path = prefill_path(circuit);
+    let input_hash: [u8; HASH_LEN] = Sha256::digest(input).into();
+    if read_entry(&path, &input_hash).is_some() {
+        return Ok(());
+    }
+    let witness = generate()?;
+    write_entry(&path, &input_hash, &witness).map_err(|e| {
+        warn!("Failed to store {} witness: {}", circuit.name(), e);
+        SynthesisError::AssignmentMissing
+    })
+}
+
+/// Delete the prefilled witness of one circuit
+pub fn remove_prefilled_witness(circuit: CircuitKind) -> std::io::Result<()> {
+    match fs::remove_file(prefill_path(circuit)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Delete the cached witness of one circuit
+pub fn remove_cached_witness(circuit: CircuitKind) -> std::io::Result<()> {
+    match fs::remove_file(cache_path(circuit)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Whether a witness of `circuit` is prefilled, whatever input it was for
+pub(crate) fn has_prefilled_witness(circuit: CircuitKind) -> bool {
+    prefill_path(circuit).exists()
+}
+
+fn cache_path(circuit: CircuitKind) -> PathBuf {
+    PathBuf::from(in_documents(WITNESS_CACHE_DIR)).join(format!("{}.bin", circuit.name()))
+}
+
+fn prefill_path(circuit: CircuitKind) -> PathBuf {
+    PathBuf::from(in_documents(WITNESS_CACHE_DIR)).join(format!("{}.prefill.bin", circuit.name()))
+}
+
+fn read_entry(path: &Path, input_hash: &[u8; HASH_LEN]) -> Option<Vec<Scalar>> {
+    let bytes = Zeroizing::new(sealed::open(fs::read(path).ok()?).ok()?);
+    if bytes.len() < HASH_LEN {
+        return None;
+    }
+    let (hash, values) = bytes.split_at(HASH_LEN);
+    if hash != input_hash {
+        return None;
+    }
+    decode_scalars(values)
+}
+
+fn write_entry(
+    path: &Path,
+    input_hash: &[u8; HASH_LEN],
+    witness: &[Scalar],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut bytes = Vec::with_capacity(HASH_LEN + witness.len() * SCALAR_LEN);
+    bytes.extend_from_slice(input_hash);
+    for scalar in witness {
+        bytes.extend_from_slice(scalar.to_repr().as_ref());
+    }
+    fs::create_dir_all(in_documents(WITNESS_CACHE_DIR))?;
+    fs::write(path, Zeroizing::new(sealed::seal_if_enabled(bytes)?))?;
+    Ok(())
+}
+
+fn decode_scalars(bytes: &[u8]) -> Option<Vec<Scalar>> {
+    if bytes.len() % SCALAR_LEN != 0 {
+        return None;
+    }
+    bytes
+        .par_chunks_exact(SCALAR_LEN)
+        .map(|chunk| {
+            let mut repr = <Scalar as PrimeField>::Repr::default();
+            repr.as_mut().copy_from_slice(chunk);
+            Scalar::from_repr(repr).into_option()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_encoding_roundtrip() {
+        let witness = vec![Scalar::from(1), Scalar::from(42), -Scalar::from(7)];
+        let bytes: Vec<u8> = witness
+            .iter()
+            .flat_map(|s| s.to_repr().as_ref().to_vec())
+            .collect();
+        assert_eq!(decode_scalars(&bytes), Some(witness));
+        assert_eq!(decode_scalars(&bytes[1..]), None);
+        assert_eq!(decode_scalars(&[0xff; SCALAR_LEN]), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Witness dumps for debugging circuits from Rust.
+//!
+//! `export_witness_debug` runs rust-witness on an input and writes the signal
+//! values as JSON or CSV. With the circuit's `.sym` file, which circom writes
+//! next to the build (`build/<name>/<name>.sym`), each value is labelled with
+//! its signal name and the dump can be narrowed to names containing a filter,
+//! such as `main.es256` or `matchIndex`.
+//!
+//! Each `.sym` line is `label,witness_index,component,name`; signals the
+//! optimizer removed have witness index -1 and have no value to show.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    variant::JwtVariant,
+    witness::{generate_witness_bigints, CircuitKind},
+};
+
+/// One witness value in a dump
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WitnessSignal {
+    pub index: usize,
+    /// First `.sym` name for the index, when names were requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Decimal value
+    pub value: String,
+}
+
+/// How a dump is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpFormat {
+    Json,
+    Csv,
+}
+
+/// The circom build name behind `circuit` for this input
+pub fn circom_name(circuit: CircuitKind, input: &Value) -> &'static str {
+    match circuit {
+        CircuitKind::Prepare => JwtVariant::for_input(input).unwrap_or_default().name(),
+        CircuitKind::Show => "show",
+    }
+}
+
+/// The `.sym` file of a circom build, relative to the circom project directory
+pub fn sym_path(name: &str) -> String {
+    format!("build/{0}/{0}.sym", name)
+}
+
+/// Witness index to signal name, keeping the first name of each index
+pub fn parse_sym(text: &str) -> Result<HashMap<usize, String>, String> {
+    let mut names = HashMap::new();
+    for (line_number, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let mut fields = line.splitn(4, ',');
+        let witness_index = fields.nth(1);
+        let name = fields.nth(1);
+        let (Some(witness_index), Some(name)) = (witness_index, name) else {
+            return Err(format!("Malformed .sym line {}", line_number + 1));
+        };
+        let witness_index: i64 = witness_index
+            .parse()
+            .map_err(|_| format!("Malformed witness index on .sym line {}", line_number + 1))?;
+        if let Ok(index) = usize::try_from(witness_index) {
+            names.entry(index).or_insert_with(|| name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// Witness values for `input`, labelled from `names` and narrowed to those whose
+/// name contains `filter`
+pub fn witness_signals(
+    circuit: CircuitKind,
+    input: &Value,
+    names: Option<&HashMap<usize, String>>,
+    filter: Option<&str>,
+) -> Result<Vec<WitnessSignal>, String> {
+    if filter.is_some() && names.is_none() {
+        return Err("Filtering signals needs their names from the .sym file".to_string());
+    }
+    let witness = generate_witness_bigints(circuit, input)
+        .map_err(|e| format!("rust-witness generation failed: {}", e))?;
+
+    Ok(witness
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| {
+            let name = names.and_then(|names| names.get(&index)).cloned();
+            match (filter, &name) {
+                (Some(filter), Some(name)) if !name.contains(filter) => None,
+                (Some(_), None) => None,
+                _ => Some(WitnessSignal {
+                    index,
+                    name,
+                    value: value.to_string(),
+                }),
+            }
+        })
+        .collect())
+}
+
+pub fn render_signals(signals: &[WitnessSignal], format: DumpFormat) -> Result<String, String> {
+    match format {
+        DumpFormat::Json => serde_json::to_string_pretty(signals).map_err(|e| e.to_string()),
+        DumpFormat::Csv => {
+            let mut csv = String::from("index,name,value\n");
+            for signal in signals {
+                csv.push_str(&format!(
+                    "{},{},{}\n",
+                    signal.index,
+                    signal.name.as_deref().unwrap_or_default(),
+                    signal.value
+                ));
+            }
+            Ok(csv)
+        }
+    }
+}
+
+/// Generate the witness for `input_json` and write the selected signals to `output`
+/// `circom_dir` is the circom project, where the `.sym` file is looked up when
+/// `signal_names` is set. Returns the number of signals written.
+pub fn export_witness_debug(
+    circuit: CircuitKind,
+    input_json: &Path,
+    circom_dir: &Path,
+    signal_names: bool,
+    filter: Option<&str>,
+    format: DumpFormat,
+    output: &Path,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let input: Value = serde_json::from_reader(fs::File::open(input_json)?)?;
+    let names = if signal_names {
+        let path = circom_dir.join(sym_path(circom_name(circuit, &input)));
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        Some(parse_sym(&text)?)
+    } else {
+        None
+    };
+
+    let signals = witness_signals(circuit, &input, names.as_ref(), filter)?;
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(output, render_signals(&signals, format)?)?;
+    Ok(signals.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sym_names_and_csv() {
+        let names =
+            parse_sym("1,1,0,main.out\n2,-1,0,main.tmp\n3,2,1,main.es256.ok\n4,2,1,alias\n")
+                .unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&2], "main.es256.ok");
+        assert!(parse_sym("1,x,0,main.out").is_err());
+
+        let signals = [WitnessSignal {
+            index: 2,
+            name: Some("main.es256.ok".to_string()),
+            value: "1".to_string(),
+        }];
+        assert_eq!(
+            render_signals(&signals, DumpFormat::Csv).unwrap(),
+            "index,name,value\n2,main.es256.ok,1\n"
+        );
+    }
+}
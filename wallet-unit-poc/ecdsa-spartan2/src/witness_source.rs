@@ -0,0 +1,225 @@
+//! Pluggable witness generation for the Prepare and Show circuits.
+//!
+//! rust-witness's peak memory adds to the prover's, which can push the app
+//! process over the platform's low-memory-killer threshold on small devices.
+//! Installing a `WitnessSource` moves witness generation elsewhere, e.g. into
+//! `HelperProcess`, which runs this crate's CLI (`<circuit> witness`) as a
+//! child process and reads the witness back over a pipe, so the generator's
+//! memory is released before proving starts. Without a source the witness is
+//! generated in-process as before. Cached witnesses (see `witness_cache`) are
+//! reused before any source is consulted.
+//!
+//! Witnesses cross the pipe in the stream format: the number of elements as a
+//! little-endian `u64`, then each element's 32-byte field encoding. The stream
+//! carries no prime, so a host that computes witnesses with circom's own tools
+//! should hand back the `.wtns` file instead; `decode_witness` accepts either
+//! and refuses a `.wtns` over another curve's field, whose values would
+//! otherwise decode into meaningless scalars.
+
+use std::{
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+
+use bellpepper_core::SynthesisError;
+use ff::PrimeField;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{
+    utils::convert_bigint_to_scalar,
+    witness::{generate_witness_bigints, CircuitKind},
+    wtns::{is_wtns, parse_wtns},
+    Scalar,
+};
+
+const SCALAR_LEN: usize = 32;
+
+/// Produces the witness of a circuit from its input JSON
+pub trait WitnessSource: Send + Sync {
+    fn generate(&self, circuit: CircuitKind, input_json: &[u8]) -> Result<Vec<Scalar>, String>;
+}
+
+static SOURCE: RwLock<Option<Arc<dyn WitnessSource>>> = RwLock::new(None);
+
+/// Install `source` for witness generation; `None` generates in-process
+pub fn set_witness_source(source: Option<Arc<dyn WitnessSource>>) {
+    let mut slot = SOURCE.write().unwrap_or_else(|e| e.into_inner());
+    *slot = source;
+}
+
+pub fn witness_source() -> Option<Arc<dyn WitnessSource>> {
+    SOURCE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// The witness for `input_json`, from the installed source or else `in_process`
+pub fn generate_witness<F>(
+    circuit: CircuitKind,
+    input_json: &[u8],
+    in_process: F,
+) -> Result<Vec<Scalar>, SynthesisError>
+where
+    F: FnOnce() -> Result<Vec<Scalar>, SynthesisError>,
+{
+    let Some(source) = witness_source() else {
+        return in_process();
+    };
+    let t0 = Instant::now();
+    let witness = source.generate(circuit, input_json).map_err(|e| {
+        warn!("Witness source failed for {}: {}", circuit.name(), e);
+        SynthesisError::AssignmentMissing
+    })?;
+    info!(
+        "External {} witness time: {} ms",
+        circuit.name(),
+        t0.elapsed().as_millis()
+    );
+    Ok(witness)
+}
+
+/// Generate the witness for `input_json` in this process with rust-witness
+pub fn generate_witness_in_process(
+    circuit: CircuitKind,
+    input_json: &[u8],
+) -> Result<Vec<Scalar>, SynthesisError> {
+    let json_value: Value =
+        serde_json::from_slice(input_json).map_err(|_| SynthesisError::AssignmentMissing)?;
+    convert_bigint_to_scalar(generate_witness_bigints(circuit, &json_value)?)
+}
+
+/// Generates witnesses in a child process running this crate's CLI
+/// The child is started as `program args... <circuit> witness`, gets the input
+/// JSON on stdin and must write the witness to stdout in the stream format.
+#[derive(Debug, Clone)]
+pub struct HelperProcess {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl WitnessSource for HelperProcess {
+    fn generate(&self, circuit: CircuitKind, input_json: &[u8]) -> Result<Vec<Scalar>, String> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .args([circuit.name(), "witness"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to start '{}': {}", self.program.display(), e))?;
+
+        // The helper reads its whole input before writing, so this cannot deadlock
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(input_json) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Failed to send input to witness helper: {}", e));
+            }
+        }
+        let witness = match child.stdout.take() {
+            Some(stdout) => read_witness_stream(BufReader::new(stdout)),
+            None => Err("Witness helper has no stdout".to_string()),
+        };
+        let status = child
+            .wait()
+            .map_err(|e| format!("Failed to wait for witness helper: {}", e))?;
+        if !status.success() {
+            return Err(format!("Witness helper exited with {}", status));
+        }
+        witness
+    }
+}
+
+/// Write `witness` in the stream format
+pub fn write_witness_stream(witness: &[Scalar], writer: impl Write) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    writer.write_all(&(witness.len() as u64).to_le_bytes())?;
+    for value in witness {
+        writer.write_all(value.to_repr().as_ref())?;
+    }
+    writer.flush()
+}
+
+/// Read a witness in the stream format, decoding elements as they arrive
+pub fn read_witness_stream(mut reader: impl Read) -> Result<Vec<Scalar>, String> {
+    let mut len = [0u8; 8];
+    reader
+        .read_exact(&mut len)
+        .map_err(|e| format!("Witness stream ended before its length: {}", e))?;
+    let len = u64::from_le_bytes(len) as usize;
+
+    // Grow as elements arrive rather than trusting the length for the allocation
+    let mut witness = Vec::new();
+    let mut repr = <Scalar as PrimeField>::Repr::default();
+    for index in 0..len {
+        reader
+            .read_exact(&mut repr.as_mut()[..SCALAR_LEN])
+            .map_err(|e| format!("Witness stream ended at element {}: {}", index, e))?;
+        let value = Option::from(Scalar::from_repr(repr))
+            .ok_or_else(|| format!("Witness element {} is not a field element", index))?;
+        witness.push(value);
+    }
+    Ok(witness)
+}
+
+/// Decode a witness in the stream format or as a `.wtns` file over this curve
+pub fn decode_witness(bytes: &[u8]) -> Result<Vec<Scalar>, String> {
+    if !is_wtns(bytes) {
+        return read_witness_stream(bytes);
+    }
+    let wtns = parse_wtns(bytes).map_err(|e| format!("Malformed .wtns witness: {}", e))?;
+    wtns.check_prime::<Scalar>()
+        .map_err(|e| format!("witness field does not match proving curve: {}", e))?;
+    wtns.to_scalars().map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_round_trip_and_truncation() {
+        let witness: Vec<Scalar> = (0..5u64).map(Scalar::from).collect();
+        let mut bytes = Vec::new();
+        write_witness_stream(&witness, &mut bytes).unwrap();
+        assert_eq!(bytes.len(), 8 + witness.len() * SCALAR_LEN);
+        assert_eq!(read_witness_stream(bytes.as_slice()).unwrap(), witness);
+
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(read_witness_stream(truncated)
+            .unwrap_err()
+            .contains("element 4"));
+    }
+
+    #[test]
+    fn test_decode_rejects_wtns_over_another_field() {
+        let witness: Vec<Scalar> = (0..3u64).map(Scalar::from).collect();
+        let mut bytes = Vec::new();
+        write_witness_stream(&witness, &mut bytes).unwrap();
+        assert_eq!(decode_witness(&bytes).unwrap(), witness);
+
+        // A BN254 witness: header with the BN254 scalar modulus, no values
+        let bn254 = "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+        let mut prime: Vec<u8> = (0..64)
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&bn254[i..i + 2], 16).unwrap())
+            .collect();
+        prime.reverse();
+        let mut header = 32u32.to_le_bytes().to_vec();
+        header.extend(prime);
+        header.extend(0u32.to_le_bytes());
+        let mut wtns = b"wtns".to_vec();
+        wtns.extend(2u32.to_le_bytes());
+        wtns.extend(2u32.to_le_bytes());
+        for (ty, payload) in [(1u32, header), (2u32, Vec::new())] {
+            wtns.extend(ty.to_le_bytes());
+            wtns.extend((payload.len() as u64).to_le_bytes());
+            wtns.extend(payload);
+        }
+        assert!(decode_witness(&wtns)
+            .unwrap_err()
+            .contains("does not match proving curve"));
+    }
+}
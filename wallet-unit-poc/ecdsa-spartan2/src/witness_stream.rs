@@ -0,0 +1,268 @@
+//! Synthesis straight from rust-witness output.
+//!
+//! rust-witness returns the witness as a `Vec<BigInt>`, which used to be
+//! converted into a full `Vec<Scalar>` before circom-scotia copied every value
+//! into the constraint system. When nothing else needs the witness as a whole,
+//! the Prepare prove instead hands the `BigInt`s to `synthesize_streamed`,
+//! which converts them a chunk at a time (in parallel within the chunk) as it
+//! assigns the wires, so the intermediate `Vec<Scalar>` is never allocated.
+//!
+//! The converted witness is still needed whole when it comes from a
+//! `WitnessSource`, goes into or comes out of the witness cache, or is checked
+//! constraint by constraint first; `can_stream` reports whether none applies.
+//! `synthesize_streamed` allocates the wires and enforces the constraints in
+//! the same order and under the same names as circom-scotia's `synthesize`, so
+//! the shape matches keys set up through either. It reads the constraints from
+//! an `ArenaR1cs` (see `r1cs_arena`) and keeps only the `Variable` of each
+//! wire, and `synthesize_checked` goes through it too. Into a witness
+//! generator, which is what proving synthesizes into, `synthesize_unchecked`
+//! only assigns the wires and never loads the constraints.
+
+use std::path::Path;
+
+use bellpepper_core::{
+    num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError, Variable,
+};
+use ff::Field;
+use rayon::prelude::*;
+use rust_witness::BigInt;
+
+use crate::{
+    constraint_check::{is_constraint_check_enabled, synthesize_checked},
+    r1cs_arena::{load_arena_r1cs, load_wire_counts, ArenaR1cs, Terms, WireCounts},
+    utils::{bigint_to_scalar, CONVERT_CHUNK},
+    witness::CircuitKind,
+    witness_cache::{has_prefilled_witness, is_witness_cache_enabled},
+    witness_source::witness_source,
+    Scalar,
+};
+
+/// Elements converted per step of a stream; a few MB of scalars at a time
+const STREAM_CHUNK: usize = 16 * CONVERT_CHUNK;
+
+/// Witness values for proving, either converted or straight from rust-witness
+pub enum SynthesisWitness {
+    Scalars(Vec<Scalar>),
+    Streamed(Vec<BigInt>),
+}
+
+/// Whether the witness of `circuit` can go from rust-witness into synthesis unconverted
+pub fn can_stream(circuit: CircuitKind) -> bool {
+    witness_source().is_none()
+        && !is_witness_cache_enabled()
+        && !has_prefilled_witness(circuit)
+        && !is_constraint_check_enabled()
+}
+
+/// rust-witness output converted to scalars a chunk at a time, as it is consumed
+pub struct ScalarStream {
+    bigints: std::vec::IntoIter<BigInt>,
+    chunk: std::vec::IntoIter<Scalar>,
+}
+
+impl ScalarStream {
+    pub fn new(bigints: Vec<BigInt>) -> Self {
+        Self {
+            bigints: bigints.into_iter(),
+            chunk: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Iterator for ScalarStream {
+    type Item = Result<Scalar, SynthesisError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(value) = self.chunk.next() {
+            return Some(Ok(value));
+        }
+        let batch: Vec<BigInt> = self.bigints.by_ref().take(STREAM_CHUNK).collect();
+        if batch.is_empty() {
+            return None;
+        }
+        match batch
+            .into_par_iter()
+            .with_min_len(CONVERT_CHUNK)
+            .map(bigint_to_scalar)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(chunk) => {
+                self.chunk = chunk.into_iter();
+                self.chunk.next().map(Ok)
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Load the R1CS at `r1cs_path` and synthesize it with `witness`
+/// Returns circom-scotia's public signals, as `synthesize_checked` does.
+pub fn synthesize_witness<CS: ConstraintSystem<Scalar>>(
+    cs: &mut CS,
+    r1cs_path: &Path,
+    witness: Option<SynthesisWitness>,
+) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError> {
+    match witness {
+        Some(SynthesisWitness::Streamed(bigints)) => {
+            synthesize_unchecked(cs, r1cs_path, Some(ScalarStream::new(bigints)))
+        }
+        Some(SynthesisWitness::Scalars(witness)) => {
+            synthesize_checked(cs, r1cs_path, Some(witness))
+        }
+        None => synthesize_checked(cs, r1cs_path, None),
+    }
+}
+
+/// Synthesize the R1CS at `r1cs_path`, loading its constraints only if `cs` keeps them
+/// A witness generator (spartan2's `SatisfyingAssignment`) drops constraints,
+/// so proving needs just the wire counts, which setup has usually cached.
+pub fn synthesize_unchecked<CS, I>(
+    cs: &mut CS,
+    r1cs_path: &Path,
+    witness: Option<I>,
+) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError>
+where
+    CS: ConstraintSystem<Scalar>,
+    I: Iterator<Item = Result<Scalar, SynthesisError>>,
+{
+    match witness {
+        Some(witness) if cs.is_witness_generator() => {
+            let (_, output) = alloc_wires(cs, load_wire_counts(r1cs_path)?, Some(witness))?;
+            Ok(output)
+        }
+        witness => synthesize_streamed(cs, &load_arena_r1cs(r1cs_path)?, witness),
+    }
+}
+
+/// circom-scotia's `synthesize`, taking the witness (constant wire first) as it is produced
+pub fn synthesize_streamed<CS, I>(
+    cs: &mut CS,
+    r1cs: &ArenaR1cs,
+    witness: Option<I>,
+) -> Result<Vec<AllocatedNum<Scalar>>, SynthesisError>
+where
+    CS: ConstraintSystem<Scalar>,
+    I: Iterator<Item = Result<Scalar, SynthesisError>>,
+{
+    let (vars, output) = alloc_wires(cs, r1cs.wire_counts(), witness)?;
+    let make_lc = |terms: Terms<'_>| {
+        terms
+            .iter()
+            .fold(LinearCombination::zero(), |lc, &(wire, coeff)| {
+                let variable = if wire == 0 { CS::one() } else { vars[wire - 1] };
+                lc + (coeff, variable)
+            })
+    };
+    for (i, [a, b, c]) in r1cs.constraints().enumerate() {
+        cs.enforce(
+            || format!("constraint {}", i),
+            |_| make_lc(a),
+            |_| make_lc(b),
+            |_| make_lc(c),
+        );
+    }
+    Ok(output)
+}
+
+/// Allocate every wire but the constant, returning their variables and the public signals
+fn alloc_wires<CS, I>(
+    cs: &mut CS,
+    counts: WireCounts,
+    mut witness: Option<I>,
+) -> Result<(Vec<Variable>, Vec<AllocatedNum<Scalar>>), SynthesisError>
+where
+    CS: ConstraintSystem<Scalar>,
+    I: Iterator<Item = Result<Scalar, SynthesisError>>,
+{
+    let mut next_value = || match witness.as_mut() {
+        None => Ok(Scalar::ONE),
+        Some(values) => values
+            .next()
+            .unwrap_or(Err(SynthesisError::AssignmentMissing)),
+    };
+    // Wire 0 is the constant one
+    next_value()?;
+
+    let num_public = counts.num_inputs.saturating_sub(1);
+    let mut output = Vec::with_capacity(num_public);
+    let mut vars: Vec<Variable> = Vec::with_capacity(num_public + counts.num_aux);
+    for i in 1..counts.num_inputs {
+        let value = next_value()?;
+        let num = AllocatedNum::alloc(cs.namespace(|| format!("public_{}", i)), || Ok(value))?;
+        vars.push(num.get_variable());
+        output.push(num);
+    }
+    for i in 0..counts.num_aux {
+        let value = next_value()?;
+        let num = AllocatedNum::alloc(cs.namespace(|| format!("aux_{}", i)), || Ok(value))?;
+        vars.push(num.get_variable());
+    }
+    Ok((vars, output))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bellpepper::util_cs::witness_cs::WitnessCS;
+    use bellpepper_core::test_cs::TestConstraintSystem;
+    use circom_scotia::r1cs::R1CS;
+
+    #[test]
+    fn test_streamed_synthesis_matches_circom_scotia() {
+        let one = Scalar::ONE;
+        // Public output w1 = w2 * w3, then w3 * 1 = w4
+        let r1cs = || R1CS {
+            num_inputs: 2,
+            num_aux: 3,
+            num_variables: 5,
+            constraints: vec![
+                (vec![(2, one)], vec![(3, one)], vec![(1, one)]),
+                (vec![(3, one)], vec![(0, one)], vec![(4, one)]),
+            ],
+        };
+        let values = [1u64, 6, 2, 3, 3];
+
+        let mut expected = TestConstraintSystem::<Scalar>::new();
+        let scotia = circom_scotia::synthesize(
+            &mut expected,
+            r1cs(),
+            Some(values.map(Scalar::from).to_vec()),
+        )
+        .unwrap();
+
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        let bigints = values.iter().map(|&v| BigInt::from(v)).collect();
+        let arena = ArenaR1cs::from(r1cs());
+        let streamed =
+            synthesize_streamed(&mut cs, &arena, Some(ScalarStream::new(bigints))).unwrap();
+        assert!(cs.is_satisfied());
+        assert_eq!(cs.num_constraints(), expected.num_constraints());
+        assert_eq!(cs.num_inputs(), expected.num_inputs());
+        assert_eq!(
+            streamed.iter().map(|v| v.get_value()).collect::<Vec<_>>(),
+            scotia.iter().map(|v| v.get_value()).collect::<Vec<_>>()
+        );
+
+        // A witness that runs out is missing an assignment rather than a panic
+        let short = values[..3].iter().map(|&v| BigInt::from(v)).collect();
+        let mut cs = TestConstraintSystem::<Scalar>::new();
+        assert!(matches!(
+            synthesize_streamed(&mut cs, &arena, Some(ScalarStream::new(short))),
+            Err(SynthesisError::AssignmentMissing)
+        ));
+
+        // A witness generator gets the same assignment from the wire counts alone
+        let mut generator = WitnessCS::<Scalar>::new();
+        let bigints = values.iter().map(|&v| BigInt::from(v)).collect();
+        let (_, assigned) = alloc_wires(
+            &mut generator,
+            arena.wire_counts(),
+            Some(ScalarStream::new(bigints)),
+        )
+        .unwrap();
+        let mut wires = generator.scalar_inputs();
+        wires.extend(generator.scalar_aux());
+        assert_eq!(wires, values.map(Scalar::from).to_vec());
+        assert_eq!(assigned.len(), streamed.len());
+    }
+}
@@ -0,0 +1,240 @@
+//! Reader for circom/snarkjs binary witness files (`.wtns`).
+//!
+//! Section 1 holds the field size in bytes, the prime (little-endian), and the
+//! witness count; section 2 holds the witness values as fixed-width
+//! little-endian integers. Sections are found by type in any order, unknown
+//! ones are skipped, and zero padding that aligns a section to 8 bytes, which
+//! some tools emit, is tolerated. `load_witness_from_file` also checks that the
+//! prime is the engine's scalar modulus, so a witness computed for another
+//! curve is reported as such instead of being reduced into wrong values.
+
+use std::{fs, path::Path};
+
+use ff::PrimeField;
+use rayon::prelude::*;
+
+use crate::{
+    binfile::{malformed, unsupported, BinFile, FormatError, Reader},
+    Scalar,
+};
+
+const MAGIC: &[u8; 4] = b"wtns";
+const HEADER_SECTION: u32 = 1;
+const VALUES_SECTION: u32 = 2;
+/// Values copied per rayon task
+const PARSE_CHUNK: usize = 4096;
+
+/// A parsed witness file
+#[derive(Debug, Clone)]
+pub struct Wtns {
+    pub version: u32,
+    /// Field element width in bytes
+    pub field_size: usize,
+    /// Field modulus, little-endian, `field_size` bytes
+    pub prime: Vec<u8>,
+    /// Witness values, little-endian, `field_size` bytes each
+    pub values: Vec<Vec<u8>>,
+}
+
+impl Wtns {
+    /// The prime as big-endian hex without leading zeros
+    fn prime_hex(&self) -> String {
+        let hex: String = self
+            .prime
+            .iter()
+            .rev()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        hex.trim_start_matches('0').to_string()
+    }
+
+    /// Error unless the witness is over `F`'s scalar field
+    pub fn check_prime<F: PrimeField>(&self) -> Result<(), FormatError> {
+        let expected = F::MODULUS
+            .trim_start_matches("0x")
+            .trim_start_matches('0')
+            .to_lowercase();
+        let found = self.prime_hex();
+        if found != expected {
+            return Err(unsupported(format!(
+                "witness is over the prime 0x{}, but the engine's scalar field is 0x{}; \
+                 the circuit was compiled for another curve",
+                found, expected
+            )));
+        }
+        Ok(())
+    }
+
+    /// The values as scalars, after checking the prime
+    pub fn to_scalars(&self) -> Result<Vec<Scalar>, FormatError> {
+        self.check_prime::<Scalar>()?;
+        self.values
+            .par_iter()
+            .enumerate()
+            .map(|(index, bytes)| {
+                let mut repr = <Scalar as PrimeField>::Repr::default();
+                let len = repr.as_ref().len();
+                if bytes[len.min(bytes.len())..].iter().any(|b| *b != 0) {
+                    return Err(malformed(format!(
+                        "witness value {} exceeds the field",
+                        index
+                    )));
+                }
+                repr.as_mut()[..len.min(bytes.len())]
+                    .copy_from_slice(&bytes[..len.min(bytes.len())]);
+                Option::from(Scalar::from_repr(repr))
+                    .ok_or_else(|| malformed(format!("witness value {} exceeds the field", index)))
+            })
+            .collect()
+    }
+}
+
+/// Whether `bytes` start like a `.wtns` file
+pub fn is_wtns(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+pub fn read_wtns_file(path: &Path) -> Result<Wtns, FormatError> {
+    parse_wtns(&fs::read(path)?)
+}
+
+/// Read a `.wtns` file as scalars of the engine's field
+pub fn load_witness_from_file(path: &Path) -> Result<Vec<Scalar>, FormatError> {
+    read_wtns_file(path)?.to_scalars()
+}
+
+pub fn parse_wtns(bytes: &[u8]) -> Result<Wtns, FormatError> {
+    let file = BinFile::parse_padded(bytes, MAGIC)?;
+
+    let mut header = Reader::new(file.require_section(HEADER_SECTION, "header")?);
+    let field_size = header.u32()? as usize;
+    if field_size == 0 || field_size % 8 != 0 {
+        return Err(malformed(format!("invalid field size {}", field_size)));
+    }
+    let prime = header.take(field_size)?.to_vec();
+    let num_witness = header.u32()? as usize;
+
+    let values_section = file.require_section(VALUES_SECTION, "values")?;
+    let expected = num_witness
+        .checked_mul(field_size)
+        .ok_or_else(|| malformed("witness count overflows"))?;
+    if values_section.len() < expected {
+        return Err(malformed(format!(
+            "values section holds {} bytes, expected {}",
+            values_section.len(),
+            expected
+        )));
+    }
+
+    let values = values_section[..expected]
+        .par_chunks_exact(field_size)
+        .with_min_len(PARSE_CHUNK)
+        .map(|chunk| chunk.to_vec())
+        .collect();
+
+    Ok(Wtns {
+        version: file.version,
+        field_size,
+        prime,
+        values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(field_size: u32, values: &[u64]) -> Vec<u8> {
+        let prime = vec![0xff; field_size as usize];
+        sections(&[
+            (HEADER_SECTION, header(field_size, &prime, values.len())),
+            (VALUES_SECTION, body(field_size, values)),
+        ])
+    }
+
+    fn header(field_size: u32, prime: &[u8], count: usize) -> Vec<u8> {
+        let mut header = field_size.to_le_bytes().to_vec();
+        header.extend(prime);
+        header.extend((count as u32).to_le_bytes());
+        header
+    }
+
+    fn body(field_size: u32, values: &[u64]) -> Vec<u8> {
+        let mut body = Vec::new();
+        for v in values {
+            let mut bytes = v.to_le_bytes().to_vec();
+            bytes.resize(field_size as usize, 0);
+            body.extend(bytes);
+        }
+        body
+    }
+
+    fn sections(sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend(2u32.to_le_bytes());
+        out.extend((sections.len() as u32).to_le_bytes());
+        for (ty, payload) in sections {
+            out.extend(ty.to_le_bytes());
+            out.extend((payload.len() as u64).to_le_bytes());
+            out.extend(payload);
+        }
+        out
+    }
+
+    /// The engine's modulus, little-endian
+    fn modulus() -> Vec<u8> {
+        let hex = Scalar::MODULUS.trim_start_matches("0x");
+        let mut bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.reverse();
+        bytes
+    }
+
+    #[test]
+    fn test_parse_wtns() {
+        let wtns = parse_wtns(&encode(32, &[1, 42, 7])).unwrap();
+        assert_eq!(wtns.field_size, 32);
+        assert_eq!(wtns.values.len(), 3);
+        assert_eq!(wtns.values[1][0], 42);
+
+        let bytes = encode(32, &[1, 42, 7]);
+        assert!(parse_wtns(&bytes[..bytes.len() - 1]).is_err());
+        assert!(parse_wtns(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_sections_in_any_order_with_padding() {
+        let prime = modulus();
+        let field_size = prime.len() as u32;
+        // Values first, an unknown section, and a header padded to 8 bytes
+        let mut padded_header = header(field_size, &prime, 2);
+        let mut bytes = sections(&[
+            (VALUES_SECTION, body(field_size, &[1, 42])),
+            (9, vec![1, 2, 3]),
+        ]);
+        bytes.extend(vec![0; bytes.len().next_multiple_of(8) - bytes.len()]);
+        bytes.extend(HEADER_SECTION.to_le_bytes());
+        bytes.extend((padded_header.len() as u64).to_le_bytes());
+        bytes.append(&mut padded_header);
+        bytes[8..12].copy_from_slice(&3u32.to_le_bytes());
+
+        let wtns = parse_wtns(&bytes).unwrap();
+        assert_eq!(wtns.values.len(), 2);
+        assert_eq!(
+            wtns.to_scalars().unwrap(),
+            [Scalar::from(1u64), Scalar::from(42u64)]
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_curve() {
+        let err = parse_wtns(&encode(32, &[1]))
+            .unwrap()
+            .to_scalars()
+            .unwrap_err();
+        assert!(matches!(err, FormatError::Unsupported(_)));
+        assert!(err.to_string().contains("another curve"));
+    }
+}
@@ -0,0 +1,173 @@
+//! dudect-style timing checks for the code that handles secret shared values.
+//!
+//! Each check times an operation on inputs from two classes, one fixed value and
+//! fresh random values, interleaved at random, and runs Welch's t-test on the two
+//! timing distributions, also after cropping the slowest measurements. A |t|
+//! above `T_THRESHOLD` means the timing depends on the secret input.
+//!
+//!   cargo test --release --features ct-test --test constant_time -- --nocapture
+//!
+//! `CT_SAMPLES` overrides the number of measurements per check. Run on an idle
+//! machine; background load shows up as noise, not as a leak.
+
+#![cfg(feature = "ct-test")]
+
+use std::{hint::black_box, time::Instant};
+
+use ecdsa_spartan2::{ct, setup_circuit_keys_no_save, Scalar, SyntheticCircuit, E};
+use ff::{
+    derive::rand_core::{OsRng, RngCore},
+    Field,
+};
+use spartan2::{
+    bellpepper::{solver::SatisfyingAssignment, zk_r1cs::SpartanWitness},
+    traits::{snark::R1CSSNARKTrait, transcript::TranscriptEngineTrait, Engine},
+    zk_spartan::R1CSSNARK,
+};
+
+/// dudect's bound for "definitely not constant time"
+const T_THRESHOLD: f64 = 10.0;
+
+/// Percentiles below which measurements are kept for the cropped tests
+const CROP_PERCENTILES: [f64; 4] = [0.5, 0.75, 0.9, 0.99];
+
+fn samples(default: usize) -> usize {
+    std::env::var("CT_SAMPLES")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Online mean and variance of each class, for Welch's t-test
+#[derive(Default)]
+struct Welch {
+    n: [f64; 2],
+    mean: [f64; 2],
+    m2: [f64; 2],
+}
+
+impl Welch {
+    fn push(&mut self, class: usize, x: f64) {
+        self.n[class] += 1.0;
+        let delta = x - self.mean[class];
+        self.mean[class] += delta / self.n[class];
+        self.m2[class] += delta * (x - self.mean[class]);
+    }
+
+    fn t(&self) -> f64 {
+        let var = |class: usize| self.m2[class] / (self.n[class] - 1.0);
+        let se = (var(0) / self.n[0] + var(1) / self.n[1]).sqrt();
+        (self.mean[0] - self.mean[1]) / se
+    }
+}
+
+/// Largest |t| over the raw and cropped measurements of `(class, nanos)`
+fn max_t(measurements: &[(usize, u64)]) -> f64 {
+    let mut sorted: Vec<u64> = measurements.iter().map(|(_, nanos)| *nanos).collect();
+    sorted.sort_unstable();
+    let cutoffs = CROP_PERCENTILES
+        .iter()
+        .map(|p| sorted[((sorted.len() - 1) as f64 * p) as usize])
+        .chain([u64::MAX]);
+
+    cutoffs
+        .map(|cutoff| {
+            let mut welch = Welch::default();
+            for &(class, nanos) in measurements.iter().filter(|(_, nanos)| *nanos <= cutoff) {
+                welch.push(class, nanos as f64);
+            }
+            welch.t().abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Time `op` on `samples` inputs, class 0 from `fixed` and class 1 from `random`
+/// Inputs are built before the clock starts, so only `op` is measured.
+fn measure<I, O>(
+    samples: usize,
+    mut fixed: impl FnMut() -> I,
+    mut random: impl FnMut() -> I,
+    mut op: impl FnMut(I) -> O,
+) -> f64 {
+    let measurements: Vec<(usize, u64)> = (0..samples)
+        .map(|_| {
+            let class = (OsRng.next_u32() & 1) as usize;
+            let input = if class == 0 { fixed() } else { random() };
+            let start = Instant::now();
+            black_box(op(black_box(input)));
+            (class, start.elapsed().as_nanos() as u64)
+        })
+        .collect();
+    max_t(&measurements)
+}
+
+#[test]
+fn base64_decode_timing_is_independent_of_input() {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+
+    // A key-binding coordinate: 32 bytes, 43 characters
+    let encode = |bytes: [u8; 32]| URL_SAFE_NO_PAD.encode(bytes).into_bytes();
+    let t = measure(
+        samples(200_000),
+        || encode([0; 32]),
+        || {
+            let mut bytes = [0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            encode(bytes)
+        },
+        |encoded| ct::decode_base64(&encoded),
+    );
+    println!("base64 decode: max |t| = {:.2}", t);
+    assert!(
+        t < T_THRESHOLD,
+        "base64 decode timing leaks: |t| = {:.2}",
+        t
+    );
+}
+
+#[test]
+fn blind_application_timing_is_independent_of_blinds() {
+    let circuit = SyntheticCircuit::new(1 << 10);
+    let (pk, _vk) = setup_circuit_keys_no_save::<E, _>(circuit.clone());
+    let new_transcript = || {
+        let mut transcript = <E as Engine>::TE::new(b"R1CSSNARK");
+        transcript.absorb(b"vk", &pk.vk_digest);
+        transcript
+    };
+    let mut prep = R1CSSNARK::<E>::prep_prove(&pk, circuit.clone(), false).expect("prep_prove");
+    let (instance, witness) = SatisfyingAssignment::r1cs_instance_and_witness(
+        &mut prep.ps,
+        &pk.S,
+        &pk.ck,
+        &circuit,
+        false,
+        &mut new_transcript(),
+    )
+    .expect("commit");
+
+    let num_blinds = instance.num_shared_rows();
+    let fixed_blinds = vec![Scalar::random(OsRng); num_blinds];
+    let inputs =
+        |blinds: Vec<Scalar>| (blinds, instance.clone(), witness.clone(), new_transcript());
+    let t = measure(
+        samples(2_000),
+        || inputs(fixed_blinds.clone()),
+        || inputs((0..num_blinds).map(|_| Scalar::random(OsRng)).collect()),
+        |(blinds, instance, witness, mut transcript)| {
+            SatisfyingAssignment::reblind_r1cs_instance_and_witness(
+                &blinds,
+                instance,
+                witness,
+                &pk.ck,
+                &mut transcript,
+            )
+            .expect("reblind")
+        },
+    );
+    println!("blind application: max |t| = {:.2}", t);
+    assert!(
+        t < T_THRESHOLD,
+        "blind application timing leaks: |t| = {:.2}",
+        t
+    );
+}
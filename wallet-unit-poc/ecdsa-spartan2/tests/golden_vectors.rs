@@ -0,0 +1,103 @@
+//! Checks the committed golden vectors against the current serialization and verifier.
+//!
+//! Regenerate with `cargo run --release --features test-determinism -- gen-vectors`
+//! after an intentional wire-format change.
+
+use std::{fs, path::Path};
+
+use ecdsa_spartan2::{
+    format::{encode, ArtifactKind},
+    load_instance, load_proof, load_verifying_key,
+    vectors::{sha256_hex, VectorManifest, MANIFEST_FILE, MANIFEST_FORMAT},
+    E,
+};
+
+const VECTORS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/vectors");
+
+fn load_manifest() -> VectorManifest {
+    let path = Path::new(VECTORS_DIR).join(MANIFEST_FILE);
+    assert!(
+        path.exists(),
+        "no golden vectors at {}; generate them with `cargo run --release --features \
+         test-determinism -- gen-vectors` and commit them",
+        VECTORS_DIR
+    );
+    let manifest: VectorManifest =
+        serde_json::from_reader(fs::File::open(path).expect("open manifest"))
+            .expect("parse manifest");
+    assert_eq!(manifest.format, MANIFEST_FORMAT, "manifest format changed");
+    assert!(!manifest.circuits.is_empty(), "manifest lists no circuits");
+    manifest
+}
+
+fn vector_path(file: &str) -> String {
+    Path::new(VECTORS_DIR).join(file).to_string_lossy().into_owned()
+}
+
+#[test]
+fn golden_artifacts_match_digests_and_reserialize() {
+    let manifest = load_manifest();
+
+    for vector in &manifest.circuits {
+        let vk_bytes = fs::read(vector_path(&vector.verifying_key)).expect("read vk");
+        let proof_bytes = fs::read(vector_path(&vector.proof)).expect("read proof");
+        let instance_bytes = fs::read(vector_path(&vector.instance)).expect("read instance");
+        assert_eq!(sha256_hex(&vk_bytes), vector.verifying_key_sha256);
+        assert_eq!(sha256_hex(&proof_bytes), vector.proof_sha256);
+        assert_eq!(sha256_hex(&instance_bytes), vector.instance_sha256);
+
+        let vk = load_verifying_key::<E>(&vector_path(&vector.verifying_key)).expect("decode vk");
+        let proof = load_proof::<E>(&vector_path(&vector.proof)).expect("decode proof");
+        let instance = load_instance::<E>(&vector_path(&vector.instance)).expect("decode instance");
+
+        assert_eq!(
+            bincode::serialize(&vk).unwrap(),
+            vk_bytes,
+            "{}: verifying key wire format changed",
+            vector.name
+        );
+        assert_eq!(
+            encode(ArtifactKind::Proof, &proof).unwrap(),
+            proof_bytes,
+            "{}: proof wire format changed",
+            vector.name
+        );
+        assert_eq!(
+            encode(ArtifactKind::Instance, &instance).unwrap(),
+            instance_bytes,
+            "{}: instance wire format changed",
+            vector.name
+        );
+        assert_eq!(
+            format!("{:?}", instance.comm_W_shared),
+            vector.comm_w_shared
+        );
+    }
+}
+
+#[test]
+fn golden_proofs_verify() {
+    let manifest = load_manifest();
+
+    for vector in &manifest.circuits {
+        let vk = load_verifying_key::<E>(&vector_path(&vector.verifying_key)).expect("decode vk");
+        let proof = load_proof::<E>(&vector_path(&vector.proof)).expect("decode proof");
+        assert!(
+            proof.verify(&vk).is_ok(),
+            "{}: golden proof no longer verifies",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn golden_proofs_share_commitment() {
+    let manifest = load_manifest();
+
+    let commitments: Vec<&str> = manifest
+        .circuits
+        .iter()
+        .map(|v| v.comm_w_shared.as_str())
+        .collect();
+    assert!(commitments.windows(2).all(|w| w[0] == w[1]));
+}